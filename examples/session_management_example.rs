@@ -13,7 +13,8 @@ async fn main() -> std::result::Result<(), Box<dyn std::error::Error>> {
 
         use supabase_lib_rs::prelude::*;
         use supabase_lib_rs::session::{
-            storage::create_default_storage, SessionEvent, SessionManager, SessionManagerConfig,
+            storage::create_default_storage, ConflictResolution, SessionEvent, SessionManager,
+            SessionManagerConfig,
         };
         use uuid::Uuid;
 
@@ -37,6 +38,7 @@ async fn main() -> std::result::Result<(), Box<dyn std::error::Error>> {
             enable_monitoring: true,
             max_memory_sessions: 100,
             sync_interval_seconds: 30,
+            conflict_resolution: ConflictResolution::NewestWins,
         };
 
         let session_manager = SessionManager::new(config);
@@ -96,6 +98,9 @@ async fn main() -> std::result::Result<(), Box<dyn std::error::Error>> {
             expires_at: Utc::now() + chrono::Duration::seconds(3600),
             token_type: "bearer".to_string(),
             user: demo_user,
+            provider_token: None,
+            provider_refresh_token: None,
+            provider: None,
         };
 
         // Store the session
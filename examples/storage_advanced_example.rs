@@ -183,6 +183,10 @@ async fn main() -> std::result::Result<(), Box<dyn std::error::Error>> {
             advanced_filters: advanced_filters.clone(),
             enable_presence: true,
             enable_broadcast: true,
+            broadcast_options: None,
+            private: false,
+            resync: None,
+            postgres_changes: Vec::new(),
             presence_callback: Some(Arc::new(|event| {
                 println!("     👥 Presence event: {:?}", event);
             })),
@@ -88,6 +88,7 @@ async fn main() -> Result<()> {
         content_type: Some("text/plain".to_string()),
         cache_control: Some("max-age=3600".to_string()),
         upsert: true,
+        ..Default::default()
     };
 
     match storage
@@ -81,6 +81,14 @@ SupabaseError supabase_functions_invoke(
 );
 
 // Error handling
+//
+// Functions returning SupabaseError return SUPABASE_SUCCESS (0) on success;
+// functions returning a pointer return NULL on failure. Every failure records
+// a per-thread last error, and every successful call clears it.
+SupabaseError supabase_last_error_code(void);
+// NULL if there is no error; valid until the next library call on this thread
+const char* supabase_last_error_message(void);
+void supabase_clear_last_error(void);
 SupabaseError supabase_get_last_error(char* buffer, size_t buffer_len);
 
 #ifdef __cplusplus
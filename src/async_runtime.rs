@@ -1,13 +1,17 @@
 //! Async runtime abstraction for cross-platform compatibility
 //!
 //! This module provides async synchronization primitives that work across
-//! both native and WASM environments. The abstractions hide platform-specific
-//! details while maintaining optimal performance for each target.
+//! both native and WASM environments, plus the sleep and background task
+//! helpers shared by the service modules. The abstractions hide
+//! platform-specific details while maintaining optimal performance for each
+//! target.
 //!
 //! ## Platform Support
 //!
-//! - **Native**: Uses `tokio::sync::RwLock` with full `Send + Sync` support
-//! - **WASM**: Uses `futures_util::lock::Mutex` without `Send + Sync` bounds
+//! - **Native**: Uses `tokio::sync::RwLock` with full `Send + Sync` support;
+//!   sleeping and background tasks need the `native` feature
+//! - **WASM**: Uses `futures_util::lock::Mutex` without `Send + Sync` bounds;
+//!   sleeping and background tasks need the `wasm` feature
 //!
 //! ## Usage
 //!
@@ -33,7 +37,95 @@
 //! # }
 //! ```
 
+#[cfg(feature = "realtime")]
 use std::future::Future;
+#[cfg(any(
+    feature = "auth-core",
+    feature = "database",
+    feature = "storage-core",
+    feature = "functions"
+))]
+use std::time::Duration;
+
+/// Whether [`async_sleep`] can wait and [`spawn_background`] can run tasks
+#[cfg(any(
+    feature = "auth-core",
+    feature = "database",
+    feature = "storage-core",
+    feature = "functions"
+))]
+pub(crate) const HAS_TIMER: bool = cfg!(any(
+    all(not(target_arch = "wasm32"), feature = "native"),
+    all(target_arch = "wasm32", feature = "wasm")
+));
+
+/// Wait for `duration` on the available runtime
+#[cfg(any(
+    feature = "auth-core",
+    feature = "database",
+    feature = "storage-core",
+    feature = "functions"
+))]
+#[cfg(all(not(target_arch = "wasm32"), feature = "native"))]
+pub(crate) async fn async_sleep(duration: Duration) {
+    tokio::time::sleep(duration).await;
+}
+
+#[cfg(any(
+    feature = "auth-core",
+    feature = "database",
+    feature = "storage-core",
+    feature = "functions"
+))]
+#[cfg(all(target_arch = "wasm32", feature = "wasm"))]
+pub(crate) async fn async_sleep(duration: Duration) {
+    gloo_timers::future::sleep(duration).await;
+}
+
+/// Returns at once, as there is no timer without a runtime (see [`HAS_TIMER`])
+#[cfg(any(
+    feature = "auth-core",
+    feature = "database",
+    feature = "storage-core",
+    feature = "functions"
+))]
+#[cfg(not(any(
+    all(not(target_arch = "wasm32"), feature = "native"),
+    all(target_arch = "wasm32", feature = "wasm")
+)))]
+pub(crate) async fn async_sleep(_duration: Duration) {}
+
+/// Run `task` in the background on the available runtime
+#[cfg(any(feature = "auth-core", feature = "functions"))]
+#[cfg(all(not(target_arch = "wasm32"), feature = "native"))]
+pub(crate) fn spawn_background<F>(task: F)
+where
+    F: std::future::Future<Output = ()> + Send + 'static,
+{
+    tokio::spawn(task);
+}
+
+#[cfg(any(feature = "auth-core", feature = "functions"))]
+#[cfg(all(target_arch = "wasm32", feature = "wasm"))]
+pub(crate) fn spawn_background<F>(task: F)
+where
+    F: std::future::Future<Output = ()> + 'static,
+{
+    wasm_bindgen_futures::spawn_local(task);
+}
+
+/// Drops `task`, as there is no runtime to run it on
+#[cfg(any(feature = "auth-core", feature = "functions"))]
+#[cfg(not(any(
+    all(not(target_arch = "wasm32"), feature = "native"),
+    all(target_arch = "wasm32", feature = "wasm")
+)))]
+pub(crate) fn spawn_background<F>(_task: F)
+where
+    F: std::future::Future<Output = ()> + 'static,
+{
+    tracing::warn!("Background tasks need the `native` or `wasm` feature; they are disabled");
+}
 
 /// Trait for async synchronization primitives on native platforms
 ///
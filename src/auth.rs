@@ -10,23 +10,42 @@
 //! - Auth state change events
 
 use crate::{
-    error::{Error, Result},
-    types::{SupabaseConfig, Timestamp},
+    async_runtime::{async_sleep, spawn_background},
+    circuit::CircuitBreaker,
+    error::{Error, ErrorContext, HttpErrorContext, RateLimitInfo, Result, RetryInfo},
+    types::{ProjectUrl, SupabaseConfig, Timestamp, TokenProvider},
 };
 use chrono::Utc;
-use reqwest::Client as HttpClient;
+use reqwest::{Client as HttpClient, RequestBuilder, Response, StatusCode};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::sync::{Arc, RwLock, Weak};
+use std::sync::{
+    atomic::{AtomicBool, AtomicU64, Ordering},
+    Arc, RwLock, Weak,
+};
+use std::time::Duration;
 use tracing::{debug, info, warn};
 use uuid::Uuid;
 
+/// Longest sleep of the auto refresh task, so it notices new sessions and stop requests
+const AUTO_REFRESH_CHECK_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Longest wait between retries of a failed automatic refresh
+const AUTO_REFRESH_MAX_BACKOFF: Duration = Duration::from_secs(60);
+
 // MFA imports
-use base32;
+#[cfg(feature = "auth-mfa")]
 use phonenumber::Mode;
+#[cfg(feature = "auth-mfa")]
 use qrcode::QrCode;
+#[cfg(feature = "auth-mfa")]
 use totp_rs::{Algorithm, TOTP};
 
+pub use jsonwebtoken::{Algorithm as JwtAlgorithm, DecodingKey};
+
+#[cfg(feature = "admin")]
+pub mod admin;
+
 /// Authentication state event types
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum AuthEvent {
@@ -57,7 +76,7 @@ pub enum MfaMethod {
     #[serde(rename = "totp")]
     Totp,
     /// SMS-based verification
-    #[serde(rename = "sms")]
+    #[serde(rename = "sms", alias = "phone")]
     Sms,
     /// Email-based verification (future)
     #[serde(rename = "email")]
@@ -96,6 +115,7 @@ pub enum MfaChallengeStatus {
 pub struct MfaFactor {
     pub id: Uuid,
     pub factor_type: MfaMethod,
+    #[serde(default)]
     pub friendly_name: String,
     pub status: String, // "verified", "unverified"
     pub created_at: Timestamp,
@@ -133,7 +153,104 @@ pub struct MfaVerificationRequest {
     pub code: String,
 }
 
+/// Authenticator assurance level of a session
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AssuranceLevel {
+    /// Signed in with a single factor, e.g. a password or magic link
+    Aal1,
+    /// Signed in with a second factor as well
+    Aal2,
+}
+
+/// Assurance level of the current session and the level the user can reach
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuthenticatorAssuranceLevel {
+    /// Level of the access token, `None` when it carries no `aal` claim
+    pub current_level: Option<AssuranceLevel>,
+    /// `Aal2` when the user has a verified factor, `Aal1` otherwise
+    pub next_level: Option<AssuranceLevel>,
+    /// Methods the session was authenticated with (`amr` claim), e.g. `password` and `totp`
+    pub current_authentication_methods: Vec<String>,
+}
+
+impl AuthenticatorAssuranceLevel {
+    /// Whether the user has to complete an MFA challenge to reach the next level
+    pub fn needs_mfa(&self) -> bool {
+        self.next_level == Some(AssuranceLevel::Aal2)
+            && self.current_level != Some(AssuranceLevel::Aal2)
+    }
+}
+
+/// Outcome of a password sign-in for users who may have MFA enabled
+#[derive(Debug, Clone)]
+pub enum SignInResult {
+    /// Signed in at the highest level the user can reach
+    SignedIn(AuthResponse),
+    /// Signed in at `aal1`; complete a challenge on one of `factors` with
+    /// [`Auth::complete_mfa_sign_in`] to reach `aal2`
+    MfaChallengeRequired {
+        response: AuthResponse,
+        /// Verified factors of the user
+        ///
+        /// Factors of types this crate does not know, such as WebAuthn, still
+        /// require the challenge but are not listed, so this is empty for
+        /// users who only have those.
+        factors: Vec<MfaFactor>,
+    },
+}
+
+/// Verified factors in the `factors` array of a GoTrue user object
+///
+/// Factors of types this crate does not know, such as WebAuthn, are skipped;
+/// see [`has_verified_factor`] to count them too.
+fn verified_factors(user: &serde_json::Value) -> Vec<MfaFactor> {
+    user["factors"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|factor| serde_json::from_value::<MfaFactor>(factor.clone()).ok())
+        .filter(|factor| factor.status == "verified")
+        .collect()
+}
+
+/// Whether a GoTrue user object has a verified factor of any type
+fn has_verified_factor(user: &serde_json::Value) -> bool {
+    user["factors"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .any(|factor| factor["status"] == "verified")
+}
+
+/// `aal` and `amr` claims of an access token, read without verifying it
+fn token_assurance(access_token: &str) -> (Option<AssuranceLevel>, Vec<String>) {
+    use base64::Engine;
+
+    let claims = access_token
+        .split('.')
+        .nth(1)
+        .and_then(|payload| {
+            base64::engine::general_purpose::URL_SAFE_NO_PAD
+                .decode(payload.trim_end_matches('='))
+                .ok()
+        })
+        .and_then(|json| serde_json::from_slice::<serde_json::Value>(&json).ok())
+        .unwrap_or_default();
+
+    let level = serde_json::from_value(claims["aal"].clone()).ok();
+    let methods = claims["amr"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| entry["method"].as_str().or(entry.as_str()))
+        .map(str::to_string)
+        .collect();
+    (level, methods)
+}
+
 /// Enhanced phone number with country code support
+#[cfg(feature = "auth-mfa")]
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EnhancedPhoneNumber {
     pub raw: String,
@@ -143,6 +260,7 @@ pub struct EnhancedPhoneNumber {
     pub is_valid: bool,
 }
 
+#[cfg(feature = "auth-mfa")]
 impl EnhancedPhoneNumber {
     /// Create enhanced phone number from raw input
     pub fn new(phone: &str, _default_region: Option<&str>) -> Result<Self> {
@@ -169,6 +287,60 @@ impl EnhancedPhoneNumber {
     }
 }
 
+/// Login identifier accepted by [`Auth::sign_in_with_password`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LoginIdentifier {
+    Email(String),
+    /// Phone number in E.164 format, e.g. `+15551234567`
+    Phone(String),
+}
+
+impl LoginIdentifier {
+    /// Tell an email address from a phone number
+    ///
+    /// Anything containing `@` is an email address. Phone numbers need an
+    /// international prefix (`+1 555 123 4567`) and are normalized to E.164.
+    pub fn detect(identifier: &str) -> Result<Self> {
+        let identifier = identifier.trim();
+        if identifier.contains('@') {
+            return Ok(Self::Email(identifier.to_string()));
+        }
+
+        to_e164(identifier).map(Self::Phone).ok_or_else(|| {
+            Error::auth(format!(
+                "'{}' is neither an email address nor an international phone number",
+                identifier
+            ))
+        })
+    }
+}
+
+/// Normalize an international phone number to E.164
+#[cfg(feature = "auth-mfa")]
+fn to_e164(phone: &str) -> Option<String> {
+    let parsed = phonenumber::parse(None, phone).ok()?;
+    Some(phonenumber::format(&parsed).mode(Mode::E164).to_string())
+}
+
+/// Normalize an international phone number to E.164
+///
+/// Without `auth-mfa` there is no numbering plan data, so only the shape is
+/// checked: a `+` followed by 8 to 15 digits, ignoring spaces, dashes, dots
+/// and parentheses.
+#[cfg(not(feature = "auth-mfa"))]
+fn to_e164(phone: &str) -> Option<String> {
+    let digits = phone.strip_prefix('+')?;
+    let mut e164 = String::from("+");
+    for c in digits.chars() {
+        match c {
+            '0'..='9' => e164.push(c),
+            ' ' | '-' | '.' | '(' | ')' => {}
+            _ => return None,
+        }
+    }
+    (9..=16).contains(&e164.len()).then_some(e164)
+}
+
 /// OAuth token metadata for advanced management
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TokenMetadata {
@@ -240,11 +412,50 @@ pub enum OAuthProvider {
     /// LinkedIn OAuth
     #[serde(rename = "linkedin_oidc")]
     LinkedIn,
+    /// GitLab OAuth
+    #[serde(rename = "gitlab")]
+    GitLab,
+    /// Bitbucket OAuth
+    #[serde(rename = "bitbucket")]
+    Bitbucket,
+    /// Slack OAuth (OpenID Connect)
+    #[serde(rename = "slack_oidc")]
+    Slack,
+    /// Spotify OAuth
+    #[serde(rename = "spotify")]
+    Spotify,
+    /// Twitch OAuth
+    #[serde(rename = "twitch")]
+    Twitch,
+    /// Zoom OAuth
+    #[serde(rename = "zoom")]
+    Zoom,
+    /// Notion OAuth
+    #[serde(rename = "notion")]
+    Notion,
+    /// WorkOS OAuth
+    #[serde(rename = "workos")]
+    WorkOS,
+    /// Kakao OAuth
+    #[serde(rename = "kakao")]
+    Kakao,
+    /// Keycloak OAuth
+    #[serde(rename = "keycloak")]
+    Keycloak,
+    /// Figma OAuth
+    #[serde(rename = "figma")]
+    Figma,
+    /// Fly.io OAuth
+    #[serde(rename = "fly")]
+    Fly,
+    /// Any other provider enabled on the project, by its GoTrue name
+    #[serde(untagged)]
+    Custom(String),
 }
 
 impl OAuthProvider {
     /// Get provider name as string
-    pub fn as_str(&self) -> &'static str {
+    pub fn as_str(&self) -> &str {
         match self {
             OAuthProvider::Google => "google",
             OAuthProvider::GitHub => "github",
@@ -254,6 +465,53 @@ impl OAuthProvider {
             OAuthProvider::Facebook => "facebook",
             OAuthProvider::Microsoft => "azure",
             OAuthProvider::LinkedIn => "linkedin_oidc",
+            OAuthProvider::GitLab => "gitlab",
+            OAuthProvider::Bitbucket => "bitbucket",
+            OAuthProvider::Slack => "slack_oidc",
+            OAuthProvider::Spotify => "spotify",
+            OAuthProvider::Twitch => "twitch",
+            OAuthProvider::Zoom => "zoom",
+            OAuthProvider::Notion => "notion",
+            OAuthProvider::WorkOS => "workos",
+            OAuthProvider::Kakao => "kakao",
+            OAuthProvider::Keycloak => "keycloak",
+            OAuthProvider::Figma => "figma",
+            OAuthProvider::Fly => "fly",
+            OAuthProvider::Custom(name) => name,
+        }
+    }
+}
+
+/// Channel a phone OTP is delivered over
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OtpChannel {
+    #[default]
+    Sms,
+    /// Requires a Twilio WhatsApp sender configured for the project
+    Whatsapp,
+}
+
+/// Options for [`Auth::sign_in_with_otp`]
+#[derive(Debug, Clone)]
+pub struct OtpOptions {
+    /// Delivery channel (default: SMS)
+    pub channel: OtpChannel,
+    /// Sign up unknown phone numbers (default: true)
+    ///
+    /// With `false` the OTP is only sent to existing users, so the code can
+    /// serve as a login-only second step.
+    pub create_user: bool,
+    /// User metadata stored when a new user is created
+    pub data: Option<serde_json::Value>,
+}
+
+impl Default for OtpOptions {
+    fn default() -> Self {
+        Self {
+            channel: OtpChannel::Sms,
+            create_user: true,
+            data: None,
         }
     }
 }
@@ -267,6 +525,37 @@ pub struct OAuthOptions {
     pub scopes: Option<Vec<String>>,
     /// Additional provider-specific options
     pub query_params: Option<std::collections::HashMap<String, String>>,
+    /// Use the PKCE flow
+    ///
+    /// The provider then redirects back with a `code` query parameter instead
+    /// of tokens in the URL fragment; pass it to
+    /// [`Auth::exchange_code_for_session`] on the same client.
+    pub pkce: bool,
+}
+
+/// S256 code challenge for a PKCE code verifier (RFC 7636)
+fn pkce_challenge(verifier: &str) -> String {
+    use base64::Engine;
+    use sha2::{Digest, Sha256};
+
+    base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(Sha256::digest(verifier.as_bytes()))
+}
+
+/// Code verifier of 32 random bytes, 43 characters in base64url
+fn pkce_verifier() -> Result<String> {
+    use base64::Engine;
+
+    let mut bytes = [0u8; 32];
+    getrandom::getrandom(&mut bytes)
+        .map_err(|e| Error::auth(format!("Failed to generate a PKCE verifier: {}", e)))?;
+    Ok(base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(bytes))
+}
+
+/// Body of `POST /token?grant_type=pkce`
+#[derive(Debug, Serialize)]
+struct PkceExchangeRequest<'a> {
+    auth_code: &'a str,
+    code_verifier: &'a str,
 }
 
 /// OAuth response with authorization URL
@@ -276,6 +565,97 @@ pub struct OAuthResponse {
     pub url: String,
 }
 
+/// State of an OAuth sign-in waiting for its redirect
+///
+/// See [`Auth::export_pending_sign_in`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PendingSignIn {
+    /// PKCE code verifier, for sign-ins started with [`OAuthOptions::pkce`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code_verifier: Option<String>,
+    /// Provider the sign-in was started with
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provider: Option<String>,
+}
+
+/// Tokens returned in the redirect URL of an OAuth sign-in
+#[derive(Debug)]
+struct OAuthCallback {
+    access_token: String,
+    refresh_token: String,
+    expires_in: i64,
+    expires_at: Option<i64>,
+    token_type: String,
+    provider_token: Option<String>,
+    provider_refresh_token: Option<String>,
+}
+
+impl OAuthCallback {
+    /// Parse the tokens from the URL fragment (or query string)
+    fn parse(url: &str) -> Result<Self> {
+        let url = url::Url::parse(url)?;
+        let params: HashMap<String, String> = url
+            .fragment()
+            .filter(|fragment| !fragment.is_empty())
+            .map(|fragment| {
+                url::form_urlencoded::parse(fragment.as_bytes())
+                    .into_owned()
+                    .collect()
+            })
+            .unwrap_or_else(|| url.query_pairs().into_owned().collect());
+
+        if let Some(error) = params.get("error") {
+            let description = params.get("error_description").unwrap_or(error);
+            return Err(Error::auth(format!(
+                "OAuth sign-in failed: {}",
+                description
+            )));
+        }
+
+        let access_token = params
+            .get("access_token")
+            .cloned()
+            .ok_or_else(|| Error::auth("OAuth callback URL has no access token"))?;
+
+        Ok(Self {
+            access_token,
+            refresh_token: params.get("refresh_token").cloned().unwrap_or_default(),
+            expires_in: params
+                .get("expires_in")
+                .and_then(|value| value.parse().ok())
+                .unwrap_or(3600),
+            expires_at: params
+                .get("expires_at")
+                .and_then(|value| value.parse().ok()),
+            token_type: params
+                .get("token_type")
+                .cloned()
+                .unwrap_or_else(|| "bearer".to_string()),
+            provider_token: params.get("provider_token").cloned(),
+            provider_refresh_token: params.get("provider_refresh_token").cloned(),
+        })
+    }
+
+    fn into_session(self, user: User) -> Session {
+        let expires_at = self
+            .expires_at
+            .and_then(|timestamp| chrono::DateTime::from_timestamp(timestamp, 0))
+            .unwrap_or_else(|| Utc::now() + chrono::Duration::seconds(self.expires_in));
+
+        Session {
+            access_token: self.access_token,
+            refresh_token: self.refresh_token,
+            expires_in: self.expires_in,
+            expires_at,
+            token_type: self.token_type,
+            user,
+            provider_token: self.provider_token,
+            provider_refresh_token: self.provider_refresh_token,
+            provider: None,
+        }
+    }
+}
+
 /// Phone authentication request
 #[derive(Debug, Serialize)]
 struct PhoneSignUpRequest {
@@ -301,6 +681,20 @@ struct OTPVerificationRequest {
     verification_type: String,
 }
 
+/// Invite verification request
+#[derive(Debug, Serialize)]
+struct InviteVerificationRequest<'a> {
+    token_hash: &'a str,
+    #[serde(rename = "type")]
+    verification_type: &'static str,
+}
+
+/// Phone change request
+#[derive(Debug, Serialize)]
+struct PhoneChangeRequest {
+    phone: String,
+}
+
 /// Magic link request
 #[derive(Debug, Serialize)]
 struct MagicLinkRequest {
@@ -311,6 +705,16 @@ struct MagicLinkRequest {
     data: Option<serde_json::Value>,
 }
 
+/// Phone OTP request
+#[derive(Debug, Serialize)]
+struct PhoneOtpRequest {
+    phone: String,
+    channel: OtpChannel,
+    create_user: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    data: Option<serde_json::Value>,
+}
+
 /// Anonymous sign-in request
 #[derive(Debug, Serialize)]
 struct AnonymousSignInRequest {
@@ -318,12 +722,221 @@ struct AnonymousSignInRequest {
     data: Option<serde_json::Value>,
 }
 
+/// Web3 wallet sign-in request
+#[derive(Debug, Serialize)]
+struct Web3SignInRequest {
+    chain: Web3Chain,
+    message: String,
+    signature: String,
+}
+
 /// Authentication client for handling user sessions and JWT tokens
 pub struct Auth {
     http_client: Arc<HttpClient>,
     config: Arc<SupabaseConfig>,
     session: Arc<RwLock<Option<Session>>>,
     event_listeners: Arc<RwLock<HashMap<Uuid, AuthStateCallback>>>,
+    pending_phone_change: Arc<RwLock<Option<String>>>,
+    refresh_gate: Arc<futures_util::lock::Mutex<Option<FailedRefresh>>>,
+    refresh_attempts: Arc<AtomicU64>,
+    session_serializer: Arc<RwLock<Option<Arc<dyn SessionSerializer>>>>,
+    account_deletion: Arc<RwLock<AccountDeletion>>,
+    provider_refresh: Arc<RwLock<ProviderRefresh>>,
+    /// Verifier of a pending PKCE sign-in
+    code_verifier: Arc<RwLock<Option<String>>>,
+    /// Provider of a pending OAuth sign-in
+    oauth_provider: Arc<RwLock<Option<String>>>,
+    /// Rate limit headers of the last auth response that sent any
+    rate_limit: Arc<RwLock<Option<RateLimitInfo>>>,
+    /// Retries requests; auth is never circuit broken, so it has no config
+    circuit: CircuitBreaker,
+    /// Where sessions are written when persistence is enabled
+    #[cfg(feature = "session-management")]
+    session_persistence: Arc<RwLock<Option<SessionPersistence>>>,
+}
+
+/// Hook for transforming sessions on their way to and from storage
+///
+/// Use it to encrypt only the refresh token, strip user metadata, and similar.
+/// Both methods pass the session through unchanged by default, and `decode`
+/// must undo whatever `encode` did. Used by [`Auth`] session persistence and
+/// by `SessionManager`.
+///
+/// # Examples
+/// ```rust
+/// use supabase_lib_rs::auth::{Session, SessionSerializer};
+///
+/// /// Keep user metadata out of storage
+/// struct StripMetadata;
+///
+/// impl SessionSerializer for StripMetadata {
+///     fn encode(&self, session: &Session) -> supabase_lib_rs::Result<Session> {
+///         let mut stored = session.clone();
+///         stored.user.user_metadata = serde_json::json!({});
+///         Ok(stored)
+///     }
+/// }
+/// ```
+pub trait SessionSerializer: Send + Sync {
+    /// Transform a session before it is written to storage
+    fn encode(&self, session: &Session) -> Result<Session> {
+        Ok(session.clone())
+    }
+
+    /// Restore a session read back from storage
+    fn decode(&self, stored: Session) -> Result<Session> {
+        Ok(stored)
+    }
+}
+
+/// Server endpoint that deletes the signed-in user's account
+///
+/// Supabase Auth has no self-service deletion endpoint, so deletion goes
+/// through project code that calls the admin API with the service role key,
+/// called with the user's access token and a JSON body
+/// `{"confirmation": "..."}`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AccountDeletionEndpoint {
+    /// Edge Function name, e.g. `delete-account`
+    EdgeFunction(String),
+    /// Database function name, called through `/rest/v1/rpc`
+    Rpc(String),
+}
+
+impl Default for AccountDeletionEndpoint {
+    fn default() -> Self {
+        Self::EdgeFunction("delete-account".to_string())
+    }
+}
+
+/// Boxed future returned by a pre-deletion hook, `Send` on native targets
+#[cfg(not(target_arch = "wasm32"))]
+pub type AccountHookFuture =
+    std::pin::Pin<Box<dyn std::future::Future<Output = Result<()>> + Send>>;
+#[cfg(target_arch = "wasm32")]
+pub type AccountHookFuture = std::pin::Pin<Box<dyn std::future::Future<Output = Result<()>>>>;
+
+/// Hook run before an account is deleted, e.g. to export the user's data
+pub type PreDeletionHook = Arc<dyn Fn(User) -> AccountHookFuture + Send + Sync>;
+
+/// Account deletion settings shared by clones of [`Auth`]
+#[derive(Clone, Default)]
+struct AccountDeletion {
+    endpoint: AccountDeletionEndpoint,
+    hook: Option<PreDeletionHook>,
+}
+
+/// OAuth provider tokens obtained by [`Auth::refresh_provider_token`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProviderTokens {
+    /// New provider access token
+    pub provider_token: String,
+    /// Rotated provider refresh token; the previous one is kept when absent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provider_refresh_token: Option<String>,
+}
+
+/// Boxed future returned by a provider token refresher, `Send` on native targets
+#[cfg(not(target_arch = "wasm32"))]
+pub type ProviderRefreshFuture =
+    std::pin::Pin<Box<dyn std::future::Future<Output = Result<ProviderTokens>> + Send>>;
+#[cfg(target_arch = "wasm32")]
+pub type ProviderRefreshFuture =
+    std::pin::Pin<Box<dyn std::future::Future<Output = Result<ProviderTokens>>>>;
+
+/// Hook exchanging a provider refresh token for new provider tokens
+pub type ProviderTokenRefresher =
+    Arc<dyn Fn(OAuthProvider, String) -> ProviderRefreshFuture + Send + Sync>;
+
+/// Provider token refresh settings shared by clones of [`Auth`]
+#[derive(Clone)]
+struct ProviderRefresh {
+    /// Edge Function holding the provider client secrets
+    function: String,
+    refresher: Option<ProviderTokenRefresher>,
+}
+
+impl Default for ProviderRefresh {
+    fn default() -> Self {
+        Self {
+            function: "refresh-provider-token".to_string(),
+            refresher: None,
+        }
+    }
+}
+
+/// [`TokenProvider`] reading the access token of an [`Auth`] session
+struct SessionTokenProvider(Arc<RwLock<Option<Session>>>);
+
+impl TokenProvider for SessionTokenProvider {
+    fn access_token(&self) -> Option<String> {
+        let session = self.0.read().ok()?;
+        session.as_ref().map(|session| session.access_token.clone())
+    }
+}
+
+/// Whether a deletion confirmation names the user
+///
+/// Accepts the email address (case-insensitive), the phone number, or, for
+/// users with neither, the user ID.
+fn confirmation_matches(user: &User, confirmation: &str) -> bool {
+    let confirmation = confirmation.trim();
+    if confirmation.is_empty() {
+        return false;
+    }
+
+    let email = user.email.as_deref().filter(|email| !email.is_empty());
+    let phone = user.phone.as_deref().filter(|phone| !phone.is_empty());
+    if email.is_none() && phone.is_none() {
+        return confirmation == user.id.to_string();
+    }
+
+    email.is_some_and(|email| email.eq_ignore_ascii_case(confirmation))
+        || phone.is_some_and(|phone| {
+            phone.trim_start_matches('+') == confirmation.trim_start_matches('+')
+        })
+}
+
+/// Handle of the task started by [`Auth::start_auto_refresh`]
+///
+/// The task stops when the handle is dropped or [`stop`](Self::stop)ped.
+#[derive(Debug)]
+pub struct AutoRefreshHandle {
+    active: Arc<AtomicBool>,
+}
+
+impl AutoRefreshHandle {
+    /// Whether the task is still scheduled
+    pub fn is_active(&self) -> bool {
+        self.active.load(Ordering::SeqCst)
+    }
+
+    /// Stop refreshing; the session is left as it is
+    pub fn stop(self) {
+        self.active.store(false, Ordering::SeqCst);
+    }
+}
+
+impl Drop for AutoRefreshHandle {
+    fn drop(&mut self) {
+        self.active.store(false, Ordering::SeqCst);
+    }
+}
+
+/// Outcome of the last failed refresh, shared with callers waiting on the gate
+/// Session manager sessions are written through, and the stored session of this client
+#[cfg(feature = "session-management")]
+#[derive(Clone)]
+struct SessionPersistence {
+    manager: Arc<crate::session::SessionManager>,
+    session_id: Option<Uuid>,
+    listener_id: Uuid,
+}
+
+#[derive(Debug, Clone)]
+struct FailedRefresh {
+    attempt: u64,
+    message: String,
 }
 
 impl Clone for Auth {
@@ -332,6 +945,18 @@ impl Clone for Auth {
             http_client: self.http_client.clone(),
             config: self.config.clone(),
             session: self.session.clone(),
+            pending_phone_change: self.pending_phone_change.clone(),
+            refresh_gate: self.refresh_gate.clone(),
+            refresh_attempts: self.refresh_attempts.clone(),
+            session_serializer: self.session_serializer.clone(),
+            account_deletion: self.account_deletion.clone(),
+            provider_refresh: self.provider_refresh.clone(),
+            code_verifier: self.code_verifier.clone(),
+            oauth_provider: self.oauth_provider.clone(),
+            rate_limit: self.rate_limit.clone(),
+            circuit: self.circuit.clone(),
+            #[cfg(feature = "session-management")]
+            session_persistence: self.session_persistence.clone(),
             event_listeners: Arc::new(RwLock::new(HashMap::new())),
         }
     }
@@ -354,6 +979,64 @@ impl std::fmt::Debug for Auth {
     }
 }
 
+/// Sending auth requests while keeping track of GoTrue rate limits
+pub(crate) trait SendTracked {
+    /// Send the request, retrying according to the policy of `circuit`
+    ///
+    /// Stores the rate limit headers of the response in `rate_limit`. A
+    /// `429 Too Many Requests` response fails with an auth error carrying
+    /// them, so callers never parse a rate limit body as a result.
+    async fn send_tracked(
+        self,
+        rate_limit: &RwLock<Option<RateLimitInfo>>,
+        circuit: &CircuitBreaker,
+    ) -> Result<Response>;
+}
+
+impl SendTracked for RequestBuilder {
+    async fn send_tracked(
+        self,
+        rate_limit: &RwLock<Option<RateLimitInfo>>,
+        circuit: &CircuitBreaker,
+    ) -> Result<Response> {
+        let response = circuit.send(self).await?;
+        let info = RateLimitInfo::from_headers(response.headers());
+        if let (Some(info), Ok(mut guard)) = (&info, rate_limit.write()) {
+            *guard = Some(info.clone());
+        }
+
+        if response.status() != StatusCode::TOO_MANY_REQUESTS {
+            return Ok(response);
+        }
+
+        let url = response.url().to_string();
+        let body = response.text().await.unwrap_or_default();
+        warn!("Auth rate limit exceeded: {}", url);
+        let context = ErrorContext {
+            http: Some(HttpErrorContext {
+                status_code: Some(StatusCode::TOO_MANY_REQUESTS.as_u16()),
+                headers: None,
+                response_body: Some(body.clone()),
+                url: Some(url),
+                method: None,
+            }),
+            retry: Some(RetryInfo {
+                attempts: 1,
+                retryable: true,
+                retry_after: info.as_ref().and_then(|info| info.retry_after),
+            }),
+            rate_limit: Some(info.unwrap_or_default()),
+            ..Default::default()
+        };
+        let message = if body.is_empty() {
+            "Rate limit exceeded".to_string()
+        } else {
+            body
+        };
+        Err(Error::auth_with_context(message, context).with_fingerprint(circuit.fingerprint()))
+    }
+}
+
 /// User information from Supabase Auth
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct User {
@@ -381,160 +1064,1272 @@ pub struct Session {
     pub expires_at: Timestamp,
     pub token_type: String,
     pub user: User,
+    /// OAuth provider access token (e.g. for the Google or GitHub API)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provider_token: Option<String>,
+    /// OAuth provider refresh token, if the provider issued one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provider_refresh_token: Option<String>,
+    /// OAuth provider that issued `provider_token` (e.g. `"google"`), recorded
+    /// when this client completes the sign-in
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provider: Option<String>,
 }
 
-/// Response from authentication operations
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct AuthResponse {
-    pub user: Option<User>,
-    pub session: Option<Session>,
+impl Session {
+    /// Carry provider tokens over from the session this one replaces
+    ///
+    /// Token refresh responses do not include provider tokens, so without this
+    /// they would be lost on the first refresh.
+    fn inherit_provider_tokens(&mut self, previous: &Session) {
+        if self.provider_token.is_none() {
+            self.provider_token = previous.provider_token.clone();
+        }
+        if self.provider_refresh_token.is_none() {
+            self.provider_refresh_token = previous.provider_refresh_token.clone();
+        }
+        if self.provider.is_none() {
+            self.provider = previous.provider.clone();
+        }
+    }
 }
 
-/// Sign up request payload
-#[derive(Debug, Serialize)]
-struct SignUpRequest {
-    email: String,
-    password: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    data: Option<serde_json::Value>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    redirect_to: Option<String>,
+/// Public Auth server settings, from `/auth/v1/settings`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AuthSettings {
+    /// Sign-in methods by name (`email`, `phone`, `anonymous_users`, OAuth providers)
+    #[serde(default)]
+    pub external: HashMap<String, bool>,
+    /// New sign-ups are rejected
+    #[serde(default)]
+    pub disable_signup: bool,
+    /// Email sign-ups are confirmed without a confirmation email
+    #[serde(default)]
+    pub mailer_autoconfirm: bool,
+    /// Phone sign-ups are confirmed without an OTP
+    #[serde(default)]
+    pub phone_autoconfirm: bool,
+    /// Configured SMS provider, e.g. `twilio`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sms_provider: Option<String>,
+    /// SAML single sign-on is enabled
+    #[serde(default)]
+    pub saml_enabled: bool,
 }
 
-/// Sign in request payload
-#[derive(Debug, Serialize)]
-struct SignInRequest {
-    email: String,
-    password: String,
+impl AuthSettings {
+    /// Whether email and password sign-in is enabled
+    pub fn email_enabled(&self) -> bool {
+        self.is_enabled("email")
+    }
+
+    /// Whether phone sign-in is enabled
+    pub fn phone_enabled(&self) -> bool {
+        self.is_enabled("phone")
+    }
+
+    /// Whether anonymous sign-in is enabled
+    pub fn anonymous_enabled(&self) -> bool {
+        self.is_enabled("anonymous_users")
+    }
+
+    /// Whether an OAuth provider is enabled
+    pub fn provider_enabled(&self, provider: &OAuthProvider) -> bool {
+        self.is_enabled(provider.as_str())
+    }
+
+    /// Enabled OAuth providers, sorted by name
+    pub fn enabled_providers(&self) -> Vec<OAuthProvider> {
+        const NON_OAUTH: &[&str] = &["email", "phone", "anonymous_users"];
+
+        let mut names: Vec<&str> = self
+            .external
+            .iter()
+            .filter(|(name, enabled)| **enabled && !NON_OAUTH.contains(&name.as_str()))
+            .map(|(name, _)| name.as_str())
+            .collect();
+        names.sort_unstable();
+
+        names
+            .into_iter()
+            .filter_map(|name| serde_json::from_value(serde_json::Value::from(name)).ok())
+            .collect()
+    }
+
+    fn is_enabled(&self, name: &str) -> bool {
+        self.external.get(name).copied().unwrap_or(false)
+    }
 }
 
-/// Password reset request payload
-#[derive(Debug, Serialize)]
-struct PasswordResetRequest {
-    email: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    redirect_to: Option<String>,
+/// Blockchain of the wallet used for Web3 sign-in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Web3Chain {
+    /// Sign-In with Ethereum (EIP-4361)
+    Ethereum,
+    /// Sign-In with Solana
+    Solana,
 }
 
-/// Token refresh request payload
-#[derive(Debug, Serialize)]
-struct RefreshTokenRequest {
-    refresh_token: String,
+impl Web3Chain {
+    /// Encode raw signature bytes the way Auth expects them for this chain
+    ///
+    /// Ethereum signatures are `0x`-prefixed hex, Solana signatures are
+    /// unpadded URL-safe base64.
+    pub fn encode_signature(&self, signature: &[u8]) -> String {
+        match self {
+            Web3Chain::Ethereum => {
+                let hex: String = signature
+                    .iter()
+                    .map(|byte| format!("{:02x}", byte))
+                    .collect();
+                format!("0x{}", hex)
+            }
+            Web3Chain::Solana => {
+                use base64::Engine;
+                base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(signature)
+            }
+        }
+    }
+
+    fn account_name(&self) -> &'static str {
+        match self {
+            Web3Chain::Ethereum => "Ethereum",
+            Web3Chain::Solana => "Solana",
+        }
+    }
 }
 
-/// Update user request payload
-#[derive(Debug, Serialize)]
-struct UpdateUserRequest {
-    #[serde(skip_serializing_if = "Option::is_none")]
-    email: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    password: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    data: Option<serde_json::Value>,
+/// Message a wallet signs to sign in with Web3
+///
+/// Renders as an EIP-4361 message for Ethereum and a Sign-In with Solana
+/// message for Solana. `domain` and `uri` must match the site the user signs
+/// in from and be allowed in the project's redirect URLs.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Web3SignInMessage {
+    /// Host requesting the sign-in, e.g. `example.com`
+    pub domain: String,
+    /// Wallet address; Ethereum addresses should use EIP-55 checksum casing
+    pub address: String,
+    /// Human-readable statement shown to the user
+    pub statement: Option<String>,
+    /// URI of the site requesting the sign-in
+    pub uri: String,
+    /// Chain ID, e.g. `1` for Ethereum mainnet or `mainnet` for Solana
+    pub chain_id: Option<String>,
+    /// Random nonce against replay
+    pub nonce: String,
+    /// When the message was created
+    pub issued_at: chrono::DateTime<Utc>,
+    /// When the signed message stops being accepted
+    pub expiration_time: Option<chrono::DateTime<Utc>>,
+    /// When the signed message starts being accepted
+    pub not_before: Option<chrono::DateTime<Utc>>,
 }
 
-impl Auth {
-    /// Create a new Auth instance
-    pub fn new(config: Arc<SupabaseConfig>, http_client: Arc<HttpClient>) -> Result<Self> {
-        debug!("Initializing Auth module");
+impl Web3SignInMessage {
+    /// Create a message issued now with a random nonce
+    pub fn new(
+        domain: impl Into<String>,
+        address: impl Into<String>,
+        uri: impl Into<String>,
+    ) -> Self {
+        Self {
+            domain: domain.into(),
+            address: address.into(),
+            statement: None,
+            uri: uri.into(),
+            chain_id: None,
+            nonce: Uuid::new_v4().simple().to_string(),
+            issued_at: Utc::now(),
+            expiration_time: None,
+            not_before: None,
+        }
+    }
 
-        Ok(Self {
-            http_client,
-            config,
-            session: Arc::new(RwLock::new(None)),
-            event_listeners: Arc::new(RwLock::new(HashMap::new())),
-        })
+    /// Set the statement shown to the user
+    pub fn statement(mut self, statement: impl Into<String>) -> Self {
+        self.statement = Some(statement.into());
+        self
     }
 
-    /// Sign up a new user with email and password
-    pub async fn sign_up_with_email_and_password(
-        &self,
-        email: &str,
-        password: &str,
-    ) -> Result<AuthResponse> {
-        self.sign_up_with_email_password_and_data(email, password, None, None)
-            .await
+    /// Set the chain ID
+    pub fn chain_id(mut self, chain_id: impl Into<String>) -> Self {
+        self.chain_id = Some(chain_id.into());
+        self
     }
 
-    /// Sign up a new user with email, password, and optional metadata
-    pub async fn sign_up_with_email_password_and_data(
-        &self,
-        email: &str,
-        password: &str,
-        data: Option<serde_json::Value>,
-        redirect_to: Option<String>,
-    ) -> Result<AuthResponse> {
-        debug!("Signing up user with email: {}", email);
+    /// Expire the message after `ttl`
+    pub fn expires_in(mut self, ttl: chrono::Duration) -> Self {
+        self.expiration_time = Some(self.issued_at + ttl);
+        self
+    }
+
+    /// Render the text to sign
+    ///
+    /// Ethereum messages without a chain ID use mainnet (`1`), as EIP-4361
+    /// requires one.
+    pub fn to_message(&self, chain: Web3Chain) -> String {
+        let timestamp = |time: &chrono::DateTime<Utc>| {
+            time.to_rfc3339_opts(chrono::SecondsFormat::Millis, true)
+        };
+
+        let mut lines = vec![
+            format!(
+                "{} wants you to sign in with your {} account:",
+                self.domain,
+                chain.account_name()
+            ),
+            self.address.clone(),
+            String::new(),
+        ];
+        if let Some(statement) = &self.statement {
+            lines.push(statement.clone());
+            lines.push(String::new());
+        }
+
+        let mut fields = Vec::new();
+        match chain {
+            Web3Chain::Ethereum => {
+                fields.push(("URI", self.uri.clone()));
+                fields.push(("Version", "1".to_string()));
+                fields.push((
+                    "Chain ID",
+                    self.chain_id.clone().unwrap_or_else(|| "1".to_string()),
+                ));
+                fields.push(("Nonce", self.nonce.clone()));
+                fields.push(("Issued At", timestamp(&self.issued_at)));
+                if let Some(time) = &self.expiration_time {
+                    fields.push(("Expiration Time", timestamp(time)));
+                }
+                if let Some(time) = &self.not_before {
+                    fields.push(("Not Before", timestamp(time)));
+                }
+            }
+            Web3Chain::Solana => {
+                fields.push(("Version", "1".to_string()));
+                fields.push(("URI", self.uri.clone()));
+                fields.push(("Issued At", timestamp(&self.issued_at)));
+                if let Some(time) = &self.expiration_time {
+                    fields.push(("Expiration Time", timestamp(time)));
+                }
+                if let Some(time) = &self.not_before {
+                    fields.push(("Not Before", timestamp(time)));
+                }
+                if let Some(chain_id) = &self.chain_id {
+                    fields.push(("Chain ID", chain_id.clone()));
+                }
+                fields.push(("Nonce", self.nonce.clone()));
+            }
+        }
+
+        lines.extend(
+            fields
+                .into_iter()
+                .map(|(name, value)| format!("{}: {}", name, value)),
+        );
+        lines.join("\n")
+    }
+}
+
+/// Response from authentication operations
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuthResponse {
+    pub user: Option<User>,
+    pub session: Option<Session>,
+}
+
+/// Claims of a Supabase access token
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JwtClaims {
+    #[serde(default)]
+    pub sub: Option<String>,
+    /// Audience, either a single string or an array of strings
+    #[serde(default)]
+    pub aud: Option<serde_json::Value>,
+    #[serde(default)]
+    pub iss: Option<String>,
+    pub exp: i64,
+    #[serde(default)]
+    pub iat: Option<i64>,
+    #[serde(default)]
+    pub role: Option<String>,
+    #[serde(default)]
+    pub email: Option<String>,
+    #[serde(default)]
+    pub session_id: Option<String>,
+    /// Any other claims, such as `app_metadata` or custom claims
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+/// Options for validating access tokens locally
+///
+/// Expiry is always checked. Audience and issuer are only checked when at
+/// least one expected value is configured; a token must then carry the claim
+/// and match one of the expected values.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use supabase_lib_rs::auth::{DecodingKey, JwtAlgorithm, JwtValidation};
+///
+/// let validation = JwtValidation::new()
+///     .audience("authenticated")
+///     .issuer("https://example.supabase.co/auth/v1")
+///     .algorithms(vec![JwtAlgorithm::HS256]);
+///
+/// let key = DecodingKey::from_secret(b"your-jwt-secret");
+/// # let token = "";
+/// let claims = validation.verify(token, &key)?;
+/// println!("Token for user {:?}", claims.sub);
+/// # Ok::<(), supabase_lib_rs::Error>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct JwtValidation {
+    audience: Vec<String>,
+    issuer: Vec<String>,
+    algorithms: Vec<JwtAlgorithm>,
+    leeway: u64,
+}
+
+impl Default for JwtValidation {
+    fn default() -> Self {
+        Self {
+            audience: Vec::new(),
+            issuer: Vec::new(),
+            algorithms: vec![JwtAlgorithm::HS256],
+            leeway: 60,
+        }
+    }
+}
+
+impl JwtValidation {
+    /// Create validation options accepting HS256 tokens with no audience or issuer checks
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add an accepted audience (`aud` claim)
+    pub fn audience(mut self, audience: &str) -> Self {
+        self.audience.push(audience.to_string());
+        self
+    }
+
+    /// Add an accepted issuer (`iss` claim)
+    pub fn issuer(mut self, issuer: &str) -> Self {
+        self.issuer.push(issuer.to_string());
+        self
+    }
+
+    /// Set the accepted signing algorithms
+    ///
+    /// All algorithms must belong to the same family as the decoding key.
+    pub fn algorithms(mut self, algorithms: Vec<JwtAlgorithm>) -> Self {
+        self.algorithms = algorithms;
+        self
+    }
+
+    /// Set the clock skew tolerance in seconds for `exp` and `nbf` (default: 60)
+    pub fn leeway(mut self, seconds: u64) -> Self {
+        self.leeway = seconds;
+        self
+    }
+
+    /// Verify a token's signature and claims, returning the claims
+    pub fn verify(&self, token: &str, key: &DecodingKey) -> Result<JwtClaims> {
+        if self.algorithms.is_empty() {
+            return Err(Error::config(
+                "JWT validation requires at least one algorithm",
+            ));
+        }
+
+        let header = jsonwebtoken::decode_header(token)?;
+        if !self.algorithms.contains(&header.alg) {
+            return Err(Error::auth(format!(
+                "Token algorithm {:?} is not accepted (expected one of {:?})",
+                header.alg, self.algorithms
+            )));
+        }
+
+        let mut validation = jsonwebtoken::Validation::new(header.alg);
+        validation.algorithms = self.algorithms.clone();
+        validation.leeway = self.leeway;
+        if self.audience.is_empty() {
+            validation.validate_aud = false;
+        } else {
+            validation.set_audience(&self.audience);
+            validation.required_spec_claims.insert("aud".to_string());
+        }
+        if !self.issuer.is_empty() {
+            validation.set_issuer(&self.issuer);
+            validation.required_spec_claims.insert("iss".to_string());
+        }
+
+        jsonwebtoken::decode::<JwtClaims>(token, key, &validation)
+            .map(|data| data.claims)
+            .map_err(|err| self.describe_error(err))
+    }
+
+    fn describe_error(&self, err: jsonwebtoken::errors::Error) -> Error {
+        use jsonwebtoken::errors::ErrorKind;
+
+        match err.kind() {
+            ErrorKind::InvalidAudience => Error::auth(format!(
+                "Token audience does not match expected audience {:?}",
+                self.audience
+            )),
+            ErrorKind::InvalidIssuer => Error::auth(format!(
+                "Token issuer does not match expected issuer {:?}",
+                self.issuer
+            )),
+            ErrorKind::MissingRequiredClaim(claim) => {
+                Error::auth(format!("Token is missing required claim: {}", claim))
+            }
+            ErrorKind::ExpiredSignature => Error::auth("Token has expired"),
+            ErrorKind::ImmatureSignature => Error::auth("Token is not yet valid"),
+            ErrorKind::InvalidSignature => Error::auth("Token signature is invalid"),
+            _ => Error::from(err),
+        }
+    }
+}
+
+/// Sign up request payload
+#[derive(Debug, Serialize)]
+struct SignUpRequest {
+    email: String,
+    password: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    data: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    redirect_to: Option<String>,
+}
+
+/// Sign in request payload
+#[derive(Debug, Serialize)]
+struct SignInRequest {
+    email: String,
+    password: String,
+}
+
+/// Password reset request payload
+#[derive(Debug, Serialize)]
+struct PasswordResetRequest {
+    email: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    redirect_to: Option<String>,
+}
+
+/// Token refresh request payload
+#[derive(Debug, Serialize)]
+struct RefreshTokenRequest {
+    refresh_token: String,
+}
+
+/// Update user request payload
+#[derive(Debug, Serialize)]
+struct UpdateUserRequest {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    email: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    password: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    data: Option<serde_json::Value>,
+}
+
+impl Auth {
+    /// Create a new Auth instance
+    pub fn new(config: Arc<SupabaseConfig>, http_client: Arc<HttpClient>) -> Result<Self> {
+        debug!("Initializing Auth module");
+
+        let circuit = CircuitBreaker::new("auth", None)
+            .with_retry(config.http_config.retry.clone())
+            .with_fingerprint(config.error_fingerprint);
+        let auth = Self {
+            http_client,
+            config,
+            session: Arc::new(RwLock::new(None)),
+            event_listeners: Arc::new(RwLock::new(HashMap::new())),
+            pending_phone_change: Arc::new(RwLock::new(None)),
+            refresh_gate: Arc::new(futures_util::lock::Mutex::new(None)),
+            refresh_attempts: Arc::new(AtomicU64::new(0)),
+            session_serializer: Arc::new(RwLock::new(None)),
+            account_deletion: Arc::new(RwLock::new(AccountDeletion::default())),
+            provider_refresh: Arc::new(RwLock::new(ProviderRefresh::default())),
+            code_verifier: Arc::new(RwLock::new(None)),
+            oauth_provider: Arc::new(RwLock::new(None)),
+            rate_limit: Arc::new(RwLock::new(None)),
+            circuit,
+            #[cfg(feature = "session-management")]
+            session_persistence: Arc::new(RwLock::new(None)),
+        };
+
+        #[cfg(feature = "session-management")]
+        if auth.config.auth_config.persist_sessions && !auth.config.is_service_role() {
+            let manager =
+                crate::session::SessionManager::new(crate::session::SessionManagerConfig {
+                    storage_backend: crate::session::storage::create_project_storage(
+                        &auth.config.url,
+                        &auth.config.auth_config.storage_key,
+                    )?,
+                    enable_cross_tab_sync: cfg!(all(target_arch = "wasm32", feature = "wasm")),
+                    ..Default::default()
+                });
+            auth.set_session_manager(Arc::new(manager));
+            auth.restore_stored_session_in_background();
+        }
+
+        #[cfg(all(target_arch = "wasm32", feature = "wasm"))]
+        if auth.persists_to_local_storage() {
+            auth.restore_persisted_session();
+        }
+
+        Ok(auth)
+    }
+
+    /// Write sessions through `manager` from now on
+    ///
+    /// Sign-ins and refreshes store the session, signing out removes it.
+    /// Set up automatically with the platform storage when
+    /// [`AuthConfig::persist_sessions`](crate::types::AuthConfig::persist_sessions)
+    /// is enabled; call it to keep sessions somewhere else, then
+    /// [`restore_stored_session`](Self::restore_stored_session).
+    ///
+    /// When another tab removes the stored session, e.g. by signing out with
+    /// cross-tab sync enabled, this client signs out as well and emits
+    /// [`AuthEvent::SignedOut`]. On WASM the manager replaces the
+    /// `persist_session` localStorage entry.
+    #[cfg(feature = "session-management")]
+    pub fn set_session_manager(&self, manager: Arc<crate::session::SessionManager>) {
+        let persistence = Arc::downgrade(&self.session_persistence);
+        let session = self.session.clone();
+        let event_listeners = self.event_listeners.clone();
+        let listener_id = manager.on_session_event(move |event| {
+            let crate::session::SessionEvent::Destroyed { session_id, .. } = event else {
+                return;
+            };
+            let Some(persistence) = persistence.upgrade() else {
+                return;
+            };
+            // Local sign-outs clear the id before removing the session
+            match persistence.write() {
+                Ok(mut guard) => match guard.as_mut() {
+                    Some(stored) if stored.session_id == Some(session_id) => {
+                        stored.session_id = None
+                    }
+                    _ => return,
+                },
+                Err(_) => return,
+            }
+
+            let signed_in = session
+                .write()
+                .map(|mut guard| guard.take().is_some())
+                .unwrap_or(false);
+            if signed_in {
+                info!("Signed out by another tab");
+                Self::notify_listeners(&session, &event_listeners, AuthEvent::SignedOut);
+            }
+        });
+
+        if let Ok(mut guard) = self.session_persistence.write() {
+            if let Some(previous) = guard.take() {
+                previous.manager.remove_event_listener(previous.listener_id);
+            }
+            *guard = Some(SessionPersistence {
+                manager,
+                session_id: None,
+                listener_id,
+            });
+        }
+    }
+
+    /// Restore the most recent stored session from the session manager
+    ///
+    /// The session is checked with the project before it is used: its access
+    /// token must be accepted by the server, and an expired one is exchanged
+    /// for a new session with the stored refresh token. Sessions the server
+    /// rejects are removed from storage.
+    ///
+    /// Emits [`AuthEvent::SignedIn`] and returns the session when one was
+    /// restored. A session signed in meanwhile is kept, and nothing happens
+    /// without a session manager.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use supabase_lib_rs::{types::{AuthConfig, SupabaseConfig}, Client};
+    ///
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = Client::new_with_config(SupabaseConfig {
+    ///     url: "https://example.supabase.co".to_string(),
+    ///     key: "your-anon-key".to_string(),
+    ///     auth_config: AuthConfig {
+    ///         persist_sessions: true,
+    ///         ..Default::default()
+    ///     },
+    ///     ..Default::default()
+    /// })?;
+    ///
+    /// // Already running in the background; await it before checking the user
+    /// if client.auth().restore_stored_session().await?.is_none() {
+    ///     println!("Please sign in");
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "session-management")]
+    pub async fn restore_stored_session(&self) -> Result<Option<Session>> {
+        let Some(persistence) = self.session_persistence() else {
+            return Ok(None);
+        };
+        if self.session.read().is_ok_and(|guard| guard.is_some()) {
+            debug!("Keeping the current session over the stored one");
+            return Ok(None);
+        }
+
+        persistence.manager.initialize().await?;
+        let latest = persistence
+            .manager
+            .list_sessions()
+            .await?
+            .into_iter()
+            .filter(|stored| stored.is_usable())
+            .max_by_key(|stored| stored.metadata.last_accessed_at);
+        let Some(stored) = latest else {
+            return Ok(None);
+        };
+        let session_id = stored.metadata.session_id;
+
+        let Some((session, refreshed)) = self.verify_stored_session(stored.session).await? else {
+            info!("Stored session was rejected by the server; removing it");
+            persistence
+                .manager
+                .remove_session(session_id, "rejected by the server".to_string())
+                .await?;
+            return Ok(None);
+        };
+        if refreshed {
+            // The old refresh token is spent; keep the new one even if unused
+            persistence
+                .manager
+                .update_session(session_id, session.clone())
+                .await?;
+        }
+
+        {
+            let mut session_guard = self
+                .session
+                .write()
+                .map_err(|_| Error::auth("Failed to write session"))?;
+            // Signed in while the stored session was being checked
+            if session_guard.is_some() {
+                debug!("Keeping the current session over the stored one");
+                return Ok(None);
+            }
+            *session_guard = Some(session.clone());
+        }
+        self.set_stored_session_id(Some(session_id));
+
+        self.trigger_auth_event(AuthEvent::SignedIn);
+        info!("Restored stored session");
+        Ok(Some(session))
+    }
+
+    /// Check a stored session with the server, refreshing it once the access token expired
+    ///
+    /// Returns the session and whether it was refreshed, or `None` when the
+    /// server rejects it.
+    #[cfg(feature = "session-management")]
+    async fn verify_stored_session(&self, mut session: Session) -> Result<Option<(Session, bool)>> {
+        if session.expires_at > Utc::now() {
+            let response = self
+                .http_client
+                .get(format!("{}/user", self.auth_url()))
+                .header("Authorization", format!("Bearer {}", session.access_token))
+                .send_tracked(&self.rate_limit, &self.circuit)
+                .await?;
+            match response.status().as_u16() {
+                200..=299 => {
+                    session.user = response.json().await?;
+                    return Ok(Some((session, false)));
+                }
+                401 | 403 => debug!("Stored access token was rejected; refreshing it"),
+                status => {
+                    return Err(Error::auth(format!(
+                        "Failed to verify stored session: HTTP {}",
+                        status
+                    )))
+                }
+            }
+        }
+
+        if session.refresh_token.is_empty() {
+            return Ok(None);
+        }
+        match self.request_refreshed_session(&session).await {
+            Ok(AuthResponse {
+                session: Some(session),
+                ..
+            }) => Ok(Some((session, true))),
+            Ok(_) => Err(Error::auth("Token refresh returned no session")),
+            Err(err) if matches!(err.status_code(), Some(400 | 401 | 403)) => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Start [`restore_stored_session`](Self::restore_stored_session) without waiting for it
+    #[cfg(feature = "session-management")]
+    fn restore_stored_session_in_background(&self) {
+        #[cfg(all(not(target_arch = "wasm32"), feature = "native"))]
+        if tokio::runtime::Handle::try_current().is_err() {
+            debug!("No async runtime; call restore_stored_session() to restore the session");
+            return;
+        }
+
+        // Share listeners so SignedIn reaches this instance's subscribers
+        let auth = Self {
+            event_listeners: self.event_listeners.clone(),
+            ..self.clone()
+        };
+        spawn_background(async move {
+            if let Err(err) = auth.restore_stored_session().await {
+                warn!("Failed to restore stored session: {}", err);
+            }
+        });
+    }
+
+    #[cfg(feature = "session-management")]
+    fn session_persistence(&self) -> Option<SessionPersistence> {
+        self.session_persistence
+            .read()
+            .ok()
+            .and_then(|guard| guard.clone())
+    }
+
+    #[cfg(feature = "session-management")]
+    fn set_stored_session_id(&self, session_id: Option<Uuid>) {
+        if let Ok(mut guard) = self.session_persistence.write() {
+            if let Some(persistence) = guard.as_mut() {
+                persistence.session_id = session_id;
+            }
+        }
+    }
+
+    /// Write `session` through the session manager, if there is one
+    #[cfg(feature = "session-management")]
+    async fn store_session(&self, session: &Session) {
+        let Some(persistence) = self.session_persistence() else {
+            return;
+        };
+
+        let result = match persistence.session_id {
+            Some(session_id) => persistence
+                .manager
+                .update_session(session_id, session.clone())
+                .await
+                .map(|()| session_id),
+            None => persistence.manager.store_session(session.clone()).await,
+        };
+        match result {
+            Ok(session_id) => self.set_stored_session_id(Some(session_id)),
+            Err(err) => warn!("Failed to store session: {}", err),
+        }
+    }
+
+    /// Remove the stored session of this client, if there is one
+    #[cfg(feature = "session-management")]
+    async fn remove_stored_session(&self) {
+        let Some(SessionPersistence {
+            manager,
+            session_id: Some(session_id),
+            ..
+        }) = self.session_persistence()
+        else {
+            return;
+        };
+
+        self.set_stored_session_id(None);
+        if let Err(err) = manager
+            .remove_session(session_id, "signed out".to_string())
+            .await
+        {
+            warn!("Failed to remove stored session: {}", err);
+        }
+    }
+
+    /// Customize how sessions are stored when `persist_session` is enabled
+    ///
+    /// On WASM builds the session already restored from localStorage is
+    /// reloaded through the new serializer.
+    pub fn set_session_serializer(&self, serializer: Arc<dyn SessionSerializer>) {
+        if let Ok(mut guard) = self.session_serializer.write() {
+            *guard = Some(serializer);
+        }
+
+        #[cfg(all(target_arch = "wasm32", feature = "wasm"))]
+        if self.persists_to_local_storage() {
+            self.load_persisted_session();
+        }
+    }
+
+    /// Apply the session serializer before a session is persisted
+    #[cfg_attr(not(all(target_arch = "wasm32", feature = "wasm")), allow(dead_code))]
+    fn encode_session(&self, session: &Session) -> Result<Session> {
+        match self.session_serializer() {
+            Some(serializer) => serializer.encode(session),
+            None => Ok(session.clone()),
+        }
+    }
+
+    /// Apply the session serializer to a session loaded from storage
+    #[cfg_attr(not(all(target_arch = "wasm32", feature = "wasm")), allow(dead_code))]
+    fn decode_session(&self, stored: Session) -> Result<Session> {
+        match self.session_serializer() {
+            Some(serializer) => serializer.decode(stored),
+            None => Ok(stored),
+        }
+    }
+
+    /// Auth (GoTrue) endpoint of the project
+    fn auth_url(&self) -> String {
+        self.config.project_url().auth_url()
+    }
+
+    fn session_serializer(&self) -> Option<Arc<dyn SessionSerializer>> {
+        self.session_serializer
+            .read()
+            .ok()
+            .and_then(|guard| guard.clone())
+    }
+
+    /// Whether the session is kept under `storage_key` in localStorage
+    ///
+    /// With a session manager, e.g. from `persist_sessions`, the manager
+    /// stores the session and syncs sign-outs across tabs instead.
+    #[cfg(all(target_arch = "wasm32", feature = "wasm"))]
+    fn persists_to_local_storage(&self) -> bool {
+        #[cfg(feature = "session-management")]
+        if self.session_persistence().is_some() {
+            return false;
+        }
+        self.config.auth_config.persist_session
+    }
+
+    /// Restore the session saved in localStorage and follow sign-outs from other tabs
+    #[cfg(all(target_arch = "wasm32", feature = "wasm"))]
+    fn restore_persisted_session(&self) {
+        self.load_persisted_session();
+
+        let storage_key = &self.config.auth_config.storage_key;
+        let session = self.session.clone();
+        let event_listeners = self.event_listeners.clone();
+        browser_persistence::watch_sign_out(storage_key, move || {
+            let signed_in = session
+                .write()
+                .map(|mut guard| guard.take().is_some())
+                .unwrap_or(false);
+
+            if signed_in {
+                debug!("Session cleared in another tab, signing out");
+                Self::notify_listeners(&session, &event_listeners, AuthEvent::SignedOut);
+            }
+        });
+    }
+
+    /// Load the session saved in localStorage, dropping expired or undecodable ones
+    #[cfg(all(target_arch = "wasm32", feature = "wasm"))]
+    fn load_persisted_session(&self) {
+        let storage_key = &self.config.auth_config.storage_key;
+
+        let Some(stored) = browser_persistence::load_session(storage_key) else {
+            return;
+        };
+
+        match self.decode_session(stored) {
+            Ok(session) if session.expires_at > Utc::now() => {
+                if let Ok(mut session_guard) = self.session.write() {
+                    *session_guard = Some(session);
+                    debug!("Restored persisted session from localStorage");
+                }
+            }
+            Ok(_) => browser_persistence::remove_session(storage_key),
+            Err(err) => {
+                warn!("Failed to decode persisted session: {}", err);
+                browser_persistence::remove_session(storage_key);
+            }
+        }
+    }
+
+    /// Sign up a new user with email and password
+    pub async fn sign_up_with_email_and_password(
+        &self,
+        email: &str,
+        password: &str,
+    ) -> Result<AuthResponse> {
+        self.sign_up_with_email_password_and_data(email, password, None, None)
+            .await
+    }
+
+    /// Sign up a new user with email, password, and optional metadata
+    pub async fn sign_up_with_email_password_and_data(
+        &self,
+        email: &str,
+        password: &str,
+        data: Option<serde_json::Value>,
+        redirect_to: Option<String>,
+    ) -> Result<AuthResponse> {
+        debug!("Signing up user with email: {}", email);
+
+        let payload = SignUpRequest {
+            email: email.to_string(),
+            password: password.to_string(),
+            data,
+            redirect_to,
+        };
+
+        let response = self
+            .http_client
+            .post(format!("{}/signup", self.auth_url()))
+            .json(&payload)
+            .send_tracked(&self.rate_limit, &self.circuit)
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_msg = match response.text().await {
+                Ok(text) => text,
+                Err(_) => format!("Sign up failed with status: {}", status),
+            };
+            return Err(Error::auth(error_msg).with_fingerprint(self.config.error_fingerprint));
+        }
+
+        let auth_response_body = response.text().await?;
+
+        let mut auth_response = serde_json::from_str::<AuthResponse>(auth_response_body.as_str())?;
+        auth_response.session = serde_json::from_str::<Session>(auth_response_body.as_str())
+            .inspect_err(|err| warn!("No session: {}", err.to_string()))
+            .ok();
+
+        if let Some(ref session) = auth_response.session {
+            self.set_session(session.clone()).await?;
+            self.trigger_auth_event(AuthEvent::SignedIn);
+            info!("User signed up successfully");
+        }
+
+        Ok(auth_response)
+    }
+
+    /// Sign in with email and password
+    ///
+    /// Users with MFA enabled are signed in at `aal1`; use
+    /// [`sign_in_with_email_and_password_mfa`](Self::sign_in_with_email_and_password_mfa)
+    /// to find out whether a challenge is still required.
+    pub async fn sign_in_with_email_and_password(
+        &self,
+        email: &str,
+        password: &str,
+    ) -> Result<AuthResponse> {
+        Ok(self.password_sign_in(email, password).await?.0)
+    }
+
+    /// Sign in with email and password, reporting when MFA is required
+    ///
+    /// The `aal1` session is stored either way, so the factors can be
+    /// challenged right away.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use supabase_lib_rs::auth::SignInResult;
+    /// # use supabase_lib_rs::Client;
+    /// # async fn example(code: &str) -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = Client::new("https://example.supabase.co", "your-anon-key")?;
+    ///
+    /// match client
+    ///     .auth()
+    ///     .sign_in_with_email_and_password_mfa("user@example.com", "password")
+    ///     .await?
+    /// {
+    ///     SignInResult::SignedIn(_) => println!("Signed in"),
+    ///     SignInResult::MfaChallengeRequired { factors, .. } => {
+    ///         // Ask the user for the code of their authenticator app
+    ///         if let Some(factor) = factors.first() {
+    ///             client.auth().complete_mfa_sign_in(factor.id, code).await?;
+    ///         }
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn sign_in_with_email_and_password_mfa(
+        &self,
+        email: &str,
+        password: &str,
+    ) -> Result<SignInResult> {
+        let (response, user) = self.password_sign_in(email, password).await?;
+        let level = response
+            .session
+            .as_ref()
+            .and_then(|session| token_assurance(&session.access_token).0);
+
+        if !has_verified_factor(&user) || level == Some(AssuranceLevel::Aal2) {
+            return Ok(SignInResult::SignedIn(response));
+        }
+
+        info!("Sign in requires an MFA challenge");
+        Ok(SignInResult::MfaChallengeRequired {
+            response,
+            factors: verified_factors(&user),
+        })
+    }
+
+    /// Password sign-in returning the raw user object as well
+    async fn password_sign_in(
+        &self,
+        email: &str,
+        password: &str,
+    ) -> Result<(AuthResponse, serde_json::Value)> {
+        debug!("Signing in user with email: {}", email);
+
+        let payload = SignInRequest {
+            email: email.to_string(),
+            password: password.to_string(),
+        };
+
+        let response = self
+            .http_client
+            .post(format!("{}/token?grant_type=password", self.auth_url()))
+            .json(&payload)
+            .send_tracked(&self.rate_limit, &self.circuit)
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_msg = match response.text().await {
+                Ok(text) => text,
+                Err(_) => format!("Sign in failed with status: {}", status),
+            };
+            return Err(Error::auth(error_msg).with_fingerprint(self.config.error_fingerprint));
+        }
+
+        let auth_response_body = response.text().await?;
+
+        let mut auth_response = serde_json::from_str::<AuthResponse>(auth_response_body.as_str())?;
+        auth_response.session = serde_json::from_str::<Session>(auth_response_body.as_str())
+            .inspect_err(|err| warn!("No session: {}", err.to_string()))
+            .ok();
+
+        if let Some(ref session) = auth_response.session {
+            self.set_session(session.clone()).await?;
+            self.trigger_auth_event(AuthEvent::SignedIn);
+            info!("User signed in successfully");
+        }
+
+        let mut body: serde_json::Value = serde_json::from_str(&auth_response_body)?;
+        Ok((auth_response, body["user"].take()))
+    }
+
+    /// Sign in with a password and either an email address or a phone number
+    ///
+    /// For login forms with a single identifier field; see
+    /// [`LoginIdentifier::detect`] for how the two are told apart.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # async fn example() -> supabase_lib_rs::Result<()> {
+    /// let client = supabase_lib_rs::Client::new("https://example.supabase.co", "key")?;
+    ///
+    /// client.auth().sign_in_with_password("user@example.com", "password").await?;
+    /// client.auth().sign_in_with_password("+1 555 123 4567", "password").await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn sign_in_with_password(
+        &self,
+        identifier: &str,
+        password: &str,
+    ) -> Result<AuthResponse> {
+        match LoginIdentifier::detect(identifier)? {
+            LoginIdentifier::Email(email) => {
+                self.sign_in_with_email_and_password(&email, password).await
+            }
+            LoginIdentifier::Phone(phone) => self.sign_in_with_phone(&phone, password).await,
+        }
+    }
+
+    /// Sign out the current user
+    pub async fn sign_out(&self) -> Result<()> {
+        debug!("Signing out user");
+
+        let session = self.get_session()?;
+
+        let response = self
+            .http_client
+            .post(format!("{}/logout", self.auth_url()))
+            .header("Authorization", format!("Bearer {}", session.access_token))
+            .send_tracked(&self.rate_limit, &self.circuit)
+            .await?;
+
+        if !response.status().is_success() {
+            warn!("Sign out request failed with status: {}", response.status());
+        }
+
+        self.clear_session().await?;
+        self.trigger_auth_event(AuthEvent::SignedOut);
+        info!("User signed out successfully");
+
+        Ok(())
+    }
+
+    /// Reset password via email
+    pub async fn reset_password_for_email(&self, email: &str) -> Result<()> {
+        self.reset_password_for_email_with_redirect(email, None)
+            .await
+    }
+
+    /// Reset password via email with optional redirect URL
+    pub async fn reset_password_for_email_with_redirect(
+        &self,
+        email: &str,
+        redirect_to: Option<String>,
+    ) -> Result<()> {
+        debug!("Requesting password reset for email: {}", email);
+
+        let payload = PasswordResetRequest {
+            email: email.to_string(),
+            redirect_to,
+        };
+
+        let response = self
+            .http_client
+            .post(format!("{}/recover", self.auth_url()))
+            .json(&payload)
+            .send_tracked(&self.rate_limit, &self.circuit)
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_msg = match response.text().await {
+                Ok(text) => text,
+                Err(_) => format!("Password reset failed with status: {}", status),
+            };
+            return Err(Error::auth(error_msg).with_fingerprint(self.config.error_fingerprint));
+        }
+
+        info!("Password reset email sent successfully");
+        Ok(())
+    }
+
+    /// Update the current user's information
+    pub async fn update_user(
+        &self,
+        email: Option<String>,
+        password: Option<String>,
+        data: Option<serde_json::Value>,
+    ) -> Result<AuthResponse> {
+        debug!("Updating user information");
 
-        let payload = SignUpRequest {
-            email: email.to_string(),
-            password: password.to_string(),
+        let session = self.get_session()?;
+
+        let payload = UpdateUserRequest {
+            email,
+            password,
             data,
-            redirect_to,
         };
 
         let response = self
             .http_client
-            .post(format!("{}/auth/v1/signup", self.config.url))
+            .put(format!("{}/user", self.auth_url()))
+            .header("Authorization", format!("Bearer {}", session.access_token))
             .json(&payload)
-            .send()
+            .send_tracked(&self.rate_limit, &self.circuit)
             .await?;
 
         if !response.status().is_success() {
             let status = response.status();
             let error_msg = match response.text().await {
                 Ok(text) => text,
-                Err(_) => format!("Sign up failed with status: {}", status),
+                Err(_) => format!("User update failed with status: {}", status),
             };
-            return Err(Error::auth(error_msg));
+            return Err(Error::auth(error_msg).with_fingerprint(self.config.error_fingerprint));
         }
 
-        let auth_response_body = response.text().await?;
-
-        let mut auth_response = serde_json::from_str::<AuthResponse>(auth_response_body.as_str())?;
-        auth_response.session = serde_json::from_str::<Session>(auth_response_body.as_str())
-            .inspect_err(|err| warn!("No session: {}", err.to_string()))
-            .ok();
+        let auth_response: AuthResponse = response.json().await?;
 
         if let Some(ref session) = auth_response.session {
             self.set_session(session.clone()).await?;
-            self.trigger_auth_event(AuthEvent::SignedIn);
-            info!("User signed up successfully");
         }
 
+        info!("User updated successfully");
         Ok(auth_response)
     }
 
-    /// Sign in with email and password
-    pub async fn sign_in_with_email_and_password(
-        &self,
-        email: &str,
-        password: &str,
-    ) -> Result<AuthResponse> {
-        debug!("Signing in user with email: {}", email);
+    /// Refresh the current session token
+    ///
+    /// Concurrent callers share a single refresh: the first one performs the
+    /// request while the others wait and receive its result.
+    pub async fn refresh_session(&self) -> Result<AuthResponse> {
+        debug!("Refreshing session token");
 
-        let payload = SignInRequest {
-            email: email.to_string(),
-            password: password.to_string(),
+        self.gated_refresh(
+            |session| self.refresh_session_request(session),
+            |session| AuthResponse {
+                user: Some(session.user.clone()),
+                session: Some(session),
+            },
+        )
+        .await
+    }
+
+    /// Send the refresh token request and store the new session
+    async fn refresh_session_request(&self, current_session: Session) -> Result<AuthResponse> {
+        let auth_response = self.request_refreshed_session(&current_session).await?;
+
+        if let Some(ref session) = auth_response.session {
+            self.set_session(session.clone()).await?;
+            self.trigger_auth_event(AuthEvent::TokenRefreshed);
+            info!("Session refreshed successfully");
+        }
+
+        Ok(auth_response)
+    }
+
+    /// Exchange the refresh token of `current_session` for a new session
+    async fn request_refreshed_session(&self, current_session: &Session) -> Result<AuthResponse> {
+        let payload = RefreshTokenRequest {
+            refresh_token: current_session.refresh_token.clone(),
         };
 
         let response = self
             .http_client
             .post(format!(
-                "{}/auth/v1/token?grant_type=password",
-                self.config.url
+                "{}/token?grant_type=refresh_token",
+                self.auth_url()
             ))
             .json(&payload)
-            .send()
+            .send_tracked(&self.rate_limit, &self.circuit)
             .await?;
 
         if !response.status().is_success() {
             let status = response.status();
             let error_msg = match response.text().await {
                 Ok(text) => text,
-                Err(_) => format!("Sign in failed with status: {}", status),
+                Err(_) => format!("Token refresh failed with status: {}", status),
+            };
+            let context = crate::error::ErrorContext {
+                http: Some(crate::error::HttpErrorContext {
+                    status_code: Some(status.as_u16()),
+                    headers: None,
+                    response_body: None,
+                    url: Some(format!("{}/token", self.auth_url())),
+                    method: Some("POST".to_string()),
+                }),
+                ..Default::default()
             };
-            return Err(Error::auth(error_msg));
+            return Err(Error::auth_with_context(error_msg, context)
+                .with_fingerprint(self.config.error_fingerprint));
         }
 
         let auth_response_body = response.text().await?;
@@ -542,151 +2337,595 @@ impl Auth {
         let mut auth_response = serde_json::from_str::<AuthResponse>(auth_response_body.as_str())?;
         auth_response.session = serde_json::from_str::<Session>(auth_response_body.as_str())
             .inspect_err(|err| warn!("No session: {}", err.to_string()))
-            .ok();
+            .ok()
+            .map(|mut session| {
+                session.inherit_provider_tokens(current_session);
+                session
+            });
 
-        if let Some(ref session) = auth_response.session {
-            self.set_session(session.clone()).await?;
-            self.trigger_auth_event(AuthEvent::SignedIn);
-            info!("User signed in successfully");
+        Ok(auth_response)
+    }
+
+    /// Run a token refresh so that only one executes at a time
+    ///
+    /// Callers that waited while another refresh ran get the session it stored,
+    /// or the error it failed with, instead of refreshing again. When the server
+    /// rejects the refresh token the session is cleared and `SignedOut` is
+    /// emitted once.
+    async fn gated_refresh<'a, T, F, Fut>(
+        &'a self,
+        refresh: F,
+        from_session: impl FnOnce(Session) -> T,
+    ) -> Result<T>
+    where
+        F: FnOnce(Session) -> Fut,
+        Fut: std::future::Future<Output = Result<T>> + 'a,
+    {
+        let observed_token = self
+            .session
+            .read()
+            .map_err(|_| Error::auth("Failed to read session"))?
+            .as_ref()
+            .map(|session| session.refresh_token.clone())
+            .ok_or_else(|| Error::auth("No active session to refresh"))?;
+
+        let observed_attempt = self.refresh_attempts.load(Ordering::SeqCst);
+        let mut last_failure = self.refresh_gate.lock().await;
+
+        // A failure recorded after we started waiting belongs to our refresh too
+        let failed_while_waiting = last_failure
+            .as_ref()
+            .filter(|failure| failure.attempt > observed_attempt)
+            .map(|failure| Error::auth(failure.message.clone()));
+
+        let current_session = self
+            .session
+            .read()
+            .map_err(|_| Error::auth("Failed to read session"))?
+            .clone();
+
+        let session = match current_session {
+            Some(session) if session.refresh_token != observed_token => {
+                debug!("Session already refreshed by a concurrent caller");
+                return Ok(from_session(session));
+            }
+            Some(session) => session,
+            None => {
+                return Err(failed_while_waiting
+                    .unwrap_or_else(|| Error::auth("Session was cleared during token refresh")));
+            }
+        };
+
+        if let Some(error) = failed_while_waiting {
+            return Err(error);
         }
 
-        Ok(auth_response)
+        let attempt = self.refresh_attempts.fetch_add(1, Ordering::SeqCst) + 1;
+        match refresh(session).await {
+            Ok(result) => {
+                *last_failure = None;
+                Ok(result)
+            }
+            Err(e) => {
+                *last_failure = Some(FailedRefresh {
+                    attempt,
+                    message: e.to_string(),
+                });
+
+                if matches!(e.status_code(), Some(400 | 401 | 403)) {
+                    warn!("Refresh token rejected, signing out");
+                    self.clear_session().await?;
+                    self.trigger_auth_event(AuthEvent::SignedOut);
+                }
+
+                Err(e)
+            }
+        }
     }
 
-    /// Sign out the current user
-    pub async fn sign_out(&self) -> Result<()> {
-        debug!("Signing out user");
+    /// Get the current user information
+    pub async fn current_user(&self) -> Result<Option<User>> {
+        let session_guard = self
+            .session
+            .read()
+            .map_err(|_| Error::auth("Failed to read session"))?;
+        Ok(session_guard.as_ref().map(|s| s.user.clone()))
+    }
+
+    /// Access token source following this client's session
+    ///
+    /// [`Client`](crate::Client) hands it to the other modules so their
+    /// requests are made as the signed-in user.
+    pub fn token_provider(&self) -> Arc<dyn TokenProvider> {
+        Arc::new(SessionTokenProvider(Arc::clone(&self.session)))
+    }
+
+    /// Rate limit headers of the last auth response that sent any
+    ///
+    /// Lets apps back off before sending more OTPs or emails instead of
+    /// running into `429` errors, which carry the same information in
+    /// [`Error::rate_limit_info`].
+    pub fn last_rate_limit(&self) -> Option<RateLimitInfo> {
+        self.rate_limit.read().ok().and_then(|guard| guard.clone())
+    }
+
+    /// Admin API, authenticated with the configured service role key
+    ///
+    /// Requests fail with a configuration error when no service role key is
+    /// set. Never ship the service role key to untrusted clients.
+    #[cfg(feature = "admin")]
+    pub fn admin(&self) -> admin::AuthAdmin {
+        admin::AuthAdmin::new(
+            Arc::clone(&self.config),
+            Arc::clone(&self.http_client),
+            Arc::clone(&self.rate_limit),
+            self.circuit.clone(),
+        )
+    }
+
+    /// Get the current session
+    pub fn get_session(&self) -> Result<Session> {
+        let session_guard = self
+            .session
+            .read()
+            .map_err(|_| Error::auth("Failed to read session"))?;
+        session_guard
+            .as_ref()
+            .cloned()
+            .ok_or_else(|| Error::auth("No active session"))
+    }
+
+    /// Get the OAuth provider access token of the current session
+    ///
+    /// Present when the session was established through an OAuth provider that
+    /// returned one; use it to call the provider's API on behalf of the user.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # fn example() -> supabase_lib_rs::Result<()> {
+    /// let client = supabase_lib_rs::Client::new("https://example.supabase.co", "key")?;
+    ///
+    /// if let Some(token) = client.auth().provider_token() {
+    ///     // e.g. call https://api.github.com/user with `Bearer {token}`
+    ///     println!("Provider token available ({} chars)", token.len());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn provider_token(&self) -> Option<String> {
+        self.session.read().ok()?.as_ref()?.provider_token.clone()
+    }
+
+    /// Get the OAuth provider refresh token of the current session
+    pub fn provider_refresh_token(&self) -> Option<String> {
+        self.session
+            .read()
+            .ok()?
+            .as_ref()?
+            .provider_refresh_token
+            .clone()
+    }
+
+    /// Choose the Edge Function [`refresh_provider_token`](Self::refresh_provider_token) calls
+    ///
+    /// Defaults to `refresh-provider-token`. Not used once a refresher is set
+    /// with [`on_provider_token_refresh`](Self::on_provider_token_refresh).
+    pub fn set_provider_refresh_function(&self, function: &str) {
+        if let Ok(mut refresh) = self.provider_refresh.write() {
+            refresh.function = function.to_string();
+        }
+    }
+
+    /// Refresh provider tokens with `refresher` instead of the Edge Function
+    ///
+    /// The refresher receives the provider and the current provider refresh
+    /// token. Setting a refresher replaces the previous one.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # async fn example() -> supabase_lib_rs::Result<()> {
+    /// use supabase_lib_rs::auth::ProviderTokens;
+    ///
+    /// let client = supabase_lib_rs::Client::new("url", "key")?;
+    ///
+    /// client.auth().on_provider_token_refresh(|provider, refresh_token| async move {
+    ///     // Call the provider's token endpoint through your backend
+    ///     println!("Refreshing {} token", provider.as_str());
+    ///     Ok(ProviderTokens {
+    ///         provider_token: format!("new-token-for-{}", refresh_token),
+    ///         provider_refresh_token: None,
+    ///     })
+    /// });
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn on_provider_token_refresh<F, Fut>(&self, refresher: F)
+    where
+        F: Fn(OAuthProvider, String) -> Fut + Send + Sync + 'static,
+        Fut: std::future::Future<Output = Result<ProviderTokens>> + Send + 'static,
+    {
+        self.set_provider_token_refresher(Arc::new(move |provider, refresh_token| {
+            Box::pin(refresher(provider, refresh_token))
+        }));
+    }
+
+    /// Refresh provider tokens with `refresher` (WASM version)
+    #[cfg(target_arch = "wasm32")]
+    pub fn on_provider_token_refresh<F, Fut>(&self, refresher: F)
+    where
+        F: Fn(OAuthProvider, String) -> Fut + Send + Sync + 'static,
+        Fut: std::future::Future<Output = Result<ProviderTokens>> + 'static,
+    {
+        self.set_provider_token_refresher(Arc::new(move |provider, refresh_token| {
+            Box::pin(refresher(provider, refresh_token))
+        }));
+    }
+
+    fn set_provider_token_refresher(&self, refresher: ProviderTokenRefresher) {
+        if let Ok(mut refresh) = self.provider_refresh.write() {
+            refresh.refresher = Some(refresher);
+        }
+    }
 
+    /// Refresh the OAuth provider token of the current session
+    ///
+    /// Supabase Auth does not refresh provider tokens, and doing so needs the
+    /// provider's client secret. The session's provider refresh token is
+    /// therefore exchanged by the refresher set with
+    /// [`on_provider_token_refresh`](Self::on_provider_token_refresh) or, by
+    /// default, by an Edge Function called with the user's access token and a
+    /// JSON body `{"provider": "...", "provider_refresh_token": "..."}`,
+    /// answering with [`ProviderTokens`].
+    ///
+    /// `provider` must be the provider that issued the session's provider
+    /// token, which this client records in [`Session::provider`] when it
+    /// completes the OAuth sign-in. The Edge Function is reached on the
+    /// Functions dev base URL when one is configured.
+    ///
+    /// The new tokens are stored in the session, so [`provider_token`](Self::provider_token)
+    /// returns the refreshed token afterwards.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # async fn example() -> supabase_lib_rs::Result<()> {
+    /// use supabase_lib_rs::auth::OAuthProvider;
+    ///
+    /// let client = supabase_lib_rs::Client::new("url", "key")?;
+    ///
+    /// let tokens = client.auth().refresh_provider_token(OAuthProvider::Google).await?;
+    /// println!("Fresh Google token ({} chars)", tokens.provider_token.len());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn refresh_provider_token(&self, provider: OAuthProvider) -> Result<ProviderTokens> {
         let session = self.get_session()?;
+        match session.provider.as_deref() {
+            Some(issuer) if issuer == provider.as_str() => {}
+            Some(issuer) => {
+                return Err(Error::invalid_input(format!(
+                    "Provider token was issued by {}, not {}",
+                    issuer,
+                    provider.as_str()
+                )));
+            }
+            None => {
+                return Err(Error::auth(
+                    "Session does not record which provider issued its provider token",
+                ));
+            }
+        }
+        let refresh_token = session.provider_refresh_token.clone().ok_or_else(|| {
+            Error::auth(format!(
+                "Session has no {} refresh token",
+                provider.as_str()
+            ))
+        })?;
 
-        let response = self
-            .http_client
-            .post(format!("{}/auth/v1/logout", self.config.url))
-            .header("Authorization", format!("Bearer {}", session.access_token))
-            .send()
-            .await?;
+        let settings = self
+            .provider_refresh
+            .read()
+            .map_err(|_| Error::auth("Failed to read provider refresh settings"))?
+            .clone();
 
-        if !response.status().is_success() {
-            warn!("Sign out request failed with status: {}", response.status());
+        debug!("Refreshing {} provider token", provider.as_str());
+        let tokens = match settings.refresher {
+            Some(refresher) => refresher(provider.clone(), refresh_token).await?,
+            None => {
+                let response = self
+                    .http_client
+                    .post(format!(
+                        "{}/{}",
+                        self.config.functions_url(),
+                        settings.function
+                    ))
+                    .header("Authorization", format!("Bearer {}", session.access_token))
+                    .json(&serde_json::json!({
+                        "provider": provider.as_str(),
+                        "provider_refresh_token": refresh_token,
+                    }))
+                    .send_tracked(&self.rate_limit, &self.circuit)
+                    .await?;
+
+                if !response.status().is_success() {
+                    let status = response.status();
+                    let error_msg = match response.text().await {
+                        Ok(text) if !text.is_empty() => text,
+                        _ => format!("Provider token refresh failed with status: {}", status),
+                    };
+                    return Err(
+                        Error::auth(error_msg).with_fingerprint(self.config.error_fingerprint)
+                    );
+                }
+
+                response.json().await?
+            }
+        };
+
+        // The session may have been refreshed meanwhile; update the current one
+        let mut current = self.get_session()?;
+        current.provider_token = Some(tokens.provider_token.clone());
+        if let Some(refresh_token) = &tokens.provider_refresh_token {
+            current.provider_refresh_token = Some(refresh_token.clone());
         }
+        self.set_session(current).await?;
+        info!("Refreshed {} provider token", provider.as_str());
 
-        self.clear_session().await?;
-        self.trigger_auth_event(AuthEvent::SignedOut);
-        info!("User signed out successfully");
+        Ok(tokens)
+    }
 
+    /// Set a new session
+    ///
+    /// Does nothing on service role clients, which keep no user session.
+    pub async fn set_session(&self, session: Session) -> Result<()> {
+        if self.config.is_service_role() {
+            debug!("Service role client keeps no user session; not storing it");
+            return Ok(());
+        }
+
+        #[cfg(all(target_arch = "wasm32", feature = "wasm"))]
+        if self.persists_to_local_storage() {
+            match self.encode_session(&session) {
+                Ok(stored) => {
+                    browser_persistence::save_session(&self.config.auth_config.storage_key, &stored)
+                }
+                Err(err) => warn!("Failed to encode session for storage: {}", err),
+            }
+        }
+
+        #[cfg(feature = "session-management")]
+        let stored = session.clone();
+        *self
+            .session
+            .write()
+            .map_err(|_| Error::auth("Failed to write session"))? = Some(session);
+
+        #[cfg(feature = "session-management")]
+        self.store_session(&stored).await;
         Ok(())
     }
 
-    /// Reset password via email
-    pub async fn reset_password_for_email(&self, email: &str) -> Result<()> {
-        self.reset_password_for_email_with_redirect(email, None)
-            .await
-    }
+    /// Set session from JWT token
+    pub async fn set_session_token(&self, token: &str) -> Result<()> {
+        debug!("Setting session from token");
 
-    /// Reset password via email with optional redirect URL
-    pub async fn reset_password_for_email_with_redirect(
-        &self,
-        email: &str,
-        redirect_to: Option<String>,
-    ) -> Result<()> {
-        debug!("Requesting password reset for email: {}", email);
+        let user_response = self
+            .http_client
+            .get(format!("{}/user", self.auth_url()))
+            .header("Authorization", format!("Bearer {}", token))
+            .send_tracked(&self.rate_limit, &self.circuit)
+            .await?;
 
-        let payload = PasswordResetRequest {
-            email: email.to_string(),
-            redirect_to,
+        if !user_response.status().is_success() {
+            return Err(
+                Error::auth("Invalid token").with_fingerprint(self.config.error_fingerprint)
+            );
+        }
+
+        let user: User = user_response.json().await?;
+
+        let session = Session {
+            access_token: token.to_string(),
+            refresh_token: String::new(),
+            expires_in: 3600,
+            expires_at: Utc::now() + chrono::Duration::seconds(3600),
+            token_type: "bearer".to_string(),
+            user,
+            provider_token: None,
+            provider_refresh_token: None,
+            provider: None,
         };
 
-        let response = self
-            .http_client
-            .post(format!("{}/auth/v1/recover", self.config.url))
-            .json(&payload)
-            .send()
-            .await?;
+        self.set_session(session).await?;
+        Ok(())
+    }
+
+    /// Clear the current session
+    pub async fn clear_session(&self) -> Result<()> {
+        *self
+            .session
+            .write()
+            .map_err(|_| Error::auth("Failed to write session"))? = None;
 
-        if !response.status().is_success() {
-            let status = response.status();
-            let error_msg = match response.text().await {
-                Ok(text) => text,
-                Err(_) => format!("Password reset failed with status: {}", status),
-            };
-            return Err(Error::auth(error_msg));
+        #[cfg(all(target_arch = "wasm32", feature = "wasm"))]
+        if self.persists_to_local_storage() {
+            browser_persistence::remove_session(&self.config.auth_config.storage_key);
         }
 
-        info!("Password reset email sent successfully");
+        #[cfg(feature = "session-management")]
+        self.remove_stored_session().await;
         Ok(())
     }
 
-    /// Update the current user's information
-    pub async fn update_user(
-        &self,
-        email: Option<String>,
-        password: Option<String>,
-        data: Option<serde_json::Value>,
-    ) -> Result<AuthResponse> {
-        debug!("Updating user information");
+    /// Check if the user is authenticated
+    pub fn is_authenticated(&self) -> bool {
+        let session_guard = self.session.read().unwrap_or_else(|_| {
+            warn!("Failed to read session lock");
+            self.session.read().unwrap()
+        });
 
-        let session = self.get_session()?;
+        match session_guard.as_ref() {
+            Some(session) => {
+                let now = Utc::now();
+                session.expires_at > now
+            }
+            None => false,
+        }
+    }
 
-        let payload = UpdateUserRequest {
-            email,
-            password,
-            data,
+    /// Check if the current token needs refresh
+    pub fn needs_refresh(&self) -> bool {
+        let session_guard = match self.session.read() {
+            Ok(guard) => guard,
+            Err(_) => {
+                warn!("Failed to read session lock");
+                return false;
+            }
         };
 
-        let response = self
-            .http_client
-            .put(format!("{}/auth/v1/user", self.config.url))
-            .header("Authorization", format!("Bearer {}", session.access_token))
-            .json(&payload)
-            .send()
-            .await?;
-
-        if !response.status().is_success() {
-            let status = response.status();
-            let error_msg = match response.text().await {
-                Ok(text) => text,
-                Err(_) => format!("User update failed with status: {}", status),
-            };
-            return Err(Error::auth(error_msg));
+        match session_guard.as_ref() {
+            Some(session) => {
+                let now = Utc::now();
+                let buffer = chrono::Duration::minutes(5); // Refresh 5 minutes before expiry
+                session.expires_at < (now + buffer)
+            }
+            None => false,
         }
+    }
 
-        let auth_response: AuthResponse = response.json().await?;
+    /// Sign in with OAuth provider
+    ///
+    /// Returns a URL that the user should be redirected to for authentication.
+    /// After successful authentication, the user will be redirected back with the session.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use supabase_lib_rs::auth::{OAuthProvider, OAuthOptions};
+    ///
+    /// # async fn example() -> supabase_lib_rs::Result<()> {
+    /// let client = supabase_lib_rs::Client::new("url", "key")?;
+    ///
+    /// let options = OAuthOptions {
+    ///     redirect_to: Some("https://myapp.com/callback".to_string()),
+    ///     scopes: Some(vec!["email".to_string(), "profile".to_string()]),
+    ///     ..Default::default()
+    /// };
+    ///
+    /// let response = client.auth().sign_in_with_oauth(OAuthProvider::Google, Some(options)).await?;
+    /// println!("Redirect to: {}", response.url);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn sign_in_with_oauth(
+        &self,
+        provider: OAuthProvider,
+        options: Option<OAuthOptions>,
+    ) -> Result<OAuthResponse> {
+        debug!("Initiating OAuth sign-in with provider: {:?}", provider);
+        self.store_pending(
+            "oauth-provider",
+            &self.oauth_provider,
+            Some(provider.as_str().to_string()),
+        );
 
-        if let Some(ref session) = auth_response.session {
-            self.set_session(session.clone()).await?;
+        let mut url = format!(
+            "{}/authorize?provider={}",
+            self.auth_url(),
+            provider.as_str()
+        );
+
+        if let Some(opts) = options {
+            if let Some(redirect_to) = opts.redirect_to {
+                url.push_str(&format!(
+                    "&redirect_to={}",
+                    urlencoding::encode(&redirect_to)
+                ));
+            }
+
+            if let Some(scopes) = opts.scopes {
+                let scope_str = scopes.join(" ");
+                url.push_str(&format!("&scope={}", urlencoding::encode(&scope_str)));
+            }
+
+            if let Some(query_params) = opts.query_params {
+                for (key, value) in query_params {
+                    url.push_str(&format!(
+                        "&{}={}",
+                        urlencoding::encode(&key),
+                        urlencoding::encode(&value)
+                    ));
+                }
+            }
+
+            if opts.pkce {
+                let verifier = pkce_verifier()?;
+                url.push_str(&format!(
+                    "&code_challenge={}&code_challenge_method=s256",
+                    pkce_challenge(&verifier)
+                ));
+                self.store_pending("code-verifier", &self.code_verifier, Some(verifier));
+            }
         }
 
-        info!("User updated successfully");
-        Ok(auth_response)
+        Ok(OAuthResponse { url })
     }
 
-    /// Refresh the current session token
-    pub async fn refresh_session(&self) -> Result<AuthResponse> {
-        debug!("Refreshing session token");
-
-        let current_session = self.get_session()?;
+    /// Complete a PKCE sign-in by exchanging the `code` from the redirect URL
+    ///
+    /// Uses the code verifier saved by [`sign_in_with_oauth`](Self::sign_in_with_oauth)
+    /// with [`OAuthOptions::pkce`] set, and stores the resulting session. On
+    /// WASM with `persist_session` the verifier survives the page reload of
+    /// the redirect in `localStorage`. A verifier is only used once.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use supabase_lib_rs::auth::{OAuthOptions, OAuthProvider};
+    ///
+    /// # async fn example() -> supabase_lib_rs::Result<()> {
+    /// let client = supabase_lib_rs::Client::new("url", "key")?;
+    ///
+    /// let options = OAuthOptions {
+    ///     redirect_to: Some("https://myapp.com/callback".to_string()),
+    ///     pkce: true,
+    ///     ..Default::default()
+    /// };
+    /// let response = client.auth().sign_in_with_oauth(OAuthProvider::GitHub, Some(options)).await?;
+    /// // ...redirect to `response.url`, which comes back to /callback?code=...
+    ///
+    /// let auth_response = client.auth().exchange_code_for_session("code-from-callback").await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn exchange_code_for_session(&self, auth_code: &str) -> Result<AuthResponse> {
+        debug!("Exchanging PKCE auth code for a session");
 
-        let payload = RefreshTokenRequest {
-            refresh_token: current_session.refresh_token.clone(),
-        };
+        let verifier = self
+            .take_pending("code-verifier", &self.code_verifier)
+            .ok_or_else(|| {
+                Error::auth("No PKCE code verifier; start the sign-in with OAuthOptions::pkce set")
+            })?;
 
         let response = self
             .http_client
-            .post(format!(
-                "{}/auth/v1/token?grant_type=refresh_token",
-                self.config.url
-            ))
-            .json(&payload)
-            .send()
+            .post(format!("{}/token?grant_type=pkce", self.auth_url()))
+            .json(&PkceExchangeRequest {
+                auth_code,
+                code_verifier: &verifier,
+            })
+            .send_tracked(&self.rate_limit, &self.circuit)
             .await?;
 
         if !response.status().is_success() {
             let status = response.status();
             let error_msg = match response.text().await {
                 Ok(text) => text,
-                Err(_) => format!("Token refresh failed with status: {}", status),
+                Err(_) => format!("Code exchange failed with status: {}", status),
             };
-            return Err(Error::auth(error_msg));
+            return Err(Error::auth(error_msg).with_fingerprint(self.config.error_fingerprint));
         }
 
         let auth_response_body = response.text().await?;
@@ -696,184 +2935,201 @@ impl Auth {
             .inspect_err(|err| warn!("No session: {}", err.to_string()))
             .ok();
 
-        if let Some(ref session) = auth_response.session {
+        let provider = self.take_pending("oauth-provider", &self.oauth_provider);
+        if let Some(session) = &mut auth_response.session {
+            if session.provider_token.is_some() {
+                session.provider = provider;
+            }
             self.set_session(session.clone()).await?;
-            self.trigger_auth_event(AuthEvent::TokenRefreshed);
-            info!("Session refreshed successfully");
+            self.trigger_auth_event(AuthEvent::SignedIn);
+            info!("PKCE sign-in completed");
         }
 
         Ok(auth_response)
     }
 
-    /// Get the current user information
-    pub async fn current_user(&self) -> Result<Option<User>> {
-        let session_guard = self
-            .session
-            .read()
-            .map_err(|_| Error::auth("Failed to read session"))?;
-        Ok(session_guard.as_ref().map(|s| s.user.clone()))
-    }
-
-    /// Get the current session
-    pub fn get_session(&self) -> Result<Session> {
-        let session_guard = self
-            .session
-            .read()
-            .map_err(|_| Error::auth("Failed to read session"))?;
-        session_guard
-            .as_ref()
-            .cloned()
-            .ok_or_else(|| Error::auth("No active session"))
+    /// State of the OAuth sign-in started last, to finish it in another process
+    ///
+    /// Natively the PKCE verifier and provider of a sign-in only live in this
+    /// client. Apps whose redirect reaches a new process, such as a desktop
+    /// app relaunched through a custom URL scheme, save this state after
+    /// [`sign_in_with_oauth`](Self::sign_in_with_oauth) and restore it with
+    /// [`import_pending_sign_in`](Self::import_pending_sign_in) before
+    /// completing the sign-in. On WASM with `persist_session` it is kept in
+    /// `localStorage` already.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use supabase_lib_rs::auth::{OAuthOptions, OAuthProvider};
+    ///
+    /// # async fn example() -> supabase_lib_rs::Result<()> {
+    /// let client = supabase_lib_rs::Client::new("url", "key")?;
+    ///
+    /// let options = OAuthOptions {
+    ///     pkce: true,
+    ///     ..Default::default()
+    /// };
+    /// client.auth().sign_in_with_oauth(OAuthProvider::GitHub, Some(options)).await?;
+    /// let saved = serde_json::to_string(&client.auth().export_pending_sign_in())?;
+    ///
+    /// // ...in the process that receives the redirect
+    /// let client = supabase_lib_rs::Client::new("url", "key")?;
+    /// client.auth().import_pending_sign_in(serde_json::from_str(&saved)?);
+    /// client.auth().exchange_code_for_session("code-from-callback").await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn export_pending_sign_in(&self) -> PendingSignIn {
+        PendingSignIn {
+            code_verifier: self.peek_pending("code-verifier", &self.code_verifier),
+            provider: self.peek_pending("oauth-provider", &self.oauth_provider),
+        }
     }
 
-    /// Set a new session
-    pub async fn set_session(&self, session: Session) -> Result<()> {
-        let mut session_guard = self
-            .session
-            .write()
-            .map_err(|_| Error::auth("Failed to write session"))?;
-        *session_guard = Some(session);
-        Ok(())
+    /// Restore the state of an OAuth sign-in saved with
+    /// [`export_pending_sign_in`](Self::export_pending_sign_in)
+    pub fn import_pending_sign_in(&self, pending: PendingSignIn) {
+        self.store_pending("code-verifier", &self.code_verifier, pending.code_verifier);
+        self.store_pending("oauth-provider", &self.oauth_provider, pending.provider);
     }
 
-    /// Set session from JWT token
-    pub async fn set_session_token(&self, token: &str) -> Result<()> {
-        debug!("Setting session from token");
-
-        let user_response = self
-            .http_client
-            .get(format!("{}/auth/v1/user", self.config.url))
-            .header("Authorization", format!("Bearer {}", token))
-            .send()
-            .await?;
+    /// Remember a value of a pending OAuth sign-in, or forget it with `None`
+    ///
+    /// `name` keys the value in `localStorage` on WASM with `persist_session`.
+    fn store_pending(&self, name: &str, slot: &RwLock<Option<String>>, value: Option<String>) {
+        #[cfg(all(target_arch = "wasm32", feature = "wasm"))]
+        if self.config.auth_config.persist_session {
+            let key = format!("{}-{}", self.config.auth_config.storage_key, name);
+            match &value {
+                Some(value) => browser_persistence::save_item(&key, value),
+                None => browser_persistence::remove_session(&key),
+            }
+        }
+        #[cfg(not(all(target_arch = "wasm32", feature = "wasm")))]
+        let _ = name;
 
-        if !user_response.status().is_success() {
-            return Err(Error::auth("Invalid token"));
+        if let Ok(mut guard) = slot.write() {
+            *guard = value;
         }
+    }
 
-        let user: User = user_response.json().await?;
+    /// Value of a pending OAuth sign-in, left in place
+    fn peek_pending(&self, name: &str, slot: &RwLock<Option<String>>) -> Option<String> {
+        let value = slot.read().ok().and_then(|v| v.clone());
 
-        let session = Session {
-            access_token: token.to_string(),
-            refresh_token: String::new(),
-            expires_in: 3600,
-            expires_at: Utc::now() + chrono::Duration::seconds(3600),
-            token_type: "bearer".to_string(),
-            user,
-        };
+        #[cfg(all(target_arch = "wasm32", feature = "wasm"))]
+        let value = value.or_else(|| {
+            let key = format!("{}-{}", self.config.auth_config.storage_key, name);
+            browser_persistence::load_item(&key)
+        });
+        #[cfg(not(all(target_arch = "wasm32", feature = "wasm")))]
+        let _ = name;
 
-        self.set_session(session).await?;
-        Ok(())
+        value
     }
 
-    /// Clear the current session
-    pub async fn clear_session(&self) -> Result<()> {
-        let mut session_guard = self
-            .session
-            .write()
-            .map_err(|_| Error::auth("Failed to write session"))?;
-        *session_guard = None;
-        Ok(())
+    /// Take a value of a pending OAuth sign-in, so that it is used only once
+    fn take_pending(&self, name: &str, slot: &RwLock<Option<String>>) -> Option<String> {
+        let value = self.peek_pending(name, slot);
+        self.store_pending(name, slot, None);
+        value
     }
 
-    /// Check if the user is authenticated
-    pub fn is_authenticated(&self) -> bool {
-        let session_guard = self.session.read().unwrap_or_else(|_| {
-            warn!("Failed to read session lock");
-            self.session.read().unwrap()
-        });
+    /// Complete an OAuth sign-in from the URL the provider redirected back to
+    ///
+    /// Reads the tokens from the URL fragment, fetches the user and stores the
+    /// new session. PKCE redirects, which carry a `code` query parameter, are
+    /// completed with [`exchange_code_for_session`](Self::exchange_code_for_session).
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # async fn example() -> supabase_lib_rs::Result<()> {
+    /// let client = supabase_lib_rs::Client::new("url", "key")?;
+    ///
+    /// let callback_url = "https://myapp.com/callback#access_token=...&refresh_token=...";
+    /// let session = client.auth().get_session_from_url(callback_url).await?;
+    /// println!("Signed in as {:?}", session.user.email);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_session_from_url(&self, url: &str) -> Result<Session> {
+        debug!("Completing OAuth sign-in from callback URL");
+
+        // PKCE redirects carry a one-time code instead of tokens
+        let parsed = url::Url::parse(url)?;
+        if let Some((_, code)) = parsed.query_pairs().find(|(key, _)| key == "code") {
+            return self
+                .exchange_code_for_session(&code)
+                .await?
+                .session
+                .ok_or_else(|| Error::auth("Code exchange returned no session"));
+        }
+
+        let callback = OAuthCallback::parse(url)?;
 
-        match session_guard.as_ref() {
-            Some(session) => {
-                let now = Utc::now();
-                session.expires_at > now
-            }
-            None => false,
-        }
-    }
+        let user_response = self
+            .http_client
+            .get(format!("{}/user", self.auth_url()))
+            .header("Authorization", format!("Bearer {}", callback.access_token))
+            .send_tracked(&self.rate_limit, &self.circuit)
+            .await?;
 
-    /// Check if the current token needs refresh
-    pub fn needs_refresh(&self) -> bool {
-        let session_guard = match self.session.read() {
-            Ok(guard) => guard,
-            Err(_) => {
-                warn!("Failed to read session lock");
-                return false;
-            }
-        };
+        if !user_response.status().is_success() {
+            return Err(Error::auth("Invalid token in OAuth callback")
+                .with_fingerprint(self.config.error_fingerprint));
+        }
 
-        match session_guard.as_ref() {
-            Some(session) => {
-                let now = Utc::now();
-                let buffer = chrono::Duration::minutes(5); // Refresh 5 minutes before expiry
-                session.expires_at < (now + buffer)
-            }
-            None => false,
+        let user: User = user_response.json().await?;
+        let mut session = callback.into_session(user);
+        let provider = self.take_pending("oauth-provider", &self.oauth_provider);
+        if session.provider_token.is_some() {
+            session.provider = provider;
         }
+
+        self.set_session(session.clone()).await?;
+        self.trigger_auth_event(AuthEvent::SignedIn);
+
+        info!("OAuth sign-in completed");
+        Ok(session)
     }
 
-    /// Sign in with OAuth provider
+    /// Sign in with an OAuth provider in a popup window
     ///
-    /// Returns a URL that the user should be redirected to for authentication.
-    /// After successful authentication, the user will be redirected back with the session.
+    /// Opens the provider's sign-in page in a popup and resolves once the popup
+    /// is redirected back to this origin, or once the callback page posts its
+    /// URL with `window.opener.postMessage(location.href, location.origin)`.
+    /// The redirect URL defaults to the current page.
     ///
     /// # Example
     ///
-    /// ```rust
-    /// use supabase_lib_rs::auth::{OAuthProvider, OAuthOptions};
+    /// ```rust,no_run
+    /// use supabase_lib_rs::auth::OAuthProvider;
     ///
     /// # async fn example() -> supabase_lib_rs::Result<()> {
     /// let client = supabase_lib_rs::Client::new("url", "key")?;
     ///
-    /// let options = OAuthOptions {
-    ///     redirect_to: Some("https://myapp.com/callback".to_string()),
-    ///     scopes: Some(vec!["email".to_string(), "profile".to_string()]),
-    ///     ..Default::default()
-    /// };
-    ///
-    /// let response = client.auth().sign_in_with_oauth(OAuthProvider::Google, Some(options)).await?;
-    /// println!("Redirect to: {}", response.url);
+    /// let session = client.auth().sign_in_with_oauth_popup(OAuthProvider::GitHub, None).await?;
+    /// println!("Signed in as {:?}", session.user.email);
     /// # Ok(())
     /// # }
     /// ```
-    pub async fn sign_in_with_oauth(
+    #[cfg(all(target_arch = "wasm32", feature = "wasm"))]
+    pub async fn sign_in_with_oauth_popup(
         &self,
         provider: OAuthProvider,
         options: Option<OAuthOptions>,
-    ) -> Result<OAuthResponse> {
-        debug!("Initiating OAuth sign-in with provider: {:?}", provider);
-
-        let mut url = format!(
-            "{}/auth/v1/authorize?provider={}",
-            self.config.url,
-            provider.as_str()
-        );
-
-        if let Some(opts) = options {
-            if let Some(redirect_to) = opts.redirect_to {
-                url.push_str(&format!(
-                    "&redirect_to={}",
-                    urlencoding::encode(&redirect_to)
-                ));
-            }
-
-            if let Some(scopes) = opts.scopes {
-                let scope_str = scopes.join(" ");
-                url.push_str(&format!("&scope={}", urlencoding::encode(&scope_str)));
-            }
-
-            if let Some(query_params) = opts.query_params {
-                for (key, value) in query_params {
-                    url.push_str(&format!(
-                        "&{}={}",
-                        urlencoding::encode(&key),
-                        urlencoding::encode(&value)
-                    ));
-                }
-            }
+    ) -> Result<Session> {
+        let mut options = options.unwrap_or_default();
+        if options.redirect_to.is_none() {
+            options.redirect_to = browser_popup::current_page_url();
         }
 
-        Ok(OAuthResponse { url })
+        let response = self.sign_in_with_oauth(provider, Some(options)).await?;
+        let callback_url = browser_popup::wait_for_callback(&response.url).await?;
+
+        self.get_session_from_url(&callback_url).await
     }
 
     /// Sign up with phone number
@@ -910,9 +3166,9 @@ impl Auth {
 
         let response = self
             .http_client
-            .post(format!("{}/auth/v1/signup", self.config.url))
+            .post(format!("{}/signup", self.auth_url()))
             .json(&payload)
-            .send()
+            .send_tracked(&self.rate_limit, &self.circuit)
             .await?;
 
         if !response.status().is_success() {
@@ -921,7 +3177,7 @@ impl Auth {
                 Ok(text) => text,
                 Err(_) => format!("Phone sign up failed with status: {}", status),
             };
-            return Err(Error::auth(error_msg));
+            return Err(Error::auth(error_msg).with_fingerprint(self.config.error_fingerprint));
         }
 
         let auth_response: AuthResponse = response.json().await?;
@@ -963,12 +3219,9 @@ impl Auth {
 
         let response = self
             .http_client
-            .post(format!(
-                "{}/auth/v1/token?grant_type=password",
-                self.config.url
-            ))
+            .post(format!("{}/token?grant_type=password", self.auth_url()))
             .json(&payload)
-            .send()
+            .send_tracked(&self.rate_limit, &self.circuit)
             .await?;
 
         if !response.status().is_success() {
@@ -977,7 +3230,7 @@ impl Auth {
                 Ok(text) => text,
                 Err(_) => format!("Phone sign in failed with status: {}", status),
             };
-            return Err(Error::auth(error_msg));
+            return Err(Error::auth(error_msg).with_fingerprint(self.config.error_fingerprint));
         }
 
         let auth_response_body = response.text().await?;
@@ -996,6 +3249,66 @@ impl Auth {
         Ok(auth_response)
     }
 
+    /// Send a one-time code to a phone number
+    ///
+    /// Complete the sign-in with [`Auth::verify_otp`] and type `"sms"`; codes
+    /// sent over WhatsApp are verified the same way. The code length and
+    /// expiry are set in the project's auth settings.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use supabase_lib_rs::auth::{OtpChannel, OtpOptions};
+    ///
+    /// # async fn example() -> supabase_lib_rs::Result<()> {
+    /// let client = supabase_lib_rs::Client::new("url", "key")?;
+    ///
+    /// // Login only, over WhatsApp: unknown numbers get no code
+    /// client.auth()
+    ///     .sign_in_with_otp(
+    ///         "+1234567890",
+    ///         OtpOptions {
+    ///             channel: OtpChannel::Whatsapp,
+    ///             create_user: false,
+    ///             ..Default::default()
+    ///         },
+    ///     )
+    ///     .await?;
+    ///
+    /// client.auth().verify_otp("+1234567890", "123456", "sms").await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn sign_in_with_otp(&self, phone: &str, options: OtpOptions) -> Result<()> {
+        debug!("Sending {:?} OTP to phone: {}", options.channel, phone);
+
+        let payload = PhoneOtpRequest {
+            phone: phone.to_string(),
+            channel: options.channel,
+            create_user: options.create_user,
+            data: options.data,
+        };
+
+        let response = self
+            .http_client
+            .post(format!("{}/otp", self.auth_url()))
+            .json(&payload)
+            .send_tracked(&self.rate_limit, &self.circuit)
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_msg = match response.text().await {
+                Ok(text) => text,
+                Err(_) => format!("OTP request failed with status: {}", status),
+            };
+            return Err(Error::auth(error_msg).with_fingerprint(self.config.error_fingerprint));
+        }
+
+        info!("OTP sent successfully");
+        Ok(())
+    }
+
     /// Verify OTP token
     ///
     /// # Example
@@ -1008,51 +3321,277 @@ impl Auth {
     ///     .verify_otp("+1234567890", "123456", "sms")
     ///     .await?;
     ///
-    /// if let Some(session) = response.session {
-    ///     println!("OTP verified, user signed in");
-    /// }
+    /// if let Some(session) = response.session {
+    ///     println!("OTP verified, user signed in");
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn verify_otp(
+        &self,
+        phone: &str,
+        token: &str,
+        verification_type: &str,
+    ) -> Result<AuthResponse> {
+        debug!("Verifying OTP for phone: {}", phone);
+
+        let payload = OTPVerificationRequest {
+            phone: phone.to_string(),
+            token: token.to_string(),
+            verification_type: verification_type.to_string(),
+        };
+
+        let response = self
+            .http_client
+            .post(format!("{}/verify", self.auth_url()))
+            .json(&payload)
+            .send_tracked(&self.rate_limit, &self.circuit)
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_msg = match response.text().await {
+                Ok(text) => text,
+                Err(_) => format!("OTP verification failed with status: {}", status),
+            };
+            return Err(Error::auth(error_msg).with_fingerprint(self.config.error_fingerprint));
+        }
+
+        let auth_response: AuthResponse = response.json().await?;
+
+        if let Some(ref session) = auth_response.session {
+            self.set_session(session.clone()).await?;
+            self.trigger_auth_event(AuthEvent::SignedIn);
+            info!("OTP verified successfully");
+        }
+
+        Ok(auth_response)
+    }
+
+    /// Request a phone number change for the current user
+    ///
+    /// Sends an OTP to the new phone number. The change takes effect once the
+    /// code is confirmed with [`Auth::verify_phone_change`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # async fn example() -> supabase_lib_rs::Result<()> {
+    /// let client = supabase_lib_rs::Client::new("url", "key")?;
+    ///
+    /// client.auth().update_phone("+1234567890").await?;
+    /// // Later, with the code received by SMS
+    /// client.auth().verify_phone_change("123456").await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn update_phone(&self, new_phone: &str) -> Result<User> {
+        debug!("Requesting phone change");
+
+        let session = self.get_session()?;
+
+        let payload = PhoneChangeRequest {
+            phone: new_phone.to_string(),
+        };
+
+        let response = self
+            .http_client
+            .put(format!("{}/user", self.auth_url()))
+            .header("Authorization", format!("Bearer {}", session.access_token))
+            .json(&payload)
+            .send_tracked(&self.rate_limit, &self.circuit)
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_msg = match response.text().await {
+                Ok(text) => text,
+                Err(_) => format!("Phone change failed with status: {}", status),
+            };
+            return Err(Error::auth(error_msg).with_fingerprint(self.config.error_fingerprint));
+        }
+
+        let user: User = response.json().await?;
+
+        let mut pending_guard = self
+            .pending_phone_change
+            .write()
+            .map_err(|_| Error::auth("Failed to write pending phone change"))?;
+        *pending_guard = Some(new_phone.to_string());
+
+        info!("Phone change OTP sent");
+        Ok(user)
+    }
+
+    /// Confirm a pending phone number change with the OTP sent to the new number
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # async fn example() -> supabase_lib_rs::Result<()> {
+    /// let client = supabase_lib_rs::Client::new("url", "key")?;
+    ///
+    /// let response = client.auth().verify_phone_change("123456").await?;
+    ///
+    /// if let Some(user) = response.user {
+    ///     println!("Phone changed to: {:?}", user.phone);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn verify_phone_change(&self, otp: &str) -> Result<AuthResponse> {
+        let phone = self
+            .pending_phone_change
+            .read()
+            .map_err(|_| Error::auth("Failed to read pending phone change"))?
+            .clone()
+            .ok_or_else(|| Error::auth("No pending phone change to verify"))?;
+
+        debug!("Verifying phone change");
+
+        let payload = OTPVerificationRequest {
+            phone,
+            token: otp.to_string(),
+            verification_type: "phone_change".to_string(),
+        };
+
+        let response = self
+            .http_client
+            .post(format!("{}/verify", self.auth_url()))
+            .json(&payload)
+            .send_tracked(&self.rate_limit, &self.circuit)
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_msg = match response.text().await {
+                Ok(text) => text,
+                Err(_) => format!("Phone change verification failed with status: {}", status),
+            };
+            return Err(Error::auth(error_msg).with_fingerprint(self.config.error_fingerprint));
+        }
+
+        let auth_response_body = response.text().await?;
+
+        let mut auth_response = serde_json::from_str::<AuthResponse>(auth_response_body.as_str())?;
+        auth_response.session = serde_json::from_str::<Session>(auth_response_body.as_str())
+            .inspect_err(|err| warn!("No session: {}", err.to_string()))
+            .ok();
+
+        if let Ok(mut pending_guard) = self.pending_phone_change.write() {
+            *pending_guard = None;
+        }
+
+        if let Some(ref session) = auth_response.session {
+            self.set_session(session.clone()).await?;
+        }
+
+        self.trigger_auth_event(AuthEvent::UserUpdated);
+        info!("Phone change verified successfully");
+
+        Ok(auth_response)
+    }
+
+    /// Accept an invitation, setting the invited user's password
+    ///
+    /// `token_hash` comes from the invite link: the `token_hash` parameter of
+    /// a link built with `{{ .TokenHash }}` in the invite email template, or
+    /// `hashed_token` of an admin-generated invite link. Verifying it signs the
+    /// invited user in; the password and `data` (merged into `user_metadata`)
+    /// are then saved. If saving them fails, the user stays signed in and can
+    /// retry with [`Auth::update_user`].
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # async fn example() -> supabase_lib_rs::Result<()> {
+    /// let client = supabase_lib_rs::Client::new("url", "key")?;
+    ///
+    /// let response = client
+    ///     .auth()
+    ///     .accept_invite(
+    ///         "token-hash-from-link",
+    ///         "new-password",
+    ///         Some(serde_json::json!({"display_name": "Ada"})),
+    ///     )
+    ///     .await?;
+    /// println!("Welcome, {:?}", response.user.and_then(|user| user.email));
     /// # Ok(())
     /// # }
     /// ```
-    pub async fn verify_otp(
+    pub async fn accept_invite(
         &self,
-        phone: &str,
-        token: &str,
-        verification_type: &str,
+        token_hash: &str,
+        password: &str,
+        data: Option<serde_json::Value>,
     ) -> Result<AuthResponse> {
-        debug!("Verifying OTP for phone: {}", phone);
+        debug!("Accepting invite");
 
-        let payload = OTPVerificationRequest {
-            phone: phone.to_string(),
-            token: token.to_string(),
-            verification_type: verification_type.to_string(),
+        let payload = InviteVerificationRequest {
+            token_hash,
+            verification_type: "invite",
         };
 
         let response = self
             .http_client
-            .post(format!("{}/auth/v1/verify", self.config.url))
+            .post(format!("{}/verify", self.auth_url()))
             .json(&payload)
-            .send()
+            .send_tracked(&self.rate_limit, &self.circuit)
             .await?;
 
         if !response.status().is_success() {
             let status = response.status();
             let error_msg = match response.text().await {
                 Ok(text) => text,
-                Err(_) => format!("OTP verification failed with status: {}", status),
+                Err(_) => format!("Invite verification failed with status: {}", status),
             };
-            return Err(Error::auth(error_msg));
+            return Err(Error::auth(error_msg).with_fingerprint(self.config.error_fingerprint));
         }
 
-        let auth_response: AuthResponse = response.json().await?;
+        let session: Session = serde_json::from_str(&response.text().await?)
+            .map_err(|e| Error::auth(format!("Invite verification returned no session: {}", e)))?;
+        self.set_session(session.clone()).await?;
+        self.trigger_auth_event(AuthEvent::SignedIn);
 
-        if let Some(ref session) = auth_response.session {
-            self.set_session(session.clone()).await?;
-            self.trigger_auth_event(AuthEvent::SignedIn);
-            info!("OTP verified successfully");
+        let payload = UpdateUserRequest {
+            email: None,
+            password: Some(password.to_string()),
+            data,
+        };
+
+        let response = self
+            .http_client
+            .put(format!("{}/user", self.auth_url()))
+            .header("Authorization", format!("Bearer {}", session.access_token))
+            .json(&payload)
+            .send_tracked(&self.rate_limit, &self.circuit)
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_msg = match response.text().await {
+                Ok(text) => text,
+                Err(_) => format!(
+                    "Setting the invited user's password failed with status: {}",
+                    status
+                ),
+            };
+            return Err(Error::auth(error_msg).with_fingerprint(self.config.error_fingerprint));
         }
 
-        Ok(auth_response)
+        let user: User = response.json().await?;
+        let session = Session {
+            user: user.clone(),
+            ..session
+        };
+        self.set_session(session.clone()).await?;
+        self.trigger_auth_event(AuthEvent::UserUpdated);
+
+        info!("Invite accepted");
+        Ok(AuthResponse {
+            user: Some(user),
+            session: Some(session),
+        })
     }
 
     /// Send magic link for passwordless authentication
@@ -1087,9 +3626,9 @@ impl Auth {
 
         let response = self
             .http_client
-            .post(format!("{}/auth/v1/magiclink", self.config.url))
+            .post(format!("{}/magiclink", self.auth_url()))
             .json(&payload)
-            .send()
+            .send_tracked(&self.rate_limit, &self.circuit)
             .await?;
 
         if !response.status().is_success() {
@@ -1098,7 +3637,7 @@ impl Auth {
                 Ok(text) => text,
                 Err(_) => format!("Magic link request failed with status: {}", status),
             };
-            return Err(Error::auth(error_msg));
+            return Err(Error::auth(error_msg).with_fingerprint(self.config.error_fingerprint));
         }
 
         info!("Magic link sent successfully");
@@ -1135,10 +3674,10 @@ impl Auth {
 
         let response = self
             .http_client
-            .post(format!("{}/auth/v1/signup", self.config.url))
+            .post(format!("{}/signup", self.auth_url()))
             .header("Authorization", format!("Bearer {}", self.config.key))
             .json(&payload)
-            .send()
+            .send_tracked(&self.rate_limit, &self.circuit)
             .await?;
 
         if !response.status().is_success() {
@@ -1147,7 +3686,7 @@ impl Auth {
                 Ok(text) => text,
                 Err(_) => format!("Anonymous sign in failed with status: {}", status),
             };
-            return Err(Error::auth(error_msg));
+            return Err(Error::auth(error_msg).with_fingerprint(self.config.error_fingerprint));
         }
 
         let auth_response: AuthResponse = response.json().await?;
@@ -1161,6 +3700,82 @@ impl Auth {
         Ok(auth_response)
     }
 
+    /// Sign in with a signed Web3 wallet message
+    ///
+    /// `message` is the exact text the wallet signed, usually built with
+    /// [`Web3SignInMessage::to_message`], and `signature` is encoded as
+    /// [`Web3Chain::encode_signature`] describes. Web3 sign-in must be enabled
+    /// for the chain in the project's Auth settings.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # use supabase_lib_rs::auth::{Web3Chain, Web3SignInMessage};
+    /// # async fn example(wallet_signature: Vec<u8>) -> supabase_lib_rs::Result<()> {
+    /// let client = supabase_lib_rs::Client::new("url", "key")?;
+    ///
+    /// let message = Web3SignInMessage::new(
+    ///     "example.com",
+    ///     "0xAb5801a7D398351b8bE11C439e05C5B3259aeC9B",
+    ///     "https://example.com/login",
+    /// )
+    /// .statement("Sign in to Example")
+    /// .to_message(Web3Chain::Ethereum);
+    ///
+    /// // Ask the wallet to sign `message`, then:
+    /// let signature = Web3Chain::Ethereum.encode_signature(&wallet_signature);
+    /// let response = client.auth()
+    ///     .sign_in_with_web3(Web3Chain::Ethereum, &message, &signature)
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn sign_in_with_web3(
+        &self,
+        chain: Web3Chain,
+        message: &str,
+        signature: &str,
+    ) -> Result<AuthResponse> {
+        debug!("Signing in with {} wallet", chain.account_name());
+
+        let payload = Web3SignInRequest {
+            chain,
+            message: message.to_string(),
+            signature: signature.to_string(),
+        };
+
+        let response = self
+            .http_client
+            .post(format!("{}/token?grant_type=web3", self.auth_url()))
+            .json(&payload)
+            .send_tracked(&self.rate_limit, &self.circuit)
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_msg = match response.text().await {
+                Ok(text) => text,
+                Err(_) => format!("Web3 sign in failed with status: {}", status),
+            };
+            return Err(Error::auth(error_msg).with_fingerprint(self.config.error_fingerprint));
+        }
+
+        let auth_response_body = response.text().await?;
+
+        let mut auth_response = serde_json::from_str::<AuthResponse>(auth_response_body.as_str())?;
+        auth_response.session = serde_json::from_str::<Session>(auth_response_body.as_str())
+            .inspect_err(|err| warn!("No session: {}", err.to_string()))
+            .ok();
+
+        if let Some(ref session) = auth_response.session {
+            self.set_session(session.clone()).await?;
+            self.trigger_auth_event(AuthEvent::SignedIn);
+            info!("User signed in with Web3 wallet successfully");
+        }
+
+        Ok(auth_response)
+    }
+
     /// Enhanced password recovery with custom redirect and options
     ///
     /// # Example
@@ -1191,100 +3806,367 @@ impl Auth {
 
         let response = self
             .http_client
-            .post(format!("{}/auth/v1/recover", self.config.url))
-            .json(&payload)
-            .send()
+            .post(format!("{}/recover", self.auth_url()))
+            .json(&payload)
+            .send_tracked(&self.rate_limit, &self.circuit)
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_msg = match response.text().await {
+                Ok(text) => text,
+                Err(_) => format!("Enhanced password recovery failed with status: {}", status),
+            };
+            return Err(Error::auth(error_msg).with_fingerprint(self.config.error_fingerprint));
+        }
+
+        self.trigger_auth_event(AuthEvent::PasswordReset);
+        info!("Enhanced password recovery email sent successfully");
+        Ok(())
+    }
+
+    /// Subscribe to authentication state changes
+    ///
+    /// Returns a handle that can be used to remove the listener later.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use supabase_lib_rs::auth::AuthEvent;
+    ///
+    /// # async fn example() -> supabase_lib_rs::Result<()> {
+    /// let client = supabase_lib_rs::Client::new("url", "key")?;
+    ///
+    /// let handle = client.auth().on_auth_state_change(|event, session| {
+    ///     match event {
+    ///         AuthEvent::SignedIn => {
+    ///             if let Some(session) = session {
+    ///                 println!("User signed in: {}", session.user.email.unwrap_or_default());
+    ///             }
+    ///         }
+    ///         AuthEvent::SignedOut => println!("User signed out"),
+    ///         AuthEvent::TokenRefreshed => println!("Token refreshed"),
+    ///         _ => {}
+    ///     }
+    /// });
+    ///
+    /// // Later remove the listener
+    /// handle.remove();
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn on_auth_state_change<F>(&self, callback: F) -> AuthEventHandle
+    where
+        F: Fn(AuthEvent, Option<Session>) + Send + Sync + 'static,
+    {
+        let id = Uuid::new_v4();
+        let callback = Box::new(callback);
+
+        if let Ok(mut listeners) = self.event_listeners.write() {
+            listeners.insert(id, callback);
+        }
+
+        AuthEventHandle {
+            id,
+            auth: Arc::downgrade(&Arc::new(self.clone())),
+        }
+    }
+
+    /// Remove an authentication state listener
+    pub fn remove_auth_listener(&self, id: Uuid) {
+        if let Ok(mut listeners) = self.event_listeners.write() {
+            listeners.remove(&id);
+        }
+    }
+
+    /// Trigger authentication state change event
+    fn trigger_auth_event(&self, event: AuthEvent) {
+        Self::notify_listeners(&self.session, &self.event_listeners, event);
+    }
+
+    /// Call every registered listener with the event and current session
+    fn notify_listeners(
+        session: &RwLock<Option<Session>>,
+        event_listeners: &RwLock<HashMap<Uuid, AuthStateCallback>>,
+        event: AuthEvent,
+    ) {
+        let session = match session.read() {
+            Ok(guard) => guard.clone(),
+            Err(_) => {
+                warn!("Failed to read session for event trigger");
+                return;
+            }
+        };
+
+        let listeners = match event_listeners.read() {
+            Ok(guard) => guard,
+            Err(_) => {
+                warn!("Failed to read event listeners");
+                return;
+            }
+        };
+
+        for callback in listeners.values() {
+            callback(event.clone(), session.clone());
+        }
+    }
+
+    // ==== Auto Refresh ====
+
+    /// Refresh the session in the background before it expires
+    ///
+    /// The session is refreshed once it is within `refresh_threshold` seconds
+    /// of `expires_at` (see [`AuthConfig`](crate::types::AuthConfig)), emitting
+    /// [`AuthEvent::TokenRefreshed`]. Failed refreshes are retried with
+    /// exponential backoff up to a minute apart; a rejected refresh token
+    /// signs the user out as with [`refresh_session`](Self::refresh_session),
+    /// and the task waits for the next session. Runs on tokio on native
+    /// targets and on browser timers on WASM.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # async fn example() -> supabase_lib_rs::Result<()> {
+    /// let client = supabase_lib_rs::Client::new("url", "key")?;
+    ///
+    /// let auto_refresh = client.auth().start_auto_refresh();
+    /// // ... on shutdown
+    /// auto_refresh.stop();
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use = "the auto refresh task stops when the handle is dropped"]
+    pub fn start_auto_refresh(&self) -> AutoRefreshHandle {
+        let active = Arc::new(AtomicBool::new(true));
+        // Share listeners so TokenRefreshed reaches this instance's subscribers
+        let auth = Self {
+            event_listeners: self.event_listeners.clone(),
+            ..self.clone()
+        };
+        spawn_background(auth.auto_refresh_loop(active.clone()));
+        debug!("Started automatic session refresh");
+
+        AutoRefreshHandle { active }
+    }
+
+    async fn auto_refresh_loop(self, active: Arc<AtomicBool>) {
+        let threshold = self.config.auth_config.refresh_threshold as i64;
+        let mut failures: u32 = 0;
+
+        while active.load(Ordering::SeqCst) {
+            let due = match self.needs_refresh_with_buffer(threshold) {
+                Ok(due) => due,
+                Err(e) => {
+                    warn!("Auto refresh could not read the session: {}", e);
+                    false
+                }
+            };
+            if !due {
+                let until_due = self
+                    .time_until_expiry()
+                    .ok()
+                    .flatten()
+                    .map(|seconds| Duration::from_secs((seconds - threshold).max(0) as u64))
+                    .unwrap_or(AUTO_REFRESH_CHECK_INTERVAL);
+                async_sleep(until_due.min(AUTO_REFRESH_CHECK_INTERVAL)).await;
+                continue;
+            }
+
+            match self.refresh_session().await {
+                Ok(_) => failures = 0,
+                Err(_) if self.get_session().is_err() => {
+                    // Signed out because the refresh token was rejected
+                    failures = 0;
+                }
+                Err(e) => {
+                    failures += 1;
+                    let backoff = Duration::from_secs(1 << (failures - 1).min(6))
+                        .min(AUTO_REFRESH_MAX_BACKOFF);
+                    warn!(
+                        "Automatic session refresh failed (attempt {}), retrying in {:?}: {}",
+                        failures, backoff, e
+                    );
+                    async_sleep(backoff).await;
+                }
+            }
+        }
+
+        debug!("Stopped automatic session refresh");
+    }
+
+    // ==== Account Deletion ====
+
+    /// Choose the endpoint [`delete_account`](Self::delete_account) calls
+    pub fn set_account_deletion_endpoint(&self, endpoint: AccountDeletionEndpoint) {
+        if let Ok(mut deletion) = self.account_deletion.write() {
+            deletion.endpoint = endpoint;
+        }
+    }
+
+    /// Run `hook` before the account is deleted
+    ///
+    /// The hook receives the user about to be deleted and runs while the
+    /// session is still valid, so it can export the user's data or clean up
+    /// records. An error from the hook cancels the deletion. Setting a hook
+    /// replaces the previous one.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # async fn example() -> supabase_lib_rs::Result<()> {
+    /// let client = supabase_lib_rs::Client::new("url", "key")?;
+    ///
+    /// client.auth().on_before_account_deletion(|user| async move {
+    ///     println!("Exporting data of {}", user.id);
+    ///     Ok(())
+    /// });
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn on_before_account_deletion<F, Fut>(&self, hook: F)
+    where
+        F: Fn(User) -> Fut + Send + Sync + 'static,
+        Fut: std::future::Future<Output = Result<()>> + Send + 'static,
+    {
+        self.set_pre_deletion_hook(Arc::new(move |user| Box::pin(hook(user))));
+    }
+
+    /// Run `hook` before the account is deleted (WASM version)
+    #[cfg(target_arch = "wasm32")]
+    pub fn on_before_account_deletion<F, Fut>(&self, hook: F)
+    where
+        F: Fn(User) -> Fut + Send + Sync + 'static,
+        Fut: std::future::Future<Output = Result<()>> + 'static,
+    {
+        self.set_pre_deletion_hook(Arc::new(move |user| Box::pin(hook(user))));
+    }
+
+    fn set_pre_deletion_hook(&self, hook: PreDeletionHook) {
+        if let Ok(mut deletion) = self.account_deletion.write() {
+            deletion.hook = Some(hook);
+        }
+    }
+
+    /// Delete the signed-in user's account
+    ///
+    /// `confirmation` must repeat the user's email address or phone number
+    /// (the user ID for accounts with neither), guarding against deleting the
+    /// wrong account. The pre-deletion hook runs first, then the configured
+    /// [`AccountDeletionEndpoint`] is called with the user's access token
+    /// and the confirmation. On success the session is cleared and a
+    /// [`AuthEvent::SignedOut`] event is emitted.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # async fn example() -> supabase_lib_rs::Result<()> {
+    /// let client = supabase_lib_rs::Client::new("url", "key")?;
+    ///
+    /// // The user typed their email address to confirm
+    /// client.auth().delete_account("user@example.com").await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn delete_account(&self, confirmation: &str) -> Result<()> {
+        let session = self.get_session()?;
+        if !confirmation_matches(&session.user, confirmation) {
+            return Err(Error::invalid_input(
+                "Confirmation does not match the signed-in account",
+            ));
+        }
+
+        let (endpoint, hook) = {
+            let deletion = self
+                .account_deletion
+                .read()
+                .map_err(|_| Error::auth("Failed to read account deletion settings"))?;
+            (deletion.endpoint.clone(), deletion.hook.clone())
+        };
+
+        if let Some(hook) = hook {
+            debug!("Running pre-deletion hook for user: {}", session.user.id);
+            hook(session.user.clone()).await?;
+        }
+
+        debug!("Deleting account of user: {}", session.user.id);
+        let url = match &endpoint {
+            AccountDeletionEndpoint::EdgeFunction(name) => {
+                format!("{}/{}", self.config.functions_url(), name)
+            }
+            AccountDeletionEndpoint::Rpc(name) => {
+                format!("{}/rpc/{}", self.config.project_url().rest_url(), name)
+            }
+        };
+
+        let response = self
+            .http_client
+            .post(url)
+            .header("Authorization", format!("Bearer {}", session.access_token))
+            .json(&serde_json::json!({ "confirmation": confirmation.trim() }))
+            .send_tracked(&self.rate_limit, &self.circuit)
             .await?;
 
         if !response.status().is_success() {
             let status = response.status();
             let error_msg = match response.text().await {
-                Ok(text) => text,
-                Err(_) => format!("Enhanced password recovery failed with status: {}", status),
+                Ok(text) if !text.is_empty() => text,
+                _ => format!("Account deletion failed with status: {}", status),
             };
-            return Err(Error::auth(error_msg));
+            return Err(Error::auth(error_msg).with_fingerprint(self.config.error_fingerprint));
         }
 
-        self.trigger_auth_event(AuthEvent::PasswordReset);
-        info!("Enhanced password recovery email sent successfully");
+        self.clear_session().await?;
+        self.trigger_auth_event(AuthEvent::SignedOut);
+        info!("Account deleted");
+
         Ok(())
     }
 
-    /// Subscribe to authentication state changes
-    ///
-    /// Returns a handle that can be used to remove the listener later.
-    ///
-    /// # Example
+    // ==== Server Settings ====
+
+    /// Fetch the Auth server's public settings
     ///
-    /// ```rust
-    /// use supabase_lib_rs::auth::AuthEvent;
+    /// Lets apps adapt to the project configuration, e.g. hide phone login
+    /// when it is disabled, instead of hardcoding assumptions.
     ///
-    /// # async fn example() -> supabase_lib_rs::Result<()> {
-    /// let client = supabase_lib_rs::Client::new("url", "key")?;
+    /// # Examples
     ///
-    /// let handle = client.auth().on_auth_state_change(|event, session| {
-    ///     match event {
-    ///         AuthEvent::SignedIn => {
-    ///             if let Some(session) = session {
-    ///                 println!("User signed in: {}", session.user.email.unwrap_or_default());
-    ///             }
-    ///         }
-    ///         AuthEvent::SignedOut => println!("User signed out"),
-    ///         AuthEvent::TokenRefreshed => println!("Token refreshed"),
-    ///         _ => {}
-    ///     }
-    /// });
+    /// ```rust,no_run
+    /// # use supabase_lib_rs::Client;
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = Client::new("https://example.supabase.co", "your-anon-key")?;
     ///
-    /// // Later remove the listener
-    /// handle.remove();
+    /// let settings = client.auth().get_settings().await?;
+    /// if !settings.phone_enabled() {
+    ///     println!("Phone login is disabled");
+    /// }
+    /// for provider in settings.enabled_providers() {
+    ///     println!("Show a button for {}", provider.as_str());
+    /// }
     /// # Ok(())
     /// # }
     /// ```
-    pub fn on_auth_state_change<F>(&self, callback: F) -> AuthEventHandle
-    where
-        F: Fn(AuthEvent, Option<Session>) + Send + Sync + 'static,
-    {
-        let id = Uuid::new_v4();
-        let callback = Box::new(callback);
-
-        if let Ok(mut listeners) = self.event_listeners.write() {
-            listeners.insert(id, callback);
-        }
+    pub async fn get_settings(&self) -> Result<AuthSettings> {
+        debug!("Fetching auth settings");
 
-        AuthEventHandle {
-            id,
-            auth: Arc::downgrade(&Arc::new(self.clone())),
-        }
-    }
+        let response = self
+            .http_client
+            .get(format!("{}/settings", self.auth_url()))
+            .send_tracked(&self.rate_limit, &self.circuit)
+            .await?;
 
-    /// Remove an authentication state listener
-    pub fn remove_auth_listener(&self, id: Uuid) {
-        if let Ok(mut listeners) = self.event_listeners.write() {
-            listeners.remove(&id);
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_msg = match response.text().await {
+                Ok(text) => text,
+                Err(_) => format!("Fetching settings failed with status: {}", status),
+            };
+            return Err(Error::auth(error_msg).with_fingerprint(self.config.error_fingerprint));
         }
-    }
-
-    /// Trigger authentication state change event
-    fn trigger_auth_event(&self, event: AuthEvent) {
-        let session = match self.session.read() {
-            Ok(guard) => guard.clone(),
-            Err(_) => {
-                warn!("Failed to read session for event trigger");
-                return;
-            }
-        };
 
-        let listeners = match self.event_listeners.read() {
-            Ok(guard) => guard,
-            Err(_) => {
-                warn!("Failed to read event listeners");
-                return;
-            }
-        };
-
-        for callback in listeners.values() {
-            callback(event.clone(), session.clone());
-        }
+        let settings: AuthSettings = response.json().await?;
+        Ok(settings)
     }
 
     // ==== MFA (Multi-Factor Authentication) Methods ====
@@ -1310,13 +4192,14 @@ impl Auth {
         let session = self.get_session()?;
         let response = self
             .http_client
-            .get(format!("{}/auth/v1/factors", self.config.url))
+            .get(format!("{}/factors", self.auth_url()))
             .header("Authorization", format!("Bearer {}", session.access_token))
-            .send()
+            .send_tracked(&self.rate_limit, &self.circuit)
             .await?;
 
         if !response.status().is_success() {
-            return Err(Error::auth("Failed to list MFA factors"));
+            return Err(Error::auth("Failed to list MFA factors")
+                .with_fingerprint(self.config.error_fingerprint));
         }
 
         let factors: Vec<MfaFactor> = response.json().await?;
@@ -1342,6 +4225,7 @@ impl Auth {
     /// # Ok(())
     /// # }
     /// ```
+    #[cfg(feature = "auth-mfa")]
     pub async fn setup_totp(&self, friendly_name: &str) -> Result<TotpSetupResponse> {
         debug!("Setting up TOTP factor: {}", friendly_name);
 
@@ -1354,14 +4238,16 @@ impl Auth {
 
         let response = self
             .http_client
-            .post(format!("{}/auth/v1/factors", self.config.url))
+            .post(format!("{}/factors", self.auth_url()))
             .header("Authorization", format!("Bearer {}", session.access_token))
             .json(&request_body)
-            .send()
+            .send_tracked(&self.rate_limit, &self.circuit)
             .await?;
 
         if !response.status().is_success() {
-            return Err(Error::auth("Failed to setup TOTP"));
+            return Err(
+                Error::auth("Failed to setup TOTP").with_fingerprint(self.config.error_fingerprint)
+            );
         }
 
         let setup_response: TotpSetupResponse = response.json().await?;
@@ -1403,6 +4289,7 @@ impl Auth {
     /// # Ok(())
     /// # }
     /// ```
+    #[cfg(feature = "auth-mfa")]
     pub async fn setup_sms_mfa(
         &self,
         phone: &str,
@@ -1428,14 +4315,15 @@ impl Auth {
 
         let response = self
             .http_client
-            .post(format!("{}/auth/v1/factors", self.config.url))
+            .post(format!("{}/factors", self.auth_url()))
             .header("Authorization", format!("Bearer {}", session.access_token))
             .json(&request_body)
-            .send()
+            .send_tracked(&self.rate_limit, &self.circuit)
             .await?;
 
         if !response.status().is_success() {
-            return Err(Error::auth("Failed to setup SMS MFA"));
+            return Err(Error::auth("Failed to setup SMS MFA")
+                .with_fingerprint(self.config.error_fingerprint));
         }
 
         let factor: MfaFactor = response.json().await?;
@@ -1475,16 +4363,18 @@ impl Auth {
         let response = self
             .http_client
             .post(format!(
-                "{}/auth/v1/factors/{}/challenge",
-                self.config.url, factor_id
+                "{}/factors/{}/challenge",
+                self.auth_url(),
+                factor_id
             ))
             .header("Authorization", format!("Bearer {}", session.access_token))
             .json(&request_body)
-            .send()
+            .send_tracked(&self.rate_limit, &self.circuit)
             .await?;
 
         if !response.status().is_success() {
-            return Err(Error::auth("Failed to create MFA challenge"));
+            return Err(Error::auth("Failed to create MFA challenge")
+                .with_fingerprint(self.config.error_fingerprint));
         }
 
         let challenge: MfaChallenge = response.json().await?;
@@ -1523,7 +4413,7 @@ impl Auth {
     ) -> Result<AuthResponse> {
         debug!("Verifying MFA challenge: {}", challenge_id);
 
-        let session = self.get_session()?;
+        let current_session = self.get_session()?;
 
         let request_body = serde_json::json!({
             "factor_id": factor_id,
@@ -1533,23 +4423,32 @@ impl Auth {
 
         let response = self
             .http_client
-            .post(format!(
-                "{}/auth/v1/factors/{}/verify",
-                self.config.url, factor_id
-            ))
-            .header("Authorization", format!("Bearer {}", session.access_token))
+            .post(format!("{}/factors/{}/verify", self.auth_url(), factor_id))
+            .header(
+                "Authorization",
+                format!("Bearer {}", current_session.access_token),
+            )
             .json(&request_body)
-            .send()
+            .send_tracked(&self.rate_limit, &self.circuit)
             .await?;
 
         if !response.status().is_success() {
-            return Err(Error::auth("Failed to verify MFA challenge"));
+            return Err(Error::auth("Failed to verify MFA challenge")
+                .with_fingerprint(self.config.error_fingerprint));
         }
 
-        let auth_response: AuthResponse = response.json().await?;
+        // The upgraded session comes back at the top level of the body
+        let auth_response_body = response.text().await?;
+        let mut auth_response = serde_json::from_str::<AuthResponse>(auth_response_body.as_str())?;
+        if auth_response.session.is_none() {
+            auth_response.session = serde_json::from_str::<Session>(auth_response_body.as_str())
+                .inspect_err(|err| warn!("No session: {}", err.to_string()))
+                .ok();
+        }
 
         // Update session if new token provided
-        if let Some(session) = &auth_response.session {
+        if let Some(session) = &mut auth_response.session {
+            session.inherit_provider_tokens(&current_session);
             self.set_session(session.clone()).await?;
         }
 
@@ -1559,6 +4458,82 @@ impl Auth {
         Ok(auth_response)
     }
 
+    /// Challenge a factor and verify `code` to upgrade the session to `aal2`
+    ///
+    /// Finishes a sign-in that returned [`SignInResult::MfaChallengeRequired`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// # use supabase_lib_rs::Client;
+    /// # use uuid::Uuid;
+    /// # async fn example(factor_id: Uuid) -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = Client::new("https://example.supabase.co", "your-anon-key")?;
+    ///
+    /// let session = client.auth().complete_mfa_sign_in(factor_id, "123456").await?;
+    /// println!("Signed in as {}", session.user.id);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn complete_mfa_sign_in(&self, factor_id: Uuid, code: &str) -> Result<Session> {
+        let challenge = self.create_mfa_challenge(factor_id).await?;
+        self.verify_mfa_challenge(factor_id, challenge.id, code)
+            .await?
+            .session
+            .ok_or_else(|| Error::auth("MFA verification returned no session"))
+    }
+
+    /// Assurance level of the current session and the level the user can reach
+    ///
+    /// The current level comes from the access token; the next level depends
+    /// on whether the user has verified factors, fetched from the server.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// # use supabase_lib_rs::Client;
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = Client::new("https://example.supabase.co", "your-anon-key")?;
+    ///
+    /// let level = client.auth().get_authenticator_assurance_level().await?;
+    /// if level.needs_mfa() {
+    ///     println!("Ask for a code before showing sensitive data");
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_authenticator_assurance_level(&self) -> Result<AuthenticatorAssuranceLevel> {
+        let session = self.get_session()?;
+        let (current_level, current_authentication_methods) =
+            token_assurance(&session.access_token);
+
+        let response = self
+            .http_client
+            .get(format!("{}/user", self.auth_url()))
+            .header("apikey", &self.config.key)
+            .header("Authorization", format!("Bearer {}", session.access_token))
+            .send_tracked(&self.rate_limit, &self.circuit)
+            .await?;
+
+        if !response.status().is_success() {
+            return Err(Error::auth("Failed to fetch user for assurance level")
+                .with_fingerprint(self.config.error_fingerprint));
+        }
+
+        let user: serde_json::Value = response.json().await?;
+        let next_level = if has_verified_factor(&user) {
+            AssuranceLevel::Aal2
+        } else {
+            AssuranceLevel::Aal1
+        };
+
+        Ok(AuthenticatorAssuranceLevel {
+            current_level,
+            next_level: Some(next_level),
+            current_authentication_methods,
+        })
+    }
+
     /// Delete an MFA factor
     ///
     /// # Examples
@@ -1583,13 +4558,14 @@ impl Auth {
 
         let response = self
             .http_client
-            .delete(format!("{}/auth/v1/factors/{}", self.config.url, factor_id))
+            .delete(format!("{}/factors/{}", self.auth_url(), factor_id))
             .header("Authorization", format!("Bearer {}", session.access_token))
-            .send()
+            .send_tracked(&self.rate_limit, &self.circuit)
             .await?;
 
         if !response.status().is_success() {
-            return Err(Error::auth("Failed to delete MFA factor"));
+            return Err(Error::auth("Failed to delete MFA factor")
+                .with_fingerprint(self.config.error_fingerprint));
         }
 
         self.trigger_auth_event(AuthEvent::MfaDisabled);
@@ -1617,6 +4593,7 @@ impl Auth {
     /// # Ok(())
     /// # }
     /// ```
+    #[cfg(feature = "auth-mfa")]
     pub fn generate_totp_code(&self, secret: &str) -> Result<String> {
         debug!("Generating TOTP code for testing");
 
@@ -1718,17 +4695,15 @@ impl Auth {
     pub async fn refresh_token_advanced(&self) -> Result<Session> {
         debug!("Refreshing token with advanced handling");
 
-        let current_session = self
-            .session
-            .read()
-            .map_err(|_| Error::auth("Failed to read session"))?
-            .clone();
-
-        let session = match current_session {
-            Some(session) => session,
-            None => return Err(Error::auth("No active session to refresh")),
-        };
+        self.gated_refresh(
+            |session| self.refresh_token_advanced_request(session),
+            |session| session,
+        )
+        .await
+    }
 
+    /// Send the refresh token request with detailed error context
+    async fn refresh_token_advanced_request(&self, session: Session) -> Result<Session> {
         let request_body = serde_json::json!({
             "refresh_token": session.refresh_token
         });
@@ -1736,13 +4711,13 @@ impl Auth {
         let response = self
             .http_client
             .post(format!(
-                "{}/auth/v1/token?grant_type=refresh_token",
-                self.config.url
+                "{}/token?grant_type=refresh_token",
+                self.auth_url()
             ))
             .header("apikey", &self.config.key)
             .header("Authorization", format!("Bearer {}", &self.config.key))
             .json(&request_body)
-            .send()
+            .send_tracked(&self.rate_limit, &self.circuit)
             .await;
 
         match response {
@@ -1757,7 +4732,8 @@ impl Auth {
                             .inspect_err(|err| warn!("No session: {}", err.to_string()))
                             .ok();
 
-                    if let Some(new_session) = auth_response.session {
+                    if let Some(mut new_session) = auth_response.session {
+                        new_session.inherit_provider_tokens(&session);
                         self.set_session(new_session.clone()).await?;
                         self.trigger_auth_event(AuthEvent::TokenRefreshed);
                         info!("Token refreshed successfully");
@@ -1776,7 +4752,7 @@ impl Auth {
                             status_code: Some(status.as_u16()),
                             headers: None,
                             response_body: Some(error_text.clone()),
-                            url: Some(format!("{}/auth/v1/token", self.config.url)),
+                            url: Some(format!("{}/token", self.auth_url())),
                             method: Some("POST".to_string()),
                         }),
                         retry: if status.is_server_error() {
@@ -1788,6 +4764,7 @@ impl Auth {
                         } else {
                             None
                         },
+                        rate_limit: None,
                         metadata: std::collections::HashMap::new(),
                         timestamp: chrono::Utc::now(),
                     };
@@ -1795,9 +4772,11 @@ impl Auth {
                     Err(Error::auth_with_context(
                         format!("Token refresh failed: {} - {}", status, error_text),
                         context,
-                    ))
+                    )
+                    .with_fingerprint(self.config.error_fingerprint))
                 }
             }
+            Err(e) if e.rate_limit_info().is_some() => Err(e),
             Err(e) => {
                 let context = crate::error::ErrorContext {
                     platform: Some(crate::error::detect_platform_context()),
@@ -1805,7 +4784,7 @@ impl Auth {
                         status_code: None,
                         headers: None,
                         response_body: None,
-                        url: Some(format!("{}/auth/v1/token", self.config.url)),
+                        url: Some(format!("{}/token", self.auth_url())),
                         method: Some("POST".to_string()),
                     }),
                     retry: Some(crate::error::RetryInfo {
@@ -1813,6 +4792,7 @@ impl Auth {
                         retry_after: Some(30),
                         attempts: 0,
                     }),
+                    rate_limit: None,
                     metadata: std::collections::HashMap::new(),
                     timestamp: chrono::Utc::now(),
                 };
@@ -1820,7 +4800,8 @@ impl Auth {
                 Err(Error::auth_with_context(
                     format!("Network error during token refresh: {}", e),
                     context,
-                ))
+                )
+                .with_fingerprint(self.config.error_fingerprint))
             }
         }
     }
@@ -1927,6 +4908,225 @@ impl Auth {
             None => Ok(false),
         }
     }
+
+    /// Validation options expecting tokens minted by this project
+    ///
+    /// Accepts HS256 tokens with the `authenticated` audience issued by
+    /// `{url}/auth/v1`. Adjust with the [`JwtValidation`] builder methods.
+    pub fn jwt_validation(&self) -> JwtValidation {
+        JwtValidation::new()
+            .audience("authenticated")
+            .issuer(&ProjectUrl::unchecked(self.config.url.trim_end_matches('/')).auth_url())
+    }
+
+    /// Verify an access token's signature and claims (server-side usage)
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use supabase_lib_rs::auth::DecodingKey;
+    /// # use supabase_lib_rs::Client;
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = Client::new("https://example.supabase.co", "your-anon-key")?;
+    /// let key = DecodingKey::from_secret(b"your-jwt-secret");
+    ///
+    /// # let token = "";
+    /// let claims = client.auth().verify_jwt(token, &key, &client.auth().jwt_validation())?;
+    /// println!("Request from user {:?} with role {:?}", claims.sub, claims.role);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn verify_jwt(
+        &self,
+        token: &str,
+        key: &DecodingKey,
+        validation: &JwtValidation,
+    ) -> Result<JwtClaims> {
+        validation.verify(token, key)
+    }
+}
+
+/// localStorage-backed session persistence for browser builds
+#[cfg(all(target_arch = "wasm32", feature = "wasm"))]
+mod browser_persistence {
+    use super::Session;
+    use tracing::warn;
+    use wasm_bindgen::{closure::Closure, JsCast};
+
+    fn local_storage() -> Option<web_sys::Storage> {
+        web_sys::window()?.local_storage().ok().flatten()
+    }
+
+    /// Load a persisted session, ignoring missing or malformed entries
+    pub(super) fn load_session(key: &str) -> Option<Session> {
+        let serialized = local_storage()?.get_item(key).ok().flatten()?;
+        serde_json::from_str(&serialized)
+            .inspect_err(|err| warn!("Failed to parse persisted session: {}", err))
+            .ok()
+    }
+
+    pub(super) fn save_session(key: &str, session: &Session) {
+        let Some(storage) = local_storage() else {
+            return;
+        };
+
+        match serde_json::to_string(session) {
+            Ok(serialized) => {
+                if storage.set_item(key, &serialized).is_err() {
+                    warn!("Failed to persist session to localStorage");
+                }
+            }
+            Err(err) => warn!("Failed to serialize session: {}", err),
+        }
+    }
+
+    pub(super) fn load_item(key: &str) -> Option<String> {
+        local_storage()?.get_item(key).ok().flatten()
+    }
+
+    pub(super) fn save_item(key: &str, value: &str) {
+        if let Some(storage) = local_storage() {
+            if storage.set_item(key, value).is_err() {
+                warn!("Failed to save {} to localStorage", key);
+            }
+        }
+    }
+
+    pub(super) fn remove_session(key: &str) {
+        if let Some(storage) = local_storage() {
+            let _ = storage.remove_item(key);
+        }
+    }
+
+    /// Call `on_sign_out` when another tab removes the persisted session
+    ///
+    /// `storage` events only fire in tabs other than the one making the change.
+    pub(super) fn watch_sign_out<F>(key: &str, on_sign_out: F)
+    where
+        F: Fn() + 'static,
+    {
+        let Some(window) = web_sys::window() else {
+            return;
+        };
+
+        let key = key.to_string();
+        let closure = Closure::<dyn FnMut(web_sys::StorageEvent)>::new(
+            move |event: web_sys::StorageEvent| {
+                // A `null` key means the whole storage area was cleared
+                let matches_key = event.key().is_none_or(|k| k == key);
+                if matches_key && event.new_value().is_none() {
+                    on_sign_out();
+                }
+            },
+        );
+
+        if window
+            .add_event_listener_with_callback("storage", closure.as_ref().unchecked_ref())
+            .is_err()
+        {
+            warn!("Failed to add storage event listener");
+        }
+
+        // The listener lives for the lifetime of the page
+        closure.forget();
+    }
+}
+
+/// Popup window handling for browser OAuth flows
+#[cfg(all(target_arch = "wasm32", feature = "wasm"))]
+mod browser_popup {
+    use crate::error::{Error, Result};
+    use std::{cell::RefCell, rc::Rc};
+    use wasm_bindgen::{closure::Closure, JsCast};
+
+    const POLL_INTERVAL_MS: u32 = 250;
+    const TIMEOUT_MS: u32 = 5 * 60 * 1000;
+
+    /// Current page URL without query string or fragment
+    pub(super) fn current_page_url() -> Option<String> {
+        let location = web_sys::window()?.location();
+        Some(format!(
+            "{}{}",
+            location.origin().ok()?,
+            location.pathname().ok()?
+        ))
+    }
+
+    /// Open `url` in a popup and wait for the OAuth callback URL
+    pub(super) async fn wait_for_callback(url: &str) -> Result<String> {
+        let window = web_sys::window().ok_or_else(|| Error::platform("No window available"))?;
+        let origin = window
+            .location()
+            .origin()
+            .map_err(|_| Error::platform("Failed to read window origin"))?;
+
+        let popup = window
+            .open_with_url_and_target_and_features(
+                url,
+                "supabase_oauth",
+                "popup,width=500,height=600",
+            )
+            .ok()
+            .flatten()
+            .ok_or_else(|| Error::auth("Failed to open OAuth popup; it may have been blocked"))?;
+
+        // The callback page may post its URL instead of waiting to be polled
+        let posted: Rc<RefCell<Option<String>>> = Rc::default();
+        let listener = {
+            let posted = posted.clone();
+            let origin = origin.clone();
+            Closure::<dyn FnMut(web_sys::MessageEvent)>::new(move |event: web_sys::MessageEvent| {
+                if event.origin() != origin {
+                    return;
+                }
+                if let Some(data) = event
+                    .data()
+                    .as_string()
+                    .filter(|data| is_callback_url(data))
+                {
+                    *posted.borrow_mut() = Some(data);
+                }
+            })
+        };
+        let _ =
+            window.add_event_listener_with_callback("message", listener.as_ref().unchecked_ref());
+
+        let mut waited = 0;
+        let result = loop {
+            if let Some(callback_url) = posted.borrow_mut().take() {
+                break Ok(callback_url);
+            }
+
+            // Reading the location fails while the popup is on the provider's origin
+            if let Ok(href) = popup.location().href() {
+                if href.starts_with(&origin) && is_callback_url(&href) {
+                    break Ok(href);
+                }
+            }
+
+            if popup.closed().unwrap_or(true) {
+                break Err(Error::auth(
+                    "OAuth popup was closed before sign-in completed",
+                ));
+            }
+            if waited >= TIMEOUT_MS {
+                break Err(Error::auth("Timed out waiting for OAuth sign-in"));
+            }
+
+            gloo_timers::future::TimeoutFuture::new(POLL_INTERVAL_MS).await;
+            waited += POLL_INTERVAL_MS;
+        };
+
+        let _ = window
+            .remove_event_listener_with_callback("message", listener.as_ref().unchecked_ref());
+        let _ = popup.close();
+
+        result
+    }
+
+    fn is_callback_url(url: &str) -> bool {
+        url.contains("access_token=") || url.contains("error=")
+    }
 }
 
 #[cfg(test)]
@@ -1944,9 +5144,132 @@ mod tests {
             auth_config: crate::types::AuthConfig::default(),
             database_config: crate::types::DatabaseConfig::default(),
             storage_config: crate::types::StorageConfig::default(),
+            functions_config: crate::types::FunctionsConfig::default(),
+            error_fingerprint: true,
         })
     }
 
+    #[test]
+    fn test_oauth_provider_names() {
+        assert_eq!(OAuthProvider::Keycloak.as_str(), "keycloak");
+        assert_eq!(
+            OAuthProvider::Custom("custom:acme".to_string()).as_str(),
+            "custom:acme"
+        );
+
+        let provider: OAuthProvider = serde_json::from_str("\"gitlab\"").unwrap();
+        assert_eq!(provider, OAuthProvider::GitLab);
+
+        let provider: OAuthProvider = serde_json::from_str("\"custom:acme\"").unwrap();
+        assert_eq!(provider, OAuthProvider::Custom("custom:acme".to_string()));
+        assert_eq!(serde_json::to_string(&provider).unwrap(), "\"custom:acme\"");
+    }
+
+    #[test]
+    fn test_oauth_callback_parsing() {
+        let callback = OAuthCallback::parse(
+            "https://myapp.com/callback#access_token=abc&refresh_token=def&expires_in=3600\
+             &expires_at=1760045195&token_type=bearer&provider_token=gh-token",
+        )
+        .unwrap();
+        assert_eq!(callback.access_token, "abc");
+        assert_eq!(callback.refresh_token, "def");
+        assert_eq!(callback.expires_at, Some(1760045195));
+        assert_eq!(callback.provider_token.as_deref(), Some("gh-token"));
+        assert!(callback.provider_refresh_token.is_none());
+
+        let err = OAuthCallback::parse(
+            "https://myapp.com/callback?error=access_denied&error_description=User+denied+access",
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("User denied access"));
+
+        assert!(OAuthCallback::parse("https://myapp.com/callback").is_err());
+    }
+
+    #[test]
+    fn test_jwt_validation() {
+        use jsonwebtoken::{encode, EncodingKey, Header};
+
+        let auth = Auth::new(mock_config(), Arc::new(HttpClient::new())).unwrap();
+        let secret = b"super-secret-jwt-token";
+        let key = DecodingKey::from_secret(secret);
+        let exp = Utc::now().timestamp() + 3600;
+
+        let token = |alg: JwtAlgorithm, claims: serde_json::Value| {
+            encode(
+                &Header::new(alg),
+                &claims,
+                &EncodingKey::from_secret(secret),
+            )
+            .unwrap()
+        };
+        let valid = serde_json::json!({
+            "sub": "user-1",
+            "aud": "authenticated",
+            "iss": "https://test.supabase.co/auth/v1",
+            "exp": exp,
+            "role": "authenticated",
+        });
+
+        let claims = auth
+            .verify_jwt(
+                &token(JwtAlgorithm::HS256, valid.clone()),
+                &key,
+                &auth.jwt_validation(),
+            )
+            .unwrap();
+        assert_eq!(claims.sub.as_deref(), Some("user-1"));
+        assert_eq!(claims.role.as_deref(), Some("authenticated"));
+
+        let mut wrong_aud = valid.clone();
+        wrong_aud["aud"] = "service".into();
+        let err = auth
+            .verify_jwt(
+                &token(JwtAlgorithm::HS256, wrong_aud),
+                &key,
+                &auth.jwt_validation(),
+            )
+            .unwrap_err();
+        assert!(err.to_string().contains("audience"));
+
+        let mut wrong_iss = valid.clone();
+        wrong_iss["iss"] = "https://other.supabase.co/auth/v1".into();
+        let err = auth
+            .verify_jwt(
+                &token(JwtAlgorithm::HS256, wrong_iss),
+                &key,
+                &auth.jwt_validation(),
+            )
+            .unwrap_err();
+        assert!(err.to_string().contains("issuer"));
+
+        let err = auth
+            .verify_jwt(
+                &token(JwtAlgorithm::HS384, valid.clone()),
+                &key,
+                &auth.jwt_validation(),
+            )
+            .unwrap_err();
+        assert!(err.to_string().contains("not accepted"));
+
+        // Without audience or issuer checks only the signature and expiry matter
+        let mut no_aud = valid;
+        no_aud.as_object_mut().unwrap().remove("aud");
+        assert!(JwtValidation::new()
+            .verify(&token(JwtAlgorithm::HS256, no_aud.clone()), &key)
+            .is_ok());
+        let err = auth
+            .verify_jwt(
+                &token(JwtAlgorithm::HS256, no_aud),
+                &key,
+                &auth.jwt_validation(),
+            )
+            .unwrap_err();
+        assert!(err.to_string().contains("missing required claim"));
+    }
+
+    #[cfg(feature = "auth-mfa")]
     #[test]
     fn test_enhanced_phone_number_creation() {
         let phone = EnhancedPhoneNumber::new("+1-555-123-4567", Some("US"));
@@ -1958,6 +5281,37 @@ mod tests {
         assert!(!phone.country_code.is_empty());
     }
 
+    #[test]
+    fn test_login_identifier_detection() {
+        assert_eq!(
+            LoginIdentifier::detect(" user@example.com ").unwrap(),
+            LoginIdentifier::Email("user@example.com".to_string())
+        );
+        assert_eq!(
+            LoginIdentifier::detect("+1 (555) 123-4567").unwrap(),
+            LoginIdentifier::Phone("+15551234567".to_string())
+        );
+        assert!(LoginIdentifier::detect("username").is_err());
+    }
+
+    #[test]
+    fn test_phone_otp_request() {
+        let payload = PhoneOtpRequest {
+            phone: "+15550100".to_string(),
+            channel: OtpChannel::Whatsapp,
+            create_user: false,
+            data: None,
+        };
+        assert_eq!(
+            serde_json::to_value(&payload).unwrap(),
+            serde_json::json!({"phone": "+15550100", "channel": "whatsapp", "create_user": false})
+        );
+
+        let defaults = OtpOptions::default();
+        assert_eq!(defaults.channel, OtpChannel::Sms);
+        assert!(defaults.create_user);
+    }
+
     #[test]
     fn test_mfa_method_serialization() {
         let totp = MfaMethod::Totp;
@@ -2063,6 +5417,45 @@ mod tests {
         assert!(auth.session.is_some());
     }
 
+    #[test]
+    fn test_provider_tokens_survive_refresh() {
+        let body = serde_json::json!({
+            "access_token": "access",
+            "refresh_token": "refresh",
+            "token_type": "bearer",
+            "expires_in": 3600,
+            "expires_at": 4102444800u64,
+            "user": {
+                "id": "31815c55-f553-41f4-b54f-14d6ac60de16",
+                "aud": "authenticated",
+                "created_at": "2025-10-09T14:53:09Z",
+                "updated_at": "2025-10-09T14:53:09Z",
+                "app_metadata": {},
+                "user_metadata": {}
+            }
+        });
+
+        // Provider tokens are optional, and refresh responses omit them
+        let mut refreshed: Session = serde_json::from_value(body.clone()).unwrap();
+        assert!(refreshed.provider_token.is_none());
+
+        let mut oauth_body = body;
+        oauth_body["provider_token"] = serde_json::json!("gho_provider");
+        oauth_body["provider_refresh_token"] = serde_json::json!("ghr_provider");
+        let oauth_session: Session = serde_json::from_value(oauth_body).unwrap();
+        assert_eq!(
+            oauth_session.provider_token.as_deref(),
+            Some("gho_provider")
+        );
+
+        refreshed.inherit_provider_tokens(&oauth_session);
+        assert_eq!(refreshed.provider_token.as_deref(), Some("gho_provider"));
+        assert_eq!(
+            refreshed.provider_refresh_token.as_deref(),
+            Some("ghr_provider")
+        );
+    }
+
     #[test]
     fn test_parsing_auth_with_missing_session() {
         let json_body = r#"{
@@ -2126,6 +5519,7 @@ mod tests {
         assert!(!auth.is_authenticated());
     }
 
+    #[cfg(feature = "auth-mfa")]
     #[test]
     fn test_totp_code_generation() {
         let config = mock_config();
@@ -2150,6 +5544,7 @@ mod tests {
         }
     }
 
+    #[cfg(feature = "auth-mfa")]
     #[test]
     fn test_totp_code_generation_invalid_secret() {
         let config = mock_config();
@@ -2161,6 +5556,277 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[tokio::test]
+    async fn test_verify_phone_change_without_pending_change() {
+        let config = mock_config();
+        let http_client = Arc::new(reqwest::Client::new());
+        let auth = Auth::new(config, http_client).unwrap();
+
+        // No update_phone call yet, so there is nothing to verify
+        let result = auth.verify_phone_change("123456").await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_refresh_is_gated() {
+        use crate::mock_http::{MockServer, Response};
+
+        let user = serde_json::json!({
+            "id": Uuid::new_v4(),
+            "aud": "authenticated",
+            "created_at": "2025-01-01T00:00:00Z",
+            "updated_at": "2025-01-01T00:00:00Z",
+            "app_metadata": {},
+            "user_metadata": {}
+        });
+        let refreshed = serde_json::json!({
+            "access_token": "new-access",
+            "refresh_token": "new-refresh",
+            "token_type": "bearer",
+            "expires_in": 3600,
+            "expires_at": 4102444800u64,
+            "user": user.clone()
+        });
+
+        // The refresh response is held back until both callers are waiting
+        let (body, chunks) = tokio::sync::mpsc::unbounded_channel();
+        let server = MockServer::start([
+            Response::streamed(200, chunks).header("content-type", "application/json")
+        ])
+        .await;
+        let config = Arc::new(SupabaseConfig {
+            url: server.url(),
+            key: "test-key".to_string(),
+            ..Default::default()
+        });
+        let auth = Auth::new(config, Arc::new(reqwest::Client::new())).unwrap();
+        auth.set_session(
+            serde_json::from_value(serde_json::json!({
+                "access_token": "old-access",
+                "refresh_token": "old-refresh",
+                "token_type": "bearer",
+                "expires_in": 3600,
+                "expires_at": 4102444800u64,
+                "user": user
+            }))
+            .unwrap(),
+        )
+        .await
+        .unwrap();
+
+        let release = async {
+            while server.requests().is_empty() {
+                tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+            // The second caller reached the gate on the first poll of the join
+            body.send(refreshed.to_string().into_bytes()).unwrap();
+            drop(body);
+        };
+        let (first, second, ()) = tokio::join!(
+            auth.refresh_session(),
+            auth.refresh_token_advanced(),
+            release
+        );
+
+        assert_eq!(first.unwrap().session.unwrap().refresh_token, "new-refresh");
+        assert_eq!(second.unwrap().refresh_token, "new-refresh");
+        // The waiting caller took the refreshed session instead of refreshing again
+        assert_eq!(server.requests().len(), 1);
+        assert_eq!(auth.refresh_attempts.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_pkce_sign_in() {
+        // Example from RFC 7636, appendix B
+        assert_eq!(
+            pkce_challenge("dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk"),
+            "E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cM"
+        );
+
+        let config = Arc::new(SupabaseConfig {
+            url: "http://127.0.0.1:1".to_string(),
+            key: "test-key".to_string(),
+            ..Default::default()
+        });
+        let auth = Auth::new(config, Arc::new(reqwest::Client::new())).unwrap();
+
+        let response = auth
+            .sign_in_with_oauth(
+                OAuthProvider::GitHub,
+                Some(OAuthOptions {
+                    pkce: true,
+                    ..Default::default()
+                }),
+            )
+            .await
+            .unwrap();
+        let verifier = auth.code_verifier.read().unwrap().clone().unwrap();
+        assert_eq!(verifier.len(), 43);
+        assert!(response.url.ends_with(&format!(
+            "&code_challenge={}&code_challenge_method=s256",
+            pkce_challenge(&verifier)
+        )));
+        assert_ne!(pkce_verifier().unwrap(), verifier);
+
+        // Another client can finish the sign-in with the exported state
+        let pending = auth.export_pending_sign_in();
+        assert_eq!(pending.code_verifier.as_deref(), Some(verifier.as_str()));
+        assert_eq!(pending.provider.as_deref(), Some("github"));
+        let other = Auth::new(auth.config.clone(), Arc::new(reqwest::Client::new())).unwrap();
+        other.import_pending_sign_in(pending.clone());
+        assert_eq!(other.export_pending_sign_in(), pending);
+
+        // The verifier is spent even when the exchange fails
+        assert!(auth.exchange_code_for_session("auth-code").await.is_err());
+        assert!(auth.code_verifier.read().unwrap().is_none());
+        let err = auth
+            .exchange_code_for_session("auth-code")
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("No PKCE code verifier"));
+    }
+
+    #[tokio::test]
+    async fn test_session_from_url_records_provider() {
+        use crate::mock_http::{MockServer, Response};
+
+        let user = r#"{"id":"31815c55-f553-41f4-b54f-14d6ac60de16","aud":"authenticated",
+            "created_at":"2025-10-09T14:53:09Z","updated_at":"2025-10-09T14:53:09Z",
+            "app_metadata":{"provider":"email","providers":["email","github"]},
+            "user_metadata":{}}"#;
+        let server = MockServer::start([Response::json(user)]).await;
+
+        let config = Arc::new(SupabaseConfig {
+            url: server.url(),
+            key: "test-key".to_string(),
+            ..Default::default()
+        });
+        let auth = Auth::new(config, Arc::new(reqwest::Client::new())).unwrap();
+        auth.sign_in_with_oauth(OAuthProvider::GitHub, None)
+            .await
+            .unwrap();
+
+        let session = auth
+            .get_session_from_url(
+                "https://myapp.com/callback#access_token=access&refresh_token=refresh\
+                 &provider_token=gho_token",
+            )
+            .await
+            .unwrap();
+        assert_eq!(session.provider.as_deref(), Some("github"));
+        assert!(auth.oauth_provider.read().unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_accept_invite() {
+        use crate::mock_http::{MockServer, Response};
+
+        let user = |metadata: &str| {
+            format!(
+                r#"{{"id":"31815c55-f553-41f4-b54f-14d6ac60de16","aud":"authenticated",
+                "email":"invited@example.com","created_at":"2025-10-09T14:53:09Z",
+                "updated_at":"2025-10-09T14:53:09Z","app_metadata":{{}},"user_metadata":{}}}"#,
+                metadata
+            )
+        };
+        let responses = [
+            format!(
+                r#"{{"access_token":"invite-access","refresh_token":"invite-refresh",
+                "token_type":"bearer","expires_in":3600,"expires_at":4102444800,"user":{}}}"#,
+                user("{}")
+            ),
+            user(r#"{"display_name":"Ada"}"#),
+        ];
+
+        let server = MockServer::start(responses.map(Response::json)).await;
+
+        let config = Arc::new(SupabaseConfig {
+            url: server.url(),
+            key: "test-key".to_string(),
+            ..Default::default()
+        });
+        let auth = Auth::new(config, Arc::new(reqwest::Client::new())).unwrap();
+
+        let response = auth
+            .accept_invite(
+                "invite-hash",
+                "s3cret-pass",
+                Some(serde_json::json!({"display_name": "Ada"})),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.user.unwrap().user_metadata["display_name"], "Ada");
+        let session = auth.get_session().unwrap();
+        assert_eq!(session.access_token, "invite-access");
+        assert_eq!(session.user.user_metadata["display_name"], "Ada");
+
+        let requests = server.requests();
+        assert_eq!(requests[0].line(), "POST /auth/v1/verify");
+        assert_eq!(
+            requests[0].text(),
+            r#"{"token_hash":"invite-hash","type":"invite"}"#
+        );
+        assert_eq!(requests[1].line(), "PUT /auth/v1/user");
+        assert_eq!(
+            requests[1].header("authorization"),
+            Some("Bearer invite-access")
+        );
+        assert_eq!(
+            requests[1].text(),
+            r#"{"password":"s3cret-pass","data":{"display_name":"Ada"}}"#
+        );
+    }
+
+    #[tokio::test]
+    async fn test_auto_refresh_keeps_session_on_transient_failure() {
+        // Nothing listens on port 1, so every refresh fails without a response
+        let config = Arc::new(SupabaseConfig {
+            url: "http://127.0.0.1:1".to_string(),
+            key: "test-key".to_string(),
+            ..Default::default()
+        });
+        let auth = Auth::new(config, Arc::new(reqwest::Client::new())).unwrap();
+        auth.set_session(Session {
+            access_token: "access-token".to_string(),
+            refresh_token: "refresh-token".to_string(),
+            expires_in: 60,
+            expires_at: Utc::now() + chrono::Duration::seconds(60),
+            token_type: "bearer".to_string(),
+            user: User {
+                id: uuid::Uuid::new_v4(),
+                email: None,
+                phone: None,
+                email_confirmed_at: None,
+                phone_confirmed_at: None,
+                created_at: Utc::now(),
+                updated_at: Utc::now(),
+                last_sign_in_at: None,
+                app_metadata: serde_json::json!({}),
+                user_metadata: serde_json::json!({}),
+                aud: "authenticated".to_string(),
+                role: None,
+            },
+            provider_token: None,
+            provider_refresh_token: None,
+            provider: None,
+        })
+        .await
+        .unwrap();
+
+        let handle = auth.start_auto_refresh();
+        for _ in 0..100 {
+            if auth.refresh_attempts.load(Ordering::SeqCst) > 0 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert!(auth.refresh_attempts.load(Ordering::SeqCst) > 0);
+        assert!(handle.is_active());
+        assert_eq!(auth.get_session().unwrap().refresh_token, "refresh-token");
+
+        handle.stop();
+    }
+
     #[tokio::test]
     async fn test_token_validation_no_session() {
         let config = mock_config();
@@ -2240,6 +5906,719 @@ mod tests {
         assert!(factor.phone.is_none());
     }
 
+    #[test]
+    fn test_session_serializer() {
+        struct ReverseRefreshToken;
+
+        impl SessionSerializer for ReverseRefreshToken {
+            fn encode(&self, session: &Session) -> Result<Session> {
+                let mut stored = session.clone();
+                stored.refresh_token = session.refresh_token.chars().rev().collect();
+                Ok(stored)
+            }
+
+            fn decode(&self, mut stored: Session) -> Result<Session> {
+                stored.refresh_token = stored.refresh_token.chars().rev().collect();
+                Ok(stored)
+            }
+        }
+
+        let session: Session = serde_json::from_value(serde_json::json!({
+            "access_token": "access",
+            "refresh_token": "refresh",
+            "expires_in": 3600,
+            "expires_at": Utc::now().timestamp() + 3600,
+            "token_type": "bearer",
+            "user": {
+                "id": uuid::Uuid::new_v4(),
+                "created_at": Utc::now(),
+                "updated_at": Utc::now(),
+                "app_metadata": {},
+                "user_metadata": {"theme": "dark"},
+                "aud": "authenticated",
+            },
+        }))
+        .unwrap();
+
+        let auth = Auth::new(mock_config(), Arc::new(HttpClient::new())).unwrap();
+        assert_eq!(
+            auth.encode_session(&session).unwrap().refresh_token,
+            "refresh"
+        );
+
+        auth.set_session_serializer(Arc::new(ReverseRefreshToken));
+        let stored = auth.encode_session(&session).unwrap();
+        assert_eq!(stored.refresh_token, "hserfer");
+        assert_eq!(stored.access_token, "access");
+        assert_eq!(
+            auth.decode_session(stored).unwrap().refresh_token,
+            "refresh"
+        );
+
+        // Clones share the serializer
+        let stored = auth.clone().encode_session(&session).unwrap();
+        assert_eq!(stored.refresh_token, "hserfer");
+    }
+
+    #[test]
+    fn test_auth_settings() {
+        let settings: AuthSettings = serde_json::from_value(serde_json::json!({
+            "external": {
+                "anonymous_users": true,
+                "apple": false,
+                "email": true,
+                "github": true,
+                "google": true,
+                "phone": false,
+                "slack_oidc": true
+            },
+            "disable_signup": false,
+            "mailer_autoconfirm": true,
+            "phone_autoconfirm": false,
+            "sms_provider": "twilio",
+            "saml_enabled": false
+        }))
+        .unwrap();
+
+        assert!(settings.email_enabled());
+        assert!(!settings.phone_enabled());
+        assert!(settings.anonymous_enabled());
+        assert!(settings.mailer_autoconfirm);
+        assert_eq!(settings.sms_provider.as_deref(), Some("twilio"));
+        assert!(settings.provider_enabled(&OAuthProvider::GitHub));
+        assert!(!settings.provider_enabled(&OAuthProvider::Apple));
+        assert!(!settings.provider_enabled(&OAuthProvider::Discord));
+        assert_eq!(
+            settings.enabled_providers(),
+            vec![
+                OAuthProvider::GitHub,
+                OAuthProvider::Google,
+                OAuthProvider::Slack
+            ]
+        );
+    }
+
+    #[test]
+    fn test_web3_sign_in_message() {
+        let issued_at = chrono::DateTime::parse_from_rfc3339("2025-01-02T03:04:05Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let message = Web3SignInMessage {
+            nonce: "32891756".to_string(),
+            issued_at,
+            ..Web3SignInMessage::new(
+                "example.com",
+                "0xAb5801a7D398351b8bE11C439e05C5B3259aeC9B",
+                "https://example.com/login",
+            )
+        }
+        .statement("Sign in to Example");
+
+        assert_eq!(
+            message.to_message(Web3Chain::Ethereum),
+            "example.com wants you to sign in with your Ethereum account:\n\
+             0xAb5801a7D398351b8bE11C439e05C5B3259aeC9B\n\
+             \n\
+             Sign in to Example\n\
+             \n\
+             URI: https://example.com/login\n\
+             Version: 1\n\
+             Chain ID: 1\n\
+             Nonce: 32891756\n\
+             Issued At: 2025-01-02T03:04:05.000Z"
+        );
+
+        let solana = Web3SignInMessage {
+            statement: None,
+            ..message
+        }
+        .expires_in(chrono::Duration::minutes(5))
+        .to_message(Web3Chain::Solana);
+        assert!(solana.starts_with(
+            "example.com wants you to sign in with your Solana account:\n\
+             0xAb5801a7D398351b8bE11C439e05C5B3259aeC9B\n\nVersion: 1\n"
+        ));
+        assert!(solana.ends_with("Expiration Time: 2025-01-02T03:09:05.000Z\nNonce: 32891756"));
+
+        assert_eq!(
+            Web3Chain::Ethereum.encode_signature(&[0x0a, 0xff]),
+            "0x0aff"
+        );
+        assert_eq!(Web3Chain::Solana.encode_signature(&[0xfb, 0xff]), "-_8");
+        assert_eq!(serde_json::to_value(Web3Chain::Solana).unwrap(), "solana");
+    }
+
+    #[tokio::test]
+    async fn test_delete_account_guards() {
+        let user = User {
+            id: uuid::Uuid::new_v4(),
+            email: Some("User@Example.com".to_string()),
+            phone: Some("+15550100".to_string()),
+            email_confirmed_at: None,
+            phone_confirmed_at: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            last_sign_in_at: None,
+            app_metadata: serde_json::json!({}),
+            user_metadata: serde_json::json!({}),
+            aud: "authenticated".to_string(),
+            role: None,
+        };
+        assert!(confirmation_matches(&user, " user@example.com "));
+        assert!(confirmation_matches(&user, "15550100"));
+        assert!(!confirmation_matches(&user, "other@example.com"));
+        assert!(!confirmation_matches(&user, &user.id.to_string()));
+        assert!(!confirmation_matches(&user, ""));
+
+        let anonymous = User {
+            email: None,
+            phone: Some(String::new()),
+            ..user.clone()
+        };
+        assert!(confirmation_matches(&anonymous, &anonymous.id.to_string()));
+
+        let config = Arc::new(SupabaseConfig {
+            url: "http://localhost:54321".to_string(),
+            key: "test-key".to_string(),
+            ..Default::default()
+        });
+        let auth = Auth::new(config, Arc::new(HttpClient::new())).unwrap();
+        assert!(auth.delete_account("user@example.com").await.is_err());
+
+        auth.set_session(Session {
+            access_token: "access-token".to_string(),
+            refresh_token: "refresh-token".to_string(),
+            expires_in: 3600,
+            expires_at: Utc::now() + chrono::Duration::hours(1),
+            token_type: "bearer".to_string(),
+            user,
+            provider_token: None,
+            provider_refresh_token: None,
+            provider: None,
+        })
+        .await
+        .unwrap();
+
+        let exported = Arc::new(std::sync::atomic::AtomicBool::new(false));
+        let hook_exported = Arc::clone(&exported);
+        auth.on_before_account_deletion(move |user| {
+            let exported = Arc::clone(&hook_exported);
+            async move {
+                assert_eq!(user.email.as_deref(), Some("User@Example.com"));
+                exported.store(true, std::sync::atomic::Ordering::SeqCst);
+                Err(Error::storage("Export bucket unavailable"))
+            }
+        });
+
+        assert!(matches!(
+            auth.delete_account("someone@example.com").await,
+            Err(Error::InvalidInput { .. })
+        ));
+        assert!(!exported.load(std::sync::atomic::Ordering::SeqCst));
+
+        assert!(matches!(
+            auth.delete_account("user@example.com").await,
+            Err(Error::Storage { .. })
+        ));
+        assert!(exported.load(std::sync::atomic::Ordering::SeqCst));
+        assert!(auth.is_authenticated());
+    }
+
+    #[tokio::test]
+    async fn test_refresh_provider_token() {
+        let config = Arc::new(SupabaseConfig {
+            url: "http://localhost:54321".to_string(),
+            key: "test-key".to_string(),
+            ..Default::default()
+        });
+        let auth = Auth::new(config, Arc::new(HttpClient::new())).unwrap();
+        let user = User {
+            id: uuid::Uuid::new_v4(),
+            email: Some("user@example.com".to_string()),
+            phone: None,
+            email_confirmed_at: Some(Utc::now()),
+            phone_confirmed_at: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            last_sign_in_at: Some(Utc::now()),
+            app_metadata: serde_json::json!({"provider": "github", "providers": ["github", "google"]}),
+            user_metadata: serde_json::json!({}),
+            aud: "authenticated".to_string(),
+            role: Some("authenticated".to_string()),
+        };
+        auth.set_session(Session {
+            access_token: "access-token".to_string(),
+            refresh_token: "refresh-token".to_string(),
+            expires_in: 3600,
+            expires_at: Utc::now() + chrono::Duration::hours(1),
+            token_type: "bearer".to_string(),
+            user,
+            provider_token: Some("ya29.old".to_string()),
+            provider_refresh_token: Some("1//refresh".to_string()),
+            // GitHub is linked too, but Google issued the provider token
+            provider: Some("google".to_string()),
+        })
+        .await
+        .unwrap();
+
+        auth.on_provider_token_refresh(|provider, refresh_token| async move {
+            assert_eq!(provider, OAuthProvider::Google);
+            assert_eq!(refresh_token, "1//refresh");
+            Ok(ProviderTokens {
+                provider_token: "ya29.new".to_string(),
+                provider_refresh_token: None,
+            })
+        });
+
+        assert!(matches!(
+            auth.refresh_provider_token(OAuthProvider::GitHub).await,
+            Err(Error::InvalidInput { .. })
+        ));
+
+        let tokens = auth
+            .refresh_provider_token(OAuthProvider::Google)
+            .await
+            .unwrap();
+        assert_eq!(tokens.provider_token, "ya29.new");
+        assert_eq!(auth.provider_token().as_deref(), Some("ya29.new"));
+        // The refresh token is kept when the provider does not rotate it
+        assert_eq!(auth.provider_refresh_token().as_deref(), Some("1//refresh"));
+
+        // A session that does not record its issuer is not refreshed
+        let mut session = auth.get_session().unwrap();
+        session.provider = None;
+        auth.set_session(session).await.unwrap();
+        assert!(matches!(
+            auth.refresh_provider_token(OAuthProvider::Google).await,
+            Err(Error::Auth { .. })
+        ));
+    }
+
+    #[cfg(feature = "session-management")]
+    #[tokio::test]
+    async fn test_session_persistence() {
+        use crate::mock_http::{MockServer, Response};
+        use crate::session::{SessionManager, SessionManagerConfig};
+
+        let manager = Arc::new(SessionManager::new(SessionManagerConfig {
+            enable_cross_tab_sync: false,
+            ..Default::default()
+        }));
+        let user: User = serde_json::from_value(serde_json::json!({
+            "id": Uuid::new_v4(),
+            "aud": "authenticated",
+            "created_at": "2025-01-01T00:00:00Z",
+            "updated_at": "2025-01-01T00:00:00Z",
+            "app_metadata": {},
+            "user_metadata": {}
+        }))
+        .unwrap();
+        let session = |refresh_token: &str| Session {
+            access_token: format!("access-{}", refresh_token),
+            refresh_token: refresh_token.to_string(),
+            expires_in: 3600,
+            expires_at: Utc::now() + chrono::Duration::hours(1),
+            token_type: "bearer".to_string(),
+            user: user.clone(),
+            provider_token: None,
+            provider_refresh_token: None,
+            provider: None,
+        };
+
+        let auth = Auth::new(mock_config(), Arc::new(HttpClient::new())).unwrap();
+        auth.set_session_manager(Arc::clone(&manager));
+        auth.set_session(session("first")).await.unwrap();
+        // A refresh replaces the stored session instead of adding one
+        auth.set_session(session("second")).await.unwrap();
+        assert_eq!(manager.list_sessions().await.unwrap().len(), 1);
+
+        // The stored access token is checked with the server before use
+        let server =
+            MockServer::start([Response::json(serde_json::to_string(&user).unwrap())]).await;
+        let config = Arc::new(SupabaseConfig {
+            url: server.url(),
+            key: "test-key".to_string(),
+            ..Default::default()
+        });
+        let restarted = Auth::new(config, Arc::new(HttpClient::new())).unwrap();
+        restarted.set_session_manager(Arc::clone(&manager));
+        let events = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let recorded = Arc::clone(&events);
+        let _handle = restarted.on_auth_state_change(move |event, session| {
+            recorded
+                .lock()
+                .unwrap()
+                .push((event, session.map(|session| session.refresh_token)));
+        });
+
+        let restored = restarted.restore_stored_session().await.unwrap().unwrap();
+        assert_eq!(restored.refresh_token, "second");
+        assert_eq!(restarted.get_session().unwrap().refresh_token, "second");
+        assert_eq!(
+            *events.lock().unwrap(),
+            vec![(AuthEvent::SignedIn, Some("second".to_string()))]
+        );
+        let requests = server.requests();
+        assert_eq!(requests[0].line(), "GET /auth/v1/user");
+        assert_eq!(
+            requests[0].header("authorization"),
+            Some("Bearer access-second")
+        );
+        // The current session wins over a second restore
+        assert!(restarted.restore_stored_session().await.unwrap().is_none());
+
+        // Signing out in another tab signs this client out too, while the
+        // removal of any other session of the user does not
+        let destroyed = |session_id| crate::session::CrossTabMessage {
+            message_id: Uuid::new_v4(),
+            session_id,
+            event_type: "session_destroyed".to_string(),
+            payload: serde_json::json!({}),
+            timestamp: Utc::now(),
+            source_tab: "other-tab".to_string(),
+        };
+        manager
+            .handle_cross_tab_message(destroyed(Uuid::new_v4()))
+            .await
+            .unwrap();
+        assert!(restarted.get_session().is_ok());
+        let stored_id = manager.list_sessions().await.unwrap()[0]
+            .metadata
+            .session_id;
+        manager
+            .handle_cross_tab_message(destroyed(stored_id))
+            .await
+            .unwrap();
+        assert!(restarted.get_session().is_err());
+        assert!(manager.list_sessions().await.unwrap().is_empty());
+        assert_eq!(
+            events.lock().unwrap().last(),
+            Some(&(AuthEvent::SignedOut, None))
+        );
+
+        restarted.set_session(session("third")).await.unwrap();
+        restarted.clear_session().await.unwrap();
+        assert!(manager.list_sessions().await.unwrap().is_empty());
+    }
+
+    #[cfg(feature = "session-management")]
+    #[tokio::test]
+    async fn test_restore_refreshes_expired_session() {
+        use crate::mock_http::{MockServer, Response};
+        use crate::session::{SessionManager, SessionManagerConfig};
+
+        let user = serde_json::json!({
+            "id": Uuid::new_v4(),
+            "aud": "authenticated",
+            "created_at": "2025-01-01T00:00:00Z",
+            "updated_at": "2025-01-01T00:00:00Z",
+            "app_metadata": {},
+            "user_metadata": {}
+        });
+        let expired = Session {
+            access_token: "expired-access".to_string(),
+            refresh_token: "stored-refresh".to_string(),
+            expires_in: 3600,
+            expires_at: Utc::now() - chrono::Duration::minutes(5),
+            token_type: "bearer".to_string(),
+            user: serde_json::from_value(user.clone()).unwrap(),
+            provider_token: None,
+            provider_refresh_token: None,
+            provider: None,
+        };
+        let manager = Arc::new(SessionManager::new(SessionManagerConfig {
+            enable_cross_tab_sync: false,
+            ..Default::default()
+        }));
+        manager.store_session(expired.clone()).await.unwrap();
+
+        let server = MockServer::start([
+            Response::json(
+                serde_json::json!({
+                    "access_token": "new-access",
+                    "refresh_token": "new-refresh",
+                    "token_type": "bearer",
+                    "expires_in": 3600,
+                    "expires_at": 4102444800u64,
+                    "user": user
+                })
+                .to_string(),
+            ),
+            Response::json(r#"{"error":"invalid_grant"}"#).status(400),
+        ])
+        .await;
+        let config = Arc::new(SupabaseConfig {
+            url: server.url(),
+            key: "test-key".to_string(),
+            ..Default::default()
+        });
+
+        let auth = Auth::new(Arc::clone(&config), Arc::new(HttpClient::new())).unwrap();
+        auth.set_session_manager(Arc::clone(&manager));
+        let restored = auth.restore_stored_session().await.unwrap().unwrap();
+        assert_eq!(restored.access_token, "new-access");
+        assert_eq!(auth.get_session().unwrap().refresh_token, "new-refresh");
+        let stored = manager.list_sessions().await.unwrap();
+        assert_eq!(stored.len(), 1);
+        assert_eq!(stored[0].session.refresh_token, "new-refresh");
+
+        let requests = server.requests();
+        assert_eq!(
+            requests[0].line(),
+            "POST /auth/v1/token?grant_type=refresh_token"
+        );
+        assert_eq!(requests[0].text(), r#"{"refresh_token":"stored-refresh"}"#);
+
+        // A refresh token the server rejects is removed instead of restored
+        manager
+            .update_session(stored[0].metadata.session_id, expired)
+            .await
+            .unwrap();
+        let restarted = Auth::new(config, Arc::new(HttpClient::new())).unwrap();
+        restarted.set_session_manager(Arc::clone(&manager));
+        assert!(restarted.restore_stored_session().await.unwrap().is_none());
+        assert!(restarted.get_session().is_err());
+        assert!(manager.list_sessions().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_mfa_sign_in() {
+        use crate::mock_http::{MockServer, Response};
+        use base64::Engine;
+
+        let factor_id = Uuid::new_v4();
+        let challenge_id = Uuid::new_v4();
+        let user = serde_json::json!({
+            "id": Uuid::new_v4(),
+            "aud": "authenticated",
+            "email": "user@example.com",
+            "created_at": "2025-01-01T00:00:00Z",
+            "updated_at": "2025-01-01T00:00:00Z",
+            "app_metadata": {},
+            "user_metadata": {},
+            "factors": [
+                {
+                    "id": factor_id,
+                    "factor_type": "totp",
+                    "status": "verified",
+                    "created_at": "2025-01-01T00:00:00Z",
+                    "updated_at": "2025-01-01T00:00:00Z"
+                },
+                {
+                    "id": Uuid::new_v4(),
+                    "factor_type": "webauthn",
+                    "status": "verified",
+                    "created_at": "2025-01-01T00:00:00Z",
+                    "updated_at": "2025-01-01T00:00:00Z"
+                }
+            ]
+        });
+        let session = |aal: &str, methods: &[&str]| {
+            let encode = |value: serde_json::Value| {
+                base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(value.to_string())
+            };
+            let amr: Vec<_> = methods
+                .iter()
+                .map(|method| serde_json::json!({ "method": method, "timestamp": 1 }))
+                .collect();
+            let token = format!(
+                "{}.{}.signature",
+                encode(serde_json::json!({ "alg": "HS256", "typ": "JWT" })),
+                encode(serde_json::json!({ "exp": 4102444800u64, "aal": aal, "amr": amr }))
+            );
+            serde_json::json!({
+                "access_token": token,
+                "refresh_token": format!("refresh-{}", aal),
+                "token_type": "bearer",
+                "expires_in": 3600,
+                "expires_at": 4102444800u64,
+                "user": user.clone()
+            })
+            .to_string()
+        };
+        let challenge = serde_json::json!({
+            "id": challenge_id,
+            "factor_id": factor_id,
+            "status": "pending",
+            "challenge_type": "totp",
+            "expires_at": "2100-01-01T00:00:00Z"
+        })
+        .to_string();
+        let responses = vec![
+            session("aal1", &["password"]),
+            challenge,
+            session("aal2", &["password", "totp"]),
+            user.to_string(),
+        ];
+
+        let server = MockServer::start(responses.into_iter().map(Response::json)).await;
+
+        let config = Arc::new(SupabaseConfig {
+            url: server.url(),
+            key: "test-key".to_string(),
+            ..Default::default()
+        });
+        let auth = Auth::new(config, Arc::new(HttpClient::new())).unwrap();
+
+        let result = auth
+            .sign_in_with_email_and_password_mfa("user@example.com", "password")
+            .await
+            .unwrap();
+        let SignInResult::MfaChallengeRequired { response, factors } = result else {
+            panic!("expected an MFA challenge");
+        };
+        assert!(response.session.is_some());
+        assert_eq!(factors.len(), 1);
+        assert_eq!(factors[0].id, factor_id);
+        assert_eq!(auth.get_session().unwrap().refresh_token, "refresh-aal1");
+
+        let session = auth
+            .complete_mfa_sign_in(factor_id, "123456")
+            .await
+            .unwrap();
+        assert_eq!(session.refresh_token, "refresh-aal2");
+        assert_eq!(auth.get_session().unwrap().refresh_token, "refresh-aal2");
+
+        let level = auth.get_authenticator_assurance_level().await.unwrap();
+        assert_eq!(level.current_level, Some(AssuranceLevel::Aal2));
+        assert_eq!(level.next_level, Some(AssuranceLevel::Aal2));
+        assert_eq!(level.current_authentication_methods, ["password", "totp"]);
+        assert!(!level.needs_mfa());
+
+        let requests = server.requests();
+        assert_eq!(
+            requests[0].line(),
+            "POST /auth/v1/token?grant_type=password"
+        );
+        assert_eq!(
+            requests[1].line(),
+            format!("POST /auth/v1/factors/{}/challenge", factor_id)
+        );
+        assert_eq!(
+            requests[2].line(),
+            format!("POST /auth/v1/factors/{}/verify", factor_id)
+        );
+        assert!(requests[2].text().contains(&challenge_id.to_string()));
+        assert!(requests[2].text().contains("\"code\":\"123456\""));
+        assert_eq!(requests[3].line(), "GET /auth/v1/user");
+    }
+
+    #[tokio::test]
+    async fn test_mfa_sign_in_with_unknown_factor() {
+        use crate::mock_http::{MockServer, Response};
+        use base64::Engine;
+
+        // Only a WebAuthn factor, which MfaFactor cannot represent
+        let user = serde_json::json!({
+            "id": Uuid::new_v4(),
+            "aud": "authenticated",
+            "email": "user@example.com",
+            "created_at": "2025-01-01T00:00:00Z",
+            "updated_at": "2025-01-01T00:00:00Z",
+            "app_metadata": {},
+            "user_metadata": {},
+            "factors": [{
+                "id": Uuid::new_v4(),
+                "factor_type": "webauthn",
+                "status": "verified",
+                "created_at": "2025-01-01T00:00:00Z",
+                "updated_at": "2025-01-01T00:00:00Z"
+            }]
+        });
+        let encode = |value: serde_json::Value| {
+            base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(value.to_string())
+        };
+        let token = format!(
+            "{}.{}.signature",
+            encode(serde_json::json!({ "alg": "HS256", "typ": "JWT" })),
+            encode(serde_json::json!({ "exp": 4102444800u64, "aal": "aal1" }))
+        );
+        let session = serde_json::json!({
+            "access_token": token,
+            "refresh_token": "refresh-aal1",
+            "token_type": "bearer",
+            "expires_in": 3600,
+            "expires_at": 4102444800u64,
+            "user": user.clone()
+        });
+
+        let server = MockServer::start([
+            Response::json(session.to_string()),
+            Response::json(user.to_string()),
+        ])
+        .await;
+        let config = Arc::new(SupabaseConfig {
+            url: server.url(),
+            key: "test-key".to_string(),
+            ..Default::default()
+        });
+        let auth = Auth::new(config, Arc::new(HttpClient::new())).unwrap();
+
+        let result = auth
+            .sign_in_with_email_and_password_mfa("user@example.com", "password")
+            .await
+            .unwrap();
+        let SignInResult::MfaChallengeRequired { factors, .. } = result else {
+            panic!("expected an MFA challenge");
+        };
+        assert!(factors.is_empty());
+
+        let level = auth.get_authenticator_assurance_level().await.unwrap();
+        assert_eq!(level.next_level, Some(AssuranceLevel::Aal2));
+        assert!(level.needs_mfa());
+    }
+
+    #[tokio::test]
+    async fn test_rate_limit_headers() {
+        use crate::mock_http::{MockServer, Response};
+
+        let response = |status, remaining: &str, body: &str| {
+            Response::json(body)
+                .status(status)
+                .header("x-ratelimit-limit", "3")
+                .header("x-ratelimit-remaining", remaining)
+                .header("x-ratelimit-reset", "1700000000")
+                .header("retry-after", "60")
+        };
+        let server = MockServer::start([
+            response(200, "2", "{}"),
+            response(
+                429,
+                "0",
+                r#"{"msg":"For security purposes, you can only request this after 60 seconds."}"#,
+            ),
+        ])
+        .await;
+
+        let config = Arc::new(SupabaseConfig {
+            url: server.url(),
+            key: "test-key".to_string(),
+            ..Default::default()
+        });
+        let auth = Auth::new(config, Arc::new(HttpClient::new())).unwrap();
+        assert!(auth.last_rate_limit().is_none());
+
+        auth.sign_in_with_magic_link("user@example.com", None, None)
+            .await
+            .unwrap();
+        let info = auth.last_rate_limit().unwrap();
+        assert_eq!(info.limit, Some(3));
+        assert_eq!(info.remaining, Some(2));
+        assert_eq!(info.reset, Some(1_700_000_000));
+
+        let err = auth
+            .sign_in_with_magic_link("user@example.com", None, None)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::Auth { .. }));
+        assert!(err.is_retryable());
+        assert_eq!(err.status_code(), Some(429));
+        assert_eq!(err.retry_after(), Some(60));
+        assert_eq!(err.rate_limit_info().unwrap().remaining, Some(0));
+        assert_eq!(auth.last_rate_limit().unwrap().remaining, Some(0));
+        assert_eq!(server.requests().len(), 2);
+    }
+
     #[test]
     fn test_enhanced_session_structure() {
         let user = User {
@@ -0,0 +1,425 @@
+//! GoTrue admin API
+//!
+//! User management endpoints that bypass row level security and email
+//! confirmation. Every request is authenticated with the service role key,
+//! so only use this from trusted server code.
+//!
+//! ```rust,no_run
+//! use supabase_lib_rs::{auth::admin::AdminUserAttributes, Client};
+//!
+//! # async fn example() -> supabase_lib_rs::Result<()> {
+//! let client = Client::service("https://example.supabase.co", "service-role-key")?;
+//!
+//! let user = client
+//!     .auth()
+//!     .admin()
+//!     .create_user(AdminUserAttributes {
+//!         email: Some("user@example.com".to_string()),
+//!         password: Some("password".to_string()),
+//!         email_confirm: Some(true),
+//!         ..Default::default()
+//!     })
+//!     .await?;
+//!
+//! let page = client.auth().admin().list_users(None).await?;
+//! println!("{} of {:?} users", page.users.len(), page.pagination.total);
+//!
+//! client.auth().admin().delete_user(&user.id.to_string()).await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use super::{SendTracked, User};
+use crate::{
+    circuit::CircuitBreaker,
+    error::{Error, RateLimitInfo, Result},
+    types::{Pagination, SupabaseConfig},
+};
+use reqwest::{Client as HttpClient, Method, RequestBuilder, Response};
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, RwLock};
+use tracing::{debug, info};
+
+/// Page size GoTrue uses when none is requested
+const DEFAULT_PER_PAGE: u32 = 50;
+
+/// Admin user management, returned by [`Auth::admin`](super::Auth::admin)
+#[derive(Debug, Clone)]
+pub struct AuthAdmin {
+    http_client: Arc<HttpClient>,
+    config: Arc<SupabaseConfig>,
+    rate_limit: Arc<RwLock<Option<RateLimitInfo>>>,
+    circuit: CircuitBreaker,
+}
+
+/// Attributes for [`AuthAdmin::create_user`] and [`AuthAdmin::update_user_by_id`]
+///
+/// Unset fields are left out of the request and keep their current value.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct AdminUserAttributes {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub phone: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub password: Option<String>,
+    /// Mark the email as confirmed without sending a confirmation email
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub email_confirm: Option<bool>,
+    /// Mark the phone as confirmed without sending an SMS
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub phone_confirm: Option<bool>,
+    /// Stored in `user_metadata`, editable by the user
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user_metadata: Option<serde_json::Value>,
+    /// Stored in `app_metadata`, only editable with the service role key
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub app_metadata: Option<serde_json::Value>,
+    /// Ban duration such as `"24h"`, or `"none"` to lift a ban
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ban_duration: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub role: Option<String>,
+}
+
+/// One page of users from [`AuthAdmin::list_users`]
+#[derive(Debug, Clone)]
+pub struct UserList {
+    pub users: Vec<User>,
+    /// The requested page, with the total from the `X-Total-Count` header
+    pub pagination: Pagination,
+}
+
+#[derive(Debug, Deserialize)]
+struct UsersResponse {
+    users: Vec<User>,
+}
+
+/// Kind of link created by [`AuthAdmin::generate_link`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GenerateLinkType {
+    /// Sign up confirmation; requires a password
+    Signup,
+    Invite,
+    #[serde(rename = "magiclink")]
+    MagicLink,
+    Recovery,
+    /// Email change confirmation sent to the current address
+    EmailChangeCurrent,
+    /// Email change confirmation sent to the new address
+    EmailChangeNew,
+}
+
+/// Request for [`AuthAdmin::generate_link`]
+#[derive(Debug, Clone, Serialize)]
+pub struct GenerateLinkParams {
+    #[serde(rename = "type")]
+    pub link_type: GenerateLinkType,
+    pub email: String,
+    /// Required for [`GenerateLinkType::Signup`]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub password: Option<String>,
+    /// Required for the email change link types
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub new_email: Option<String>,
+    /// User metadata for signup and invite links
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub redirect_to: Option<String>,
+}
+
+impl GenerateLinkParams {
+    /// Link of the given type for an email address
+    pub fn new(link_type: GenerateLinkType, email: impl Into<String>) -> Self {
+        Self {
+            link_type,
+            email: email.into(),
+            password: None,
+            new_email: None,
+            data: None,
+            redirect_to: None,
+        }
+    }
+}
+
+/// Link created by [`AuthAdmin::generate_link`], without sending an email
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GenerateLinkResponse {
+    /// Link for the user to follow
+    pub action_link: String,
+    /// One-time code equivalent to the link
+    pub email_otp: String,
+    /// Token hash for verifying the link server-side
+    pub hashed_token: String,
+    pub verification_type: String,
+    pub redirect_to: String,
+    /// The user the link was created for
+    #[serde(flatten)]
+    pub user: User,
+}
+
+#[derive(Debug, Serialize)]
+struct InviteRequest<'a> {
+    email: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    data: Option<serde_json::Value>,
+}
+
+impl AuthAdmin {
+    pub(crate) fn new(
+        config: Arc<SupabaseConfig>,
+        http_client: Arc<HttpClient>,
+        rate_limit: Arc<RwLock<Option<RateLimitInfo>>>,
+        circuit: CircuitBreaker,
+    ) -> Self {
+        Self {
+            http_client,
+            config,
+            rate_limit,
+            circuit,
+        }
+    }
+
+    /// Build a request authenticated with the service role key
+    fn request(&self, method: Method, path: &str) -> Result<RequestBuilder> {
+        let key = self.config.service_role_key.as_deref().ok_or_else(|| {
+            Error::config("The admin API requires a service role key in the configuration")
+        })?;
+
+        Ok(self
+            .http_client
+            .request(
+                method,
+                format!("{}{}", self.config.project_url().auth_url(), path),
+            )
+            .header("apikey", key)
+            .bearer_auth(key))
+    }
+
+    /// Fail with the response body on a non-success status
+    async fn check(&self, response: Response, action: &str) -> Result<Response> {
+        if response.status().is_success() {
+            return Ok(response);
+        }
+
+        let status = response.status();
+        let error_msg = match response.text().await {
+            Ok(text) => text,
+            Err(_) => format!("{} failed with status: {}", action, status),
+        };
+        Err(Error::auth(error_msg).with_fingerprint(self.config.error_fingerprint))
+    }
+
+    /// List users, one page at a time
+    ///
+    /// `pagination.page` is 0-based like elsewhere in this crate; without
+    /// pagination the first page of 50 users is returned.
+    pub async fn list_users(&self, pagination: Option<Pagination>) -> Result<UserList> {
+        let (page, per_page) = pagination
+            .map(|p| (p.page, p.per_page))
+            .unwrap_or((0, DEFAULT_PER_PAGE));
+        debug!("Listing users, page {} of size {}", page, per_page);
+
+        let response = self
+            .request(Method::GET, "/admin/users")?
+            .query(&[("page", page + 1), ("per_page", per_page)])
+            .send_tracked(&self.rate_limit, &self.circuit)
+            .await?;
+        let response = self.check(response, "Listing users").await?;
+
+        let total = response
+            .headers()
+            .get("x-total-count")
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.parse::<u64>().ok());
+        let body: UsersResponse = response.json().await?;
+        let has_more = total.map(|total| u64::from(page + 1) * u64::from(per_page) < total);
+
+        Ok(UserList {
+            users: body.users,
+            pagination: Pagination {
+                page,
+                per_page,
+                total,
+                has_more,
+            },
+        })
+    }
+
+    /// Get a user by ID
+    pub async fn get_user_by_id(&self, user_id: &str) -> Result<User> {
+        debug!("Getting user {}", user_id);
+
+        let response = self
+            .request(Method::GET, &format!("/admin/users/{}", user_id))?
+            .send_tracked(&self.rate_limit, &self.circuit)
+            .await?;
+        let response = self.check(response, "Getting user").await?;
+
+        Ok(response.json().await?)
+    }
+
+    /// Create a user
+    ///
+    /// No confirmation email is sent; set `email_confirm` to let the user
+    /// sign in right away.
+    pub async fn create_user(&self, attributes: AdminUserAttributes) -> Result<User> {
+        debug!("Creating user");
+
+        let response = self
+            .request(Method::POST, "/admin/users")?
+            .json(&attributes)
+            .send_tracked(&self.rate_limit, &self.circuit)
+            .await?;
+        let response = self.check(response, "User creation").await?;
+
+        let user: User = response.json().await?;
+        info!("Created user {}", user.id);
+        Ok(user)
+    }
+
+    /// Update a user's attributes
+    pub async fn update_user_by_id(
+        &self,
+        user_id: &str,
+        attributes: AdminUserAttributes,
+    ) -> Result<User> {
+        debug!("Updating user {}", user_id);
+
+        let response = self
+            .request(Method::PUT, &format!("/admin/users/{}", user_id))?
+            .json(&attributes)
+            .send_tracked(&self.rate_limit, &self.circuit)
+            .await?;
+        let response = self.check(response, "User update").await?;
+
+        Ok(response.json().await?)
+    }
+
+    /// Delete a user
+    pub async fn delete_user(&self, user_id: &str) -> Result<()> {
+        debug!("Deleting user {}", user_id);
+
+        let response = self
+            .request(Method::DELETE, &format!("/admin/users/{}", user_id))?
+            .send_tracked(&self.rate_limit, &self.circuit)
+            .await?;
+        self.check(response, "User deletion").await?;
+
+        info!("Deleted user {}", user_id);
+        Ok(())
+    }
+
+    /// Invite a user by email
+    ///
+    /// Sends an invite link; `data` is stored in the new user's
+    /// `user_metadata`.
+    pub async fn invite_user_by_email(
+        &self,
+        email: &str,
+        data: Option<serde_json::Value>,
+        redirect_to: Option<String>,
+    ) -> Result<User> {
+        debug!("Inviting user with email: {}", email);
+
+        let mut request = self
+            .request(Method::POST, "/invite")?
+            .json(&InviteRequest { email, data });
+        if let Some(redirect_to) = redirect_to {
+            request = request.query(&[("redirect_to", redirect_to)]);
+        }
+        let response = self
+            .check(
+                request
+                    .send_tracked(&self.rate_limit, &self.circuit)
+                    .await?,
+                "Invite",
+            )
+            .await?;
+
+        Ok(response.json().await?)
+    }
+
+    /// Generate an email link without sending it
+    ///
+    /// Useful for sending the email through your own provider.
+    pub async fn generate_link(&self, params: GenerateLinkParams) -> Result<GenerateLinkResponse> {
+        debug!("Generating {:?} link", params.link_type);
+
+        let response = self
+            .request(Method::POST, "/admin/generate_link")?
+            .json(&params)
+            .send_tracked(&self.rate_limit, &self.circuit)
+            .await?;
+        let response = self.check(response, "Link generation").await?;
+
+        Ok(response.json().await?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock_http::{MockServer, Response};
+
+    #[tokio::test]
+    async fn test_admin_requests_use_service_role_key() {
+        let admin = AuthAdmin::new(
+            Arc::new(SupabaseConfig {
+                url: "http://127.0.0.1:1".to_string(),
+                key: "anon-key".to_string(),
+                ..Default::default()
+            }),
+            Arc::new(HttpClient::new()),
+            Arc::default(),
+            CircuitBreaker::new("auth", None),
+        );
+        let err = admin.delete_user("some-id").await.unwrap_err();
+        assert!(err.to_string().contains("service role key"));
+
+        let server =
+            MockServer::start([Response::json(r#"{"users":[],"aud":"authenticated"}"#)
+                .header("x-total-count", "45")])
+            .await;
+
+        let admin = AuthAdmin::new(
+            Arc::new(SupabaseConfig {
+                url: server.url(),
+                key: "anon-key".to_string(),
+                service_role_key: Some("service-key".to_string()),
+                ..Default::default()
+            }),
+            Arc::new(HttpClient::new()),
+            Arc::default(),
+            CircuitBreaker::new("auth", None),
+        );
+        let page = admin
+            .list_users(Some(Pagination {
+                page: 1,
+                per_page: 20,
+                total: None,
+                has_more: None,
+            }))
+            .await
+            .unwrap();
+        assert!(page.users.is_empty());
+        assert_eq!(page.pagination.total, Some(45));
+        assert_eq!(page.pagination.has_more, Some(true));
+
+        let request = &server.requests()[0];
+        assert_eq!(
+            request.line(),
+            "GET /auth/v1/admin/users?page=2&per_page=20"
+        );
+        assert_eq!(request.header("apikey"), Some("service-key"));
+        assert_eq!(request.header("authorization"), Some("Bearer service-key"));
+
+        let params = GenerateLinkParams::new(GenerateLinkType::MagicLink, "user@example.com");
+        assert_eq!(
+            serde_json::to_value(&params).unwrap(),
+            serde_json::json!({"type": "magiclink", "email": "user@example.com"})
+        );
+    }
+}
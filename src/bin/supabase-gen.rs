@@ -0,0 +1,122 @@
+//! Generate Rust models from a Supabase project's database schema
+//!
+//! ```text
+//! supabase-gen --url https://your-project.supabase.co --key <service-role-key> --output src/models.rs
+//! supabase-gen --input openapi.json --camel-case
+//! ```
+//!
+//! `--url` and `--key` default to the `SUPABASE_URL` and `SUPABASE_KEY`
+//! environment variables. Without `--output` the code is written to stdout.
+
+use std::process::ExitCode;
+use supabase_lib_rs::{
+    codegen::generate_models_with_casing, database::Method, types::FieldCasing, Client,
+};
+
+const USAGE: &str = "\
+Usage: supabase-gen [OPTIONS]
+
+Options:
+  --url <URL>        Project URL (default: $SUPABASE_URL)
+  --key <KEY>        API key; the service role key also sees tables hidden by RLS (default: $SUPABASE_KEY)
+  --schema <NAME>    Database schema to read (default: public)
+  --input <FILE>     Read a saved OpenAPI document instead of fetching it
+  --output <FILE>    Write the generated code to FILE instead of stdout
+  --camel-case       Serialize fields in camelCase, for clients with FieldCasing::CamelCase
+  -h, --help         Print this help";
+
+#[derive(Debug, Default)]
+struct Args {
+    url: Option<String>,
+    key: Option<String>,
+    schema: Option<String>,
+    input: Option<String>,
+    output: Option<String>,
+    camel_case: bool,
+}
+
+fn parse_args() -> Result<Option<Args>, String> {
+    let mut args = Args::default();
+    let mut iter = std::env::args().skip(1);
+
+    while let Some(arg) = iter.next() {
+        let slot = match arg.as_str() {
+            "-h" | "--help" => return Ok(None),
+            "--camel-case" => {
+                args.camel_case = true;
+                continue;
+            }
+            "--url" => &mut args.url,
+            "--key" => &mut args.key,
+            "--schema" => &mut args.schema,
+            "--input" => &mut args.input,
+            "--output" => &mut args.output,
+            other => return Err(format!("unknown argument: {}", other)),
+        };
+        *slot = Some(
+            iter.next()
+                .ok_or_else(|| format!("{} needs a value", arg))?,
+        );
+    }
+
+    args.url = args.url.or_else(|| std::env::var("SUPABASE_URL").ok());
+    args.key = args.key.or_else(|| std::env::var("SUPABASE_KEY").ok());
+    Ok(Some(args))
+}
+
+async fn load_spec(args: &Args) -> Result<serde_json::Value, Box<dyn std::error::Error>> {
+    if let Some(input) = &args.input {
+        return Ok(serde_json::from_str(&std::fs::read_to_string(input)?)?);
+    }
+
+    let (Some(url), Some(key)) = (&args.url, &args.key) else {
+        return Err("--url and --key (or SUPABASE_URL and SUPABASE_KEY) are required".into());
+    };
+
+    let client = Client::new(url, key)?;
+    let schema = args.schema.as_deref().unwrap_or("public");
+    let response = client
+        .database()
+        .request_with_headers(Method::GET, "/", &[], None, &[("Accept-Profile", schema)])
+        .await?;
+
+    Ok(response.data)
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let args = match parse_args() {
+        Ok(Some(args)) => args,
+        Ok(None) => {
+            println!("{}", USAGE);
+            return ExitCode::SUCCESS;
+        }
+        Err(err) => {
+            eprintln!("error: {}\n\n{}", err, USAGE);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let result = async {
+        let casing = if args.camel_case {
+            FieldCasing::CamelCase
+        } else {
+            FieldCasing::SnakeCase
+        };
+        let source = generate_models_with_casing(&load_spec(&args).await?, casing)?;
+        match &args.output {
+            Some(output) => std::fs::write(output, source)?,
+            None => print!("{}", source),
+        }
+        Ok::<_, Box<dyn std::error::Error>>(())
+    }
+    .await;
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("error: {}", err);
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,397 @@
+//! Circuit breakers that fail fast while a Supabase service is down
+//!
+//! Database, GraphQL, storage and functions each have a breaker, enabled with
+//! [`HttpConfig::circuit_breaker`](crate::types::HttpConfig::circuit_breaker).
+//! After `failure_threshold` consecutive failures (connection errors,
+//! timeouts or 5xx responses) the circuit opens and requests to that service
+//! return [`Error::CircuitOpen`] without touching the network. Once
+//! `open_duration` has passed, one probe request is let through: success
+//! closes the circuit, failure opens it again. Each attempt of a
+//! [retried](crate::retry) request counts as a request of its own.
+//!
+//! Edge Functions share one breaker, so their 5xx responses, which come from
+//! the code of a single function, never count as failures there; only
+//! failures to reach the functions runtime do.
+//!
+//! ```rust,no_run
+//! use std::time::Duration;
+//! use supabase_lib_rs::{
+//!     circuit::{CircuitBreakerConfig, CircuitState},
+//!     types::{HttpConfig, SupabaseConfig},
+//!     Client, Error,
+//! };
+//!
+//! # async fn example() -> supabase_lib_rs::Result<()> {
+//! let client = Client::new_with_config(SupabaseConfig {
+//!     url: "https://example.supabase.co".to_string(),
+//!     key: "your-anon-key".to_string(),
+//!     http_config: HttpConfig {
+//!         circuit_breaker: Some(CircuitBreakerConfig {
+//!             failure_threshold: 3,
+//!             open_duration: Duration::from_secs(10),
+//!         }),
+//!         ..Default::default()
+//!     },
+//!     ..Default::default()
+//! })?;
+//!
+//! match client.database().from("posts").execute::<serde_json::Value>().await {
+//!     Err(Error::CircuitOpen { retry_after, .. }) => {
+//!         println!("Database unavailable, retry in {:?}", retry_after);
+//!     }
+//!     result => println!("{:?}", result?),
+//! }
+//! assert_eq!(client.storage().circuit_breaker().state(), CircuitState::Closed);
+//! # Ok(())
+//! # }
+//! ```
+
+// Breakers exist only with the services that send requests through them
+#[cfg(any(
+    feature = "auth-core",
+    feature = "database",
+    feature = "storage-core",
+    feature = "functions"
+))]
+use crate::{
+    error::{Error, Result},
+    retry::{self, RetryPolicy},
+};
+#[cfg(any(
+    feature = "auth-core",
+    feature = "database",
+    feature = "storage-core",
+    feature = "functions"
+))]
+use chrono::{DateTime, Utc};
+#[cfg(any(
+    feature = "auth-core",
+    feature = "database",
+    feature = "storage-core",
+    feature = "functions"
+))]
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+#[cfg(any(
+    feature = "auth-core",
+    feature = "database",
+    feature = "storage-core",
+    feature = "functions"
+))]
+use tracing::{info, warn};
+
+/// Circuit breaker settings, shared by all services
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CircuitBreakerConfig {
+    /// Consecutive failures that open the circuit
+    pub failure_threshold: u32,
+    /// How long an open circuit fails fast before a probe is let through
+    pub open_duration: Duration,
+}
+
+impl Default for CircuitBreakerConfig {
+    fn default() -> Self {
+        Self {
+            failure_threshold: 5,
+            open_duration: Duration::from_secs(30),
+        }
+    }
+}
+
+/// State of a [`CircuitBreaker`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CircuitState {
+    /// Requests are sent normally
+    Closed,
+    /// Requests fail fast with [`Error::CircuitOpen`]
+    Open,
+    /// A probe request decides whether the circuit closes again
+    HalfOpen,
+}
+
+/// Circuit breaker for one service, shared by all clones of its client
+#[cfg(any(
+    feature = "auth-core",
+    feature = "database",
+    feature = "storage-core",
+    feature = "functions"
+))]
+#[derive(Debug, Clone)]
+pub struct CircuitBreaker {
+    service: &'static str,
+    config: Option<CircuitBreakerConfig>,
+    retry: Option<RetryPolicy>,
+    /// Whether 5xx responses count as failures
+    server_errors: bool,
+    /// Whether errors carry the environment fingerprint
+    fingerprint: bool,
+    inner: Arc<Mutex<Inner>>,
+}
+
+#[cfg(any(
+    feature = "auth-core",
+    feature = "database",
+    feature = "storage-core",
+    feature = "functions"
+))]
+#[derive(Debug)]
+struct Inner {
+    state: CircuitState,
+    failures: u32,
+    opened_at: DateTime<Utc>,
+    /// Start of the probe in flight, while half-open
+    probe_started: Option<DateTime<Utc>>,
+}
+
+#[cfg(any(
+    feature = "auth-core",
+    feature = "database",
+    feature = "storage-core",
+    feature = "functions"
+))]
+impl CircuitBreaker {
+    /// A breaker for `service`; without `config` every request is let through
+    pub(crate) fn new(service: &'static str, config: Option<CircuitBreakerConfig>) -> Self {
+        Self {
+            service,
+            config,
+            retry: None,
+            server_errors: true,
+            fingerprint: true,
+            inner: Arc::new(Mutex::new(Inner {
+                state: CircuitState::Closed,
+                failures: 0,
+                opened_at: Utc::now(),
+                probe_started: None,
+            })),
+        }
+    }
+
+    /// Retry failed requests sent through this breaker according to `policy`
+    pub(crate) fn with_retry(mut self, policy: Option<RetryPolicy>) -> Self {
+        self.retry = policy;
+        self
+    }
+
+    /// Add the environment fingerprint to errors only when `enabled`
+    pub(crate) fn with_fingerprint(mut self, enabled: bool) -> Self {
+        self.fingerprint = enabled;
+        self
+    }
+
+    /// Whether errors carry the environment fingerprint
+    #[cfg(any(feature = "auth-core", feature = "storage-core"))]
+    pub(crate) fn fingerprint(&self) -> bool {
+        self.fingerprint
+    }
+
+    /// Let 5xx responses through without counting them as failures
+    #[cfg(feature = "functions")]
+    pub(crate) fn ignore_server_errors(mut self) -> Self {
+        self.server_errors = false;
+        self
+    }
+
+    /// Name of the service this breaker protects
+    pub fn service(&self) -> &str {
+        self.service
+    }
+
+    /// Current state, always `Closed` when circuit breaking is disabled
+    pub fn state(&self) -> CircuitState {
+        self.lock().state
+    }
+
+    /// Close the circuit and forget past failures
+    pub fn reset(&self) {
+        let mut inner = self.lock();
+        inner.state = CircuitState::Closed;
+        inner.failures = 0;
+        inner.probe_started = None;
+    }
+
+    /// Send `request` unless the circuit is open, recording the outcome
+    pub(crate) async fn send(&self, request: reqwest::RequestBuilder) -> Result<reqwest::Response> {
+        retry::send(request, self.retry.as_ref(), Some(self))
+            .await
+            .map_err(|e| e.with_fingerprint(self.fingerprint))
+    }
+
+    /// Record the outcome of a request let through by [`acquire`](Self::acquire)
+    pub(crate) fn observe(&self, result: &std::result::Result<reqwest::Response, reqwest::Error>) {
+        match result {
+            Ok(response) => {
+                self.record(!(self.server_errors && response.status().is_server_error()))
+            }
+            // The request was never sent, so it says nothing about the service
+            Err(e) if e.is_builder() => self.lock().probe_started = None,
+            Err(_) => self.record(false),
+        }
+    }
+
+    /// Check whether a request may be sent now
+    pub(crate) fn acquire(&self) -> Result<()> {
+        let Some(config) = self.config else {
+            return Ok(());
+        };
+
+        let now = Utc::now();
+        let open_duration =
+            chrono::Duration::from_std(config.open_duration).unwrap_or(chrono::Duration::MAX);
+        let mut inner = self.lock();
+
+        match inner.state {
+            CircuitState::Closed => Ok(()),
+            CircuitState::Open => {
+                let elapsed = now - inner.opened_at;
+                if elapsed >= open_duration {
+                    inner.state = CircuitState::HalfOpen;
+                    inner.probe_started = Some(now);
+                    Ok(())
+                } else {
+                    Err(Error::circuit_open(
+                        self.service,
+                        (open_duration - elapsed).to_std().ok(),
+                    ))
+                }
+            }
+            // A probe that never reported back (e.g. a dropped future) is
+            // replaced after `open_duration`
+            CircuitState::HalfOpen => match inner.probe_started {
+                Some(started) if now - started < open_duration => {
+                    Err(Error::circuit_open(self.service, None))
+                }
+                _ => {
+                    inner.probe_started = Some(now);
+                    Ok(())
+                }
+            },
+        }
+    }
+
+    /// Record whether a request let through by [`acquire`](Self::acquire) succeeded
+    fn record(&self, success: bool) {
+        let Some(config) = self.config else {
+            return;
+        };
+
+        let mut inner = self.lock();
+        if success {
+            if inner.state != CircuitState::Closed {
+                info!("Circuit for {} closed", self.service);
+            }
+            inner.state = CircuitState::Closed;
+            inner.failures = 0;
+            inner.probe_started = None;
+            return;
+        }
+
+        inner.failures = inner.failures.saturating_add(1);
+        if inner.state == CircuitState::HalfOpen || inner.failures >= config.failure_threshold {
+            if inner.state != CircuitState::Open {
+                warn!(
+                    "Circuit for {} opened after {} consecutive failures",
+                    self.service, inner.failures
+                );
+            }
+            inner.state = CircuitState::Open;
+            inner.opened_at = Utc::now();
+            inner.probe_started = None;
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Inner> {
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(all(
+    test,
+    any(
+        feature = "auth-core",
+        feature = "database",
+        feature = "storage-core",
+        feature = "functions"
+    )
+))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_circuit_state_transitions() {
+        let breaker = CircuitBreaker::new(
+            "database",
+            Some(CircuitBreakerConfig {
+                failure_threshold: 2,
+                open_duration: Duration::from_millis(50),
+            }),
+        );
+
+        breaker.acquire().unwrap();
+        breaker.record(false);
+        breaker.record(true);
+        breaker.record(false);
+        assert_eq!(breaker.state(), CircuitState::Closed);
+        breaker.record(false);
+        assert_eq!(breaker.state(), CircuitState::Open);
+
+        match breaker.acquire().unwrap_err() {
+            Error::CircuitOpen {
+                service,
+                retry_after,
+            } => {
+                assert_eq!(service, "database");
+                assert!(retry_after.unwrap() <= Duration::from_millis(50));
+            }
+            other => panic!("unexpected error: {:?}", other),
+        }
+
+        // One probe after the open period, and only one at a time
+        std::thread::sleep(Duration::from_millis(60));
+        breaker.acquire().unwrap();
+        assert_eq!(breaker.state(), CircuitState::HalfOpen);
+        assert!(breaker.acquire().is_err());
+
+        breaker.record(false);
+        assert_eq!(breaker.state(), CircuitState::Open);
+
+        std::thread::sleep(Duration::from_millis(60));
+        breaker.acquire().unwrap();
+        breaker.record(true);
+        assert_eq!(breaker.state(), CircuitState::Closed);
+        breaker.acquire().unwrap();
+
+        let disabled = CircuitBreaker::new("storage", None);
+        for _ in 0..10 {
+            disabled.record(false);
+        }
+        assert_eq!(disabled.state(), CircuitState::Closed);
+    }
+
+    #[tokio::test]
+    async fn test_connection_failures_open_circuit() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/rest/v1/posts", listener.local_addr().unwrap());
+        drop(listener);
+
+        let breaker = CircuitBreaker::new(
+            "database",
+            Some(CircuitBreakerConfig {
+                failure_threshold: 1,
+                open_duration: Duration::from_secs(60),
+            }),
+        );
+        let client = reqwest::Client::new();
+
+        let err = breaker.send(client.get(&url)).await.unwrap_err();
+        assert!(!matches!(err, Error::CircuitOpen { .. }));
+        assert_eq!(breaker.state(), CircuitState::Open);
+
+        let err = breaker.send(client.get(&url)).await.unwrap_err();
+        assert!(matches!(err, Error::CircuitOpen { .. }));
+        assert!(err.is_retryable());
+
+        breaker.reset();
+        assert_eq!(breaker.state(), CircuitState::Closed);
+    }
+}
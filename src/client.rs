@@ -2,16 +2,22 @@
 
 use crate::{
     error::{Error, Result},
-    types::{AuthConfig, DatabaseConfig, HttpConfig, StorageConfig, SupabaseConfig},
+    types::{
+        AuthConfig, DatabaseConfig, FunctionsConfig, HttpConfig, ProjectUrl, StorageConfig,
+        SupabaseConfig,
+    },
 };
 
-#[cfg(feature = "auth")]
+#[cfg(feature = "auth-core")]
 use crate::auth::Auth;
 
 #[cfg(feature = "database")]
 use crate::database::Database;
 
-#[cfg(feature = "storage")]
+#[cfg(feature = "graphql")]
+use crate::graphql::GraphQL;
+
+#[cfg(feature = "storage-core")]
 use crate::storage::Storage;
 
 #[cfg(feature = "functions")]
@@ -22,10 +28,36 @@ use crate::realtime::Realtime;
 use reqwest::{header::HeaderMap, Client as HttpClient};
 use std::{collections::HashMap, sync::Arc};
 
+#[cfg(any(
+    feature = "graphql",
+    feature = "storage-core",
+    feature = "functions",
+    feature = "realtime"
+))]
+use std::sync::OnceLock;
+
 #[cfg(not(target_arch = "wasm32"))]
 use std::time::Duration;
 use tracing::{debug, error, info};
-use url::Url;
+
+/// Compile-time guard that keeps [`Client::service`] out of WASM builds
+///
+/// Sealed: no type can implement it, so the call never compiles.
+#[cfg(target_arch = "wasm32")]
+#[doc(hidden)]
+#[diagnostic::on_unimplemented(
+    message = "`Client::service` is not available for wasm32 targets",
+    label = "the service role key must never be shipped to a browser",
+    note = "use `Client::new` with the anon key and Row Level Security instead"
+)]
+pub trait ServiceKeyOnServer: sealed::Sealed {}
+
+#[cfg(target_arch = "wasm32")]
+mod sealed {
+    /// Not reachable from other crates, which keeps them from implementing
+    /// [`ServiceKeyOnServer`](super::ServiceKeyOnServer)
+    pub trait Sealed {}
+}
 
 /// Main Supabase client for interacting with all services
 #[derive(Debug, Clone)]
@@ -34,22 +66,27 @@ pub struct Client {
     http_client: Arc<HttpClient>,
     /// Client configuration
     config: Arc<SupabaseConfig>,
+    /// Validated project URL
+    project_url: ProjectUrl,
     /// Authentication module
-    #[cfg(feature = "auth")]
+    #[cfg(feature = "auth-core")]
     auth: Auth,
     /// Database module
     #[cfg(feature = "database")]
     database: Database,
-    /// Storage module
-    #[cfg(feature = "storage")]
-    storage: Storage,
-
+    /// GraphQL module, created on first access and shared between clones
+    #[cfg(feature = "graphql")]
+    graphql: Arc<OnceLock<GraphQL>>,
+    /// Storage module, created on first access and shared between clones
+    #[cfg(feature = "storage-core")]
+    storage: Arc<OnceLock<Storage>>,
+    /// Functions module, created on first access and shared between clones
     #[cfg(feature = "functions")]
-    functions: Functions,
+    functions: Arc<OnceLock<Functions>>,
 
-    /// Realtime module
+    /// Realtime module, created on first access and shared between clones
     #[cfg(feature = "realtime")]
-    realtime: Realtime,
+    realtime: Arc<OnceLock<Realtime>>,
 }
 
 impl Client {
@@ -77,6 +114,8 @@ impl Client {
             auth_config: AuthConfig::default(),
             database_config: DatabaseConfig::default(),
             storage_config: StorageConfig::default(),
+            functions_config: FunctionsConfig::default(),
+            error_fingerprint: true,
         };
 
         Self::new_with_config(config)
@@ -115,11 +154,66 @@ impl Client {
             auth_config: AuthConfig::default(),
             database_config: DatabaseConfig::default(),
             storage_config: StorageConfig::default(),
+            functions_config: FunctionsConfig::default(),
+            error_fingerprint: true,
+        };
+
+        Self::new_with_config(config)
+    }
+
+    /// Create a client for a backend service authenticated by the service role key
+    ///
+    /// Every request sends the service role key as both `apikey` and bearer
+    /// token, so Row Level Security is bypassed and storage, database and
+    /// admin endpoints act with full privileges. The client keeps no user
+    /// session: tokens are neither persisted nor refreshed, and sessions
+    /// returned by sign-in calls are handed back to the caller without being
+    /// stored.
+    ///
+    /// The service role key must never reach a browser, so this constructor
+    /// does not compile for `wasm32` targets.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use supabase_lib_rs::Client;
+    ///
+    /// let key = std::env::var("SUPABASE_SERVICE_ROLE_KEY").unwrap_or_default();
+    /// let client = Client::service("https://your-project.supabase.co", &key)?;
+    /// assert!(client.is_service());
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn service(url: &str, service_key: &str) -> Result<Self> {
+        let config = SupabaseConfig {
+            url: url.to_string(),
+            key: service_key.to_string(),
+            service_role_key: Some(service_key.to_string()),
+            auth_config: AuthConfig {
+                auto_refresh_token: false,
+                persist_session: false,
+                ..AuthConfig::default()
+            },
+            ..SupabaseConfig::default()
         };
 
         Self::new_with_config(config)
     }
 
+    /// Service role clients are not available in WASM builds
+    ///
+    /// Calling this fails to compile: a service role key shipped to a browser
+    /// gives every visitor full access to the project.
+    #[cfg(target_arch = "wasm32")]
+    pub fn service<K: ServiceKeyOnServer>(_url: &str, _service_key: K) -> Result<Self> {
+        unreachable!("ServiceKeyOnServer has no implementations")
+    }
+
+    /// Whether this client authenticates with the service role key
+    pub fn is_service(&self) -> bool {
+        self.config.is_service_role()
+    }
+
     /// Create a new Supabase client with custom configuration
     ///
     /// # Arguments
@@ -139,6 +233,8 @@ impl Client {
     ///     auth_config: AuthConfig::default(),
     ///     database_config: DatabaseConfig::default(),
     ///     storage_config: StorageConfig::default(),
+    ///     functions_config: FunctionsConfig::default(),
+    ///     error_fingerprint: true,
     /// };
     ///
     /// let client = Client::new_with_config(config)?;
@@ -146,51 +242,51 @@ impl Client {
     /// ```
     pub fn new_with_config(config: SupabaseConfig) -> Result<Self> {
         // Validate URL
-        let _base_url =
-            Url::parse(&config.url).map_err(|e| Error::config(format!("Invalid URL: {}", e)))?;
+        let project_url = ProjectUrl::parse(&config.url)?;
 
-        debug!("Creating Supabase client for URL: {}", config.url);
+        debug!(
+            "Creating Supabase client for project: {}",
+            project_url.project_ref().unwrap_or(project_url.as_str())
+        );
 
         // Build HTTP client
         let http_client = Arc::new(Self::build_http_client(&config)?);
         let config = Arc::new(config);
 
         // Initialize modules conditionally based on features
-        #[cfg(feature = "auth")]
+        #[cfg(feature = "auth-core")]
         let auth = Auth::new(Arc::clone(&config), Arc::clone(&http_client))?;
 
         #[cfg(feature = "database")]
         let database = Database::new(Arc::clone(&config), Arc::clone(&http_client))?;
 
-        #[cfg(feature = "storage")]
-        let storage = Storage::new(Arc::clone(&config), Arc::clone(&http_client))?;
-
-        #[cfg(feature = "functions")]
-        let functions = Functions::new(Arc::clone(&config), Arc::clone(&http_client))?;
-
-        #[cfg(feature = "realtime")]
-        let realtime = Realtime::new(Arc::clone(&config))?;
+        // Send the signed-in user's access token instead of the API key
+        #[cfg(all(feature = "auth-core", feature = "database"))]
+        let database = database.with_token_provider(auth.token_provider());
 
         info!("Supabase client initialized successfully");
 
         Ok(Self {
             http_client,
             config,
-            #[cfg(feature = "auth")]
+            project_url,
+            #[cfg(feature = "auth-core")]
             auth,
             #[cfg(feature = "database")]
             database,
-            #[cfg(feature = "storage")]
-            storage,
+            #[cfg(feature = "graphql")]
+            graphql: Arc::new(OnceLock::new()),
+            #[cfg(feature = "storage-core")]
+            storage: Arc::new(OnceLock::new()),
             #[cfg(feature = "functions")]
-            functions,
+            functions: Arc::new(OnceLock::new()),
             #[cfg(feature = "realtime")]
-            realtime,
+            realtime: Arc::new(OnceLock::new()),
         })
     }
 
     /// Get the authentication module
-    #[cfg(feature = "auth")]
+    #[cfg(feature = "auth-core")]
     pub fn auth(&self) -> &Auth {
         &self.auth
     }
@@ -201,22 +297,72 @@ impl Client {
         &self.database
     }
 
-    /// Get the storage module
-    #[cfg(feature = "storage")]
+    /// Get the GraphQL module, created on first access
+    #[cfg(feature = "graphql")]
+    pub fn graphql(&self) -> &GraphQL {
+        self.graphql.get_or_init(|| {
+            let graphql =
+                GraphQL::with_http_client(Arc::clone(&self.config), Arc::clone(&self.http_client));
+
+            #[cfg(feature = "auth-core")]
+            let graphql = graphql.with_token_provider(self.auth.token_provider());
+
+            graphql
+        })
+    }
+
+    /// Get the storage module, created on first access
+    #[cfg(feature = "storage-core")]
     pub fn storage(&self) -> &Storage {
-        &self.storage
+        self.storage.get_or_init(|| {
+            let storage =
+                Storage::with_http_client(Arc::clone(&self.config), Arc::clone(&self.http_client));
+
+            #[cfg(feature = "auth-core")]
+            let storage = storage.with_token_provider(self.auth.token_provider());
+
+            storage
+        })
     }
 
-    /// Get the functions module
+    /// Get the functions module, created on first access
     #[cfg(feature = "functions")]
     pub fn functions(&self) -> &Functions {
-        &self.functions
+        self.functions.get_or_init(|| {
+            let functions = Functions::with_http_client(
+                Arc::clone(&self.config),
+                Arc::clone(&self.http_client),
+            );
+
+            #[cfg(feature = "auth-core")]
+            let functions = functions.with_token_provider(self.auth.token_provider());
+
+            // Drop responses cached for another user
+            #[cfg(feature = "auth-core")]
+            functions.bind_auth(&self.auth);
+
+            functions
+        })
     }
 
     /// Get the realtime module
+    ///
+    /// The WebSocket machinery is set up on first access, so clients that never
+    /// use realtime do not pay for it.
     #[cfg(feature = "realtime")]
     pub fn realtime(&self) -> &Realtime {
-        &self.realtime
+        self.realtime.get_or_init(|| {
+            let realtime = Realtime::with_project_url(&self.config, &self.project_url);
+
+            #[cfg(feature = "auth-core")]
+            let realtime = realtime.with_token_provider(self.auth.token_provider());
+
+            // Keep realtime channel authorization in sync with the auth session
+            #[cfg(all(feature = "auth-core", not(target_arch = "wasm32")))]
+            realtime.bind_auth(&self.auth);
+
+            realtime
+        })
     }
 
     /// Get the HTTP client
@@ -234,31 +380,41 @@ impl Client {
         &self.config.url
     }
 
+    /// Get the validated project URL and its derived endpoints
+    pub fn project_url(&self) -> &ProjectUrl {
+        &self.project_url
+    }
+
+    /// Get the project reference for hosted projects
+    pub fn project_ref(&self) -> Option<&str> {
+        self.project_url.project_ref()
+    }
+
     /// Get the API key
     pub fn key(&self) -> &str {
         &self.config.key
     }
 
     /// Set a custom authorization header (JWT token)
-    #[cfg(feature = "auth")]
+    #[cfg(feature = "auth-core")]
     pub async fn set_auth(&self, token: &str) -> Result<()> {
         self.auth.set_session_token(token).await
     }
 
     /// Clear the current authorization
-    #[cfg(feature = "auth")]
+    #[cfg(feature = "auth-core")]
     pub async fn clear_auth(&self) -> Result<()> {
         self.auth.clear_session().await
     }
 
     /// Check if client is authenticated
-    #[cfg(feature = "auth")]
+    #[cfg(feature = "auth-core")]
     pub fn is_authenticated(&self) -> bool {
         self.auth.is_authenticated()
     }
 
     /// Get current user if authenticated
-    #[cfg(feature = "auth")]
+    #[cfg(feature = "auth-core")]
     pub async fn current_user(&self) -> Result<Option<crate::auth::User>> {
         self.auth.current_user().await
     }
@@ -301,6 +457,8 @@ impl Client {
                 config.http_config.max_redirects,
             ))
             .default_headers(headers)
+            .gzip(config.http_config.response_compression)
+            .brotli(config.http_config.response_compression)
             .build()
             .map_err(|e| Error::config(format!("Failed to build HTTP client: {}", e)))?;
 
@@ -340,7 +498,7 @@ impl Client {
 
         let response = self
             .http_client
-            .get(format!("{}/rest/v1/", self.config.url))
+            .get(format!("{}/", self.project_url.rest_url()))
             .send()
             .await?;
 
@@ -378,6 +536,109 @@ mod tests {
         assert_eq!(client.url(), "https://test.supabase.co");
     }
 
+    #[test]
+    fn test_trailing_slash_url_rejected() {
+        let client = Client::new("https://test.supabase.co/", "test-key");
+        assert!(client.is_err());
+    }
+
+    #[test]
+    fn test_client_project_ref() {
+        let client = Client::new("https://test.supabase.co", "test-key").unwrap();
+        assert_eq!(client.project_ref(), Some("test"));
+    }
+
+    #[test]
+    #[cfg(feature = "realtime")]
+    fn test_realtime_initialized_lazily() {
+        let client = Client::new("http://localhost:54321", "test-key").unwrap();
+        assert!(client.realtime.get().is_none());
+
+        let clone = client.clone();
+        let realtime = client.realtime();
+        assert!(std::ptr::eq(realtime, clone.realtime()));
+    }
+
+    #[test]
+    #[cfg(all(feature = "storage-core", feature = "functions"))]
+    fn test_modules_initialized_lazily() {
+        let client = Client::new("http://localhost:54321", "test-key").unwrap();
+        assert!(client.storage.get().is_none());
+        assert!(client.functions.get().is_none());
+
+        let clone = client.clone();
+        assert!(std::ptr::eq(client.storage(), clone.storage()));
+        assert!(std::ptr::eq(client.functions(), clone.functions()));
+        assert!(client.storage.get().is_some());
+
+        #[cfg(feature = "graphql")]
+        {
+            assert!(client.graphql.get().is_none());
+            assert!(std::ptr::eq(client.graphql(), clone.graphql()));
+        }
+    }
+
+    #[tokio::test]
+    #[cfg(feature = "auth-core")]
+    async fn test_token_provider_follows_session() {
+        let client = Client::new("http://localhost:54321", "test-key").unwrap();
+        let provider = client.auth().token_provider();
+        assert_eq!(provider.access_token(), None);
+
+        let user = serde_json::from_value(serde_json::json!({
+            "id": uuid::Uuid::new_v4(),
+            "email": "user@example.com",
+            "phone": null,
+            "email_confirmed_at": null,
+            "phone_confirmed_at": null,
+            "created_at": chrono::Utc::now(),
+            "updated_at": chrono::Utc::now(),
+            "last_sign_in_at": null,
+            "app_metadata": {},
+            "user_metadata": {},
+            "aud": "authenticated",
+            "role": null,
+        }))
+        .unwrap();
+        client
+            .auth()
+            .set_session(crate::auth::Session {
+                access_token: "user-jwt".to_string(),
+                refresh_token: "refresh-token".to_string(),
+                expires_in: 3600,
+                expires_at: chrono::Utc::now() + chrono::Duration::hours(1),
+                token_type: "bearer".to_string(),
+                user,
+                provider_token: None,
+                provider_refresh_token: None,
+                provider: None,
+            })
+            .await
+            .unwrap();
+        assert_eq!(provider.access_token().as_deref(), Some("user-jwt"));
+
+        client.auth().clear_session().await.unwrap();
+        assert_eq!(provider.access_token(), None);
+    }
+
+    #[test]
+    fn test_service_client() {
+        let client = Client::service("https://test.supabase.co", "service-key").unwrap();
+        assert!(client.is_service());
+        assert_eq!(client.key(), "service-key");
+        assert_eq!(
+            client.config().service_role_key.as_deref(),
+            Some("service-key")
+        );
+        assert!(!client.config().auth_config.auto_refresh_token);
+        assert!(!client.config().auth_config.persist_session);
+
+        let client =
+            Client::new_with_service_role("https://test.supabase.co", "anon-key", "service-key")
+                .unwrap();
+        assert!(!client.is_service());
+    }
+
     #[test]
     fn test_client_key() {
         let client = Client::new("https://test.supabase.co", "test-key").unwrap();
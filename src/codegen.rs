@@ -0,0 +1,418 @@
+//! Rust model generation from a PostgREST schema
+//!
+//! PostgREST describes every exposed table and view in the OpenAPI (Swagger
+//! 2.0) document served at `/rest/v1/`. [`generate_models`] turns that
+//! description into Rust source: one struct per table with serde derives and a
+//! [`Table`](crate::database::Table) impl listing its columns, plus an enum for
+//! every PostgreSQL enum type used by a column. The `supabase-gen` binary wraps it.
+
+use crate::{
+    error::{Error, Result},
+    types::FieldCasing,
+};
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+
+/// Rust keywords that cannot be used as raw identifiers
+const NON_RAW_KEYWORDS: &[&str] = &["crate", "self", "Self", "super"];
+
+/// Rust keywords, usable as field names in raw form (`r#type`)
+const KEYWORDS: &[&str] = &[
+    "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "do", "dyn",
+    "else", "enum", "extern", "false", "final", "fn", "for", "gen", "if", "impl", "in", "let",
+    "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref", "return",
+    "static", "struct", "trait", "true", "try", "type", "typeof", "unsafe", "unsized", "use",
+    "virtual", "where", "while", "yield",
+];
+
+/// Generate Rust models from a PostgREST OpenAPI document
+///
+/// # Examples
+/// ```rust
+/// use serde_json::json;
+/// use supabase_lib_rs::codegen::generate_models;
+///
+/// let spec = json!({
+///     "definitions": {
+///         "todos": {
+///             "required": ["id", "title"],
+///             "properties": {
+///                 "id": {"type": "integer", "format": "bigint"},
+///                 "title": {"type": "string", "format": "text"},
+///                 "done": {"type": "boolean", "format": "boolean"}
+///             }
+///         }
+///     }
+/// });
+///
+/// let source = generate_models(&spec)?;
+/// assert!(source.contains("pub struct Todos {"));
+/// assert!(source.contains("pub done: Option<bool>,"));
+/// # Ok::<(), supabase_lib_rs::Error>(())
+/// ```
+pub fn generate_models(spec: &Value) -> Result<String> {
+    generate_models_with_casing(spec, FieldCasing::SnakeCase)
+}
+
+/// Generate Rust models for a client using `casing`
+///
+/// With [`FieldCasing::CamelCase`] every struct serializes its fields in
+/// camelCase via `#[serde(rename_all = "camelCase")]`, matching a client whose
+/// [`DatabaseConfig::field_casing`](crate::types::DatabaseConfig::field_casing)
+/// is the same.
+///
+/// # Examples
+/// ```rust
+/// use serde_json::json;
+/// use supabase_lib_rs::{codegen::generate_models_with_casing, types::FieldCasing};
+///
+/// let spec = json!({
+///     "definitions": {
+///         "todos": {
+///             "properties": {"created_at": {"type": "string", "format": "text"}}
+///         }
+///     }
+/// });
+///
+/// let source = generate_models_with_casing(&spec, FieldCasing::CamelCase)?;
+/// assert!(source.contains("#[serde(rename_all = \"camelCase\")]\npub struct Todos {"));
+/// # Ok::<(), supabase_lib_rs::Error>(())
+/// ```
+pub fn generate_models_with_casing(spec: &Value, casing: FieldCasing) -> Result<String> {
+    let definitions = spec
+        .get("definitions")
+        .and_then(Value::as_object)
+        .ok_or_else(|| Error::invalid_input("OpenAPI document has no `definitions` object"))?;
+
+    let mut enums = BTreeMap::new();
+    let mut structs = String::new();
+
+    let mut tables: Vec<_> = definitions.iter().collect();
+    tables.sort_by_key(|(name, _)| *name);
+
+    for (table, definition) in tables {
+        structs.push('\n');
+        write_struct(&mut structs, table, definition, casing, &mut enums);
+    }
+
+    let mut source = String::from(
+        "// Generated by supabase-gen from the PostgREST schema. Do not edit by hand.\n\n\
+         use serde::{Deserialize, Serialize};\n\
+         use supabase_lib_rs::database::Table;\n",
+    );
+
+    for (name, values) in &enums {
+        source.push('\n');
+        write_enum(&mut source, name, values);
+    }
+    source.push_str(&structs);
+
+    Ok(source)
+}
+
+fn write_struct(
+    out: &mut String,
+    table: &str,
+    definition: &Value,
+    casing: FieldCasing,
+    enums: &mut BTreeMap<String, Vec<String>>,
+) {
+    let name = type_name(table);
+    let required: Vec<&str> = definition
+        .get("required")
+        .and_then(Value::as_array)
+        .map(|columns| columns.iter().filter_map(Value::as_str).collect())
+        .unwrap_or_default();
+
+    write_doc(out, "", definition.get("description"));
+    out.push_str("#[derive(Debug, Clone, Serialize, Deserialize)]\n");
+    if casing == FieldCasing::CamelCase {
+        out.push_str("#[serde(rename_all = \"camelCase\")]\n");
+    }
+    let _ = writeln!(out, "pub struct {} {{", name);
+
+    let mut columns = Vec::new();
+    let mut fields = BTreeSet::new();
+    if let Some(properties) = definition.get("properties").and_then(Value::as_object) {
+        for (column, property) in properties {
+            columns.push(format!("\"{}\"", escape(column)));
+            let mut rust_type = column_type(property, &name, column, enums);
+            let optional = !required.contains(&column.as_str());
+            if optional {
+                rust_type = format!("Option<{}>", rust_type);
+            }
+
+            let ident = unique_field_name(column, &mut fields);
+            write_doc(out, "    ", property.get("description"));
+            if ident.trim_start_matches("r#") != column {
+                let _ = writeln!(out, "    #[serde(rename = \"{}\")]", escape(column));
+            }
+            if optional {
+                out.push_str("    #[serde(default, skip_serializing_if = \"Option::is_none\")]\n");
+            }
+            let _ = writeln!(out, "    pub {}: {},", ident, rust_type);
+        }
+    }
+
+    out.push_str("}\n\n");
+    let _ = writeln!(
+        out,
+        "impl Table for {} {{\n    const NAME: &'static str = \"{}\";\n    \
+         const COLUMNS: &'static [&'static str] = &[{}];\n}}",
+        name,
+        escape(table),
+        columns.join(", ")
+    );
+}
+
+fn write_enum(out: &mut String, name: &str, values: &[String]) {
+    out.push_str("#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]\n");
+    let _ = writeln!(out, "pub enum {} {{", name);
+    for value in values {
+        let _ = writeln!(
+            out,
+            "    #[serde(rename = \"{}\")]\n    {},",
+            escape(value),
+            type_name(value)
+        );
+    }
+    out.push_str("}\n");
+}
+
+/// Write a column or table comment as doc comments, without PostgREST's key notes
+fn write_doc(out: &mut String, indent: &str, description: Option<&Value>) {
+    let Some(description) = description.and_then(Value::as_str) else {
+        return;
+    };
+
+    for line in description.lines() {
+        let line = line.trim();
+        if line.is_empty() || line == "Note:" || line.contains("<pk/>") || line.contains("<fk ") {
+            continue;
+        }
+        let _ = writeln!(out, "{}/// {}", indent, line);
+    }
+}
+
+/// Map a column to a Rust type, registering any enum it uses
+fn column_type(
+    property: &Value,
+    struct_name: &str,
+    column: &str,
+    enums: &mut BTreeMap<String, Vec<String>>,
+) -> String {
+    let format = property.get("format").and_then(Value::as_str).unwrap_or("");
+
+    if let Some(values) = property.get("enum").and_then(Value::as_array) {
+        let enum_name = match format.rsplit('.').next() {
+            Some(type_name_part) if !type_name_part.is_empty() => type_name(type_name_part),
+            _ => format!("{}{}", struct_name, type_name(column)),
+        };
+        let values = values
+            .iter()
+            .filter_map(Value::as_str)
+            .map(str::to_string)
+            .collect();
+        enums.entry(enum_name.clone()).or_insert(values);
+        return enum_name;
+    }
+
+    match property.get("type").and_then(Value::as_str) {
+        Some("integer") => match format {
+            "smallint" => "i16",
+            "integer" => "i32",
+            _ => "i64",
+        }
+        .to_string(),
+        Some("number") => match format {
+            "real" => "f32",
+            _ => "f64",
+        }
+        .to_string(),
+        Some("boolean") => "bool".to_string(),
+        Some("array") => {
+            let item = property
+                .get("items")
+                .map(|items| column_type(items, struct_name, column, enums))
+                .unwrap_or_else(|| "serde_json::Value".to_string());
+            format!("Vec<{}>", item)
+        }
+        Some("string") => match format {
+            "uuid" => "uuid::Uuid",
+            "timestamp with time zone" => "chrono::DateTime<chrono::Utc>",
+            "timestamp without time zone" => "chrono::NaiveDateTime",
+            "date" => "chrono::NaiveDate",
+            _ => "String",
+        }
+        .to_string(),
+        // json and jsonb columns carry no type
+        _ => "serde_json::Value".to_string(),
+    }
+}
+
+/// `PascalCase` identifier for a table, enum or variant name
+fn type_name(name: &str) -> String {
+    let mut ident: String = name
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let mut chars = part.chars();
+            chars
+                .next()
+                .map(|first| first.to_ascii_uppercase().to_string() + chars.as_str())
+                .unwrap_or_default()
+        })
+        .collect();
+
+    if !ident.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        ident.insert(0, 'V');
+    }
+    ident
+}
+
+/// `snake_case` identifier for a column name
+fn field_name(column: &str) -> String {
+    let mut ident: String = column
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '_'
+            }
+        })
+        .collect();
+
+    if ident.is_empty() || ident.starts_with(|c: char| c.is_ascii_digit()) {
+        ident.insert(0, '_');
+    }
+
+    if NON_RAW_KEYWORDS.contains(&ident.as_str()) {
+        ident.push('_');
+    } else if KEYWORDS.contains(&ident.as_str()) {
+        ident.insert_str(0, "r#");
+    }
+    ident
+}
+
+/// [`field_name`] for a column, suffixed with `_2`, `_3`, ... when an earlier
+/// column of the same table maps to the same identifier
+fn unique_field_name(column: &str, fields: &mut BTreeSet<String>) -> String {
+    let ident = field_name(column);
+    if fields.insert(ident.clone()) {
+        return ident;
+    }
+
+    let base = ident.trim_start_matches("r#");
+    let ident = (2..)
+        .map(|n| format!("{}_{}", base, n))
+        .find(|candidate| !fields.contains(candidate))
+        .expect("unbounded suffixes");
+    fields.insert(ident.clone());
+    ident
+}
+
+/// Escape a value for use inside a Rust string literal
+fn escape(value: &str) -> String {
+    value.escape_default().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_generate_models() {
+        let spec = json!({
+            "definitions": {
+                "order_items": {
+                    "description": "Line items of an order",
+                    "required": ["id", "order_id", "status", "created_at"],
+                    "properties": {
+                        "id": {
+                            "description": "Note:\nThis is a Primary Key.<pk/>",
+                            "type": "string",
+                            "format": "uuid"
+                        },
+                        "order_id": {
+                            "description": "Note:\nThis is a Foreign Key to `orders.id`.<fk table='orders' column='id'/>",
+                            "type": "integer",
+                            "format": "bigint"
+                        },
+                        "status": {
+                            "type": "string",
+                            "format": "public.item_status",
+                            "enum": ["in-stock", "backordered"]
+                        },
+                        "created_at": {"type": "string", "format": "timestamp with time zone"},
+                        "type": {"type": "string", "format": "text"},
+                        "Quantity": {"type": "integer", "format": "smallint"},
+                        "tags": {"type": "array", "format": "text[]", "items": {"type": "string"}},
+                        "attributes": {"format": "jsonb"}
+                    }
+                }
+            }
+        });
+
+        let source = generate_models(&spec).unwrap();
+
+        assert!(source.contains("/// Line items of an order\n"));
+        assert!(source.contains("pub struct OrderItems {"));
+        assert!(source.contains("    pub id: uuid::Uuid,\n"));
+        assert!(!source.contains("Primary Key"));
+        assert!(source.contains("    pub order_id: i64,\n"));
+        assert!(source.contains("    pub status: ItemStatus,\n"));
+        assert!(source.contains("    pub created_at: chrono::DateTime<chrono::Utc>,\n"));
+        assert!(source.contains("    pub r#type: Option<String>,\n"));
+        assert!(source.contains(
+            "    #[serde(rename = \"Quantity\")]\n    \
+             #[serde(default, skip_serializing_if = \"Option::is_none\")]\n    \
+             pub quantity: Option<i16>,\n"
+        ));
+        assert!(source.contains("    pub tags: Option<Vec<String>>,\n"));
+        assert!(source.contains("    pub attributes: Option<serde_json::Value>,\n"));
+        assert!(source.contains("pub enum ItemStatus {"));
+        assert!(source.contains("    #[serde(rename = \"in-stock\")]\n    InStock,\n"));
+        assert!(source.contains("const NAME: &'static str = \"order_items\";"));
+        assert!(source
+            .contains("const COLUMNS: &'static [&'static str] = &[\"Quantity\", \"attributes\", "));
+
+        assert!(!source.contains("rename_all"));
+        let camel = generate_models_with_casing(&spec, FieldCasing::CamelCase).unwrap();
+        assert!(camel.contains("#[serde(rename_all = \"camelCase\")]\npub struct OrderItems {"));
+        assert!(camel.contains("    pub created_at: chrono::DateTime<chrono::Utc>,\n"));
+
+        assert!(generate_models(&json!({"paths": {}})).is_err());
+    }
+
+    #[test]
+    fn test_colliding_field_names() {
+        let spec = json!({
+            "definitions": {
+                "people": {
+                    "required": ["Name", "name", "first-name", "first_name", "name_2"],
+                    "properties": {
+                        "Name": {"type": "string", "format": "text"},
+                        "name": {"type": "string", "format": "text"},
+                        "name_2": {"type": "string", "format": "text"},
+                        "first-name": {"type": "string", "format": "text"},
+                        "first_name": {"type": "string", "format": "text"}
+                    }
+                }
+            }
+        });
+
+        let source = generate_models(&spec).unwrap();
+
+        assert!(source.contains("    #[serde(rename = \"Name\")]\n    pub name: String,\n"));
+        assert!(source.contains("    #[serde(rename = \"name\")]\n    pub name_2: String,\n"));
+        assert!(source.contains("    #[serde(rename = \"name_2\")]\n    pub name_2_2: String,\n"));
+        assert!(
+            source.contains("    #[serde(rename = \"first-name\")]\n    pub first_name: String,\n")
+        );
+        assert!(source
+            .contains("    #[serde(rename = \"first_name\")]\n    pub first_name_2: String,\n"));
+    }
+}
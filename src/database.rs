@@ -1,21 +1,41 @@
 //! Database module for Supabase REST API
 
 use crate::{
-    error::{Error, Result},
-    types::{FilterOperator, JsonValue, OrderDirection, SupabaseConfig},
+    circuit::CircuitBreaker,
+    error::{Error, ErrorContext, HttpErrorContext, PostgresErrorKind, Result, RetryInfo},
+    types::{
+        FieldCasing, FilterOperator, JsonValue, OrderDirection, RequestAuth, SupabaseConfig,
+        TokenProvider,
+    },
 };
 use reqwest::Client as HttpClient;
+pub use reqwest::Method;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
-use std::{collections::HashMap, sync::Arc};
-use tracing::{debug, info};
+use std::{
+    collections::HashMap,
+    marker::PhantomData,
+    sync::{Arc, RwLock},
+};
+use tracing::{debug, info, warn};
 use url::Url;
 
+mod compression;
+mod page_token;
+mod strict;
+
+pub use crate::error::RateLimitInfo;
+pub use page_token::PageToken;
+
 /// Database client for REST API operations
 #[derive(Debug, Clone)]
 pub struct Database {
     http_client: Arc<HttpClient>,
     config: Arc<SupabaseConfig>,
+    profiles: Arc<RwLock<HashMap<String, Profile>>>,
+    token_provider: RequestAuth,
+    query_logging: Arc<RwLock<Option<Redaction>>>,
+    circuit: CircuitBreaker,
 }
 
 /// Query builder for SELECT operations
@@ -29,7 +49,70 @@ pub struct QueryBuilder {
     limit: Option<u32>,
     offset: Option<u32>,
     single: bool,
+    count: Option<CountStrategy>,
+    range: Option<(u64, u64)>,
     joins: Vec<Join>,
+    computed_columns: Vec<ComputedColumn>,
+    budget: Option<QueryBudget>,
+    known_columns: Option<&'static [&'static str]>,
+    #[cfg(feature = "postgis")]
+    geo_filter: Option<GeoFilter>,
+}
+
+/// Spatial filter evaluated by a per-table PostGIS function
+#[cfg(feature = "postgis")]
+#[derive(Debug, Clone)]
+enum GeoFilter {
+    DWithin {
+        column: String,
+        point: crate::geo::Point,
+        meters: f64,
+    },
+    BboxOverlaps {
+        column: String,
+        bbox: crate::geo::BoundingBox,
+    },
+}
+
+#[cfg(feature = "postgis")]
+impl GeoFilter {
+    /// Name of the function that applies this filter to `table`
+    fn function_name(&self, table: &str) -> String {
+        match self {
+            GeoFilter::DWithin { .. } => format!("{}_st_dwithin", table),
+            GeoFilter::BboxOverlaps { .. } => format!("{}_bbox_overlaps", table),
+        }
+    }
+
+    /// Function arguments, passed as query parameters
+    fn params(&self) -> Vec<(&'static str, String)> {
+        match self {
+            GeoFilter::DWithin {
+                column,
+                point,
+                meters,
+            } => vec![
+                ("column_name", column.clone()),
+                ("point", point.to_ewkt()),
+                ("meters", meters.to_string()),
+            ],
+            GeoFilter::BboxOverlaps { column, bbox } => {
+                vec![("column_name", column.clone()), ("bbox", bbox.to_ewkt())]
+            }
+        }
+    }
+}
+
+/// A computed (virtual) column backed by a PostgreSQL function on the row type
+///
+/// PostgREST runs the function once for every returned row, so each computed
+/// column adds per-row execution cost to the query.
+#[derive(Debug, Clone)]
+pub struct ComputedColumn {
+    /// Function name exposed as a column
+    pub name: String,
+    /// Optional alias for the column in the response
+    pub alias: Option<String>,
 }
 
 /// Represents a table join operation
@@ -45,6 +128,104 @@ pub struct Join {
     pub alias: Option<String>,
 }
 
+/// Planner estimates returned by `QueryBuilder::explain`
+#[derive(Debug, Clone)]
+pub struct QueryPlan {
+    /// Estimated total cost of the top plan node
+    pub total_cost: f64,
+    /// Estimated number of rows returned
+    pub plan_rows: u64,
+    /// Full plan as returned by PostgreSQL
+    pub raw: JsonValue,
+}
+
+impl QueryPlan {
+    /// Parse a PostgreSQL JSON plan (`[{"Plan": {...}}]` or `{"Plan": {...}}`)
+    pub fn from_json(raw: JsonValue) -> Result<Self> {
+        let plan = raw
+            .get(0)
+            .unwrap_or(&raw)
+            .get("Plan")
+            .ok_or_else(|| Error::database("Query plan is missing the 'Plan' node"))?;
+
+        let total_cost = plan
+            .get("Total Cost")
+            .and_then(JsonValue::as_f64)
+            .ok_or_else(|| Error::database("Query plan is missing 'Total Cost'"))?;
+        let plan_rows = plan
+            .get("Plan Rows")
+            .and_then(JsonValue::as_f64)
+            .ok_or_else(|| Error::database("Query plan is missing 'Plan Rows'"))?
+            as u64;
+
+        Ok(Self {
+            total_cost,
+            plan_rows,
+            raw,
+        })
+    }
+}
+
+/// Limits on planner estimates for a query
+#[derive(Debug, Clone, Default)]
+pub struct QueryBudget {
+    /// Maximum acceptable total plan cost
+    pub max_cost: Option<f64>,
+    /// Maximum acceptable estimated row count
+    pub max_rows: Option<u64>,
+    /// Fail the query instead of logging a warning when the budget is exceeded
+    pub fail_on_exceed: bool,
+}
+
+impl QueryBudget {
+    /// List every limit the plan exceeds
+    pub fn violations(&self, plan: &QueryPlan) -> Vec<BudgetViolation> {
+        let mut violations = Vec::new();
+
+        if let Some(max) = self.max_cost {
+            if plan.total_cost > max {
+                violations.push(BudgetViolation::Cost {
+                    estimated: plan.total_cost,
+                    max,
+                });
+            }
+        }
+
+        if let Some(max) = self.max_rows {
+            if plan.plan_rows > max {
+                violations.push(BudgetViolation::Rows {
+                    estimated: plan.plan_rows,
+                    max,
+                });
+            }
+        }
+
+        violations
+    }
+}
+
+/// A query budget limit exceeded by the plan estimates
+#[derive(Debug, Clone, PartialEq)]
+pub enum BudgetViolation {
+    /// Estimated total cost is above `max_cost`
+    Cost { estimated: f64, max: f64 },
+    /// Estimated row count is above `max_rows`
+    Rows { estimated: u64, max: u64 },
+}
+
+impl std::fmt::Display for BudgetViolation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BudgetViolation::Cost { estimated, max } => {
+                write!(f, "estimated cost {} > {}", estimated, max)
+            }
+            BudgetViolation::Rows { estimated, max } => {
+                write!(f, "estimated rows {} > {}", estimated, max)
+            }
+        }
+    }
+}
+
 /// Types of JOIN operations supported by PostgREST
 #[derive(Debug, Clone)]
 pub enum JoinType {
@@ -54,11 +235,34 @@ pub enum JoinType {
     Left,
 }
 
+/// How PostgREST counts the rows matching a query, see [`QueryBuilder::count`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CountStrategy {
+    /// `COUNT(*)` over the matching rows; precise, but slow on large tables
+    Exact,
+    /// The planner's row estimate; fast, but can be far off
+    Planned,
+    /// Exact below PostgREST's `db-max-rows`, planned above it
+    Estimated,
+}
+
+impl CountStrategy {
+    /// Value of the `Prefer: count=` header
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CountStrategy::Exact => "exact",
+            CountStrategy::Planned => "planned",
+            CountStrategy::Estimated => "estimated",
+        }
+    }
+}
+
 /// Transaction builder for batching multiple database operations
 #[derive(Debug, Clone)]
 pub struct TransactionBuilder {
     database: Database,
     operations: Vec<JsonValue>,
+    max_attempts: u32,
 }
 
 /// Types of transaction operations
@@ -81,6 +285,134 @@ pub struct InsertBuilder {
     upsert: bool,
     on_conflict: Option<String>,
     returning: Option<String>,
+    default_for_missing: bool,
+    conflict_retry: Option<ConflictRetry>,
+    generated_id: Option<(String, IdStrategy)>,
+}
+
+/// Client-side primary key format for [`InsertBuilder::generate_id`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IdStrategy {
+    /// Random UUID (version 4)
+    UuidV4,
+    /// Time-ordered UUID (version 7), index-friendly
+    UuidV7,
+    /// Time-ordered ULID in its 26-character Crockford base32 form
+    Ulid,
+}
+
+impl IdStrategy {
+    /// Generate a new identifier
+    pub fn generate(&self) -> String {
+        match self {
+            IdStrategy::UuidV4 => uuid::Uuid::new_v4().to_string(),
+            IdStrategy::UuidV7 => uuid::Uuid::now_v7().to_string(),
+            IdStrategy::Ulid => {
+                const CROCKFORD: &[u8; 32] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+
+                let millis = chrono::Utc::now().timestamp_millis().max(0) as u128;
+                let random = u128::from_be_bytes(*uuid::Uuid::new_v4().as_bytes());
+                let value = (millis << 80) | (random & ((1 << 80) - 1));
+
+                (0..26)
+                    .map(|i| CROCKFORD[((value >> (125 - 5 * i)) & 0x1f) as usize] as char)
+                    .collect()
+            }
+        }
+    }
+}
+
+/// Payload mutation applied before retrying an insert that hit a unique violation
+type ConflictMutation = Arc<dyn Fn(&mut JsonValue, u32) + Send + Sync>;
+
+/// Retry settings for [`InsertBuilder::retry_on_conflict`]
+#[derive(Clone)]
+struct ConflictRetry {
+    max_attempts: u32,
+    mutate: ConflictMutation,
+}
+
+impl std::fmt::Debug for ConflictRetry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ConflictRetry")
+            .field("max_attempts", &self.max_attempts)
+            .field("mutate", &"Fn(&mut JsonValue, u32)")
+            .finish()
+    }
+}
+
+/// Whether an error is a unique violation on `column` alone
+fn is_column_conflict(error: &Error, column: &str) -> bool {
+    error
+        .postgres_error()
+        .filter(|pg| pg.kind() == PostgresErrorKind::UniqueViolation)
+        .and_then(|pg| pg.details)
+        .map(|details| details.starts_with(&format!("Key ({})=", column)))
+        .unwrap_or(false)
+}
+
+/// Point at the install SQL when the approximate count helper is missing
+fn missing_count_helper(error: Error) -> Error {
+    // PostgREST reports unknown functions as PGRST202
+    let missing = error
+        .postgres_error()
+        .is_some_and(|pg| pg.code == "PGRST202");
+
+    if missing {
+        Error::database(format!(
+            "Database function {} is not installed; run database::APPROXIMATE_COUNT_SQL first",
+            APPROXIMATE_COUNT_FUNCTION
+        ))
+    } else {
+        error
+    }
+}
+
+/// Read the result of the approximate count helper
+fn parse_approximate_count(table: &str, result: JsonValue) -> Result<u64> {
+    match result {
+        JsonValue::Null => Err(Error::not_found(format!("Table not found: {}", table))),
+        value => value
+            .as_u64()
+            .ok_or_else(|| Error::database(format!("Unexpected row estimate: {}", value))),
+    }
+}
+
+/// `INSERT INTO table (columns) VALUES (...)` for a row or an array of rows
+fn insert_sql(table: &str, payload: &JsonValue, redaction: &Redaction) -> String {
+    let rows: Vec<&serde_json::Map<String, JsonValue>> = match payload {
+        JsonValue::Array(rows) => rows.iter().filter_map(JsonValue::as_object).collect(),
+        JsonValue::Object(row) => vec![row],
+        _ => Vec::new(),
+    };
+    let Some(first) = rows.first() else {
+        return format!("INSERT INTO {} DEFAULT VALUES", table);
+    };
+
+    let columns: Vec<&String> = first.keys().collect();
+    let values: Vec<String> = rows
+        .iter()
+        .map(|row| {
+            let literals: Vec<String> = columns
+                .iter()
+                .map(|column| {
+                    redaction.json_literal(column, row.get(*column).unwrap_or(&JsonValue::Null))
+                })
+                .collect();
+            format!("({})", literals.join(", "))
+        })
+        .collect();
+
+    format!(
+        "INSERT INTO {} ({}) VALUES {}",
+        table,
+        columns
+            .iter()
+            .map(|column| column.as_str())
+            .collect::<Vec<_>>()
+            .join(", "),
+        values.join(", ")
+    )
 }
 
 /// Update builder for UPDATE operations
@@ -91,6 +423,7 @@ pub struct UpdateBuilder {
     data: JsonValue,
     filters: Vec<Filter>,
     returning: Option<String>,
+    allow_unfiltered: bool,
 }
 
 /// Delete builder for DELETE operations
@@ -100,6 +433,32 @@ pub struct DeleteBuilder {
     table: String,
     filters: Vec<Filter>,
     returning: Option<String>,
+    allow_unfiltered: bool,
+}
+
+/// Builder for calling a database function, from [`Database::rpc_builder`]
+///
+/// `T` is the type the result deserializes into, [`JsonValue`] until set
+/// with [`returns`](RpcBuilder::returns).
+#[derive(Debug, Clone)]
+pub struct RpcBuilder<T = JsonValue> {
+    database: Database,
+    function: String,
+    params: Option<JsonValue>,
+    single: bool,
+    read_only: bool,
+    single_object: bool,
+    schema: Option<String>,
+    _returns: PhantomData<fn() -> T>,
+}
+
+/// Method, query, headers and body of an RPC request
+#[derive(Debug)]
+struct RpcRequest {
+    method: Method,
+    query: Vec<(String, String)>,
+    headers: Vec<(&'static str, String)>,
+    body: Option<JsonValue>,
 }
 
 /// Database filter for WHERE clauses
@@ -124,10 +483,147 @@ pub enum Filter {
 struct OrderBy {
     column: String,
     direction: OrderDirection,
-    #[allow(dead_code)]
     nulls_first: Option<bool>,
 }
 
+impl OrderBy {
+    /// PostgREST form, e.g. `created_at.desc.nullslast`
+    fn to_param(&self) -> String {
+        let direction = match self.direction {
+            OrderDirection::Ascending => "asc",
+            OrderDirection::Descending => "desc",
+        };
+        match self.nulls_first {
+            Some(true) => format!("{}.{}.nullsfirst", self.column, direction),
+            Some(false) => format!("{}.{}.nullslast", self.column, direction),
+            None => format!("{}.{}", self.column, direction),
+        }
+    }
+
+    /// Pseudo-SQL form, e.g. `created_at DESC NULLS LAST`
+    fn to_sql(&self) -> String {
+        let direction = match self.direction {
+            OrderDirection::Ascending => "ASC",
+            OrderDirection::Descending => "DESC",
+        };
+        match self.nulls_first {
+            Some(true) => format!("{} {} NULLS FIRST", self.column, direction),
+            Some(false) => format!("{} {} NULLS LAST", self.column, direction),
+            None => format!("{} {}", self.column, direction),
+        }
+    }
+}
+
+/// Which values are hidden when queries are rendered for logging
+///
+/// See [`Database::set_query_logging`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum Redaction {
+    /// Replace every value with `?`
+    #[default]
+    All,
+    /// Replace only the values of these columns
+    Columns(Vec<String>),
+    /// Show all values; logs may then contain personal data
+    None,
+}
+
+impl Redaction {
+    fn hides(&self, column: &str) -> bool {
+        match self {
+            Redaction::All => true,
+            Redaction::Columns(columns) => columns.iter().any(|c| c == column),
+            Redaction::None => false,
+        }
+    }
+
+    /// SQL literal for a filter value given as text
+    fn literal(&self, column: &str, value: &str) -> String {
+        if self.hides(column) {
+            return "?".to_string();
+        }
+        let bare = value.parse::<f64>().is_ok()
+            || matches!(value, "true" | "false" | "null")
+            || (value.starts_with('(') && value.ends_with(')'));
+        if bare {
+            value.to_string()
+        } else {
+            format!("'{}'", value.replace('\'', "''"))
+        }
+    }
+
+    /// SQL literal for a JSON value written to `column`
+    fn json_literal(&self, column: &str, value: &JsonValue) -> String {
+        match value {
+            _ if self.hides(column) => "?".to_string(),
+            JsonValue::String(text) => format!("'{}'", text.replace('\'', "''")),
+            JsonValue::Array(_) | JsonValue::Object(_) => format!("'{}'", value),
+            _ => value.to_string(),
+        }
+    }
+
+    /// Pseudo-SQL condition for `filter`
+    fn condition(&self, filter: &Filter) -> String {
+        match filter {
+            Filter::Simple {
+                column,
+                operator,
+                value,
+            } => {
+                let op = match operator {
+                    FilterOperator::Equal => "=",
+                    FilterOperator::NotEqual => "<>",
+                    FilterOperator::GreaterThan => ">",
+                    FilterOperator::GreaterThanOrEqual => ">=",
+                    FilterOperator::LessThan => "<",
+                    FilterOperator::LessThanOrEqual => "<=",
+                    FilterOperator::Like => "LIKE",
+                    FilterOperator::ILike => "ILIKE",
+                    // NULL, TRUE and FALSE are not worth hiding
+                    FilterOperator::Is => return format!("{} IS {}", column, value.to_uppercase()),
+                    FilterOperator::In => "IN",
+                    FilterOperator::Contains => "@>",
+                    FilterOperator::ContainedBy => "<@",
+                    FilterOperator::StrictlyLeft => "<<",
+                    FilterOperator::StrictlyRight => ">>",
+                    FilterOperator::NotExtendToRight => "&<",
+                    FilterOperator::NotExtendToLeft => "&>",
+                    FilterOperator::Adjacent => "-|-",
+                };
+                let literal = self.literal(column, value);
+                if matches!(operator, FilterOperator::In) && literal == "?" {
+                    format!("{} IN (?)", column)
+                } else {
+                    format!("{} {} {}", column, op, literal)
+                }
+            }
+            Filter::And(filters) => format!("({})", self.conditions(filters, " AND ")),
+            Filter::Or(filters) => format!("({})", self.conditions(filters, " OR ")),
+            Filter::Not(filter) => match filter.as_ref() {
+                Filter::Simple { .. } => format!("NOT ({})", self.condition(filter)),
+                _ => format!("NOT {}", self.condition(filter)),
+            },
+        }
+    }
+
+    fn conditions(&self, filters: &[Filter], separator: &str) -> String {
+        filters
+            .iter()
+            .map(|filter| self.condition(filter))
+            .collect::<Vec<_>>()
+            .join(separator)
+    }
+
+    /// ` WHERE ...` clause for `filters`, empty without filters
+    fn where_clause(&self, filters: &[Filter]) -> String {
+        if filters.is_empty() {
+            String::new()
+        } else {
+            format!(" WHERE {}", self.conditions(filters, " AND "))
+        }
+    }
+}
+
 /// Database response wrapper
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DatabaseResponse<T> {
@@ -135,65 +631,580 @@ pub struct DatabaseResponse<T> {
     pub count: Option<u64>,
 }
 
+/// Rows together with the response metadata they came with
+#[derive(Debug, Clone)]
+pub struct ResponseWithMeta<T> {
+    pub data: Vec<T>,
+    pub meta: ResponseMeta,
+}
+
+/// Rows an UPDATE or DELETE would affect, reported by a dry run
+#[derive(Debug, Clone)]
+pub struct DryRun<T> {
+    /// Number of rows matching the filters
+    pub affected: u64,
+    /// Up to [`DRY_RUN_SAMPLE_SIZE`] of the matching rows
+    pub sample: Vec<T>,
+}
+
+/// Whether PostgREST rejected a range starting past the last row
+fn is_range_not_satisfiable(error: &Error) -> bool {
+    error.status_code() == Some(416)
+        || error
+            .postgres_error()
+            .is_some_and(|error| error.code == "PGRST103")
+}
+
+/// Number of sample rows returned by a dry run
+pub const DRY_RUN_SAMPLE_SIZE: u32 = 10;
+
+/// Database function used by [`Database::approximate_count`]
+pub const APPROXIMATE_COUNT_FUNCTION: &str = "approximate_row_count";
+
+/// SQL that installs the [`APPROXIMATE_COUNT_FUNCTION`] helper
+///
+/// Run it once in the SQL editor or a migration. The function reads the
+/// planner's estimate from `pg_class.reltuples`, summed over partitions, so
+/// its accuracy depends on how recently the table was analyzed. It returns
+/// `null` for unknown tables.
+///
+/// The function reveals the size of every table, so only signed-in users and
+/// the service role may call it; `anon` and `public` have their execute
+/// privilege revoked.
+pub const APPROXIMATE_COUNT_SQL: &str = r#"create or replace function public.approximate_row_count(
+  table_name text,
+  schema_name text default 'public'
+)
+returns bigint
+language sql
+stable
+set search_path = ''
+as $$
+  select sum(greatest(c.reltuples, 0))::bigint
+  from pg_catalog.pg_class c
+  join pg_catalog.pg_namespace n on n.oid = c.relnamespace
+  where c.relkind in ('r', 'p', 'm')
+    and (
+      (n.nspname = schema_name and c.relname = table_name)
+      or c.oid in (
+        select i.inhrelid
+        from pg_catalog.pg_inherits i
+        join pg_catalog.pg_class p on p.oid = i.inhparent
+        join pg_catalog.pg_namespace pn on pn.oid = p.relnamespace
+        where pn.nspname = schema_name and p.relname = table_name
+      )
+    )
+$$;
+
+revoke execute on function public.approximate_row_count(text, text) from public, anon;
+grant execute on function public.approximate_row_count(text, text) to authenticated, service_role;
+
+notify pgrst, 'reload schema';
+"#;
+
+/// Deserialized body of a raw request together with the response metadata
+#[derive(Debug, Clone)]
+pub struct TypedResponse<T> {
+    pub data: T,
+    pub meta: ResponseMeta,
+}
+
+/// Metadata from a PostgREST response
+#[derive(Debug, Clone, Default)]
+pub struct ResponseMeta {
+    /// HTTP status code
+    pub status: u16,
+    /// Parsed `Content-Range` header
+    pub content_range: Option<ContentRange>,
+    /// Rate limit headers, if the gateway sent any
+    pub rate_limit: Option<RateLimitInfo>,
+    /// Raw `Server-Timing` header
+    pub server_timing: Option<String>,
+    /// `sb-gateway-version` header
+    pub gateway_version: Option<String>,
+    /// All response headers, with lowercase names
+    pub headers: HashMap<String, String>,
+}
+
+/// Row range reported in a `Content-Range` header, e.g. `0-24/3573`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ContentRange {
+    /// First row index, `None` for an empty range (`*/0`)
+    pub start: Option<u64>,
+    /// Last row index (inclusive)
+    pub end: Option<u64>,
+    /// Total rows, known only when a count was requested
+    pub total: Option<u64>,
+}
+
+impl ContentRange {
+    /// Parse a `Content-Range` header value
+    pub fn parse(value: &str) -> Option<Self> {
+        let value = value.trim();
+        let value = value.strip_prefix("items ").unwrap_or(value);
+        let (range, total) = value.split_once('/')?;
+
+        let total = match total {
+            "*" => None,
+            total => Some(total.parse().ok()?),
+        };
+
+        let (start, end) = match range {
+            "*" => (None, None),
+            range => {
+                let (start, end) = range.split_once('-')?;
+                (Some(start.parse().ok()?), Some(end.parse().ok()?))
+            }
+        };
+
+        Some(Self { start, end, total })
+    }
+}
+
+impl ResponseMeta {
+    /// Collect metadata from a response's status and headers
+    pub fn from_response(response: &reqwest::Response) -> Self {
+        let headers: HashMap<String, String> = response
+            .headers()
+            .iter()
+            .filter_map(|(name, value)| {
+                value
+                    .to_str()
+                    .ok()
+                    .map(|value| (name.as_str().to_ascii_lowercase(), value.to_string()))
+            })
+            .collect();
+
+        Self::from_parts(response.status().as_u16(), headers)
+    }
+
+    fn from_parts(status: u16, headers: HashMap<String, String>) -> Self {
+        Self {
+            status,
+            content_range: headers
+                .get("content-range")
+                .and_then(|value| ContentRange::parse(value)),
+            rate_limit: RateLimitInfo::parse(|name| headers.get(name).map(String::as_str)),
+            server_timing: headers.get("server-timing").cloned(),
+            gateway_version: headers.get("sb-gateway-version").cloned(),
+            headers,
+        }
+    }
+}
+
+/// A type stored in a database table, used to key table profiles
+pub trait Table {
+    /// Table name as exposed by PostgREST
+    const NAME: &'static str;
+
+    /// Columns of the table; an empty list disables select validation
+    const COLUMNS: &'static [&'static str] = &[];
+
+    /// Check that a select list only names columns in [`Table::COLUMNS`]
+    ///
+    /// Embedded resources, `*`, aliases, casts and JSON paths are accepted;
+    /// only the underlying column names are checked.
+    fn validate_select(select: &str) -> Result<()> {
+        validate_columns(Self::NAME, Self::COLUMNS, select)
+    }
+}
+
+/// Rename the keys of every object in `data`, nested ones included
+fn rename_keys(data: &mut JsonValue, rename: &impl Fn(&str) -> String) {
+    match data {
+        JsonValue::Array(items) => {
+            for item in items {
+                rename_keys(item, rename);
+            }
+        }
+        JsonValue::Object(object) => {
+            *object = std::mem::take(object)
+                .into_iter()
+                .map(|(key, mut value)| {
+                    rename_keys(&mut value, rename);
+                    (rename(&key), value)
+                })
+                .collect();
+        }
+        _ => {}
+    }
+}
+
+/// Check the top-level columns of a select list against the known columns
+fn validate_columns(table: &str, known: &[&str], select: &str) -> Result<()> {
+    if known.is_empty() {
+        return Ok(());
+    }
+
+    let mut depth = 0usize;
+    let mut start = 0;
+    let mut items = Vec::new();
+    for (index, ch) in select.char_indices() {
+        match ch {
+            '(' => depth += 1,
+            ')' => depth = depth.saturating_sub(1),
+            ',' if depth == 0 => {
+                items.push(&select[start..index]);
+                start = index + 1;
+            }
+            _ => {}
+        }
+    }
+    items.push(&select[start..]);
+
+    for item in items {
+        let item = item.trim();
+        // Embedded resources are validated by PostgREST against the relation
+        if item.is_empty() || item == "*" || item.contains('(') {
+            continue;
+        }
+
+        let column = item.split("::").next().unwrap_or(item);
+        let column = column.rsplit(':').next().unwrap_or(column);
+        let column = column.split("->").next().unwrap_or(column).trim();
+        if !known.contains(&column) {
+            return Err(Error::invalid_input(format!(
+                "Unknown column `{}` in select for table {}",
+                column, table
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+/// A foreign-key relationship embedded with [`QueryBuilder::with_related`]
+///
+/// Implemented on a marker type per relationship. PostgREST decides the
+/// cardinality from the foreign key: to-many relationships embed an array
+/// (deserialize into `Vec<T>`), to-one relationships embed an object or
+/// `null` (deserialize into `Option<T>`).
+pub trait Relation {
+    /// Related table
+    type Target: Table;
+    /// Field the related rows are embedded under
+    const FIELD: &'static str;
+    /// Foreign key column or constraint name, needed when several foreign
+    /// keys link the same tables
+    const FOREIGN_KEY: Option<&'static str> = None;
+    /// Columns selected from the related table; defaults to the target's
+    /// profile select list, or `*` without a profile
+    const COLUMNS: Option<&'static str> = None;
+}
+
+/// Per-table defaults applied by the query builders
+///
+/// The default select list is used when a query does not call
+/// [`QueryBuilder::select`], which keeps heavy columns out of routine queries.
+/// Write-skipped columns (e.g. generated or server-managed ones) are removed
+/// from insert and update payloads.
+#[derive(Debug, Clone, Default)]
+pub struct Profile {
+    select: Option<String>,
+    skip_on_write: Vec<String>,
+}
+
+impl Profile {
+    /// Create a profile with a default column list for SELECT queries
+    pub fn default_select(columns: &str) -> Self {
+        Self {
+            select: Some(columns.to_string()),
+            ..Default::default()
+        }
+    }
+
+    /// Remove these columns from insert and update payloads
+    pub fn skip_on_write(mut self, columns: &[&str]) -> Self {
+        self.skip_on_write
+            .extend(columns.iter().map(|column| column.to_string()));
+        self
+    }
+
+    /// Strip write-skipped columns from a row or an array of rows
+    fn strip_payload(&self, data: &mut JsonValue) {
+        if self.skip_on_write.is_empty() {
+            return;
+        }
+
+        let rows = match data {
+            JsonValue::Array(rows) => rows.iter_mut().collect(),
+            row => vec![row],
+        };
+        for row in rows {
+            if let Some(object) = row.as_object_mut() {
+                for column in &self.skip_on_write {
+                    object.remove(column);
+                }
+            }
+        }
+    }
+}
+
 impl Database {
     /// Create a new Database instance
     pub fn new(config: Arc<SupabaseConfig>, http_client: Arc<HttpClient>) -> Result<Self> {
         debug!("Initializing Database module");
 
+        let circuit = CircuitBreaker::new("database", config.http_config.circuit_breaker)
+            .with_retry(config.http_config.retry.clone())
+            .with_fingerprint(config.error_fingerprint);
+
         Ok(Self {
             http_client,
             config,
+            profiles: Arc::new(RwLock::new(HashMap::new())),
+            token_provider: RequestAuth::default(),
+            query_logging: Arc::new(RwLock::new(None)),
+            circuit,
         })
     }
 
-    /// Start a query from a table
-    pub fn from(&self, table: &str) -> QueryBuilder {
-        QueryBuilder::new(self.clone(), table.to_string())
-    }
-
-    /// Insert data into a table
-    pub fn insert(&self, table: &str) -> InsertBuilder {
-        InsertBuilder::new(self.clone(), table.to_string())
+    /// Circuit breaker guarding requests to the REST API
+    pub fn circuit_breaker(&self) -> &CircuitBreaker {
+        &self.circuit
     }
 
-    /// Upsert data into a table (insert or update if exists)
+    /// Log every executed query as pseudo-SQL, or stop with `None`
     ///
-    /// # Examples
+    /// Queries are logged at `info` level, e.g.
+    /// `SELECT title FROM posts WHERE published = ? LIMIT 10`, with values
+    /// hidden according to `redaction`. The setting is shared by all clones
+    /// of this database client. The rendering is for reading only; PostgREST
+    /// still receives the usual REST request.
     ///
-    /// ```rust,no_run
-    /// # use supabase_lib_rs::Client;
-    /// # use serde_json::{json, Value};
-    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
-    /// let client = Client::new("http://localhost:54321", "test-key").unwrap();
+    /// # Examples
+    /// ```rust
+    /// use supabase_lib_rs::{database::Redaction, Client};
     ///
-    /// // Upsert a user (insert if new, update if exists)
-    /// let user: Vec<Value> = client.database()
-    ///     .upsert("users")
-    ///     .values(json!({
-    ///         "id": 1,
-    ///         "name": "John Doe",
-    ///         "email": "john@example.com"
-    ///     }))
-    ///     .unwrap()
-    ///     .execute()
-    ///     .await
-    ///     .unwrap();
-    /// # Ok(())
-    /// # }
+    /// let client = Client::new("https://example.supabase.co", "your-anon-key")?;
+    /// client
+    ///     .database()
+    ///     .set_query_logging(Some(Redaction::Columns(vec!["email".to_string()])));
+    /// # Ok::<(), supabase_lib_rs::Error>(())
     /// ```
-    pub fn upsert(&self, table: &str) -> InsertBuilder {
-        InsertBuilder::new(self.clone(), table.to_string()).upsert()
+    pub fn set_query_logging(&self, redaction: Option<Redaction>) {
+        if let Ok(mut logging) = self.query_logging.write() {
+            *logging = redaction;
+        }
     }
 
-    /// Bulk insert multiple records at once
-    ///
-    /// # Examples
-    ///
-    /// ```rust,no_run
-    /// # use supabase_lib_rs::Client;
-    /// # use serde_json::{json, Value};
-    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
-    /// let client = Client::new("http://localhost:54321", "test-key").unwrap();
+    /// Log a query rendered by `render`, if query logging is on
+    fn log_query(&self, render: impl FnOnce(&Redaction) -> String) {
+        let redaction = self
+            .query_logging
+            .read()
+            .ok()
+            .and_then(|logging| logging.clone());
+        if let Some(redaction) = redaction {
+            info!("{}", render(&redaction));
+        }
+    }
+
+    /// Deserialize rows, renaming their columns per `field_casing`
+    fn decode<T>(&self, mut value: JsonValue) -> Result<T>
+    where
+        T: for<'de> Deserialize<'de>,
+    {
+        let casing = self.config.database_config.field_casing;
+        if casing != FieldCasing::SnakeCase {
+            rename_keys(&mut value, &|column| casing.to_field(column));
+        }
+        self.deserialize(value)
+    }
+
+    /// Deserialize response data, strictly if `strict_models` is set
+    fn deserialize<T>(&self, value: JsonValue) -> Result<T>
+    where
+        T: for<'de> Deserialize<'de>,
+    {
+        if !self.config.database_config.strict_models {
+            return Ok(serde_json::from_value(value)?);
+        }
+
+        strict::from_value(&value)
+            .map_err(|e| Error::database(format!("Response does not match the model: {}", e)))
+    }
+
+    /// Column name, or list of columns, for a field name per `field_casing`
+    fn column(&self, field: &str) -> String {
+        self.config.database_config.field_casing.to_column(field)
+    }
+
+    /// Serialize a row or rows to a payload keyed by column names
+    fn encode<T: Serialize>(&self, data: T) -> Result<JsonValue> {
+        let mut value = serde_json::to_value(data)?;
+        let casing = self.config.database_config.field_casing;
+        if casing != FieldCasing::SnakeCase {
+            rename_keys(&mut value, &|field| casing.to_column(field));
+        }
+        Ok(value)
+    }
+
+    /// Authorize requests with the access token from `provider`
+    ///
+    /// [`Client`](crate::Client) sets this to its auth session, so queries run
+    /// as the signed-in user. Without a token the API key is sent.
+    pub fn with_token_provider(mut self, provider: Arc<dyn TokenProvider>) -> Self {
+        self.token_provider = RequestAuth::new(provider);
+        self
+    }
+
+    /// Start a request carrying the current access token
+    fn build_request(&self, method: Method, url: &str) -> reqwest::RequestBuilder {
+        self.token_provider
+            .apply(self.http_client.request(method, url))
+    }
+
+    /// Attach `body` as JSON, gzipped once it reaches the configured
+    /// [`request_compression_threshold`](crate::types::HttpConfig::request_compression_threshold)
+    fn json_body<B: Serialize + ?Sized>(
+        &self,
+        request: reqwest::RequestBuilder,
+        body: &B,
+    ) -> reqwest::RequestBuilder {
+        let Some(threshold) = self.config.http_config.request_compression_threshold else {
+            return request.json(body);
+        };
+        let json = match serde_json::to_vec(body) {
+            Ok(json) if json.len() >= threshold => json,
+            _ => return request.json(body),
+        };
+
+        match compression::gzip(&json) {
+            Ok(compressed) => {
+                debug!(
+                    "Compressed request body from {} to {} bytes",
+                    json.len(),
+                    compressed.len()
+                );
+                request
+                    .header(reqwest::header::CONTENT_TYPE, "application/json")
+                    .header(reqwest::header::CONTENT_ENCODING, "gzip")
+                    .body(compressed)
+            }
+            Err(e) => {
+                warn!("Sending request body uncompressed: {}", e);
+                request.json(body)
+            }
+        }
+    }
+
+    /// Start a query from a table
+    pub fn from(&self, table: &str) -> QueryBuilder {
+        QueryBuilder::new(self.clone(), table.to_string())
+    }
+
+    /// Start a query from a [`Table`] type
+    ///
+    /// In debug builds the select list is checked against [`Table::COLUMNS`]
+    /// before the request is sent, so a misspelled column fails with its name
+    /// instead of a PostgREST 400.
+    pub fn from_typed<T: Table>(&self) -> QueryBuilder {
+        let mut query = self.from(T::NAME);
+        query.known_columns = Some(T::COLUMNS);
+        query
+    }
+
+    /// Register the profile for a [`Table`] type
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use serde::Deserialize;
+    /// use supabase_lib_rs::database::{Profile, Table};
+    /// # use supabase_lib_rs::Client;
+    ///
+    /// #[derive(Deserialize)]
+    /// struct Post {
+    ///     id: i64,
+    ///     title: String,
+    ///     author_id: i64,
+    /// }
+    ///
+    /// impl Table for Post {
+    ///     const NAME: &'static str = "posts";
+    /// }
+    ///
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let client = Client::new("http://localhost:54321", "test-key")?;
+    /// client.database().register_profile::<Post>(
+    ///     Profile::default_select("id,title,author_id").skip_on_write(&["id"]),
+    /// );
+    ///
+    /// // Selects only id,title,author_id; the body column is left out
+    /// let posts: Vec<Post> = client.database().from(Post::NAME).execute().await?;
+    ///
+    /// // Ask for the heavy column explicitly
+    /// let full: Vec<serde_json::Value> = client.database()
+    ///     .from(Post::NAME)
+    ///     .include_columns("body")
+    ///     .execute()
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn register_profile<T: Table>(&self, profile: Profile) {
+        self.register_table_profile(T::NAME, profile);
+    }
+
+    /// Register the profile for a table by name
+    ///
+    /// Profiles are shared by all clones of this database handle and replace
+    /// any profile previously registered for the table.
+    pub fn register_table_profile(&self, table: &str, profile: Profile) {
+        if let Ok(mut profiles) = self.profiles.write() {
+            profiles.insert(table.to_string(), profile);
+        }
+    }
+
+    /// Get the profile registered for a table
+    pub fn profile(&self, table: &str) -> Option<Profile> {
+        self.profiles.read().ok()?.get(table).cloned()
+    }
+
+    /// Insert data into a table
+    pub fn insert(&self, table: &str) -> InsertBuilder {
+        InsertBuilder::new(self.clone(), table.to_string())
+    }
+
+    /// Upsert data into a table (insert or update if exists)
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// # use supabase_lib_rs::Client;
+    /// # use serde_json::{json, Value};
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = Client::new("http://localhost:54321", "test-key").unwrap();
+    ///
+    /// // Upsert a user (insert if new, update if exists)
+    /// let user: Vec<Value> = client.database()
+    ///     .upsert("users")
+    ///     .values(json!({
+    ///         "id": 1,
+    ///         "name": "John Doe",
+    ///         "email": "john@example.com"
+    ///     }))
+    ///     .unwrap()
+    ///     .execute()
+    ///     .await
+    ///     .unwrap();
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn upsert(&self, table: &str) -> InsertBuilder {
+        InsertBuilder::new(self.clone(), table.to_string()).upsert()
+    }
+
+    /// Bulk insert multiple records at once
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// # use supabase_lib_rs::Client;
+    /// # use serde_json::{json, Value};
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = Client::new("http://localhost:54321", "test-key").unwrap();
     ///
     /// // Insert multiple users at once
     /// let users: Vec<Value> = client.database()
@@ -217,13 +1228,14 @@ impl Database {
             data.len()
         );
 
+        let data = self.encode(data)?;
         let url = format!("{}/{}", self.rest_url(), table);
         let response = self
-            .http_client
-            .post(&url)
-            .json(&data)
-            .header("Prefer", "return=representation")
-            .send()
+            .circuit
+            .send(
+                self.json_body(self.build_request(Method::POST, &url), &data)
+                    .header("Prefer", "return=representation"),
+            )
             .await?;
 
         if !response.status().is_success() {
@@ -232,10 +1244,10 @@ impl Database {
                 Ok(text) => text,
                 Err(_) => format!("Bulk insert failed with status: {}", status),
             };
-            return Err(Error::database(error_msg));
+            return Err(Error::database(error_msg).with_fingerprint(self.config.error_fingerprint));
         }
 
-        let result: Vec<T> = response.json().await?;
+        let result: Vec<T> = self.decode(response.json().await?)?;
         info!("Bulk insert executed successfully on table: {}", table);
         Ok(result)
     }
@@ -271,16 +1283,17 @@ impl Database {
             data.len()
         );
 
+        let data = self.encode(data)?;
         let url = format!("{}/{}", self.rest_url(), table);
         let response = self
-            .http_client
-            .post(&url)
-            .json(&data)
-            .header(
-                "Prefer",
-                "return=representation,resolution=merge-duplicates",
+            .circuit
+            .send(
+                self.json_body(self.build_request(Method::POST, &url), &data)
+                    .header(
+                        "Prefer",
+                        "return=representation,resolution=merge-duplicates",
+                    ),
             )
-            .send()
             .await?;
 
         if !response.status().is_success() {
@@ -289,10 +1302,10 @@ impl Database {
                 Ok(text) => text,
                 Err(_) => format!("Bulk upsert failed with status: {}", status),
             };
-            return Err(Error::database(error_msg));
+            return Err(Error::database(error_msg).with_fingerprint(self.config.error_fingerprint));
         }
 
-        let result: Vec<T> = response.json().await?;
+        let result: Vec<T> = self.decode(response.json().await?)?;
         info!("Bulk upsert executed successfully on table: {}", table);
         Ok(result)
     }
@@ -334,14 +1347,14 @@ impl Database {
             JsonValue::Array(arr) => {
                 let mut results = Vec::with_capacity(arr.len());
                 for item in arr {
-                    let parsed: T = serde_json::from_value(item)?;
+                    let parsed: T = self.decode(item)?;
                     results.push(parsed);
                 }
                 Ok(results)
             }
             _ => {
                 // If single result, wrap in array
-                let parsed: T = serde_json::from_value(result)?;
+                let parsed: T = self.decode(result)?;
                 Ok(vec![parsed])
             }
         }
@@ -382,14 +1395,14 @@ impl Database {
             JsonValue::Array(arr) => {
                 let mut results = Vec::with_capacity(arr.len());
                 for item in arr {
-                    let parsed: T = serde_json::from_value(item)?;
+                    let parsed: T = self.decode(item)?;
                     results.push(parsed);
                 }
                 Ok(results)
             }
             _ => {
                 // If single result, wrap in array
-                let parsed: T = serde_json::from_value(result)?;
+                let parsed: T = self.decode(result)?;
                 Ok(vec![parsed])
             }
         }
@@ -432,6 +1445,42 @@ impl Database {
         }
     }
 
+    /// Estimate the number of rows in a table without scanning it
+    ///
+    /// Reads PostgreSQL's planner statistics through the helper function in
+    /// [`APPROXIMATE_COUNT_SQL`], which must be installed first. Unlike
+    /// `count=exact` this takes constant time on tables of any size, at the
+    /// cost of precision: the estimate is refreshed by `ANALYZE` and
+    /// autovacuum, and is 0 for tables never analyzed. The table is looked up
+    /// in the configured database schema.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// # use supabase_lib_rs::Client;
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = Client::new("http://localhost:54321", "test-key")?;
+    ///
+    /// let events = client.database().approximate_count("events").await?;
+    /// println!("~{} events", events);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn approximate_count(&self, table: &str) -> Result<u64> {
+        debug!("Estimating row count of table: {}", table);
+
+        let params = json!({
+            "table_name": table,
+            "schema_name": self.config.database_config.schema,
+        });
+        let result = self
+            .rpc(APPROXIMATE_COUNT_FUNCTION, Some(params))
+            .await
+            .map_err(missing_count_helper)?;
+
+        parse_approximate_count(table, result)
+    }
+
     /// Begin a database transaction (via RPC)
     ///
     /// # Examples
@@ -481,14 +1530,14 @@ impl Database {
             JsonValue::Array(arr) => {
                 let mut results = Vec::with_capacity(arr.len());
                 for item in arr {
-                    let parsed: T = serde_json::from_value(item)?;
+                    let parsed: T = self.decode(item)?;
                     results.push(parsed);
                 }
                 Ok(results)
             }
             _ => {
                 // If single result, wrap in array
-                let parsed: T = serde_json::from_value(result)?;
+                let parsed: T = self.decode(result)?;
                 Ok(vec![parsed])
             }
         }
@@ -534,15 +1583,15 @@ impl Database {
     pub async fn rpc(&self, function_name: &str, params: Option<JsonValue>) -> Result<JsonValue> {
         debug!("Executing RPC function: {}", function_name);
 
-        let url = format!("{}/rest/v1/rpc/{}", self.config.url, function_name);
+        let url = format!("{}/rpc/{}", self.rest_url(), function_name);
 
-        let mut request = self.http_client.post(&url);
+        let mut request = self.build_request(Method::POST, &url);
 
         if let Some(params) = params {
             request = request.json(&params);
         }
 
-        let response = request.send().await?;
+        let response = self.circuit.send(request).await?;
 
         if !response.status().is_success() {
             let status = response.status();
@@ -550,7 +1599,7 @@ impl Database {
                 Ok(text) => text,
                 Err(_) => format!("RPC failed with status: {}", status),
             };
-            return Err(Error::database(error_msg));
+            return Err(Error::database(error_msg).with_fingerprint(self.config.error_fingerprint));
         }
 
         let result: JsonValue = response.json().await?;
@@ -559,14 +1608,327 @@ impl Database {
         Ok(result)
     }
 
+    /// Call a database function and deserialize its result into `T`
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// # use supabase_lib_rs::Client;
+    /// # use serde::Deserialize;
+    /// # use serde_json::json;
+    /// #[derive(Deserialize)]
+    /// struct Leader {
+    ///     name: String,
+    ///     score: i64,
+    /// }
+    ///
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let client = Client::new("http://localhost:54321", "test-key")?;
+    /// let leaders: Vec<Leader> = client.database()
+    ///     .rpc_typed("top_players", Some(json!({"limit": 10})))
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn rpc_typed<T>(&self, function_name: &str, params: Option<JsonValue>) -> Result<T>
+    where
+        T: for<'de> Deserialize<'de>,
+    {
+        let mut builder = self.rpc_builder(function_name);
+        builder.params = params;
+        builder.returns::<T>().execute().await
+    }
+
+    /// Start a call to a database function
+    ///
+    /// Unlike [`rpc`](Database::rpc), the builder can call read-only (`STABLE`
+    /// or `IMMUTABLE`) functions with GET, return a single object, count the
+    /// returned rows and target a schema other than the default.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// # use supabase_lib_rs::Client;
+    /// # use serde::Deserialize;
+    /// # use serde_json::json;
+    /// #[derive(Deserialize)]
+    /// struct Stats {
+    ///     posts: i64,
+    ///     comments: i64,
+    /// }
+    ///
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let client = Client::new("http://localhost:54321", "test-key")?;
+    /// // GET /rest/v1/rpc/user_stats?user_id=42, one row as an object
+    /// let stats = client.database()
+    ///     .rpc_builder("user_stats")
+    ///     .params(json!({"user_id": 42}))?
+    ///     .read_only()
+    ///     .single()
+    ///     .returns::<Stats>()
+    ///     .execute()
+    ///     .await?;
+    ///
+    /// // Number of rows a set-returning function yields, without the rows
+    /// let total = client.database()
+    ///     .rpc_builder("search_posts")
+    ///     .params(json!({"term": "rust"}))?
+    ///     .schema("api")
+    ///     .count()
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn rpc_builder(&self, function_name: &str) -> RpcBuilder {
+        RpcBuilder::new(self.clone(), function_name.to_string())
+    }
+
+    /// Send a raw request to the REST API
+    ///
+    /// An escape hatch for PostgREST features the builders do not model yet.
+    /// `path` is relative to `/rest/v1`; the client's API key and authorization
+    /// headers are sent as usual. An empty response body deserializes as JSON
+    /// `null`, so use `()` or `Option<T>` for requests that return nothing.
+    ///
+    /// Failed requests return a database error whose context carries the HTTP
+    /// status, headers and body; 429 and 5xx responses are marked retryable,
+    /// with the `Retry-After` delay when the server sends one.
+    ///
+    /// # Examples
+    /// ```rust,no_run
+    /// use serde_json::{json, Value};
+    /// use supabase_lib_rs::database::Method;
+    /// # use supabase_lib_rs::Client;
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let client = Client::new("http://localhost:54321", "test-key")?;
+    ///
+    /// // Full-text search, not modelled by the query builder
+    /// let response = client.database()
+    ///     .request::<Vec<Value>>(
+    ///         Method::GET,
+    ///         "/articles",
+    ///         &[("select", "id,title"), ("body", "fts(english).rust")],
+    ///         None,
+    ///     )
+    ///     .await?;
+    /// println!("{} articles, status {}", response.data.len(), response.meta.status);
+    ///
+    /// // Update with a custom body
+    /// client.database()
+    ///     .request::<()>(Method::PATCH, "/articles", &[("id", "eq.1")], Some(json!({"title": "New"})))
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn request<T>(
+        &self,
+        method: Method,
+        path: &str,
+        query: &[(&str, &str)],
+        body: Option<JsonValue>,
+    ) -> Result<TypedResponse<T>>
+    where
+        T: for<'de> Deserialize<'de>,
+    {
+        self.request_with_headers(method, path, query, body, &[])
+            .await
+    }
+
+    /// Send a raw request to the REST API with extra headers (e.g. `Prefer`)
+    pub async fn request_with_headers<T>(
+        &self,
+        method: Method,
+        path: &str,
+        query: &[(&str, &str)],
+        body: Option<JsonValue>,
+        headers: &[(&str, &str)],
+    ) -> Result<TypedResponse<T>>
+    where
+        T: for<'de> Deserialize<'de>,
+    {
+        let url = self.raw_request_url(path, query)?;
+        debug!("Executing raw {} request: {}", method, url);
+
+        let mut request = self.build_request(method.clone(), url.as_str());
+        for (name, value) in headers {
+            request = request.header(*name, *value);
+        }
+        if let Some(body) = body {
+            request = request.json(&body);
+        }
+
+        let response = self.circuit.send(request).await?;
+        let meta = ResponseMeta::from_response(&response);
+        let text = response.text().await?;
+
+        if !(200..300).contains(&meta.status) {
+            let retryable = meta.status == 429 || meta.status >= 500;
+            let context = ErrorContext {
+                http: Some(HttpErrorContext {
+                    status_code: Some(meta.status),
+                    headers: Some(meta.headers.clone()),
+                    response_body: Some(text.clone()),
+                    url: Some(url.to_string()),
+                    method: Some(method.to_string()),
+                }),
+                retry: retryable.then(|| RetryInfo {
+                    attempts: 1,
+                    retryable,
+                    retry_after: meta.rate_limit.as_ref().and_then(|limit| limit.retry_after),
+                }),
+                rate_limit: meta.rate_limit.clone(),
+                ..Default::default()
+            };
+            let message = if text.is_empty() {
+                format!("Request failed with status: {}", meta.status)
+            } else {
+                text
+            };
+            return Err(Error::database_with_context(message, context)
+                .with_fingerprint(self.config.error_fingerprint));
+        }
+
+        let data = if text.trim().is_empty() {
+            self.deserialize(JsonValue::Null)?
+        } else {
+            self.deserialize(serde_json::from_str(&text)?)?
+        };
+
+        info!("Raw {} request to {} executed successfully", method, path);
+        Ok(TypedResponse { data, meta })
+    }
+
+    /// Resolve a raw request path under the REST URL
+    fn raw_request_url(&self, path: &str, query: &[(&str, &str)]) -> Result<Url> {
+        let path = path.trim_start_matches('/');
+        if path.contains("://") || path.split('/').any(|segment| segment == "..") {
+            return Err(Error::invalid_input(format!(
+                "Raw request path must be relative to the REST API: {}",
+                path
+            )));
+        }
+
+        let mut url = Url::parse(&format!("{}/{}", self.rest_url(), path))?;
+        if !query.is_empty() {
+            url.query_pairs_mut().extend_pairs(query);
+        }
+
+        Ok(url)
+    }
+
+    /// Count and sample the rows matching `filters` without modifying them
+    async fn preview_affected<T>(
+        &self,
+        table: &str,
+        filters: &[Filter],
+        columns: Option<&str>,
+    ) -> Result<DryRun<T>>
+    where
+        T: for<'de> Deserialize<'de>,
+    {
+        let url = self.dry_run_url(table, filters, columns)?;
+        debug!("Executing dry run query: {}", url);
+
+        let response = self
+            .circuit
+            .send(
+                self.build_request(Method::GET, url.as_str())
+                    .header("Prefer", "count=exact"),
+            )
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_msg = match response.text().await {
+                Ok(text) => text,
+                Err(_) => format!("Dry run failed with status: {}", status),
+            };
+            return Err(Error::database(error_msg).with_fingerprint(self.config.error_fingerprint));
+        }
+
+        let meta = ResponseMeta::from_response(&response);
+        let sample: Vec<T> = self.decode(response.json().await?)?;
+        let affected = meta
+            .content_range
+            .and_then(|range| range.total)
+            .unwrap_or(sample.len() as u64);
+
+        info!("Dry run on table {} matched {} rows", table, affected);
+        Ok(DryRun { affected, sample })
+    }
+
+    /// Send a mutation with `return=minimal` and read the affected row count
+    async fn affected_rows(&self, request: reqwest::RequestBuilder, action: &str) -> Result<u64> {
+        let request = request.header("Prefer", "return=minimal,count=exact");
+        let response = self.circuit.send(request).await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_msg = match response.text().await {
+                Ok(text) => text,
+                Err(_) => format!("{} failed with status: {}", action, status),
+            };
+            return Err(Error::database(error_msg).with_fingerprint(self.config.error_fingerprint));
+        }
+
+        let meta = ResponseMeta::from_response(&response);
+        let affected = meta
+            .content_range
+            .and_then(|range| range.total)
+            .ok_or_else(|| {
+                Error::database(format!("{} response has no count in Content-Range", action))
+            })?;
+
+        info!("{} affected {} rows", action, affected);
+        Ok(affected)
+    }
+
+    /// Build the SELECT equivalent of an UPDATE or DELETE
+    fn dry_run_url(&self, table: &str, filters: &[Filter], columns: Option<&str>) -> Result<Url> {
+        let mut query = self.from(table).limit(DRY_RUN_SAMPLE_SIZE);
+        query.filters = filters.to_vec();
+        if let Some(columns) = columns {
+            query = query.select(columns);
+        }
+
+        query.build_url()
+    }
+
     /// Get the base REST URL
     fn rest_url(&self) -> String {
-        format!("{}/rest/v1", self.config.url)
+        self.config.project_url().rest_url()
+    }
+
+    /// Table URL with `filters` as query parameters, for UPDATE and DELETE
+    ///
+    /// Fails without filters unless `allow_unfiltered` is set.
+    fn filtered_url(
+        &self,
+        action: &str,
+        table: &str,
+        filters: &[Filter],
+        allow_unfiltered: bool,
+    ) -> Result<Url> {
+        if filters.is_empty() && !allow_unfiltered {
+            return Err(Error::invalid_input(format!(
+                "Refusing to {} every row of {} without filters; call allow_unfiltered() to confirm",
+                action, table
+            )));
+        }
+
+        let mut url = Url::parse(&format!("{}/{}", self.rest_url(), table))?;
+        for (key, value) in self.build_query_params(filters) {
+            url.query_pairs_mut().append_pair(&key, &value);
+        }
+        Ok(url)
     }
 
     /// Build query parameters from filters
-    fn build_query_params(&self, filters: &[Filter]) -> HashMap<String, String> {
-        let mut params = HashMap::new();
+    ///
+    /// Parameters keep their order and may repeat, e.g. two bounds on one
+    /// column, which PostgREST combines with AND.
+    fn build_query_params(&self, filters: &[Filter]) -> Vec<(String, String)> {
+        let mut params = Vec::new();
 
         for filter in filters {
             self.build_filter_params(filter, &mut params);
@@ -576,34 +1938,17 @@ impl Database {
     }
 
     /// Build parameters for a single filter (recursive for logical operators)
-    fn build_filter_params(&self, filter: &Filter, params: &mut HashMap<String, String>) {
+    fn build_filter_params(&self, filter: &Filter, params: &mut Vec<(String, String)>) {
         match filter {
             Filter::Simple {
                 column,
                 operator,
                 value,
             } => {
-                let filter_value = match operator {
-                    FilterOperator::Equal => format!("eq.{}", value),
-                    FilterOperator::NotEqual => format!("neq.{}", value),
-                    FilterOperator::GreaterThan => format!("gt.{}", value),
-                    FilterOperator::GreaterThanOrEqual => format!("gte.{}", value),
-                    FilterOperator::LessThan => format!("lt.{}", value),
-                    FilterOperator::LessThanOrEqual => format!("lte.{}", value),
-                    FilterOperator::Like => format!("like.{}", value),
-                    FilterOperator::ILike => format!("ilike.{}", value),
-                    FilterOperator::Is => format!("is.{}", value),
-                    FilterOperator::In => format!("in.{}", value),
-                    FilterOperator::Contains => format!("cs.{}", value),
-                    FilterOperator::ContainedBy => format!("cd.{}", value),
-                    FilterOperator::StrictlyLeft => format!("sl.{}", value),
-                    FilterOperator::StrictlyRight => format!("sr.{}", value),
-                    FilterOperator::NotExtendToRight => format!("nxr.{}", value),
-                    FilterOperator::NotExtendToLeft => format!("nxl.{}", value),
-                    FilterOperator::Adjacent => format!("adj.{}", value),
-                };
-
-                params.insert(column.clone(), filter_value);
+                params.push((
+                    self.column(column),
+                    format!("{}.{}", operator_code(operator), value),
+                ));
             }
             Filter::And(filters) => {
                 // AND is the default behavior in PostgREST - just add all filters
@@ -613,146 +1958,124 @@ impl Database {
             }
             Filter::Or(filters) => {
                 // OR requires special syntax: or=(condition1,condition2,...)
-                let or_conditions: Vec<String> = filters
-                    .iter()
-                    .map(|f| self.build_filter_condition(f))
-                    .collect();
-
-                if !or_conditions.is_empty() {
-                    let or_value = format!("({})", or_conditions.join(","));
-                    params.insert("or".to_string(), or_value);
+                if !filters.is_empty() {
+                    params.push(("or".to_string(), self.build_condition_list(filters)));
                 }
             }
-            Filter::Not(filter) => {
-                // NOT requires prefixing with "not."
-                match filter.as_ref() {
-                    Filter::Simple {
-                        column,
-                        operator,
-                        value,
-                    } => {
-                        let filter_value = match operator {
-                            FilterOperator::Equal => format!("eq.{}", value),
-                            FilterOperator::NotEqual => format!("neq.{}", value),
-                            FilterOperator::GreaterThan => format!("gt.{}", value),
-                            FilterOperator::GreaterThanOrEqual => format!("gte.{}", value),
-                            FilterOperator::LessThan => format!("lt.{}", value),
-                            FilterOperator::LessThanOrEqual => format!("lte.{}", value),
-                            FilterOperator::Like => format!("like.{}", value),
-                            FilterOperator::ILike => format!("ilike.{}", value),
-                            FilterOperator::Is => format!("is.{}", value),
-                            FilterOperator::In => format!("in.{}", value),
-                            FilterOperator::Contains => format!("cs.{}", value),
-                            FilterOperator::ContainedBy => format!("cd.{}", value),
-                            FilterOperator::StrictlyLeft => format!("sl.{}", value),
-                            FilterOperator::StrictlyRight => format!("sr.{}", value),
-                            FilterOperator::NotExtendToRight => format!("nxr.{}", value),
-                            FilterOperator::NotExtendToLeft => format!("nxl.{}", value),
-                            FilterOperator::Adjacent => format!("adj.{}", value),
-                        };
-
-                        params.insert(format!("not.{}", column), filter_value);
-                    }
-                    Filter::And(and_filters) => {
-                        // NOT(AND(...)) becomes NOT with multiple conditions
-                        let and_conditions: Vec<String> = and_filters
-                            .iter()
-                            .map(|f| self.build_filter_condition(f))
-                            .collect();
-
-                        if !and_conditions.is_empty() {
-                            let not_value = format!("and.({})", and_conditions.join(","));
-                            params.insert("not".to_string(), not_value);
-                        }
-                    }
-                    Filter::Or(or_filters) => {
-                        // NOT(OR(...)) becomes NOT with OR conditions
-                        let or_conditions: Vec<String> = or_filters
-                            .iter()
-                            .map(|f| self.build_filter_condition(f))
-                            .collect();
-
-                        if !or_conditions.is_empty() {
-                            let not_value = format!("or.({})", or_conditions.join(","));
-                            params.insert("not".to_string(), not_value);
-                        }
-                    }
-                    Filter::Not(_) => {
-                        // Double negation - just apply the inner filter normally
-                        // NOT(NOT(x)) = x
-                        if let Filter::Not(inner) = filter.as_ref() {
-                            self.build_filter_params(inner, params);
-                        }
-                    }
+            Filter::Not(filter) => match filter.as_ref() {
+                // Negation prefixes the operator: column=not.op.value
+                Filter::Simple {
+                    column,
+                    operator,
+                    value,
+                } => {
+                    params.push((
+                        self.column(column),
+                        format!("not.{}.{}", operator_code(operator), value),
+                    ));
                 }
-            }
-        }
+                Filter::And(filters) if !filters.is_empty() => {
+                    params.push(("not.and".to_string(), self.build_condition_list(filters)));
+                }
+                Filter::Or(filters) if !filters.is_empty() => {
+                    params.push(("not.or".to_string(), self.build_condition_list(filters)));
+                }
+                Filter::And(_) | Filter::Or(_) => {}
+                // NOT(NOT(x)) = x
+                Filter::Not(inner) => self.build_filter_params(inner, params),
+            },
+        }
+    }
+
+    /// Conditions of a logic tree, as `(condition1,condition2,...)`
+    fn build_condition_list(&self, filters: &[Filter]) -> String {
+        let conditions: Vec<String> = filters
+            .iter()
+            .map(|f| self.build_filter_condition(f))
+            .collect();
+        format!("({})", conditions.join(","))
     }
 
     /// Build a single condition string for complex filters
-    #[allow(clippy::only_used_in_recursion)]
+    ///
+    /// Uses the logic tree syntax of `or=(...)` parameters: `column.op.value`,
+    /// `column.not.op.value`, `and(...)`, `or(...)`, `not.and(...)` and `not.or(...)`.
     fn build_filter_condition(&self, filter: &Filter) -> String {
         match filter {
             Filter::Simple {
                 column,
                 operator,
                 value,
-            } => {
-                let op_str = match operator {
-                    FilterOperator::Equal => "eq",
-                    FilterOperator::NotEqual => "neq",
-                    FilterOperator::GreaterThan => "gt",
-                    FilterOperator::GreaterThanOrEqual => "gte",
-                    FilterOperator::LessThan => "lt",
-                    FilterOperator::LessThanOrEqual => "lte",
-                    FilterOperator::Like => "like",
-                    FilterOperator::ILike => "ilike",
-                    FilterOperator::Is => "is",
-                    FilterOperator::In => "in",
-                    FilterOperator::Contains => "cs",
-                    FilterOperator::ContainedBy => "cd",
-                    FilterOperator::StrictlyLeft => "sl",
-                    FilterOperator::StrictlyRight => "sr",
-                    FilterOperator::NotExtendToRight => "nxr",
-                    FilterOperator::NotExtendToLeft => "nxl",
-                    FilterOperator::Adjacent => "adj",
-                };
-                format!("{}.{}.{}", column, op_str, value)
-            }
-            Filter::And(filters) => {
-                let conditions: Vec<String> = filters
-                    .iter()
-                    .map(|f| self.build_filter_condition(f))
-                    .collect();
-                format!("and.({})", conditions.join(","))
-            }
-            Filter::Or(filters) => {
-                let conditions: Vec<String> = filters
-                    .iter()
-                    .map(|f| self.build_filter_condition(f))
-                    .collect();
-                format!("or.({})", conditions.join(","))
-            }
-            Filter::Not(filter) => {
-                let condition = self.build_filter_condition(filter);
-                format!("not.({})", condition)
-            }
+            } => format!(
+                "{}.{}.{}",
+                self.column(column),
+                operator_code(operator),
+                value
+            ),
+            Filter::And(filters) => format!("and{}", self.build_condition_list(filters)),
+            Filter::Or(filters) => format!("or{}", self.build_condition_list(filters)),
+            Filter::Not(filter) => match filter.as_ref() {
+                Filter::Simple {
+                    column,
+                    operator,
+                    value,
+                } => format!(
+                    "{}.not.{}.{}",
+                    self.column(column),
+                    operator_code(operator),
+                    value
+                ),
+                Filter::Not(inner) => self.build_filter_condition(inner),
+                tree => format!("not.{}", self.build_filter_condition(tree)),
+            },
         }
     }
 }
 
+/// PostgREST operator for a filter
+fn operator_code(operator: &FilterOperator) -> &'static str {
+    match operator {
+        FilterOperator::Equal => "eq",
+        FilterOperator::NotEqual => "neq",
+        FilterOperator::GreaterThan => "gt",
+        FilterOperator::GreaterThanOrEqual => "gte",
+        FilterOperator::LessThan => "lt",
+        FilterOperator::LessThanOrEqual => "lte",
+        FilterOperator::Like => "like",
+        FilterOperator::ILike => "ilike",
+        FilterOperator::Is => "is",
+        FilterOperator::In => "in",
+        FilterOperator::Contains => "cs",
+        FilterOperator::ContainedBy => "cd",
+        FilterOperator::StrictlyLeft => "sl",
+        FilterOperator::StrictlyRight => "sr",
+        FilterOperator::NotExtendToRight => "nxr",
+        FilterOperator::NotExtendToLeft => "nxl",
+        FilterOperator::Adjacent => "adj",
+    }
+}
+
 impl QueryBuilder {
     fn new(database: Database, table: String) -> Self {
+        let columns = database.profile(&table).and_then(|profile| profile.select);
+
         Self {
             database,
             table,
-            columns: None,
+            columns,
             filters: Vec::new(),
             order_by: Vec::new(),
             limit: None,
             offset: None,
             single: false,
+            count: None,
+            range: None,
             joins: Vec::new(),
+            computed_columns: Vec::new(),
+            budget: None,
+            known_columns: None,
+            #[cfg(feature = "postgis")]
+            geo_filter: None,
         }
     }
 
@@ -762,6 +2085,15 @@ impl QueryBuilder {
         self
     }
 
+    /// Add columns to the current selection, e.g. ones left out by the table profile
+    pub fn include_columns(mut self, columns: &str) -> Self {
+        self.columns = Some(match self.columns.take() {
+            Some(existing) if existing != "*" => format!("{},{}", existing, columns),
+            _ => format!("*,{}", columns),
+        });
+        self
+    }
+
     /// Add an equality filter
     pub fn eq(mut self, column: &str, value: &str) -> Self {
         self.filters.push(Filter::Simple {
@@ -873,6 +2205,76 @@ impl QueryBuilder {
         self
     }
 
+    /// Add ordering by several columns, in priority order
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// # use supabase_lib_rs::Client;
+    /// # use supabase_lib_rs::types::OrderDirection::Descending;
+    /// # use serde_json::Value;
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = Client::new("https://example.supabase.co", "your-anon-key")?;
+    ///
+    /// // order=created_at.desc,id.desc
+    /// let posts: Vec<Value> = client
+    ///     .database()
+    ///     .from("posts")
+    ///     .select("*")
+    ///     .order_many(&[("created_at", Descending), ("id", Descending)])
+    ///     .execute()
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn order_many(mut self, columns: &[(&str, OrderDirection)]) -> Self {
+        for (column, direction) in columns {
+            self = self.order(column, direction.clone());
+        }
+        self
+    }
+
+    /// Sort NULLs before other values in the last added ordering
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// # use supabase_lib_rs::Client;
+    /// # use supabase_lib_rs::types::OrderDirection;
+    /// # use serde_json::Value;
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = Client::new("https://example.supabase.co", "your-anon-key")?;
+    ///
+    /// // order=published_at.desc.nullslast,id.desc
+    /// let posts: Vec<Value> = client
+    ///     .database()
+    ///     .from("posts")
+    ///     .select("*")
+    ///     .order("published_at", OrderDirection::Descending)
+    ///     .nulls_last()
+    ///     .order("id", OrderDirection::Descending)
+    ///     .execute()
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn nulls_first(self) -> Self {
+        self.set_nulls_first(true)
+    }
+
+    /// Sort NULLs after other values in the last added ordering
+    pub fn nulls_last(self) -> Self {
+        self.set_nulls_first(false)
+    }
+
+    fn set_nulls_first(mut self, nulls_first: bool) -> Self {
+        match self.order_by.last_mut() {
+            Some(order) => order.nulls_first = Some(nulls_first),
+            None => warn!("nulls_first/nulls_last called before order(); ignoring"),
+        }
+        self
+    }
+
     /// Set limit
     pub fn limit(mut self, limit: u32) -> Self {
         self.limit = Some(limit);
@@ -891,6 +2293,86 @@ impl QueryBuilder {
         self
     }
 
+    /// Return rows `from` through `to` (inclusive, 0-based)
+    ///
+    /// Sent as `Range`/`Range-Unit` headers rather than `limit` and `offset`
+    /// query parameters. Order the query so the pages are stable.
+    pub fn range(mut self, from: u64, to: u64) -> Self {
+        self.range = Some((from, to));
+        self
+    }
+
+    /// Fetch all matching rows page by page, as a stream of pages
+    ///
+    /// Each page is requested with [`range`](QueryBuilder::range) when the
+    /// previous one has been consumed, and the stream ends after the first
+    /// page shorter than `page_size` or the first error; `limit` and `offset`
+    /// are ignored. A page past the end of the table, which PostgREST answers
+    /// with `416 Range Not Satisfiable` when the rows shrank meanwhile, ends
+    /// the stream as well. Order the query by a unique column, or rows may be
+    /// skipped or repeated between pages.
+    ///
+    /// # Examples
+    /// ```rust,no_run
+    /// use futures_util::TryStreamExt;
+    /// use serde_json::Value;
+    /// use supabase_lib_rs::types::OrderDirection;
+    /// # use supabase_lib_rs::Client;
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let client = Client::new("http://localhost:54321", "test-key")?;
+    ///
+    /// let mut pages = Box::pin(
+    ///     client.database()
+    ///         .from("events")
+    ///         .order("id", OrderDirection::Ascending)
+    ///         .paginate::<Value>(1000),
+    /// );
+    /// while let Some(page) = pages.try_next().await? {
+    ///     println!("Exporting {} rows", page.len());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn paginate<T>(&self, page_size: u64) -> impl futures_util::Stream<Item = Result<Vec<T>>>
+    where
+        T: for<'de> Deserialize<'de>,
+    {
+        let mut query = self.clone();
+        query.single = false;
+        query.limit = None;
+        query.offset = None;
+
+        futures_util::stream::unfold(Some(0u64), move |from| {
+            let query = query.clone();
+            async move {
+                let from = from?;
+                if page_size == 0 {
+                    let error = Error::invalid_input("Page size must be greater than zero");
+                    return Some((Err(error), None));
+                }
+
+                match query.range(from, from + page_size - 1).execute::<T>().await {
+                    Ok(page) => {
+                        let next = (page.len() as u64 == page_size).then_some(from + page_size);
+                        (!page.is_empty()).then_some((Ok(page), next))
+                    }
+                    Err(e) if is_range_not_satisfiable(&e) => None,
+                    Err(e) => Some((Err(e), None)),
+                }
+            }
+        })
+    }
+
+    /// Ask PostgREST to count all matching rows with `strategy`
+    ///
+    /// The total arrives in the `Content-Range` header; read it with
+    /// [`execute_with_count`](QueryBuilder::execute_with_count) or from
+    /// [`ResponseMeta::content_range`].
+    pub fn count(mut self, strategy: CountStrategy) -> Self {
+        self.count = Some(strategy);
+        self
+    }
+
     /// Group filters with AND logic
     ///
     /// # Examples
@@ -1016,6 +2498,12 @@ impl QueryBuilder {
         self
     }
 
+    /// Add a prebuilt filter, e.g. one produced by [`SafeFilterParser`]
+    pub fn with_filter(mut self, filter: Filter) -> Self {
+        self.filters.push(filter);
+        self
+    }
+
     /// Add an INNER JOIN to another table
     ///
     /// # Examples
@@ -1145,92 +2633,645 @@ impl QueryBuilder {
         self
     }
 
-    /// Execute the query
-    pub async fn execute<T>(&self) -> Result<Vec<T>>
-    where
-        T: for<'de> Deserialize<'de>,
-    {
-        debug!("Executing SELECT query on table: {}", self.table);
+    /// Embed a related table's rows (1:N or N:1) declared by a [`Relation`]
+    ///
+    /// # Examples
+    /// ```rust,no_run
+    /// use serde::Deserialize;
+    /// use supabase_lib_rs::database::{Relation, Table};
+    /// # use supabase_lib_rs::Client;
+    ///
+    /// #[derive(Deserialize)]
+    /// struct Author { id: i64, name: String }
+    /// impl Table for Author { const NAME: &'static str = "authors"; }
+    ///
+    /// #[derive(Deserialize)]
+    /// struct Comment { id: i64, body: String }
+    /// impl Table for Comment { const NAME: &'static str = "comments"; }
+    ///
+    /// #[derive(Deserialize)]
+    /// struct Post {
+    ///     id: i64,
+    ///     title: String,
+    ///     author: Option<Author>,
+    ///     comments: Vec<Comment>,
+    /// }
+    /// impl Table for Post { const NAME: &'static str = "posts"; }
+    ///
+    /// // posts.author_id -> authors.id (N:1)
+    /// struct PostAuthor;
+    /// impl Relation for PostAuthor {
+    ///     type Target = Author;
+    ///     const FIELD: &'static str = "author";
+    ///     const FOREIGN_KEY: Option<&'static str> = Some("author_id");
+    /// }
+    ///
+    /// // comments.post_id -> posts.id (1:N)
+    /// struct Comments;
+    /// impl Relation for Comments {
+    ///     type Target = Comment;
+    ///     const FIELD: &'static str = "comments";
+    ///     const COLUMNS: Option<&'static str> = Some("id,body");
+    /// }
+    ///
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = Client::new("http://localhost:54321", "test-key")?;
+    ///
+    /// // SELECT *, author:authors!author_id(*), comments:comments(id,body) FROM posts
+    /// let posts: Vec<Post> = client.database()
+    ///     .from(Post::NAME)
+    ///     .with_related::<PostAuthor>()
+    ///     .with_related::<Comments>()
+    ///     .execute()
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_related<R: Relation>(self) -> Self {
+        self.join_relation::<R>(JoinType::Left)
+    }
 
-        let mut url = Url::parse(&format!("{}/{}", self.database.rest_url(), self.table))?;
+    /// Embed a related table and only return rows that have related rows
+    pub fn with_related_inner<R: Relation>(self) -> Self {
+        self.join_relation::<R>(JoinType::Inner)
+    }
 
-        // Add query parameters
-        let mut query_params = self.database.build_query_params(&self.filters);
+    fn join_relation<R: Relation>(mut self, join_type: JoinType) -> Self {
+        let target = R::Target::NAME;
+        let foreign_table = match R::FOREIGN_KEY {
+            Some(foreign_key) => format!("{}!{}", target, foreign_key),
+            None => target.to_string(),
+        };
+        let foreign_columns = match R::COLUMNS {
+            Some(columns) => columns.to_string(),
+            None => self
+                .database
+                .profile(target)
+                .and_then(|profile| profile.select)
+                .unwrap_or_else(|| "*".to_string()),
+        };
 
-        // Build select statement with joins
-        let select_clause = self.build_select_with_joins();
-        query_params.insert("select".to_string(), select_clause);
+        self.joins.push(Join {
+            join_type,
+            foreign_table,
+            foreign_columns,
+            alias: Some(R::FIELD.to_string()),
+        });
+        self
+    }
 
-        if !self.order_by.is_empty() {
-            let order_clauses: Vec<String> = self
-                .order_by
-                .iter()
-                .map(|order| {
-                    let direction = match order.direction {
-                        OrderDirection::Ascending => "asc",
-                        OrderDirection::Descending => "desc",
-                    };
-                    format!("{}.{}", order.column, direction)
-                })
-                .collect();
-            query_params.insert("order".to_string(), order_clauses.join(","));
-        }
+    /// Keep rows whose geography `column` lies within `meters` of `point`
+    ///
+    /// PostgREST has no spatial operators, so the query is sent to the
+    /// `<table>_st_dwithin` function instead of the table. The function must
+    /// return `setof <table>`, which keeps every other filter, ordering and
+    /// pagination option working:
+    ///
+    /// ```sql
+    /// create function places_st_dwithin(column_name text, point geography, meters float8)
+    /// returns setof places language plpgsql stable as $$
+    /// begin
+    ///   return query execute format(
+    ///     'select * from places where st_dwithin(%I, $1, $2)', column_name
+    ///   ) using point, meters;
+    /// end $$;
+    /// ```
+    ///
+    /// A query has at most one geo filter; a later call replaces an earlier one.
+    ///
+    /// # Examples
+    /// ```rust,no_run
+    /// use supabase_lib_rs::geo::Point;
+    /// # use supabase_lib_rs::Client;
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = Client::new("http://localhost:54321", "test-key")?;
+    ///
+    /// let nearby: Vec<serde_json::Value> = client.database()
+    ///     .from("places")
+    ///     .select("id,name,location::json")
+    ///     .st_dwithin("location", Point::new(13.405, 52.52), 500.0)
+    ///     .eq("category", "cafe")
+    ///     .execute()
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "postgis")]
+    pub fn st_dwithin(mut self, column: &str, point: crate::geo::Point, meters: f64) -> Self {
+        self.geo_filter = Some(GeoFilter::DWithin {
+            column: column.to_string(),
+            point,
+            meters,
+        });
+        self
+    }
 
-        if let Some(limit) = self.limit {
-            query_params.insert("limit".to_string(), limit.to_string());
-        }
+    /// Keep rows whose geometry `column` bounding box overlaps `bbox` (`&&`)
+    ///
+    /// Like [`st_dwithin`](Self::st_dwithin), the query is sent to a
+    /// `<table>_bbox_overlaps(column_name text, bbox geometry)` function
+    /// returning `setof <table>`.
+    #[cfg(feature = "postgis")]
+    pub fn bbox_overlaps(mut self, column: &str, bbox: crate::geo::BoundingBox) -> Self {
+        self.geo_filter = Some(GeoFilter::BboxOverlaps {
+            column: column.to_string(),
+            bbox,
+        });
+        self
+    }
 
-        if let Some(offset) = self.offset {
-            query_params.insert("offset".to_string(), offset.to_string());
-        }
+    /// Select a computed column
+    ///
+    /// Computed columns are PostgreSQL functions taking the table's row type,
+    /// e.g. `full_name(users)`. They are appended to the selected columns and
+    /// executed by PostgREST for every returned row.
+    ///
+    /// # Examples
+    /// ```rust,no_run
+    /// # use supabase_lib_rs::Client;
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = Client::new("http://localhost:54321", "test-key")?;
+    ///
+    /// // SELECT *, full_name FROM users
+    /// let users: Vec<serde_json::Value> = client.database()
+    ///     .from("users")
+    ///     .select("*")
+    ///     .computed("full_name")
+    ///     .execute()
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn computed(mut self, function_name: &str) -> Self {
+        self.computed_columns.push(ComputedColumn {
+            name: function_name.to_string(),
+            alias: None,
+        });
+        self
+    }
 
-        // Set URL query parameters
-        for (key, value) in query_params {
-            url.query_pairs_mut().append_pair(&key, &value);
-        }
+    /// Select a computed column under an alias
+    ///
+    /// # Examples
+    /// ```rust,no_run
+    /// # use supabase_lib_rs::Client;
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = Client::new("http://localhost:54321", "test-key")?;
+    ///
+    /// // SELECT id, display:full_name FROM users
+    /// let users: Vec<serde_json::Value> = client.database()
+    ///     .from("users")
+    ///     .select("id")
+    ///     .computed_as("full_name", "display")
+    ///     .execute()
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn computed_as(mut self, function_name: &str, alias: &str) -> Self {
+        self.computed_columns.push(ComputedColumn {
+            name: function_name.to_string(),
+            alias: Some(alias.to_string()),
+        });
+        self
+    }
 
-        debug!("Generated query URL: {}", url.as_str());
-        let mut request = self.database.http_client.get(url.as_str());
+    /// Computed columns that will trigger function execution for this query
+    pub fn computed_columns(&self) -> &[ComputedColumn] {
+        &self.computed_columns
+    }
 
-        if self.single {
-            request = request.header("Accept", "application/vnd.pgrst.object+json");
+    /// Check that computed column names and aliases are plain identifiers
+    fn validate_computed_columns(&self) -> Result<()> {
+        let is_identifier = |value: &str| {
+            !value.is_empty()
+                && !value.starts_with(|c: char| c.is_ascii_digit())
+                && value.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        };
+
+        for column in &self.computed_columns {
+            if !is_identifier(&column.name) {
+                return Err(Error::invalid_input(format!(
+                    "Invalid computed column name: {}",
+                    column.name
+                )));
+            }
+            if let Some(alias) = &column.alias {
+                if !is_identifier(alias) {
+                    return Err(Error::invalid_input(format!(
+                        "Invalid computed column alias: {}",
+                        alias
+                    )));
+                }
+            }
         }
 
-        let response = request.send().await?;
+        Ok(())
+    }
+
+    /// Set a cost/row budget checked against the query plan in debug builds
+    ///
+    /// Before executing, the query is explained and the planner estimates are
+    /// compared with the budget. Exceeding it logs a warning, or fails the query
+    /// when `fail_on_exceed` is set. Requires `db-plan-enabled` in PostgREST and
+    /// is skipped entirely in release builds.
+    ///
+    /// # Examples
+    /// ```rust,no_run
+    /// # use supabase_lib_rs::Client;
+    /// # use supabase_lib_rs::database::QueryBudget;
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = Client::new("http://localhost:54321", "test-key")?;
+    ///
+    /// let posts: Vec<serde_json::Value> = client.database()
+    ///     .from("posts")
+    ///     .select("*")
+    ///     .eq("author_id", "42")
+    ///     .budget(QueryBudget {
+    ///         max_cost: Some(1000.0),
+    ///         max_rows: Some(500),
+    ///         fail_on_exceed: true,
+    ///     })
+    ///     .execute()
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn budget(mut self, budget: QueryBudget) -> Self {
+        self.budget = Some(budget);
+        self
+    }
+
+    /// Fetch the PostgreSQL plan for this query without returning rows
+    ///
+    /// Requires `db-plan-enabled` in the PostgREST configuration.
+    ///
+    /// # Examples
+    /// ```rust,no_run
+    /// # use supabase_lib_rs::Client;
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = Client::new("http://localhost:54321", "test-key")?;
+    ///
+    /// let plan = client.database()
+    ///     .from("posts")
+    ///     .select("*")
+    ///     .eq("status", "published")
+    ///     .explain()
+    ///     .await?;
+    ///
+    /// println!("Estimated cost: {}, rows: {}", plan.total_cost, plan.plan_rows);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn explain(&self) -> Result<QueryPlan> {
+        debug!("Explaining SELECT query on table: {}", self.table);
+
+        let url = self.build_url()?;
+
+        let response = self
+            .database
+            .circuit
+            .send(
+                self.database
+                    .build_request(Method::GET, url.as_str())
+                    .header(
+                        "Accept",
+                        "application/vnd.pgrst.plan+json; for=\"application/json\"",
+                    ),
+            )
+            .await?;
 
         if !response.status().is_success() {
             let status = response.status();
             let error_msg = match response.text().await {
                 Ok(text) => text,
-                Err(_) => format!("Query failed with status: {}", status),
+                Err(_) => format!("Explain failed with status: {}", status),
             };
-            return Err(Error::database(error_msg));
+            return Err(
+                Error::database(error_msg).with_fingerprint(self.database.config.error_fingerprint)
+            );
         }
 
-        let result = if self.single {
-            let single_item: T = response.json().await?;
-            vec![single_item]
-        } else {
-            response.json().await?
+        let raw: JsonValue = response.json().await?;
+        QueryPlan::from_json(raw)
+    }
+
+    /// Explain the query and compare the plan against the configured budget
+    async fn check_budget(&self, budget: &QueryBudget) -> Result<()> {
+        let plan = match self.explain().await {
+            Ok(plan) => plan,
+            Err(e) => {
+                warn!("Skipping query budget check, explain failed: {}", e);
+                return Ok(());
+            }
+        };
+
+        let violations = budget.violations(&plan);
+        if violations.is_empty() {
+            return Ok(());
+        }
+
+        let details: Vec<String> = violations.iter().map(ToString::to_string).collect();
+        let message = format!(
+            "Query on table {} exceeds budget: {}",
+            self.table,
+            details.join(", ")
+        );
+
+        if budget.fail_on_exceed {
+            return Err(Error::database(message));
+        }
+
+        warn!("{}", message);
+        Ok(())
+    }
+
+    /// Build the request URL with all query parameters
+    fn build_url(&self) -> Result<Url> {
+        #[cfg(debug_assertions)]
+        if let (Some(known), Some(columns)) = (self.known_columns, &self.columns) {
+            validate_columns(&self.table, known, &self.database.column(columns))?;
+        }
+
+        #[cfg(feature = "postgis")]
+        if let Some(geo_filter) = &self.geo_filter {
+            let function = geo_filter.function_name(&self.table);
+            let mut url = self.build_url_for(&format!("rpc/{}", function))?;
+            for (key, value) in geo_filter.params() {
+                url.query_pairs_mut().append_pair(key, &value);
+            }
+            return Ok(url);
+        }
+
+        self.build_url_for(&self.table)
+    }
+
+    /// Build the request URL for `path` under the REST endpoint
+    fn build_url_for(&self, path: &str) -> Result<Url> {
+        let mut url = Url::parse(&format!("{}/{}", self.database.rest_url(), path))?;
+
+        // Add query parameters
+        let mut query_params = self.database.build_query_params(&self.filters);
+
+        // Build select statement with joins
+        let select_clause = self.build_select_with_joins();
+        query_params.push(("select".to_string(), self.database.column(&select_clause)));
+
+        if !self.order_by.is_empty() {
+            let order_clauses: Vec<String> = self
+                .order_by
+                .iter()
+                .map(|order| {
+                    OrderBy {
+                        column: self.database.column(&order.column),
+                        ..order.clone()
+                    }
+                    .to_param()
+                })
+                .collect();
+            query_params.push(("order".to_string(), order_clauses.join(",")));
+        }
+
+        if let Some(limit) = self.limit {
+            query_params.push(("limit".to_string(), limit.to_string()));
+        }
+
+        if let Some(offset) = self.offset {
+            query_params.push(("offset".to_string(), offset.to_string()));
+        }
+
+        // Set URL query parameters
+        for (key, value) in query_params {
+            url.query_pairs_mut().append_pair(&key, &value);
+        }
+
+        Ok(url)
+    }
+
+    /// Execute the query
+    pub async fn execute<T>(&self) -> Result<Vec<T>>
+    where
+        T: for<'de> Deserialize<'de>,
+    {
+        Ok(self.execute_with_meta().await?.data)
+    }
+
+    /// Execute the query and return the rows with the response metadata
+    ///
+    /// The metadata carries the status, `Content-Range`, rate limit headers and
+    /// server timing, for pagination, backoff and monitoring without a second
+    /// request.
+    ///
+    /// # Examples
+    /// ```rust,no_run
+    /// use serde_json::Value;
+    /// # use supabase_lib_rs::Client;
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let client = Client::new("http://localhost:54321", "test-key")?;
+    ///
+    /// let response = client.database()
+    ///     .from("posts")
+    ///     .select("*")
+    ///     .limit(25)
+    ///     .execute_with_meta::<Value>()
+    ///     .await?;
+    ///
+    /// if let Some(range) = response.meta.content_range {
+    ///     println!("Rows {:?}-{:?} of {:?}", range.start, range.end, range.total);
+    /// }
+    /// if let Some(rate_limit) = response.meta.rate_limit {
+    ///     println!("Requests remaining: {:?}", rate_limit.remaining);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn execute_with_meta<T>(&self) -> Result<ResponseWithMeta<T>>
+    where
+        T: for<'de> Deserialize<'de>,
+    {
+        let accept = self.single.then_some("application/vnd.pgrst.object+json");
+        let response = self.send_select(accept).await?;
+        let meta = ResponseMeta::from_response(&response);
+
+        let result = if self.single {
+            let single_item: T = self.database.decode(response.json().await?)?;
+            vec![single_item]
+        } else {
+            self.database.decode(response.json().await?)?
         };
 
         info!(
             "SELECT query executed successfully on table: {}",
             self.table
         );
-        Ok(result)
+        Ok(ResponseWithMeta { data: result, meta })
+    }
+
+    /// Execute the query and return the rows as CSV
+    ///
+    /// PostgREST renders the result with a header row of column names. For
+    /// large exports, fetch the table in parts with [`range`](QueryBuilder::range).
+    ///
+    /// # Examples
+    /// ```rust,no_run
+    /// # use supabase_lib_rs::Client;
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let client = Client::new("http://localhost:54321", "test-key")?;
+    /// let csv = client.database()
+    ///     .from("orders")
+    ///     .select("id,total,created_at")
+    ///     .execute_csv()
+    ///     .await?;
+    /// std::fs::write("orders.csv", csv)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn execute_csv(&self) -> Result<String> {
+        let response = self.send_select(Some("text/csv")).await?;
+        let csv = response.text().await?;
+
+        info!("CSV query executed successfully on table: {}", self.table);
+        Ok(csv)
+    }
+
+    /// Execute the query as CSV and deserialize each record into `T`
+    ///
+    /// Fields are matched to the CSV header by name. Requires the
+    /// `database-csv` feature.
+    #[cfg(feature = "database-csv")]
+    pub async fn execute_csv_typed<T>(&self) -> Result<Vec<T>>
+    where
+        T: for<'de> Deserialize<'de>,
+    {
+        let csv = self.execute_csv().await?;
+        csv::Reader::from_reader(csv.as_bytes())
+            .deserialize()
+            .collect::<std::result::Result<Vec<T>, _>>()
+            .map_err(|e| Error::database(format!("Invalid CSV response: {}", e)))
+    }
+
+    /// Send the SELECT request, returning the response if it succeeded
+    async fn send_select(&self, accept: Option<&str>) -> Result<reqwest::Response> {
+        debug!("Executing SELECT query on table: {}", self.table);
+
+        self.validate_computed_columns()?;
+        if !self.computed_columns.is_empty() {
+            debug!(
+                "Query selects {} computed column(s), executed per row",
+                self.computed_columns.len()
+            );
+        }
+
+        if cfg!(debug_assertions) {
+            if let Some(budget) = &self.budget {
+                self.check_budget(budget).await?;
+            }
+        }
+
+        let url = self.build_url()?;
+
+        debug!("Generated query URL: {}", url.as_str());
+        self.database.log_query(|redaction| self.to_sql(redaction));
+        let mut request = self.database.build_request(Method::GET, url.as_str());
+
+        if let Some(accept) = accept {
+            request = request.header("Accept", accept);
+        }
+        if let Some(count) = self.count {
+            request = request.header("Prefer", format!("count={}", count.as_str()));
+        }
+        if let Some((from, to)) = self.range {
+            request = request
+                .header("Range-Unit", "items")
+                .header("Range", format!("{}-{}", from, to));
+        }
+
+        let response = self.database.circuit.send(request).await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_msg = match response.text().await {
+                Ok(text) => text,
+                Err(_) => format!("Query failed with status: {}", status),
+            };
+            let context = ErrorContext {
+                http: Some(HttpErrorContext {
+                    status_code: Some(status.as_u16()),
+                    headers: None,
+                    response_body: None,
+                    url: Some(url.to_string()),
+                    method: Some(Method::GET.to_string()),
+                }),
+                ..Default::default()
+            };
+            return Err(Error::database_with_context(error_msg, context)
+                .with_fingerprint(self.database.config.error_fingerprint));
+        }
+
+        Ok(response)
+    }
+
+    /// Render the query as pseudo-SQL, hiding values per `redaction`
+    ///
+    /// # Examples
+    /// ```rust
+    /// use supabase_lib_rs::{database::Redaction, Client};
+    ///
+    /// let client = Client::new("https://example.supabase.co", "your-anon-key")?;
+    /// let query = client
+    ///     .database()
+    ///     .from("posts")
+    ///     .select("title")
+    ///     .eq("published", "true")
+    ///     .limit(10);
+    ///
+    /// assert_eq!(
+    ///     query.to_sql(&Redaction::None),
+    ///     "SELECT title FROM posts WHERE published = true LIMIT 10"
+    /// );
+    /// assert_eq!(
+    ///     query.to_sql(&Redaction::All),
+    ///     "SELECT title FROM posts WHERE published = ? LIMIT 10"
+    /// );
+    /// # Ok::<(), supabase_lib_rs::Error>(())
+    /// ```
+    pub fn to_sql(&self, redaction: &Redaction) -> String {
+        let mut sql = format!(
+            "SELECT {} FROM {}{}",
+            self.build_select_with_joins(),
+            self.table,
+            redaction.where_clause(&self.filters)
+        );
+        if !self.order_by.is_empty() {
+            let order: Vec<String> = self.order_by.iter().map(OrderBy::to_sql).collect();
+            sql.push_str(&format!(" ORDER BY {}", order.join(", ")));
+        }
+        if let Some(limit) = self.limit {
+            sql.push_str(&format!(" LIMIT {}", limit));
+        }
+        if let Some(offset) = self.offset {
+            sql.push_str(&format!(" OFFSET {}", offset));
+        }
+        sql
     }
 
     /// Build the SELECT clause including any joins
     fn build_select_with_joins(&self) -> String {
         let base_columns = self.columns.as_deref().unwrap_or("*");
 
-        if self.joins.is_empty() {
+        if self.joins.is_empty() && self.computed_columns.is_empty() {
             return base_columns.to_string();
         }
 
         let mut select_parts = vec![base_columns.to_string()];
 
+        for column in &self.computed_columns {
+            match &column.alias {
+                Some(alias) => select_parts.push(format!("{}:{}", alias, column.name)),
+                None => select_parts.push(column.name.clone()),
+            }
+        }
+
         for join in &self.joins {
             let join_clause = self.build_join_clause(join);
             select_parts.push(join_clause);
@@ -1264,6 +3305,40 @@ impl QueryBuilder {
         }
     }
 
+    /// Execute the query and return the rows with the total matching count
+    ///
+    /// The total covers all rows matching the filters, ignoring `limit` and
+    /// `offset`. It is `None` unless a [`count`](QueryBuilder::count)
+    /// strategy is set.
+    ///
+    /// # Examples
+    /// ```rust,no_run
+    /// use serde_json::Value;
+    /// use supabase_lib_rs::database::CountStrategy;
+    /// # use supabase_lib_rs::Client;
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let client = Client::new("http://localhost:54321", "test-key")?;
+    ///
+    /// let (page, total) = client.database()
+    ///     .from("posts")
+    ///     .select("id,title")
+    ///     .count(CountStrategy::Exact)
+    ///     .limit(20)
+    ///     .execute_with_count::<Value>()
+    ///     .await?;
+    /// println!("Showing {} of {:?} posts", page.len(), total);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn execute_with_count<T>(&self) -> Result<(Vec<T>, Option<u64>)>
+    where
+        T: for<'de> Deserialize<'de>,
+    {
+        let response = self.execute_with_meta().await?;
+        let total = response.meta.content_range.and_then(|range| range.total);
+        Ok((response.data, total))
+    }
+
     /// Execute the query and return a single row
     pub async fn single_execute<T>(&self) -> Result<Option<T>>
     where
@@ -1286,12 +3361,18 @@ impl InsertBuilder {
             upsert: false,
             on_conflict: None,
             returning: None,
+            default_for_missing: false,
+            conflict_retry: None,
+            generated_id: None,
         }
     }
 
     /// Set the data to insert
     pub fn values<T: Serialize>(mut self, data: T) -> Result<Self> {
-        self.data = serde_json::to_value(data)?;
+        self.data = self.database.encode(data)?;
+        if let Some(profile) = self.database.profile(&self.table) {
+            profile.strip_payload(&mut self.data);
+        }
         Ok(self)
     }
 
@@ -1313,67 +3394,408 @@ impl InsertBuilder {
         self
     }
 
-    /// Execute the insert
-    pub async fn execute<T>(&self) -> Result<Vec<T>>
+    /// Use column defaults for keys missing from some of the rows
+    ///
+    /// By default, PostgREST inserts `NULL` for columns that a row in a bulk
+    /// insert does not mention. This sends `Prefer: missing=default` together
+    /// with the union of all row keys as `columns`, so missing values take the
+    /// database default instead.
+    ///
+    /// # Examples
+    /// ```rust,no_run
+    /// use serde_json::{json, Value};
+    /// # use supabase_lib_rs::Client;
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let client = Client::new("http://localhost:54321", "test-key")?;
+    ///
+    /// // `status` falls back to its column default for the first row
+    /// let rows: Vec<Value> = client.database()
+    ///     .insert("tasks")
+    ///     .values(json!([
+    ///         {"title": "Write docs"},
+    ///         {"title": "Ship release", "status": "done"}
+    ///     ]))?
+    ///     .use_default_for_missing()
+    ///     .returning("*")
+    ///     .execute()
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn use_default_for_missing(mut self) -> Self {
+        self.default_for_missing = true;
+        self
+    }
+
+    /// Fill the `id` column client-side for rows that do not have one
+    ///
+    /// See [`generate_id_in`](Self::generate_id_in).
+    pub fn generate_id(self, strategy: IdStrategy) -> Self {
+        self.generate_id_in("id", strategy)
+    }
+
+    /// Fill `column` client-side for rows where it is missing or `null`
+    ///
+    /// Rows that already carry an id keep it, so records created offline with
+    /// [`IdStrategy::generate`] sync under their original ids. If the insert
+    /// conflicts on `column` alone, the row already exists and an
+    /// [`Error::AlreadyExists`] is returned instead of the database error.
+    ///
+    /// # Examples
+    /// ```rust,no_run
+    /// use serde_json::{json, Value};
+    /// use supabase_lib_rs::database::IdStrategy;
+    /// # use supabase_lib_rs::Client;
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let client = Client::new("http://localhost:54321", "test-key")?;
+    ///
+    /// // Created offline earlier
+    /// let offline_id = IdStrategy::UuidV7.generate();
+    ///
+    /// let notes: Vec<Value> = client.database()
+    ///     .insert("notes")
+    ///     .values(json!([
+    ///         {"id": offline_id, "body": "Written on the train"},
+    ///         {"body": "Gets a fresh id"}
+    ///     ]))?
+    ///     .generate_id(IdStrategy::UuidV7)
+    ///     .returning("*")
+    ///     .execute()
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn generate_id_in(mut self, column: &str, strategy: IdStrategy) -> Self {
+        self.generated_id = Some((column.to_string(), strategy));
+        self
+    }
+
+    /// Fill missing ids into `payload` as configured by `generate_id`
+    fn fill_generated_ids(&self, payload: &mut JsonValue) {
+        let Some((column, strategy)) = &self.generated_id else {
+            return;
+        };
+
+        let rows: Vec<&mut JsonValue> = match payload {
+            JsonValue::Array(rows) => rows.iter_mut().collect(),
+            row => vec![row],
+        };
+
+        for row in rows {
+            if let JsonValue::Object(map) = row {
+                if map.get(column).is_none_or(JsonValue::is_null) {
+                    map.insert(column.clone(), JsonValue::String(strategy.generate()));
+                }
+            }
+        }
+    }
+
+    /// Retry the insert when it hits a unique constraint violation
+    ///
+    /// Before each retry, `mutate` receives the payload and the retry number
+    /// (starting at 1) so it can regenerate the conflicting value, e.g. a slug
+    /// suffix. Changes to the payload carry over between retries. The insert
+    /// runs at most `max_attempts` times in total; the last unique violation is
+    /// returned if every attempt conflicts. Other errors are returned at once.
+    ///
+    /// # Examples
+    /// ```rust,no_run
+    /// use serde_json::{json, Value};
+    /// # use supabase_lib_rs::Client;
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let client = Client::new("http://localhost:54321", "test-key")?;
+    ///
+    /// let posts: Vec<Value> = client.database()
+    ///     .insert("posts")
+    ///     .values(json!({"title": "Hello", "slug": "hello"}))?
+    ///     .retry_on_conflict(5, |post, retry| {
+    ///         post["slug"] = json!(format!("hello-{}", retry + 1));
+    ///     })
+    ///     .returning("*")
+    ///     .execute()
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn retry_on_conflict<F>(mut self, max_attempts: u32, mutate: F) -> Self
     where
-        T: for<'de> Deserialize<'de>,
+        F: Fn(&mut JsonValue, u32) + Send + Sync + 'static,
     {
-        debug!("Executing INSERT query on table: {}", self.table);
+        self.conflict_retry = Some(ConflictRetry {
+            max_attempts: max_attempts.max(1),
+            mutate: Arc::new(mutate),
+        });
+        self
+    }
 
-        let url = format!("{}/{}", self.database.rest_url(), self.table);
-        let mut request = self.database.http_client.post(&url).json(&self.data);
+    /// Build the `Prefer` header value, if any preference is set
+    fn prefer_header(&self) -> Option<String> {
+        let mut preferences = Vec::new();
 
-        if let Some(ref _returning) = self.returning {
-            request = request.header("Prefer", "return=representation".to_string());
+        if self.returning.is_some() {
+            preferences.push("return=representation");
         }
 
         if self.upsert {
-            request = request.header("Prefer", "resolution=merge-duplicates");
+            preferences.push("resolution=merge-duplicates");
         }
 
-        let response = request.send().await?;
-
-        if !response.status().is_success() {
-            let status = response.status();
-            let error_msg = match response.text().await {
-                Ok(text) => text,
-                Err(_) => format!("Insert failed with status: {}", status),
-            };
-            return Err(Error::database(error_msg));
+        if self.default_for_missing {
+            preferences.push("missing=default");
         }
 
-        let result: Vec<T> = response.json().await?;
-        info!(
-            "INSERT query executed successfully on table: {}",
-            self.table
-        );
-
-        Ok(result)
+        if preferences.is_empty() {
+            None
+        } else {
+            Some(preferences.join(","))
+        }
     }
-}
 
-impl UpdateBuilder {
-    fn new(database: Database, table: String) -> Self {
-        Self {
-            database,
-            table,
-            data: JsonValue::Null,
-            filters: Vec::new(),
-            returning: None,
+    /// Union of keys across all rows, in first-seen order
+    fn payload_columns(&self) -> Vec<String> {
+        let rows: Vec<&JsonValue> = match &self.data {
+            JsonValue::Array(rows) => rows.iter().collect(),
+            row => vec![row],
+        };
+
+        let mut columns: Vec<String> = Vec::new();
+        for row in rows {
+            if let JsonValue::Object(map) = row {
+                for key in map.keys() {
+                    if !columns.contains(key) {
+                        columns.push(key.clone());
+                    }
+                }
+            }
         }
+
+        columns
     }
 
-    /// Set the data to update
-    pub fn set<T: Serialize>(mut self, data: T) -> Result<Self> {
-        self.data = serde_json::to_value(data)?;
-        Ok(self)
+    /// Execute the insert
+    pub async fn execute<T>(&self) -> Result<Vec<T>>
+    where
+        T: for<'de> Deserialize<'de>,
+    {
+        let mut payload = self.data.clone();
+        self.fill_generated_ids(&mut payload);
+
+        self.send_with_retries(payload)
+            .await
+            .map_err(|err| self.id_conflict_error(err))
     }
 
-    /// Add an equality filter
-    pub fn eq(mut self, column: &str, value: &str) -> Self {
+    /// Send the insert, retrying unique violations if `retry_on_conflict` is set
+    async fn send_with_retries<T>(&self, mut payload: JsonValue) -> Result<Vec<T>>
+    where
+        T: for<'de> Deserialize<'de>,
+    {
+        let Some(retry) = &self.conflict_retry else {
+            return self.send(&payload).await;
+        };
+
+        let mut attempt = 1;
+        loop {
+            match self.send(&payload).await {
+                Err(err) if attempt < retry.max_attempts && err.is_unique_violation() => {
+                    warn!(
+                        "INSERT on {} hit a unique violation, retrying ({}/{})",
+                        self.table, attempt, retry.max_attempts
+                    );
+                    (retry.mutate)(&mut payload, attempt);
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Report a conflict on the generated id column as an existing row
+    fn id_conflict_error(&self, error: Error) -> Error {
+        match &self.generated_id {
+            Some((column, _)) if is_column_conflict(&error, column) => {
+                Error::already_exists(format!(
+                    "A row with this {} already exists in {}",
+                    column, self.table
+                ))
+            }
+            _ => error,
+        }
+    }
+
+    /// Render the insert as pseudo-SQL, hiding values per `redaction`
+    pub fn to_sql(&self, redaction: &Redaction) -> String {
+        insert_sql(&self.table, &self.data, redaction)
+    }
+
+    /// Send `payload` as a single insert request
+    async fn send<T>(&self, payload: &JsonValue) -> Result<Vec<T>>
+    where
+        T: for<'de> Deserialize<'de>,
+    {
+        debug!("Executing INSERT query on table: {}", self.table);
+
+        let mut url = Url::parse(&format!("{}/{}", self.database.rest_url(), self.table))?;
+
+        if self.default_for_missing {
+            let columns = self.payload_columns();
+            if !columns.is_empty() {
+                url.query_pairs_mut()
+                    .append_pair("columns", &columns.join(","));
+            }
+        }
+
+        self.database
+            .log_query(|redaction| insert_sql(&self.table, payload, redaction));
+        let mut request = self.database.json_body(
+            self.database.build_request(Method::POST, url.as_str()),
+            payload,
+        );
+
+        if let Some(prefer) = self.prefer_header() {
+            request = request.header("Prefer", prefer);
+        }
+
+        let response = self.database.circuit.send(request).await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_msg = match response.text().await {
+                Ok(text) => text,
+                Err(_) => format!("Insert failed with status: {}", status),
+            };
+            return Err(
+                Error::database(error_msg).with_fingerprint(self.database.config.error_fingerprint)
+            );
+        }
+
+        let result: Vec<T> = self.database.decode(response.json().await?)?;
+        info!(
+            "INSERT query executed successfully on table: {}",
+            self.table
+        );
+
+        Ok(result)
+    }
+}
+
+impl UpdateBuilder {
+    fn new(database: Database, table: String) -> Self {
+        Self {
+            database,
+            table,
+            data: JsonValue::Null,
+            filters: Vec::new(),
+            returning: None,
+            allow_unfiltered: false,
+        }
+    }
+
+    /// Set the data to update
+    pub fn set<T: Serialize>(mut self, data: T) -> Result<Self> {
+        self.data = self.database.encode(data)?;
+        if let Some(profile) = self.database.profile(&self.table) {
+            profile.strip_payload(&mut self.data);
+        }
+        Ok(self)
+    }
+
+    /// Add an equality filter
+    pub fn eq(self, column: &str, value: &str) -> Self {
+        self.filter(column, FilterOperator::Equal, value)
+    }
+
+    /// Add a not equal filter
+    pub fn neq(self, column: &str, value: &str) -> Self {
+        self.filter(column, FilterOperator::NotEqual, value)
+    }
+
+    /// Add a greater than filter
+    pub fn gt(self, column: &str, value: &str) -> Self {
+        self.filter(column, FilterOperator::GreaterThan, value)
+    }
+
+    /// Add a greater than or equal filter
+    pub fn gte(self, column: &str, value: &str) -> Self {
+        self.filter(column, FilterOperator::GreaterThanOrEqual, value)
+    }
+
+    /// Add a less than filter
+    pub fn lt(self, column: &str, value: &str) -> Self {
+        self.filter(column, FilterOperator::LessThan, value)
+    }
+
+    /// Add a less than or equal filter
+    pub fn lte(self, column: &str, value: &str) -> Self {
+        self.filter(column, FilterOperator::LessThanOrEqual, value)
+    }
+
+    /// Add a LIKE filter
+    pub fn like(self, column: &str, pattern: &str) -> Self {
+        self.filter(column, FilterOperator::Like, pattern)
+    }
+
+    /// Add an ILIKE filter (case-insensitive)
+    pub fn ilike(self, column: &str, pattern: &str) -> Self {
+        self.filter(column, FilterOperator::ILike, pattern)
+    }
+
+    /// Add an IS filter (for null checks)
+    pub fn is(self, column: &str, value: &str) -> Self {
+        self.filter(column, FilterOperator::Is, value)
+    }
+
+    /// Add an IN filter
+    pub fn r#in(self, column: &str, values: &[&str]) -> Self {
+        let value = format!("({})", values.join(","));
+        self.filter(column, FilterOperator::In, &value)
+    }
+
+    /// Match rows where any of the filters built by `builder_fn` holds
+    ///
+    /// Works like [`QueryBuilder::or`].
+    pub fn or<F>(mut self, builder_fn: F) -> Self
+    where
+        F: FnOnce(QueryBuilder) -> QueryBuilder,
+    {
+        let filters = builder_fn(self.database.from(&self.table)).filters;
+        if !filters.is_empty() {
+            self.filters.push(Filter::Or(filters));
+        }
+        self
+    }
+
+    /// Match rows where the filters built by `builder_fn` do not all hold
+    ///
+    /// Works like [`QueryBuilder::not`].
+    pub fn not<F>(mut self, builder_fn: F) -> Self
+    where
+        F: FnOnce(QueryBuilder) -> QueryBuilder,
+    {
+        let mut filters = builder_fn(self.database.from(&self.table)).filters;
+        match filters.len() {
+            0 => {}
+            1 => self.filters.push(Filter::Not(Box::new(filters.remove(0)))),
+            _ => self
+                .filters
+                .push(Filter::Not(Box::new(Filter::And(filters)))),
+        }
+        self
+    }
+
+    /// Allow executing without filters, updating every row of the table
+    ///
+    /// Without this, executing an UPDATE that has no filters fails with
+    /// [`Error::InvalidInput`] instead of touching the whole table.
+    pub fn allow_unfiltered(mut self) -> Self {
+        self.allow_unfiltered = true;
+        self
+    }
+
+    fn filter(mut self, column: &str, operator: FilterOperator, value: &str) -> Self {
         self.filters.push(Filter::Simple {
             column: column.to_string(),
-            operator: FilterOperator::Equal,
+            operator,
             value: value.to_string(),
         });
         self
@@ -1392,25 +3814,19 @@ impl UpdateBuilder {
     {
         debug!("Executing UPDATE query on table: {}", self.table);
 
-        let mut url = Url::parse(&format!("{}/{}", self.database.rest_url(), self.table))?;
-
-        // Add filters as query parameters
-        let query_params = self.database.build_query_params(&self.filters);
-        for (key, value) in query_params {
-            url.query_pairs_mut().append_pair(&key, &value);
-        }
+        let url = self.url()?;
 
-        let mut request = self
-            .database
-            .http_client
-            .patch(url.as_str())
-            .json(&self.data);
+        self.database.log_query(|redaction| self.to_sql(redaction));
+        let mut request = self.database.json_body(
+            self.database.build_request(Method::PATCH, url.as_str()),
+            &self.data,
+        );
 
         if let Some(ref _returning) = self.returning {
             request = request.header("Prefer", "return=representation");
         }
 
-        let response = request.send().await?;
+        let response = self.database.circuit.send(request).await?;
 
         if !response.status().is_success() {
             let status = response.status();
@@ -1418,10 +3834,12 @@ impl UpdateBuilder {
                 Ok(text) => text,
                 Err(_) => format!("Update failed with status: {}", status),
             };
-            return Err(Error::database(error_msg));
+            return Err(
+                Error::database(error_msg).with_fingerprint(self.database.config.error_fingerprint)
+            );
         }
 
-        let result: Vec<T> = response.json().await?;
+        let result: Vec<T> = self.database.decode(response.json().await?)?;
         info!(
             "UPDATE query executed successfully on table: {}",
             self.table
@@ -1429,6 +3847,104 @@ impl UpdateBuilder {
 
         Ok(result)
     }
+
+    /// Execute the update and return the number of rows changed
+    ///
+    /// Asks for `return=minimal` with an exact count, so no row bodies are
+    /// transferred, whatever `returning` is set to.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// # use supabase_lib_rs::Client;
+    /// # use serde_json::json;
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let client = Client::new("http://localhost:54321", "test-key")?;
+    /// let archived = client.database()
+    ///     .update("users")
+    ///     .set(json!({"status": "archived"}))?
+    ///     .eq("status", "inactive")
+    ///     .execute_count()
+    ///     .await?;
+    /// println!("Archived {} users", archived);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn execute_count(&self) -> Result<u64> {
+        debug!("Executing counted UPDATE query on table: {}", self.table);
+
+        let url = self.url()?;
+        self.database.log_query(|redaction| self.to_sql(redaction));
+        let request = self.database.json_body(
+            self.database.build_request(Method::PATCH, url.as_str()),
+            &self.data,
+        );
+
+        self.database.affected_rows(request, "Update").await
+    }
+
+    /// Table URL with the filters as query parameters
+    ///
+    /// Fails without filters unless [`allow_unfiltered`](Self::allow_unfiltered) was called.
+    fn url(&self) -> Result<Url> {
+        self.database
+            .filtered_url("UPDATE", &self.table, &self.filters, self.allow_unfiltered)
+    }
+
+    /// Render the update as pseudo-SQL, hiding values per `redaction`
+    pub fn to_sql(&self, redaction: &Redaction) -> String {
+        let assignments: Vec<String> = self
+            .data
+            .as_object()
+            .into_iter()
+            .flatten()
+            .map(|(column, value)| {
+                format!("{} = {}", column, redaction.json_literal(column, value))
+            })
+            .collect();
+        format!(
+            "UPDATE {} SET {}{}",
+            self.table,
+            assignments.join(", "),
+            redaction.where_clause(&self.filters)
+        )
+    }
+
+    /// Report the rows this update would change, without changing them
+    ///
+    /// Runs a SELECT with the same filters and returns the number of matching
+    /// rows and a sample of them (the `returning` columns, if set).
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// # use supabase_lib_rs::Client;
+    /// # use serde_json::{json, Value};
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = Client::new("http://localhost:54321", "test-key")?;
+    ///
+    /// let update = client.database()
+    ///     .update("users")
+    ///     .set(json!({"status": "archived"}))?
+    ///     .eq("status", "inactive");
+    ///
+    /// let preview = update.dry_run::<Value>().await?;
+    /// println!("Would archive {} users, e.g. {:?}", preview.affected, preview.sample.first());
+    ///
+    /// if preview.affected < 100 {
+    ///     update.execute::<Value>().await?;
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn dry_run<T>(&self) -> Result<DryRun<T>>
+    where
+        T: for<'de> Deserialize<'de>,
+    {
+        self.database
+            .preview_affected(&self.table, &self.filters, self.returning.as_deref())
+            .await
+    }
 }
 
 impl DeleteBuilder {
@@ -1438,14 +3954,106 @@ impl DeleteBuilder {
             table,
             filters: Vec::new(),
             returning: None,
+            allow_unfiltered: false,
         }
     }
 
     /// Add an equality filter
-    pub fn eq(mut self, column: &str, value: &str) -> Self {
+    pub fn eq(self, column: &str, value: &str) -> Self {
+        self.filter(column, FilterOperator::Equal, value)
+    }
+
+    /// Add a not equal filter
+    pub fn neq(self, column: &str, value: &str) -> Self {
+        self.filter(column, FilterOperator::NotEqual, value)
+    }
+
+    /// Add a greater than filter
+    pub fn gt(self, column: &str, value: &str) -> Self {
+        self.filter(column, FilterOperator::GreaterThan, value)
+    }
+
+    /// Add a greater than or equal filter
+    pub fn gte(self, column: &str, value: &str) -> Self {
+        self.filter(column, FilterOperator::GreaterThanOrEqual, value)
+    }
+
+    /// Add a less than filter
+    pub fn lt(self, column: &str, value: &str) -> Self {
+        self.filter(column, FilterOperator::LessThan, value)
+    }
+
+    /// Add a less than or equal filter
+    pub fn lte(self, column: &str, value: &str) -> Self {
+        self.filter(column, FilterOperator::LessThanOrEqual, value)
+    }
+
+    /// Add a LIKE filter
+    pub fn like(self, column: &str, pattern: &str) -> Self {
+        self.filter(column, FilterOperator::Like, pattern)
+    }
+
+    /// Add an ILIKE filter (case-insensitive)
+    pub fn ilike(self, column: &str, pattern: &str) -> Self {
+        self.filter(column, FilterOperator::ILike, pattern)
+    }
+
+    /// Add an IS filter (for null checks)
+    pub fn is(self, column: &str, value: &str) -> Self {
+        self.filter(column, FilterOperator::Is, value)
+    }
+
+    /// Add an IN filter
+    pub fn r#in(self, column: &str, values: &[&str]) -> Self {
+        let value = format!("({})", values.join(","));
+        self.filter(column, FilterOperator::In, &value)
+    }
+
+    /// Match rows where any of the filters built by `builder_fn` holds
+    ///
+    /// Works like [`QueryBuilder::or`].
+    pub fn or<F>(mut self, builder_fn: F) -> Self
+    where
+        F: FnOnce(QueryBuilder) -> QueryBuilder,
+    {
+        let filters = builder_fn(self.database.from(&self.table)).filters;
+        if !filters.is_empty() {
+            self.filters.push(Filter::Or(filters));
+        }
+        self
+    }
+
+    /// Match rows where the filters built by `builder_fn` do not all hold
+    ///
+    /// Works like [`QueryBuilder::not`].
+    pub fn not<F>(mut self, builder_fn: F) -> Self
+    where
+        F: FnOnce(QueryBuilder) -> QueryBuilder,
+    {
+        let mut filters = builder_fn(self.database.from(&self.table)).filters;
+        match filters.len() {
+            0 => {}
+            1 => self.filters.push(Filter::Not(Box::new(filters.remove(0)))),
+            _ => self
+                .filters
+                .push(Filter::Not(Box::new(Filter::And(filters)))),
+        }
+        self
+    }
+
+    /// Allow executing without filters, deleting every row of the table
+    ///
+    /// Without this, executing a DELETE that has no filters fails with
+    /// [`Error::InvalidInput`] instead of touching the whole table.
+    pub fn allow_unfiltered(mut self) -> Self {
+        self.allow_unfiltered = true;
+        self
+    }
+
+    fn filter(mut self, column: &str, operator: FilterOperator, value: &str) -> Self {
         self.filters.push(Filter::Simple {
             column: column.to_string(),
-            operator: FilterOperator::Equal,
+            operator,
             value: value.to_string(),
         });
         self
@@ -1464,21 +4072,16 @@ impl DeleteBuilder {
     {
         debug!("Executing DELETE query on table: {}", self.table);
 
-        let mut url = Url::parse(&format!("{}/{}", self.database.rest_url(), self.table))?;
-
-        // Add filters as query parameters
-        let query_params = self.database.build_query_params(&self.filters);
-        for (key, value) in query_params {
-            url.query_pairs_mut().append_pair(&key, &value);
-        }
+        let url = self.url()?;
 
-        let mut request = self.database.http_client.delete(url.as_str());
+        self.database.log_query(|redaction| self.to_sql(redaction));
+        let mut request = self.database.build_request(Method::DELETE, url.as_str());
 
         if let Some(ref _returning) = self.returning {
             request = request.header("Prefer", "return=representation");
         }
 
-        let response = request.send().await?;
+        let response = self.database.circuit.send(request).await?;
 
         if !response.status().is_success() {
             let status = response.status();
@@ -1486,10 +4089,12 @@ impl DeleteBuilder {
                 Ok(text) => text,
                 Err(_) => format!("Delete failed with status: {}", status),
             };
-            return Err(Error::database(error_msg));
+            return Err(
+                Error::database(error_msg).with_fingerprint(self.database.config.error_fingerprint)
+            );
         }
 
-        let result: Vec<T> = response.json().await?;
+        let result: Vec<T> = self.database.decode(response.json().await?)?;
         info!(
             "DELETE query executed successfully on table: {}",
             self.table
@@ -1497,27 +4102,291 @@ impl DeleteBuilder {
 
         Ok(result)
     }
+
+    /// Execute the delete and return the number of rows removed
+    ///
+    /// Asks for `return=minimal` with an exact count, so no row bodies are
+    /// transferred, whatever `returning` is set to.
+    pub async fn execute_count(&self) -> Result<u64> {
+        debug!("Executing counted DELETE query on table: {}", self.table);
+
+        let url = self.url()?;
+        self.database.log_query(|redaction| self.to_sql(redaction));
+        let request = self.database.build_request(Method::DELETE, url.as_str());
+
+        self.database.affected_rows(request, "Delete").await
+    }
+
+    /// Table URL with the filters as query parameters
+    ///
+    /// Fails without filters unless [`allow_unfiltered`](Self::allow_unfiltered) was called.
+    fn url(&self) -> Result<Url> {
+        self.database
+            .filtered_url("DELETE", &self.table, &self.filters, self.allow_unfiltered)
+    }
+
+    /// Render the delete as pseudo-SQL, hiding values per `redaction`
+    pub fn to_sql(&self, redaction: &Redaction) -> String {
+        format!(
+            "DELETE FROM {}{}",
+            self.table,
+            redaction.where_clause(&self.filters)
+        )
+    }
+
+    /// Report the rows this delete would remove, without removing them
+    ///
+    /// Runs a SELECT with the same filters and returns the number of matching
+    /// rows and a sample of them (the `returning` columns, if set).
+    pub async fn dry_run<T>(&self) -> Result<DryRun<T>>
+    where
+        T: for<'de> Deserialize<'de>,
+    {
+        self.database
+            .preview_affected(&self.table, &self.filters, self.returning.as_deref())
+            .await
+    }
 }
 
-impl TransactionBuilder {
-    fn new(database: Database) -> Self {
+impl RpcBuilder {
+    fn new(database: Database, function: String) -> Self {
         Self {
             database,
-            operations: Vec::new(),
+            function,
+            params: None,
+            single: false,
+            read_only: false,
+            single_object: false,
+            schema: None,
+            _returns: PhantomData,
         }
     }
+}
 
-    /// Add an INSERT operation to the transaction
-    pub fn insert(mut self, table: &str, data: JsonValue) -> Self {
-        self.operations.push(json!({
-            "operation": TransactionOperation::Insert,
-            "table": table,
-            "data": data
-        }));
-        self
+impl<T> RpcBuilder<T> {
+    /// Set the function arguments, an object keyed by parameter name
+    pub fn params<P: Serialize>(mut self, params: P) -> Result<Self> {
+        self.params = Some(serde_json::to_value(params)?);
+        Ok(self)
     }
 
-    /// Add an UPDATE operation to the transaction
+    /// Expect exactly one row and return it as an object instead of an array
+    pub fn single(mut self) -> Self {
+        self.single = true;
+        self
+    }
+
+    /// Call with GET, passing the arguments as query parameters
+    ///
+    /// PostgREST only allows this for functions declared `STABLE` or
+    /// `IMMUTABLE`; in exchange the call can run on a read replica.
+    pub fn read_only(mut self) -> Self {
+        self.read_only = true;
+        self
+    }
+
+    /// Pass the arguments to the function as one JSON parameter
+    ///
+    /// Sends `Prefer: params=single-object`, for functions taking a single
+    /// unnamed `json` or `jsonb` argument. Has no effect with [`read_only`](RpcBuilder::read_only).
+    pub fn single_object(mut self) -> Self {
+        self.single_object = true;
+        self
+    }
+
+    /// Call the function in `schema` instead of the default one
+    ///
+    /// The schema must be listed in the project's exposed schemas.
+    pub fn schema(mut self, schema: &str) -> Self {
+        self.schema = Some(schema.to_string());
+        self
+    }
+
+    /// Deserialize the result into `U`
+    pub fn returns<U>(self) -> RpcBuilder<U> {
+        RpcBuilder {
+            database: self.database,
+            function: self.function,
+            params: self.params,
+            single: self.single,
+            read_only: self.read_only,
+            single_object: self.single_object,
+            schema: self.schema,
+            _returns: PhantomData,
+        }
+    }
+
+    /// Call the function
+    pub async fn execute(&self) -> Result<T>
+    where
+        T: for<'de> Deserialize<'de>,
+    {
+        debug!("Executing RPC function: {}", self.function);
+
+        let request = self.to_request(false)?;
+        let response = self.send::<T>(request).await?;
+
+        info!("RPC function {} executed successfully", self.function);
+        Ok(response.data)
+    }
+
+    /// Count the rows the function returns, without fetching them
+    ///
+    /// Sends a HEAD request with `Prefer: count=exact`, so like
+    /// [`read_only`](RpcBuilder::read_only) this needs a `STABLE` or
+    /// `IMMUTABLE` function.
+    pub async fn count(&self) -> Result<u64> {
+        debug!("Counting rows of RPC function: {}", self.function);
+
+        let request = self.to_request(true)?;
+        let response = self.send::<JsonValue>(request).await?;
+
+        response
+            .meta
+            .content_range
+            .and_then(|range| range.total)
+            .ok_or_else(|| Error::database("RPC count response has no total in Content-Range"))
+    }
+
+    async fn send<R>(&self, request: RpcRequest) -> Result<TypedResponse<R>>
+    where
+        R: for<'de> Deserialize<'de>,
+    {
+        let query: Vec<(&str, &str)> = request
+            .query
+            .iter()
+            .map(|(key, value)| (key.as_str(), value.as_str()))
+            .collect();
+        let headers: Vec<(&str, &str)> = request
+            .headers
+            .iter()
+            .map(|(name, value)| (*name, value.as_str()))
+            .collect();
+
+        self.database
+            .request_with_headers(
+                request.method,
+                &format!("/rpc/{}", self.function),
+                &query,
+                request.body,
+                &headers,
+            )
+            .await
+    }
+
+    /// Build the request, as a HEAD count request if `count` is set
+    fn to_request(&self, count: bool) -> Result<RpcRequest> {
+        let method = match (count, self.read_only) {
+            (true, _) => Method::HEAD,
+            (false, true) => Method::GET,
+            (false, false) => Method::POST,
+        };
+
+        let mut headers = Vec::new();
+        let mut prefer = Vec::new();
+        if self.single {
+            headers.push(("Accept", "application/vnd.pgrst.object+json".to_string()));
+        }
+        if count {
+            prefer.push("count=exact");
+        }
+
+        let (query, body) = if method == Method::POST {
+            if self.single_object {
+                prefer.push("params=single-object");
+            }
+            (Vec::new(), self.params.clone())
+        } else {
+            (self.query_params()?, None)
+        };
+
+        if !prefer.is_empty() {
+            headers.push(("Prefer", prefer.join(", ")));
+        }
+        if let Some(schema) = &self.schema {
+            let profile = if method == Method::POST {
+                "Content-Profile"
+            } else {
+                "Accept-Profile"
+            };
+            headers.push((profile, schema.clone()));
+        }
+
+        Ok(RpcRequest {
+            method,
+            query,
+            headers,
+            body,
+        })
+    }
+
+    /// Arguments as query parameters, with arrays in PostgreSQL `{a,b}` form
+    fn query_params(&self) -> Result<Vec<(String, String)>> {
+        let params = match &self.params {
+            None | Some(JsonValue::Null) => return Ok(Vec::new()),
+            Some(JsonValue::Object(params)) => params,
+            Some(_) => {
+                return Err(Error::invalid_input(format!(
+                    "Arguments of read-only RPC function {} must be an object",
+                    self.function
+                )))
+            }
+        };
+
+        let scalar = |value: &JsonValue| match value {
+            JsonValue::String(s) => s.clone(),
+            other => other.to_string(),
+        };
+
+        Ok(params
+            .iter()
+            .map(|(key, value)| {
+                let value = match value {
+                    JsonValue::Array(items) => {
+                        format!(
+                            "{{{}}}",
+                            items.iter().map(scalar).collect::<Vec<_>>().join(",")
+                        )
+                    }
+                    other => scalar(other),
+                };
+                (key.clone(), value)
+            })
+            .collect())
+    }
+}
+
+impl TransactionBuilder {
+    fn new(database: Database) -> Self {
+        Self {
+            database,
+            operations: Vec::new(),
+            max_attempts: 1,
+        }
+    }
+
+    /// Re-run the transaction when it fails with a serialization failure
+    ///
+    /// Under `REPEATABLE READ` or `SERIALIZABLE` isolation PostgreSQL aborts
+    /// one of two conflicting transactions with SQLSTATE `40001`; running it
+    /// again usually succeeds. Other errors are returned immediately.
+    /// `max_attempts` counts the first attempt.
+    pub fn retry_serialization_failures(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+
+    /// Add an INSERT operation to the transaction
+    pub fn insert(mut self, table: &str, data: JsonValue) -> Self {
+        self.operations.push(json!({
+            "operation": TransactionOperation::Insert,
+            "table": table,
+            "data": data
+        }));
+        self
+    }
+
+    /// Add an UPDATE operation to the transaction
     pub fn update(mut self, table: &str, data: JsonValue, where_clause: &str) -> Self {
         self.operations.push(json!({
             "operation": TransactionOperation::Update,
@@ -1573,65 +4442,1512 @@ impl TransactionBuilder {
             return Ok(Vec::new());
         }
 
-        self.database.transaction(self.operations).await
-    }
+        let mut attempt = 1;
+        loop {
+            match self.database.transaction(self.operations.clone()).await {
+                Err(err) if attempt < self.max_attempts && err.is_serialization_failure() => {
+                    warn!(
+                        "Transaction hit a serialization failure, retrying (attempt {} of {})",
+                        attempt + 1,
+                        self.max_attempts
+                    );
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Get the number of operations in the transaction
+    pub fn len(&self) -> usize {
+        self.operations.len()
+    }
+
+    /// Check if the transaction is empty
+    pub fn is_empty(&self) -> bool {
+        self.operations.is_empty()
+    }
+}
+
+// ========================
+// SAFE FILTER PARSING
+// ========================
+
+/// Operators for ordinary comparisons (`=`, `!=`, `>`, `>=`, `<`, `<=`)
+pub const COMPARISON_OPERATORS: &[FilterOperator] = &[
+    FilterOperator::Equal,
+    FilterOperator::NotEqual,
+    FilterOperator::GreaterThan,
+    FilterOperator::GreaterThanOrEqual,
+    FilterOperator::LessThan,
+    FilterOperator::LessThanOrEqual,
+];
+
+/// Operators for text search (`=`, `!=`, `like`, `ilike`)
+pub const TEXT_OPERATORS: &[FilterOperator] = &[
+    FilterOperator::Equal,
+    FilterOperator::NotEqual,
+    FilterOperator::Like,
+    FilterOperator::ILike,
+];
+
+/// Parser for filter expressions written by end users
+///
+/// Turns a small expression language into a [`Filter`], only accepting
+/// whitelisted columns and operators, so a search box or query editor cannot
+/// reach columns or PostgREST syntax the app did not intend to expose.
+///
+/// Grammar (keywords are case-insensitive):
+///
+/// ```text
+/// expr       := term ("or" term)*
+/// term       := factor ("and" factor)*
+/// factor     := "not" factor | "(" expr ")" | column op value
+/// op         := "=" | "!=" | "<>" | ">" | ">=" | "<" | "<=" | "like" | "ilike" | "is" | "in"
+/// value      := 'quoted' | "quoted" | bare-word | "(" value ("," value)* ")"
+/// ```
+///
+/// Values are escaped for PostgREST, so quotes, commas and parentheses inside
+/// them cannot change the structure of the query.
+///
+/// # Examples
+/// ```rust,no_run
+/// use supabase_lib_rs::database::{SafeFilterParser, COMPARISON_OPERATORS, TEXT_OPERATORS};
+/// use supabase_lib_rs::types::FilterOperator;
+/// use serde_json::Value;
+/// # use supabase_lib_rs::Client;
+/// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+/// # let client = Client::new("http://localhost:54321", "test-key")?;
+///
+/// let parser = SafeFilterParser::new()
+///     .allow_column("name", TEXT_OPERATORS)
+///     .allow_column("age", COMPARISON_OPERATORS)
+///     .allow_column("status", &[FilterOperator::Equal, FilterOperator::In]);
+///
+/// // Expression typed by the user
+/// let filter = parser.parse("status in (active, trial) and (age >= 18 or name ilike '*admin*')")?;
+///
+/// let users: Vec<Value> = client.database()
+///     .from("users")
+///     .select("*")
+///     .with_filter(filter)
+///     .execute()
+///     .await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct SafeFilterParser {
+    allowed: HashMap<String, Vec<FilterOperator>>,
+    max_conditions: usize,
+    max_depth: usize,
+    max_length: usize,
+}
+
+impl Default for SafeFilterParser {
+    fn default() -> Self {
+        Self {
+            allowed: HashMap::new(),
+            max_conditions: 20,
+            max_depth: 5,
+            max_length: 1024,
+        }
+    }
+}
+
+/// Token of a user filter expression
+#[derive(Debug, Clone, PartialEq)]
+enum FilterToken {
+    Word(String),
+    Quoted(String),
+    Symbol(&'static str),
+    LParen,
+    RParen,
+    Comma,
+}
+
+impl SafeFilterParser {
+    /// Create a parser with no allowed columns
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Allow filtering on `column` with the given operators
+    pub fn allow_column(mut self, column: &str, operators: &[FilterOperator]) -> Self {
+        self.allowed
+            .entry(column.to_string())
+            .or_default()
+            .extend(operators.iter().cloned());
+        self
+    }
+
+    /// Limit the number of conditions in one expression (default: 20)
+    pub fn max_conditions(mut self, max_conditions: usize) -> Self {
+        self.max_conditions = max_conditions;
+        self
+    }
+
+    /// Limit the nesting depth of parentheses and `not` (default: 5)
+    pub fn max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// Limit the length of the expression in bytes (default: 1024)
+    pub fn max_length(mut self, max_length: usize) -> Self {
+        self.max_length = max_length;
+        self
+    }
+
+    /// Parse a user-supplied expression into a filter
+    pub fn parse(&self, input: &str) -> Result<Filter> {
+        if input.len() > self.max_length {
+            return Err(Error::invalid_input(format!(
+                "Filter expression exceeds {} bytes",
+                self.max_length
+            )));
+        }
+
+        let tokens = Self::tokenize(input)?;
+        if tokens.is_empty() {
+            return Err(Error::invalid_input("Filter expression is empty"));
+        }
+
+        let mut state = FilterParseState {
+            tokens: &tokens,
+            position: 0,
+            conditions: 0,
+        };
+        let filter = self.parse_or(&mut state, 0)?;
+
+        if let Some(token) = state.peek() {
+            return Err(Error::invalid_input(format!(
+                "Unexpected {:?} in filter expression",
+                token
+            )));
+        }
+
+        Ok(Self::escape_values(filter, false))
+    }
+
+    fn tokenize(input: &str) -> Result<Vec<FilterToken>> {
+        let mut tokens = Vec::new();
+        let mut chars = input.chars().peekable();
+
+        while let Some(&c) = chars.peek() {
+            match c {
+                c if c.is_whitespace() => {
+                    chars.next();
+                }
+                '(' => {
+                    chars.next();
+                    tokens.push(FilterToken::LParen);
+                }
+                ')' => {
+                    chars.next();
+                    tokens.push(FilterToken::RParen);
+                }
+                ',' => {
+                    chars.next();
+                    tokens.push(FilterToken::Comma);
+                }
+                '\'' | '"' => {
+                    chars.next();
+                    let mut value = String::new();
+                    loop {
+                        match chars.next() {
+                            // A doubled quote stands for the quote itself
+                            Some(q) if q == c && chars.peek() == Some(&c) => {
+                                chars.next();
+                                value.push(c);
+                            }
+                            Some(q) if q == c => break,
+                            Some(other) => value.push(other),
+                            None => {
+                                return Err(Error::invalid_input(
+                                    "Unterminated string in filter expression",
+                                ))
+                            }
+                        }
+                    }
+                    tokens.push(FilterToken::Quoted(value));
+                }
+                '=' | '!' | '<' | '>' => {
+                    chars.next();
+                    let symbol = match (c, chars.peek()) {
+                        ('!', Some('=')) => "!=",
+                        ('<', Some('>')) => "<>",
+                        ('<', Some('=')) => "<=",
+                        ('>', Some('=')) => ">=",
+                        ('=', _) => "=",
+                        ('<', _) => "<",
+                        ('>', _) => ">",
+                        _ => {
+                            return Err(Error::invalid_input("Unexpected '!' in filter expression"))
+                        }
+                    };
+                    if symbol.len() == 2 {
+                        chars.next();
+                    }
+                    tokens.push(FilterToken::Symbol(symbol));
+                }
+                c if Self::is_word_char(c) => {
+                    let mut word = String::new();
+                    while let Some(&c) = chars.peek() {
+                        if !Self::is_word_char(c) {
+                            break;
+                        }
+                        word.push(c);
+                        chars.next();
+                    }
+                    tokens.push(FilterToken::Word(word));
+                }
+                other => {
+                    return Err(Error::invalid_input(format!(
+                        "Unexpected character '{}' in filter expression",
+                        other
+                    )))
+                }
+            }
+        }
+
+        Ok(tokens)
+    }
+
+    fn is_word_char(c: char) -> bool {
+        c.is_alphanumeric() || matches!(c, '_' | '-' | '.' | ':' | '+' | '*' | '@')
+    }
+
+    fn parse_or(&self, state: &mut FilterParseState<'_>, depth: usize) -> Result<Filter> {
+        let mut filters = vec![self.parse_and(state, depth)?];
+        while state.eat_keyword("or") {
+            filters.push(self.parse_and(state, depth)?);
+        }
+
+        Ok(if filters.len() == 1 {
+            filters.remove(0)
+        } else {
+            Filter::Or(filters)
+        })
+    }
+
+    fn parse_and(&self, state: &mut FilterParseState<'_>, depth: usize) -> Result<Filter> {
+        let mut filters = vec![self.parse_factor(state, depth)?];
+        while state.eat_keyword("and") {
+            filters.push(self.parse_factor(state, depth)?);
+        }
+
+        Ok(if filters.len() == 1 {
+            filters.remove(0)
+        } else {
+            Filter::And(filters)
+        })
+    }
+
+    fn parse_factor(&self, state: &mut FilterParseState<'_>, depth: usize) -> Result<Filter> {
+        if depth >= self.max_depth {
+            return Err(Error::invalid_input(format!(
+                "Filter expression is nested deeper than {} levels",
+                self.max_depth
+            )));
+        }
+
+        if state.eat_keyword("not") {
+            let filter = self.parse_factor(state, depth + 1)?;
+            return Ok(Filter::Not(Box::new(filter)));
+        }
+
+        if state.peek() == Some(&FilterToken::LParen) {
+            state.position += 1;
+            let filter = self.parse_or(state, depth + 1)?;
+            state.expect(&FilterToken::RParen)?;
+            return Ok(filter);
+        }
+
+        self.parse_condition(state)
+    }
+
+    fn parse_condition(&self, state: &mut FilterParseState<'_>) -> Result<Filter> {
+        let column = match state.next() {
+            Some(FilterToken::Word(word)) => word.clone(),
+            other => {
+                return Err(Error::invalid_input(format!(
+                    "Expected a column name in filter expression, found {:?}",
+                    other
+                )))
+            }
+        };
+
+        let Some(allowed_operators) = self.allowed.get(&column) else {
+            return Err(Error::invalid_input(format!(
+                "Filtering on column '{}' is not allowed",
+                column
+            )));
+        };
+
+        let operator = match state.next() {
+            Some(FilterToken::Symbol(symbol)) => match *symbol {
+                "=" => FilterOperator::Equal,
+                "!=" | "<>" => FilterOperator::NotEqual,
+                ">" => FilterOperator::GreaterThan,
+                ">=" => FilterOperator::GreaterThanOrEqual,
+                "<" => FilterOperator::LessThan,
+                _ => FilterOperator::LessThanOrEqual,
+            },
+            Some(FilterToken::Word(word)) => match word.to_ascii_lowercase().as_str() {
+                "like" => FilterOperator::Like,
+                "ilike" => FilterOperator::ILike,
+                "is" => FilterOperator::Is,
+                "in" => FilterOperator::In,
+                _ => {
+                    return Err(Error::invalid_input(format!(
+                        "Unknown operator '{}' in filter expression",
+                        word
+                    )))
+                }
+            },
+            other => {
+                return Err(Error::invalid_input(format!(
+                    "Expected an operator after '{}', found {:?}",
+                    column, other
+                )))
+            }
+        };
+
+        if !allowed_operators.contains(&operator) {
+            return Err(Error::invalid_input(format!(
+                "Operator {:?} is not allowed on column '{}'",
+                operator, column
+            )));
+        }
+
+        let value = match operator {
+            FilterOperator::In => {
+                state.expect(&FilterToken::LParen)?;
+                let mut items = vec![state.value()?];
+                while state.peek() == Some(&FilterToken::Comma) {
+                    state.position += 1;
+                    items.push(state.value()?);
+                }
+                state.expect(&FilterToken::RParen)?;
+
+                let items: Vec<String> = items.iter().map(|item| Self::quote(item)).collect();
+                format!("({})", items.join(","))
+            }
+            FilterOperator::Is => {
+                let value = state.value()?.to_ascii_lowercase();
+                if !matches!(value.as_str(), "null" | "true" | "false" | "unknown") {
+                    return Err(Error::invalid_input(format!(
+                        "'is' only accepts null, true, false or unknown, found '{}'",
+                        value
+                    )));
+                }
+                value
+            }
+            _ => state.value()?,
+        };
+
+        state.conditions += 1;
+        if state.conditions > self.max_conditions {
+            return Err(Error::invalid_input(format!(
+                "Filter expression has more than {} conditions",
+                self.max_conditions
+            )));
+        }
+
+        Ok(Filter::Simple {
+            column,
+            operator,
+            value,
+        })
+    }
+
+    /// Quote values that end up inside a logic tree (`or=(...)`, `not=and(...)`)
+    ///
+    /// Top-level conditions become their own query parameter, where PostgREST
+    /// takes the value verbatim; inside a logic tree reserved characters must
+    /// be quoted.
+    fn escape_values(filter: Filter, in_logic_tree: bool) -> Filter {
+        match filter {
+            Filter::Simple {
+                column,
+                operator,
+                value,
+            } => {
+                let value = match operator {
+                    FilterOperator::In | FilterOperator::Is => value,
+                    _ if in_logic_tree => Self::quote(&value),
+                    _ => value,
+                };
+                Filter::Simple {
+                    column,
+                    operator,
+                    value,
+                }
+            }
+            Filter::And(filters) => Filter::And(
+                filters
+                    .into_iter()
+                    .map(|f| Self::escape_values(f, in_logic_tree))
+                    .collect(),
+            ),
+            Filter::Or(filters) => Filter::Or(
+                filters
+                    .into_iter()
+                    .map(|f| Self::escape_values(f, true))
+                    .collect(),
+            ),
+            // Double negations are sent as the inner filter
+            Filter::Not(inner) if matches!(*inner, Filter::Not(_)) => {
+                let Filter::Not(filter) = *inner else {
+                    unreachable!()
+                };
+                Self::escape_values(*filter, in_logic_tree)
+            }
+            Filter::Not(inner) => {
+                let nested = in_logic_tree || !matches!(*inner, Filter::Simple { .. });
+                Filter::Not(Box::new(Self::escape_values(*inner, nested)))
+            }
+        }
+    }
+
+    /// Double-quote a value if it contains PostgREST reserved characters
+    fn quote(value: &str) -> String {
+        let reserved =
+            |c: char| matches!(c, ',' | '.' | ':' | '(' | ')' | '"' | '\\') || c.is_whitespace();
+        if !value.is_empty() && !value.chars().any(reserved) {
+            return value.to_string();
+        }
+
+        format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+    }
+}
+
+/// Cursor over the tokens of a filter expression
+struct FilterParseState<'a> {
+    tokens: &'a [FilterToken],
+    position: usize,
+    conditions: usize,
+}
+
+impl FilterParseState<'_> {
+    fn peek(&self) -> Option<&FilterToken> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<&FilterToken> {
+        let token = self.tokens.get(self.position);
+        self.position += 1;
+        token
+    }
+
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        match self.peek() {
+            Some(FilterToken::Word(word)) if word.eq_ignore_ascii_case(keyword) => {
+                self.position += 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn expect(&mut self, expected: &FilterToken) -> Result<()> {
+        match self.next() {
+            Some(token) if token == expected => Ok(()),
+            other => Err(Error::invalid_input(format!(
+                "Expected {:?} in filter expression, found {:?}",
+                expected, other
+            ))),
+        }
+    }
+
+    fn value(&mut self) -> Result<String> {
+        match self.next() {
+            Some(FilterToken::Word(word)) | Some(FilterToken::Quoted(word)) => Ok(word.clone()),
+            other => Err(Error::invalid_input(format!(
+                "Expected a value in filter expression, found {:?}",
+                other
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_logical_operators() {
+        // Test AND operator
+        let and_filter = Filter::And(vec![
+            Filter::Simple {
+                column: "age".to_string(),
+                operator: FilterOperator::GreaterThanOrEqual,
+                value: "18".to_string(),
+            },
+            Filter::Simple {
+                column: "status".to_string(),
+                operator: FilterOperator::Equal,
+                value: "active".to_string(),
+            },
+        ]);
+
+        // Test OR operator
+        let or_filter = Filter::Or(vec![
+            Filter::Simple {
+                column: "role".to_string(),
+                operator: FilterOperator::Equal,
+                value: "admin".to_string(),
+            },
+            Filter::Simple {
+                column: "role".to_string(),
+                operator: FilterOperator::Equal,
+                value: "moderator".to_string(),
+            },
+        ]);
+
+        // Test NOT operator
+        let not_filter = Filter::Not(Box::new(Filter::Simple {
+            column: "status".to_string(),
+            operator: FilterOperator::Equal,
+            value: "banned".to_string(),
+        }));
+
+        // These should compile and be constructed properly
+        assert!(matches!(and_filter, Filter::And(_)));
+        assert!(matches!(or_filter, Filter::Or(_)));
+        assert!(matches!(not_filter, Filter::Not(_)));
+    }
+
+    #[test]
+    fn test_query_sql_rendering() {
+        use crate::types::SupabaseConfig;
+        use reqwest::Client as HttpClient;
+
+        let db = Database::new(
+            Arc::new(SupabaseConfig::default()),
+            Arc::new(HttpClient::new()),
+        )
+        .unwrap();
+        let emails = Redaction::Columns(vec!["email".to_string()]);
+
+        let query = db
+            .from("users")
+            .select("id,email")
+            .eq("email", "ada@example.com")
+            .gte("age", "18")
+            .is("deleted_at", "null")
+            .order("created_at", OrderDirection::Descending)
+            .nulls_last()
+            .offset(20);
+        assert_eq!(
+            query.to_sql(&emails),
+            "SELECT id,email FROM users WHERE email = ? AND age >= 18 AND deleted_at IS NULL \
+             ORDER BY created_at DESC NULLS LAST OFFSET 20"
+        );
+        assert_eq!(
+            db.from("users")
+                .eq("name", "O'Brien")
+                .to_sql(&Redaction::None),
+            "SELECT * FROM users WHERE name = 'O''Brien'"
+        );
+
+        let insert = db
+            .insert("users")
+            .values(json!([{"email": "a@example.com", "age": 30}, {"email": "b@example.com", "age": 41}]))
+            .unwrap();
+        assert_eq!(
+            insert.to_sql(&emails),
+            "INSERT INTO users (age, email) VALUES (30, ?), (41, ?)"
+        );
+
+        let update = db
+            .update("users")
+            .set(json!({"email": "c@example.com"}))
+            .unwrap()
+            .eq("id", "7");
+        assert_eq!(
+            update.to_sql(&Redaction::All),
+            "UPDATE users SET email = ? WHERE id = ?"
+        );
+        assert_eq!(
+            db.delete("users").eq("id", "7").to_sql(&emails),
+            "DELETE FROM users WHERE id = 7"
+        );
+    }
+
+    #[test]
+    fn test_strict_models() {
+        use crate::types::{DatabaseConfig, SupabaseConfig};
+        use reqwest::Client as HttpClient;
+
+        #[derive(Debug, PartialEq, Deserialize)]
+        struct Author {
+            name: String,
+        }
+
+        #[derive(Debug, PartialEq, Deserialize)]
+        struct Post {
+            id: i64,
+            title: Option<String>,
+            author: Author,
+        }
+
+        let database = |strict_models| {
+            Database::new(
+                Arc::new(SupabaseConfig {
+                    database_config: DatabaseConfig {
+                        strict_models,
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                }),
+                Arc::new(HttpClient::new()),
+            )
+            .unwrap()
+        };
+        let lenient = database(false);
+        let strict = database(true);
+
+        let exact = json!([{"id": 1, "title": null, "author": {"name": "Ada"}}]);
+        assert_eq!(
+            strict.decode::<Vec<Post>>(exact).unwrap(),
+            vec![Post {
+                id: 1,
+                title: None,
+                author: Author {
+                    name: "Ada".to_string()
+                }
+            }]
+        );
+
+        let extra = json!([{"id": 1, "title": "Hi", "author": {"name": "Ada", "email": "a@b.c"}}]);
+        assert!(lenient.decode::<Vec<Post>>(extra.clone()).is_ok());
+        let err = strict.decode::<Vec<Post>>(extra).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("unknown field `email` at $[0].author for Author"));
+
+        let missing = json!([{"id": 1, "author": {"name": "Ada"}}]);
+        assert_eq!(
+            lenient.decode::<Vec<Post>>(missing.clone()).unwrap()[0].title,
+            None
+        );
+        let err = strict.decode::<Vec<Post>>(missing).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("missing field `title` at $[0] for Post"));
+
+        #[derive(Debug, PartialEq, Deserialize)]
+        #[serde(rename_all = "lowercase")]
+        enum Status {
+            Draft,
+            Published,
+            #[serde(other)]
+            Unknown,
+        }
+
+        #[derive(Debug, PartialEq, Deserialize)]
+        enum Body {
+            Text(String),
+            Image { url: String },
+        }
+
+        #[derive(Debug, PartialEq, Deserialize)]
+        struct Entry {
+            status: Status,
+            body: Body,
+        }
+
+        let known = json!([{"status": "published", "body": {"Image": {"url": "a.png"}}}]);
+        assert_eq!(
+            strict.decode::<Vec<Entry>>(known).unwrap(),
+            vec![Entry {
+                status: Status::Published,
+                body: Body::Image {
+                    url: "a.png".to_string()
+                }
+            }]
+        );
+
+        // `#[serde(other)]` only catches unknown values in lenient mode
+        let unknown = json!([{"status": "archived", "body": {"Text": "Hi"}}]);
+        assert_eq!(
+            lenient.decode::<Vec<Entry>>(unknown.clone()).unwrap()[0].status,
+            Status::Unknown
+        );
+        let err = strict.decode::<Vec<Entry>>(unknown).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("unknown variant `archived` at $[0].status for Status"));
+
+        let extra = json!([{"status": "draft", "body": {"Image": {"url": "a.png", "alt": ""}}}]);
+        assert!(lenient.decode::<Vec<Entry>>(extra.clone()).is_ok());
+        let err = strict.decode::<Vec<Entry>>(extra).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("unknown field `alt` at $[0].body.Image for Body"));
+    }
+
+    #[test]
+    fn test_field_casing() {
+        use crate::types::{DatabaseConfig, SupabaseConfig};
+        use reqwest::Client as HttpClient;
+
+        #[derive(Debug, PartialEq, Serialize, Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Post {
+            author_id: i64,
+            created_at: String,
+            metadata: JsonValue,
+        }
+
+        let db = Database::new(
+            Arc::new(SupabaseConfig {
+                url: "http://localhost:54321".to_string(),
+                database_config: DatabaseConfig {
+                    field_casing: FieldCasing::CamelCase,
+                    ..Default::default()
+                },
+                ..Default::default()
+            }),
+            Arc::new(HttpClient::new()),
+        )
+        .unwrap();
+
+        let url = db
+            .from("posts")
+            .select("authorId,createdAt,author:users(displayName)")
+            .eq("authorId", "1")
+            .not(|q| q.is("deletedAt", "null"))
+            .or(|q| q.gt("createdAt", "2024-01-01").eq("isPinned", "true"))
+            .order("createdAt", OrderDirection::Descending)
+            .build_url()
+            .unwrap();
+        let mut pairs: Vec<(String, String)> = url
+            .query_pairs()
+            .map(|(k, v)| (k.into_owned(), v.into_owned()))
+            .collect();
+        pairs.sort();
+        assert_eq!(
+            pairs,
+            vec![
+                ("author_id".to_string(), "eq.1".to_string()),
+                ("deleted_at".to_string(), "not.is.null".to_string()),
+                (
+                    "or".to_string(),
+                    "(created_at.gt.2024-01-01,is_pinned.eq.true)".to_string()
+                ),
+                ("order".to_string(), "created_at.desc".to_string()),
+                (
+                    "select".to_string(),
+                    "author_id,created_at,author:users(display_name)".to_string()
+                ),
+            ]
+        );
+
+        let post = Post {
+            author_id: 1,
+            created_at: "2024-01-01".to_string(),
+            metadata: json!({"sourceUrl": "https://example.com"}),
+        };
+        let insert = db.insert("posts").values(vec![&post]).unwrap();
+        assert_eq!(
+            insert.data,
+            json!([{
+                "author_id": 1,
+                "created_at": "2024-01-01",
+                "metadata": {"source_url": "https://example.com"}
+            }])
+        );
+        let update = db.update("posts").set(&post).unwrap();
+        assert!(update.data.get("author_id").is_some());
+
+        // Nested keys round-trip
+        let rows = json!([{
+            "author_id": 1,
+            "created_at": "2024-01-01",
+            "metadata": {"source_url": "https://example.com"}
+        }]);
+        assert_eq!(db.decode::<Vec<Post>>(rows).unwrap(), vec![post]);
+    }
+
+    #[test]
+    fn test_field_casing_nested_rows_and_verbatim_identifiers() {
+        use crate::types::{DatabaseConfig, SupabaseConfig};
+        use reqwest::Client as HttpClient;
+
+        #[derive(Debug, PartialEq, Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Author {
+            display_name: String,
+        }
+
+        #[derive(Debug, PartialEq, Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Post {
+            post_id: i64,
+            co_authors: Vec<Author>,
+        }
+
+        let db = Database::new(
+            Arc::new(SupabaseConfig {
+                url: "http://localhost:54321".to_string(),
+                database_config: DatabaseConfig {
+                    field_casing: FieldCasing::CamelCase,
+                    ..Default::default()
+                },
+                ..Default::default()
+            }),
+            Arc::new(HttpClient::new()),
+        )
+        .unwrap();
+
+        let url = db
+            .from("posts")
+            .select(r#"postId,"LegacyTitle",Slug,metaData->>sourceUrl,metaData->tags->0"#)
+            .eq("metaData->>sourceUrl", "x")
+            .build_url()
+            .unwrap();
+        let pairs: HashMap<String, String> = url
+            .query_pairs()
+            .map(|(k, v)| (k.into_owned(), v.into_owned()))
+            .collect();
+        assert_eq!(
+            pairs["select"],
+            r#"post_id,"LegacyTitle",Slug,meta_data->>sourceUrl,meta_data->tags->0"#
+        );
+        assert_eq!(pairs["meta_data->>sourceUrl"], "eq.x");
+
+        // Embedded resources are renamed too
+        let rows = json!([{
+            "post_id": 1,
+            "co_authors": [{"display_name": "Ada"}, {"display_name": "Grace"}]
+        }]);
+        assert_eq!(
+            db.decode::<Vec<Post>>(rows).unwrap(),
+            vec![Post {
+                post_id: 1,
+                co_authors: vec![
+                    Author {
+                        display_name: "Ada".to_string()
+                    },
+                    Author {
+                        display_name: "Grace".to_string()
+                    },
+                ],
+            }]
+        );
+    }
+
+    #[test]
+    fn test_rpc_builder_requests() {
+        use crate::types::SupabaseConfig;
+        use reqwest::Client as HttpClient;
+
+        let db = Database::new(
+            Arc::new(SupabaseConfig::default()),
+            Arc::new(HttpClient::new()),
+        )
+        .unwrap();
+        let params = json!({"user_id": 42, "tags": ["a", "b"], "name": "Ada"});
+
+        let post = db
+            .rpc_builder("user_stats")
+            .params(&params)
+            .unwrap()
+            .single()
+            .single_object()
+            .schema("api")
+            .to_request(false)
+            .unwrap();
+        assert_eq!(post.method, Method::POST);
+        assert!(post.query.is_empty());
+        assert_eq!(post.body, Some(params.clone()));
+        assert_eq!(
+            post.headers,
+            vec![
+                ("Accept", "application/vnd.pgrst.object+json".to_string()),
+                ("Prefer", "params=single-object".to_string()),
+                ("Content-Profile", "api".to_string()),
+            ]
+        );
+
+        let get = db
+            .rpc_builder("user_stats")
+            .params(&params)
+            .unwrap()
+            .read_only()
+            .single_object()
+            .schema("api")
+            .returns::<Vec<i64>>();
+        let request = get.to_request(false).unwrap();
+        assert_eq!(request.method, Method::GET);
+        assert_eq!(request.body, None);
+        assert_eq!(
+            request.query,
+            vec![
+                ("name".to_string(), "Ada".to_string()),
+                ("tags".to_string(), "{a,b}".to_string()),
+                ("user_id".to_string(), "42".to_string()),
+            ]
+        );
+        assert_eq!(request.headers, vec![("Accept-Profile", "api".to_string())]);
+
+        let head = get.to_request(true).unwrap();
+        assert_eq!(head.method, Method::HEAD);
+        assert_eq!(
+            head.headers,
+            vec![
+                ("Prefer", "count=exact".to_string()),
+                ("Accept-Profile", "api".to_string()),
+            ]
+        );
+
+        let err = db
+            .rpc_builder("user_stats")
+            .params(json!([1, 2]))
+            .unwrap()
+            .read_only()
+            .to_request(false)
+            .unwrap_err();
+        assert!(matches!(err, Error::InvalidInput { .. }));
+    }
+
+    #[tokio::test]
+    async fn test_execute_with_count() {
+        use crate::mock_http::{MockServer, Response};
+        use crate::types::SupabaseConfig;
+        use reqwest::Client as HttpClient;
+
+        let server = MockServer::start([Response::json(r#"[{"id":1},{"id":2}]"#)
+            .status(206)
+            .header("content-range", "0-1/57")])
+        .await;
+
+        let db = Database::new(
+            Arc::new(SupabaseConfig {
+                url: server.url(),
+                ..Default::default()
+            }),
+            Arc::new(HttpClient::new()),
+        )
+        .unwrap();
+        let (rows, total) = db
+            .from("posts")
+            .select("id")
+            .count(CountStrategy::Planned)
+            .limit(2)
+            .execute_with_count::<JsonValue>()
+            .await
+            .unwrap();
+
+        assert_eq!(rows, vec![json!({"id": 1}), json!({"id": 2})]);
+        assert_eq!(total, Some(57));
+        assert_eq!(server.requests()[0].header("prefer"), Some("count=planned"));
+    }
+
+    #[tokio::test]
+    async fn test_error_fingerprint_per_client() {
+        use crate::mock_http::{MockServer, Response};
+        use crate::types::SupabaseConfig;
+        use reqwest::Client as HttpClient;
+
+        let server = MockServer::with_handler(|_| Response::new(500).body("boom")).await;
+        let database = |error_fingerprint| {
+            Database::new(
+                Arc::new(SupabaseConfig {
+                    url: server.url(),
+                    error_fingerprint,
+                    ..Default::default()
+                }),
+                Arc::new(HttpClient::new()),
+            )
+            .unwrap()
+        };
+        let enabled = database(true);
+        let disabled = database(false);
+
+        let error = disabled
+            .from("posts")
+            .select("*")
+            .execute::<JsonValue>()
+            .await
+            .unwrap_err();
+        assert!(error.context().unwrap().metadata.is_empty());
+
+        // A client with the fingerprint enabled does not affect the other one
+        let error = enabled
+            .from("posts")
+            .select("*")
+            .execute::<JsonValue>()
+            .await
+            .unwrap_err();
+        assert_eq!(
+            error
+                .context()
+                .unwrap()
+                .metadata
+                .get("endpoint")
+                .map(String::as_str),
+            Some("database")
+        );
+        let error = disabled
+            .from("posts")
+            .select("*")
+            .execute::<JsonValue>()
+            .await
+            .unwrap_err();
+        assert!(error.context().unwrap().metadata.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_compressed_bulk_insert() {
+        use crate::mock_http::{MockServer, Response};
+        use crate::types::{HttpConfig, SupabaseConfig};
+        use flate2::read::GzDecoder;
+        use std::io::Read;
+
+        let server = MockServer::start([Response::json("")
+            .status(201)
+            .header("content-encoding", "gzip")
+            .body(compression::gzip(br#"[{"id":1}]"#).unwrap())])
+        .await;
+
+        let config = Arc::new(SupabaseConfig {
+            url: server.url(),
+            key: "test-key".to_string(),
+            http_config: HttpConfig {
+                request_compression_threshold: Some(1024),
+                ..Default::default()
+            },
+            ..Default::default()
+        });
+        let http_client = crate::Client::new_with_config(config.as_ref().clone())
+            .unwrap()
+            .http_client();
+        let db = Database::new(config, http_client).unwrap();
+        let rows: Vec<JsonValue> = (0..200)
+            .map(|id| json!({ "id": id, "status": "active" }))
+            .collect();
+        let inserted: Vec<JsonValue> = db.bulk_insert("users", rows.clone()).await.unwrap();
+        assert_eq!(inserted, vec![json!({"id": 1})]);
+
+        let request = &server.requests()[0];
+        assert_eq!(request.header("content-encoding"), Some("gzip"));
+        assert!(request.header("accept-encoding").unwrap().contains("gzip"));
+        let body = &request.body;
+        let mut json = Vec::new();
+        GzDecoder::new(body.as_slice())
+            .read_to_end(&mut json)
+            .unwrap();
+        assert!(body.len() < json.len());
+        assert_eq!(
+            serde_json::from_slice::<Vec<JsonValue>>(&json).unwrap(),
+            rows
+        );
+    }
+
+    #[tokio::test]
+    async fn test_paginate_fetches_ranges() {
+        use crate::mock_http::{MockServer, Response};
+        use crate::types::SupabaseConfig;
+        use futures_util::TryStreamExt;
+        use reqwest::Client as HttpClient;
+
+        // Serves ids 0..5 for whatever `Range` is requested
+        let server = MockServer::with_handler(|request| {
+            assert_eq!(request.header("range-unit"), Some("items"));
+            let (from, to) = request.header("range").unwrap().split_once('-').unwrap();
+            let (from, to): (u64, u64) = (from.parse().unwrap(), to.parse().unwrap());
+            let rows: Vec<JsonValue> = (from..=to.min(4)).map(|id| json!({"id": id})).collect();
+            Response::json(serde_json::to_string(&rows).unwrap())
+        })
+        .await;
+        let ranges = || -> Vec<String> {
+            server
+                .requests()
+                .iter()
+                .map(|request| request.header("range").unwrap().to_string())
+                .collect()
+        };
+
+        let db = Database::new(
+            Arc::new(SupabaseConfig {
+                url: server.url(),
+                ..Default::default()
+            }),
+            Arc::new(HttpClient::new()),
+        )
+        .unwrap();
+        let pages: Vec<Vec<JsonValue>> = db.from("events").paginate(2).try_collect().await.unwrap();
+
+        assert_eq!(
+            pages.iter().map(Vec::len).collect::<Vec<_>>(),
+            vec![2, 2, 1]
+        );
+        assert_eq!(pages[2], vec![json!({"id": 4})]);
+        assert_eq!(ranges(), vec!["0-1", "2-3", "4-5"]);
+
+        // An exact multiple of the page size ends with an empty request
+        let pages: Vec<Vec<JsonValue>> = db.from("events").paginate(5).try_collect().await.unwrap();
+        assert_eq!(pages.len(), 1);
+        assert_eq!(ranges()[3..], ["0-4", "5-9"]);
+    }
+
+    #[tokio::test]
+    async fn test_paginate_ends_on_unsatisfiable_range() {
+        use crate::mock_http::{MockServer, Response};
+        use crate::types::SupabaseConfig;
+        use futures_util::TryStreamExt;
+        use reqwest::Client as HttpClient;
+
+        // Rows were deleted after the first page, so the second starts past the end
+        let server = MockServer::start([
+            Response::json(r#"[{"id":0},{"id":1}]"#),
+            Response::json(
+                r#"{"code":"PGRST103","details":"An offset of 2 was requested, but there are only 1 rows.","hint":null,"message":"Requested range not satisfiable"}"#,
+            )
+            .status(416),
+            Response::json("[]").status(416),
+        ])
+        .await;
+        let db = Database::new(
+            Arc::new(SupabaseConfig {
+                url: server.url(),
+                ..Default::default()
+            }),
+            Arc::new(HttpClient::new()),
+        )
+        .unwrap();
+
+        let pages: Vec<Vec<JsonValue>> = db.from("events").paginate(2).try_collect().await.unwrap();
+        assert_eq!(pages, vec![vec![json!({"id": 0}), json!({"id": 1})]]);
+
+        // A bare 416 ends the stream too
+        let pages: Vec<Vec<JsonValue>> = db.from("events").paginate(2).try_collect().await.unwrap();
+        assert!(pages.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_insert_retry_on_conflict() {
+        use crate::mock_http::{MockServer, Response};
+        use crate::types::SupabaseConfig;
+        use reqwest::Client as HttpClient;
+
+        let conflict = || {
+            Response::json(
+                r#"{"code":"23505","details":"Key (slug)=(hello) already exists.","hint":null,"message":"duplicate key value violates unique constraint \"posts_slug_key\""}"#,
+            )
+            .status(409)
+        };
+        let server = MockServer::start([
+            conflict(),
+            conflict(),
+            Response::json(r#"[{"id":1,"slug":"hello-3"}]"#).status(201),
+            conflict(),
+            conflict(),
+            Response::json(r#"{"code":"23503","message":"insert or update violates foreign key"}"#)
+                .status(409),
+        ])
+        .await;
+        let db = Database::new(
+            Arc::new(SupabaseConfig {
+                url: server.url(),
+                ..Default::default()
+            }),
+            Arc::new(HttpClient::new()),
+        )
+        .unwrap();
+        let insert = || {
+            db.insert("posts")
+                .values(json!({"title": "Hello", "slug": "hello"}))
+                .unwrap()
+                .retry_on_conflict(3, |post, retry| {
+                    post["slug"] = json!(format!("hello-{}", retry + 1));
+                })
+        };
+
+        let posts: Vec<JsonValue> = insert().execute().await.unwrap();
+        assert_eq!(posts, vec![json!({"id": 1, "slug": "hello-3"})]);
+        let slugs: Vec<_> = server
+            .requests()
+            .iter()
+            .map(|request| {
+                serde_json::from_str::<JsonValue>(&request.text()).unwrap()["slug"].clone()
+            })
+            .collect();
+        assert_eq!(
+            slugs,
+            vec![json!("hello"), json!("hello-2"), json!("hello-3")]
+        );
+
+        // Only unique violations are retried
+        let err = insert().execute::<JsonValue>().await.unwrap_err();
+        assert!(err.is_foreign_key_violation());
+        assert_eq!(server.requests().len(), 6);
+
+        // The last conflict is returned once every attempt is used
+        let server = MockServer::start([conflict(), conflict()]).await;
+        let db = Database::new(
+            Arc::new(SupabaseConfig {
+                url: server.url(),
+                ..Default::default()
+            }),
+            Arc::new(HttpClient::new()),
+        )
+        .unwrap();
+        let err = db
+            .insert("posts")
+            .values(json!({"slug": "hello"}))
+            .unwrap()
+            .retry_on_conflict(2, |_, _| {})
+            .execute::<JsonValue>()
+            .await
+            .unwrap_err();
+        assert!(err.is_unique_violation());
+        assert_eq!(server.requests().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_execute_csv() {
+        use crate::mock_http::{MockServer, Response};
+        use crate::types::SupabaseConfig;
+        use reqwest::Client as HttpClient;
+
+        let server = MockServer::with_handler(|_| {
+            Response::new(200)
+                .header("content-type", "text/csv")
+                .body("id,title\n1,Hello\n2,\"Comma, inside\"\n")
+        })
+        .await;
+
+        let db = Database::new(
+            Arc::new(SupabaseConfig {
+                url: server.url(),
+                ..Default::default()
+            }),
+            Arc::new(HttpClient::new()),
+        )
+        .unwrap();
+        let query = db.from("posts").select("id,title");
+
+        let csv = query.execute_csv().await.unwrap();
+        assert_eq!(csv, "id,title\n1,Hello\n2,\"Comma, inside\"\n");
+
+        #[cfg(feature = "database-csv")]
+        {
+            #[derive(Debug, PartialEq, Deserialize)]
+            struct Post {
+                id: i64,
+                title: String,
+            }
+
+            let posts: Vec<Post> = query.execute_csv_typed().await.unwrap();
+            assert_eq!(posts[1].title, "Comma, inside");
+        }
+
+        for request in server.requests() {
+            assert_eq!(request.header("accept"), Some("text/csv"));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_mutation_execute_count() {
+        use crate::mock_http::{MockServer, Response};
+        use crate::types::SupabaseConfig;
+        use reqwest::Client as HttpClient;
+
+        let server = MockServer::start([
+            Response::new(204).header("content-range", "*/3"),
+            Response::new(204).header("content-range", "*/0"),
+        ])
+        .await;
+
+        let db = Database::new(
+            Arc::new(SupabaseConfig {
+                url: server.url(),
+                ..Default::default()
+            }),
+            Arc::new(HttpClient::new()),
+        )
+        .unwrap();
+
+        let updated = db
+            .update("users")
+            .set(json!({"status": "archived"}))
+            .unwrap()
+            .eq("status", "inactive")
+            .returning("*")
+            .execute_count()
+            .await
+            .unwrap();
+        assert_eq!(updated, 3);
+
+        let deleted = db
+            .delete("users")
+            .eq("id", "42")
+            .execute_count()
+            .await
+            .unwrap();
+        assert_eq!(deleted, 0);
+
+        let requests = server.requests();
+        assert_eq!(
+            requests[0].line(),
+            "PATCH /rest/v1/users?status=eq.inactive"
+        );
+        assert_eq!(requests[1].line(), "DELETE /rest/v1/users?id=eq.42");
+        for request in requests {
+            assert_eq!(request.header("prefer"), Some("return=minimal,count=exact"));
+        }
+    }
+
+    #[test]
+    fn test_mutation_filters() {
+        use crate::types::SupabaseConfig;
+        use reqwest::Client as HttpClient;
+
+        let db = Database::new(
+            Arc::new(SupabaseConfig {
+                url: "http://localhost:54321".to_string(),
+                ..Default::default()
+            }),
+            Arc::new(HttpClient::new()),
+        )
+        .unwrap();
+        let query_pairs = |url: Url| {
+            let mut pairs: Vec<(String, String)> = url
+                .query_pairs()
+                .map(|(k, v)| (k.into_owned(), v.into_owned()))
+                .collect();
+            pairs.sort();
+            pairs
+        };
+
+        let update = db
+            .update("users")
+            .set(json!({"status": "archived"}))
+            .unwrap()
+            .lt("last_login", "2024-01-01")
+            .r#in("role", &["guest", "trial"])
+            .or(|q| q.eq("plan", "free").is("plan", "null"));
+        assert_eq!(
+            query_pairs(update.url().unwrap()),
+            vec![
+                ("last_login".to_string(), "lt.2024-01-01".to_string()),
+                ("or".to_string(), "(plan.eq.free,plan.is.null)".to_string()),
+                ("role".to_string(), "in.(guest,trial)".to_string()),
+            ]
+        );
+
+        let delete = db.delete("sessions").not(|q| q.gte("expires_at", "now"));
+        assert_eq!(
+            query_pairs(delete.url().unwrap()),
+            vec![("expires_at".to_string(), "not.gte.now".to_string())]
+        );
+
+        let err = db.delete("sessions").url().unwrap_err();
+        assert!(matches!(err, Error::InvalidInput { .. }));
+        assert!(db
+            .update("users")
+            .set(json!({"status": "archived"}))
+            .unwrap()
+            .url()
+            .is_err());
+        assert!(db.delete("sessions").allow_unfiltered().url().is_ok());
+    }
+
+    #[test]
+    fn test_mutation_filters_keep_every_condition() {
+        use crate::types::SupabaseConfig;
+        use reqwest::Client as HttpClient;
+
+        let db = Database::new(
+            Arc::new(SupabaseConfig {
+                url: "http://localhost:54321".to_string(),
+                ..Default::default()
+            }),
+            Arc::new(HttpClient::new()),
+        )
+        .unwrap();
+        let query_pairs = |url: Url| {
+            url.query_pairs()
+                .map(|(k, v)| (k.into_owned(), v.into_owned()))
+                .collect::<Vec<_>>()
+        };
+
+        // Both bounds on one column reach PostgREST, which ANDs them
+        let delete = db
+            .delete("events")
+            .gte("created_at", "2024-01-01")
+            .lt("created_at", "2024-02-01")
+            .not(|q| q.or(|q| q.eq("kind", "audit").eq("kind", "billing")));
+        assert_eq!(
+            query_pairs(delete.url().unwrap()),
+            vec![
+                ("created_at".to_string(), "gte.2024-01-01".to_string()),
+                ("created_at".to_string(), "lt.2024-02-01".to_string()),
+                (
+                    "not.or".to_string(),
+                    "(kind.eq.audit,kind.eq.billing)".to_string()
+                ),
+            ]
+        );
 
-    /// Get the number of operations in the transaction
-    pub fn len(&self) -> usize {
-        self.operations.len()
+        let update = db
+            .update("users")
+            .set(json!({"status": "archived"}))
+            .unwrap()
+            .or(|q| q.eq("plan", "free").eq("plan", "trial"))
+            .or(|q| q.is("last_login", "null").lt("last_login", "2024-01-01"));
+        assert_eq!(
+            query_pairs(update.url().unwrap()),
+            vec![
+                ("or".to_string(), "(plan.eq.free,plan.eq.trial)".to_string()),
+                (
+                    "or".to_string(),
+                    "(last_login.is.null,last_login.lt.2024-01-01)".to_string()
+                ),
+            ]
+        );
     }
 
-    /// Check if the transaction is empty
-    pub fn is_empty(&self) -> bool {
-        self.operations.is_empty()
-    }
-}
+    #[test]
+    fn test_requests_carry_access_token() {
+        use crate::types::{SupabaseConfig, TokenProvider};
+        use reqwest::Client as HttpClient;
+        use std::sync::Mutex;
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+        struct Token(Mutex<Option<String>>);
 
-    #[test]
-    fn test_logical_operators() {
-        // Test AND operator
-        let and_filter = Filter::And(vec![
-            Filter::Simple {
-                column: "age".to_string(),
-                operator: FilterOperator::GreaterThanOrEqual,
-                value: "18".to_string(),
-            },
-            Filter::Simple {
-                column: "status".to_string(),
-                operator: FilterOperator::Equal,
-                value: "active".to_string(),
-            },
-        ]);
+        impl TokenProvider for Token {
+            fn access_token(&self) -> Option<String> {
+                self.0.lock().unwrap().clone()
+            }
+        }
 
-        // Test OR operator
-        let or_filter = Filter::Or(vec![
-            Filter::Simple {
-                column: "role".to_string(),
-                operator: FilterOperator::Equal,
-                value: "admin".to_string(),
-            },
-            Filter::Simple {
-                column: "role".to_string(),
-                operator: FilterOperator::Equal,
-                value: "moderator".to_string(),
-            },
-        ]);
+        let token = Arc::new(Token(Mutex::new(None)));
+        let db = Database::new(
+            Arc::new(SupabaseConfig::default()),
+            Arc::new(HttpClient::new()),
+        )
+        .unwrap()
+        .with_token_provider(token.clone());
+        let authorization = |db: &Database| {
+            db.build_request(Method::GET, "http://localhost/rest/v1/posts")
+                .build()
+                .unwrap()
+                .headers()
+                .get("Authorization")
+                .map(|value| value.to_str().unwrap().to_string())
+        };
 
-        // Test NOT operator
-        let not_filter = Filter::Not(Box::new(Filter::Simple {
-            column: "status".to_string(),
-            operator: FilterOperator::Equal,
-            value: "banned".to_string(),
-        }));
+        // Without a session the client's default API key header applies
+        assert_eq!(authorization(&db), None);
 
-        // These should compile and be constructed properly
-        assert!(matches!(and_filter, Filter::And(_)));
-        assert!(matches!(or_filter, Filter::Or(_)));
-        assert!(matches!(not_filter, Filter::Not(_)));
+        *token.0.lock().unwrap() = Some("user-jwt".to_string());
+        assert_eq!(authorization(&db).as_deref(), Some("Bearer user-jwt"));
     }
 
     #[test]
@@ -1667,7 +5983,7 @@ mod tests {
             },
         ]);
         let condition = db.build_filter_condition(&or_filter);
-        assert_eq!(condition, "or.(status.eq.online,status.eq.away)");
+        assert_eq!(condition, "or(status.eq.online,status.eq.away)");
 
         // Test NOT condition
         let not_filter = Filter::Not(Box::new(Filter::Simple {
@@ -1676,7 +5992,7 @@ mod tests {
             value: "true".to_string(),
         }));
         let condition = db.build_filter_condition(&not_filter);
-        assert_eq!(condition, "not.(banned.eq.true)");
+        assert_eq!(condition, "banned.not.eq.true");
     }
 
     #[test]
@@ -1754,6 +6070,61 @@ mod tests {
         assert_eq!(join.alias.as_ref().unwrap(), "author");
     }
 
+    #[test]
+    fn test_computed_columns_in_select() {
+        use crate::types::SupabaseConfig;
+        use reqwest::Client as HttpClient;
+        use std::sync::Arc;
+
+        let config = Arc::new(SupabaseConfig::default());
+        let http_client = Arc::new(HttpClient::new());
+        let db = Database::new(config, http_client).unwrap();
+
+        let query = db
+            .from("users")
+            .select("id")
+            .computed("full_name")
+            .computed_as("age_in_days", "age")
+            .left_join("profiles", "bio");
+
+        assert_eq!(query.computed_columns().len(), 2);
+        assert_eq!(
+            query.build_select_with_joins(),
+            "id,full_name,age:age_in_days,profiles(bio)"
+        );
+        assert!(query.validate_computed_columns().is_ok());
+
+        // Expressions are rejected, only function names are allowed
+        let query = db.from("users").computed("full_name(users); drop");
+        assert!(query.validate_computed_columns().is_err());
+    }
+
+    #[test]
+    fn test_query_budget_violations() {
+        let plan = QueryPlan::from_json(json!([{
+            "Plan": {"Node Type": "Seq Scan", "Total Cost": 2500.5, "Plan Rows": 10000}
+        }]))
+        .unwrap();
+        assert_eq!(plan.total_cost, 2500.5);
+        assert_eq!(plan.plan_rows, 10000);
+
+        let budget = QueryBudget {
+            max_cost: Some(1000.0),
+            max_rows: Some(20000),
+            fail_on_exceed: false,
+        };
+        assert_eq!(
+            budget.violations(&plan),
+            vec![BudgetViolation::Cost {
+                estimated: 2500.5,
+                max: 1000.0
+            }]
+        );
+
+        assert!(QueryBudget::default().violations(&plan).is_empty());
+        assert!(QueryPlan::from_json(json!({"rows": []})).is_err());
+    }
+
     #[test]
     fn test_join_clause_generation() {
         use crate::types::SupabaseConfig;
@@ -1872,6 +6243,519 @@ mod tests {
         assert_eq!(builder.on_conflict.as_ref().unwrap(), "email,id");
     }
 
+    #[test]
+    fn test_insert_default_for_missing() {
+        use crate::types::SupabaseConfig;
+        use reqwest::Client as HttpClient;
+        use std::sync::Arc;
+
+        let config = Arc::new(SupabaseConfig::default());
+        let http_client = Arc::new(HttpClient::new());
+        let db = Database::new(config, http_client).unwrap();
+
+        let builder = db
+            .insert("tasks")
+            .values(serde_json::json!([
+                {"title": "a"},
+                {"title": "b", "status": "done"}
+            ]))
+            .unwrap();
+        assert!(builder.prefer_header().is_none());
+
+        let builder = builder.upsert().returning("*").use_default_for_missing();
+        assert_eq!(builder.payload_columns(), vec!["title", "status"]);
+        assert_eq!(
+            builder.prefer_header().as_deref(),
+            Some("return=representation,resolution=merge-duplicates,missing=default")
+        );
+    }
+
+    #[test]
+    fn test_safe_filter_parser() {
+        use crate::types::SupabaseConfig;
+        use reqwest::Client as HttpClient;
+        use std::sync::Arc;
+
+        let config = Arc::new(SupabaseConfig::default());
+        let http_client = Arc::new(HttpClient::new());
+        let db = Database::new(config, http_client).unwrap();
+
+        let parser = SafeFilterParser::new()
+            .allow_column("name", TEXT_OPERATORS)
+            .allow_column("age", COMPARISON_OPERATORS)
+            .allow_column("status", &[FilterOperator::Equal, FilterOperator::In]);
+
+        let filter = parser
+            .parse("status IN (active, 'on hold') and (age >= 18 or not name ilike 'a,b)')")
+            .unwrap();
+        assert_eq!(
+            db.build_filter_condition(&filter),
+            "and(status.in.(active,\"on hold\"),or(age.gte.18,name.not.ilike.\"a,b)\"))"
+        );
+
+        // Top-level values are sent verbatim as their own query parameter
+        let filter = parser.parse("name = 'O''Brien, Pat'").unwrap();
+        assert_eq!(db.build_filter_condition(&filter), "name.eq.O'Brien, Pat");
+
+        assert!(parser.parse("password = 'x'").is_err());
+        assert!(parser.parse("status > 3").is_err());
+        assert!(parser.parse("age = 1 or").is_err());
+        assert!(parser.parse("(age = 1").is_err());
+        assert!(parser.parse("age = 'unterminated").is_err());
+        assert!(parser.parse("not not not not not age = 1").is_err());
+        assert!(SafeFilterParser::new()
+            .allow_column("age", COMPARISON_OPERATORS)
+            .max_conditions(2)
+            .parse("age = 1 and age = 2 and age = 3")
+            .is_err());
+    }
+
+    #[test]
+    fn test_safe_filter_query_params() {
+        use crate::types::SupabaseConfig;
+        use reqwest::Client as HttpClient;
+
+        let db = Database::new(
+            Arc::new(SupabaseConfig {
+                url: "http://localhost:54321".to_string(),
+                ..Default::default()
+            }),
+            Arc::new(HttpClient::new()),
+        )
+        .unwrap();
+        let parser = SafeFilterParser::new()
+            .allow_column("name", TEXT_OPERATORS)
+            .allow_column("age", COMPARISON_OPERATORS);
+        let params = |input: &str| {
+            let url = db
+                .from("users")
+                .with_filter(parser.parse(input).unwrap())
+                .build_url()
+                .unwrap();
+            url.query_pairs()
+                .filter(|(key, _)| key != "select")
+                .map(|(k, v)| (k.into_owned(), v.into_owned()))
+                .collect::<Vec<_>>()
+        };
+        let pair = |key: &str, value: &str| (key.to_string(), value.to_string());
+
+        assert_eq!(
+            params("age >= 18 and age < 65"),
+            vec![pair("age", "gte.18"), pair("age", "lt.65")]
+        );
+        assert_eq!(
+            params("(age < 18 or age > 65) and (name = a or name = b)"),
+            vec![
+                pair("or", "(age.lt.18,age.gt.65)"),
+                pair("or", "(name.eq.a,name.eq.b)"),
+            ]
+        );
+        assert_eq!(
+            params("not name = 'O''Brien, Pat'"),
+            vec![pair("name", "not.eq.O'Brien, Pat")]
+        );
+        assert_eq!(params("not not name = 'a b'"), vec![pair("name", "eq.a b")]);
+        assert_eq!(
+            params("not (age < 18 or name ilike 'x, y')"),
+            vec![pair("not.or", "(age.lt.18,name.ilike.\"x, y\")")]
+        );
+    }
+
+    #[test]
+    fn test_response_meta_parsing() {
+        assert_eq!(
+            ContentRange::parse("0-24/3573"),
+            Some(ContentRange {
+                start: Some(0),
+                end: Some(24),
+                total: Some(3573)
+            })
+        );
+        assert_eq!(
+            ContentRange::parse("*/0"),
+            Some(ContentRange {
+                start: None,
+                end: None,
+                total: Some(0)
+            })
+        );
+        assert_eq!(ContentRange::parse("10-19/*").unwrap().total, None);
+        assert!(ContentRange::parse("garbage").is_none());
+
+        let headers = HashMap::from([
+            ("content-range".to_string(), "0-9/100".to_string()),
+            ("x-ratelimit-remaining".to_string(), "42".to_string()),
+            ("sb-gateway-version".to_string(), "1".to_string()),
+        ]);
+        let meta = ResponseMeta::from_parts(206, headers);
+        assert_eq!(meta.status, 206);
+        assert_eq!(meta.content_range.unwrap().total, Some(100));
+        assert_eq!(meta.rate_limit.unwrap().remaining, Some(42));
+        assert_eq!(meta.gateway_version.as_deref(), Some("1"));
+        assert!(meta.server_timing.is_none());
+
+        let meta = ResponseMeta::from_parts(200, HashMap::new());
+        assert!(meta.rate_limit.is_none());
+    }
+
+    #[test]
+    fn test_table_profiles() {
+        use crate::types::SupabaseConfig;
+        use reqwest::Client as HttpClient;
+        use std::sync::Arc;
+
+        struct Post;
+        impl Table for Post {
+            const NAME: &'static str = "posts";
+        }
+
+        let config = Arc::new(SupabaseConfig::default());
+        let db = Database::new(config, Arc::new(HttpClient::new())).unwrap();
+        db.register_profile::<Post>(
+            Profile::default_select("id,title").skip_on_write(&["id", "created_at"]),
+        );
+
+        assert_eq!(db.from("posts").build_select_with_joins(), "id,title");
+        assert_eq!(
+            db.from("posts")
+                .include_columns("body")
+                .build_select_with_joins(),
+            "id,title,body"
+        );
+        assert_eq!(db.from("posts").select("*").build_select_with_joins(), "*");
+        assert_eq!(db.from("users").build_select_with_joins(), "*");
+        assert_eq!(
+            db.from("users")
+                .include_columns("body")
+                .build_select_with_joins(),
+            "*,body"
+        );
+
+        let insert = db
+            .insert("posts")
+            .values(json!([{"id": 1, "title": "a"}, {"id": 2, "title": "b"}]))
+            .unwrap();
+        assert_eq!(insert.data, json!([{"title": "a"}, {"title": "b"}]));
+
+        let update = db
+            .update("posts")
+            .set(json!({"title": "c", "created_at": "now"}))
+            .unwrap();
+        assert_eq!(update.data, json!({"title": "c"}));
+    }
+
+    #[test]
+    fn test_validate_select() {
+        use crate::types::SupabaseConfig;
+        use reqwest::Client as HttpClient;
+        use std::sync::Arc;
+
+        struct Post;
+        impl Table for Post {
+            const NAME: &'static str = "posts";
+            const COLUMNS: &'static [&'static str] = &["id", "title", "author_id", "meta"];
+        }
+        struct Untyped;
+        impl Table for Untyped {
+            const NAME: &'static str = "untyped";
+        }
+
+        assert!(Post::validate_select("*").is_ok());
+        assert!(Post::validate_select("id, title").is_ok());
+        assert!(Post::validate_select(
+            "id,name:title,author_id::text,meta->tags,author:authors(*)"
+        )
+        .is_ok());
+        let error = Post::validate_select("id,title,missing").unwrap_err();
+        assert!(error.to_string().contains("`missing`"));
+        assert!(Untyped::validate_select("anything").is_ok());
+
+        let config = Arc::new(SupabaseConfig {
+            url: "http://localhost:54321".to_string(),
+            ..Default::default()
+        });
+        let db = Database::new(config, Arc::new(HttpClient::new())).unwrap();
+        assert!(db
+            .from_typed::<Post>()
+            .select("id,title")
+            .build_url()
+            .is_ok());
+        assert!(db.from("posts").select("id,missing").build_url().is_ok());
+        #[cfg(debug_assertions)]
+        assert!(db
+            .from_typed::<Post>()
+            .select("id,missing")
+            .build_url()
+            .is_err());
+    }
+
+    #[test]
+    fn test_with_related() {
+        use crate::types::SupabaseConfig;
+        use reqwest::Client as HttpClient;
+        use std::sync::Arc;
+
+        struct Author;
+        impl Table for Author {
+            const NAME: &'static str = "authors";
+        }
+        struct Comment;
+        impl Table for Comment {
+            const NAME: &'static str = "comments";
+        }
+
+        struct PostAuthor;
+        impl Relation for PostAuthor {
+            type Target = Author;
+            const FIELD: &'static str = "author";
+            const FOREIGN_KEY: Option<&'static str> = Some("author_id");
+        }
+        struct Comments;
+        impl Relation for Comments {
+            type Target = Comment;
+            const FIELD: &'static str = "comments";
+        }
+
+        let db = Database::new(
+            Arc::new(SupabaseConfig::default()),
+            Arc::new(HttpClient::new()),
+        )
+        .unwrap();
+        db.register_profile::<Comment>(Profile::default_select("id,body"));
+
+        let select = db
+            .from("posts")
+            .with_related::<PostAuthor>()
+            .with_related_inner::<Comments>()
+            .build_select_with_joins();
+        assert_eq!(
+            select,
+            "*,author:authors!author_id(*),comments:comments!inner(id,body)"
+        );
+    }
+
+    #[cfg(feature = "postgis")]
+    #[test]
+    fn test_geo_filters_call_table_functions() {
+        use crate::geo::{BoundingBox, Point};
+        use crate::types::SupabaseConfig;
+        use reqwest::Client as HttpClient;
+        use std::sync::Arc;
+
+        let config = Arc::new(SupabaseConfig {
+            url: "http://localhost:54321".to_string(),
+            ..Default::default()
+        });
+        let db = Database::new(config, Arc::new(HttpClient::new())).unwrap();
+
+        let url = db
+            .from("places")
+            .select("id")
+            .st_dwithin("location", Point::new(13.405, 52.52), 500.0)
+            .eq("category", "cafe")
+            .build_url()
+            .unwrap();
+        let params: HashMap<_, _> = url.query_pairs().into_owned().collect();
+
+        assert_eq!(url.path(), "/rest/v1/rpc/places_st_dwithin");
+        assert_eq!(
+            params.get("point").map(String::as_str),
+            Some("SRID=4326;POINT(13.405 52.52)")
+        );
+        assert_eq!(params.get("meters").map(String::as_str), Some("500"));
+        assert_eq!(
+            params.get("column_name").map(String::as_str),
+            Some("location")
+        );
+        assert_eq!(params.get("category").map(String::as_str), Some("eq.cafe"));
+
+        let url = db
+            .from("regions")
+            .bbox_overlaps("shape", BoundingBox::new(0.0, 0.0, 1.0, 1.0))
+            .build_url()
+            .unwrap();
+        let params: HashMap<_, _> = url.query_pairs().into_owned().collect();
+
+        assert_eq!(url.path(), "/rest/v1/rpc/regions_bbox_overlaps");
+        assert_eq!(
+            params.get("bbox").map(String::as_str),
+            Some("SRID=4326;POLYGON((0 0,1 0,1 1,0 1,0 0))")
+        );
+    }
+
+    #[test]
+    fn test_order_nulls_and_many() {
+        use crate::types::{OrderDirection::*, SupabaseConfig};
+        use reqwest::Client as HttpClient;
+        use std::sync::Arc;
+
+        let config = Arc::new(SupabaseConfig {
+            url: "http://localhost:54321".to_string(),
+            ..Default::default()
+        });
+        let db = Database::new(config, Arc::new(HttpClient::new())).unwrap();
+        let order = |query: QueryBuilder| {
+            let url = query.build_url().unwrap();
+            let params: HashMap<_, _> = url.query_pairs().into_owned().collect();
+            params.get("order").cloned()
+        };
+
+        assert_eq!(
+            order(
+                db.from("posts")
+                    .order("created_at", Descending)
+                    .nulls_last()
+                    .order("id", Descending)
+            )
+            .as_deref(),
+            Some("created_at.desc.nullslast,id.desc")
+        );
+        assert_eq!(
+            order(
+                db.from("posts")
+                    .order_many(&[("score", Ascending), ("id", Descending)])
+                    .nulls_first()
+            )
+            .as_deref(),
+            Some("score.asc,id.desc.nullsfirst")
+        );
+        assert_eq!(order(db.from("posts").nulls_first()), None);
+    }
+
+    #[test]
+    fn test_approximate_count_result() {
+        assert_eq!(
+            parse_approximate_count("events", json!(123_456_789)).unwrap(),
+            123_456_789
+        );
+        assert!(matches!(
+            parse_approximate_count("missing", JsonValue::Null),
+            Err(Error::NotFound { .. })
+        ));
+        assert!(parse_approximate_count("events", json!("many")).is_err());
+
+        let missing = missing_count_helper(Error::database(
+            r#"{"code":"PGRST202","details":null,"hint":null,"message":"Could not find the function public.approximate_row_count(schema_name, table_name) in the schema cache"}"#,
+        ));
+        assert!(missing.to_string().contains("APPROXIMATE_COUNT_SQL"));
+
+        let other = missing_count_helper(Error::database("RPC failed with status: 500"));
+        assert_eq!(
+            other.to_string(),
+            "Database error: RPC failed with status: 500"
+        );
+
+        assert!(APPROXIMATE_COUNT_SQL
+            .contains(&format!("function public.{}(", APPROXIMATE_COUNT_FUNCTION)));
+        assert!(APPROXIMATE_COUNT_SQL.contains(&format!(
+            "revoke execute on function public.{}(text, text) from public, anon;",
+            APPROXIMATE_COUNT_FUNCTION
+        )));
+    }
+
+    #[test]
+    fn test_generate_id() {
+        use crate::types::SupabaseConfig;
+        use reqwest::Client as HttpClient;
+        use std::sync::Arc;
+
+        let v4 = uuid::Uuid::parse_str(&IdStrategy::UuidV4.generate()).unwrap();
+        assert_eq!(v4.get_version_num(), 4);
+        let v7 = uuid::Uuid::parse_str(&IdStrategy::UuidV7.generate()).unwrap();
+        assert_eq!(v7.get_version_num(), 7);
+
+        let ulid = IdStrategy::Ulid.generate();
+        assert_eq!(ulid.len(), 26);
+        assert!(ulid
+            .chars()
+            .all(|c| "0123456789ABCDEFGHJKMNPQRSTVWXYZ".contains(c)));
+        assert!(ulid.as_str() <= "7ZZZZZZZZZZZZZZZZZZZZZZZZZ");
+        assert_ne!(IdStrategy::Ulid.generate(), ulid);
+
+        let config = Arc::new(SupabaseConfig {
+            url: "http://localhost:54321".to_string(),
+            ..Default::default()
+        });
+        let db = Database::new(config, Arc::new(HttpClient::new())).unwrap();
+        let insert = db
+            .insert("notes")
+            .values(json!([{"id": "kept", "body": "a"}, {"id": null}, {"body": "c"}]))
+            .unwrap()
+            .generate_id(IdStrategy::UuidV4);
+
+        let mut payload = insert.data.clone();
+        insert.fill_generated_ids(&mut payload);
+        assert_eq!(payload[0]["id"], "kept");
+        assert!(uuid::Uuid::parse_str(payload[1]["id"].as_str().unwrap()).is_ok());
+        assert!(uuid::Uuid::parse_str(payload[2]["id"].as_str().unwrap()).is_ok());
+
+        let conflict = Error::database(
+            r#"{"code":"23505","details":"Key (id)=(kept) already exists.","message":"duplicate key value"}"#,
+        );
+        assert!(matches!(
+            insert.id_conflict_error(conflict),
+            Error::AlreadyExists { .. }
+        ));
+        let slug_conflict = Error::database(
+            r#"{"code":"23505","details":"Key (slug)=(a) already exists.","message":"duplicate key value"}"#,
+        );
+        assert!(matches!(
+            insert.id_conflict_error(slug_conflict),
+            Error::Database { .. }
+        ));
+    }
+
+    #[test]
+    fn test_dry_run_url() {
+        use crate::types::SupabaseConfig;
+        use reqwest::Client as HttpClient;
+        use std::sync::Arc;
+
+        let config = Arc::new(SupabaseConfig {
+            url: "http://localhost:54321".to_string(),
+            ..Default::default()
+        });
+        let db = Database::new(config, Arc::new(HttpClient::new())).unwrap();
+
+        let delete = db.delete("users").eq("status", "inactive").returning("id");
+        let url = db
+            .dry_run_url(&delete.table, &delete.filters, delete.returning.as_deref())
+            .unwrap();
+        let params: HashMap<_, _> = url.query_pairs().into_owned().collect();
+
+        assert_eq!(url.path(), "/rest/v1/users");
+        assert_eq!(
+            params.get("status").map(String::as_str),
+            Some("eq.inactive")
+        );
+        assert_eq!(params.get("select").map(String::as_str), Some("id"));
+        assert_eq!(params.get("limit").map(String::as_str), Some("10"));
+    }
+
+    #[test]
+    fn test_raw_request_url() {
+        use crate::types::SupabaseConfig;
+        use reqwest::Client as HttpClient;
+        use std::sync::Arc;
+
+        let config = Arc::new(SupabaseConfig {
+            url: "http://localhost:54321".to_string(),
+            ..Default::default()
+        });
+        let db = Database::new(config, Arc::new(HttpClient::new())).unwrap();
+
+        let url = db
+            .raw_request_url("/articles", &[("select", "id,title"), ("id", "eq.1")])
+            .unwrap();
+        assert_eq!(
+            url.as_str(),
+            "http://localhost:54321/rest/v1/articles?select=id%2Ctitle&id=eq.1"
+        );
+
+        assert!(db.raw_request_url("../auth/v1/admin/users", &[]).is_err());
+        assert!(db.raw_request_url("http://evil.example/x", &[]).is_err());
+    }
+
     #[test]
     fn test_transaction_builder() {
         use crate::types::SupabaseConfig;
@@ -0,0 +1,40 @@
+//! Gzip compression of request bodies
+//!
+//! Enabled with
+//! [`HttpConfig::request_compression_threshold`](crate::types::HttpConfig::request_compression_threshold).
+//! Responses need nothing here: the HTTP client negotiates and decompresses
+//! them on its own.
+
+use flate2::{write::GzEncoder, Compression};
+use std::io::Write;
+
+/// Gzip `bytes` at the default compression level
+pub(super) fn gzip(bytes: &[u8]) -> std::io::Result<Vec<u8>> {
+    let mut encoder = GzEncoder::new(Vec::with_capacity(bytes.len() / 4), Compression::default());
+    encoder.write_all(bytes)?;
+    encoder.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::read::GzDecoder;
+    use std::io::Read;
+
+    #[test]
+    fn test_gzip_round_trip() {
+        let rows: Vec<_> = (0..1000)
+            .map(|id| serde_json::json!({ "id": id, "status": "active", "name": format!("user {}", id) }))
+            .collect();
+        let json = serde_json::to_vec(&rows).unwrap();
+
+        let compressed = gzip(&json).unwrap();
+        assert!(compressed.len() * 5 < json.len());
+
+        let mut decompressed = Vec::new();
+        GzDecoder::new(compressed.as_slice())
+            .read_to_end(&mut decompressed)
+            .unwrap();
+        assert_eq!(decompressed, json);
+    }
+}
@@ -0,0 +1,260 @@
+//! Opaque tokens for keyset pagination
+//!
+//! A [`PageToken`] holds the ordering keys of the last row of a page. It
+//! encodes to a URL-safe string that services built on this crate can hand
+//! out as `next_page_token`, and decodes back to the filter selecting the
+//! rows after it. Tokens come back from clients, so decoding checks them
+//! against the columns the query orders by.
+
+use super::{Filter, SafeFilterParser};
+use crate::{
+    error::{Error, Result},
+    types::{FilterOperator, JsonValue},
+};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+use serde::Serialize;
+
+/// Position after a row in a query ordered by one or more key columns
+///
+/// Order the query ascending by the same columns, in the same order, with
+/// the last one unique (e.g. `created_at, id`); [`filter`](Self::filter)
+/// then selects exactly the rows that follow the token.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use serde_json::Value;
+/// use supabase_lib_rs::{database::PageToken, types::OrderDirection::Ascending};
+/// # use supabase_lib_rs::Client;
+/// # async fn example(next_page_token: Option<&str>) -> Result<(), Box<dyn std::error::Error>> {
+/// # let client = Client::new("http://localhost:54321", "test-key")?;
+///
+/// let mut query = client
+///     .database()
+///     .from("posts")
+///     .select("*")
+///     .order_many(&[("created_at", Ascending), ("id", Ascending)])
+///     .limit(50);
+/// if let Some(token) = next_page_token {
+///     query = query.with_filter(PageToken::decode(token, &["created_at", "id"])?.filter());
+/// }
+///
+/// let posts: Vec<Value> = query.execute().await?;
+/// let next = match posts.last() {
+///     Some(last) if posts.len() == 50 => {
+///         Some(PageToken::from_row(last, &["created_at", "id"])?.encode())
+///     }
+///     _ => None,
+/// };
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct PageToken {
+    keys: Vec<(String, JsonValue)>,
+}
+
+impl PageToken {
+    /// Token for the given key columns and their values, in ordering priority
+    pub fn new(keys: Vec<(String, JsonValue)>) -> Result<Self> {
+        if keys.is_empty() {
+            return Err(Error::invalid_input("A page token needs at least one key"));
+        }
+        for (column, value) in &keys {
+            if !matches!(
+                value,
+                JsonValue::String(_) | JsonValue::Number(_) | JsonValue::Bool(_)
+            ) {
+                return Err(Error::invalid_input(format!(
+                    "Page token key '{}' must be a string, number or boolean, found {}",
+                    column, value
+                )));
+            }
+        }
+        Ok(Self { keys })
+    }
+
+    /// Token positioned after `row`, taking the values of `columns`
+    pub fn from_row<T: Serialize>(row: &T, columns: &[&str]) -> Result<Self> {
+        let row = serde_json::to_value(row)?;
+        let keys = columns
+            .iter()
+            .map(|column| match row.get(column) {
+                Some(value) => Ok((column.to_string(), value.clone())),
+                None => Err(Error::invalid_input(format!(
+                    "Row has no column '{}' for the page token",
+                    column
+                ))),
+            })
+            .collect::<Result<_>>()?;
+        Self::new(keys)
+    }
+
+    /// Key columns and the values of the last row
+    pub fn keys(&self) -> &[(String, JsonValue)] {
+        &self.keys
+    }
+
+    /// URL-safe string form; equal tokens always encode to the same string
+    pub fn encode(&self) -> String {
+        let pairs: Vec<(&str, &JsonValue)> = self
+            .keys
+            .iter()
+            .map(|(column, value)| (column.as_str(), value))
+            .collect();
+        let json = serde_json::to_vec(&pairs).unwrap_or_default();
+        URL_SAFE_NO_PAD.encode(json)
+    }
+
+    /// Parse a token produced by [`encode`](Self::encode) for a query ordered by `columns`
+    ///
+    /// Fails unless the token holds exactly these key columns, in this order,
+    /// so a client cannot filter on other columns by crafting a token.
+    pub fn decode(token: &str, columns: &[&str]) -> Result<Self> {
+        let invalid = || Error::invalid_input("Invalid page token");
+        let json = URL_SAFE_NO_PAD.decode(token).map_err(|_| invalid())?;
+        let keys: Vec<(String, JsonValue)> =
+            serde_json::from_slice(&json).map_err(|_| invalid())?;
+        if !keys
+            .iter()
+            .map(|(column, _)| column.as_str())
+            .eq(columns.iter().copied())
+        {
+            return Err(invalid());
+        }
+        Self::new(keys)
+    }
+
+    /// Filter for the rows after this token
+    ///
+    /// Compares the keys as a tuple: `a > x`, or `a = x and b > y`, and so on.
+    pub fn filter(&self) -> Filter {
+        let in_tree = self.keys.len() > 1;
+        let condition = |(column, value): &(String, JsonValue), operator| {
+            let value = match value {
+                JsonValue::String(text) => text.clone(),
+                other => other.to_string(),
+            };
+            Filter::Simple {
+                column: column.clone(),
+                operator,
+                value: if in_tree {
+                    SafeFilterParser::quote(&value)
+                } else {
+                    value
+                },
+            }
+        };
+
+        let mut branches: Vec<Filter> = (0..self.keys.len())
+            .map(|position| {
+                let mut conditions: Vec<Filter> = self.keys[..position]
+                    .iter()
+                    .map(|key| condition(key, FilterOperator::Equal))
+                    .collect();
+                conditions.push(condition(&self.keys[position], FilterOperator::GreaterThan));
+                match conditions.len() {
+                    1 => conditions.remove(0),
+                    _ => Filter::And(conditions),
+                }
+            })
+            .collect();
+        match branches.len() {
+            1 => branches.remove(0),
+            _ => Filter::Or(branches),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_page_token_round_trip_and_filter() {
+        let row = json!({ "created_at": "2024-01-01T00:00:00Z", "id": 7, "title": "Hi" });
+        let token = PageToken::from_row(&row, &["created_at", "id"]).unwrap();
+        let encoded = token.encode();
+        assert_eq!(encoded, token.clone().encode());
+        assert!(encoded
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'));
+        assert_eq!(
+            PageToken::decode(&encoded, &["created_at", "id"]).unwrap(),
+            token
+        );
+        // Tokens for other columns or another ordering are rejected
+        assert!(PageToken::decode(&encoded, &["id", "created_at"]).is_err());
+        assert!(PageToken::decode(&encoded, &["created_at"]).is_err());
+        let forged = PageToken::new(vec![("password_hash".to_string(), json!("a"))])
+            .unwrap()
+            .encode();
+        assert!(PageToken::decode(&forged, &["id"]).is_err());
+
+        let Filter::Or(branches) = token.filter() else {
+            panic!("expected an or filter");
+        };
+        assert!(matches!(
+            &branches[0],
+            Filter::Simple { column, operator: FilterOperator::GreaterThan, value }
+                if column == "created_at" && value == "\"2024-01-01T00:00:00Z\""
+        ));
+        assert!(matches!(
+            &branches[1],
+            Filter::And(conditions) if matches!(
+                &conditions[1],
+                Filter::Simple { column, operator: FilterOperator::GreaterThan, value }
+                    if column == "id" && value == "7"
+            )
+        ));
+
+        let single = PageToken::new(vec![("id".to_string(), json!(7))]).unwrap();
+        assert!(matches!(
+            single.filter(),
+            Filter::Simple { operator: FilterOperator::GreaterThan, value, .. } if value == "7"
+        ));
+
+        assert!(PageToken::decode("not a token", &["id"]).is_err());
+        assert!(PageToken::from_row(&row, &["missing"]).is_err());
+        assert!(PageToken::new(vec![("id".to_string(), JsonValue::Null)]).is_err());
+    }
+
+    #[test]
+    fn test_page_token_filter_keeps_user_filters() {
+        use crate::{database::Database, types::SupabaseConfig};
+        use std::sync::Arc;
+
+        let db = Database::new(
+            Arc::new(SupabaseConfig {
+                url: "http://localhost:54321".to_string(),
+                ..Default::default()
+            }),
+            Arc::new(reqwest::Client::new()),
+        )
+        .unwrap();
+        let token = PageToken::new(vec![
+            ("created_at".to_string(), json!("2024-01-01")),
+            ("id".to_string(), json!(7)),
+        ])
+        .unwrap();
+        let url = db
+            .from("posts")
+            .or(|q| q.eq("status", "draft").eq("status", "review"))
+            .with_filter(token.filter())
+            .build_url()
+            .unwrap();
+        let groups: Vec<String> = url
+            .query_pairs()
+            .filter(|(key, _)| key == "or")
+            .map(|(_, value)| value.into_owned())
+            .collect();
+        assert_eq!(
+            groups,
+            vec![
+                "(status.eq.draft,status.eq.review)",
+                "(created_at.gt.2024-01-01,and(created_at.eq.2024-01-01,id.gt.7))",
+            ]
+        );
+    }
+}
@@ -0,0 +1,272 @@
+//! Strict deserialization for [`DatabaseConfig::strict_models`](crate::types::DatabaseConfig::strict_models)
+//!
+//! Deserializes a JSON value like `serde_json::from_value`, but every struct
+//! must match its JSON object exactly: keys the struct does not declare and
+//! declared fields the object lacks are both errors, even where serde would
+//! ignore the key or default the field (`Option`, `#[serde(default)]`).
+//! Externally tagged enums must name one of their declared variants, so a
+//! `#[serde(other)]` catch-all does not swallow new values. Errors name the
+//! offending field or variant and its path, e.g. `$[3].author`.
+
+use serde::de::{
+    self, value::BorrowedStrDeserializer, DeserializeSeed, EnumAccess, MapAccess, SeqAccess,
+    VariantAccess, Visitor,
+};
+use serde::{forward_to_deserialize_any, Deserialize};
+use serde_json::{Error, Value};
+
+/// Deserialize `value` into `T`, rejecting any drift between the two
+pub(crate) fn from_value<'de, T: Deserialize<'de>>(value: &'de Value) -> Result<T, Error> {
+    T::deserialize(Strict {
+        value,
+        path: "$".to_string(),
+    })
+}
+
+struct Strict<'de> {
+    value: &'de Value,
+    path: String,
+}
+
+impl<'de> de::Deserializer<'de> for Strict<'de> {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self.value {
+            Value::Null => visitor.visit_unit(),
+            Value::Bool(b) => visitor.visit_bool(*b),
+            Value::Number(n) => {
+                if let Some(u) = n.as_u64() {
+                    visitor.visit_u64(u)
+                } else if let Some(i) = n.as_i64() {
+                    visitor.visit_i64(i)
+                } else {
+                    visitor.visit_f64(n.as_f64().unwrap_or(f64::NAN))
+                }
+            }
+            Value::String(s) => visitor.visit_borrowed_str(s),
+            Value::Array(items) => visitor.visit_seq(StrictSeq {
+                items: items.iter().enumerate(),
+                path: self.path,
+            }),
+            Value::Object(map) => visitor.visit_map(StrictMap {
+                entries: map.iter(),
+                value: None,
+                path: self.path,
+            }),
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self.value {
+            Value::Null => visitor.visit_none(),
+            _ => visitor.visit_some(self),
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        let tagged = match self.value {
+            Value::String(variant) => Some((variant.as_str(), None)),
+            Value::Object(map) if map.len() == 1 => map
+                .iter()
+                .next()
+                .map(|(variant, value)| (variant.as_str(), Some(value))),
+            _ => None,
+        };
+        let Some((variant, value)) = tagged else {
+            return Err(de::Error::custom(format!(
+                "expected a variant of {} at {}",
+                name, self.path
+            )));
+        };
+
+        if !variants.contains(&variant) {
+            return Err(de::Error::custom(format!(
+                "unknown variant `{}` at {} for {}, expected one of {}",
+                variant,
+                self.path,
+                name,
+                variants.join(", ")
+            )));
+        }
+
+        visitor.visit_enum(StrictEnum {
+            name,
+            variant,
+            value,
+            path: self.path,
+        })
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        if let Value::Object(map) = self.value {
+            if let Some(key) = map.keys().find(|key| !fields.contains(&key.as_str())) {
+                return Err(de::Error::custom(format!(
+                    "unknown field `{}` at {} for {}",
+                    key, self.path, name
+                )));
+            }
+            if let Some(field) = fields.iter().find(|field| !map.contains_key(**field)) {
+                return Err(de::Error::custom(format!(
+                    "missing field `{}` at {} for {}",
+                    field, self.path, name
+                )));
+            }
+        }
+        self.deserialize_any(visitor)
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple tuple_struct map identifier ignored_any
+    }
+}
+
+struct StrictSeq<'de> {
+    items: std::iter::Enumerate<std::slice::Iter<'de, Value>>,
+    path: String,
+}
+
+impl<'de> SeqAccess<'de> for StrictSeq<'de> {
+    type Error = Error;
+
+    fn next_element_seed<S: DeserializeSeed<'de>>(
+        &mut self,
+        seed: S,
+    ) -> Result<Option<S::Value>, Error> {
+        match self.items.next() {
+            Some((index, value)) => seed
+                .deserialize(Strict {
+                    value,
+                    path: format!("{}[{}]", self.path, index),
+                })
+                .map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.items.len())
+    }
+}
+
+struct StrictMap<'de> {
+    entries: serde_json::map::Iter<'de>,
+    value: Option<(&'de str, &'de Value)>,
+    path: String,
+}
+
+impl<'de> MapAccess<'de> for StrictMap<'de> {
+    type Error = Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, Error> {
+        match self.entries.next() {
+            Some((key, value)) => {
+                self.value = Some((key, value));
+                seed.deserialize(BorrowedStrDeserializer::new(key))
+                    .map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, Error> {
+        let (key, value) = self
+            .value
+            .take()
+            .ok_or_else(|| <Error as de::Error>::custom("value requested before key"))?;
+        seed.deserialize(Strict {
+            value,
+            path: format!("{}.{}", self.path, key),
+        })
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.entries.len())
+    }
+}
+
+struct StrictEnum<'de> {
+    name: &'static str,
+    variant: &'de str,
+    value: Option<&'de Value>,
+    path: String,
+}
+
+impl<'de> StrictEnum<'de> {
+    /// The variant's content, which all but unit variants require
+    fn content(self) -> Result<Strict<'de>, Error> {
+        match self.value {
+            Some(value) => Ok(Strict {
+                value,
+                path: format!("{}.{}", self.path, self.variant),
+            }),
+            None => Err(de::Error::custom(format!(
+                "missing content of {}::{} at {}",
+                self.name, self.variant, self.path
+            ))),
+        }
+    }
+}
+
+impl<'de> EnumAccess<'de> for StrictEnum<'de> {
+    type Error = Error;
+    type Variant = Self;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(self, seed: V) -> Result<(V::Value, Self), Error> {
+        let variant = seed.deserialize(BorrowedStrDeserializer::new(self.variant))?;
+        Ok((variant, self))
+    }
+}
+
+impl<'de> VariantAccess<'de> for StrictEnum<'de> {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<(), Error> {
+        match self.value {
+            None | Some(Value::Null) => Ok(()),
+            Some(_) => Err(de::Error::custom(format!(
+                "unexpected content of {}::{} at {}",
+                self.name, self.variant, self.path
+            ))),
+        }
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value, Error> {
+        seed.deserialize(self.content()?)
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value, Error> {
+        de::Deserializer::deserialize_seq(self.content()?, visitor)
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        let name = self.name;
+        de::Deserializer::deserialize_struct(self.content()?, name, fields, visitor)
+    }
+}
@@ -54,6 +54,38 @@ pub struct RetryInfo {
     pub retry_after: Option<u64>,
 }
 
+/// Rate limit state reported by the API gateway
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RateLimitInfo {
+    /// `x-ratelimit-limit`
+    pub limit: Option<u64>,
+    /// `x-ratelimit-remaining`
+    pub remaining: Option<u64>,
+    /// `x-ratelimit-reset`
+    pub reset: Option<u64>,
+    /// `retry-after`, in seconds
+    pub retry_after: Option<u64>,
+}
+
+impl RateLimitInfo {
+    /// Read the rate limit headers of a response, if it sent any
+    pub fn from_headers(headers: &reqwest::header::HeaderMap) -> Option<Self> {
+        Self::parse(|name| headers.get(name).and_then(|value| value.to_str().ok()))
+    }
+
+    /// Read the rate limit headers through `header`, which looks up lowercase names
+    pub(crate) fn parse<'a>(header: impl Fn(&str) -> Option<&'a str>) -> Option<Self> {
+        let number = |name: &str| header(name).and_then(|value| value.trim().parse().ok());
+        let info = Self {
+            limit: number("x-ratelimit-limit"),
+            remaining: number("x-ratelimit-remaining"),
+            reset: number("x-ratelimit-reset"),
+            retry_after: number("retry-after"),
+        };
+        (info != Self::default()).then_some(info)
+    }
+}
+
 /// Enhanced error context
 #[derive(Debug, Clone)]
 pub struct ErrorContext {
@@ -63,6 +95,8 @@ pub struct ErrorContext {
     pub http: Option<HttpErrorContext>,
     /// Retry information
     pub retry: Option<RetryInfo>,
+    /// Rate limit headers of the failed response
+    pub rate_limit: Option<RateLimitInfo>,
     /// Additional metadata
     pub metadata: HashMap<String, String>,
     /// Error timestamp
@@ -75,12 +109,347 @@ impl Default for ErrorContext {
             platform: None,
             http: None,
             retry: None,
+            rate_limit: None,
             metadata: HashMap::new(),
             timestamp: chrono::Utc::now(),
         }
     }
 }
 
+impl ErrorContext {
+    /// Add the environment fingerprint to the metadata
+    ///
+    /// Records the crate version, enabled features, async runtime, target and,
+    /// if given, the kind of endpoint that failed. Keys already present are
+    /// kept.
+    pub fn with_environment(mut self, endpoint: Option<&str>) -> Self {
+        let runtime = if cfg!(target_arch = "wasm32") {
+            "wasm"
+        } else {
+            "tokio"
+        };
+        let mut entries = vec![
+            ("crate_version", env!("CARGO_PKG_VERSION").to_string()),
+            ("features", enabled_features().join(",")),
+            ("runtime", runtime.to_string()),
+            (
+                "target",
+                format!("{}-{}", std::env::consts::ARCH, std::env::consts::OS),
+            ),
+        ];
+        if let Some(endpoint) = endpoint {
+            entries.push(("endpoint", endpoint.to_string()));
+        }
+
+        for (key, value) in entries {
+            self.metadata.entry(key.to_string()).or_insert(value);
+        }
+        self
+    }
+}
+
+/// Crate features compiled into this build
+fn enabled_features() -> Vec<&'static str> {
+    [
+        ("auth", cfg!(feature = "auth-core")),
+        ("auth-mfa", cfg!(feature = "auth-mfa")),
+        ("database", cfg!(feature = "database")),
+        ("graphql", cfg!(feature = "graphql")),
+        ("storage", cfg!(feature = "storage-core")),
+        ("storage-resumable", cfg!(feature = "storage-resumable")),
+        ("functions", cfg!(feature = "functions")),
+        ("realtime", cfg!(feature = "realtime")),
+        ("native", cfg!(feature = "native")),
+        ("wasm", cfg!(feature = "wasm")),
+        ("session-management", cfg!(feature = "session-management")),
+        ("session-encryption", cfg!(feature = "session-encryption")),
+        ("ffi", cfg!(feature = "ffi")),
+    ]
+    .into_iter()
+    .filter(|(_, enabled)| *enabled)
+    .map(|(name, _)| name)
+    .collect()
+}
+
+/// Details of a content checksum mismatch
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IntegrityError {
+    /// Checksum algorithm used for the comparison (e.g. "md5", "sha256")
+    pub algorithm: String,
+    /// Expected checksum (lowercase hex)
+    pub expected: String,
+    /// Checksum computed from the received content (lowercase hex)
+    pub actual: String,
+    /// Object path, if known
+    pub path: Option<String>,
+}
+
+impl std::fmt::Display for IntegrityError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} checksum mismatch{}: expected {}, got {}",
+            self.algorithm,
+            self.path
+                .as_ref()
+                .map(|path| format!(" for {}", path))
+                .unwrap_or_default(),
+            self.expected,
+            self.actual
+        )
+    }
+}
+
+/// Settings rejected before a request was sent, with every problem found
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationError {
+    /// Descriptions of the problems, one per setting
+    pub problems: Vec<String>,
+}
+
+impl std::fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.problems.join("; "))
+    }
+}
+
+/// Class of a PostgreSQL error reported by PostgREST
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PostgresErrorKind {
+    /// `23505`: a unique constraint was violated
+    UniqueViolation,
+    /// `23503`: a foreign key constraint was violated
+    ForeignKeyViolation,
+    /// `40001`: a concurrent transaction conflicted; safe to retry
+    SerializationFailure,
+    /// `57014`: the statement was canceled, usually by `statement_timeout`
+    QueryCanceled,
+    /// `P0001`: raised by `RAISE EXCEPTION` in a function or trigger
+    RaiseException,
+    /// Any other SQLSTATE code
+    Other(String),
+}
+
+impl PostgresErrorKind {
+    /// Classify a SQLSTATE code
+    pub fn from_code(code: &str) -> Self {
+        match code {
+            "23505" => Self::UniqueViolation,
+            "23503" => Self::ForeignKeyViolation,
+            "40001" => Self::SerializationFailure,
+            "57014" => Self::QueryCanceled,
+            "P0001" => Self::RaiseException,
+            other => Self::Other(other.to_string()),
+        }
+    }
+}
+
+/// PostgreSQL error carried in a PostgREST error body
+///
+/// Constraint violations raised by deferred constraints at commit time and
+/// errors raised by triggers arrive in the same form as those of the
+/// statement itself.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize)]
+pub struct PostgresError {
+    /// SQLSTATE code, e.g. `23505`
+    pub code: String,
+    pub message: String,
+    #[serde(default)]
+    pub details: Option<String>,
+    #[serde(default)]
+    pub hint: Option<String>,
+}
+
+impl PostgresError {
+    /// Error class of the SQLSTATE code
+    pub fn kind(&self) -> PostgresErrorKind {
+        PostgresErrorKind::from_code(&self.code)
+    }
+}
+
+/// Storage API failure that upload UIs can explain to users
+///
+/// Parsed from Storage error bodies such as
+/// `{"statusCode":"413","error":"Payload too large","message":"..."}`. The
+/// API does not always state the limits; [`StorageError::with_bucket`]
+/// fills them in from the bucket settings.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StorageError {
+    /// The object is larger than the bucket or project allows
+    PayloadTooLarge {
+        /// Largest accepted size in bytes, if known
+        limit: Option<u64>,
+    },
+    /// The bucket does not accept the object's content type
+    InvalidMimeType {
+        /// Rejected content type, if reported
+        mime_type: Option<String>,
+        /// Content types the bucket accepts, if known
+        allowed: Vec<String>,
+    },
+    /// The bucket does not exist or is not visible to the caller
+    BucketNotFound,
+    /// The object does not exist or is not visible to the caller
+    ObjectNotFound,
+    /// The project's storage quota is used up
+    QuotaExceeded,
+}
+
+impl StorageError {
+    /// Classify a Storage API error response
+    pub fn from_response(status: Option<u16>, body: &str) -> Option<Self> {
+        let json: serde_json::Value = serde_json::from_str(body).ok()?;
+        let field = |name: &str| match &json[name] {
+            serde_json::Value::String(value) => value.clone(),
+            serde_json::Value::Number(value) => value.to_string(),
+            _ => String::new(),
+        };
+        let (code, error, message) = (field("code"), field("error"), field("message"));
+        let status_code = field("statusCode")
+            .parse::<u16>()
+            .ok()
+            .or(status)
+            .unwrap_or_default();
+        let text = format!("{} {}", error, message).to_ascii_lowercase();
+
+        if code == "NoSuchBucket" || text.contains("bucket not found") {
+            Some(Self::BucketNotFound)
+        } else if code == "NoSuchKey" || text.contains("object not found") {
+            Some(Self::ObjectNotFound)
+        } else if code == "EntityTooLarge"
+            || status_code == 413
+            || text.contains("payload too large")
+        {
+            Some(Self::PayloadTooLarge {
+                limit: parse_size(&message),
+            })
+        } else if code == "InvalidMimeType" || text.contains("mime type") {
+            let mime_type = message
+                .to_ascii_lowercase()
+                .split("mime type ")
+                .nth(1)
+                .and_then(|rest| rest.split_whitespace().next())
+                .map(str::to_string);
+            Some(Self::InvalidMimeType {
+                mime_type,
+                allowed: Vec::new(),
+            })
+        } else if code == "QuotaExceeded" || status_code == 402 || text.contains("quota") {
+            Some(Self::QuotaExceeded)
+        } else {
+            None
+        }
+    }
+}
+
+/// First size in a message, in bytes
+///
+/// Reads sizes such as `1048576`, `50MB`, `1.5 GB` or `10 MiB`; like the
+/// Storage API, KB, MB and GB are decimal units.
+fn parse_size(message: &str) -> Option<u64> {
+    let start = message.find(|ch: char| ch.is_ascii_digit())?;
+    let rest = &message[start..];
+    let number_end = rest
+        .find(|ch: char| !ch.is_ascii_digit() && ch != '.')
+        .unwrap_or(rest.len());
+    let number: f64 = rest[..number_end].trim_end_matches('.').parse().ok()?;
+    let unit: String = rest[number_end..]
+        .trim_start()
+        .chars()
+        .take_while(char::is_ascii_alphabetic)
+        .collect();
+    let multiplier: u64 = match unit.to_ascii_lowercase().as_str() {
+        "kb" => 1_000,
+        "mb" => 1_000_000,
+        "gb" => 1_000_000_000,
+        "tb" => 1_000_000_000_000,
+        "kib" => 1 << 10,
+        "mib" => 1 << 20,
+        "gib" => 1 << 30,
+        "tib" => 1 << 40,
+        _ => 1,
+    };
+    Some((number * multiplier as f64).round() as u64)
+}
+
+impl std::fmt::Display for StorageError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::PayloadTooLarge { limit: Some(limit) } => {
+                write!(f, "The file is larger than the limit of {} bytes", limit)
+            }
+            Self::PayloadTooLarge { limit: None } => write!(f, "The file is too large"),
+            Self::InvalidMimeType { mime_type, allowed } => {
+                match mime_type {
+                    Some(mime_type) => write!(f, "Files of type {} are not accepted", mime_type)?,
+                    None => write!(f, "This file type is not accepted")?,
+                }
+                if !allowed.is_empty() {
+                    write!(f, "; allowed types: {}", allowed.join(", "))?;
+                }
+                Ok(())
+            }
+            Self::BucketNotFound => write!(f, "The bucket does not exist"),
+            Self::ObjectNotFound => write!(f, "The file does not exist"),
+            Self::QuotaExceeded => write!(f, "The storage quota is exceeded"),
+        }
+    }
+}
+
+/// Error body returned by an Edge Function with a non-2xx status
+///
+/// Understands the common shapes `{"error": "..."}`,
+/// `{"error": {"code": "...", "message": "..."}}` and
+/// `{"code": "...", "message": "...", "details": ...}`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FunctionError {
+    /// HTTP status of the response
+    pub status: u16,
+    /// Machine-readable error code, if the function sent one
+    pub code: Option<String>,
+    pub message: String,
+    /// Any further data the function attached
+    pub details: Option<serde_json::Value>,
+}
+
+impl FunctionError {
+    /// Parse a function's error response, if the body is a JSON error object
+    pub fn from_response(status: u16, body: &str) -> Option<Self> {
+        let json: serde_json::Value = serde_json::from_str(body).ok()?;
+        let inner = match json.get("error") {
+            Some(error) if error.is_object() => error,
+            _ => &json,
+        };
+        let text = |value: Option<&serde_json::Value>| match value? {
+            serde_json::Value::String(text) => Some(text.clone()),
+            serde_json::Value::Number(number) => Some(number.to_string()),
+            _ => None,
+        };
+
+        let message = text(inner.get("message"))
+            .or_else(|| text(json.get("error")))
+            .or_else(|| text(inner.get("msg")))?;
+        Some(Self {
+            status,
+            code: text(inner.get("code")),
+            message,
+            details: inner
+                .get("details")
+                .filter(|details| !details.is_null())
+                .cloned(),
+        })
+    }
+}
+
+impl std::fmt::Display for FunctionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.code {
+            Some(code) => write!(f, "{} ({})", self.message, code),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
 /// Main error type for Supabase operations
 #[derive(Error, Debug)]
 pub enum Error {
@@ -102,7 +471,7 @@ pub enum Error {
     UrlParse(#[from] url::ParseError),
 
     /// JWT token errors
-    #[cfg(feature = "auth")]
+    #[cfg(feature = "auth-core")]
     #[error("JWT error: {0}")]
     Jwt(#[from] jsonwebtoken::errors::Error),
 
@@ -170,6 +539,13 @@ pub enum Error {
         context: ErrorContext,
     },
 
+    /// Resource already exists errors with enhanced context
+    #[error("Already exists: {message}")]
+    AlreadyExists {
+        message: String,
+        context: ErrorContext,
+    },
+
     /// Generic errors
     #[error("{message}")]
     Generic { message: String },
@@ -194,6 +570,22 @@ pub enum Error {
         message: String,
         context: ErrorContext,
     },
+
+    /// Content integrity errors (checksum mismatch)
+    #[error("Integrity error: {0}")]
+    Integrity(IntegrityError),
+
+    /// Invalid settings, caught before anything was sent
+    #[error("Validation failed: {0}")]
+    Validation(ValidationError),
+
+    /// A service's circuit breaker is open, so the request was not sent
+    #[error("Circuit open for {service}: failing fast after repeated failures")]
+    CircuitOpen {
+        service: String,
+        /// Time until a probe request is let through, if known
+        retry_after: Option<std::time::Duration>,
+    },
 }
 
 impl Error {
@@ -318,6 +710,14 @@ impl Error {
         }
     }
 
+    /// Create an already exists error with enhanced context
+    pub fn already_exists<S: Into<String>>(message: S) -> Self {
+        Self::AlreadyExists {
+            message: message.into(),
+            context: ErrorContext::default(),
+        }
+    }
+
     /// Create a configuration error
     pub fn config<S: Into<String>>(message: S) -> Self {
         Self::Config {
@@ -332,6 +732,27 @@ impl Error {
         }
     }
 
+    /// Create an integrity error for a checksum mismatch
+    pub fn integrity(error: IntegrityError) -> Self {
+        Self::Integrity(error)
+    }
+
+    /// Create a validation error listing `problems`
+    pub fn validation(problems: Vec<String>) -> Self {
+        Self::Validation(ValidationError { problems })
+    }
+
+    /// Create a circuit open error for `service`
+    pub fn circuit_open<S: Into<String>>(
+        service: S,
+        retry_after: Option<std::time::Duration>,
+    ) -> Self {
+        Self::CircuitOpen {
+            service: service.into(),
+            retry_after,
+        }
+    }
+
     /// Create a generic error
     pub fn generic<S: Into<String>>(message: S) -> Self {
         Self::Generic {
@@ -351,24 +772,152 @@ impl Error {
             Error::RateLimit { context, .. } => Some(context),
             Error::PermissionDenied { context, .. } => Some(context),
             Error::NotFound { context, .. } => Some(context),
+            Error::AlreadyExists { context, .. } => Some(context),
             Error::Functions { context, .. } => Some(context),
             _ => None,
         }
     }
 
+    /// Add the environment fingerprint when `enabled`
+    ///
+    /// Services pass [`SupabaseConfig::error_fingerprint`](crate::types::SupabaseConfig::error_fingerprint)
+    /// of the client that made the request, see [`ErrorContext::with_environment`].
+    #[cfg(any(
+        feature = "auth-core",
+        feature = "database",
+        feature = "storage-core",
+        feature = "functions"
+    ))]
+    pub(crate) fn with_fingerprint(mut self, enabled: bool) -> Self {
+        if !enabled {
+            return self;
+        }
+
+        let (context, endpoint) = match &mut self {
+            Error::Auth { context, .. } => (context, Some("auth")),
+            Error::Database { context, .. } => (context, Some("database")),
+            Error::Storage { context, .. } => (context, Some("storage")),
+            Error::Realtime { context, .. } => (context, Some("realtime")),
+            Error::Functions { context, .. } => (context, Some("functions")),
+            Error::Http { context, .. }
+            | Error::Network { context, .. }
+            | Error::RateLimit { context, .. }
+            | Error::PermissionDenied { context, .. }
+            | Error::NotFound { context, .. }
+            | Error::AlreadyExists { context, .. }
+            | Error::Platform { context, .. }
+            | Error::Crypto { context, .. } => (context, None),
+            _ => return self,
+        };
+        *context = std::mem::take(context).with_environment(endpoint);
+        self
+    }
+
     /// Check if error is retryable
+    ///
+    /// Serialization failures are always retryable: the transaction can
+    /// succeed once the conflicting one has finished. So are open circuits,
+    /// once the service has recovered.
     pub fn is_retryable(&self) -> bool {
-        self.context()
-            .and_then(|ctx| ctx.retry.as_ref())
-            .map(|retry| retry.retryable)
-            .unwrap_or(false)
+        matches!(self, Error::CircuitOpen { .. })
+            || self.is_serialization_failure()
+            || self
+                .context()
+                .and_then(|ctx| ctx.retry.as_ref())
+                .map(|retry| retry.retryable)
+                .unwrap_or(false)
+    }
+
+    /// PostgreSQL error of a failed database request, if the body carries one
+    pub fn postgres_error(&self) -> Option<PostgresError> {
+        let Error::Database { message, context } = self else {
+            return None;
+        };
+
+        let body = context
+            .http
+            .as_ref()
+            .and_then(|http| http.response_body.as_deref());
+        [Some(message.as_str()), body]
+            .into_iter()
+            .flatten()
+            .find_map(|text| serde_json::from_str::<PostgresError>(text).ok())
+    }
+
+    /// PostgreSQL error class of a failed database request
+    pub fn postgres_error_kind(&self) -> Option<PostgresErrorKind> {
+        self.postgres_error().map(|error| error.kind())
+    }
+
+    /// Whether a database request violated a unique constraint
+    pub fn is_unique_violation(&self) -> bool {
+        self.postgres_error_kind() == Some(PostgresErrorKind::UniqueViolation)
+    }
+
+    /// Whether a database request violated a foreign key constraint
+    pub fn is_foreign_key_violation(&self) -> bool {
+        self.postgres_error_kind() == Some(PostgresErrorKind::ForeignKeyViolation)
+    }
+
+    /// Whether a database request failed with a serialization failure
+    pub fn is_serialization_failure(&self) -> bool {
+        self.postgres_error_kind() == Some(PostgresErrorKind::SerializationFailure)
+    }
+
+    /// Whether a database request was canceled, e.g. by `statement_timeout`
+    pub fn is_query_canceled(&self) -> bool {
+        self.postgres_error_kind() == Some(PostgresErrorKind::QueryCanceled)
+    }
+
+    /// Errors reported by a failed GraphQL operation
+    #[cfg(feature = "graphql")]
+    pub fn graphql_errors(&self) -> Option<Vec<crate::graphql::GraphQLError>> {
+        let Error::Database { context, .. } = self else {
+            return None;
+        };
+
+        let body = context.http.as_ref()?.response_body.as_deref()?;
+        crate::graphql::errors_from_body(body)
+    }
+
+    /// Storage API failure of a storage request, if the response explains it
+    pub fn storage_error(&self) -> Option<StorageError> {
+        let Error::Storage { message, context } = self else {
+            return None;
+        };
+
+        let http = context.http.as_ref();
+        let status = http.and_then(|http| http.status_code);
+        let body = http.and_then(|http| http.response_body.as_deref());
+        [Some(message.as_str()), body]
+            .into_iter()
+            .flatten()
+            .find_map(|text| StorageError::from_response(status, text))
+    }
+
+    /// Error body of a failed Edge Function invocation, if it sent one
+    pub fn function_error(&self) -> Option<FunctionError> {
+        let Error::Functions { context, .. } = self else {
+            return None;
+        };
+
+        let http = context.http.as_ref()?;
+        FunctionError::from_response(http.status_code?, http.response_body.as_deref()?)
     }
 
     /// Get retry delay in seconds
     pub fn retry_after(&self) -> Option<u64> {
-        self.context()
-            .and_then(|ctx| ctx.retry.as_ref())
+        let context = self.context()?;
+        context
+            .retry
+            .as_ref()
             .and_then(|retry| retry.retry_after)
+            .or_else(|| context.rate_limit.as_ref()?.retry_after)
+    }
+
+    /// Rate limit headers of the response that failed, if it sent any
+    pub fn rate_limit_info(&self) -> Option<&RateLimitInfo> {
+        self.context()?.rate_limit.as_ref()
     }
 
     /// Get HTTP status code if available
@@ -512,6 +1061,165 @@ impl From<reqwest::Error> for Error {
 mod tests {
     use super::*;
 
+    #[test]
+    #[cfg(any(
+        feature = "auth-core",
+        feature = "database",
+        feature = "storage-core",
+        feature = "functions"
+    ))]
+    fn test_environment_fingerprint() {
+        let error = Error::storage("upload failed");
+        assert!(error.context().unwrap().metadata.is_empty());
+
+        let error = error.with_fingerprint(true);
+        let metadata = &error.context().unwrap().metadata;
+        assert_eq!(
+            metadata.get("crate_version").map(String::as_str),
+            Some(env!("CARGO_PKG_VERSION"))
+        );
+        assert_eq!(
+            metadata.get("endpoint").map(String::as_str),
+            Some("storage")
+        );
+        assert!(metadata.contains_key("features"));
+        assert!(metadata.contains_key("runtime"));
+
+        let mut context = ErrorContext::default();
+        context
+            .metadata
+            .insert("endpoint".to_string(), "custom".to_string());
+        let error = Error::database_with_context("query failed", context).with_fingerprint(true);
+        assert_eq!(
+            error
+                .context()
+                .unwrap()
+                .metadata
+                .get("endpoint")
+                .map(String::as_str),
+            Some("custom")
+        );
+
+        let error = Error::network("connection reset").with_fingerprint(false);
+        assert!(error.context().unwrap().metadata.is_empty());
+    }
+
+    #[test]
+    fn test_payload_too_large_limit() {
+        let limit = |message: &str| {
+            let body = serde_json::json!({
+                "statusCode": "413",
+                "error": "Payload too large",
+                "message": message,
+            });
+            match StorageError::from_response(None, &body.to_string()) {
+                Some(StorageError::PayloadTooLarge { limit }) => limit,
+                other => panic!("unexpected {:?}", other),
+            }
+        };
+
+        assert_eq!(limit("The object exceeded the maximum allowed size"), None);
+        assert_eq!(limit("Maximum size is 1048576"), Some(1_048_576));
+        assert_eq!(limit("Maximum size is 50MB"), Some(50_000_000));
+        assert_eq!(limit("Maximum size is 1.5 GB"), Some(1_500_000_000));
+        assert_eq!(limit("Maximum size is 10 MiB."), Some(10 * 1024 * 1024));
+        assert_eq!(limit("Maximum size is 512 bytes"), Some(512));
+    }
+
+    #[test]
+    fn test_postgres_error_mapping() {
+        let serialization = Error::database(
+            r#"{"code":"40001","details":null,"hint":"The transaction might succeed if retried.","message":"could not serialize access due to concurrent update"}"#,
+        );
+        assert!(serialization.is_serialization_failure());
+        assert!(serialization.is_retryable());
+        assert_eq!(
+            serialization.postgres_error().unwrap().hint.as_deref(),
+            Some("The transaction might succeed if retried.")
+        );
+
+        let deferred = Error::database(
+            r#"{"code":"23503","details":"Key (author_id)=(7) is not present in table \"authors\".","message":"insert or update on table \"posts\" violates foreign key constraint"}"#,
+        );
+        assert!(deferred.is_foreign_key_violation());
+        assert!(!deferred.is_retryable());
+
+        let conflict = Error::database(
+            r#"{"code":"23505","details":"Key (slug)=(hello) already exists.","hint":null,"message":"duplicate key value violates unique constraint \"posts_slug_key\""}"#,
+        );
+        assert!(conflict.is_unique_violation());
+        assert!(!deferred.is_unique_violation());
+
+        let context = ErrorContext {
+            http: Some(HttpErrorContext {
+                status_code: Some(500),
+                headers: None,
+                response_body: Some(
+                    r#"{"code":"57014","message":"canceling statement due to statement timeout"}"#
+                        .to_string(),
+                ),
+                url: None,
+                method: None,
+            }),
+            ..Default::default()
+        };
+        let canceled = Error::database_with_context("Request failed", context);
+        assert!(canceled.is_query_canceled());
+
+        let trigger = Error::database(r#"{"code":"P0001","message":"stock cannot be negative"}"#);
+        assert_eq!(
+            trigger.postgres_error_kind(),
+            Some(PostgresErrorKind::RaiseException)
+        );
+        assert_eq!(
+            Error::database(r#"{"code":"22P02","message":"invalid input syntax"}"#)
+                .postgres_error_kind(),
+            Some(PostgresErrorKind::Other("22P02".to_string()))
+        );
+
+        assert!(Error::database("Insert failed with status: 409")
+            .postgres_error()
+            .is_none());
+        assert!(!Error::network(r#"{"code":"40001","message":"x"}"#).is_serialization_failure());
+        assert!(!Error::network(r#"{"code":"23505"}"#).is_unique_violation());
+    }
+
+    #[test]
+    fn test_function_error_parsing() {
+        let nested = FunctionError::from_response(
+            422,
+            r#"{"error": {"code": "invalid_plan", "message": "Unknown plan", "details": {"plan": "gold"}}}"#,
+        )
+        .unwrap();
+        assert_eq!(nested.code.as_deref(), Some("invalid_plan"));
+        assert_eq!(nested.message, "Unknown plan");
+        assert_eq!(nested.details, Some(serde_json::json!({"plan": "gold"})));
+        assert_eq!(nested.to_string(), "Unknown plan (invalid_plan)");
+
+        let flat = FunctionError::from_response(400, r#"{"error": "Missing name"}"#).unwrap();
+        assert_eq!(
+            (flat.status, flat.code, flat.message.as_str()),
+            (400, None, "Missing name")
+        );
+        assert!(FunctionError::from_response(500, "Internal Server Error").is_none());
+
+        let error = Error::functions_with_context(
+            "Unknown plan",
+            ErrorContext {
+                http: Some(HttpErrorContext {
+                    status_code: Some(422),
+                    headers: None,
+                    response_body: Some(r#"{"message": "Unknown plan"}"#.to_string()),
+                    url: None,
+                    method: None,
+                }),
+                ..Default::default()
+            },
+        );
+        assert_eq!(error.function_error().unwrap().message, "Unknown plan");
+        assert!(Error::storage("failed").function_error().is_none());
+    }
+
     #[test]
     fn test_error_creation() {
         let error = Error::auth("test message");
@@ -11,6 +11,15 @@
 //! - Comprehensive error handling with detailed context
 //! - Thread-safe operations
 //!
+//! # Error Conventions
+//!
+//! - Functions returning `SupabaseError` return `SUPABASE_SUCCESS` (0) on
+//!   success and a non-zero code on failure
+//! - Functions returning a pointer return NULL on failure
+//! - Every failure records a thread-local last error, available through
+//!   `supabase_last_error_code()` and `supabase_last_error_message()`;
+//!   successful calls clear it
+//!
 //! # Safety
 //!
 //! All FFI functions are marked as `unsafe` and require careful handling of
@@ -29,6 +38,8 @@
 //!
 //!     if (error == SUPABASE_SUCCESS) {
 //!         printf("Success: %s\n", result);
+//!     } else {
+//!         printf("Error %d: %s\n", supabase_last_error_code(), supabase_last_error_message());
 //!     }
 //!
 //!     supabase_client_free(client);
@@ -36,15 +47,64 @@
 //! }
 //! ```
 
+use std::cell::RefCell;
 use std::ffi::{CStr, CString};
 use std::os::raw::c_char;
 use std::ptr;
-use std::sync::Mutex;
 
 use crate::{Client, Error};
 
-/// Thread-safe error storage for FFI
-static ERROR_STORAGE: Mutex<Option<String>> = Mutex::new(None);
+/// Error recorded by the last failing FFI call
+struct LastError {
+    code: SupabaseError,
+    message: CString,
+}
+
+thread_local! {
+    /// Per-thread error storage, so concurrent callers never see each other's errors
+    static LAST_ERROR: RefCell<Option<LastError>> = const { RefCell::new(None) };
+}
+
+/// Record an error for the current thread and return its code
+fn set_last_error(code: SupabaseError, message: impl Into<String>) -> SupabaseError {
+    let message = CString::new(message.into().replace('\0', " ")).unwrap_or_default();
+    LAST_ERROR.with(|last| *last.borrow_mut() = Some(LastError { code, message }));
+    code
+}
+
+/// Clear the current thread's last error
+fn clear_last_error() {
+    LAST_ERROR.with(|last| *last.borrow_mut() = None);
+}
+
+/// Read a required C string argument, recording an error if it is invalid
+unsafe fn str_arg<'a>(value: *const c_char, name: &str) -> Result<&'a str, SupabaseError> {
+    if value.is_null() {
+        return Err(set_last_error(
+            SupabaseError::InvalidInput,
+            format!("`{}` must not be NULL", name),
+        ));
+    }
+
+    CStr::from_ptr(value).to_str().map_err(|_| {
+        set_last_error(
+            SupabaseError::InvalidInput,
+            format!("`{}` is not valid UTF-8", name),
+        )
+    })
+}
+
+/// Check a required pointer argument, recording an error if it is NULL
+fn non_null_arg<T>(value: *const T, name: &str) -> Result<(), SupabaseError> {
+    if value.is_null() {
+        Err(set_last_error(
+            SupabaseError::InvalidInput,
+            format!("`{}` must not be NULL", name),
+        ))
+    } else {
+        Ok(())
+    }
+}
 
 /// Opaque handle to a Supabase client with runtime
 pub struct SupabaseClient {
@@ -54,7 +114,7 @@ pub struct SupabaseClient {
 
 /// Enhanced C-compatible error codes
 #[repr(C)]
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum SupabaseError {
     Success = 0,
     InvalidInput = 1,
@@ -70,22 +130,22 @@ pub enum SupabaseError {
 
 impl From<Error> for SupabaseError {
     fn from(err: Error) -> Self {
-        // Store detailed error message
-        if let Ok(mut storage) = ERROR_STORAGE.lock() {
-            *storage = Some(format!("{}", err));
-        }
-
-        match err {
-            Error::InvalidInput { .. } => SupabaseError::InvalidInput,
-            Error::Network { .. } => SupabaseError::NetworkError,
+        let code = match err {
+            Error::InvalidInput { .. } | Error::Validation(_) => SupabaseError::InvalidInput,
+            Error::Network { .. } | Error::CircuitOpen { .. } => SupabaseError::NetworkError,
             Error::Auth { .. } => SupabaseError::AuthError,
             Error::Database { .. } => SupabaseError::DatabaseError,
-            Error::Storage { .. } => SupabaseError::StorageError,
+            Error::Storage { .. } | Error::Integrity(_) | Error::AlreadyExists { .. } => {
+                SupabaseError::StorageError
+            }
             Error::Functions { .. } => SupabaseError::FunctionsError,
             Error::Realtime { .. } => SupabaseError::RealtimeError,
             Error::Platform { .. } | Error::Crypto { .. } => SupabaseError::RuntimeError,
             _ => SupabaseError::UnknownError,
-        }
+        };
+
+        // Store detailed error message
+        set_last_error(code, err.to_string())
     }
 }
 
@@ -94,22 +154,18 @@ impl From<Error> for SupabaseError {
 /// # Safety
 ///
 /// `url` and `key` must be valid C strings
-/// Returns NULL on error
+/// Returns NULL on error; see `supabase_last_error_message` for details
 #[no_mangle]
 pub unsafe extern "C" fn supabase_client_new(
     url: *const c_char,
     key: *const c_char,
 ) -> *mut SupabaseClient {
-    if url.is_null() || key.is_null() {
-        return ptr::null_mut();
-    }
-
-    let url_str = match CStr::from_ptr(url).to_str() {
+    let url_str = match str_arg(url, "url") {
         Ok(s) => s,
         Err(_) => return ptr::null_mut(),
     };
 
-    let key_str = match CStr::from_ptr(key).to_str() {
+    let key_str = match str_arg(key, "key") {
         Ok(s) => s,
         Err(_) => return ptr::null_mut(),
     };
@@ -117,12 +173,24 @@ pub unsafe extern "C" fn supabase_client_new(
     // Create tokio runtime for async operations
     let runtime = match tokio::runtime::Runtime::new() {
         Ok(rt) => rt,
-        Err(_) => return ptr::null_mut(),
+        Err(err) => {
+            set_last_error(
+                SupabaseError::RuntimeError,
+                format!("Failed to create async runtime: {}", err),
+            );
+            return ptr::null_mut();
+        }
     };
 
     match Client::new(url_str, key_str) {
-        Ok(client) => Box::into_raw(Box::new(SupabaseClient { client, runtime })),
-        Err(_) => ptr::null_mut(),
+        Ok(client) => {
+            clear_last_error();
+            Box::into_raw(Box::new(SupabaseClient { client, runtime }))
+        }
+        Err(err) => {
+            let _ = SupabaseError::from(err);
+            ptr::null_mut()
+        }
     }
 }
 
@@ -152,20 +220,20 @@ pub unsafe extern "C" fn supabase_auth_sign_in(
     result: *mut c_char,
     result_len: usize,
 ) -> SupabaseError {
-    if client.is_null() || email.is_null() || password.is_null() || result.is_null() {
-        return SupabaseError::InvalidInput;
+    if let Err(code) = non_null_arg(client, "client").and_then(|_| non_null_arg(result, "result")) {
+        return code;
     }
 
     let client_ref = &(*client);
 
-    let email_str = match CStr::from_ptr(email).to_str() {
+    let email_str = match str_arg(email, "email") {
         Ok(s) => s,
-        Err(_) => return SupabaseError::InvalidInput,
+        Err(code) => return code,
     };
 
-    let password_str = match CStr::from_ptr(password).to_str() {
+    let password_str = match str_arg(password, "password") {
         Ok(s) => s,
-        Err(_) => return SupabaseError::InvalidInput,
+        Err(code) => return code,
     };
 
     // Execute async operation in runtime
@@ -181,7 +249,12 @@ pub unsafe extern "C" fn supabase_auth_sign_in(
         Ok(session) => {
             let response = match serde_json::to_string(&session) {
                 Ok(json) => json,
-                Err(_) => return SupabaseError::UnknownError,
+                Err(err) => {
+                    return set_last_error(
+                        SupabaseError::UnknownError,
+                        format!("Failed to serialize response: {}", err),
+                    )
+                }
             };
 
             write_string_to_buffer(&response, result, result_len)
@@ -203,20 +276,20 @@ pub unsafe extern "C" fn supabase_auth_sign_up(
     result: *mut c_char,
     result_len: usize,
 ) -> SupabaseError {
-    if client.is_null() || email.is_null() || password.is_null() || result.is_null() {
-        return SupabaseError::InvalidInput;
+    if let Err(code) = non_null_arg(client, "client").and_then(|_| non_null_arg(result, "result")) {
+        return code;
     }
 
     let client_ref = &(*client);
 
-    let email_str = match CStr::from_ptr(email).to_str() {
+    let email_str = match str_arg(email, "email") {
         Ok(s) => s,
-        Err(_) => return SupabaseError::InvalidInput,
+        Err(code) => return code,
     };
 
-    let password_str = match CStr::from_ptr(password).to_str() {
+    let password_str = match str_arg(password, "password") {
         Ok(s) => s,
-        Err(_) => return SupabaseError::InvalidInput,
+        Err(code) => return code,
     };
 
     let auth_result = client_ref.runtime.block_on(async {
@@ -231,7 +304,12 @@ pub unsafe extern "C" fn supabase_auth_sign_up(
         Ok(session) => {
             let response = match serde_json::to_string(&session) {
                 Ok(json) => json,
-                Err(_) => return SupabaseError::UnknownError,
+                Err(err) => {
+                    return set_last_error(
+                        SupabaseError::UnknownError,
+                        format!("Failed to serialize response: {}", err),
+                    )
+                }
             };
 
             write_string_to_buffer(&response, result, result_len)
@@ -253,23 +331,23 @@ pub unsafe extern "C" fn supabase_database_select(
     result: *mut c_char,
     result_len: usize,
 ) -> SupabaseError {
-    if client.is_null() || table.is_null() || result.is_null() {
-        return SupabaseError::InvalidInput;
+    if let Err(code) = non_null_arg(client, "client").and_then(|_| non_null_arg(result, "result")) {
+        return code;
     }
 
     let client_ref = &(*client);
 
-    let table_str = match CStr::from_ptr(table).to_str() {
+    let table_str = match str_arg(table, "table") {
         Ok(s) => s,
-        Err(_) => return SupabaseError::InvalidInput,
+        Err(code) => return code,
     };
 
     let columns_str = if columns.is_null() {
         "*"
     } else {
-        match CStr::from_ptr(columns).to_str() {
+        match str_arg(columns, "columns") {
             Ok(s) => s,
-            Err(_) => return SupabaseError::InvalidInput,
+            Err(code) => return code,
         }
     };
 
@@ -304,25 +382,30 @@ pub unsafe extern "C" fn supabase_database_insert(
     result: *mut c_char,
     result_len: usize,
 ) -> SupabaseError {
-    if client.is_null() || table.is_null() || json_data.is_null() || result.is_null() {
-        return SupabaseError::InvalidInput;
+    if let Err(code) = non_null_arg(client, "client").and_then(|_| non_null_arg(result, "result")) {
+        return code;
     }
 
     let client_ref = &(*client);
 
-    let table_str = match CStr::from_ptr(table).to_str() {
+    let table_str = match str_arg(table, "table") {
         Ok(s) => s,
-        Err(_) => return SupabaseError::InvalidInput,
+        Err(code) => return code,
     };
 
-    let json_str = match CStr::from_ptr(json_data).to_str() {
+    let json_str = match str_arg(json_data, "json_data") {
         Ok(s) => s,
-        Err(_) => return SupabaseError::InvalidInput,
+        Err(code) => return code,
     };
 
     let json_value: serde_json::Value = match serde_json::from_str(json_str) {
         Ok(v) => v,
-        Err(_) => return SupabaseError::InvalidInput,
+        Err(err) => {
+            return set_last_error(
+                SupabaseError::InvalidInput,
+                format!("`json_data` is not valid JSON: {}", err),
+            )
+        }
     };
 
     let db_result = client_ref.runtime.block_on(async {
@@ -357,8 +440,8 @@ pub unsafe extern "C" fn supabase_storage_list_buckets(
     result: *mut c_char,
     result_len: usize,
 ) -> SupabaseError {
-    if client.is_null() || result.is_null() {
-        return SupabaseError::InvalidInput;
+    if let Err(code) = non_null_arg(client, "client").and_then(|_| non_null_arg(result, "result")) {
+        return code;
     }
 
     let client_ref = &(*client);
@@ -371,7 +454,12 @@ pub unsafe extern "C" fn supabase_storage_list_buckets(
         Ok(buckets) => {
             let response = match serde_json::to_string(&buckets) {
                 Ok(json) => json,
-                Err(_) => return SupabaseError::UnknownError,
+                Err(err) => {
+                    return set_last_error(
+                        SupabaseError::UnknownError,
+                        format!("Failed to serialize response: {}", err),
+                    )
+                }
             };
             write_string_to_buffer(&response, result, result_len)
         }
@@ -392,26 +480,31 @@ pub unsafe extern "C" fn supabase_functions_invoke(
     result: *mut c_char,
     result_len: usize,
 ) -> SupabaseError {
-    if client.is_null() || function_name.is_null() || result.is_null() {
-        return SupabaseError::InvalidInput;
+    if let Err(code) = non_null_arg(client, "client").and_then(|_| non_null_arg(result, "result")) {
+        return code;
     }
 
     let client_ref = &(*client);
 
-    let function_str = match CStr::from_ptr(function_name).to_str() {
+    let function_str = match str_arg(function_name, "function_name") {
         Ok(s) => s,
-        Err(_) => return SupabaseError::InvalidInput,
+        Err(code) => return code,
     };
 
     let payload = if json_payload.is_null() {
         None
     } else {
-        match CStr::from_ptr(json_payload).to_str() {
+        match str_arg(json_payload, "json_payload") {
             Ok(s) => match serde_json::from_str::<serde_json::Value>(s) {
                 Ok(v) => Some(v),
-                Err(_) => return SupabaseError::InvalidInput,
+                Err(err) => {
+                    return set_last_error(
+                        SupabaseError::InvalidInput,
+                        format!("`json_payload` is not valid JSON: {}", err),
+                    )
+                }
             },
-            Err(_) => return SupabaseError::InvalidInput,
+            Err(code) => return code,
         }
     };
 
@@ -435,7 +528,43 @@ pub unsafe extern "C" fn supabase_functions_invoke(
     }
 }
 
-/// Get the last error message
+/// Get the error code recorded by the last failing call on this thread
+///
+/// Returns `SUPABASE_SUCCESS` if the last call succeeded.
+#[no_mangle]
+pub extern "C" fn supabase_last_error_code() -> SupabaseError {
+    LAST_ERROR.with(|last| {
+        last.borrow()
+            .as_ref()
+            .map(|error| error.code)
+            .unwrap_or(SupabaseError::Success)
+    })
+}
+
+/// Get the error message recorded by the last failing call on this thread
+///
+/// Returns NULL if the last call succeeded. The string is owned by the library
+/// and stays valid until the next call into the library on the same thread;
+/// copy it if it must outlive that.
+#[no_mangle]
+pub extern "C" fn supabase_last_error_message() -> *const c_char {
+    LAST_ERROR.with(|last| {
+        last.borrow()
+            .as_ref()
+            .map(|error| error.message.as_ptr())
+            .unwrap_or(ptr::null())
+    })
+}
+
+/// Clear the last error on this thread
+#[no_mangle]
+pub extern "C" fn supabase_clear_last_error() {
+    clear_last_error();
+}
+
+/// Copy the last error message into a buffer
+///
+/// Writes "No error" if the last call succeeded. Does not modify the last error.
 ///
 /// # Safety
 ///
@@ -449,24 +578,42 @@ pub unsafe extern "C" fn supabase_get_last_error(
         return SupabaseError::InvalidInput;
     }
 
-    let error_msg = if let Ok(storage) = ERROR_STORAGE.lock() {
-        storage
+    let error_msg = LAST_ERROR.with(|last| {
+        last.borrow()
             .as_ref()
-            .cloned()
+            .map(|error| error.message.to_string_lossy().into_owned())
             .unwrap_or_else(|| "No error".to_string())
-    } else {
-        "Failed to access error storage".to_string()
-    };
+    });
 
-    write_string_to_buffer(&error_msg, buffer, buffer_len)
+    copy_to_buffer(&error_msg, buffer, buffer_len)
 }
 
-/// Helper function to write string to C buffer
+/// Helper function to write a call's result to a C buffer
+///
+/// Clears the last error on success, records it on failure.
 unsafe fn write_string_to_buffer(
     data: &str,
     buffer: *mut c_char,
     buffer_len: usize,
 ) -> SupabaseError {
+    match copy_to_buffer(data, buffer, buffer_len) {
+        SupabaseError::Success => {
+            clear_last_error();
+            SupabaseError::Success
+        }
+        _ => set_last_error(
+            SupabaseError::InvalidInput,
+            format!(
+                "Result buffer too small: {} bytes needed, {} available",
+                data.len() + 1,
+                buffer_len
+            ),
+        ),
+    }
+}
+
+/// Copy a string with its NUL terminator to a C buffer
+unsafe fn copy_to_buffer(data: &str, buffer: *mut c_char, buffer_len: usize) -> SupabaseError {
     let data_cstring = match CString::new(data) {
         Ok(s) => s,
         Err(_) => return SupabaseError::UnknownError,
@@ -508,6 +655,31 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_last_error_accessors() {
+        let key = CString::new("test-key").unwrap();
+
+        unsafe {
+            let client = supabase_client_new(ptr::null(), key.as_ptr());
+            assert!(client.is_null());
+            assert_eq!(supabase_last_error_code(), SupabaseError::InvalidInput);
+
+            let message = CStr::from_ptr(supabase_last_error_message());
+            assert!(message.to_str().unwrap().contains("`url`"));
+
+            // Errors are per thread
+            let other = std::thread::spawn(|| supabase_last_error_message().is_null());
+            assert!(other.join().unwrap());
+
+            let url = CString::new("http://localhost:54321").unwrap();
+            let client = supabase_client_new(url.as_ptr(), key.as_ptr());
+            assert!(!client.is_null());
+            assert_eq!(supabase_last_error_code(), SupabaseError::Success);
+            assert!(supabase_last_error_message().is_null());
+            supabase_client_free(client);
+        }
+    }
+
     #[test]
     fn test_error_storage() {
         let mut buffer = [0u8; 256];
@@ -12,37 +12,32 @@
 //! - **Local Development**: Testing utilities for local functions
 //! - **Enhanced Error Handling**: Detailed error context and retry logic
 
+#[cfg(feature = "auth-core")]
+use crate::auth::{Auth, AuthEvent, AuthEventHandle};
 use crate::{
-    error::{Error, Result},
-    types::SupabaseConfig,
+    async_runtime::{async_sleep, spawn_background},
+    circuit::CircuitBreaker,
+    error::{Error, ErrorContext, FunctionError, HttpErrorContext, Result},
+    types::{ProjectUrl, RequestAuth, SupabaseConfig, TokenProvider},
 };
+use bytes::Bytes;
+#[cfg(not(target_arch = "wasm32"))]
+use futures_util::{Stream, StreamExt};
 use reqwest::Client as HttpClient;
+use reqwest::Method;
 #[cfg(not(target_arch = "wasm32"))]
 use reqwest::Response;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use std::{collections::HashMap, sync::Arc, time::Duration};
 #[cfg(not(target_arch = "wasm32"))]
-use tokio_stream::Stream;
+use std::collections::VecDeque;
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+    time::Duration,
+};
 use tracing::{debug, info, warn};
 
-// Helper for async sleep across platforms
-#[cfg(not(target_arch = "wasm32"))]
-async fn async_sleep(duration: Duration) {
-    tokio::time::sleep(duration).await;
-}
-
-#[cfg(all(target_arch = "wasm32", feature = "wasm"))]
-async fn async_sleep(duration: Duration) {
-    use gloo_timers::future::sleep as gloo_sleep;
-    gloo_sleep(duration).await;
-}
-
-#[cfg(all(target_arch = "wasm32", not(feature = "wasm")))]
-async fn async_sleep(_duration: Duration) {
-    // No-op for wasm32 without wasm feature (retry delays not supported)
-}
-
 /// Edge Functions client for invoking serverless functions
 ///
 /// # Examples
@@ -94,6 +89,102 @@ async fn async_sleep(_duration: Duration) {
 pub struct Functions {
     http_client: Arc<HttpClient>,
     config: Arc<SupabaseConfig>,
+    dev_base_url: Arc<RwLock<Option<String>>>,
+    cache: Arc<RwLock<HashMap<CacheKey, CachedResponse>>>,
+    token_provider: RequestAuth,
+    circuit: CircuitBreaker,
+}
+
+/// Request a cached response answers
+///
+/// Responses of another endpoint, user or request body are never served.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct CacheKey {
+    /// Functions endpoint, which follows the dev base URL
+    base_url: String,
+    /// User the request is made as, see [`token_principal`]
+    principal: Option<String>,
+    function_name: String,
+    /// Serialized request body
+    body: Option<String>,
+}
+
+/// Response caching for [`Functions::invoke_cached`]
+///
+/// A response is served from the cache for `ttl` after it was fetched. For
+/// a further `swr` it is still served, while a background invocation
+/// refreshes it; after that the next call waits for a fresh response.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CachePolicy {
+    /// How long a response stays fresh
+    pub ttl: Duration,
+    /// How long a stale response may be served while it is revalidated
+    pub swr: Duration,
+}
+
+/// Age class of a cached response under a [`CachePolicy`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Freshness {
+    Fresh,
+    Stale,
+    Expired,
+}
+
+impl CachePolicy {
+    fn freshness(&self, age: Duration) -> Freshness {
+        if age < self.ttl {
+            Freshness::Fresh
+        } else if age < self.ttl + self.swr {
+            Freshness::Stale
+        } else {
+            Freshness::Expired
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct CachedResponse {
+    value: Value,
+    fetched_at: chrono::DateTime<chrono::Utc>,
+    refreshing: bool,
+}
+
+/// Make room for `key` in a cache holding at most `capacity` responses
+///
+/// Evicts the responses fetched longest ago.
+fn evict_for(cache: &mut HashMap<CacheKey, CachedResponse>, key: &CacheKey, capacity: usize) {
+    if cache.contains_key(key) {
+        return;
+    }
+    while !cache.is_empty() && cache.len() >= capacity {
+        let oldest = cache
+            .iter()
+            .min_by_key(|(_, entry)| entry.fetched_at)
+            .map(|(key, _)| key.clone());
+        if let Some(oldest) = oldest {
+            cache.remove(&oldest);
+        }
+    }
+}
+
+/// `sub` claim of an access token, read without verifying it, or the whole
+/// token when it has none
+///
+/// Keying on the subject keeps a user's cached responses across token refreshes.
+fn token_principal(access_token: &str) -> String {
+    use base64::Engine;
+
+    access_token
+        .split('.')
+        .nth(1)
+        .and_then(|payload| {
+            base64::engine::general_purpose::URL_SAFE_NO_PAD
+                .decode(payload.trim_end_matches('='))
+                .ok()
+        })
+        .and_then(|json| serde_json::from_slice::<Value>(&json).ok())
+        .and_then(|claims| claims["sub"].as_str().map(|sub| format!("sub:{}", sub)))
+        .unwrap_or_else(|| format!("token:{}", access_token))
 }
 
 /// Function metadata and introspection information
@@ -172,17 +263,139 @@ impl Default for RetryConfig {
     }
 }
 
-/// Streaming chunk from function response
+/// Server-Sent Event from [`Functions::invoke_stream`]
 #[derive(Debug, Clone)]
 pub struct StreamChunk {
-    /// Chunk data
+    /// Event data, parsed as JSON or else kept as a string
     pub data: Value,
-    /// Chunk sequence number
+    /// Event `id` if it is a number, otherwise the position of the event
     pub sequence: Option<u64>,
-    /// Whether this is the last chunk
+    /// Whether this is the `[DONE]` marker ending the stream
     pub is_final: bool,
 }
 
+/// Unprocessed function response from [`Functions::invoke_raw`]
+#[derive(Debug, Clone)]
+pub struct FunctionResponse {
+    pub status: u16,
+    /// Response headers, names lowercased
+    pub headers: HashMap<String, String>,
+    pub body: Bytes,
+}
+
+impl FunctionResponse {
+    /// Whether the status is 2xx
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+
+    /// `Content-Type` of the body, without parameters
+    pub fn content_type(&self) -> Option<&str> {
+        self.headers
+            .get("content-type")
+            .and_then(|value| value.split(';').next())
+            .map(str::trim)
+    }
+
+    /// Parse the body as JSON
+    pub fn json<T: for<'de> Deserialize<'de>>(&self) -> Result<T> {
+        Ok(serde_json::from_slice(&self.body)?)
+    }
+
+    /// The body as text, with invalid UTF-8 replaced
+    pub fn text(&self) -> String {
+        String::from_utf8_lossy(&self.body).into_owned()
+    }
+
+    /// Structured error body of a non-2xx response
+    pub fn error(&self) -> Option<FunctionError> {
+        if self.is_success() {
+            return None;
+        }
+        FunctionError::from_response(self.status, &self.text())
+    }
+}
+
+/// Collect response headers with lowercased names
+fn header_map(headers: &reqwest::header::HeaderMap) -> HashMap<String, String> {
+    headers
+        .iter()
+        .filter_map(|(name, value)| {
+            value
+                .to_str()
+                .ok()
+                .map(|value| (name.as_str().to_ascii_lowercase(), value.to_string()))
+        })
+        .collect()
+}
+
+/// Turn a non-2xx function response into an error carrying its body
+///
+/// The message comes from the function's error JSON when it sent one; the
+/// full body stays available through [`Error::function_error`].
+async fn response_error(response: reqwest::Response, method: &str) -> Error {
+    let status = response.status();
+    let headers = header_map(response.headers());
+    let url = response.url().to_string();
+    let body = response.text().await.unwrap_or_default();
+
+    let message = match FunctionError::from_response(status.as_u16(), &body) {
+        Some(error) => error.to_string(),
+        None if !body.is_empty() => body.clone(),
+        None => format!("Function invocation failed with status: {}", status),
+    };
+    Error::functions_with_context(
+        message,
+        ErrorContext {
+            http: Some(HttpErrorContext {
+                status_code: Some(status.as_u16()),
+                headers: Some(headers),
+                response_body: Some(body),
+                url: Some(url),
+                method: Some(method.to_string()),
+            }),
+            ..Default::default()
+        },
+    )
+}
+
+/// Decode a successful function response by its `Content-Type`
+///
+/// JSON (or an untyped body) is parsed, `text/*` becomes a JSON string and
+/// an empty body is `null`. Other types need [`Functions::invoke_raw`].
+async fn negotiate_response(response: reqwest::Response) -> Result<Value> {
+    let content_type = response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .map(|value| {
+            value
+                .split(';')
+                .next()
+                .unwrap_or_default()
+                .trim()
+                .to_string()
+        });
+    let body = response.bytes().await?;
+
+    if body.is_empty() {
+        return Ok(Value::Null);
+    }
+    match content_type.as_deref() {
+        None => Ok(serde_json::from_slice(&body)?),
+        Some(json) if json == "application/json" || json.ends_with("+json") => {
+            Ok(serde_json::from_slice(&body)?)
+        }
+        Some(text) if text.starts_with("text/") => {
+            Ok(Value::String(String::from_utf8_lossy(&body).into_owned()))
+        }
+        Some(other) => Err(Error::functions(format!(
+            "Function returned {} content; use invoke_raw() to read it",
+            other
+        ))),
+    }
+}
+
 /// Local development configuration
 #[derive(Debug, Clone)]
 pub struct LocalConfig {
@@ -197,14 +410,71 @@ pub struct LocalConfig {
 impl Functions {
     /// Create a new Functions instance
     pub fn new(config: Arc<SupabaseConfig>, http_client: Arc<HttpClient>) -> Result<Self> {
+        Ok(Self::with_http_client(config, http_client))
+    }
+
+    /// Create the Functions module of a [`Client`](crate::Client), which cannot fail
+    pub(crate) fn with_http_client(
+        config: Arc<SupabaseConfig>,
+        http_client: Arc<HttpClient>,
+    ) -> Self {
         debug!("Initializing Functions module");
 
-        Ok(Self {
+        let dev_base_url = config.functions_config.dev_base_url.clone();
+
+        // A 5xx comes from the code of one function, so it must not fail
+        // fast the calls to every other function
+        let circuit = CircuitBreaker::new("functions", config.http_config.circuit_breaker)
+            .with_retry(config.http_config.retry.clone())
+            .with_fingerprint(config.error_fingerprint)
+            .ignore_server_errors();
+
+        Self {
             http_client,
             config,
+            dev_base_url: Arc::new(RwLock::new(dev_base_url)),
+            cache: Arc::new(RwLock::new(HashMap::new())),
+            token_provider: RequestAuth::default(),
+            circuit,
+        }
+    }
+
+    /// Circuit breaker guarding Edge Function invocations
+    ///
+    /// Opens only when the functions runtime cannot be reached; error
+    /// responses of a function do not count.
+    pub fn circuit_breaker(&self) -> &CircuitBreaker {
+        &self.circuit
+    }
+
+    /// Clear the response cache whenever the signed-in user of `auth` changes
+    ///
+    /// [`Client`](crate::Client) binds its own auth module, so responses
+    /// cached for one user are dropped when another signs in.
+    #[cfg(feature = "auth-core")]
+    pub fn bind_auth(&self, auth: &Auth) -> AuthEventHandle {
+        let cache = Arc::clone(&self.cache);
+
+        auth.on_auth_state_change(move |event, _session| {
+            // A refreshed token belongs to the same user
+            if event == AuthEvent::TokenRefreshed {
+                return;
+            }
+            if let Ok(mut cache) = cache.write() {
+                cache.clear();
+            }
         })
     }
 
+    /// Authorize invocations with the access token from `provider`
+    ///
+    /// [`Client`](crate::Client) sets this to its auth session, so functions
+    /// receive the signed-in user's JWT. Without a token the API key is sent.
+    pub fn with_token_provider(mut self, provider: Arc<dyn TokenProvider>) -> Self {
+        self.token_provider = RequestAuth::new(provider);
+        self
+    }
+
     /// Invoke an Edge Function
     ///
     /// # Parameters
@@ -233,6 +503,141 @@ impl Functions {
         self.invoke_with_options(function_name, body, None).await
     }
 
+    /// Invoke an Edge Function, caching its response
+    ///
+    /// Responses are cached per endpoint, user, function name and request
+    /// body under `policy`: fresh responses are returned without a request, stale ones
+    /// are returned immediately while a background invocation refreshes them.
+    /// Errors are never cached. Use this for expensive functions whose
+    /// results change slowly; clones of this client share the cache.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use serde_json::json;
+    /// use std::time::Duration;
+    /// use supabase_lib_rs::functions::CachePolicy;
+    ///
+    /// # async fn example(functions: &supabase_lib_rs::Functions) -> supabase_lib_rs::Result<()> {
+    /// let policy = CachePolicy {
+    ///     ttl: Duration::from_secs(60),
+    ///     swr: Duration::from_secs(600),
+    /// };
+    /// let leaderboard = functions
+    ///     .invoke_cached("leaderboard", Some(json!({"season": 3})), policy)
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn invoke_cached(
+        &self,
+        function_name: &str,
+        body: Option<Value>,
+        policy: CachePolicy,
+    ) -> Result<Value> {
+        let key = self.cache_key(function_name, body.as_ref());
+
+        let cached = {
+            let mut cache = self
+                .cache
+                .write()
+                .map_err(|_| Error::functions("Failed to access response cache"))?;
+            match cache.get_mut(&key) {
+                Some(entry) => {
+                    let age = (chrono::Utc::now() - entry.fetched_at)
+                        .to_std()
+                        .unwrap_or_default();
+                    match policy.freshness(age) {
+                        Freshness::Fresh => Some((entry.value.clone(), false)),
+                        Freshness::Stale => {
+                            let revalidate = !entry.refreshing;
+                            entry.refreshing = true;
+                            Some((entry.value.clone(), revalidate))
+                        }
+                        Freshness::Expired => None,
+                    }
+                }
+                None => None,
+            }
+        };
+
+        if let Some((value, revalidate)) = cached {
+            if revalidate {
+                debug!("Revalidating cached response of {}", function_name);
+                let functions = self.clone();
+                let function_name = function_name.to_string();
+                spawn_background(async move {
+                    if let Err(e) = functions.fetch_cached(&function_name, body, key).await {
+                        warn!("Failed to revalidate {}: {}", function_name, e);
+                    }
+                });
+            }
+            return Ok(value);
+        }
+
+        self.fetch_cached(function_name, body, key).await
+    }
+
+    /// Drop cached responses of one function, or of all functions with `None`
+    pub fn invalidate_cache(&self, function_name: Option<&str>) {
+        if let Ok(mut cache) = self.cache.write() {
+            match function_name {
+                Some(function_name) => cache.retain(|key, _| key.function_name != function_name),
+                None => cache.clear(),
+            }
+        }
+    }
+
+    /// Key of a call to `function_name` with `body`, as the current user
+    fn cache_key(&self, function_name: &str, body: Option<&Value>) -> CacheKey {
+        CacheKey {
+            base_url: self.functions_url(),
+            principal: self
+                .token_provider
+                .access_token()
+                .map(|token| token_principal(&token)),
+            function_name: function_name.to_string(),
+            body: body.map(Value::to_string),
+        }
+    }
+
+    /// Invoke a function and store its response in the cache
+    async fn fetch_cached(
+        &self,
+        function_name: &str,
+        body: Option<Value>,
+        key: CacheKey,
+    ) -> Result<Value> {
+        let result = self.invoke(function_name, body).await;
+
+        if let Ok(mut cache) = self.cache.write() {
+            match &result {
+                Ok(value) => {
+                    let capacity = self.config.functions_config.cache_capacity;
+                    evict_for(&mut cache, &key, capacity);
+                    if capacity > 0 {
+                        cache.insert(
+                            key,
+                            CachedResponse {
+                                value: value.clone(),
+                                fetched_at: chrono::Utc::now(),
+                                refreshing: false,
+                            },
+                        );
+                    }
+                }
+                Err(_) => {
+                    // Let the next call retry the revalidation
+                    if let Some(entry) = cache.get_mut(&key) {
+                        entry.refreshing = false;
+                    }
+                }
+            }
+        }
+
+        result
+    }
+
     /// Invoke an Edge Function with custom options
     ///
     /// # Parameters
@@ -268,12 +673,15 @@ impl Functions {
     ) -> Result<Value> {
         debug!("Invoking Edge Function: {}", function_name);
 
-        let url = format!("{}/functions/v1/{}", self.config.url, function_name);
+        let url = format!("{}/{}", self.functions_url(), function_name);
 
         let mut request = self
             .http_client
             .post(&url)
-            .header("Authorization", format!("Bearer {}", self.config.key))
+            .header(
+                "Authorization",
+                self.token_provider.bearer(&self.config.key),
+            )
             .header("Content-Type", "application/json");
 
         // Add custom headers if provided
@@ -288,38 +696,88 @@ impl Functions {
             request = request.json(&body);
         }
 
-        let response = request.send().await?;
+        let response = self.circuit.send(request).await?;
 
         if !response.status().is_success() {
-            let status = response.status();
-            let error_msg = match response.text().await {
-                Ok(text) => {
-                    // Try to parse error message from Supabase
-                    if let Ok(error_json) = serde_json::from_str::<Value>(&text) {
-                        if let Some(message) = error_json.get("message") {
-                            message.as_str().unwrap_or(&text).to_string()
-                        } else {
-                            text
-                        }
-                    } else {
-                        text
-                    }
-                }
-                Err(_) => format!("Function invocation failed with status: {}", status),
-            };
-            return Err(Error::functions(error_msg));
+            return Err(response_error(response, "POST")
+                .await
+                .with_fingerprint(self.config.error_fingerprint));
         }
 
-        let result: Value = response.json().await?;
+        let result = negotiate_response(response).await?;
         info!("Edge Function {} invoked successfully", function_name);
 
         Ok(result)
     }
 
-    /// Invoke an Edge Function with streaming response (native only)
+    /// Invoke an Edge Function and return its response unprocessed
     ///
-    /// This method enables server-sent events or streaming responses from functions.
-    /// Only available on native platforms (not WASM).
+    /// Gives full control over the method, body and headers, and never fails
+    /// on the response status: use it for functions returning images, CSV or
+    /// other non-JSON content, or to inspect error responses yourself.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use reqwest::Method;
+    ///
+    /// # async fn example(functions: &supabase_lib_rs::Functions) -> supabase_lib_rs::Result<()> {
+    /// let response = functions
+    ///     .invoke_raw("render-chart", Method::GET, None, None)
+    ///     .await?;
+    ///
+    /// if let Some(error) = response.error() {
+    ///     println!("Function failed: {}", error);
+    /// } else if response.content_type() == Some("image/png") {
+    ///     std::fs::write("chart.png", &response.body).unwrap();
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn invoke_raw(
+        &self,
+        function_name: &str,
+        method: Method,
+        body: Option<Bytes>,
+        headers: Option<HashMap<String, String>>,
+    ) -> Result<FunctionResponse> {
+        debug!("Invoking Edge Function {} ({})", function_name, method);
+
+        let url = format!("{}/{}", self.functions_url(), function_name);
+
+        let mut request = self.http_client.request(method, &url).header(
+            "Authorization",
+            self.token_provider.bearer(&self.config.key),
+        );
+
+        if let Some(custom_headers) = headers {
+            for (key, value) in custom_headers {
+                request = request.header(key, value);
+            }
+        }
+
+        if let Some(body) = body {
+            request = request.body(body);
+        }
+
+        let response = self.circuit.send(request).await?;
+        let status = response.status().as_u16();
+        let headers = header_map(response.headers());
+        let body = response.bytes().await?;
+
+        Ok(FunctionResponse {
+            status,
+            headers,
+            body,
+        })
+    }
+
+    /// Invoke an Edge Function and read its Server-Sent Events as they arrive
+    ///
+    /// Each event is yielded as soon as its terminating blank line is
+    /// received, so functions streaming with `ReadableStream` (e.g. LLM
+    /// proxies) can be consumed incrementally. Only available on native
+    /// platforms (not WASM).
     ///
     /// # Parameters
     ///
@@ -352,41 +810,107 @@ impl Functions {
         &self,
         function_name: &str,
         body: Option<Value>,
+    ) -> Result<impl Stream<Item = Result<StreamChunk>>> {
+        self.invoke_stream_with_options(function_name, body, InvokeOptions::default())
+            .await
+    }
+
+    /// Invoke an Edge Function with custom headers or a timeout and read its
+    /// Server-Sent Events as they arrive
+    ///
+    /// `options.timeout` covers the whole invocation, including reading the
+    /// stream; `retry` and `streaming` are ignored. Only available on native
+    /// platforms (not WASM).
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use serde_json::json;
+    /// use std::{collections::HashMap, time::Duration};
+    /// use supabase_lib_rs::functions::InvokeOptions;
+    /// use tokio_stream::StreamExt;
+    ///
+    /// # async fn example(functions: &supabase_lib_rs::Functions) -> supabase_lib_rs::Result<()> {
+    /// let options = InvokeOptions {
+    ///     headers: Some(HashMap::from([("X-Model".to_string(), "small".to_string())])),
+    ///     timeout: Some(Duration::from_secs(120)),
+    ///     ..Default::default()
+    /// };
+    ///
+    /// let mut tokens = functions
+    ///     .invoke_stream_with_options("llm-proxy", Some(json!({"prompt": "Hi"})), options)
+    ///     .await?;
+    ///
+    /// while let Some(chunk) = tokens.next().await {
+    ///     let chunk = chunk?;
+    ///     if chunk.is_final {
+    ///         break;
+    ///     }
+    ///     print!("{}", chunk.data.as_str().unwrap_or_default());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(not(target_arch = "wasm32"))]
+    pub async fn invoke_stream_with_options(
+        &self,
+        function_name: &str,
+        body: Option<Value>,
+        options: InvokeOptions,
     ) -> Result<impl Stream<Item = Result<StreamChunk>>> {
         debug!(
             "Starting streaming invocation of function: {}",
             function_name
         );
 
-        let url = format!("{}/functions/v1/{}", self.config.url, function_name);
-
-        let mut request = self
-            .http_client
-            .post(&url)
-            .header("Authorization", format!("Bearer {}", self.config.key))
-            .header("Content-Type", "application/json")
-            .header("Accept", "text/event-stream")
-            .header("Cache-Control", "no-cache");
+        let response = self
+            .send_streaming(function_name, body, &options, Some("text/event-stream"))
+            .await?;
 
-        // Add body if provided
-        if let Some(body) = body {
-            request = request.json(&body);
-        }
+        Ok(sse_events(response))
+    }
 
-        let response = request.send().await?;
+    /// Invoke an Edge Function and read its response body as it arrives
+    ///
+    /// For functions streaming plain text, NDJSON or binary data rather than
+    /// Server-Sent Events. Headers and timeout are taken from `options` as in
+    /// [`invoke_stream_with_options`](Self::invoke_stream_with_options).
+    /// Only available on native platforms (not WASM).
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use supabase_lib_rs::functions::InvokeOptions;
+    /// use tokio_stream::StreamExt;
+    ///
+    /// # async fn example(functions: &supabase_lib_rs::Functions) -> supabase_lib_rs::Result<()> {
+    /// let mut body = functions
+    ///     .invoke_byte_stream("export-ndjson", None, InvokeOptions::default())
+    ///     .await?;
+    ///
+    /// while let Some(bytes) = body.next().await {
+    ///     println!("Received {} bytes", bytes?.len());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(not(target_arch = "wasm32"))]
+    pub async fn invoke_byte_stream(
+        &self,
+        function_name: &str,
+        body: Option<Value>,
+        options: InvokeOptions,
+    ) -> Result<impl Stream<Item = Result<Bytes>>> {
+        debug!(
+            "Starting byte stream invocation of function: {}",
+            function_name
+        );
 
-        if !response.status().is_success() {
-            let status = response.status();
-            let error_msg = response.text().await.unwrap_or_else(|_| {
-                format!(
-                    "Streaming function invocation failed with status: {}",
-                    status
-                )
-            });
-            return Err(Error::functions(error_msg));
-        }
+        let response = self
+            .send_streaming(function_name, body, &options, None)
+            .await?;
 
-        self.process_stream(response).await
+        Ok(response.bytes_stream().map(|bytes| Ok(bytes?)))
     }
 
     /// Get metadata for a specific function
@@ -409,16 +933,14 @@ impl Functions {
     pub async fn get_function_metadata(&self, function_name: &str) -> Result<FunctionMetadata> {
         debug!("Fetching metadata for function: {}", function_name);
 
-        let url = format!(
-            "{}/functions/v1/{}/metadata",
-            self.config.url, function_name
-        );
+        let url = format!("{}/{}/metadata", self.functions_url(), function_name);
 
         let response = self
-            .http_client
-            .get(&url)
-            .header("Authorization", format!("Bearer {}", self.config.key))
-            .send()
+            .circuit
+            .send(self.http_client.get(&url).header(
+                "Authorization",
+                self.token_provider.bearer(&self.config.key),
+            ))
             .await?;
 
         if !response.status().is_success() {
@@ -426,7 +948,7 @@ impl Functions {
             let error_msg = response.text().await.unwrap_or_else(|_| {
                 format!("Failed to fetch function metadata, status: {}", status)
             });
-            return Err(Error::functions(error_msg));
+            return Err(Error::functions(error_msg).with_fingerprint(self.config.error_fingerprint));
         }
 
         let metadata: FunctionMetadata = response.json().await?;
@@ -451,13 +973,14 @@ impl Functions {
     pub async fn list_functions(&self) -> Result<Vec<FunctionMetadata>> {
         debug!("Listing all available functions");
 
-        let url = format!("{}/functions/v1", self.config.url);
+        let url = self.functions_url();
 
         let response = self
-            .http_client
-            .get(&url)
-            .header("Authorization", format!("Bearer {}", self.config.key))
-            .send()
+            .circuit
+            .send(self.http_client.get(&url).header(
+                "Authorization",
+                self.token_provider.bearer(&self.config.key),
+            ))
             .await?;
 
         if !response.status().is_success() {
@@ -466,7 +989,7 @@ impl Functions {
                 .text()
                 .await
                 .unwrap_or_else(|_| format!("Failed to list functions, status: {}", status));
-            return Err(Error::functions(error_msg));
+            return Err(Error::functions(error_msg).with_fingerprint(self.config.error_fingerprint));
         }
 
         let functions: Vec<FunctionMetadata> = response.json().await?;
@@ -589,7 +1112,10 @@ impl Functions {
         let mut request = self
             .http_client
             .post(&url)
-            .header("Authorization", format!("Bearer {}", self.config.key))
+            .header(
+                "Authorization",
+                self.token_provider.bearer(&self.config.key),
+            )
             .header("Content-Type", "application/json")
             .header("X-Local-Test", "true");
 
@@ -597,7 +1123,7 @@ impl Functions {
             request = request.json(&body);
         }
 
-        let response = request.send().await?;
+        let response = self.circuit.send(request).await?;
 
         if !response.status().is_success() {
             let status = response.status();
@@ -605,7 +1131,7 @@ impl Functions {
                 .text()
                 .await
                 .unwrap_or_else(|_| format!("Local function test failed with status: {}", status));
-            return Err(Error::functions(error_msg));
+            return Err(Error::functions(error_msg).with_fingerprint(self.config.error_fingerprint));
         }
 
         let result: Value = response.json().await?;
@@ -614,9 +1140,40 @@ impl Functions {
         Ok(result)
     }
 
-    /// Get the base Functions URL
+    /// Get the base Functions URL used for invocations
+    ///
+    /// This is the local development server when a dev base URL is set.
     pub fn functions_url(&self) -> String {
-        format!("{}/functions/v1", self.config.url)
+        let base_url = self
+            .dev_base_url
+            .read()
+            .ok()
+            .and_then(|dev_base_url| dev_base_url.clone())
+            .unwrap_or_else(|| self.config.url.clone());
+
+        ProjectUrl::unchecked(base_url.trim_end_matches('/')).functions_url()
+    }
+
+    /// Route function invocations to a local `supabase functions serve` instance
+    ///
+    /// Pass `None` to go back to the project URL. While set, every Functions
+    /// call, including metadata and listing, goes to the dev base URL.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// # async fn example(functions: &supabase_lib_rs::Functions) -> supabase_lib_rs::Result<()> {
+    /// functions.set_dev_base_url(Some("http://localhost:54321"));
+    ///
+    /// // POST http://localhost:54321/functions/v1/hello
+    /// let result = functions.invoke("hello", None).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_dev_base_url(&self, base_url: Option<&str>) {
+        if let Ok(mut dev_base_url) = self.dev_base_url.write() {
+            *dev_base_url = base_url.map(str::to_string);
+        }
     }
 
     // Private helper methods
@@ -627,12 +1184,15 @@ impl Functions {
         body: Option<Value>,
         options: &InvokeOptions,
     ) -> Result<Value> {
-        let url = format!("{}/functions/v1/{}", self.config.url, function_name);
+        let url = format!("{}/{}", self.functions_url(), function_name);
 
         let mut request = self
             .http_client
             .post(&url)
-            .header("Authorization", format!("Bearer {}", self.config.key))
+            .header(
+                "Authorization",
+                self.token_provider.bearer(&self.config.key),
+            )
             .header("Content-Type", "application/json");
 
         // Add custom headers
@@ -652,108 +1212,203 @@ impl Functions {
             request = request.json(&body);
         }
 
-        let response = request.send().await?;
+        let response = self.circuit.send(request).await?;
 
         if !response.status().is_success() {
-            let status = response.status();
-            let error_msg = match response.text().await {
-                Ok(text) => {
-                    // Enhanced error parsing
-                    if let Ok(error_json) = serde_json::from_str::<Value>(&text) {
-                        self.parse_function_error(&error_json)
-                    } else {
-                        text
-                    }
-                }
-                Err(_) => format!("Function invocation failed with status: {}", status),
-            };
-            return Err(Error::functions(error_msg));
+            return Err(response_error(response, "POST")
+                .await
+                .with_fingerprint(self.config.error_fingerprint));
         }
 
-        let result: Value = response.json().await?;
-        Ok(result)
+        negotiate_response(response).await
     }
 
+    /// Send a streaming invocation, failing on an error status
     #[cfg(not(target_arch = "wasm32"))]
-    async fn process_stream(
+    async fn send_streaming(
         &self,
-        response: Response,
-    ) -> Result<impl Stream<Item = Result<StreamChunk>>> {
-        use tokio_stream::StreamExt;
-
-        // Simplified streaming - read response as text and split by lines
-        let text = response.text().await?;
-        let lines: Vec<String> = text.lines().map(|s| s.to_string()).collect();
-
-        let stream = tokio_stream::iter(lines.into_iter().map(Ok::<String, Error>));
-
-        Ok(
-            stream.map(|line_result: Result<String>| -> Result<StreamChunk> {
-                let line = line_result?;
-
-                // Parse Server-Sent Events format
-                if let Some(data_str) = line.strip_prefix("data: ") {
-                    // Remove "data: " prefix
-                    if data_str == "[DONE]" {
-                        return Ok(StreamChunk {
-                            data: Value::Null,
-                            sequence: None,
-                            is_final: true,
-                        });
-                    }
+        function_name: &str,
+        body: Option<Value>,
+        options: &InvokeOptions,
+        accept: Option<&str>,
+    ) -> Result<Response> {
+        let url = format!("{}/{}", self.functions_url(), function_name);
 
-                    let data: Value = serde_json::from_str(data_str).map_err(|e| {
-                        Error::functions(format!("Failed to parse stream data: {}", e))
-                    })?;
-
-                    Ok(StreamChunk {
-                        data,
-                        sequence: None,
-                        is_final: false,
-                    })
-                } else if !line.is_empty() && !line.starts_with(':') {
-                    // Skip non-data lines (event:, id:, etc.) and empty lines
-                    Ok(StreamChunk {
-                        data: Value::Null,
-                        sequence: None,
-                        is_final: false,
-                    })
-                } else {
-                    Ok(StreamChunk {
-                        data: Value::Null,
-                        sequence: None,
-                        is_final: false,
-                    })
-                }
-            }),
-        )
+        let mut request = self
+            .http_client
+            .post(&url)
+            .header(
+                "Authorization",
+                self.token_provider.bearer(&self.config.key),
+            )
+            .header("Content-Type", "application/json")
+            .header("Cache-Control", "no-cache");
+
+        if let Some(accept) = accept {
+            request = request.header("Accept", accept);
+        }
+
+        if let Some(custom_headers) = &options.headers {
+            for (key, value) in custom_headers {
+                request = request.header(key, value);
+            }
+        }
+
+        if let Some(timeout) = options.timeout {
+            request = request.timeout(timeout);
+        }
+
+        if let Some(body) = body {
+            request = request.json(&body);
+        }
+
+        let response = self.circuit.send(request).await?;
+
+        if !response.status().is_success() {
+            return Err(response_error(response, "POST")
+                .await
+                .with_fingerprint(self.config.error_fingerprint));
+        }
+
+        Ok(response)
     }
+}
+
+/// Parse a response body as Server-Sent Events, chunk by chunk
+#[cfg(not(target_arch = "wasm32"))]
+fn sse_events(response: Response) -> impl Stream<Item = Result<StreamChunk>> {
+    let state = (
+        Box::pin(response.bytes_stream()),
+        SseParser::default(),
+        VecDeque::new(),
+        false,
+    );
+
+    // Boxed so callers can poll it without pinning it themselves
+    Box::pin(futures_util::stream::unfold(
+        state,
+        |(mut bytes, mut parser, mut pending, mut ended)| async move {
+            loop {
+                if let Some(event) = pending.pop_front() {
+                    return Some((event, (bytes, parser, pending, ended)));
+                }
+                if ended {
+                    return None;
+                }
 
-    fn parse_function_error(&self, error_json: &Value) -> String {
-        // Enhanced error parsing for different error formats
-        if let Some(message) = error_json.get("error") {
-            if let Some(details) = message.get("message") {
-                return details.as_str().unwrap_or("Unknown error").to_string();
+                match bytes.next().await {
+                    Some(Ok(chunk)) => pending.extend(parser.push(&chunk)),
+                    Some(Err(e)) => {
+                        pending.push_back(Err(e.into()));
+                        ended = true;
+                    }
+                    None => {
+                        pending.extend(parser.finish());
+                        ended = true;
+                    }
+                }
             }
-            return message.as_str().unwrap_or("Unknown error").to_string();
+        },
+    ))
+}
+
+/// Incremental Server-Sent Events parser
+///
+/// Bytes may split lines and events anywhere; an event is complete at the
+/// blank line following its fields.
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug, Default)]
+struct SseParser {
+    /// Bytes of the current, unterminated line
+    line: Vec<u8>,
+    /// `data` fields of the current event
+    data: Vec<String>,
+    /// `id` field of the current event
+    id: Option<String>,
+    /// Events dispatched so far
+    count: u64,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl SseParser {
+    /// Feed received bytes, returning the events they complete
+    fn push(&mut self, bytes: &[u8]) -> Vec<Result<StreamChunk>> {
+        let mut events = Vec::new();
+        for &byte in bytes {
+            if byte != b'\n' {
+                self.line.push(byte);
+                continue;
+            }
+
+            let mut line = std::mem::take(&mut self.line);
+            if line.last() == Some(&b'\r') {
+                line.pop();
+            }
+            events.extend(self.process_line(&String::from_utf8_lossy(&line)));
+        }
+        events
+    }
+
+    /// Dispatch whatever the stream ended in the middle of
+    fn finish(&mut self) -> Vec<Result<StreamChunk>> {
+        let mut events = self.push(b"\n");
+        events.extend(self.dispatch());
+        events
+    }
+
+    fn process_line(&mut self, line: &str) -> Option<Result<StreamChunk>> {
+        if line.is_empty() {
+            return self.dispatch();
+        }
+        // Comment, e.g. a keep-alive
+        if line.starts_with(':') {
+            return None;
         }
 
-        if let Some(message) = error_json.get("message") {
-            return message.as_str().unwrap_or("Unknown error").to_string();
+        let (field, value) = line.split_once(':').unwrap_or((line, ""));
+        let value = value.strip_prefix(' ').unwrap_or(value);
+        match field {
+            "data" => self.data.push(value.to_string()),
+            "id" => self.id = Some(value.to_string()),
+            // `event` and `retry` do not affect the chunk
+            _ => {}
         }
+        None
+    }
 
-        if let Some(details) = error_json.get("details") {
-            return details.as_str().unwrap_or("Unknown error").to_string();
+    fn dispatch(&mut self) -> Option<Result<StreamChunk>> {
+        let id = self.id.take();
+        if self.data.is_empty() {
+            return None;
         }
 
-        "Function execution failed".to_string()
+        let data = std::mem::take(&mut self.data).join("\n");
+        let sequence = id.and_then(|id| id.parse().ok()).unwrap_or(self.count);
+        self.count += 1;
+
+        if data == "[DONE]" {
+            return Some(Ok(StreamChunk {
+                data: Value::Null,
+                sequence: Some(sequence),
+                is_final: true,
+            }));
+        }
+
+        let data = serde_json::from_str(&data).unwrap_or(Value::String(data));
+        Some(Ok(StreamChunk {
+            data,
+            sequence: Some(sequence),
+            is_final: false,
+        }))
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::types::{AuthConfig, DatabaseConfig, HttpConfig, StorageConfig, SupabaseConfig};
+    use crate::types::{
+        AuthConfig, DatabaseConfig, FunctionsConfig, HttpConfig, StorageConfig, SupabaseConfig,
+    };
 
     fn create_test_functions() -> Functions {
         let config = Arc::new(SupabaseConfig {
@@ -764,6 +1419,8 @@ mod tests {
             auth_config: AuthConfig::default(),
             database_config: DatabaseConfig::default(),
             storage_config: StorageConfig::default(),
+            functions_config: FunctionsConfig::default(),
+            error_fingerprint: true,
         });
 
         let http_client = Arc::new(HttpClient::new());
@@ -787,4 +1444,393 @@ mod tests {
             "http://localhost:54321/functions/v1"
         );
     }
+
+    #[tokio::test]
+    async fn test_error_bodies_and_raw_responses() {
+        use crate::mock_http::{MockServer, Response};
+
+        let server = MockServer::start([
+            Response::new(422)
+                .header("content-type", "application/json")
+                .body(r#"{"error":{"code":"invalid_plan","message":"Unknown plan"}}"#),
+            Response::new(200)
+                .header("content-type", "text/plain; charset=utf-8")
+                .body("pong"),
+            Response::new(200)
+                .header("content-type", "text/csv")
+                .body("id,name\n1,Ada\n"),
+        ])
+        .await;
+
+        let functions = Functions::new(
+            Arc::new(SupabaseConfig {
+                url: server.url(),
+                ..Default::default()
+            }),
+            Arc::new(HttpClient::new()),
+        )
+        .unwrap();
+
+        let error = functions.invoke("subscribe", None).await.unwrap_err();
+        assert_eq!(error.status_code(), Some(422));
+        let function_error = error.function_error().unwrap();
+        assert_eq!(function_error.code.as_deref(), Some("invalid_plan"));
+        assert_eq!(function_error.message, "Unknown plan");
+
+        assert_eq!(functions.invoke("ping", None).await.unwrap(), "pong");
+
+        let raw = functions
+            .invoke_raw("export", Method::GET, None, None)
+            .await
+            .unwrap();
+        assert!(raw.is_success());
+        assert_eq!(raw.content_type(), Some("text/csv"));
+        assert_eq!(raw.text(), "id,name\n1,Ada\n");
+    }
+
+    #[tokio::test]
+    async fn test_function_errors_do_not_open_circuit() {
+        use crate::circuit::{CircuitBreakerConfig, CircuitState};
+        use crate::mock_http::{MockServer, Response};
+
+        let server = MockServer::start([
+            Response::new(500).body("boom"),
+            Response::new(500).body("boom"),
+            Response::json(r#"{"ok":true}"#),
+        ])
+        .await;
+
+        let functions = Functions::new(
+            Arc::new(SupabaseConfig {
+                url: server.url(),
+                http_config: crate::types::HttpConfig {
+                    circuit_breaker: Some(CircuitBreakerConfig {
+                        failure_threshold: 1,
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                },
+                ..Default::default()
+            }),
+            Arc::new(HttpClient::new()),
+        )
+        .unwrap();
+
+        assert!(functions.invoke("broken", None).await.is_err());
+        assert!(functions.invoke("broken", None).await.is_err());
+        assert_eq!(functions.circuit_breaker().state(), CircuitState::Closed);
+        assert_eq!(
+            functions.invoke("healthy", None).await.unwrap(),
+            serde_json::json!({"ok": true})
+        );
+    }
+
+    #[tokio::test]
+    async fn test_invoke_stream_is_incremental() {
+        use crate::mock_http::{MockServer, Response};
+
+        let (chunks, body) = tokio::sync::mpsc::unbounded_channel();
+        chunks
+            .send(b": keep-alive\n\ndata: {\"token\":\"Hel\"}\n\n".to_vec())
+            .unwrap();
+        let server = MockServer::start([
+            Response::streamed(200, body).header("content-type", "text/event-stream")
+        ])
+        .await;
+
+        let functions = Functions::new(
+            Arc::new(SupabaseConfig {
+                url: server.url(),
+                ..Default::default()
+            }),
+            Arc::new(HttpClient::new()),
+        )
+        .unwrap();
+        let options = InvokeOptions {
+            headers: Some(HashMap::from([(
+                "X-Model".to_string(),
+                "small".to_string(),
+            )])),
+            timeout: Some(Duration::from_secs(10)),
+            ..Default::default()
+        };
+
+        let mut stream = functions
+            .invoke_stream_with_options("llm-proxy", Some(serde_json::json!({"q": 1})), options)
+            .await
+            .unwrap();
+
+        let first = stream.next().await.unwrap().unwrap();
+        assert_eq!(first.data, serde_json::json!({"token": "Hel"}));
+        assert_eq!(first.sequence, Some(0));
+        assert!(!first.is_final);
+        // The rest is only sent once the client has seen the first event
+        for part in [
+            "id: 7\r\nevent: token\r\ndata: lo",
+            "\r\n\r\ndata: line one\ndata: line two\n\n",
+            "data: [DONE]\n\n",
+        ] {
+            chunks.send(part.as_bytes().to_vec()).unwrap();
+        }
+        drop(chunks);
+
+        let second = stream.next().await.unwrap().unwrap();
+        assert_eq!(second.data, "lo");
+        assert_eq!(second.sequence, Some(7));
+
+        let third = stream.next().await.unwrap().unwrap();
+        assert_eq!(third.data, "line one\nline two");
+        assert_eq!(third.sequence, Some(2));
+
+        let last = stream.next().await.unwrap().unwrap();
+        assert!(last.is_final);
+        assert!(stream.next().await.is_none());
+
+        let request = &server.requests()[0];
+        assert_eq!(request.line(), "POST /functions/v1/llm-proxy");
+        assert_eq!(request.header("accept"), Some("text/event-stream"));
+        assert_eq!(request.header("x-model"), Some("small"));
+    }
+
+    #[test]
+    fn test_cache_policy_and_keys() {
+        let policy = CachePolicy {
+            ttl: Duration::from_secs(60),
+            swr: Duration::from_secs(300),
+        };
+        assert_eq!(policy.freshness(Duration::ZERO), Freshness::Fresh);
+        assert_eq!(policy.freshness(Duration::from_secs(60)), Freshness::Stale);
+        assert_eq!(policy.freshness(Duration::from_secs(359)), Freshness::Stale);
+        assert_eq!(
+            policy.freshness(Duration::from_secs(360)),
+            Freshness::Expired
+        );
+
+        struct Token(std::sync::Mutex<Option<String>>);
+
+        impl TokenProvider for Token {
+            fn access_token(&self) -> Option<String> {
+                self.0.lock().unwrap().clone()
+            }
+        }
+
+        let token = Arc::new(Token(std::sync::Mutex::new(None)));
+        let functions = create_test_functions().with_token_provider(token.clone());
+        let body = serde_json::json!({"season": 3});
+        let key = functions.cache_key("leaderboard", Some(&body));
+        assert_eq!(key, functions.cache_key("leaderboard", Some(&body.clone())));
+        assert_ne!(key, functions.cache_key("leaderboard", None));
+        assert_ne!(key, functions.cache_key("stats", Some(&body)));
+        assert_ne!(
+            key,
+            functions.cache_key("leaderboard", Some(&serde_json::json!({"season": 4})))
+        );
+
+        // Another endpoint or user never shares a response
+        functions.set_dev_base_url(Some("http://localhost:9000"));
+        assert_ne!(key, functions.cache_key("leaderboard", Some(&body)));
+        functions.set_dev_base_url(None);
+        *token.0.lock().unwrap() = Some("opaque-token".to_string());
+        let user_key = functions.cache_key("leaderboard", Some(&body));
+        assert_ne!(key, user_key);
+        assert_eq!(user_key.principal.as_deref(), Some("token:opaque-token"));
+
+        // Refreshed tokens of the same user share the subject
+        let jwt = |payload: serde_json::Value| {
+            use base64::Engine;
+            format!(
+                "header.{}.signature",
+                base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(payload.to_string())
+            )
+        };
+        assert_eq!(
+            token_principal(&jwt(serde_json::json!({"sub": "user-1", "exp": 1}))),
+            token_principal(&jwt(serde_json::json!({"sub": "user-1", "exp": 2})))
+        );
+        assert_ne!(
+            token_principal(&jwt(serde_json::json!({"sub": "user-1"}))),
+            token_principal(&jwt(serde_json::json!({"sub": "user-2"})))
+        );
+        *token.0.lock().unwrap() = None;
+
+        functions.cache.write().unwrap().insert(
+            functions.cache_key("leaderboard", None),
+            CachedResponse {
+                value: serde_json::json!([]),
+                fetched_at: chrono::Utc::now(),
+                refreshing: false,
+            },
+        );
+        let copy = functions.clone();
+        copy.invalidate_cache(Some("stats"));
+        assert_eq!(functions.cache.read().unwrap().len(), 1);
+        copy.invalidate_cache(Some("leaderboard"));
+        assert!(functions.cache.read().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    #[cfg(feature = "auth-core")]
+    async fn test_cache_not_shared_between_users() {
+        use crate::mock_http::{MockServer, Response};
+
+        let session = |user: &str| {
+            serde_json::json!({
+                "access_token": format!("access-{}", user),
+                "refresh_token": format!("refresh-{}", user),
+                "token_type": "bearer",
+                "expires_in": 3600,
+                "expires_at": 4102444800u64,
+                "user": {
+                    "id": uuid::Uuid::new_v4(),
+                    "aud": "authenticated",
+                    "email": format!("{}@example.com", user),
+                    "created_at": "2025-01-01T00:00:00Z",
+                    "updated_at": "2025-01-01T00:00:00Z",
+                    "app_metadata": {},
+                    "user_metadata": {}
+                }
+            })
+            .to_string()
+        };
+        let server = MockServer::start([
+            Response::json(session("a")),
+            Response::json(r#"{"user":"a"}"#),
+            Response::json(session("b")),
+            Response::json(r#"{"user":"b"}"#),
+        ])
+        .await;
+
+        let client = crate::Client::new(&server.url(), "test-key").unwrap();
+        let policy = CachePolicy {
+            ttl: Duration::from_secs(60),
+            swr: Duration::ZERO,
+        };
+
+        client
+            .auth()
+            .sign_in_with_email_and_password("a@example.com", "password")
+            .await
+            .unwrap();
+        let first = client
+            .functions()
+            .invoke_cached("profile", None, policy)
+            .await
+            .unwrap();
+        let cached = client
+            .functions()
+            .invoke_cached("profile", None, policy)
+            .await
+            .unwrap();
+        assert_eq!(first, serde_json::json!({"user": "a"}));
+        assert_eq!(cached, first);
+        assert_eq!(server.requests().len(), 2);
+
+        client
+            .auth()
+            .sign_in_with_email_and_password("b@example.com", "password")
+            .await
+            .unwrap();
+        let second = client
+            .functions()
+            .invoke_cached("profile", None, policy)
+            .await
+            .unwrap();
+        assert_eq!(second, serde_json::json!({"user": "b"}));
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 4);
+        assert_eq!(requests[3].line(), "POST /functions/v1/profile");
+        assert_eq!(requests[3].header("authorization"), Some("Bearer access-b"));
+        // Signing in as b dropped the response cached for a
+        assert_eq!(client.functions().cache.read().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_cache_eviction() {
+        let functions = create_test_functions();
+        let cache_key = |name| functions.cache_key(name, None);
+        let entry = |age_secs| CachedResponse {
+            value: serde_json::json!(age_secs),
+            fetched_at: chrono::Utc::now() - chrono::Duration::seconds(age_secs),
+            refreshing: false,
+        };
+        let mut cache = HashMap::from([
+            (cache_key("a"), entry(30)),
+            (cache_key("b"), entry(10)),
+            (cache_key("c"), entry(20)),
+        ]);
+
+        // Replacing a cached response needs no room
+        evict_for(&mut cache, &cache_key("b"), 3);
+        assert_eq!(cache.len(), 3);
+
+        // The response fetched longest ago goes first
+        evict_for(&mut cache, &cache_key("d"), 3);
+        assert_eq!(cache.len(), 2);
+        assert!(!cache.contains_key(&cache_key("a")));
+
+        // A shrunk capacity evicts down to one below it
+        evict_for(&mut cache, &cache_key("d"), 1);
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn test_dev_base_url_routing() {
+        let config = Arc::new(SupabaseConfig {
+            url: "https://project.supabase.co".to_string(),
+            functions_config: FunctionsConfig {
+                dev_base_url: Some("http://localhost:54321/".to_string()),
+                ..Default::default()
+            },
+            ..Default::default()
+        });
+        let functions = Functions::new(config, Arc::new(HttpClient::new())).unwrap();
+        assert_eq!(
+            functions.functions_url(),
+            "http://localhost:54321/functions/v1"
+        );
+
+        functions.set_dev_base_url(Some("http://127.0.0.1:9000"));
+        assert_eq!(
+            functions.functions_url(),
+            "http://127.0.0.1:9000/functions/v1"
+        );
+
+        functions.set_dev_base_url(None);
+        assert_eq!(
+            functions.functions_url(),
+            "https://project.supabase.co/functions/v1"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_dev_base_url_routes_metadata() {
+        use crate::mock_http::{MockServer, Response};
+
+        let metadata = r#"{"name":"hello","env_vars":{},"status":"active"}"#;
+        let server = MockServer::start([
+            Response::json(metadata),
+            Response::json(format!("[{}]", metadata)),
+        ])
+        .await;
+        let config = Arc::new(SupabaseConfig {
+            url: "https://project.supabase.co".to_string(),
+            functions_config: FunctionsConfig {
+                dev_base_url: Some(server.url()),
+                ..Default::default()
+            },
+            ..Default::default()
+        });
+        let functions = Functions::new(config, Arc::new(HttpClient::new())).unwrap();
+
+        assert_eq!(
+            functions.get_function_metadata("hello").await.unwrap().name,
+            "hello"
+        );
+        assert_eq!(functions.list_functions().await.unwrap().len(), 1);
+
+        let requests = server.requests();
+        assert_eq!(requests[0].line(), "GET /functions/v1/hello/metadata");
+        assert_eq!(requests[1].line(), "GET /functions/v1");
+    }
 }
@@ -0,0 +1,204 @@
+//! GeoJSON geometry types for PostGIS columns
+//!
+//! PostGIS 3 casts geometry and geography values to GeoJSON, so selecting a
+//! column as `location::json` returns a value that deserializes into
+//! [`Geometry`].
+
+use serde::{Deserialize, Serialize};
+
+/// Spatial reference used for all coordinates: WGS 84 longitude/latitude
+pub const SRID: u32 = 4326;
+
+/// A GeoJSON position: longitude, latitude and optional altitude
+pub type Position = Vec<f64>;
+
+/// GeoJSON geometry object (RFC 7946)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum Geometry {
+    /// A single position
+    Point {
+        /// The point's position
+        coordinates: Position,
+    },
+    /// Unconnected positions
+    MultiPoint {
+        /// One position per point
+        coordinates: Vec<Position>,
+    },
+    /// A path through two or more positions
+    LineString {
+        /// The path's positions in order
+        coordinates: Vec<Position>,
+    },
+    /// Unconnected paths
+    MultiLineString {
+        /// One list of positions per path
+        coordinates: Vec<Vec<Position>>,
+    },
+    /// An area bounded by closed rings
+    Polygon {
+        /// The exterior ring followed by any holes, each ending where it starts
+        coordinates: Vec<Vec<Position>>,
+    },
+    /// Unconnected areas
+    MultiPolygon {
+        /// One list of rings per polygon
+        coordinates: Vec<Vec<Vec<Position>>>,
+    },
+    /// Geometries of any type, grouped
+    GeometryCollection {
+        /// The grouped geometries
+        geometries: Vec<Geometry>,
+    },
+}
+
+impl Geometry {
+    /// Create a point geometry
+    pub fn point(longitude: f64, latitude: f64) -> Self {
+        Point::new(longitude, latitude).into()
+    }
+}
+
+/// A WGS 84 point
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Point {
+    /// Degrees east of Greenwich, from -180 to 180
+    pub longitude: f64,
+    /// Degrees north of the equator, from -90 to 90
+    pub latitude: f64,
+}
+
+impl Point {
+    /// Create a point from longitude and latitude
+    pub fn new(longitude: f64, latitude: f64) -> Self {
+        Self {
+            longitude,
+            latitude,
+        }
+    }
+
+    /// Extended WKT form accepted by PostGIS input functions
+    pub fn to_ewkt(&self) -> String {
+        format!("SRID={};POINT({} {})", SRID, self.longitude, self.latitude)
+    }
+}
+
+impl From<Point> for Geometry {
+    fn from(point: Point) -> Self {
+        Geometry::Point {
+            coordinates: vec![point.longitude, point.latitude],
+        }
+    }
+}
+
+impl TryFrom<&Geometry> for Point {
+    type Error = crate::Error;
+
+    fn try_from(geometry: &Geometry) -> crate::Result<Self> {
+        match geometry {
+            Geometry::Point { coordinates } if coordinates.len() >= 2 => {
+                Ok(Point::new(coordinates[0], coordinates[1]))
+            }
+            _ => Err(crate::Error::invalid_input(
+                "Geometry is not a two-dimensional point",
+            )),
+        }
+    }
+}
+
+/// An axis-aligned WGS 84 bounding box
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BoundingBox {
+    /// Western edge
+    pub min_longitude: f64,
+    /// Southern edge
+    pub min_latitude: f64,
+    /// Eastern edge
+    pub max_longitude: f64,
+    /// Northern edge
+    pub max_latitude: f64,
+}
+
+impl BoundingBox {
+    /// Create a bounding box from its south-west and north-east corners
+    pub fn new(
+        min_longitude: f64,
+        min_latitude: f64,
+        max_longitude: f64,
+        max_latitude: f64,
+    ) -> Self {
+        Self {
+            min_longitude,
+            min_latitude,
+            max_longitude,
+            max_latitude,
+        }
+    }
+
+    /// Extended WKT polygon accepted by PostGIS input functions
+    pub fn to_ewkt(&self) -> String {
+        let (x1, y1, x2, y2) = (
+            self.min_longitude,
+            self.min_latitude,
+            self.max_longitude,
+            self.max_latitude,
+        );
+        format!(
+            "SRID={};POLYGON(({x1} {y1},{x2} {y1},{x2} {y2},{x1} {y2},{x1} {y1}))",
+            SRID
+        )
+    }
+}
+
+impl From<BoundingBox> for Geometry {
+    fn from(bbox: BoundingBox) -> Self {
+        let (x1, y1, x2, y2) = (
+            bbox.min_longitude,
+            bbox.min_latitude,
+            bbox.max_longitude,
+            bbox.max_latitude,
+        );
+        Geometry::Polygon {
+            coordinates: vec![vec![
+                vec![x1, y1],
+                vec![x2, y1],
+                vec![x2, y2],
+                vec![x1, y2],
+                vec![x1, y1],
+            ]],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_geojson_round_trip() {
+        let polygon: Geometry = BoundingBox::new(0.0, 0.0, 1.0, 2.0).into();
+        let value = serde_json::to_value(&polygon).unwrap();
+        assert_eq!(
+            value,
+            json!({
+                "type": "Polygon",
+                "coordinates": [[[0.0, 0.0], [1.0, 0.0], [1.0, 2.0], [0.0, 2.0], [0.0, 0.0]]]
+            })
+        );
+        assert_eq!(serde_json::from_value::<Geometry>(value).unwrap(), polygon);
+
+        let collection: Geometry = serde_json::from_value(json!({
+            "type": "GeometryCollection",
+            "geometries": [{"type": "Point", "coordinates": [13.4, 52.5, 34.0]}]
+        }))
+        .unwrap();
+        let Geometry::GeometryCollection { geometries } = collection else {
+            panic!("expected a geometry collection");
+        };
+        let point = Point::try_from(&geometries[0]).unwrap();
+        assert_eq!(point, Point::new(13.4, 52.5));
+        assert_eq!(point.to_ewkt(), "SRID=4326;POINT(13.4 52.5)");
+    }
+}
@@ -0,0 +1,536 @@
+//! GraphQL module for the Supabase `pg_graphql` API
+//!
+//! Sends queries and mutations to `/graphql/v1` with the signed-in user's
+//! access token, so Row Level Security applies exactly as it does for REST
+//! requests. GraphQL errors in the response become [`Error::Database`]; the
+//! individual errors are available from [`Error::graphql_errors`].
+//!
+//! ```rust,no_run
+//! use serde::Deserialize;
+//! use serde_json::json;
+//! use supabase_lib_rs::Client;
+//!
+//! #[derive(Deserialize)]
+//! #[serde(rename_all = "camelCase")]
+//! struct Posts {
+//!     posts_collection: Collection,
+//! }
+//!
+//! #[derive(Deserialize)]
+//! struct Collection {
+//!     edges: Vec<serde_json::Value>,
+//! }
+//!
+//! # async fn example() -> supabase_lib_rs::Result<()> {
+//! let client = Client::new("https://example.supabase.co", "your-anon-key")?;
+//!
+//! let posts: Posts = client
+//!     .graphql()
+//!     .query(
+//!         "query Posts($first: Int!) { postsCollection(first: $first) { edges { node { id title } } } }",
+//!         json!({ "first": 10 }),
+//!     )
+//!     .await?;
+//! println!("{} posts", posts.posts_collection.edges.len());
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{
+    circuit::CircuitBreaker,
+    error::{Error, ErrorContext, HttpErrorContext, Result},
+    types::{RequestAuth, SupabaseConfig, TokenProvider},
+};
+use reqwest::Client as HttpClient;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::sync::Arc;
+use tracing::{debug, info};
+
+/// GraphQL client for the `pg_graphql` endpoint
+#[derive(Debug, Clone)]
+pub struct GraphQL {
+    http_client: Arc<HttpClient>,
+    config: Arc<SupabaseConfig>,
+    token_provider: RequestAuth,
+    circuit: CircuitBreaker,
+    persisted_queries: bool,
+}
+
+/// A GraphQL operation with its variables
+#[derive(Debug, Clone, PartialEq)]
+pub struct GraphQLRequest {
+    /// Query or mutation document
+    pub query: String,
+    /// Variables, always a JSON object when set
+    pub variables: Option<Value>,
+    /// Operation to run when the document defines several
+    pub operation_name: Option<String>,
+}
+
+impl GraphQLRequest {
+    /// Request for `query` without variables
+    pub fn new(query: impl Into<String>) -> Self {
+        Self {
+            query: query.into(),
+            variables: None,
+            operation_name: None,
+        }
+    }
+
+    /// Set the variables from anything that serializes to a JSON object
+    ///
+    /// Values serializing to `null`, such as `()` or `None`, clear the
+    /// variables; anything else that is not an object is rejected.
+    pub fn with_variables<V: Serialize>(mut self, variables: V) -> Result<Self> {
+        self.variables = match serde_json::to_value(variables)? {
+            Value::Null => None,
+            Value::Object(variables) => Some(Value::Object(variables)),
+            other => {
+                return Err(Error::invalid_input(format!(
+                    "GraphQL variables must serialize to an object, got {}",
+                    other
+                )))
+            }
+        };
+        Ok(self)
+    }
+
+    /// Select the operation to run
+    pub fn with_operation_name(mut self, operation_name: impl Into<String>) -> Self {
+        self.operation_name = Some(operation_name.into());
+        self
+    }
+
+    /// SHA-256 of the document, as used by persisted queries
+    pub fn query_hash(&self) -> String {
+        format!("{:x}", Sha256::digest(self.query.as_bytes()))
+    }
+}
+
+/// Response body of a GraphQL operation
+#[derive(Debug, Clone, Deserialize)]
+pub struct GraphQLResponse<T> {
+    /// Result of the operation; may be partial when there are errors
+    pub data: Option<T>,
+    /// Errors raised while running the operation
+    #[serde(default)]
+    pub errors: Vec<GraphQLError>,
+}
+
+/// An error reported in a GraphQL response
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct GraphQLError {
+    /// Error message
+    pub message: String,
+    /// Positions in the document the error refers to
+    #[serde(default)]
+    pub locations: Vec<GraphQLLocation>,
+    /// Path of the response field that failed, as field names and list indices
+    #[serde(default)]
+    pub path: Vec<Value>,
+    /// Server-specific details, e.g. an error `code`
+    #[serde(default)]
+    pub extensions: Option<Value>,
+}
+
+/// Position in a GraphQL document
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub struct GraphQLLocation {
+    /// Line, starting at 1
+    pub line: u32,
+    /// Column, starting at 1
+    pub column: u32,
+}
+
+impl GraphQLError {
+    /// Whether the server does not know a persisted query hash yet
+    fn is_persisted_query_not_found(&self) -> bool {
+        let code = self
+            .extensions
+            .as_ref()
+            .and_then(|extensions| extensions.get("code"))
+            .and_then(Value::as_str);
+        code == Some("PERSISTED_QUERY_NOT_FOUND") || self.message == "PersistedQueryNotFound"
+    }
+}
+
+/// Body as sent on the wire
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct RequestBody<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    query: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    variables: Option<&'a Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    operation_name: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    extensions: Option<Value>,
+}
+
+/// Raw outcome of one operation, kept for error context
+struct Exchange {
+    url: String,
+    status: u16,
+    text: String,
+    response: GraphQLResponse<Value>,
+}
+
+impl GraphQL {
+    /// Create a new GraphQL instance
+    pub fn new(config: Arc<SupabaseConfig>, http_client: Arc<HttpClient>) -> Result<Self> {
+        Ok(Self::with_http_client(config, http_client))
+    }
+
+    /// Create the GraphQL module of a [`Client`](crate::Client), which cannot fail
+    pub(crate) fn with_http_client(
+        config: Arc<SupabaseConfig>,
+        http_client: Arc<HttpClient>,
+    ) -> Self {
+        debug!("Initializing GraphQL module");
+
+        let circuit = CircuitBreaker::new("graphql", config.http_config.circuit_breaker)
+            .with_retry(config.http_config.retry.clone())
+            .with_fingerprint(config.error_fingerprint);
+
+        Self {
+            http_client,
+            config,
+            token_provider: RequestAuth::default(),
+            circuit,
+            persisted_queries: false,
+        }
+    }
+
+    /// Circuit breaker guarding requests to the GraphQL endpoint
+    pub fn circuit_breaker(&self) -> &CircuitBreaker {
+        &self.circuit
+    }
+
+    /// Authorize requests with the access token from `provider`
+    ///
+    /// [`Client`](crate::Client) sets this to its auth session, so operations
+    /// run as the signed-in user. Without a token the API key is sent.
+    pub fn with_token_provider(mut self, provider: Arc<dyn TokenProvider>) -> Self {
+        self.token_provider = RequestAuth::new(provider);
+        self
+    }
+
+    /// Send persisted query hashes instead of full documents
+    ///
+    /// Uses the automatic persisted query protocol: each operation is first
+    /// sent as the SHA-256 of its document, and only when the server answers
+    /// `PersistedQueryNotFound` is it sent again with the document, which the
+    /// server then stores. Needs a gateway in front of `pg_graphql` that
+    /// supports the protocol.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use supabase_lib_rs::Client;
+    ///
+    /// let client = Client::new("https://example.supabase.co", "your-anon-key")?;
+    /// let graphql = client.graphql().clone().with_persisted_queries(true);
+    /// # Ok::<(), supabase_lib_rs::Error>(())
+    /// ```
+    pub fn with_persisted_queries(mut self, enabled: bool) -> Self {
+        self.persisted_queries = enabled;
+        self
+    }
+
+    /// Get the GraphQL endpoint URL
+    pub fn graphql_url(&self) -> String {
+        self.config.project_url().graphql_url()
+    }
+
+    /// Run a query and deserialize its `data` into `T`
+    ///
+    /// `variables` may be anything that serializes to a JSON object, or `()`
+    /// for none. Any error in the response fails the whole call.
+    pub async fn query<T, V>(&self, query: &str, variables: V) -> Result<T>
+    where
+        T: DeserializeOwned,
+        V: Serialize,
+    {
+        let request = GraphQLRequest::new(query).with_variables(variables)?;
+        self.run(&request).await
+    }
+
+    /// Run a mutation and deserialize its `data` into `T`
+    ///
+    /// Mutations are sent like queries, but are never replayed by the
+    /// [retry policy](crate::retry) once the server may have received them.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use serde_json::{json, Value};
+    /// # use supabase_lib_rs::Client;
+    ///
+    /// # async fn example(client: Client) -> supabase_lib_rs::Result<()> {
+    /// let created: Value = client
+    ///     .graphql()
+    ///     .mutate(
+    ///         "mutation Add($title: String!) {
+    ///             insertIntoPostsCollection(objects: [{ title: $title }]) { affectedCount }
+    ///         }",
+    ///         json!({ "title": "Hello" }),
+    ///     )
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn mutate<T, V>(&self, mutation: &str, variables: V) -> Result<T>
+    where
+        T: DeserializeOwned,
+        V: Serialize,
+    {
+        let request = GraphQLRequest::new(mutation).with_variables(variables)?;
+        self.run(&request).await
+    }
+
+    /// Run an operation and return its `data` and `errors` as sent
+    ///
+    /// Unlike [`query`](Self::query), errors in the response do not fail the
+    /// call, so partial data can be used. Requests the server rejects as a
+    /// whole, e.g. with a non-2xx status and no GraphQL body, still fail.
+    pub async fn execute<T: DeserializeOwned>(
+        &self,
+        request: &GraphQLRequest,
+    ) -> Result<GraphQLResponse<T>> {
+        let exchange = self.exchange(request).await?;
+        let data = match exchange.response.data {
+            Some(Value::Null) | None => None,
+            Some(data) => Some(serde_json::from_value(data)?),
+        };
+
+        Ok(GraphQLResponse {
+            data,
+            errors: exchange.response.errors,
+        })
+    }
+
+    /// Run an operation that must succeed without errors
+    async fn run<T: DeserializeOwned>(&self, request: &GraphQLRequest) -> Result<T> {
+        let exchange = self.exchange(request).await?;
+        if !exchange.response.errors.is_empty() {
+            return Err(graphql_error(&exchange).with_fingerprint(self.config.error_fingerprint));
+        }
+
+        match exchange.response.data {
+            Some(data) => Ok(serde_json::from_value(data)?),
+            None => Err(Error::database("GraphQL response has no data")),
+        }
+    }
+
+    /// Send an operation, registering a persisted query when the server asks
+    async fn exchange(&self, request: &GraphQLRequest) -> Result<Exchange> {
+        debug!(
+            "Executing GraphQL operation: {}",
+            request.operation_name.as_deref().unwrap_or("<anonymous>")
+        );
+
+        if self.persisted_queries {
+            let exchange = self.send(request, false).await?;
+            let not_found = exchange
+                .response
+                .errors
+                .iter()
+                .any(GraphQLError::is_persisted_query_not_found);
+            if !not_found {
+                return Ok(exchange);
+            }
+            debug!("Registering persisted query {}", request.query_hash());
+        }
+
+        self.send(request, true).await
+    }
+
+    async fn send(&self, request: &GraphQLRequest, include_query: bool) -> Result<Exchange> {
+        let extensions = self.persisted_queries.then(|| {
+            json!({
+                "persistedQuery": { "version": 1, "sha256Hash": request.query_hash() }
+            })
+        });
+        let body = RequestBody {
+            query: include_query.then_some(request.query.as_str()),
+            variables: request.variables.as_ref(),
+            operation_name: request.operation_name.as_deref(),
+            extensions,
+        };
+
+        let url = self.graphql_url();
+        let http_request = self
+            .token_provider
+            .apply(self.http_client.post(&url))
+            .json(&body);
+
+        let response = self.circuit.send(http_request).await?;
+        let status = response.status().as_u16();
+        let text = response.text().await?;
+
+        // pg_graphql reports most failures as errors in a 200 response, but
+        // a GraphQL body on an error status is just as informative
+        let response = match serde_json::from_str::<GraphQLResponse<Value>>(&text) {
+            Ok(response) if (200..300).contains(&status) || !response.errors.is_empty() => response,
+            _ if (200..300).contains(&status) => {
+                return Err(Error::database(format!(
+                    "Invalid GraphQL response: {}",
+                    text
+                )))
+            }
+            _ => {
+                let message = if text.is_empty() {
+                    format!("GraphQL request failed with status: {}", status)
+                } else {
+                    text.clone()
+                };
+                return Err(Error::database_with_context(
+                    message,
+                    http_context(&url, status, &text),
+                )
+                .with_fingerprint(self.config.error_fingerprint));
+            }
+        };
+
+        info!("GraphQL operation completed with status {}", status);
+        Ok(Exchange {
+            url,
+            status,
+            text,
+            response,
+        })
+    }
+}
+
+fn http_context(url: &str, status: u16, text: &str) -> ErrorContext {
+    ErrorContext {
+        http: Some(HttpErrorContext {
+            status_code: Some(status),
+            headers: None,
+            response_body: Some(text.to_string()),
+            url: Some(url.to_string()),
+            method: Some("POST".to_string()),
+        }),
+        ..Default::default()
+    }
+}
+
+/// Database error listing the messages of all GraphQL errors
+fn graphql_error(exchange: &Exchange) -> Error {
+    let messages: Vec<&str> = exchange
+        .response
+        .errors
+        .iter()
+        .map(|error| error.message.as_str())
+        .collect();
+
+    Error::database_with_context(
+        format!("GraphQL error: {}", messages.join("; ")),
+        http_context(&exchange.url, exchange.status, &exchange.text),
+    )
+}
+
+/// GraphQL errors carried by a failed response body
+pub(crate) fn errors_from_body(body: &str) -> Option<Vec<GraphQLError>> {
+    serde_json::from_str::<GraphQLResponse<Value>>(body)
+        .ok()
+        .map(|response| response.errors)
+        .filter(|errors| !errors.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_variables() {
+        let request = GraphQLRequest::new("query { a }")
+            .with_variables(json!({"first": 2}))
+            .unwrap()
+            .with_operation_name("A");
+        assert_eq!(request.variables, Some(json!({"first": 2})));
+
+        assert!(GraphQLRequest::new("query { a }")
+            .with_variables(())
+            .unwrap()
+            .variables
+            .is_none());
+        assert!(matches!(
+            GraphQLRequest::new("query { a }").with_variables(vec![1, 2]),
+            Err(Error::InvalidInput { .. })
+        ));
+
+        assert_eq!(
+            GraphQLRequest::new("{ a }").query_hash(),
+            "1c7e1e347f726166b5b1c55afd61f278cc9b45e00c108ec33d540a566379811b"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_queries_errors_and_persisted_queries() {
+        use crate::mock_http::{MockServer, Response};
+
+        let server = MockServer::start([
+            Response::json(r#"{"data":{"posts":[{"id":1}]}}"#),
+            Response::json(
+                r#"{"data":null,"errors":[{"message":"Unknown field \"nope\"","locations":[{"line":1,"column":3}]}]}"#,
+            ),
+            Response::json(
+                r#"{"errors":[{"message":"PersistedQueryNotFound","extensions":{"code":"PERSISTED_QUERY_NOT_FOUND"}}]}"#,
+            ),
+            Response::json(r#"{"data":{"posts":[]}}"#),
+        ])
+        .await;
+
+        let graphql = GraphQL::new(
+            Arc::new(SupabaseConfig {
+                url: server.url(),
+                ..Default::default()
+            }),
+            Arc::new(HttpClient::new()),
+        )
+        .unwrap();
+
+        let data: Value = graphql
+            .query("query Posts($first: Int!) { posts }", json!({"first": 1}))
+            .await
+            .unwrap();
+        assert_eq!(data, json!({"posts": [{"id": 1}]}));
+
+        let error = graphql.query::<Value, _>("{ nope }", ()).await.unwrap_err();
+        assert!(matches!(error, Error::Database { .. }));
+        assert_eq!(error.status_code(), Some(200));
+        let errors = error.graphql_errors().unwrap();
+        assert_eq!(errors[0].message, "Unknown field \"nope\"");
+        assert_eq!(errors[0].locations[0].column, 3);
+
+        let persisted = graphql.clone().with_persisted_queries(true);
+        let response: GraphQLResponse<Value> = persisted
+            .execute(&GraphQLRequest::new("{ posts }"))
+            .await
+            .unwrap();
+        assert_eq!(response.data, Some(json!({"posts": []})));
+        assert!(response.errors.is_empty());
+
+        let requests: Vec<_> = server.requests();
+        assert_eq!(requests[0].line(), "POST /graphql/v1");
+        assert_eq!(
+            requests[0].text(),
+            r#"{"query":"query Posts($first: Int!) { posts }","variables":{"first":1}}"#
+        );
+        assert_eq!(requests[1].text(), r#"{"query":"{ nope }"}"#);
+        let requests: Vec<String> = requests.iter().map(|request| request.text()).collect();
+
+        let hash = GraphQLRequest::new("{ posts }").query_hash();
+        let extensions = format!(
+            r#""extensions":{{"persistedQuery":{{"sha256Hash":"{}","version":1}}}}"#,
+            hash
+        );
+        assert!(!requests[2].contains(r#""query""#));
+        assert!(requests[2].contains(&extensions));
+        assert!(requests[3].contains(r#""query":"{ posts }""#));
+        assert!(requests[3].contains(&extensions));
+    }
+}
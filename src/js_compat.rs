@@ -0,0 +1,373 @@
+//! supabase-js style API for porting TypeScript code
+//!
+//! Mirrors the shape of `@supabase/supabase-js` so existing code and
+//! documentation examples translate line by line: method names are the
+//! snake_case form of their JavaScript counterparts, and object arguments
+//! become structs with the same field names. Failures surface as
+//! [`Error`](crate::Error) instead of a `{ data, error }` pair.
+//!
+//! ```rust,no_run
+//! use supabase_lib_rs::js_compat::{create_client, SignInWithPasswordCredentials};
+//!
+//! # async fn example() -> supabase_lib_rs::Result<()> {
+//! // const supabase = createClient(url, key)
+//! let supabase = create_client("https://example.supabase.co", "your-anon-key")?;
+//!
+//! // await supabase.auth.signInWithPassword({ email, password })
+//! supabase
+//!     .auth
+//!     .sign_in_with_password(SignInWithPasswordCredentials {
+//!         email: "user@example.com".to_string(),
+//!         password: "password".to_string(),
+//!     })
+//!     .await?;
+//!
+//! // await supabase.from('posts').select('id,title').eq('published', true)
+//! let posts: Vec<serde_json::Value> = supabase
+//!     .from("posts")
+//!     .select("id,title")
+//!     .eq("published", "true")
+//!     .execute()
+//!     .await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{
+    auth::{Auth, AuthResponse, Session, User},
+    database::QueryBuilder,
+    error::Result,
+    functions::Functions,
+    storage::{FileObject, FileOptions, SignedUploadUrl, Storage, UploadResponse},
+    Client,
+};
+use bytes::Bytes;
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// Create a client, like `createClient(supabaseUrl, supabaseKey)`
+pub fn create_client(supabase_url: &str, supabase_key: &str) -> Result<SupabaseClient> {
+    Ok(SupabaseClient::from_client(Client::new(
+        supabase_url,
+        supabase_key,
+    )?))
+}
+
+/// Client with supabase-js naming, as returned by [`create_client`]
+#[derive(Debug, Clone)]
+pub struct SupabaseClient {
+    /// `supabase.auth`
+    pub auth: AuthClient,
+    /// `supabase.storage`
+    pub storage: StorageClient,
+    /// `supabase.functions`
+    pub functions: FunctionsClient,
+    client: Client,
+}
+
+impl SupabaseClient {
+    /// Wrap an existing client
+    pub fn from_client(client: Client) -> Self {
+        Self {
+            auth: AuthClient(client.auth().clone()),
+            storage: StorageClient(client.storage().clone()),
+            functions: FunctionsClient(client.functions().clone()),
+            client,
+        }
+    }
+
+    /// The underlying client, for APIs without a supabase-js counterpart
+    pub fn client(&self) -> &Client {
+        &self.client
+    }
+
+    /// Query a table or view, like `supabase.from(table)`
+    pub fn from(&self, table: &str) -> QueryBuilder {
+        self.client.database().from(table)
+    }
+
+    /// Call a database function, like `supabase.rpc(fn, args)`
+    pub async fn rpc(&self, function: &str, args: Option<Value>) -> Result<Value> {
+        self.client.database().rpc(function, args).await
+    }
+}
+
+/// Credentials for [`AuthClient::sign_in_with_password`]
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct SignInWithPasswordCredentials {
+    pub email: String,
+    pub password: String,
+}
+
+/// Credentials for [`AuthClient::sign_up`]
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct SignUpWithPasswordCredentials {
+    pub email: String,
+    pub password: String,
+    #[serde(default)]
+    pub options: SignUpOptions,
+}
+
+/// `options` of [`SignUpWithPasswordCredentials`]
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SignUpOptions {
+    /// User metadata stored in `user_metadata`
+    pub data: Option<Value>,
+    pub email_redirect_to: Option<String>,
+}
+
+/// Credentials for [`AuthClient::sign_in_with_otp`]
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct SignInWithOtpCredentials {
+    pub email: String,
+    #[serde(default)]
+    pub options: SignInWithOtpOptions,
+}
+
+/// `options` of [`SignInWithOtpCredentials`]
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SignInWithOtpOptions {
+    pub data: Option<Value>,
+    pub email_redirect_to: Option<String>,
+}
+
+/// Attributes for [`AuthClient::update_user`]
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct UserAttributes {
+    pub email: Option<String>,
+    pub password: Option<String>,
+    pub data: Option<Value>,
+}
+
+/// Options for [`AuthClient::reset_password_for_email`]
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResetPasswordOptions {
+    pub redirect_to: Option<String>,
+}
+
+/// `supabase.auth`
+#[derive(Debug, Clone)]
+pub struct AuthClient(Auth);
+
+impl AuthClient {
+    /// `auth.signUp({ email, password, options })`
+    pub async fn sign_up(
+        &self,
+        credentials: SignUpWithPasswordCredentials,
+    ) -> Result<AuthResponse> {
+        self.0
+            .sign_up_with_email_password_and_data(
+                &credentials.email,
+                &credentials.password,
+                credentials.options.data,
+                credentials.options.email_redirect_to,
+            )
+            .await
+    }
+
+    /// `auth.signInWithPassword({ email, password })`
+    pub async fn sign_in_with_password(
+        &self,
+        credentials: SignInWithPasswordCredentials,
+    ) -> Result<AuthResponse> {
+        self.0
+            .sign_in_with_email_and_password(&credentials.email, &credentials.password)
+            .await
+    }
+
+    /// `auth.signInWithOtp({ email, options })`, sending a magic link
+    pub async fn sign_in_with_otp(&self, credentials: SignInWithOtpCredentials) -> Result<()> {
+        self.0
+            .sign_in_with_magic_link(
+                &credentials.email,
+                credentials.options.email_redirect_to,
+                credentials.options.data,
+            )
+            .await
+    }
+
+    /// `auth.signInAnonymously()`
+    pub async fn sign_in_anonymously(&self) -> Result<AuthResponse> {
+        self.0.sign_in_anonymously(None).await
+    }
+
+    /// `auth.signOut()`
+    pub async fn sign_out(&self) -> Result<()> {
+        self.0.sign_out().await
+    }
+
+    /// `auth.getSession()`: the stored session, or `None` when signed out
+    pub fn get_session(&self) -> Option<Session> {
+        self.0.get_session().ok()
+    }
+
+    /// `auth.getUser()`
+    pub async fn get_user(&self) -> Result<Option<User>> {
+        self.0.current_user().await
+    }
+
+    /// `auth.refreshSession()`
+    pub async fn refresh_session(&self) -> Result<AuthResponse> {
+        self.0.refresh_session().await
+    }
+
+    /// `auth.updateUser({ email, password, data })`
+    pub async fn update_user(&self, attributes: UserAttributes) -> Result<AuthResponse> {
+        self.0
+            .update_user(attributes.email, attributes.password, attributes.data)
+            .await
+    }
+
+    /// `auth.resetPasswordForEmail(email, { redirectTo })`
+    pub async fn reset_password_for_email(
+        &self,
+        email: &str,
+        options: ResetPasswordOptions,
+    ) -> Result<()> {
+        self.0
+            .reset_password_for_email_with_redirect(email, options.redirect_to)
+            .await
+    }
+}
+
+/// `supabase.storage`
+#[derive(Debug, Clone)]
+pub struct StorageClient(Storage);
+
+impl StorageClient {
+    /// File operations on one bucket, like `storage.from(bucket)`
+    pub fn from(&self, bucket: &str) -> StorageFileApi {
+        StorageFileApi {
+            storage: self.0.clone(),
+            bucket: bucket.to_string(),
+        }
+    }
+}
+
+/// `supabase.storage.from(bucket)`
+#[derive(Debug, Clone)]
+pub struct StorageFileApi {
+    storage: Storage,
+    bucket: String,
+}
+
+impl StorageFileApi {
+    /// `upload(path, fileBody, options)`
+    pub async fn upload(
+        &self,
+        path: &str,
+        file_body: impl Into<Bytes>,
+        options: Option<FileOptions>,
+    ) -> Result<UploadResponse> {
+        self.storage
+            .upload(&self.bucket, path, file_body.into(), options)
+            .await
+    }
+
+    /// `download(path)`
+    pub async fn download(&self, path: &str) -> Result<Bytes> {
+        self.storage.download(&self.bucket, path).await
+    }
+
+    /// `remove(paths)`
+    pub async fn remove(&self, paths: &[&str]) -> Result<()> {
+        self.storage.remove(&self.bucket, paths).await
+    }
+
+    /// `list(path)`
+    pub async fn list(&self, path: Option<&str>) -> Result<Vec<FileObject>> {
+        self.storage.list(&self.bucket, path).await
+    }
+
+    /// `createSignedUrl(path, expiresIn)`
+    pub async fn create_signed_url(&self, path: &str, expires_in: u32) -> Result<String> {
+        self.storage
+            .create_signed_url(&self.bucket, path, expires_in, None)
+            .await
+    }
+
+    /// `createSignedUploadUrl(path)`
+    pub async fn create_signed_upload_url(&self, path: &str) -> Result<SignedUploadUrl> {
+        self.storage
+            .create_signed_upload_url(&self.bucket, path, false)
+            .await
+    }
+
+    /// `uploadToSignedUrl(path, token, fileBody, options)`
+    pub async fn upload_to_signed_url(
+        &self,
+        path: &str,
+        token: &str,
+        file_body: impl Into<Bytes>,
+        options: Option<FileOptions>,
+    ) -> Result<UploadResponse> {
+        self.storage
+            .upload_to_signed_url(&self.bucket, path, token, file_body.into(), options)
+            .await
+    }
+
+    /// `getPublicUrl(path)`
+    pub fn get_public_url(&self, path: &str) -> String {
+        self.storage.get_public_url(&self.bucket, path)
+    }
+}
+
+/// Options for [`FunctionsClient::invoke`]
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct FunctionInvokeOptions {
+    pub body: Option<Value>,
+    pub headers: Option<HashMap<String, String>>,
+}
+
+/// `supabase.functions`
+#[derive(Debug, Clone)]
+pub struct FunctionsClient(Functions);
+
+impl FunctionsClient {
+    /// `functions.invoke(name, { body, headers })`
+    pub async fn invoke(
+        &self,
+        function_name: &str,
+        options: FunctionInvokeOptions,
+    ) -> Result<Value> {
+        self.0
+            .invoke_with_options(function_name, options.body, options.headers)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_js_style_client() {
+        let supabase = create_client("https://example.supabase.co", "anon-key").unwrap();
+
+        assert_eq!(
+            supabase.storage.from("avatars").get_public_url("me.png"),
+            supabase
+                .client()
+                .storage()
+                .get_public_url("avatars", "me.png")
+        );
+        assert!(supabase.auth.get_session().is_none());
+
+        let credentials: SignUpWithPasswordCredentials = serde_json::from_value(json!({
+            "email": "user@example.com",
+            "password": "secret",
+            "options": {"data": {"name": "Ada"}, "emailRedirectTo": "https://example.com"}
+        }))
+        .unwrap();
+        assert_eq!(credentials.options.data, Some(json!({"name": "Ada"})));
+        assert_eq!(
+            credentials.options.email_redirect_to.as_deref(),
+            Some("https://example.com")
+        );
+    }
+}
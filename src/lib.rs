@@ -323,7 +323,7 @@
 //!
 //! This project is licensed under the MIT License.
 
-#[cfg(feature = "auth")]
+#[cfg(feature = "auth-core")]
 pub mod auth;
 
 #[cfg(feature = "session-management")]
@@ -334,12 +334,28 @@ pub mod client;
 #[cfg(feature = "database")]
 pub mod database;
 
+#[cfg(feature = "graphql")]
+pub mod graphql;
+
+#[cfg(feature = "postgis")]
+pub mod geo;
+
+#[cfg(feature = "cli")]
+pub mod codegen;
+
+#[cfg(feature = "js-compat")]
+pub mod js_compat;
+
 pub mod error;
 
+pub mod circuit;
+
+pub mod retry;
+
 #[cfg(feature = "realtime")]
 pub mod realtime;
 
-#[cfg(feature = "storage")]
+#[cfg(feature = "storage-core")]
 pub mod storage;
 
 #[cfg(feature = "functions")]
@@ -358,25 +374,30 @@ pub mod ffi;
 pub mod types;
 
 // Internal modules
-#[cfg(feature = "realtime")]
 mod async_runtime;
 
 #[cfg(feature = "realtime")]
 mod websocket;
 
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod mock_http;
+
 pub use client::Client;
 pub use error::{Error, Result};
 
-#[cfg(feature = "auth")]
+#[cfg(feature = "auth-core")]
 pub use auth::Auth;
 
 #[cfg(feature = "database")]
 pub use database::Database;
 
+#[cfg(feature = "graphql")]
+pub use graphql::GraphQL;
+
 #[cfg(feature = "realtime")]
 pub use realtime::Realtime;
 
-#[cfg(feature = "storage")]
+#[cfg(feature = "storage-core")]
 pub use storage::Storage;
 
 #[cfg(feature = "functions")]
@@ -403,7 +424,7 @@ pub mod prelude {
     pub use crate::types::*;
     pub use crate::{Client, Error, Result};
 
-    #[cfg(feature = "auth")]
+    #[cfg(feature = "auth-core")]
     pub use crate::auth::{Auth, AuthResponse, Session, User};
 
     #[cfg(feature = "database")]
@@ -411,7 +432,10 @@ pub mod prelude {
         Database, DeleteBuilder, InsertBuilder, QueryBuilder, UpdateBuilder,
     };
 
-    #[cfg(feature = "storage")]
+    #[cfg(feature = "graphql")]
+    pub use crate::graphql::GraphQL;
+
+    #[cfg(feature = "storage-core")]
     pub use crate::storage::{Bucket, FileObject, Storage};
 
     #[cfg(feature = "functions")]
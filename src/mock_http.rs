@@ -0,0 +1,262 @@
+//! Local HTTP server for unit tests
+//!
+//! [`MockServer`] binds an ephemeral port, parses each request (head plus a
+//! `Content-Length` or chunked body, over keep-alive connections) and answers
+//! it with a [`Response`], either from a fixed script or from a handler.
+//! Every request is recorded so tests can assert on what the client sent.
+
+// Which helpers get used depends on the enabled features
+#![allow(dead_code)]
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::mpsc;
+
+type Handler = Box<dyn FnMut(&Request) -> Response + Send>;
+
+/// A request received by [`MockServer`]
+#[derive(Debug, Clone)]
+pub(crate) struct Request {
+    pub method: String,
+    /// Path and query, as sent on the request line
+    pub target: String,
+    /// Header names are lowercased
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl Request {
+    /// First value of the header `name` (case-insensitive)
+    pub fn header(&self, name: &str) -> Option<&str> {
+        let name = name.to_ascii_lowercase();
+        self.headers
+            .iter()
+            .find(|(key, _)| *key == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// `"METHOD target"`, e.g. `"GET /rest/v1/posts?id=eq.1"`
+    pub fn line(&self) -> String {
+        format!("{} {}", self.method, self.target)
+    }
+
+    pub fn text(&self) -> String {
+        String::from_utf8_lossy(&self.body).into_owned()
+    }
+}
+
+enum Body {
+    Full(Vec<u8>),
+    /// Written as it arrives, then the connection is closed
+    Streamed(mpsc::UnboundedReceiver<Vec<u8>>),
+}
+
+/// A canned response
+pub(crate) struct Response {
+    status: u16,
+    headers: Vec<(String, String)>,
+    body: Body,
+}
+
+impl Response {
+    pub fn new(status: u16) -> Self {
+        Self {
+            status,
+            headers: Vec::new(),
+            body: Body::Full(Vec::new()),
+        }
+    }
+
+    /// `200 OK` with a JSON body
+    pub fn json(body: impl Into<String>) -> Self {
+        Self::new(200)
+            .header("content-type", "application/json")
+            .body(body.into())
+    }
+
+    /// A body fed from `chunks` until the sender is dropped
+    pub fn streamed(status: u16, chunks: mpsc::UnboundedReceiver<Vec<u8>>) -> Self {
+        Self {
+            status,
+            headers: Vec::new(),
+            body: Body::Streamed(chunks),
+        }
+    }
+
+    pub fn status(mut self, status: u16) -> Self {
+        self.status = status;
+        self
+    }
+
+    pub fn header(mut self, name: &str, value: impl Into<String>) -> Self {
+        self.headers.push((name.to_string(), value.into()));
+        self
+    }
+
+    pub fn body(mut self, body: impl Into<Vec<u8>>) -> Self {
+        self.body = Body::Full(body.into());
+        self
+    }
+}
+
+/// HTTP server on `127.0.0.1` that records requests
+pub(crate) struct MockServer {
+    url: String,
+    requests: Arc<Mutex<Vec<Request>>>,
+}
+
+impl MockServer {
+    /// Answer requests with `responses` in order; extra requests get a 500
+    pub async fn start(responses: impl IntoIterator<Item = Response>) -> Self {
+        let mut responses: VecDeque<Response> = responses.into_iter().collect();
+        Self::with_handler(move |request| {
+            responses.pop_front().unwrap_or_else(|| {
+                Response::new(500).body(format!("unexpected request: {}", request.line()))
+            })
+        })
+        .await
+    }
+
+    /// Answer every request with `handler`
+    pub async fn with_handler(handler: impl FnMut(&Request) -> Response + Send + 'static) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let handler: Arc<Mutex<Handler>> = Arc::new(Mutex::new(Box::new(handler)));
+
+        let recorded = requests.clone();
+        tokio::spawn(async move {
+            while let Ok((socket, _)) = listener.accept().await {
+                tokio::spawn(serve(socket, handler.clone(), recorded.clone()));
+            }
+        });
+
+        Self { url, requests }
+    }
+
+    /// Base URL, e.g. `http://127.0.0.1:41234`
+    pub fn url(&self) -> String {
+        self.url.clone()
+    }
+
+    /// Requests received so far, in arrival order
+    pub fn requests(&self) -> Vec<Request> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+async fn serve(
+    socket: TcpStream,
+    handler: Arc<Mutex<Handler>>,
+    requests: Arc<Mutex<Vec<Request>>>,
+) {
+    let (reader, mut writer) = socket.into_split();
+    let mut reader = BufReader::new(reader);
+
+    while let Some(request) = read_request(&mut reader).await {
+        let close = request
+            .header("connection")
+            .is_some_and(|value| value.eq_ignore_ascii_case("close"));
+        // Record before answering so the client never observes a response
+        // whose request is missing from `requests()`
+        requests.lock().unwrap().push(request.clone());
+        let response = (handler.lock().unwrap())(&request);
+
+        let mut head = format!(
+            "HTTP/1.1 {} {}\r\n",
+            response.status,
+            reqwest::StatusCode::from_u16(response.status)
+                .ok()
+                .and_then(|status| status.canonical_reason())
+                .unwrap_or("Unknown")
+        );
+        for (name, value) in &response.headers {
+            head.push_str(&format!("{}: {}\r\n", name, value));
+        }
+
+        match response.body {
+            Body::Full(body) => {
+                if !matches!(response.status, 204 | 304) {
+                    head.push_str(&format!("content-length: {}\r\n", body.len()));
+                }
+                head.push_str("\r\n");
+                if writer.write_all(head.as_bytes()).await.is_err()
+                    || writer.write_all(&body).await.is_err()
+                {
+                    return;
+                }
+            }
+            Body::Streamed(mut chunks) => {
+                head.push_str("connection: close\r\n\r\n");
+                if writer.write_all(head.as_bytes()).await.is_err() {
+                    return;
+                }
+                while let Some(chunk) = chunks.recv().await {
+                    if writer.write_all(&chunk).await.is_err() || writer.flush().await.is_err() {
+                        return;
+                    }
+                }
+                return;
+            }
+        }
+
+        if close || writer.flush().await.is_err() {
+            return;
+        }
+    }
+}
+
+/// Read one request, or `None` once the client closes the connection
+async fn read_request<R: AsyncBufReadExt + Unpin>(reader: &mut R) -> Option<Request> {
+    let mut line = String::new();
+    if reader.read_line(&mut line).await.ok()? == 0 {
+        return None;
+    }
+    let mut parts = line.split_whitespace();
+    let method = parts.next()?.to_string();
+    let target = parts.next()?.to_string();
+
+    let mut headers = Vec::new();
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line).await.ok()? == 0 {
+            return None;
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        let (name, value) = line.split_once(':')?;
+        headers.push((name.trim().to_ascii_lowercase(), value.trim().to_string()));
+    }
+
+    let mut request = Request {
+        method,
+        target,
+        headers,
+        body: Vec::new(),
+    };
+    if request
+        .header("transfer-encoding")
+        .is_some_and(|value| value.eq_ignore_ascii_case("chunked"))
+    {
+        loop {
+            let mut size = String::new();
+            reader.read_line(&mut size).await.ok()?;
+            let size = usize::from_str_radix(size.trim().split(';').next()?, 16).ok()?;
+            let mut chunk = vec![0; size + 2];
+            reader.read_exact(&mut chunk).await.ok()?;
+            if size == 0 {
+                break;
+            }
+            request.body.extend_from_slice(&chunk[..size]);
+        }
+    } else if let Some(length) = request.header("content-length") {
+        let mut body = vec![0; length.parse().ok()?];
+        reader.read_exact(&mut body).await.ok()?;
+        request.body = body;
+    }
+    Some(request)
+}
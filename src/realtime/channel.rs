@@ -0,0 +1,671 @@
+//! Channel handles in the style of supabase-js
+//!
+//! A [`RealtimeChannel`] is one joined topic with everything that happens on
+//! it: database changes, broadcast messages and presence. Handlers are
+//! registered before [`subscribe`](RealtimeChannel::subscribe) and the join
+//! payload asks the server for exactly what they need.
+//!
+//! ```rust,no_run
+//! use supabase_lib_rs::{
+//!     realtime::{PostgresChanges, PresenceState, RealtimeEvent},
+//!     Client,
+//! };
+//!
+//! # async fn example() -> supabase_lib_rs::Result<()> {
+//! let client = Client::new("your-url", "your-key")?;
+//!
+//! let room = client
+//!     .realtime()
+//!     .channel("room-1")
+//!     .on_postgres_changes(
+//!         PostgresChanges::table("messages").event(RealtimeEvent::Insert),
+//!         |change| println!("New message: {:?}", change.payload.record),
+//!     )
+//!     .on_broadcast("cursor", |message| println!("Cursor: {}", message.payload))
+//!     .on_presence_sync(|state| println!("{} users online", state.len()));
+//! room.subscribe().await?;
+//!
+//! room.track(PresenceState {
+//!     user_id: "user123".to_string(),
+//!     online_at: chrono::Utc::now().to_rfc3339(),
+//!     metadata: None,
+//! })
+//! .await?;
+//! room.send_broadcast("cursor", serde_json::json!({ "x": 10, "y": 20 }))
+//!     .await?;
+//!
+//! // ... later
+//! room.unsubscribe().await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use super::{
+    BroadcastMessage, BroadcastOptions, ChangeStream, ChannelBuilder, PostgresChanges,
+    PresenceChange, PresenceEventType, PresenceMap, PresenceState, Realtime, RealtimeEvent,
+    RealtimeMessage, RealtimeStream, ResyncPolicy, SubscriptionConfig, DEFAULT_STREAM_CAPACITY,
+};
+use crate::error::Result;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+#[cfg(not(target_arch = "wasm32"))]
+type Handler<T> = Arc<dyn Fn(T) + Send + Sync>;
+
+#[cfg(target_arch = "wasm32")]
+type Handler<T> = Arc<dyn Fn(T)>;
+
+/// Handle for one realtime channel, returned by [`Realtime::channel`]
+///
+/// Clones share handlers and subscription state.
+#[derive(Clone)]
+pub struct RealtimeChannel {
+    realtime: Realtime,
+    name: String,
+    topic: String,
+    shared: Arc<ChannelShared>,
+}
+
+impl std::fmt::Debug for RealtimeChannel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RealtimeChannel")
+            .field("topic", &self.topic)
+            .field("subscribed", &self.is_subscribed())
+            .finish()
+    }
+}
+
+/// State shared by clones of a channel and its subscription
+pub(crate) struct ChannelShared {
+    state: Mutex<ChannelState>,
+}
+
+#[derive(Default)]
+struct ChannelState {
+    private: bool,
+    broadcast_options: BroadcastOptions,
+    postgres_changes: Vec<(PostgresChanges, Handler<RealtimeMessage>)>,
+    broadcast: Vec<(String, Handler<BroadcastMessage>)>,
+    presence_sync: Vec<Handler<PresenceMap>>,
+    presence_join: Vec<Handler<PresenceChange>>,
+    presence_leave: Vec<Handler<PresenceChange>>,
+    subscription_id: Option<String>,
+    tracked_user: Option<String>,
+}
+
+impl RealtimeChannel {
+    pub(crate) fn new(realtime: Realtime, name: &str) -> Self {
+        Self {
+            realtime,
+            name: name.to_string(),
+            topic: format!("realtime:{}", name),
+            shared: Arc::new(ChannelShared {
+                state: Mutex::new(ChannelState::default()),
+            }),
+        }
+    }
+
+    /// Channel name as passed to [`Realtime::channel`]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Topic joined on the server, `realtime:<name>`
+    pub fn topic(&self) -> &str {
+        &self.topic
+    }
+
+    /// Whether [`subscribe`](Self::subscribe) joined the channel
+    pub fn is_subscribed(&self) -> bool {
+        self.shared.state().subscription_id.is_some()
+    }
+
+    /// Subscribe to a single table's changes through a [`ChannelBuilder`]
+    pub fn table(self, table: &str) -> ChannelBuilder {
+        self.builder().table(table)
+    }
+
+    /// Subscribe to a schema's changes through a [`ChannelBuilder`]
+    pub fn schema(self, schema: &str) -> ChannelBuilder {
+        self.builder().schema(schema)
+    }
+
+    /// Subscribe to one change type through a [`ChannelBuilder`]
+    pub fn event(self, event: RealtimeEvent) -> ChannelBuilder {
+        self.builder().event(event)
+    }
+
+    /// Subscribe to filtered changes through a [`ChannelBuilder`]
+    pub fn filter(self, filter: &str) -> ChannelBuilder {
+        self.builder().filter(filter)
+    }
+
+    /// Replace lagging changes with a snapshot through a [`ChannelBuilder`]
+    pub fn resync_on_lag(self, policy: ResyncPolicy) -> ChannelBuilder {
+        self.builder().resync_on_lag(policy)
+    }
+
+    /// Bound a stream's buffer through a [`ChannelBuilder`]
+    pub fn stream_capacity(self, capacity: usize) -> ChannelBuilder {
+        self.builder().stream_capacity(capacity)
+    }
+
+    /// Handle inserts through a [`ChannelBuilder`]
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn on_insert<F>(self, callback: F) -> ChannelBuilder
+    where
+        F: Fn(RealtimeMessage) + Send + Sync + 'static,
+    {
+        self.builder().on_insert(callback)
+    }
+
+    /// Handle updates through a [`ChannelBuilder`]
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn on_update<F>(self, callback: F) -> ChannelBuilder
+    where
+        F: Fn(RealtimeMessage) + Send + Sync + 'static,
+    {
+        self.builder().on_update(callback)
+    }
+
+    /// Handle deletes through a [`ChannelBuilder`]
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn on_delete<F>(self, callback: F) -> ChannelBuilder
+    where
+        F: Fn(RealtimeMessage) + Send + Sync + 'static,
+    {
+        self.builder().on_delete(callback)
+    }
+
+    /// Handle inserts through a [`ChannelBuilder`] (WASM version)
+    #[cfg(target_arch = "wasm32")]
+    pub fn on_insert<F>(self, callback: F) -> ChannelBuilder
+    where
+        F: Fn(RealtimeMessage) + 'static,
+    {
+        self.builder().on_insert(callback)
+    }
+
+    /// Handle updates through a [`ChannelBuilder`] (WASM version)
+    #[cfg(target_arch = "wasm32")]
+    pub fn on_update<F>(self, callback: F) -> ChannelBuilder
+    where
+        F: Fn(RealtimeMessage) + 'static,
+    {
+        self.builder().on_update(callback)
+    }
+
+    /// Handle deletes through a [`ChannelBuilder`] (WASM version)
+    #[cfg(target_arch = "wasm32")]
+    pub fn on_delete<F>(self, callback: F) -> ChannelBuilder
+    where
+        F: Fn(RealtimeMessage) + 'static,
+    {
+        self.builder().on_delete(callback)
+    }
+
+    /// Same as [`ChannelBuilder::subscribe_all`] without a table
+    pub async fn subscribe_all(self) -> Result<String> {
+        self.builder().subscribe_all().await
+    }
+
+    /// Same as [`ChannelBuilder::subscribe_stream`] without a table
+    pub async fn subscribe_stream(self) -> Result<RealtimeStream> {
+        self.builder().subscribe_stream().await
+    }
+
+    /// Same as [`ChannelBuilder::subscribe_changes`] without a table
+    pub async fn subscribe_changes<T>(self) -> Result<ChangeStream<T>>
+    where
+        T: DeserializeOwned,
+    {
+        self.builder().subscribe_changes().await
+    }
+
+    /// A builder keeping the channel's privacy and broadcast options
+    fn builder(self) -> ChannelBuilder {
+        let config = {
+            let state = self.shared.state();
+            let broadcast = (state.broadcast_options != BroadcastOptions::default())
+                .then(|| state.broadcast_options.clone());
+            SubscriptionConfig {
+                private: state.private,
+                enable_broadcast: broadcast.is_some(),
+                broadcast_options: broadcast,
+                ..Default::default()
+            }
+        };
+        ChannelBuilder {
+            realtime: self.realtime,
+            config,
+            handlers: Vec::new(),
+            stream_capacity: DEFAULT_STREAM_CAPACITY,
+        }
+    }
+
+    /// Mark the channel as private
+    ///
+    /// Private channels are authorized with the signed-in user's token and are
+    /// dropped when the user signs out.
+    pub fn private(self) -> Self {
+        self.shared.state().private = true;
+        self
+    }
+
+    /// Set the broadcast options sent when joining
+    ///
+    /// Own broadcasts are not delivered back unless
+    /// [`self_send`](BroadcastOptions::self_send) is set.
+    pub fn broadcast_options(self, options: BroadcastOptions) -> Self {
+        self.shared.state().broadcast_options = options;
+        self
+    }
+
+    /// Receive database changes matching `changes`
+    ///
+    /// Register one handler per table or change type; the server is asked for
+    /// all of them when the channel is joined.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn on_postgres_changes<F>(self, changes: PostgresChanges, callback: F) -> Self
+    where
+        F: Fn(RealtimeMessage) + Send + Sync + 'static,
+    {
+        self.shared
+            .state()
+            .postgres_changes
+            .push((changes, Arc::new(callback)));
+        self
+    }
+
+    /// Receive database changes matching `changes` (WASM version)
+    #[cfg(target_arch = "wasm32")]
+    pub fn on_postgres_changes<F>(self, changes: PostgresChanges, callback: F) -> Self
+    where
+        F: Fn(RealtimeMessage) + 'static,
+    {
+        self.shared
+            .state()
+            .postgres_changes
+            .push((changes, Arc::new(callback)));
+        self
+    }
+
+    /// Receive broadcasts of `event`, or of every event with `"*"`
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn on_broadcast<F>(self, event: &str, callback: F) -> Self
+    where
+        F: Fn(BroadcastMessage) + Send + Sync + 'static,
+    {
+        self.shared
+            .state()
+            .broadcast
+            .push((event.to_string(), Arc::new(callback)));
+        self
+    }
+
+    /// Receive broadcasts of `event`, or of every event with `"*"` (WASM version)
+    #[cfg(target_arch = "wasm32")]
+    pub fn on_broadcast<F>(self, event: &str, callback: F) -> Self
+    where
+        F: Fn(BroadcastMessage) + 'static,
+    {
+        self.shared
+            .state()
+            .broadcast
+            .push((event.to_string(), Arc::new(callback)));
+        self
+    }
+
+    /// Receive the full presence state after every presence update
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn on_presence_sync<F>(self, callback: F) -> Self
+    where
+        F: Fn(PresenceMap) + Send + Sync + 'static,
+    {
+        self.shared.state().presence_sync.push(Arc::new(callback));
+        self
+    }
+
+    /// Receive the full presence state after every presence update (WASM version)
+    #[cfg(target_arch = "wasm32")]
+    pub fn on_presence_sync<F>(self, callback: F) -> Self
+    where
+        F: Fn(PresenceMap) + 'static,
+    {
+        self.shared.state().presence_sync.push(Arc::new(callback));
+        self
+    }
+
+    /// Receive presences joining the channel
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn on_presence_join<F>(self, callback: F) -> Self
+    where
+        F: Fn(PresenceChange) + Send + Sync + 'static,
+    {
+        self.shared.state().presence_join.push(Arc::new(callback));
+        self
+    }
+
+    /// Receive presences joining the channel (WASM version)
+    #[cfg(target_arch = "wasm32")]
+    pub fn on_presence_join<F>(self, callback: F) -> Self
+    where
+        F: Fn(PresenceChange) + 'static,
+    {
+        self.shared.state().presence_join.push(Arc::new(callback));
+        self
+    }
+
+    /// Receive presences leaving the channel
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn on_presence_leave<F>(self, callback: F) -> Self
+    where
+        F: Fn(PresenceChange) + Send + Sync + 'static,
+    {
+        self.shared.state().presence_leave.push(Arc::new(callback));
+        self
+    }
+
+    /// Receive presences leaving the channel (WASM version)
+    #[cfg(target_arch = "wasm32")]
+    pub fn on_presence_leave<F>(self, callback: F) -> Self
+    where
+        F: Fn(PresenceChange) + 'static,
+    {
+        self.shared.state().presence_leave.push(Arc::new(callback));
+        self
+    }
+
+    /// Join the channel
+    ///
+    /// Handlers registered afterwards are called too, but database changes
+    /// only arrive for the settings sent in the join. Subscribing an already
+    /// joined channel does nothing.
+    pub async fn subscribe(&self) -> Result<()> {
+        let config = {
+            let state = self.shared.state();
+            if state.subscription_id.is_some() {
+                return Ok(());
+            }
+
+            let shared = Arc::clone(&self.shared);
+            SubscriptionConfig {
+                private: state.private,
+                enable_presence: true,
+                enable_broadcast: true,
+                broadcast_options: Some(state.broadcast_options.clone()),
+                postgres_changes: state
+                    .postgres_changes
+                    .iter()
+                    .map(|(changes, _)| changes.clone())
+                    .collect(),
+                broadcast_callback: Some(Arc::new(move |message| {
+                    shared.dispatch_broadcast(message)
+                })),
+                ..Default::default()
+            }
+        };
+        config.validate_join(true)?;
+
+        let shared = Arc::clone(&self.shared);
+        let id = self
+            .realtime
+            .subscribe_topic(
+                self.topic.clone(),
+                config,
+                Arc::new(move |message| shared.dispatch_postgres_changes(message)),
+                Some(Arc::clone(&self.shared)),
+            )
+            .await?;
+        self.shared.state().subscription_id = Some(id);
+        Ok(())
+    }
+
+    /// Leave the channel
+    ///
+    /// Handlers stay registered, so the channel can be joined again.
+    pub async fn unsubscribe(&self) -> Result<()> {
+        let id = self.shared.state().subscription_id.take();
+        match id {
+            Some(id) => self.realtime.unsubscribe(&id).await,
+            None => Ok(()),
+        }
+    }
+
+    /// Send a broadcast to everyone on the channel
+    pub async fn send_broadcast(&self, event: &str, payload: Value) -> Result<()> {
+        self.realtime
+            .broadcast(&self.name, event, payload, None)
+            .await
+    }
+
+    /// Share this client's presence with the channel
+    ///
+    /// The presence is re-sent after reconnects until [`untrack`](Self::untrack)
+    /// is called.
+    pub async fn track(&self, presence_state: PresenceState) -> Result<()> {
+        let user_id = presence_state.user_id.clone();
+        self.realtime
+            .track_presence(&self.name, presence_state)
+            .await?;
+        self.shared.state().tracked_user = Some(user_id);
+        Ok(())
+    }
+
+    /// Stop sharing the presence set by [`track`](Self::track)
+    pub async fn untrack(&self) -> Result<()> {
+        let user_id = self.shared.state().tracked_user.take();
+        match user_id {
+            Some(user_id) => self.realtime.untrack_presence(&self.name, &user_id).await,
+            None => Ok(()),
+        }
+    }
+
+    /// Presences currently on the channel, by presence key
+    ///
+    /// Empty until the server sent the channel's presence state after joining.
+    pub fn presence_state(&self) -> PresenceMap {
+        self.realtime.presence_map(&self.topic)
+    }
+}
+
+impl ChannelShared {
+    fn state(&self) -> MutexGuard<'_, ChannelState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn dispatch_postgres_changes(&self, message: RealtimeMessage) {
+        let handlers: Vec<_> = self
+            .state()
+            .postgres_changes
+            .iter()
+            .filter(|(changes, _)| changes.matches(&message))
+            .map(|(_, handler)| Arc::clone(handler))
+            .collect();
+        for handler in handlers {
+            handler(message.clone());
+        }
+    }
+
+    fn dispatch_broadcast(&self, message: BroadcastMessage) {
+        let handlers: Vec<_> = self
+            .state()
+            .broadcast
+            .iter()
+            .filter(|(event, _)| event == "*" || *event == message.event)
+            .map(|(_, handler)| Arc::clone(handler))
+            .collect();
+        for handler in handlers {
+            handler(message.clone());
+        }
+    }
+
+    /// Notify the presence handlers of `changes`, then sync with `state`
+    pub(crate) fn presence_changed(
+        &self,
+        changes: &[(PresenceEventType, PresenceChange)],
+        state: &PresenceMap,
+    ) {
+        let (sync, on_join, on_leave) = {
+            let state = self.state();
+            (
+                state.presence_sync.clone(),
+                state.presence_join.clone(),
+                state.presence_leave.clone(),
+            )
+        };
+
+        for (event_type, change) in changes {
+            let handlers = match event_type {
+                PresenceEventType::Join => &on_join,
+                PresenceEventType::Leave => &on_leave,
+            };
+            for handler in handlers {
+                handler(change.clone());
+            }
+        }
+        for handler in &sync {
+            handler(state.clone());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{realtime::testing::MockRealtimeServer, types::SupabaseConfig};
+    use serde_json::json;
+    use std::time::Duration;
+
+    #[test]
+    fn test_builder_keeps_channel_options() {
+        let realtime = Realtime::new(Arc::new(SupabaseConfig {
+            url: "https://test.supabase.co".to_string(),
+            key: "test-key".to_string(),
+            ..Default::default()
+        }))
+        .unwrap();
+
+        let builder = realtime.channel("posts").table("posts");
+        assert!(!builder.config.private);
+        assert!(!builder.config.enable_broadcast);
+
+        let options = BroadcastOptions {
+            ack: true,
+            ..Default::default()
+        };
+        let builder = realtime
+            .channel("posts")
+            .private()
+            .broadcast_options(options.clone())
+            .table("posts");
+        assert!(builder.config.private);
+        assert!(builder.config.enable_broadcast);
+        assert_eq!(builder.config.broadcast_options, Some(options));
+        assert_eq!(builder.config.table.as_deref(), Some("posts"));
+    }
+
+    #[tokio::test]
+    async fn test_channel_handlers() {
+        const TIMEOUT: Duration = Duration::from_secs(5);
+
+        let server = MockRealtimeServer::start().await.unwrap();
+        let realtime = Realtime::new(Arc::new(SupabaseConfig {
+            url: server.url(),
+            key: "test-key".to_string(),
+            ..Default::default()
+        }))
+        .unwrap();
+
+        let (sender, mut received) = tokio::sync::mpsc::unbounded_channel();
+        let (changes, broadcasts, syncs) = (sender.clone(), sender.clone(), sender);
+        let room = realtime
+            .channel("room-1")
+            .on_postgres_changes(
+                PostgresChanges::table("messages").event(RealtimeEvent::Insert),
+                move |message| {
+                    let _ = changes.send(format!(
+                        "{} {}",
+                        message.event,
+                        message.payload.table.unwrap()
+                    ));
+                },
+            )
+            .on_broadcast("cursor", move |message| {
+                let _ = broadcasts.send(format!("broadcast {}", message.payload["x"]));
+            })
+            .on_presence_sync(move |state| {
+                let _ = syncs.send(format!("sync {}", state.len()));
+            });
+        room.subscribe().await.unwrap();
+        room.subscribe().await.unwrap();
+        assert!(room.is_subscribed());
+
+        server
+            .wait_for_join("realtime:room-1", TIMEOUT)
+            .await
+            .unwrap();
+        let join = server.wait_for_event("phx_join", TIMEOUT).await.unwrap();
+        let config = &join.payload["config"];
+        assert_eq!(config["presence"], json!({ "key": "" }));
+        assert_eq!(config["broadcast"]["self"], false);
+        assert_eq!(
+            config["postgres_changes"],
+            json!([{ "event": "INSERT", "schema": "public", "table": "messages" }])
+        );
+
+        let change = |table: &str| {
+            json!({
+                "type": "INSERT",
+                "schema": "public",
+                "table": table,
+                "commit_timestamp": "2024-01-01T00:00:00Z",
+                "record": { "id": 1 },
+                "old_record": null,
+            })
+        };
+        server.push("realtime:room-1", "INSERT", change("other"));
+        server.push("realtime:room-1", "INSERT", change("messages"));
+        server.push(
+            "realtime:room-1",
+            "broadcast",
+            json!({ "type": "broadcast", "event": "typing", "payload": { "x": 0 } }),
+        );
+        server.push(
+            "realtime:room-1",
+            "broadcast",
+            json!({ "type": "broadcast", "event": "cursor", "payload": { "x": 10 } }),
+        );
+        server.push(
+            "realtime:room-1",
+            "presence_state",
+            json!({ "user-1": { "metas": [{ "phx_ref": "1" }] } }),
+        );
+
+        let mut events = Vec::new();
+        for _ in 0..3 {
+            let event = tokio::time::timeout(TIMEOUT, received.recv())
+                .await
+                .unwrap();
+            events.push(event.unwrap());
+        }
+        assert_eq!(events, vec!["INSERT messages", "broadcast 10", "sync 1"]);
+        assert_eq!(room.presence_state()["user-1"][0].user_id, "user-1");
+        let present = realtime.get_presence("room-1").await.unwrap();
+        assert_eq!(present.len(), 1);
+
+        room.send_broadcast("cursor", json!({ "x": 5 }))
+            .await
+            .unwrap();
+        let sent = server.wait_for_event("broadcast", TIMEOUT).await.unwrap();
+        assert_eq!(sent.topic, "realtime:room-1");
+        assert_eq!(sent.payload["event"], "cursor");
+
+        room.unsubscribe().await.unwrap();
+        assert!(!room.is_subscribed());
+        assert!(room.presence_state().is_empty());
+        let leave = server.wait_for_event("phx_leave", TIMEOUT).await.unwrap();
+        assert_eq!(leave.topic, "realtime:room-1");
+    }
+}
@@ -33,24 +33,49 @@
 //! # }
 //! ```
 
+#[cfg(feature = "realtime")]
+pub mod channel;
+#[cfg(feature = "realtime")]
+mod presence;
+#[cfg(feature = "realtime")]
+pub mod recorder;
+#[cfg(all(
+    feature = "realtime",
+    not(target_arch = "wasm32"),
+    any(test, feature = "realtime-testing")
+))]
+pub mod testing;
+
+#[cfg(feature = "realtime")]
+pub use channel::RealtimeChannel;
+#[cfg(feature = "realtime")]
+pub use presence::{PresenceChange, PresenceMap};
+
 #[cfg(feature = "realtime")]
 use crate::{
     async_runtime::{AsyncLock, RuntimeLock},
     error::{Error, Result},
-    types::SupabaseConfig,
+    types::{ProjectUrl, RequestAuth, SupabaseConfig, TokenProvider},
     websocket::{create_websocket, WebSocketConnection},
 };
 
 #[cfg(feature = "realtime")]
-use serde::{Deserialize, Serialize};
+use futures_util::{task::AtomicWaker, Stream};
+
+#[cfg(feature = "realtime")]
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 
 #[cfg(feature = "realtime")]
 use std::{
-    collections::HashMap,
+    collections::{HashMap, VecDeque},
+    future::Future,
+    marker::PhantomData,
+    pin::Pin,
     sync::{
         atomic::{AtomicBool, AtomicU64, Ordering},
-        Arc,
+        Arc, Mutex,
     },
+    task::{Context, Poll},
     time::Duration,
 };
 
@@ -60,6 +85,27 @@ use tracing::{debug, error, info, warn};
 #[cfg(feature = "realtime")]
 use uuid::Uuid;
 
+#[cfg(all(feature = "realtime", feature = "auth-core"))]
+use crate::auth::{AuthEvent, Session};
+
+#[cfg(all(feature = "realtime", feature = "database"))]
+use crate::database::Database;
+
+#[cfg(all(
+    feature = "realtime",
+    feature = "auth-core",
+    not(target_arch = "wasm32")
+))]
+use crate::auth::{Auth, AuthEventHandle};
+
+/// Longest time the message loop holds the connection while waiting for a frame
+#[cfg(all(feature = "realtime", not(target_arch = "wasm32")))]
+const RECEIVE_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Messages a [`RealtimeStream`] buffers before dropping the oldest
+#[cfg(feature = "realtime")]
+pub const DEFAULT_STREAM_CAPACITY: usize = 1024;
+
 /// Type alias for complex connection storage
 #[cfg(feature = "realtime")]
 pub type ConnectionStorage = Arc<RuntimeLock<Vec<Option<Box<dyn WebSocketConnection>>>>>;
@@ -100,10 +146,33 @@ pub struct Realtime {
 struct ConnectionManager {
     url: String,
     api_key: String,
+    access_token: RuntimeLock<Option<String>>,
+    /// Fallback for joins while no token was set explicitly
+    token_provider: Mutex<RequestAuth>,
     connection: RuntimeLock<Option<Box<dyn WebSocketConnection>>>,
     ref_counter: AtomicU64,
     subscriptions: RuntimeLock<HashMap<String, Subscription>>,
+    groups: RuntimeLock<HashMap<String, Vec<String>>>,
     is_message_loop_running: AtomicBool,
+    protocol_v2: AtomicBool,
+    join_refs: Mutex<HashMap<String, String>>,
+    stats: Mutex<HashMap<String, SubscriptionStats>>,
+    /// Last tracked presence state per channel
+    presence: Mutex<HashMap<String, PresenceState>>,
+    /// Presences reported by the server per topic
+    presence_states: Mutex<HashMap<String, presence::PresenceTracker>>,
+    /// Lag resync progress per subscription
+    resyncs: Mutex<HashMap<String, ResyncState>>,
+    /// Backoff settings for automatic reconnects
+    reconnect: Mutex<ConnectionPoolConfig>,
+    state: Mutex<ConnectionState>,
+    state_listeners: Mutex<HashMap<String, ConnectionStateCallback>>,
+    /// Ref of the heartbeat awaiting a reply
+    pending_heartbeat: Mutex<Option<String>>,
+    /// Bumped on every new socket so heartbeat tasks of old ones stop
+    connection_generation: AtomicU64,
+    /// Raw frame hooks, in the order they were added
+    frame_interceptors: Mutex<Vec<(String, Arc<dyn FrameInterceptor>)>>,
 }
 
 #[cfg(feature = "realtime")]
@@ -112,13 +181,435 @@ impl std::fmt::Debug for ConnectionManager {
         f.debug_struct("ConnectionManager")
             .field("url", &self.url)
             .field("api_key", &"[REDACTED]")
+            .field("access_token", &"[REDACTED]")
             .field("ref_counter", &self.ref_counter)
             .field("connection", &"<WebSocket connection>")
             .field("subscriptions", &"<subscriptions>")
+            .field("groups", &"<groups>")
+            .field("protocol_version", &self.protocol_version())
+            .field("state", &self.state())
+            .finish()
+    }
+}
+
+#[cfg(feature = "realtime")]
+impl ConnectionManager {
+    fn state(&self) -> ConnectionState {
+        self.state
+            .lock()
+            .map(|state| *state)
+            .unwrap_or(ConnectionState::Closed)
+    }
+
+    /// Record a state change and notify listeners, unless the state is unchanged
+    fn set_state(&self, state: ConnectionState) {
+        match self.state.lock() {
+            Ok(mut current) if *current != state => *current = state,
+            _ => return,
+        }
+
+        debug!("Realtime connection state: {:?}", state);
+        let listeners: Vec<ConnectionStateCallback> = match self.state_listeners.lock() {
+            Ok(listeners) => listeners.values().cloned().collect(),
+            Err(_) => return,
+        };
+        for listener in listeners {
+            listener(state);
+        }
+    }
+
+    fn protocol_version(&self) -> ProtocolVersion {
+        if self.protocol_v2.load(Ordering::SeqCst) {
+            ProtocolVersion::V2
+        } else {
+            ProtocolVersion::V1
+        }
+    }
+
+    /// Serialize an outgoing message in the negotiated frame format
+    ///
+    /// V2 frames carry the join ref of the channel, so joins and leaves are
+    /// tracked per topic. The frame is then passed through the
+    /// [`FrameInterceptor`]s.
+    fn encode(&self, message: &RealtimeProtocolMessage) -> Result<String> {
+        let join_ref = match self.join_refs.lock() {
+            Ok(mut join_refs) => match message.event.as_str() {
+                "phx_join" => {
+                    join_refs.insert(message.topic.clone(), message.ref_id.clone());
+                    Some(message.ref_id.clone())
+                }
+                "phx_leave" => join_refs.remove(&message.topic),
+                _ => join_refs.get(&message.topic).cloned(),
+            },
+            Err(_) => None,
+        };
+
+        let encoded = match self.protocol_version() {
+            ProtocolVersion::V1 => serde_json::to_string(message),
+            ProtocolVersion::V2 => serde_json::to_string(&serde_json::json!([
+                join_ref,
+                message.ref_id,
+                message.topic,
+                message.event,
+                message.payload,
+            ])),
+        };
+
+        let frame = encoded.map_err(|e| {
+            Error::realtime(format!(
+                "Failed to serialize {} message: {}",
+                message.event, e
+            ))
+        })?;
+
+        self.frame_interceptors()
+            .iter()
+            .try_fold(frame, |frame, interceptor| interceptor.outbound(frame))
+    }
+
+    /// Pass a received frame through the [`FrameInterceptor`]s, innermost first
+    fn intercept_inbound(&self, frame: String) -> Option<String> {
+        self.frame_interceptors()
+            .iter()
+            .rev()
+            .try_fold(frame, |frame, interceptor| interceptor.inbound(frame))
+    }
+
+    fn frame_interceptors(&self) -> Vec<Arc<dyn FrameInterceptor>> {
+        self.frame_interceptors
+            .lock()
+            .map(|interceptors| {
+                interceptors
+                    .iter()
+                    .map(|(_, interceptor)| Arc::clone(interceptor))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+/// Hook into raw realtime frames as they cross the socket
+///
+/// Outgoing frames reach [`outbound`](Self::outbound) after serialization,
+/// right before they are written; incoming frames reach
+/// [`inbound`](Self::inbound) before they are parsed. Useful for custom
+/// metrics, an encryption layer or protocol experiments. Interceptors see
+/// outgoing frames in the order they were added and incoming frames in
+/// reverse order, so each one unwraps what it wrapped.
+///
+/// # Examples
+/// ```rust,no_run
+/// use std::sync::{
+///     atomic::{AtomicUsize, Ordering},
+///     Arc,
+/// };
+/// use supabase_lib_rs::{realtime::FrameInterceptor, Client};
+///
+/// #[derive(Default)]
+/// struct ByteCounter {
+///     sent: AtomicUsize,
+///     received: AtomicUsize,
+/// }
+///
+/// impl FrameInterceptor for ByteCounter {
+///     fn outbound(&self, frame: String) -> supabase_lib_rs::Result<String> {
+///         self.sent.fetch_add(frame.len(), Ordering::Relaxed);
+///         Ok(frame)
+///     }
+///
+///     fn inbound(&self, frame: String) -> Option<String> {
+///         self.received.fetch_add(frame.len(), Ordering::Relaxed);
+///         Some(frame)
+///     }
+/// }
+///
+/// # fn example() -> supabase_lib_rs::Result<()> {
+/// let client = Client::new("your-url", "your-key")?;
+/// let counter = Arc::new(ByteCounter::default());
+/// client.realtime().add_frame_interceptor(counter.clone());
+/// # Ok(())
+/// # }
+/// ```
+#[cfg(feature = "realtime")]
+pub trait FrameInterceptor: Send + Sync {
+    /// Observe or rewrite a frame about to be sent
+    ///
+    /// An error fails the send with that error.
+    fn outbound(&self, frame: String) -> Result<String> {
+        Ok(frame)
+    }
+
+    /// Observe or rewrite a received frame
+    ///
+    /// Returning `None` drops the frame.
+    fn inbound(&self, frame: String) -> Option<String> {
+        Some(frame)
+    }
+}
+
+/// Delivery metrics for a single subscription
+///
+/// Lag is measured from the change's `commit_timestamp` to local receipt, so
+/// it includes clock skew between the database and this machine.
+#[cfg(feature = "realtime")]
+#[derive(Debug, Clone, Default)]
+pub struct SubscriptionStats {
+    /// Messages delivered to the subscription
+    pub events_received: u64,
+    /// Receipt time of the first message
+    pub first_event_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// Receipt time of the latest message
+    pub last_event_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// Largest lag seen
+    pub max_lag: Option<Duration>,
+    lag_total: Duration,
+    lag_samples: u64,
+}
+
+#[cfg(feature = "realtime")]
+impl SubscriptionStats {
+    /// Average lag over messages that carried a `commit_timestamp`
+    pub fn average_lag(&self) -> Option<Duration> {
+        (self.lag_samples > 0).then(|| self.lag_total / self.lag_samples as u32)
+    }
+
+    /// Average delivery rate between the first and latest message
+    pub fn events_per_second(&self) -> Option<f64> {
+        let (first, last) = (self.first_event_at?, self.last_event_at?);
+        let elapsed = (last - first).to_std().ok()?.as_secs_f64();
+        (elapsed > 0.0).then(|| self.events_received as f64 / elapsed)
+    }
+
+    fn record(
+        &mut self,
+        received_at: chrono::DateTime<chrono::Utc>,
+        commit_timestamp: Option<&str>,
+    ) {
+        self.events_received += 1;
+        self.first_event_at.get_or_insert(received_at);
+        self.last_event_at = Some(received_at);
+
+        if let Some(committed_at) = committed_at(commit_timestamp) {
+            let lag = commit_lag(received_at, committed_at);
+            self.lag_total += lag;
+            self.lag_samples += 1;
+            self.max_lag = Some(self.max_lag.map_or(lag, |max| max.max(lag)));
+        }
+    }
+}
+
+/// Parse a change's `commit_timestamp`
+#[cfg(feature = "realtime")]
+fn committed_at(commit_timestamp: Option<&str>) -> Option<chrono::DateTime<chrono::Utc>> {
+    commit_timestamp
+        .and_then(|timestamp| chrono::DateTime::parse_from_rfc3339(timestamp).ok())
+        .map(|committed_at| committed_at.with_timezone(&chrono::Utc))
+}
+
+/// Time from commit to receipt
+#[cfg(feature = "realtime")]
+fn commit_lag(
+    received_at: chrono::DateTime<chrono::Utc>,
+    committed_at: chrono::DateTime<chrono::Utc>,
+) -> Duration {
+    // Clock skew can put the commit in the future; count that as no lag
+    (received_at - committed_at).to_std().unwrap_or_default()
+}
+
+/// Event name of the snapshot message delivered by a [`ResyncPolicy`]
+#[cfg(feature = "realtime")]
+pub const RESYNC_EVENT: &str = "RESYNC";
+
+/// Rows loaded by a [`ResyncPolicy`] snapshot
+#[cfg(all(feature = "realtime", not(target_arch = "wasm32")))]
+pub type SnapshotFuture = Pin<Box<dyn Future<Output = Result<Vec<serde_json::Value>>> + Send>>;
+
+/// Rows loaded by a [`ResyncPolicy`] snapshot (WASM version)
+#[cfg(all(feature = "realtime", target_arch = "wasm32"))]
+pub type SnapshotFuture = Pin<Box<dyn Future<Output = Result<Vec<serde_json::Value>>>>>;
+
+#[cfg(all(feature = "realtime", not(target_arch = "wasm32")))]
+type SnapshotFn = Arc<dyn Fn(&SubscriptionConfig) -> SnapshotFuture + Send + Sync>;
+
+#[cfg(all(feature = "realtime", target_arch = "wasm32"))]
+type SnapshotFn = Arc<dyn Fn(&SubscriptionConfig) -> SnapshotFuture>;
+
+/// Replace a lagging change stream with a fresh snapshot
+///
+/// When a change arrives more than `max_lag` after its commit, e.g. during a
+/// burst of thousands of writes, the subscription stops delivering changes,
+/// loads a snapshot and delivers it as a [`RESYNC_EVENT`] message whose
+/// `payload.record` is the array of rows. Changes committed before the
+/// snapshot started are dropped because the snapshot already reflects them;
+/// later ones are delivered after it and live delivery resumes. If the
+/// snapshot fails, a warning is logged and live delivery resumes.
+///
+/// Lag is compared against the local clock, so it includes clock skew
+/// between the database and this machine.
+#[cfg(feature = "realtime")]
+#[derive(Clone)]
+pub struct ResyncPolicy {
+    /// Largest commit-to-receipt lag tolerated before resyncing
+    pub max_lag: Duration,
+    snapshot: SnapshotFn,
+}
+
+#[cfg(feature = "realtime")]
+impl std::fmt::Debug for ResyncPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ResyncPolicy")
+            .field("max_lag", &self.max_lag)
+            .field("snapshot", &"<snapshot fn>")
             .finish()
     }
 }
 
+#[cfg(feature = "realtime")]
+impl ResyncPolicy {
+    /// Resync with a custom snapshot, given the subscription's configuration
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn new<F, Fut>(max_lag: Duration, snapshot: F) -> Self
+    where
+        F: Fn(&SubscriptionConfig) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<Vec<serde_json::Value>>> + Send + 'static,
+    {
+        Self {
+            max_lag,
+            snapshot: Arc::new(move |config| Box::pin(snapshot(config))),
+        }
+    }
+
+    /// Resync with a custom snapshot (WASM version)
+    #[cfg(target_arch = "wasm32")]
+    pub fn new<F, Fut>(max_lag: Duration, snapshot: F) -> Self
+    where
+        F: Fn(&SubscriptionConfig) -> Fut + 'static,
+        Fut: Future<Output = Result<Vec<serde_json::Value>>> + 'static,
+    {
+        Self {
+            max_lag,
+            snapshot: Arc::new(move |config| Box::pin(snapshot(config))),
+        }
+    }
+
+    /// Resync by selecting the subscribed table's rows through `database`
+    ///
+    /// The subscription's `column=op.value` filter is applied to the query.
+    /// The table is read from the database's configured schema.
+    ///
+    /// # Examples
+    /// ```rust,no_run
+    /// use std::time::Duration;
+    /// use supabase_lib_rs::realtime::{ResyncPolicy, RESYNC_EVENT};
+    /// # use supabase_lib_rs::Client;
+    /// # async fn example() -> supabase_lib_rs::Result<()> {
+    /// let client = Client::new("your-url", "your-key")?;
+    ///
+    /// let subscription = client.realtime()
+    ///     .channel("orders")
+    ///     .table("orders")
+    ///     .resync_on_lag(ResyncPolicy::database(
+    ///         client.database().clone(),
+    ///         Duration::from_secs(5),
+    ///     ))
+    ///     .subscribe(|message| {
+    ///         if message.event == RESYNC_EVENT {
+    ///             println!("Reloaded orders: {:?}", message.payload.record);
+    ///         } else {
+    ///             println!("Order changed: {:?}", message.payload.record);
+    ///         }
+    ///     })
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "database")]
+    pub fn database(database: Database, max_lag: Duration) -> Self {
+        Self::new(max_lag, move |config: &SubscriptionConfig| {
+            let table = config.table.clone();
+            let filter = config.filter.clone();
+            let database = database.clone();
+            async move {
+                let table = table.ok_or_else(|| {
+                    Error::invalid_input("Resyncing from the database requires a table")
+                })?;
+                let mut query = database.from(&table).select("*");
+                if let Some(filter) = filter {
+                    query = query.with_filter(parse_change_filter(&filter)?);
+                }
+                query.execute().await
+            }
+        })
+    }
+}
+
+/// Turn a realtime `column=op.value` filter into a query filter
+#[cfg(all(feature = "realtime", feature = "database"))]
+fn parse_change_filter(filter: &str) -> Result<crate::database::Filter> {
+    let invalid = || Error::invalid_input(format!("Unsupported realtime filter: {}", filter));
+    let (column, condition) = filter.split_once('=').ok_or_else(invalid)?;
+    let (operator, value) = condition.split_once('.').ok_or_else(invalid)?;
+    let operator = serde_json::from_value(serde_json::Value::String(operator.to_string()))
+        .map_err(|_| invalid())?;
+
+    Ok(crate::database::Filter::Simple {
+        column: column.to_string(),
+        operator,
+        value: value.to_string(),
+    })
+}
+
+/// Progress of a subscription's lag resync
+#[cfg(feature = "realtime")]
+#[derive(Debug, Default)]
+struct ResyncState {
+    /// Start of the running snapshot and the changes held back until it is delivered
+    running: Option<(chrono::DateTime<chrono::Utc>, Vec<RealtimeMessage>)>,
+    /// Start of the last delivered snapshot; changes committed earlier are in it
+    covered_until: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// What to do with a change for a subscription with a [`ResyncPolicy`]
+#[cfg(feature = "realtime")]
+#[derive(Debug, PartialEq)]
+enum ResyncGate {
+    Deliver,
+    Hold,
+    Start(chrono::DateTime<chrono::Utc>),
+}
+
+#[cfg(feature = "realtime")]
+impl ResyncState {
+    fn gate(
+        &mut self,
+        message: &RealtimeMessage,
+        received_at: chrono::DateTime<chrono::Utc>,
+        max_lag: Duration,
+    ) -> ResyncGate {
+        let committed_at = committed_at(message.payload.commit_timestamp.as_deref());
+        let covered =
+            |since: chrono::DateTime<chrono::Utc>| committed_at.is_some_and(|at| at < since);
+
+        if self.covered_until.is_some_and(covered) {
+            return ResyncGate::Hold;
+        }
+        if let Some((started_at, held)) = &mut self.running {
+            if !covered(*started_at) {
+                held.push(message.clone());
+            }
+            return ResyncGate::Hold;
+        }
+
+        match committed_at {
+            Some(committed_at) if commit_lag(received_at, committed_at) > max_lag => {
+                self.running = Some((received_at, Vec::new()));
+                ResyncGate::Start(received_at)
+            }
+            _ => ResyncGate::Deliver,
+        }
+    }
+}
+
 /// Subscription information
 #[cfg(feature = "realtime")]
 #[derive(Clone)]
@@ -130,6 +621,8 @@ pub struct Subscription {
     pub callback: Arc<dyn Fn(RealtimeMessage) + Send + Sync>,
     #[cfg(target_arch = "wasm32")]
     pub callback: Arc<dyn Fn(RealtimeMessage)>,
+    /// Channel handle that joined the topic, receiving its presence updates
+    pub(crate) channel: Option<Arc<channel::ChannelShared>>,
 }
 
 #[cfg(feature = "realtime")]
@@ -144,6 +637,51 @@ impl std::fmt::Debug for Subscription {
     }
 }
 
+/// Broadcast settings sent when joining a channel
+///
+/// # Examples
+/// ```rust
+/// use supabase_lib_rs::realtime::BroadcastOptions;
+///
+/// // Receive own messages and anything sent in the last five minutes
+/// let options = BroadcastOptions {
+///     self_send: true,
+///     replay_since: Some(chrono::Utc::now() - chrono::Duration::minutes(5)),
+///     ..Default::default()
+/// };
+/// ```
+#[cfg(feature = "realtime")]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BroadcastOptions {
+    /// Ask the server to acknowledge each broadcast this client sends
+    pub ack: bool,
+    /// Deliver this client's own broadcasts back to it
+    pub self_send: bool,
+    /// Replay broadcasts sent since this time right after joining
+    ///
+    /// Replay is only available on private channels; servers without it
+    /// ignore the setting.
+    pub replay_since: Option<chrono::DateTime<chrono::Utc>>,
+    /// Maximum number of replayed messages; the server default applies if unset
+    pub replay_limit: Option<u32>,
+}
+
+#[cfg(feature = "realtime")]
+impl BroadcastOptions {
+    /// `broadcast` section of the join payload's `config`
+    fn join_config(&self) -> serde_json::Value {
+        let mut config = serde_json::json!({ "ack": self.ack, "self": self.self_send });
+        if let Some(since) = self.replay_since {
+            let mut replay = serde_json::json!({ "since": since.timestamp_millis() });
+            if let Some(limit) = self.replay_limit {
+                replay["limit"] = serde_json::json!(limit);
+            }
+            config["replay"] = replay;
+        }
+        config
+    }
+}
+
 /// Configuration for subscriptions
 ///
 /// # Examples
@@ -165,6 +703,10 @@ impl std::fmt::Debug for Subscription {
 ///     ],
 ///     enable_presence: false,
 ///     enable_broadcast: false,
+///     broadcast_options: None,
+///     private: false,
+///     resync: None,
+///     postgres_changes: Vec::new(),
 ///     presence_callback: None,
 ///     broadcast_callback: None,
 /// };
@@ -179,6 +721,14 @@ pub struct SubscriptionConfig {
     pub advanced_filters: Vec<AdvancedFilter>,
     pub enable_presence: bool,
     pub enable_broadcast: bool,
+    /// Broadcast settings; without them an enabled broadcast receives its own messages
+    pub broadcast_options: Option<BroadcastOptions>,
+    /// Private channel: joined with the user's access token and dropped on sign-out
+    pub private: bool,
+    /// Replace the change stream with a snapshot when it lags too far behind
+    pub resync: Option<ResyncPolicy>,
+    /// Database changes requested in the join payload's `postgres_changes`
+    pub postgres_changes: Vec<PostgresChanges>,
     #[cfg(not(target_arch = "wasm32"))]
     pub presence_callback: Option<PresenceCallback>,
     #[cfg(target_arch = "wasm32")]
@@ -200,6 +750,10 @@ impl std::fmt::Debug for SubscriptionConfig {
             .field("advanced_filters", &self.advanced_filters)
             .field("enable_presence", &self.enable_presence)
             .field("enable_broadcast", &self.enable_broadcast)
+            .field("broadcast_options", &self.broadcast_options)
+            .field("private", &self.private)
+            .field("resync", &self.resync)
+            .field("postgres_changes", &self.postgres_changes)
             .field("presence_callback", &"<callback fn>")
             .field("broadcast_callback", &"<callback fn>")
             .finish()
@@ -217,86 +771,410 @@ impl Default for SubscriptionConfig {
             advanced_filters: Vec::new(),
             enable_presence: false,
             enable_broadcast: false,
+            broadcast_options: None,
+            private: false,
+            resync: None,
+            postgres_changes: Vec::new(),
             presence_callback: None,
             broadcast_callback: None,
         }
     }
 }
 
-/// Realtime event types for filtering subscriptions
+/// Database changes a channel listens to
 ///
 /// # Examples
 /// ```rust
-/// use supabase_lib_rs::realtime::RealtimeEvent;
-///
-/// // Listen to all events
-/// let all_events = RealtimeEvent::All;
+/// use supabase_lib_rs::realtime::{PostgresChanges, RealtimeEvent};
 ///
-/// // Listen only to inserts
-/// let inserts_only = RealtimeEvent::Insert;
+/// let new_orders = PostgresChanges::table("orders")
+///     .event(RealtimeEvent::Insert)
+///     .filter("status=eq.open");
+/// assert_eq!(new_orders.schema, "public");
 /// ```
 #[cfg(feature = "realtime")]
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
-pub enum RealtimeEvent {
-    #[serde(rename = "INSERT")]
-    Insert,
-    #[serde(rename = "UPDATE")]
-    Update,
-    #[serde(rename = "DELETE")]
-    Delete,
-    #[serde(rename = "*")]
-    All,
+#[derive(Debug, Clone, PartialEq)]
+pub struct PostgresChanges {
+    /// Change type to receive
+    pub event: RealtimeEvent,
+    pub schema: String,
+    /// Table, or every table of the schema if unset
+    pub table: Option<String>,
+    /// Row filter such as `id=eq.1`
+    pub filter: Option<String>,
 }
 
-/// Realtime message received from Supabase
-///
-/// Contains the event data and metadata about the database change.
 #[cfg(feature = "realtime")]
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct RealtimeMessage {
-    pub event: String,
-    pub payload: RealtimePayload,
-    pub ref_id: Option<String>,
-    pub topic: String,
+impl Default for PostgresChanges {
+    fn default() -> Self {
+        Self {
+            event: RealtimeEvent::All,
+            schema: "public".to_string(),
+            table: None,
+            filter: None,
+        }
+    }
 }
 
-/// Payload of a realtime message
-///
-/// Contains the actual data from database changes.
 #[cfg(feature = "realtime")]
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct RealtimePayload {
-    pub record: Option<serde_json::Value>,
-    pub old_record: Option<serde_json::Value>,
-    pub schema: Option<String>,
-    pub table: Option<String>,
-    pub commit_timestamp: Option<String>,
-    pub event_type: Option<String>,
-    pub new: Option<serde_json::Value>,
-    pub old: Option<serde_json::Value>,
-}
+impl PostgresChanges {
+    /// All changes to a table in the `public` schema
+    pub fn table(table: &str) -> Self {
+        Self {
+            table: Some(table.to_string()),
+            ..Default::default()
+        }
+    }
 
-/// Supabase realtime protocol message for sending to server
-#[cfg(feature = "realtime")]
-#[derive(Debug, Serialize)]
-struct RealtimeProtocolMessage {
-    topic: String,
-    event: String,
-    payload: serde_json::Value,
-    ref_id: String,
-}
+    /// Set the schema
+    pub fn schema(mut self, schema: &str) -> Self {
+        self.schema = schema.to_string();
+        self
+    }
 
-/// Presence state for user tracking
-#[cfg(feature = "realtime")]
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct PresenceState {
-    pub user_id: String,
-    pub online_at: String,
-    pub metadata: Option<HashMap<String, serde_json::Value>>,
-}
+    /// Only receive one change type
+    pub fn event(mut self, event: RealtimeEvent) -> Self {
+        self.event = event;
+        self
+    }
 
-/// Presence event for tracking user joins/leaves
-#[cfg(feature = "realtime")]
+    /// Only receive changes to rows matching `filter`
+    pub fn filter(mut self, filter: &str) -> Self {
+        self.filter = Some(filter.to_string());
+        self
+    }
+
+    /// Entry of the join payload's `postgres_changes` list
+    fn join_config(&self) -> serde_json::Value {
+        let mut config = serde_json::json!({ "event": self.event, "schema": self.schema });
+        if let Some(table) = &self.table {
+            config["table"] = serde_json::json!(table);
+        }
+        if let Some(filter) = &self.filter {
+            config["filter"] = serde_json::json!(filter);
+        }
+        config
+    }
+
+    /// Whether a change message falls under these settings
+    fn matches(&self, message: &RealtimeMessage) -> bool {
+        let event_matches = match (&self.event, message.event.as_str()) {
+            (RealtimeEvent::All, _) => true,
+            (RealtimeEvent::Insert, event) => event == "INSERT",
+            (RealtimeEvent::Update, event) => event == "UPDATE",
+            (RealtimeEvent::Delete, event) => event == "DELETE",
+        };
+        let payload = &message.payload;
+        event_matches
+            && payload
+                .schema
+                .as_ref()
+                .is_none_or(|schema| *schema == self.schema)
+            && self
+                .table
+                .as_ref()
+                .is_none_or(|table| payload.table.as_ref() == Some(table))
+    }
+}
+
+#[cfg(feature = "realtime")]
+impl SubscriptionConfig {
+    /// `broadcast` section of the join payload's `config`, if broadcast is enabled
+    fn broadcast_join_config(&self) -> Option<serde_json::Value> {
+        self.enable_broadcast.then(|| {
+            self.broadcast_options
+                .as_ref()
+                .map(BroadcastOptions::join_config)
+                .unwrap_or_else(|| serde_json::json!({ "self": true }))
+        })
+    }
+
+    /// Check the settings before they are sent in a join
+    ///
+    /// Returns an [`Error::Validation`] listing every problem instead of
+    /// letting the server refuse the join: filters without a table, filters
+    /// that are not `column=operator.value` with an operator the server
+    /// supports, filtered delete events (the server cannot filter those),
+    /// broadcast options with broadcast disabled, and presence, which needs
+    /// a channel joined with [`Realtime::channel`] or
+    /// [`Realtime::subscribe_advanced`].
+    ///
+    /// # Examples
+    /// ```rust
+    /// use supabase_lib_rs::{realtime::SubscriptionConfig, Error};
+    ///
+    /// let config = SubscriptionConfig {
+    ///     filter: Some("author_id=like.1".to_string()),
+    ///     ..Default::default()
+    /// };
+    /// match config.validate() {
+    ///     Err(Error::Validation(error)) => assert_eq!(error.problems.len(), 2),
+    ///     _ => unreachable!(),
+    /// }
+    /// ```
+    pub fn validate(&self) -> Result<()> {
+        self.validate_join(false)
+    }
+
+    /// [`validate`](Self::validate) for a join on a channel when `on_channel`
+    fn validate_join(&self, on_channel: bool) -> Result<()> {
+        let mut problems = Vec::new();
+
+        let filters = self.filter.iter().count() + self.advanced_filters.len();
+        changes_problems(
+            "",
+            &self.schema,
+            self.table.as_deref(),
+            self.event.as_ref(),
+            filters,
+            &mut problems,
+        );
+        if let Some(filter) = &self.filter {
+            problems.extend(change_filter_problem(filter));
+        }
+        for filter in &self.advanced_filters {
+            let operator = serde_json::to_value(&filter.operator)?;
+            let operator = operator.as_str().unwrap_or_default();
+            if filter.column.trim().is_empty() {
+                problems.push("advanced filter has no column".to_string());
+            }
+            if !CHANGE_FILTER_OPERATORS.contains(&operator) {
+                problems.push(format!(
+                    "advanced filter on `{}` uses `{}`; realtime supports {}",
+                    filter.column,
+                    operator,
+                    CHANGE_FILTER_OPERATORS.join(", ")
+                ));
+            } else if operator == "in" && !filter.value.is_array() {
+                problems.push(format!(
+                    "advanced filter on `{}` uses `in` without a list of values",
+                    filter.column
+                ));
+            }
+        }
+
+        for (index, changes) in self.postgres_changes.iter().enumerate() {
+            changes_problems(
+                &format!("postgres_changes[{}]: ", index),
+                &changes.schema,
+                changes.table.as_deref(),
+                Some(&changes.event),
+                changes.filter.iter().count(),
+                &mut problems,
+            );
+            if let Some(filter) = &changes.filter {
+                problems.extend(
+                    change_filter_problem(filter)
+                        .map(|problem| format!("postgres_changes[{}]: {}", index, problem)),
+                );
+            }
+        }
+
+        if self.broadcast_options.is_some() && !self.enable_broadcast {
+            problems.push("broadcast options are set but broadcast is disabled".to_string());
+        }
+        if self.enable_presence && !on_channel {
+            problems.push(
+                "presence needs a channel; join one with `Realtime::channel` or `subscribe_advanced`"
+                    .to_string(),
+            );
+        }
+
+        match problems.is_empty() {
+            true => Ok(()),
+            false => Err(Error::validation(problems)),
+        }
+    }
+}
+
+/// Operators the realtime server accepts in change filters
+#[cfg(feature = "realtime")]
+const CHANGE_FILTER_OPERATORS: &[&str] = &["eq", "neq", "lt", "lte", "gt", "gte", "in"];
+
+/// Problems with the schema, table and event of one change stream
+#[cfg(feature = "realtime")]
+fn changes_problems(
+    prefix: &str,
+    schema: &str,
+    table: Option<&str>,
+    event: Option<&RealtimeEvent>,
+    filters: usize,
+    problems: &mut Vec<String>,
+) {
+    if schema.trim().is_empty() {
+        problems.push(format!("{}schema is empty", prefix));
+    }
+    match table {
+        Some(table) if table.trim().is_empty() => {
+            problems.push(format!("{}table is empty", prefix))
+        }
+        None if filters > 0 => problems.push(format!(
+            "{}filters reference columns but no table is set",
+            prefix
+        )),
+        _ => {}
+    }
+    if filters > 0 && event == Some(&RealtimeEvent::Delete) {
+        problems.push(format!("{}delete events cannot be filtered", prefix));
+    }
+}
+
+/// What is wrong with a `column=operator.value` change filter, if anything
+#[cfg(feature = "realtime")]
+fn change_filter_problem(filter: &str) -> Option<String> {
+    let Some((column, operator, value)) = filter.split_once('=').and_then(|(column, condition)| {
+        let (operator, value) = condition.split_once('.')?;
+        Some((column, operator, value))
+    }) else {
+        return Some(format!(
+            "filter `{}` is not in the form `column=operator.value`",
+            filter
+        ));
+    };
+
+    if column.trim().is_empty() || column.contains(char::is_whitespace) {
+        Some(format!("filter `{}` has an invalid column name", filter))
+    } else if !CHANGE_FILTER_OPERATORS.contains(&operator) {
+        Some(format!(
+            "filter `{}` uses `{}`; realtime supports {}",
+            filter,
+            operator,
+            CHANGE_FILTER_OPERATORS.join(", ")
+        ))
+    } else if operator == "in" && !(value.starts_with('(') && value.ends_with(')')) {
+        Some(format!(
+            "filter `{}` needs a parenthesized list such as `in.(1,2)`",
+            filter
+        ))
+    } else {
+        None
+    }
+}
+
+/// Realtime event types for filtering subscriptions
+///
+/// # Examples
+/// ```rust
+/// use supabase_lib_rs::realtime::RealtimeEvent;
+///
+/// // Listen to all events
+/// let all_events = RealtimeEvent::All;
+///
+/// // Listen only to inserts
+/// let inserts_only = RealtimeEvent::Insert;
+/// ```
+#[cfg(feature = "realtime")]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum RealtimeEvent {
+    #[serde(rename = "INSERT")]
+    Insert,
+    #[serde(rename = "UPDATE")]
+    Update,
+    #[serde(rename = "DELETE")]
+    Delete,
+    #[serde(rename = "*")]
+    All,
+}
+
+/// Realtime message received from Supabase
+///
+/// Contains the event data and metadata about the database change.
+#[cfg(feature = "realtime")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RealtimeMessage {
+    pub event: String,
+    pub payload: RealtimePayload,
+    #[serde(alias = "ref")]
+    pub ref_id: Option<String>,
+    pub topic: String,
+}
+
+/// Payload of a realtime message
+///
+/// Contains the actual data from database changes.
+#[cfg(feature = "realtime")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RealtimePayload {
+    pub record: Option<serde_json::Value>,
+    pub old_record: Option<serde_json::Value>,
+    pub schema: Option<String>,
+    pub table: Option<String>,
+    pub commit_timestamp: Option<String>,
+    pub event_type: Option<String>,
+    pub new: Option<serde_json::Value>,
+    pub old: Option<serde_json::Value>,
+}
+
+/// Phoenix serializer version used on the realtime socket
+///
+/// Incoming frames are decoded in either format regardless of this setting.
+#[cfg(feature = "realtime")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ProtocolVersion {
+    /// JSON object frames (`vsn=1.0.0`)
+    #[default]
+    V1,
+    /// JSON array frames `[join_ref, ref, topic, event, payload]` (`vsn=2.0.0`)
+    V2,
+}
+
+#[cfg(feature = "realtime")]
+impl ProtocolVersion {
+    /// Value of the `vsn` connection parameter
+    pub fn vsn(&self) -> &'static str {
+        match self {
+            ProtocolVersion::V1 => "1.0.0",
+            ProtocolVersion::V2 => "2.0.0",
+        }
+    }
+}
+
+#[cfg(feature = "realtime")]
+impl RealtimeMessage {
+    /// Decode a frame in either the V1 object or V2 array format
+    pub fn decode(frame: &str) -> Result<Self> {
+        if !frame.trim_start().starts_with('[') {
+            return Ok(serde_json::from_str(frame)?);
+        }
+
+        let (_join_ref, ref_id, topic, event, payload): (
+            Option<String>,
+            Option<String>,
+            String,
+            String,
+            serde_json::Value,
+        ) = serde_json::from_str(frame)?;
+
+        Ok(Self {
+            event,
+            payload: serde_json::from_value(payload)?,
+            ref_id,
+            topic,
+        })
+    }
+}
+
+/// Supabase realtime protocol message for sending to server
+#[cfg(feature = "realtime")]
+#[derive(Debug, Serialize)]
+struct RealtimeProtocolMessage {
+    topic: String,
+    event: String,
+    payload: serde_json::Value,
+    ref_id: String,
+}
+
+/// Presence state for user tracking
+#[cfg(feature = "realtime")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PresenceState {
+    pub user_id: String,
+    pub online_at: String,
+    pub metadata: Option<HashMap<String, serde_json::Value>>,
+}
+
+/// Presence event for tracking user joins/leaves
+#[cfg(feature = "realtime")]
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PresenceEvent {
     pub event_type: PresenceEventType,
@@ -314,6 +1192,102 @@ pub enum PresenceEventType {
     Leave,
 }
 
+/// Keeps presence tracked with [`Realtime::track_presence_auto`] alive
+///
+/// The state is re-sent every refresh interval until [`untrack`](Self::untrack)
+/// is called, the presence is untracked through [`Realtime::untrack_presence`],
+/// or the handle is dropped. Dropping the handle only stops the refresh; the
+/// presence itself stays tracked until the channel or connection closes.
+#[cfg(feature = "realtime")]
+#[derive(Debug)]
+pub struct PresenceKeepAlive {
+    realtime: Realtime,
+    channel: String,
+    user_id: String,
+    active: Arc<AtomicBool>,
+}
+
+#[cfg(feature = "realtime")]
+impl PresenceKeepAlive {
+    /// Channel the presence is tracked in
+    pub fn channel(&self) -> &str {
+        &self.channel
+    }
+
+    /// Whether the state is still being refreshed
+    pub fn is_active(&self) -> bool {
+        self.active.load(Ordering::SeqCst)
+    }
+
+    /// Stop refreshing and untrack the presence
+    pub async fn untrack(self) -> Result<()> {
+        self.active.store(false, Ordering::SeqCst);
+        self.realtime
+            .untrack_presence(&self.channel, &self.user_id)
+            .await
+    }
+}
+
+#[cfg(feature = "realtime")]
+impl Drop for PresenceKeepAlive {
+    fn drop(&mut self) {
+        self.active.store(false, Ordering::SeqCst);
+    }
+}
+
+/// Sleep without blocking the executor: tokio on native, `setTimeout` on WASM
+#[cfg(feature = "realtime")]
+async fn sleep(duration: Duration) {
+    #[cfg(not(target_arch = "wasm32"))]
+    tokio::time::sleep(duration).await;
+
+    #[cfg(target_arch = "wasm32")]
+    {
+        use wasm_bindgen_futures::JsFuture;
+
+        let millis = duration.as_millis().min(i32::MAX as u128) as i32;
+        let promise = js_sys::Promise::new(&mut |resolve, _| {
+            web_sys::window()
+                .unwrap()
+                .set_timeout_with_callback_and_timeout_and_arguments_0(&resolve, millis)
+                .unwrap();
+        });
+        let _ = JsFuture::from(promise).await;
+    }
+}
+
+/// Callback for postgres change messages
+#[cfg(all(feature = "realtime", not(target_arch = "wasm32")))]
+pub type MessageCallback = Arc<dyn Fn(RealtimeMessage) + Send + Sync>;
+
+/// Callback for postgres change messages (WASM version)
+#[cfg(all(feature = "realtime", target_arch = "wasm32"))]
+pub type MessageCallback = Arc<dyn Fn(RealtimeMessage)>;
+
+/// State of the realtime socket
+///
+/// Observe changes with [`Realtime::on_connection_state_change`].
+#[cfg(feature = "realtime")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConnectionState {
+    /// Opening the socket
+    Connecting,
+    /// Connected; channels are joined
+    Open,
+    /// Not connected and not trying to reconnect
+    Closed,
+    /// Connection lost, waiting before reconnect attempt `attempt` (1-based)
+    Reconnecting { attempt: u32 },
+}
+
+/// Callback for connection state changes
+#[cfg(all(feature = "realtime", not(target_arch = "wasm32")))]
+pub type ConnectionStateCallback = Arc<dyn Fn(ConnectionState) + Send + Sync>;
+
+/// Callback for connection state changes (WASM version)
+#[cfg(all(feature = "realtime", target_arch = "wasm32"))]
+pub type ConnectionStateCallback = Arc<dyn Fn(ConnectionState)>;
+
 /// Callback for presence events
 #[cfg(feature = "realtime")]
 pub type PresenceCallback = Arc<dyn Fn(PresenceEvent) + Send + Sync>;
@@ -325,9 +1299,115 @@ pub struct BroadcastMessage {
     pub event: String,
     pub payload: serde_json::Value,
     pub from_user_id: Option<String>,
+    #[serde(default)]
     pub timestamp: String,
 }
 
+/// `content_type` of a binary broadcast payload envelope
+#[cfg(feature = "realtime")]
+const MSGPACK_CONTENT_TYPE: &str = "application/msgpack";
+
+/// Wrap a MessagePack-encoded value in a broadcast payload envelope
+///
+/// Realtime frames are JSON text, so the bytes travel base64-encoded next to
+/// a `content_type` that tells receivers how to decode them.
+#[cfg(feature = "realtime-msgpack")]
+fn msgpack_envelope<T: Serialize + ?Sized>(value: &T) -> Result<serde_json::Value> {
+    use base64::Engine;
+
+    let bytes = rmp_serde::to_vec_named(value)
+        .map_err(|e| Error::realtime(format!("Failed to encode MessagePack payload: {}", e)))?;
+    Ok(serde_json::json!({
+        "content_type": MSGPACK_CONTENT_TYPE,
+        "data": base64::engine::general_purpose::STANDARD.encode(bytes),
+    }))
+}
+
+#[cfg(feature = "realtime")]
+impl BroadcastMessage {
+    /// Whether the payload is a binary (MessagePack) envelope
+    pub fn is_binary(&self) -> bool {
+        self.payload["content_type"] == MSGPACK_CONTENT_TYPE
+    }
+
+    /// Decode the payload into `T`, whether it was sent as JSON or MessagePack
+    ///
+    /// MessagePack payloads need the `realtime-msgpack` feature.
+    pub fn decode<T: DeserializeOwned>(&self) -> Result<T> {
+        if !self.is_binary() {
+            return Ok(serde_json::from_value(self.payload.clone())?);
+        }
+
+        #[cfg(feature = "realtime-msgpack")]
+        {
+            use base64::Engine;
+
+            let data = self.payload["data"]
+                .as_str()
+                .ok_or_else(|| Error::realtime("Binary broadcast payload has no data"))?;
+            let bytes = base64::engine::general_purpose::STANDARD
+                .decode(data)
+                .map_err(|e| Error::realtime(format!("Invalid binary broadcast payload: {}", e)))?;
+            rmp_serde::from_slice(&bytes).map_err(|e| {
+                Error::realtime(format!("Failed to decode MessagePack payload: {}", e))
+            })
+        }
+
+        #[cfg(not(feature = "realtime-msgpack"))]
+        Err(Error::realtime(
+            "Received a MessagePack broadcast; enable the realtime-msgpack feature to decode it",
+        ))
+    }
+
+    /// Broadcast callback receiving payloads decoded into `T`
+    ///
+    /// Messages that fail to decode are logged and skipped.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use serde::Deserialize;
+    /// use supabase_lib_rs::realtime::{BroadcastMessage, SubscriptionConfig};
+    ///
+    /// #[derive(Deserialize)]
+    /// struct Cursor {
+    ///     x: f64,
+    ///     y: f64,
+    /// }
+    ///
+    /// let config = SubscriptionConfig {
+    ///     enable_broadcast: true,
+    ///     broadcast_callback: Some(BroadcastMessage::typed(|cursor: Cursor| {
+    ///         println!("Cursor at {}, {}", cursor.x, cursor.y);
+    ///     })),
+    ///     ..Default::default()
+    /// };
+    /// ```
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn typed<T, F>(callback: F) -> BroadcastCallback
+    where
+        T: DeserializeOwned,
+        F: Fn(T) + Send + Sync + 'static,
+    {
+        Arc::new(move |message: BroadcastMessage| match message.decode() {
+            Ok(value) => callback(value),
+            Err(e) => warn!("Skipping broadcast '{}': {}", message.event, e),
+        })
+    }
+
+    /// Broadcast callback receiving payloads decoded into `T` (WASM version)
+    #[cfg(target_arch = "wasm32")]
+    pub fn typed<T, F>(callback: F) -> Arc<dyn Fn(BroadcastMessage)>
+    where
+        T: DeserializeOwned,
+        F: Fn(T) + 'static,
+    {
+        Arc::new(move |message: BroadcastMessage| match message.decode() {
+            Ok(value) => callback(value),
+            Err(e) => warn!("Skipping broadcast '{}': {}", message.event, e),
+        })
+    }
+}
+
 /// Callback for broadcast messages
 #[cfg(feature = "realtime")]
 pub type BroadcastCallback = Arc<dyn Fn(BroadcastMessage) + Send + Sync>;
@@ -379,11 +1459,11 @@ pub struct ConnectionPoolConfig {
     pub max_connections: usize,
     /// Connection timeout in seconds (default: 30)
     pub connection_timeout: u64,
-    /// Keep-alive interval in seconds (default: 30)
+    /// Heartbeat interval in seconds, 0 to disable heartbeats (default: 30)
     pub keep_alive_interval: u64,
-    /// Reconnect delay in milliseconds (default: 1000)
+    /// Reconnect delay in milliseconds, doubled after every failed attempt (default: 1000)
     pub reconnect_delay: u64,
-    /// Maximum reconnect attempts (default: 5)
+    /// Maximum reconnect attempts, 0 to disable reconnecting (default: 5)
     pub max_reconnect_attempts: u32,
 }
 
@@ -540,29 +1620,46 @@ impl Realtime {
     /// let realtime = Realtime::new(config).unwrap();
     /// ```
     pub fn new(config: Arc<SupabaseConfig>) -> Result<Self> {
+        let project_url = ProjectUrl::parse(&config.url)?;
+        Ok(Self::with_project_url(&config, &project_url))
+    }
+
+    /// Create a realtime client for an already validated project URL
+    pub(crate) fn with_project_url(config: &SupabaseConfig, project_url: &ProjectUrl) -> Self {
         debug!("Creating realtime client");
 
-        let ws_url = config
-            .url
-            .replace("http://", "ws://")
-            .replace("https://", "wss://");
-        let realtime_url = format!("{}/realtime/v1/websocket", ws_url);
+        let realtime_url = project_url.realtime_url();
 
         let connection_manager = Arc::new(ConnectionManager {
             url: realtime_url,
             api_key: config.key.clone(),
+            access_token: RuntimeLock::new(None),
+            token_provider: Mutex::new(RequestAuth::default()),
             connection: RuntimeLock::new(None),
             ref_counter: AtomicU64::new(0),
             subscriptions: RuntimeLock::new(HashMap::new()),
+            groups: RuntimeLock::new(HashMap::new()),
             is_message_loop_running: AtomicBool::new(false),
+            protocol_v2: AtomicBool::new(false),
+            join_refs: Mutex::new(HashMap::new()),
+            stats: Mutex::new(HashMap::new()),
+            presence: Mutex::new(HashMap::new()),
+            presence_states: Mutex::new(HashMap::new()),
+            resyncs: Mutex::new(HashMap::new()),
+            reconnect: Mutex::new(ConnectionPoolConfig::default()),
+            state: Mutex::new(ConnectionState::Closed),
+            state_listeners: Mutex::new(HashMap::new()),
+            pending_heartbeat: Mutex::new(None),
+            connection_generation: AtomicU64::new(0),
+            frame_interceptors: Mutex::new(Vec::new()),
         });
 
         let message_loop_handle = Arc::new(AtomicBool::new(false));
 
-        Ok(Self {
+        Self {
             connection_manager,
             message_loop_handle,
-        })
+        }
     }
 
     /// Connect to the realtime server (cross-platform)
@@ -580,6 +1677,15 @@ impl Realtime {
     /// # }
     /// ```
     pub async fn connect(&self) -> Result<()> {
+        let result = self.open().await;
+        if result.is_err() {
+            self.connection_manager.set_state(ConnectionState::Closed);
+        }
+        result
+    }
+
+    /// Open the socket unless connected, then rejoin channels and presence
+    async fn open(&self) -> Result<()> {
         debug!("Connecting to realtime server");
 
         let mut connection_guard = self.connection_manager.connection.write().await;
@@ -591,37 +1697,240 @@ impl Realtime {
             }
         }
 
+        self.connection_manager
+            .set_state(ConnectionState::Connecting);
         let mut connection = create_websocket();
         let url = format!(
-            "{}?apikey={}&vsn=1.0.0",
-            self.connection_manager.url, self.connection_manager.api_key
+            "{}?apikey={}&vsn={}",
+            self.connection_manager.url,
+            self.connection_manager.api_key,
+            self.connection_manager.protocol_version().vsn()
         );
 
         connection.connect(&url).await?;
         *connection_guard = Some(connection);
 
         // Start message loop
-        self.start_message_loop().await?;
+        self.start_message_loop();
+        self.start_heartbeat();
+        drop(connection_guard);
 
         info!("Connected to realtime server");
+
+        // Restore channels and presence from before a reconnect
+        self.join_stored_channels().await;
+        self.retrack_presence(|_| true).await;
+        self.connection_manager.set_state(ConnectionState::Open);
         Ok(())
     }
 
-    /// Disconnect from the realtime server
-    ///
-    /// # Examples
-    /// ```rust,no_run
-    /// # use supabase_lib_rs::Client;
-    /// # async fn example() -> supabase_lib_rs::Result<()> {
-    /// let client = Client::new("your-url", "your-key")?;
-    /// let realtime = client.realtime();
-    ///
-    /// realtime.connect().await?;
-    /// // ... do work ...
-    /// realtime.disconnect().await?;
-    /// # Ok(())
-    /// # }
-    /// ```
+    /// Send a join for every stored subscription on a fresh socket
+    async fn join_stored_channels(&self) {
+        let channels: Vec<(String, SubscriptionConfig)> = {
+            let subscriptions = self.connection_manager.subscriptions.read().await;
+            subscriptions
+                .values()
+                .map(|subscription| (subscription.topic.clone(), subscription.config.clone()))
+                .collect()
+        };
+
+        for (topic, config) in &channels {
+            if let Err(e) = self.send_join_message(topic, config).await {
+                warn!("Failed to rejoin topic {}: {}", topic, e);
+            }
+        }
+
+        if !channels.is_empty() {
+            debug!("Rejoined {} channels after connecting", channels.len());
+        }
+    }
+
+    /// Reconnect with exponential backoff after the connection was lost
+    async fn reconnect(self) {
+        let config = self
+            .connection_manager
+            .reconnect
+            .lock()
+            .map(|config| config.clone())
+            .unwrap_or_default();
+
+        for attempt in 1..=config.max_reconnect_attempts {
+            self.connection_manager
+                .set_state(ConnectionState::Reconnecting { attempt });
+            let backoff = 1u64 << (attempt - 1).min(16);
+            sleep(Duration::from_millis(
+                config.reconnect_delay.saturating_mul(backoff),
+            ))
+            .await;
+
+            // Stopped by disconnect() in the meantime
+            if !self.message_loop_handle.load(Ordering::SeqCst) {
+                return;
+            }
+
+            match self.open().await {
+                Ok(()) => {
+                    info!("Reconnected to realtime server after {} attempts", attempt);
+                    return;
+                }
+                Err(e) => warn!("Realtime reconnect attempt {} failed: {}", attempt, e),
+            }
+        }
+
+        error!(
+            "Giving up on the realtime server after {} reconnect attempts",
+            config.max_reconnect_attempts
+        );
+        self.connection_manager.set_state(ConnectionState::Closed);
+    }
+
+    /// Configure automatic reconnects
+    ///
+    /// After the connection drops, the client waits `reconnect_delay`
+    /// milliseconds, doubling the wait after every failed attempt, and gives
+    /// up after `max_reconnect_attempts`. Subscriptions and tracked presence
+    /// are restored once reconnected. Other fields are ignored.
+    ///
+    /// # Examples
+    /// ```rust,no_run
+    /// # use supabase_lib_rs::Client;
+    /// # use supabase_lib_rs::realtime::ConnectionPoolConfig;
+    /// # fn example() -> supabase_lib_rs::Result<()> {
+    /// let client = Client::new("your-url", "your-key")?;
+    ///
+    /// client.realtime().set_connection_config(ConnectionPoolConfig {
+    ///     reconnect_delay: 500,
+    ///     max_reconnect_attempts: 10,
+    ///     ..Default::default()
+    /// });
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_connection_config(&self, config: ConnectionPoolConfig) {
+        if let Ok(mut reconnect) = self.connection_manager.reconnect.lock() {
+            *reconnect = config;
+        }
+    }
+
+    /// Current state of the socket
+    pub fn connection_state(&self) -> ConnectionState {
+        self.connection_manager.state()
+    }
+
+    /// Call `callback` on every connection state change
+    ///
+    /// Returns an ID for [`remove_connection_state_listener`](Self::remove_connection_state_listener).
+    ///
+    /// # Examples
+    /// ```rust,no_run
+    /// # use supabase_lib_rs::Client;
+    /// # use supabase_lib_rs::realtime::ConnectionState;
+    /// # fn example() -> supabase_lib_rs::Result<()> {
+    /// let client = Client::new("your-url", "your-key")?;
+    ///
+    /// client.realtime().on_connection_state_change(|state| match state {
+    ///     ConnectionState::Reconnecting { attempt } => println!("Reconnecting (attempt {})", attempt),
+    ///     ConnectionState::Open => println!("Live"),
+    ///     _ => {}
+    /// });
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn on_connection_state_change<F>(&self, callback: F) -> String
+    where
+        F: Fn(ConnectionState) + Send + Sync + 'static,
+    {
+        self.add_connection_state_listener(Arc::new(callback))
+    }
+
+    /// Call `callback` on every connection state change (WASM version)
+    #[cfg(target_arch = "wasm32")]
+    pub fn on_connection_state_change<F>(&self, callback: F) -> String
+    where
+        F: Fn(ConnectionState) + 'static,
+    {
+        self.add_connection_state_listener(Arc::new(callback))
+    }
+
+    fn add_connection_state_listener(&self, callback: ConnectionStateCallback) -> String {
+        let id = Uuid::new_v4().to_string();
+        if let Ok(mut listeners) = self.connection_manager.state_listeners.lock() {
+            listeners.insert(id.clone(), callback);
+        }
+        id
+    }
+
+    /// Remove a connection state listener
+    pub fn remove_connection_state_listener(&self, id: &str) {
+        if let Ok(mut listeners) = self.connection_manager.state_listeners.lock() {
+            listeners.remove(id);
+        }
+    }
+
+    /// Pass every raw frame sent or received through `interceptor`
+    ///
+    /// Applies to frames from then on. Returns an ID for
+    /// [`remove_frame_interceptor`](Self::remove_frame_interceptor).
+    pub fn add_frame_interceptor(&self, interceptor: Arc<dyn FrameInterceptor>) -> String {
+        let id = Uuid::new_v4().to_string();
+        if let Ok(mut interceptors) = self.connection_manager.frame_interceptors.lock() {
+            interceptors.push((id.clone(), interceptor));
+        }
+        id
+    }
+
+    /// Remove a frame interceptor
+    pub fn remove_frame_interceptor(&self, id: &str) {
+        if let Ok(mut interceptors) = self.connection_manager.frame_interceptors.lock() {
+            interceptors.retain(|(interceptor_id, _)| interceptor_id != id);
+        }
+    }
+
+    /// Set the serializer version negotiated on the next connect
+    ///
+    /// Newer realtime servers support the Phoenix V2 array frame format.
+    /// Takes effect the next time the socket connects.
+    ///
+    /// # Examples
+    /// ```rust,no_run
+    /// # use supabase_lib_rs::Client;
+    /// # use supabase_lib_rs::realtime::ProtocolVersion;
+    /// # async fn example() -> supabase_lib_rs::Result<()> {
+    /// let client = Client::new("your-url", "your-key")?;
+    /// let realtime = client.realtime();
+    ///
+    /// realtime.set_protocol_version(ProtocolVersion::V2);
+    /// realtime.connect().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_protocol_version(&self, version: ProtocolVersion) {
+        self.connection_manager
+            .protocol_v2
+            .store(version == ProtocolVersion::V2, Ordering::SeqCst);
+    }
+
+    /// Get the serializer version used for outgoing frames
+    pub fn protocol_version(&self) -> ProtocolVersion {
+        self.connection_manager.protocol_version()
+    }
+
+    /// Disconnect from the realtime server
+    ///
+    /// # Examples
+    /// ```rust,no_run
+    /// # use supabase_lib_rs::Client;
+    /// # async fn example() -> supabase_lib_rs::Result<()> {
+    /// let client = Client::new("your-url", "your-key")?;
+    /// let realtime = client.realtime();
+    ///
+    /// realtime.connect().await?;
+    /// // ... do work ...
+    /// realtime.disconnect().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
     pub async fn disconnect(&self) -> Result<()> {
         debug!("Disconnecting from realtime server");
 
@@ -641,6 +1950,14 @@ impl Realtime {
         let mut subscriptions = self.connection_manager.subscriptions.write().await;
         subscriptions.clear();
 
+        if let Ok(mut presence) = self.connection_manager.presence.lock() {
+            presence.clear();
+        }
+        if let Ok(mut presence_states) = self.connection_manager.presence_states.lock() {
+            presence_states.clear();
+        }
+
+        self.connection_manager.set_state(ConnectionState::Closed);
         info!("Disconnected from realtime server");
         Ok(())
     }
@@ -669,7 +1986,12 @@ impl Realtime {
         }
     }
 
-    /// Create a channel subscription builder
+    /// Get a handle for the channel `name`, joined as topic `realtime:<name>`
+    ///
+    /// Register handlers on the [`RealtimeChannel`], then join with
+    /// [`subscribe`](RealtimeChannel::subscribe). Calling
+    /// [`table`](RealtimeChannel::table) or [`schema`](RealtimeChannel::schema)
+    /// instead starts a [`ChannelBuilder`] for a single table's changes.
     ///
     /// # Examples
     /// ```rust,no_run
@@ -677,6 +1999,11 @@ impl Realtime {
     /// # async fn example() -> supabase_lib_rs::Result<()> {
     /// let client = Client::new("your-url", "your-key")?;
     ///
+    /// let room = client.realtime()
+    ///     .channel("room-1")
+    ///     .on_broadcast("message", |message| println!("Message: {}", message.payload));
+    /// room.subscribe().await?;
+    ///
     /// let subscription = client.realtime()
     ///     .channel("public-posts")
     ///     .table("posts")
@@ -685,11 +2012,8 @@ impl Realtime {
     /// # Ok(())
     /// # }
     /// ```
-    pub fn channel(&self, _topic: &str) -> ChannelBuilder {
-        ChannelBuilder {
-            realtime: self.clone(),
-            config: SubscriptionConfig::default(),
-        }
+    pub fn channel(&self, name: &str) -> RealtimeChannel {
+        RealtimeChannel::new(self.clone(), name)
     }
 
     /// Unsubscribe from a channel
@@ -716,7 +2040,14 @@ impl Realtime {
         debug!("Unsubscribing from subscription: {}", subscription_id);
 
         let mut subscriptions = self.connection_manager.subscriptions.write().await;
+        if let Ok(mut stats) = self.connection_manager.stats.lock() {
+            stats.remove(subscription_id);
+        }
+        if let Ok(mut resyncs) = self.connection_manager.resyncs.lock() {
+            resyncs.remove(subscription_id);
+        }
         if let Some(subscription) = subscriptions.remove(subscription_id) {
+            self.forget_presence(&subscriptions, [subscription.topic.as_str()]);
             // Send leave message to server
             self.send_leave_message(&subscription.topic).await?;
             info!("Unsubscribed from subscription: {}", subscription_id);
@@ -727,105 +2058,488 @@ impl Realtime {
         Ok(())
     }
 
-    /// Subscribe to a channel with custom configuration
-    #[cfg(not(target_arch = "wasm32"))]
-    pub async fn subscribe<F>(
-        &self,
-        subscription_config: SubscriptionConfig,
-        callback: F,
-    ) -> Result<String>
-    where
-        F: Fn(RealtimeMessage) + Send + Sync + 'static,
-    {
-        let subscription_id = Uuid::new_v4().to_string();
-        let topic = self.build_topic(&subscription_config);
-
-        debug!(
-            "Creating subscription {} for topic {}",
-            subscription_id, topic
-        );
+    /// Get delivery metrics for a subscription
+    ///
+    /// Returns `None` for unknown subscriptions.
+    ///
+    /// # Examples
+    /// ```rust,no_run
+    /// # use supabase_lib_rs::Client;
+    /// # async fn example() -> supabase_lib_rs::Result<()> {
+    /// let client = Client::new("your-url", "your-key")?;
+    ///
+    /// let id = client.realtime()
+    ///     .channel("orders")
+    ///     .table("orders")
+    ///     .subscribe(|_| {})
+    ///     .await?;
+    ///
+    /// if let Some(stats) = client.realtime().subscription_stats(&id).await {
+    ///     println!(
+    ///         "{} events, {:?}/s, average lag {:?}",
+    ///         stats.events_received,
+    ///         stats.events_per_second(),
+    ///         stats.average_lag()
+    ///     );
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn subscription_stats(&self, subscription_id: &str) -> Option<SubscriptionStats> {
+        let active = self
+            .connection_manager
+            .subscriptions
+            .read()
+            .await
+            .contains_key(subscription_id);
+
+        let mut stats = self.connection_manager.stats.lock().ok()?;
+        if !active {
+            stats.remove(subscription_id);
+            return None;
+        }
 
-        // Ensure we're connected
-        self.connect().await?;
+        Some(stats.get(subscription_id).cloned().unwrap_or_default())
+    }
 
-        // Send join message to server
-        self.send_join_message(&topic, &subscription_config).await?;
+    /// Unsubscribe from every active subscription
+    ///
+    /// Leave messages are sent on a best-effort basis; subscriptions are removed
+    /// locally even if the server cannot be reached. All groups are cleared.
+    ///
+    /// # Examples
+    /// ```rust,no_run
+    /// # use supabase_lib_rs::Client;
+    /// # async fn example() -> supabase_lib_rs::Result<()> {
+    /// let client = Client::new("your-url", "your-key")?;
+    ///
+    /// client.realtime().unsubscribe_all().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn unsubscribe_all(&self) -> Result<()> {
+        debug!("Unsubscribing from all subscriptions");
 
-        // Store subscription
-        let subscription = Subscription {
-            id: subscription_id.clone(),
-            topic: topic.clone(),
-            config: subscription_config,
-            callback: Arc::new(callback),
+        let ids: Vec<String> = {
+            let subscriptions = self.connection_manager.subscriptions.read().await;
+            subscriptions.keys().cloned().collect()
         };
 
-        let mut subscriptions = self.connection_manager.subscriptions.write().await;
-        subscriptions.insert(subscription_id.clone(), subscription);
+        self.unsubscribe_many(&ids).await;
+        self.connection_manager.groups.write().await.clear();
 
-        info!("Subscribed to topic {} with ID {}", topic, subscription_id);
-        Ok(subscription_id)
+        info!("Unsubscribed from {} subscriptions", ids.len());
+        Ok(())
     }
 
-    /// Subscribe to a channel with custom configuration (WASM version)
-    #[cfg(target_arch = "wasm32")]
-    pub async fn subscribe<F>(
-        &self,
-        subscription_config: SubscriptionConfig,
-        callback: F,
-    ) -> Result<String>
-    where
-        F: Fn(RealtimeMessage) + 'static,
-    {
-        let subscription_id = Uuid::new_v4().to_string();
-        let topic = self.build_topic(&subscription_config);
-
-        debug!(
-            "Creating subscription {} for topic {}",
-            subscription_id, topic
-        );
-
-        // Ensure we're connected
-        self.connect().await?;
+    /// Get a named group for bulk management of subscriptions
+    ///
+    /// # Examples
+    /// ```rust,no_run
+    /// # use supabase_lib_rs::Client;
+    /// # async fn example() -> supabase_lib_rs::Result<()> {
+    /// let client = Client::new("your-url", "your-key")?;
+    /// let realtime = client.realtime();
+    ///
+    /// let posts = realtime.channel("posts").table("posts").subscribe(|_| {}).await?;
+    /// let comments = realtime.channel("comments").table("comments").subscribe(|_| {}).await?;
+    ///
+    /// let dashboard = realtime.group("dashboard");
+    /// dashboard.add(&posts).await;
+    /// dashboard.add(&comments).await;
+    ///
+    /// // On navigation away from the dashboard
+    /// realtime.close_group("dashboard").await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn group(&self, name: &str) -> ChannelGroup {
+        ChannelGroup {
+            realtime: self.clone(),
+            name: name.to_string(),
+        }
+    }
 
-        // Send join message to server
-        self.send_join_message(&topic, &subscription_config).await?;
+    /// Unsubscribe every subscription in a group and remove the group
+    pub async fn close_group(&self, name: &str) -> Result<()> {
+        debug!("Closing subscription group: {}", name);
 
-        // Store subscription
-        let subscription = Subscription {
-            id: subscription_id.clone(),
-            topic: topic.clone(),
-            config: subscription_config,
-            callback: Arc::new(callback),
-        };
+        let ids = self
+            .connection_manager
+            .groups
+            .write()
+            .await
+            .remove(name)
+            .unwrap_or_default();
 
-        let mut subscriptions = self.connection_manager.subscriptions.write().await;
-        subscriptions.insert(subscription_id.clone(), subscription);
+        self.unsubscribe_many(&ids).await;
 
-        info!("Subscribed to topic {} with ID {}", topic, subscription_id);
-        Ok(subscription_id)
+        info!("Closed group {} with {} subscriptions", name, ids.len());
+        Ok(())
     }
 
-    /// Build topic string from subscription config
-    fn build_topic(&self, config: &SubscriptionConfig) -> String {
-        if let Some(ref table) = config.table {
-            format!("realtime:{}:{}", config.schema, table)
-        } else {
-            format!("realtime:{}", config.schema)
-        }
+    /// Create a [`RowWatcher`] that shares one subscription per table
+    ///
+    /// # Examples
+    /// ```rust,no_run
+    /// # use supabase_lib_rs::Client;
+    /// # async fn example() -> supabase_lib_rs::Result<()> {
+    /// let client = Client::new("your-url", "your-key")?;
+    /// let watcher = client.realtime().row_watcher();
+    ///
+    /// // Both rows are served by a single `posts` channel
+    /// let first = watcher.watch_row("posts", 1, |msg| println!("Post 1: {}", msg.event)).await?;
+    /// let second = watcher.watch_row("posts", 2, |msg| println!("Post 2: {}", msg.event)).await?;
+    ///
+    /// first.unwatch().await?;
+    /// second.unwatch().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn row_watcher(&self) -> RowWatcher {
+        RowWatcher::new(self.clone())
     }
 
-    /// Send join message to Supabase realtime server
-    async fn send_join_message(&self, topic: &str, config: &SubscriptionConfig) -> Result<()> {
-        let mut payload = serde_json::Map::new();
-
-        if let Some(ref table) = config.table {
-            payload.insert(
-                "table".to_string(),
-                serde_json::Value::String(table.clone()),
-            );
-        }
-
-        if let Some(ref event) = config.event {
+    /// Create a [`ChangeRecorder`](recorder::ChangeRecorder) writing to `sink`
+    ///
+    /// # Examples
+    /// ```rust,no_run
+    /// # use supabase_lib_rs::Client;
+    /// use supabase_lib_rs::realtime::recorder::ChannelSink;
+    /// use futures_util::StreamExt;
+    ///
+    /// # async fn example() -> supabase_lib_rs::Result<()> {
+    /// let client = Client::new("your-url", "your-key")?;
+    /// let (sink, mut changes) = ChannelSink::new();
+    ///
+    /// let recorder = client.realtime().change_recorder(sink).table("orders");
+    /// recorder.start().await?;
+    ///
+    /// while let Some(change) = changes.next().await {
+    ///     println!("#{} {} on {}", change.sequence, change.event, change.table);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn change_recorder<S: recorder::ChangeSink + 'static>(
+        &self,
+        sink: S,
+    ) -> recorder::ChangeRecorder {
+        recorder::ChangeRecorder::new(self.clone(), sink)
+    }
+
+    /// Remove subscriptions and send leave messages, logging failures
+    async fn unsubscribe_many(&self, subscription_ids: &[String]) {
+        let removed: Vec<Subscription> = {
+            let mut subscriptions = self.connection_manager.subscriptions.write().await;
+            let removed: Vec<Subscription> = subscription_ids
+                .iter()
+                .filter_map(|id| subscriptions.remove(id))
+                .collect();
+            self.forget_presence(
+                &subscriptions,
+                removed
+                    .iter()
+                    .map(|subscription| subscription.topic.as_str()),
+            );
+            removed
+        };
+
+        if !self.is_connected().await {
+            return;
+        }
+
+        for subscription in removed {
+            if let Err(e) = self.send_leave_message(&subscription.topic).await {
+                warn!(
+                    "Failed to leave topic {} for subscription {}: {}",
+                    subscription.topic, subscription.id, e
+                );
+            }
+        }
+    }
+
+    /// Drop the presence state of `topics` no subscription is joined to anymore
+    fn forget_presence<'a>(
+        &self,
+        subscriptions: &HashMap<String, Subscription>,
+        topics: impl IntoIterator<Item = &'a str>,
+    ) {
+        if let Ok(mut presence_states) = self.connection_manager.presence_states.lock() {
+            for topic in topics {
+                if !subscriptions.values().any(|other| other.topic == topic) {
+                    presence_states.remove(topic);
+                }
+            }
+        }
+    }
+
+    /// Presences currently on `topic`
+    fn presence_map(&self, topic: &str) -> PresenceMap {
+        self.connection_manager
+            .presence_states
+            .lock()
+            .ok()
+            .and_then(|presence_states| presence_states.get(topic).map(|tracker| tracker.state()))
+            .unwrap_or_default()
+    }
+
+    /// Authorize channel joins with the access token from `provider`
+    ///
+    /// Used while no token was set through [`set_auth`](Self::set_auth).
+    /// [`Client`](crate::Client) sets this to its auth session.
+    pub fn with_token_provider(self, provider: Arc<dyn TokenProvider>) -> Self {
+        if let Ok(mut token_provider) = self.connection_manager.token_provider.lock() {
+            *token_provider = RequestAuth::new(provider);
+        }
+        self
+    }
+
+    /// Set the user access token used to authorize channels
+    ///
+    /// The token is sent with every subsequent channel join, and pushed to the
+    /// already joined channels when connected. Passing `None` falls back to the
+    /// token provider, if any, and then to the API key, i.e. anonymous access.
+    ///
+    /// # Examples
+    /// ```rust,no_run
+    /// # use supabase_lib_rs::Client;
+    /// # async fn example() -> supabase_lib_rs::Result<()> {
+    /// let client = Client::new("your-url", "your-key")?;
+    ///
+    /// client.realtime().set_auth(Some("user-jwt".to_string())).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn set_auth(&self, access_token: Option<String>) -> Result<()> {
+        debug!("Updating realtime access token");
+
+        *self.connection_manager.access_token.write().await = access_token.clone();
+
+        if !self.is_connected().await {
+            return Ok(());
+        }
+
+        let token = access_token.unwrap_or_else(|| self.connection_manager.api_key.clone());
+        for topic in self.subscription_topics(|_| true).await {
+            let message = RealtimeProtocolMessage {
+                topic,
+                event: "access_token".to_string(),
+                payload: serde_json::json!({ "access_token": token }),
+                ref_id: Uuid::new_v4().to_string(),
+            };
+            self.send_message(&message).await?;
+        }
+
+        Ok(())
+    }
+
+    /// React to an authentication state change
+    ///
+    /// - `SignedIn`: switch to the session's token and rejoin private channels
+    ///   so they are authorized for the new user
+    /// - `TokenRefreshed`: push the new token to joined channels
+    /// - `SignedOut`: drop private subscriptions and rejoin the remaining
+    ///   channels anonymously
+    ///
+    /// A [`Client`](crate::Client) forwards auth events here automatically on
+    /// native targets; on WASM call it from an auth state listener.
+    #[cfg(feature = "auth-core")]
+    pub async fn handle_auth_event(
+        &self,
+        event: AuthEvent,
+        session: Option<&Session>,
+    ) -> Result<()> {
+        let access_token = session.map(|session| session.access_token.clone());
+
+        match event {
+            AuthEvent::SignedIn => {
+                self.set_auth(access_token).await?;
+                self.rejoin(|config| config.private).await
+            }
+            AuthEvent::TokenRefreshed => self.set_auth(access_token).await,
+            AuthEvent::SignedOut => {
+                *self.connection_manager.access_token.write().await = None;
+
+                let private_ids: Vec<String> = {
+                    let subscriptions = self.connection_manager.subscriptions.read().await;
+                    subscriptions
+                        .values()
+                        .filter(|subscription| subscription.config.private)
+                        .map(|subscription| subscription.id.clone())
+                        .collect()
+                };
+                self.unsubscribe_many(&private_ids).await;
+
+                info!(
+                    "Dropped {} private subscriptions after sign-out",
+                    private_ids.len()
+                );
+                self.rejoin(|_| true).await
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Forward auth state changes of `auth` to [`Realtime::handle_auth_event`]
+    #[cfg(all(feature = "auth-core", not(target_arch = "wasm32")))]
+    pub fn bind_auth(&self, auth: &Auth) -> AuthEventHandle {
+        let realtime = self.clone();
+
+        auth.on_auth_state_change(move |event, session| {
+            let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+                warn!(
+                    "No async runtime to apply auth event {:?} to realtime",
+                    event
+                );
+                return;
+            };
+
+            let realtime = realtime.clone();
+            runtime.spawn(async move {
+                if let Err(e) = realtime.handle_auth_event(event, session.as_ref()).await {
+                    warn!("Failed to apply auth event to realtime: {}", e);
+                }
+            });
+        })
+    }
+
+    /// Topics of subscriptions whose config matches `filter`
+    async fn subscription_topics<P>(&self, filter: P) -> Vec<String>
+    where
+        P: Fn(&SubscriptionConfig) -> bool,
+    {
+        let subscriptions = self.connection_manager.subscriptions.read().await;
+        let mut topics: Vec<String> = subscriptions
+            .values()
+            .filter(|subscription| filter(&subscription.config))
+            .map(|subscription| subscription.topic.clone())
+            .collect();
+        topics.sort();
+        topics.dedup();
+        topics
+    }
+
+    /// Leave and re-join matching channels with the current access token
+    #[cfg(feature = "auth-core")]
+    async fn rejoin<P>(&self, filter: P) -> Result<()>
+    where
+        P: Fn(&SubscriptionConfig) -> bool,
+    {
+        if !self.is_connected().await {
+            return Ok(());
+        }
+
+        let channels: Vec<(String, SubscriptionConfig)> = {
+            let subscriptions = self.connection_manager.subscriptions.read().await;
+            subscriptions
+                .values()
+                .filter(|subscription| filter(&subscription.config))
+                .map(|subscription| (subscription.topic.clone(), subscription.config.clone()))
+                .collect()
+        };
+
+        for (topic, config) in &channels {
+            self.send_leave_message(topic).await?;
+            self.send_join_message(topic, config).await?;
+        }
+
+        debug!("Rejoined {} channels", channels.len());
+        self.retrack_presence(|topic| channels.iter().any(|(rejoined, _)| rejoined == topic))
+            .await;
+        Ok(())
+    }
+
+    /// Subscribe to a channel with custom configuration
+    #[cfg(not(target_arch = "wasm32"))]
+    pub async fn subscribe<F>(
+        &self,
+        subscription_config: SubscriptionConfig,
+        callback: F,
+    ) -> Result<String>
+    where
+        F: Fn(RealtimeMessage) + Send + Sync + 'static,
+    {
+        subscription_config.validate()?;
+        let topic = self.build_topic(&subscription_config);
+        self.subscribe_topic(topic, subscription_config, Arc::new(callback), None)
+            .await
+    }
+
+    /// Subscribe to a channel with custom configuration (WASM version)
+    #[cfg(target_arch = "wasm32")]
+    pub async fn subscribe<F>(
+        &self,
+        subscription_config: SubscriptionConfig,
+        callback: F,
+    ) -> Result<String>
+    where
+        F: Fn(RealtimeMessage) + 'static,
+    {
+        subscription_config.validate()?;
+        let topic = self.build_topic(&subscription_config);
+        self.subscribe_topic(topic, subscription_config, Arc::new(callback), None)
+            .await
+    }
+
+    /// Join `topic` and route its messages to `callback`
+    async fn subscribe_topic(
+        &self,
+        topic: String,
+        subscription_config: SubscriptionConfig,
+        callback: MessageCallback,
+        channel: Option<Arc<channel::ChannelShared>>,
+    ) -> Result<String> {
+        let subscription_id = Uuid::new_v4().to_string();
+
+        debug!(
+            "Creating subscription {} for topic {}",
+            subscription_id, topic
+        );
+
+        // Ensure we're connected
+        self.connect().await?;
+
+        // Send join message to server
+        self.send_join_message(&topic, &subscription_config).await?;
+
+        // Store subscription
+        let subscription = Subscription {
+            id: subscription_id.clone(),
+            topic: topic.clone(),
+            config: subscription_config,
+            callback,
+            channel,
+        };
+
+        let mut subscriptions = self.connection_manager.subscriptions.write().await;
+        subscriptions.insert(subscription_id.clone(), subscription);
+
+        info!("Subscribed to topic {} with ID {}", topic, subscription_id);
+        Ok(subscription_id)
+    }
+
+    /// Build topic string from subscription config
+    fn build_topic(&self, config: &SubscriptionConfig) -> String {
+        if let Some(ref table) = config.table {
+            format!("realtime:{}:{}", config.schema, table)
+        } else {
+            format!("realtime:{}", config.schema)
+        }
+    }
+
+    /// Send join message to Supabase realtime server
+    async fn send_join_message(&self, topic: &str, config: &SubscriptionConfig) -> Result<()> {
+        let mut payload = serde_json::Map::new();
+
+        if let Some(ref table) = config.table {
+            payload.insert(
+                "table".to_string(),
+                serde_json::Value::String(table.clone()),
+            );
+        }
+
+        if let Some(ref event) = config.event {
             let event_str = match event {
                 RealtimeEvent::Insert => "INSERT",
                 RealtimeEvent::Update => "UPDATE",
@@ -845,6 +2559,44 @@ impl Realtime {
             );
         }
 
+        let mut join_config = serde_json::Map::new();
+        if config.private {
+            join_config.insert("private".to_string(), serde_json::Value::Bool(true));
+        }
+        if let Some(broadcast) = config.broadcast_join_config() {
+            join_config.insert("broadcast".to_string(), broadcast);
+        }
+        if config.enable_presence {
+            join_config.insert("presence".to_string(), serde_json::json!({ "key": "" }));
+        }
+        if !config.postgres_changes.is_empty() {
+            join_config.insert(
+                "postgres_changes".to_string(),
+                config
+                    .postgres_changes
+                    .iter()
+                    .map(PostgresChanges::join_config)
+                    .collect(),
+            );
+        }
+        if !join_config.is_empty() {
+            payload.insert("config".to_string(), serde_json::Value::Object(join_config));
+        }
+
+        let access_token = self.connection_manager.access_token.read().await.clone();
+        if let Some(access_token) = access_token.or_else(|| {
+            self.connection_manager
+                .token_provider
+                .lock()
+                .ok()
+                .and_then(|provider| provider.access_token())
+        }) {
+            payload.insert(
+                "access_token".to_string(),
+                serde_json::Value::String(access_token),
+            );
+        }
+
         let message = RealtimeProtocolMessage {
             topic: topic.to_string(),
             event: "phx_join".to_string(),
@@ -869,7 +2621,7 @@ impl Realtime {
 
     /// Send message through WebSocket
     async fn send_message(&self, message: &RealtimeProtocolMessage) -> Result<()> {
-        let message_json = serde_json::to_string(message)?;
+        let message_json = self.connection_manager.encode(message)?;
 
         let mut connection_guard = self.connection_manager.connection.write().await;
         if let Some(ref mut connection) = *connection_guard {
@@ -883,14 +2635,14 @@ impl Realtime {
     }
 
     /// Start the message processing loop
-    async fn start_message_loop(&self) -> Result<()> {
+    fn start_message_loop(&self) {
         if self
             .connection_manager
             .is_message_loop_running
             .load(Ordering::SeqCst)
         {
             debug!("Message loop already running");
-            return Ok(());
+            return;
         }
 
         self.connection_manager
@@ -921,7 +2673,83 @@ impl Realtime {
         }
 
         info!("Started realtime message loop");
-        Ok(())
+    }
+
+    /// Start sending heartbeats on the current socket
+    fn start_heartbeat(&self) {
+        let generation = self
+            .connection_manager
+            .connection_generation
+            .fetch_add(1, Ordering::SeqCst)
+            + 1;
+        if let Ok(mut pending) = self.connection_manager.pending_heartbeat.lock() {
+            *pending = None;
+        }
+
+        let heartbeat = self.clone().heartbeat_loop(generation);
+        #[cfg(not(target_arch = "wasm32"))]
+        tokio::spawn(heartbeat);
+        #[cfg(target_arch = "wasm32")]
+        wasm_bindgen_futures::spawn_local(heartbeat);
+    }
+
+    /// Send heartbeats on the `phoenix` topic until the socket is replaced
+    ///
+    /// The server closes sockets that stay silent for about a minute. A
+    /// heartbeat still unanswered when the next one is due means the
+    /// connection is dead, so it is closed and the reconnect logic takes over.
+    async fn heartbeat_loop(self, generation: u64) {
+        let interval = self
+            .connection_manager
+            .reconnect
+            .lock()
+            .map(|config| config.keep_alive_interval)
+            .unwrap_or(30);
+        if interval == 0 {
+            debug!("Realtime heartbeat disabled");
+            return;
+        }
+
+        loop {
+            sleep(Duration::from_secs(interval)).await;
+
+            let manager = &self.connection_manager;
+            if !self.message_loop_handle.load(Ordering::SeqCst)
+                || manager.connection_generation.load(Ordering::SeqCst) != generation
+            {
+                return;
+            }
+
+            let ref_id = manager
+                .ref_counter
+                .fetch_add(1, Ordering::SeqCst)
+                .to_string();
+            let missed = match manager.pending_heartbeat.lock() {
+                Ok(mut pending) => pending.replace(ref_id.clone()).is_some(),
+                Err(_) => false,
+            };
+            if missed {
+                warn!("Realtime heartbeat was not answered, treating the connection as lost");
+                let mut connection_guard = manager.connection.write().await;
+                if let Some(ref mut connection) = *connection_guard {
+                    if let Err(e) = connection.close().await {
+                        debug!("Failed to close unresponsive connection: {}", e);
+                    }
+                }
+                return;
+            }
+
+            let message = RealtimeProtocolMessage {
+                topic: "phoenix".to_string(),
+                event: "heartbeat".to_string(),
+                payload: serde_json::Value::Object(serde_json::Map::new()),
+                ref_id,
+            };
+            if let Err(e) = self.send_message(&message).await {
+                debug!("Stopping realtime heartbeat: {}", e);
+                return;
+            }
+        }
     }
 
     /// Main message processing loop
@@ -942,7 +2770,16 @@ impl Realtime {
                         break;
                     }
 
-                    match connection.receive().await {
+                    // Bound the wait so the lock is released for outgoing messages
+                    #[cfg(not(target_arch = "wasm32"))]
+                    let received =
+                        tokio::time::timeout(RECEIVE_POLL_INTERVAL, connection.receive())
+                            .await
+                            .unwrap_or(Ok(None));
+                    #[cfg(target_arch = "wasm32")]
+                    let received = connection.receive().await;
+
+                    match received {
                         Ok(Some(msg)) => Some(msg),
                         Ok(None) => None,
                         Err(e) => {
@@ -956,12 +2793,40 @@ impl Realtime {
                 }
             };
 
+            let message = message.and_then(|frame| connection_manager.intercept_inbound(frame));
             if let Some(message_str) = message {
                 debug!("Received realtime message: {}", message_str);
 
                 // Parse the message
-                match serde_json::from_str::<RealtimeMessage>(&message_str) {
+                match RealtimeMessage::decode(&message_str) {
+                    Ok(realtime_message) if realtime_message.topic == "phoenix" => {
+                        if let Ok(mut pending) = connection_manager.pending_heartbeat.lock() {
+                            if *pending == realtime_message.ref_id {
+                                *pending = None;
+                            }
+                        }
+                    }
                     Ok(realtime_message) => {
+                        if realtime_message.event == "broadcast" {
+                            Self::process_broadcast(
+                                &connection_manager,
+                                &realtime_message.topic,
+                                &message_str,
+                            )
+                            .await;
+                        }
+                        if matches!(
+                            realtime_message.event.as_str(),
+                            "presence_state" | "presence_diff"
+                        ) {
+                            Self::process_presence(
+                                &connection_manager,
+                                &realtime_message.topic,
+                                &realtime_message.event,
+                                &message_str,
+                            )
+                            .await;
+                        }
                         // Process the message
                         Self::process_message(&connection_manager, realtime_message).await;
                     }
@@ -981,43 +2846,130 @@ impl Realtime {
             }
 
             // Small delay to prevent busy waiting
-            #[cfg(not(target_arch = "wasm32"))]
-            tokio::time::sleep(Duration::from_millis(10)).await;
-
-            #[cfg(target_arch = "wasm32")]
-            {
-                // For WASM, use a simple promise-based delay
-                use wasm_bindgen::prelude::*;
-                use wasm_bindgen_futures::JsFuture;
-
-                let promise = js_sys::Promise::new(&mut |resolve, _| {
-                    web_sys::window()
-                        .unwrap()
-                        .set_timeout_with_callback_and_timeout_and_arguments_0(&resolve, 10)
-                        .unwrap();
-                });
-                let _ = JsFuture::from(promise).await;
-            }
+            sleep(Duration::from_millis(10)).await;
         }
 
         connection_manager
             .is_message_loop_running
             .store(false, Ordering::SeqCst);
         debug!("Realtime message loop stopped");
+
+        // Still running means the connection dropped rather than disconnect()
+        if loop_handle.load(Ordering::SeqCst) {
+            let realtime = Realtime {
+                connection_manager,
+                message_loop_handle: loop_handle,
+            };
+            #[cfg(not(target_arch = "wasm32"))]
+            {
+                let reconnect: Pin<Box<dyn Future<Output = ()> + Send>> =
+                    Box::pin(realtime.reconnect());
+                tokio::spawn(reconnect);
+            }
+            #[cfg(target_arch = "wasm32")]
+            {
+                let reconnect: Pin<Box<dyn Future<Output = ()>>> = Box::pin(realtime.reconnect());
+                wasm_bindgen_futures::spawn_local(reconnect);
+            }
+        }
     }
 
-    /// Process incoming realtime message
-    async fn process_message(
+    /// Hand an incoming broadcast to the broadcast callbacks of its channel
+    async fn process_broadcast(
         connection_manager: &Arc<ConnectionManager>,
-        message: RealtimeMessage,
+        topic: &str,
+        frame: &str,
     ) {
-        debug!("Processing message for topic: {}", message.topic);
+        let payload = match serde_json::from_str::<serde_json::Value>(frame) {
+            Ok(serde_json::Value::Array(mut parts)) if parts.len() == 5 => parts.swap_remove(4),
+            Ok(mut object) => object["payload"].take(),
+            Err(_) => return,
+        };
+        let message: BroadcastMessage = match serde_json::from_value(payload) {
+            Ok(message) => message,
+            Err(e) => {
+                debug!("Ignoring malformed broadcast on {}: {}", topic, e);
+                return;
+            }
+        };
 
-        let subscriptions = connection_manager.subscriptions.read().await;
+        let callbacks: Vec<_> = connection_manager
+            .subscriptions
+            .read()
+            .await
+            .values()
+            .filter(|subscription| Self::topic_matches(&subscription.topic, topic))
+            .filter_map(|subscription| subscription.config.broadcast_callback.clone())
+            .collect();
+        for callback in callbacks {
+            callback(message.clone());
+        }
+    }
 
-        let mut matched_subscriptions = Vec::new();
+    /// Apply a presence state or diff to `topic` and notify its presence callbacks
+    async fn process_presence(
+        connection_manager: &Arc<ConnectionManager>,
+        topic: &str,
+        event: &str,
+        frame: &str,
+    ) {
+        let payload = match serde_json::from_str::<serde_json::Value>(frame) {
+            Ok(serde_json::Value::Array(mut parts)) if parts.len() == 5 => parts.swap_remove(4),
+            Ok(mut object) => object["payload"].take(),
+            Err(_) => return,
+        };
 
-        // Find matching subscriptions
+        let (changes, state) = match connection_manager.presence_states.lock() {
+            Ok(mut presence_states) => {
+                let tracker = presence_states.entry(topic.to_string()).or_default();
+                (tracker.apply(event, &payload), tracker.state())
+            }
+            Err(_) => return,
+        };
+        debug!("Presence update on {}: {} changes", topic, changes.len());
+
+        let (callbacks, channels): (Vec<_>, Vec<_>) = connection_manager
+            .subscriptions
+            .read()
+            .await
+            .values()
+            .filter(|subscription| subscription.topic == topic)
+            .map(|subscription| {
+                (
+                    subscription.config.presence_callback.clone(),
+                    subscription.channel.clone(),
+                )
+            })
+            .unzip();
+
+        for callback in callbacks.into_iter().flatten() {
+            for (event_type, change) in &changes {
+                for presence_state in &change.presences {
+                    callback(PresenceEvent {
+                        event_type: event_type.clone(),
+                        user_id: presence_state.user_id.clone(),
+                        presence_state: presence_state.clone(),
+                    });
+                }
+            }
+        }
+        for channel in channels.into_iter().flatten() {
+            channel.presence_changed(&changes, &state);
+        }
+    }
+
+    /// Process incoming realtime message
+    async fn process_message(
+        connection_manager: &Arc<ConnectionManager>,
+        message: RealtimeMessage,
+    ) {
+        debug!("Processing message for topic: {}", message.topic);
+
+        let subscriptions = connection_manager.subscriptions.read().await;
+
+        let mut matched_subscriptions = Vec::new();
+
+        // Find matching subscriptions
         for subscription in subscriptions.values() {
             if Self::topic_matches(&subscription.topic, &message.topic) {
                 // Check event filter
@@ -1042,10 +2994,116 @@ impl Realtime {
 
         drop(subscriptions); // Explicitly drop the guard
 
+        let received_at = chrono::Utc::now();
+        if !matched_subscriptions.is_empty() {
+            if let Ok(mut stats) = connection_manager.stats.lock() {
+                for subscription in &matched_subscriptions {
+                    stats
+                        .entry(subscription.id.clone())
+                        .or_default()
+                        .record(received_at, message.payload.commit_timestamp.as_deref());
+                }
+            }
+        }
+
         // Call callbacks for matched subscriptions
         for subscription in matched_subscriptions {
-            debug!("Calling callback for subscription: {}", subscription.id);
-            (subscription.callback)(message.clone());
+            let gate = match (
+                &subscription.config.resync,
+                connection_manager.resyncs.lock(),
+            ) {
+                (Some(policy), Ok(mut resyncs)) => resyncs
+                    .entry(subscription.id.clone())
+                    .or_default()
+                    .gate(&message, received_at, policy.max_lag),
+                _ => ResyncGate::Deliver,
+            };
+
+            match gate {
+                ResyncGate::Deliver => {
+                    debug!("Calling callback for subscription: {}", subscription.id);
+                    (subscription.callback)(message.clone());
+                }
+                ResyncGate::Hold => {}
+                ResyncGate::Start(started_at) => {
+                    info!(
+                        "Subscription {} lags behind, resyncing from a snapshot",
+                        subscription.id
+                    );
+                    let resync =
+                        Self::resync(Arc::clone(connection_manager), subscription, started_at);
+                    #[cfg(not(target_arch = "wasm32"))]
+                    tokio::spawn(resync);
+                    #[cfg(target_arch = "wasm32")]
+                    wasm_bindgen_futures::spawn_local(resync);
+                }
+            }
+        }
+    }
+
+    /// Deliver a snapshot in place of a lagging change stream, then the held changes
+    async fn resync(
+        connection_manager: Arc<ConnectionManager>,
+        subscription: Subscription,
+        started_at: chrono::DateTime<chrono::Utc>,
+    ) {
+        let Some(policy) = subscription.config.resync.clone() else {
+            return;
+        };
+
+        match (policy.snapshot)(&subscription.config).await {
+            Ok(rows) => {
+                debug!(
+                    "Delivering {} snapshot rows to subscription {}",
+                    rows.len(),
+                    subscription.id
+                );
+                (subscription.callback)(RealtimeMessage {
+                    event: RESYNC_EVENT.to_string(),
+                    payload: RealtimePayload {
+                        record: Some(serde_json::Value::Array(rows)),
+                        old_record: None,
+                        schema: Some(subscription.config.schema.clone()),
+                        table: subscription.config.table.clone(),
+                        commit_timestamp: Some(started_at.to_rfc3339()),
+                        event_type: Some(RESYNC_EVENT.to_string()),
+                        new: None,
+                        old: None,
+                    },
+                    ref_id: None,
+                    topic: subscription.topic.clone(),
+                });
+            }
+            Err(e) => warn!(
+                "Snapshot for subscription {} failed, resuming live changes: {}",
+                subscription.id, e
+            ),
+        }
+
+        // Changes held during delivery are picked up by the next round, so
+        // live delivery only resumes once nothing is held back
+        loop {
+            let held = {
+                let Ok(mut resyncs) = connection_manager.resyncs.lock() else {
+                    return;
+                };
+                let Some(state) = resyncs.get_mut(&subscription.id) else {
+                    // Unsubscribed during the snapshot
+                    return;
+                };
+                match &mut state.running {
+                    Some((_, held)) if !held.is_empty() => std::mem::take(held),
+                    _ => {
+                        state.running = None;
+                        state.covered_until = Some(started_at);
+                        break;
+                    }
+                }
+            };
+
+            for message in held {
+                (subscription.callback)(message);
+            }
         }
     }
 
@@ -1083,35 +3141,142 @@ impl Realtime {
             presence_state.user_id, channel
         );
 
-        let topic = format!("realtime:{}", channel);
-        let ref_id = Uuid::new_v4().to_string();
+        self.send_presence_track(channel, &presence_state).await?;
+        info!(
+            "Started tracking presence for user {}",
+            presence_state.user_id
+        );
+
+        // Remembered so the state can be re-sent after a reconnect or rejoin
+        if let Ok(mut presence) = self.connection_manager.presence.lock() {
+            presence.insert(channel.to_string(), presence_state);
+        }
 
+        Ok(())
+    }
+
+    /// Track presence and keep re-sending it every `refresh_interval`
+    ///
+    /// Periodic re-tracking restores the presence if the server dropped it,
+    /// e.g. after a network blip. Refreshing stops when the returned handle
+    /// is dropped or untracked.
+    ///
+    /// # Examples
+    /// ```rust,no_run
+    /// use std::time::Duration;
+    /// use supabase_lib_rs::realtime::PresenceState;
+    ///
+    /// # async fn example(realtime: &supabase_lib_rs::realtime::Realtime) -> supabase_lib_rs::Result<()> {
+    /// let presence = PresenceState {
+    ///     user_id: "user123".to_string(),
+    ///     online_at: chrono::Utc::now().to_rfc3339(),
+    ///     metadata: None,
+    /// };
+    ///
+    /// let keep_alive = realtime
+    ///     .track_presence_auto("lobby", presence, Duration::from_secs(30))
+    ///     .await?;
+    /// // ... later
+    /// keep_alive.untrack().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn track_presence_auto(
+        &self,
+        channel: &str,
+        presence_state: PresenceState,
+        refresh_interval: Duration,
+    ) -> Result<PresenceKeepAlive> {
+        let user_id = presence_state.user_id.clone();
+        self.track_presence(channel, presence_state).await?;
+
+        let active = Arc::new(AtomicBool::new(true));
+        let realtime = self.clone();
+        let refresh_channel = channel.to_string();
+        let refresh_active = Arc::clone(&active);
+        let refresh = async move {
+            loop {
+                sleep(refresh_interval).await;
+                if !refresh_active.load(Ordering::SeqCst) {
+                    break;
+                }
+
+                let state = realtime
+                    .connection_manager
+                    .presence
+                    .lock()
+                    .ok()
+                    .and_then(|presence| presence.get(&refresh_channel).cloned());
+                let Some(state) = state else {
+                    // Untracked through `untrack_presence`
+                    refresh_active.store(false, Ordering::SeqCst);
+                    break;
+                };
+
+                if realtime.is_connected().await {
+                    if let Err(e) = realtime.send_presence_track(&refresh_channel, &state).await {
+                        warn!("Failed to refresh presence in {}: {}", refresh_channel, e);
+                    }
+                }
+            }
+            debug!("Stopped refreshing presence in {}", refresh_channel);
+        };
+
+        #[cfg(not(target_arch = "wasm32"))]
+        tokio::spawn(refresh);
+        #[cfg(target_arch = "wasm32")]
+        wasm_bindgen_futures::spawn_local(refresh);
+
+        Ok(PresenceKeepAlive {
+            realtime: self.clone(),
+            channel: channel.to_string(),
+            user_id,
+            active,
+        })
+    }
+
+    /// Send a presence `track` message without remembering the state
+    async fn send_presence_track(
+        &self,
+        channel: &str,
+        presence_state: &PresenceState,
+    ) -> Result<()> {
         let message = RealtimeProtocolMessage {
-            topic: topic.clone(),
+            topic: format!("realtime:{}", channel),
             event: "presence".to_string(),
             payload: serde_json::json!({
                 "event": "track",
                 "payload": presence_state
             }),
-            ref_id,
+            ref_id: Uuid::new_v4().to_string(),
         };
 
-        let mut connection_guard = self.connection_manager.connection.write().await;
-        if let Some(ref mut connection) = *connection_guard {
-            let message_json = serde_json::to_string(&message).map_err(|e| {
-                Error::realtime(format!("Failed to serialize presence message: {}", e))
-            })?;
+        self.send_message(&message).await
+    }
 
-            connection.send(&message_json).await?;
-            info!(
-                "Started tracking presence for user {}",
-                presence_state.user_id
-            );
-        } else {
-            return Err(Error::realtime("Not connected to realtime server"));
+    /// Re-send tracked presence for channels whose topic passes `filter`
+    async fn retrack_presence<P>(&self, filter: P)
+    where
+        P: Fn(&str) -> bool,
+    {
+        let tracked: Vec<(String, PresenceState)> = match self.connection_manager.presence.lock() {
+            Ok(presence) => presence
+                .iter()
+                .filter(|(channel, _)| filter(&format!("realtime:{}", channel)))
+                .map(|(channel, state)| (channel.clone(), state.clone()))
+                .collect(),
+            Err(_) => return,
+        };
+
+        for (channel, state) in &tracked {
+            if let Err(e) = self.send_presence_track(channel, state).await {
+                warn!("Failed to re-track presence in {}: {}", channel, e);
+            }
         }
 
-        Ok(())
+        if !tracked.is_empty() {
+            debug!("Re-tracked presence in {} channels", tracked.len());
+        }
     }
 
     /// Stop tracking user presence in a channel
@@ -1129,6 +3294,10 @@ impl Realtime {
             user_id, channel
         );
 
+        if let Ok(mut presence) = self.connection_manager.presence.lock() {
+            presence.remove(channel);
+        }
+
         let topic = format!("realtime:{}", channel);
         let ref_id = Uuid::new_v4().to_string();
 
@@ -1146,9 +3315,7 @@ impl Realtime {
 
         let mut connection_guard = self.connection_manager.connection.write().await;
         if let Some(ref mut connection) = *connection_guard {
-            let message_json = serde_json::to_string(&message).map_err(|e| {
-                Error::realtime(format!("Failed to serialize presence message: {}", e))
-            })?;
+            let message_json = self.connection_manager.encode(&message)?;
 
             connection.send(&message_json).await?;
             info!("Stopped tracking presence for user {}", user_id);
@@ -1161,6 +3328,9 @@ impl Realtime {
 
     /// Get all users currently present in a channel
     ///
+    /// Answers from the presence state the server sent for a joined channel,
+    /// so it is empty until a subscription on the channel has been joined.
+    ///
     /// # Examples
     /// ```rust,no_run
     /// # async fn example(realtime: &supabase_lib_rs::realtime::Realtime) -> supabase_lib_rs::Result<()> {
@@ -1173,32 +3343,7 @@ impl Realtime {
         debug!("Getting presence for channel: {}", channel);
 
         let topic = format!("realtime:{}", channel);
-        let ref_id = Uuid::new_v4().to_string();
-
-        let message = RealtimeProtocolMessage {
-            topic: topic.clone(),
-            event: "presence".to_string(),
-            payload: serde_json::json!({
-                "event": "state"
-            }),
-            ref_id,
-        };
-
-        let mut connection_guard = self.connection_manager.connection.write().await;
-        if let Some(ref mut connection) = *connection_guard {
-            let message_json = serde_json::to_string(&message).map_err(|e| {
-                Error::realtime(format!("Failed to serialize presence message: {}", e))
-            })?;
-
-            connection.send(&message_json).await?;
-
-            // Note: In a real implementation, you'd wait for the response
-            // For now, returning empty vec as this would require more complex message handling
-            info!("Requested presence state for channel: {}", channel);
-            Ok(Vec::new())
-        } else {
-            Err(Error::realtime("Not connected to realtime server"))
-        }
+        Ok(self.presence_map(&topic).into_values().flatten().collect())
     }
 
     /// Send a broadcast message to all subscribers in a channel
@@ -1249,9 +3394,7 @@ impl Realtime {
 
         let mut connection_guard = self.connection_manager.connection.write().await;
         if let Some(ref mut connection) = *connection_guard {
-            let message_json = serde_json::to_string(&message).map_err(|e| {
-                Error::realtime(format!("Failed to serialize broadcast message: {}", e))
-            })?;
+            let message_json = self.connection_manager.encode(&message)?;
 
             connection.send(&message_json).await?;
             info!("Sent broadcast message to channel: {}", channel);
@@ -1262,6 +3405,42 @@ impl Realtime {
         Ok(())
     }
 
+    /// Send a broadcast with a MessagePack-encoded payload
+    ///
+    /// Realtime frames are JSON text, so the MessagePack bytes are sent
+    /// base64-encoded and the frame is usually larger than the same payload
+    /// sent as JSON (base64 adds about a third). Use it for peers that exchange
+    /// MessagePack; receivers decode it with [`BroadcastMessage::decode`] or a
+    /// [`BroadcastMessage::typed`] callback.
+    ///
+    /// # Examples
+    /// ```rust,no_run
+    /// use serde::Serialize;
+    ///
+    /// #[derive(Serialize)]
+    /// struct Cursor {
+    ///     x: f64,
+    ///     y: f64,
+    /// }
+    ///
+    /// # async fn example(realtime: &supabase_lib_rs::realtime::Realtime) -> supabase_lib_rs::Result<()> {
+    /// realtime
+    ///     .broadcast_binary("canvas", "cursor", &Cursor { x: 10.0, y: 20.0 })
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "realtime-msgpack")]
+    pub async fn broadcast_binary<T: Serialize + ?Sized>(
+        &self,
+        channel: &str,
+        event: &str,
+        payload: &T,
+    ) -> Result<()> {
+        self.broadcast(channel, event, msgpack_envelope(payload)?, None)
+            .await
+    }
+
     /// Subscribe to a channel with advanced configuration
     ///
     /// This method provides more control over subscriptions including presence tracking,
@@ -1286,6 +3465,7 @@ impl Realtime {
     ///     ],
     ///     enable_presence: true,
     ///     enable_broadcast: true,
+    ///     private: false,
     ///     presence_callback: Some(Arc::new(|event| {
     ///         println!("Presence event: {:?}", event);
     ///     })),
@@ -1313,6 +3493,7 @@ impl Realtime {
         F: Fn(RealtimeMessage) + Send + Sync + 'static,
     {
         debug!("Creating advanced subscription for channel: {}", channel);
+        config.validate_join(true)?;
 
         let subscription_id = Uuid::new_v4().to_string();
         let topic = if let Some(ref table) = config.table {
@@ -1372,6 +3553,7 @@ impl Realtime {
                 ..config.clone()
             },
             callback: Arc::new(callback),
+            channel: None,
         };
 
         // Store subscription
@@ -1390,7 +3572,7 @@ impl Realtime {
         let mut join_payload = serde_json::json!({
             "config": {
                 "postgres_changes": [{
-                    "event": config.event.unwrap_or(RealtimeEvent::All),
+                    "event": config.event.clone().unwrap_or(RealtimeEvent::All),
                     "schema": config.schema,
                 }]
             }
@@ -1412,8 +3594,8 @@ impl Realtime {
         }
 
         // Add broadcast configuration
-        if config.enable_broadcast {
-            join_payload["config"]["broadcast"] = serde_json::json!({ "self": true });
+        if let Some(broadcast) = config.broadcast_join_config() {
+            join_payload["config"]["broadcast"] = broadcast;
         }
 
         let join_message = RealtimeProtocolMessage {
@@ -1425,8 +3607,7 @@ impl Realtime {
 
         let mut connection_guard = self.connection_manager.connection.write().await;
         if let Some(ref mut connection) = *connection_guard {
-            let message_json = serde_json::to_string(&join_message)
-                .map_err(|e| Error::realtime(format!("Failed to serialize join message: {}", e)))?;
+            let message_json = self.connection_manager.encode(&join_message)?;
 
             connection.send(&message_json).await?;
             info!("Advanced subscription created: {}", subscription_id);
@@ -1449,6 +3630,7 @@ impl Realtime {
         F: Fn(RealtimeMessage) + 'static,
     {
         debug!("Creating advanced subscription for channel: {}", channel);
+        config.validate_join(true)?;
 
         let subscription_id = Uuid::new_v4().to_string();
         let topic = if let Some(ref table) = config.table {
@@ -1508,6 +3690,7 @@ impl Realtime {
                 ..config.clone()
             },
             callback: Arc::new(callback),
+            channel: None,
         };
 
         // Store subscription
@@ -1526,7 +3709,7 @@ impl Realtime {
         let mut join_payload = serde_json::json!({
             "config": {
                 "postgres_changes": [{
-                    "event": config.event.unwrap_or(RealtimeEvent::All),
+                    "event": config.event.clone().unwrap_or(RealtimeEvent::All),
                     "schema": config.schema,
                 }]
             }
@@ -1548,8 +3731,8 @@ impl Realtime {
         }
 
         // Add broadcast configuration
-        if config.enable_broadcast {
-            join_payload["config"]["broadcast"] = serde_json::json!({ "self": true });
+        if let Some(broadcast) = config.broadcast_join_config() {
+            join_payload["config"]["broadcast"] = broadcast;
         }
 
         let join_message = RealtimeProtocolMessage {
@@ -1561,8 +3744,7 @@ impl Realtime {
 
         let mut connection_guard = self.connection_manager.connection.write().await;
         if let Some(ref mut connection) = *connection_guard {
-            let message_json = serde_json::to_string(&join_message)
-                .map_err(|e| Error::realtime(format!("Failed to serialize join message: {}", e)))?;
+            let message_json = self.connection_manager.encode(&join_message)?;
 
             connection.send(&message_json).await?;
             info!("Advanced subscription created: {}", subscription_id);
@@ -1574,6 +3756,53 @@ impl Realtime {
     }
 }
 
+/// Named group of subscriptions that can be torn down together
+///
+/// Created with [`Realtime::group`]. Groups only hold subscription IDs, so the
+/// same subscription may belong to several groups.
+#[cfg(feature = "realtime")]
+#[derive(Debug, Clone)]
+pub struct ChannelGroup {
+    realtime: Realtime,
+    name: String,
+}
+
+#[cfg(feature = "realtime")]
+impl ChannelGroup {
+    /// Group name
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Add a subscription to the group
+    pub async fn add(&self, subscription_id: &str) {
+        let mut groups = self.realtime.connection_manager.groups.write().await;
+        let ids = groups.entry(self.name.clone()).or_default();
+        if !ids.iter().any(|id| id == subscription_id) {
+            ids.push(subscription_id.to_string());
+        }
+    }
+
+    /// Remove a subscription from the group without unsubscribing it
+    pub async fn remove(&self, subscription_id: &str) {
+        let mut groups = self.realtime.connection_manager.groups.write().await;
+        if let Some(ids) = groups.get_mut(&self.name) {
+            ids.retain(|id| id != subscription_id);
+        }
+    }
+
+    /// Subscription IDs currently in the group
+    pub async fn subscription_ids(&self) -> Vec<String> {
+        let groups = self.realtime.connection_manager.groups.read().await;
+        groups.get(&self.name).cloned().unwrap_or_default()
+    }
+
+    /// Unsubscribe every subscription in the group and remove the group
+    pub async fn close(&self) -> Result<()> {
+        self.realtime.close_group(&self.name).await
+    }
+}
+
 /// Builder for channel subscriptions
 ///
 /// Provides a fluent API for configuring realtime subscriptions.
@@ -1601,6 +3830,8 @@ impl Realtime {
 pub struct ChannelBuilder {
     realtime: Realtime,
     config: SubscriptionConfig,
+    handlers: Vec<(RealtimeEvent, MessageCallback)>,
+    stream_capacity: usize,
 }
 
 #[cfg(feature = "realtime")]
@@ -1695,6 +3926,82 @@ impl ChannelBuilder {
         self
     }
 
+    /// Mark the channel as private
+    ///
+    /// Private channels are authorized with the signed-in user's token and are
+    /// dropped when the user signs out.
+    ///
+    /// # Examples
+    /// ```rust,no_run
+    /// # use supabase_lib_rs::Client;
+    /// # async fn example() -> supabase_lib_rs::Result<()> {
+    /// let client = Client::new("your-url", "your-key")?;
+    ///
+    /// let subscription = client.realtime()
+    ///     .channel("inbox")
+    ///     .table("messages")
+    ///     .private()
+    ///     .subscribe(|_| {})
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn private(mut self) -> Self {
+        self.config.private = true;
+        self
+    }
+
+    /// Enable broadcast on the channel with the given options
+    ///
+    /// # Examples
+    /// ```rust,no_run
+    /// # use supabase_lib_rs::Client;
+    /// # use supabase_lib_rs::realtime::BroadcastOptions;
+    /// # async fn example() -> supabase_lib_rs::Result<()> {
+    /// let client = Client::new("your-url", "your-key")?;
+    ///
+    /// // Catch up on the last minute of chat after joining
+    /// let subscription = client.realtime()
+    ///     .channel("room-1")
+    ///     .table("messages")
+    ///     .private()
+    ///     .broadcast(BroadcastOptions {
+    ///         ack: true,
+    ///         replay_since: Some(chrono::Utc::now() - chrono::Duration::minutes(1)),
+    ///         ..Default::default()
+    ///     })
+    ///     .subscribe(|_| {})
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn broadcast(mut self, options: BroadcastOptions) -> Self {
+        self.config.enable_broadcast = true;
+        self.config.broadcast_options = Some(options);
+        self
+    }
+
+    /// Replace the change stream with a snapshot when it lags behind
+    ///
+    /// The snapshot arrives at the subscription's callback as a
+    /// [`RESYNC_EVENT`] message; see [`ResyncPolicy`].
+    pub fn resync_on_lag(mut self, policy: ResyncPolicy) -> Self {
+        self.config.resync = Some(policy);
+        self
+    }
+
+    /// Set how many messages a stream buffers before dropping the oldest
+    ///
+    /// Applies to [`subscribe_stream`](Self::subscribe_stream) and
+    /// [`subscribe_changes`](Self::subscribe_changes); defaults to
+    /// [`DEFAULT_STREAM_CAPACITY`]. A full buffer drops its oldest message
+    /// rather than stalling the connection, which also carries heartbeats and
+    /// every other channel; [`RealtimeStream::dropped`] counts the losses.
+    pub fn stream_capacity(mut self, capacity: usize) -> Self {
+        self.stream_capacity = capacity.max(1);
+        self
+    }
+
     /// Subscribe with a callback function
     ///
     /// # Examples
@@ -1734,6 +4041,668 @@ impl ChannelBuilder {
     {
         self.realtime.subscribe(self.config, callback).await
     }
+
+    /// Register a callback for INSERT events, used by [`subscribe_all`](Self::subscribe_all)
+    ///
+    /// # Examples
+    /// ```rust,no_run
+    /// # use supabase_lib_rs::Client;
+    /// # async fn example() -> supabase_lib_rs::Result<()> {
+    /// let client = Client::new("your-url", "your-key")?;
+    ///
+    /// let subscription_id = client.realtime()
+    ///     .channel("posts")
+    ///     .table("posts")
+    ///     .on_insert(|message| println!("New post: {:?}", message.payload.record))
+    ///     .on_update(|message| println!("Post updated: {:?}", message.payload.record))
+    ///     .on_delete(|message| println!("Post deleted: {:?}", message.payload.old_record))
+    ///     .subscribe_all()
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn on_insert<F>(self, callback: F) -> Self
+    where
+        F: Fn(RealtimeMessage) + Send + Sync + 'static,
+    {
+        self.on(RealtimeEvent::Insert, Arc::new(callback))
+    }
+
+    /// Register a callback for UPDATE events, used by [`subscribe_all`](Self::subscribe_all)
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn on_update<F>(self, callback: F) -> Self
+    where
+        F: Fn(RealtimeMessage) + Send + Sync + 'static,
+    {
+        self.on(RealtimeEvent::Update, Arc::new(callback))
+    }
+
+    /// Register a callback for DELETE events, used by [`subscribe_all`](Self::subscribe_all)
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn on_delete<F>(self, callback: F) -> Self
+    where
+        F: Fn(RealtimeMessage) + Send + Sync + 'static,
+    {
+        self.on(RealtimeEvent::Delete, Arc::new(callback))
+    }
+
+    /// Register a callback for INSERT events (WASM version)
+    #[cfg(target_arch = "wasm32")]
+    pub fn on_insert<F>(self, callback: F) -> Self
+    where
+        F: Fn(RealtimeMessage) + 'static,
+    {
+        self.on(RealtimeEvent::Insert, Arc::new(callback))
+    }
+
+    /// Register a callback for UPDATE events (WASM version)
+    #[cfg(target_arch = "wasm32")]
+    pub fn on_update<F>(self, callback: F) -> Self
+    where
+        F: Fn(RealtimeMessage) + 'static,
+    {
+        self.on(RealtimeEvent::Update, Arc::new(callback))
+    }
+
+    /// Register a callback for DELETE events (WASM version)
+    #[cfg(target_arch = "wasm32")]
+    pub fn on_delete<F>(self, callback: F) -> Self
+    where
+        F: Fn(RealtimeMessage) + 'static,
+    {
+        self.on(RealtimeEvent::Delete, Arc::new(callback))
+    }
+
+    /// Subscribe once and route each message to the callbacks registered for its event
+    ///
+    /// Registering callbacks for a single event type narrows the subscription
+    /// to that event; otherwise all events are requested from the server.
+    pub async fn subscribe_all(mut self) -> Result<String> {
+        if self.handlers.is_empty() {
+            return Err(Error::invalid_input(
+                "subscribe_all requires at least one on_insert, on_update or on_delete callback",
+            ));
+        }
+
+        let first = self.handlers[0].0.clone();
+        self.config.event = if self.handlers.iter().all(|(event, _)| *event == first) {
+            Some(first)
+        } else {
+            Some(RealtimeEvent::All)
+        };
+
+        let dispatch = Self::dispatcher(self.handlers);
+        self.realtime
+            .subscribe(self.config, move |message| dispatch(message))
+            .await
+    }
+
+    /// Subscribe and receive messages as a [`Stream`](futures_util::Stream)
+    ///
+    /// Dropping the stream unsubscribes. Messages the stream has not yet
+    /// yielded are buffered up to [`stream_capacity`](Self::stream_capacity).
+    ///
+    /// # Examples
+    /// ```rust,no_run
+    /// # use supabase_lib_rs::Client;
+    /// use futures_util::StreamExt;
+    ///
+    /// # async fn example() -> supabase_lib_rs::Result<()> {
+    /// let client = Client::new("your-url", "your-key")?;
+    ///
+    /// let mut stream = client.realtime()
+    ///     .channel("posts")
+    ///     .table("posts")
+    ///     .subscribe_stream()
+    ///     .await?;
+    ///
+    /// while let Some(message) = stream.next().await {
+    ///     println!("{}: {:?}", message.event, message.payload.record);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn subscribe_stream(self) -> Result<RealtimeStream> {
+        let queue = Arc::new(StreamQueue::new(self.stream_capacity));
+        let sender = queue.clone();
+        let subscription_id = self
+            .realtime
+            .subscribe(self.config, move |message| sender.push(message))
+            .await?;
+
+        Ok(RealtimeStream {
+            realtime: self.realtime,
+            subscription_id,
+            queue,
+        })
+    }
+
+    /// Subscribe and receive database changes decoded into `T`
+    ///
+    /// Changes whose record does not deserialize into `T` are logged and
+    /// skipped. Dropping the stream unsubscribes.
+    ///
+    /// # Examples
+    /// ```rust,no_run
+    /// # use supabase_lib_rs::Client;
+    /// use futures_util::StreamExt;
+    /// use serde::Deserialize;
+    /// use supabase_lib_rs::realtime::ChangeEvent;
+    ///
+    /// #[derive(Debug, Deserialize)]
+    /// struct Post {
+    ///     id: i64,
+    ///     title: String,
+    /// }
+    ///
+    /// # async fn example() -> supabase_lib_rs::Result<()> {
+    /// let client = Client::new("your-url", "your-key")?;
+    ///
+    /// let mut changes = client.realtime()
+    ///     .channel("posts")
+    ///     .table("posts")
+    ///     .subscribe_changes::<Post>()
+    ///     .await?;
+    ///
+    /// while let Some(change) = changes.next().await {
+    ///     match change {
+    ///         ChangeEvent::Insert(post) => println!("New post: {}", post.title),
+    ///         ChangeEvent::Update { new, .. } => println!("Post {} updated", new.id),
+    ///         ChangeEvent::Delete { old } => println!("Post deleted: {}", old),
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn subscribe_changes<T>(self) -> Result<ChangeStream<T>>
+    where
+        T: DeserializeOwned,
+    {
+        Ok(ChangeStream {
+            messages: self.subscribe_stream().await?,
+            _record: PhantomData,
+        })
+    }
+
+    fn on(mut self, event: RealtimeEvent, callback: MessageCallback) -> Self {
+        self.handlers.push((event, callback));
+        self
+    }
+
+    /// Build a callback that routes messages by their event type
+    fn dispatcher(handlers: Vec<(RealtimeEvent, MessageCallback)>) -> MessageCallback {
+        Arc::new(move |message: RealtimeMessage| {
+            let event = match message.event.as_str() {
+                "INSERT" => RealtimeEvent::Insert,
+                "UPDATE" => RealtimeEvent::Update,
+                "DELETE" => RealtimeEvent::Delete,
+                other => {
+                    debug!("No event callback for realtime event: {}", other);
+                    return;
+                }
+            };
+
+            for (_, callback) in handlers.iter().filter(|(handled, _)| *handled == event) {
+                callback(message.clone());
+            }
+        })
+    }
+}
+
+/// Fan-out of one table subscription to per-row listeners
+///
+/// Watching many rows with a channel each quickly runs into the server's
+/// channel limit. A `RowWatcher` instead joins one channel per table on the
+/// first [`watch_row`](Self::watch_row) and routes each change to the listeners
+/// registered for the row's key. The channel is left once the last listener
+/// of the table is removed.
+///
+/// Rows are matched on the `id` column unless another key column is set with
+/// [`key_column`](Self::key_column). Deletes are matched on the old record.
+#[cfg(feature = "realtime")]
+#[derive(Clone)]
+pub struct RowWatcher {
+    realtime: Realtime,
+    key_column: String,
+    tables: Arc<RuntimeLock<HashMap<String, WatchedTable>>>,
+    next_id: Arc<AtomicU64>,
+}
+
+/// Listeners of one watched table, keyed by row key
+#[cfg(feature = "realtime")]
+type RowListeners = Arc<Mutex<HashMap<String, Vec<(u64, MessageCallback)>>>>;
+
+#[cfg(feature = "realtime")]
+struct WatchedTable {
+    subscription_id: String,
+    listeners: RowListeners,
+}
+
+#[cfg(feature = "realtime")]
+impl std::fmt::Debug for RowWatcher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RowWatcher")
+            .field("key_column", &self.key_column)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(feature = "realtime")]
+impl RowWatcher {
+    /// Create a watcher matching rows on the `id` column
+    pub fn new(realtime: Realtime) -> Self {
+        Self {
+            realtime,
+            key_column: "id".to_string(),
+            tables: Arc::new(RuntimeLock::new(HashMap::new())),
+            next_id: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    /// Match rows on another column, e.g. a UUID or natural key
+    pub fn key_column(mut self, column: &str) -> Self {
+        self.key_column = column.to_string();
+        self
+    }
+
+    /// Call `callback` for every change to the row of `table` with key `key`
+    ///
+    /// Joins the table's channel if no row of it is watched yet.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub async fn watch_row<K, F>(&self, table: &str, key: K, callback: F) -> Result<RowWatch>
+    where
+        K: std::fmt::Display,
+        F: Fn(RealtimeMessage) + Send + Sync + 'static,
+    {
+        self.watch(table, key.to_string(), Arc::new(callback)).await
+    }
+
+    /// Call `callback` for every change to one row (WASM version)
+    #[cfg(target_arch = "wasm32")]
+    pub async fn watch_row<K, F>(&self, table: &str, key: K, callback: F) -> Result<RowWatch>
+    where
+        K: std::fmt::Display,
+        F: Fn(RealtimeMessage) + 'static,
+    {
+        self.watch(table, key.to_string(), Arc::new(callback)).await
+    }
+
+    /// Number of rows currently watched in `table`
+    pub async fn watched_rows(&self, table: &str) -> usize {
+        let tables = self.tables.read().await;
+        tables
+            .get(table)
+            .and_then(|watched| watched.listeners.lock().ok().map(|l| l.len()))
+            .unwrap_or_default()
+    }
+
+    /// Drop every listener and leave all table channels
+    pub async fn close(&self) -> Result<()> {
+        let ids: Vec<String> = self
+            .tables
+            .write()
+            .await
+            .drain()
+            .map(|(_, watched)| watched.subscription_id)
+            .collect();
+        self.realtime.unsubscribe_many(&ids).await;
+        Ok(())
+    }
+
+    async fn watch(&self, table: &str, key: String, callback: MessageCallback) -> Result<RowWatch> {
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+        let mut tables = self.tables.write().await;
+
+        if !tables.contains_key(table) {
+            let listeners: RowListeners = Arc::new(Mutex::new(HashMap::new()));
+            let dispatch = Self::dispatcher(self.key_column.clone(), listeners.clone());
+            let subscription_id = self
+                .realtime
+                .channel(table)
+                .table(table)
+                .subscribe(move |message| dispatch(message))
+                .await?;
+            debug!(
+                "Watching table {} via subscription {}",
+                table, subscription_id
+            );
+            tables.insert(
+                table.to_string(),
+                WatchedTable {
+                    subscription_id,
+                    listeners,
+                },
+            );
+        }
+
+        if let Some(watched) = tables.get(table) {
+            if let Ok(mut listeners) = watched.listeners.lock() {
+                listeners
+                    .entry(key.clone())
+                    .or_default()
+                    .push((id, callback));
+            }
+        }
+
+        Ok(RowWatch {
+            watcher: self.clone(),
+            table: table.to_string(),
+            key,
+            id,
+        })
+    }
+
+    async fn unwatch(&self, table: &str, key: &str, id: u64) -> Result<()> {
+        let mut tables = self.tables.write().await;
+        let Some(watched) = tables.get(table) else {
+            return Ok(());
+        };
+
+        let table_empty = match watched.listeners.lock() {
+            Ok(mut listeners) => {
+                if let Some(callbacks) = listeners.get_mut(key) {
+                    callbacks.retain(|(listener, _)| *listener != id);
+                    if callbacks.is_empty() {
+                        listeners.remove(key);
+                    }
+                }
+                listeners.is_empty()
+            }
+            Err(_) => false,
+        };
+
+        if table_empty {
+            if let Some(watched) = tables.remove(table) {
+                debug!("No rows watched in {}, leaving its channel", table);
+                self.realtime.unsubscribe(&watched.subscription_id).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Build a callback that routes messages by the changed row's key
+    fn dispatcher(key_column: String, listeners: RowListeners) -> MessageCallback {
+        Arc::new(move |message: RealtimeMessage| {
+            let Some(key) = row_key(&message, &key_column) else {
+                debug!("Realtime change without a {} value", key_column);
+                return;
+            };
+
+            let callbacks: Vec<MessageCallback> = match listeners.lock() {
+                Ok(listeners) => listeners
+                    .get(&key)
+                    .map(|callbacks| callbacks.iter().map(|(_, cb)| cb.clone()).collect())
+                    .unwrap_or_default(),
+                Err(_) => return,
+            };
+
+            for callback in callbacks {
+                callback(message.clone());
+            }
+        })
+    }
+}
+
+/// Key of the row a change applies to, taken from the old record for deletes
+#[cfg(feature = "realtime")]
+fn row_key(message: &RealtimeMessage, key_column: &str) -> Option<String> {
+    let payload = &message.payload;
+    let records = if message.event == "DELETE" {
+        [
+            &payload.old_record,
+            &payload.old,
+            &payload.record,
+            &payload.new,
+        ]
+    } else {
+        [
+            &payload.record,
+            &payload.new,
+            &payload.old_record,
+            &payload.old,
+        ]
+    };
+
+    let value = records
+        .into_iter()
+        .flatten()
+        .find_map(|record| record.get(key_column))?;
+    match value {
+        serde_json::Value::Null => None,
+        serde_json::Value::String(key) => Some(key.clone()),
+        other => Some(other.to_string()),
+    }
+}
+
+/// Listener registered with [`RowWatcher::watch_row`]
+///
+/// Dropping the handle keeps the listener; call [`unwatch`](Self::unwatch)
+/// to remove it.
+#[cfg(feature = "realtime")]
+#[derive(Debug)]
+pub struct RowWatch {
+    watcher: RowWatcher,
+    table: String,
+    key: String,
+    id: u64,
+}
+
+#[cfg(feature = "realtime")]
+impl RowWatch {
+    /// Watched table
+    pub fn table(&self) -> &str {
+        &self.table
+    }
+
+    /// Watched row key
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// Remove the listener, leaving the table channel if it was the last one
+    pub async fn unwatch(self) -> Result<()> {
+        self.watcher.unwatch(&self.table, &self.key, self.id).await
+    }
+}
+
+/// Messages buffered between a subscription callback and its stream
+///
+/// The callback runs on the connection's message loop, so a full queue drops
+/// its oldest message instead of waiting for the stream to catch up.
+#[cfg(feature = "realtime")]
+struct StreamQueue {
+    messages: Mutex<VecDeque<RealtimeMessage>>,
+    capacity: usize,
+    dropped: AtomicU64,
+    waker: AtomicWaker,
+}
+
+#[cfg(feature = "realtime")]
+impl StreamQueue {
+    fn new(capacity: usize) -> Self {
+        Self {
+            messages: Mutex::new(VecDeque::new()),
+            capacity,
+            dropped: AtomicU64::new(0),
+            waker: AtomicWaker::new(),
+        }
+    }
+
+    fn push(&self, message: RealtimeMessage) {
+        if let Ok(mut messages) = self.messages.lock() {
+            if messages.len() >= self.capacity {
+                messages.pop_front();
+                if self.dropped.fetch_add(1, Ordering::Relaxed) == 0 {
+                    warn!(
+                        "Realtime stream is full ({} messages), dropping the oldest",
+                        self.capacity
+                    );
+                }
+            }
+            messages.push_back(message);
+        }
+        self.waker.wake();
+    }
+
+    fn poll_pop(&self, cx: &mut Context<'_>) -> Poll<RealtimeMessage> {
+        self.waker.register(cx.waker());
+        match self.messages.lock().ok().and_then(|mut m| m.pop_front()) {
+            Some(message) => Poll::Ready(message),
+            None => Poll::Pending,
+        }
+    }
+}
+
+/// Stream of messages from [`ChannelBuilder::subscribe_stream`]
+///
+/// The stream never ends on its own; drop it to unsubscribe.
+#[cfg(feature = "realtime")]
+pub struct RealtimeStream {
+    realtime: Realtime,
+    subscription_id: String,
+    queue: Arc<StreamQueue>,
+}
+
+#[cfg(feature = "realtime")]
+impl std::fmt::Debug for RealtimeStream {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RealtimeStream")
+            .field("subscription_id", &self.subscription_id)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(feature = "realtime")]
+impl RealtimeStream {
+    /// ID of the underlying subscription
+    pub fn subscription_id(&self) -> &str {
+        &self.subscription_id
+    }
+
+    /// Messages dropped because the stream's buffer was full
+    pub fn dropped(&self) -> u64 {
+        self.queue.dropped.load(Ordering::Relaxed)
+    }
+}
+
+#[cfg(feature = "realtime")]
+impl Stream for RealtimeStream {
+    type Item = RealtimeMessage;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.queue.poll_pop(cx).map(Some)
+    }
+}
+
+#[cfg(feature = "realtime")]
+impl Drop for RealtimeStream {
+    fn drop(&mut self) {
+        let realtime = self.realtime.clone();
+        let subscription_id = std::mem::take(&mut self.subscription_id);
+        let unsubscribe = async move {
+            if let Err(e) = realtime.unsubscribe(&subscription_id).await {
+                debug!(
+                    "Failed to unsubscribe dropped stream {}: {}",
+                    subscription_id, e
+                );
+            }
+        };
+
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Ok(runtime) = tokio::runtime::Handle::try_current() {
+            runtime.spawn(unsubscribe);
+        }
+        #[cfg(target_arch = "wasm32")]
+        wasm_bindgen_futures::spawn_local(unsubscribe);
+    }
+}
+
+/// Database change decoded into a record type
+///
+/// Old records are kept as JSON: with the default replica identity they only
+/// hold the primary key.
+#[cfg(feature = "realtime")]
+#[derive(Debug, Clone, PartialEq)]
+pub enum ChangeEvent<T> {
+    Insert(T),
+    Update { new: T, old: serde_json::Value },
+    Delete { old: serde_json::Value },
+}
+
+#[cfg(feature = "realtime")]
+impl<T: DeserializeOwned> ChangeEvent<T> {
+    /// Decode an INSERT, UPDATE or DELETE message
+    pub fn from_message(message: &RealtimeMessage) -> Result<Self> {
+        let payload = &message.payload;
+        let new = payload.record.as_ref().or(payload.new.as_ref());
+        let old = payload
+            .old_record
+            .as_ref()
+            .or(payload.old.as_ref())
+            .cloned()
+            .unwrap_or(serde_json::Value::Null);
+        let decode = |record: Option<&serde_json::Value>| -> Result<T> {
+            let record = record.ok_or_else(|| {
+                Error::realtime(format!("{} message without a record", message.event))
+            })?;
+            Ok(serde_json::from_value(record.clone())?)
+        };
+
+        match message.event.as_str() {
+            "INSERT" => Ok(Self::Insert(decode(new)?)),
+            "UPDATE" => Ok(Self::Update {
+                new: decode(new)?,
+                old,
+            }),
+            "DELETE" => Ok(Self::Delete { old }),
+            other => Err(Error::realtime(format!(
+                "Not a database change event: {}",
+                other
+            ))),
+        }
+    }
+}
+
+/// Stream of typed changes from [`ChannelBuilder::subscribe_changes`]
+///
+/// The stream never ends on its own; drop it to unsubscribe.
+#[cfg(feature = "realtime")]
+#[derive(Debug)]
+pub struct ChangeStream<T> {
+    messages: RealtimeStream,
+    _record: PhantomData<fn() -> T>,
+}
+
+#[cfg(feature = "realtime")]
+impl<T> ChangeStream<T> {
+    /// ID of the underlying subscription
+    pub fn subscription_id(&self) -> &str {
+        self.messages.subscription_id()
+    }
+
+    /// Messages dropped because the stream's buffer was full
+    pub fn dropped(&self) -> u64 {
+        self.messages.dropped()
+    }
+}
+
+#[cfg(feature = "realtime")]
+impl<T: DeserializeOwned> Stream for ChangeStream<T> {
+    type Item = ChangeEvent<T>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        loop {
+            let message = match Pin::new(&mut self.messages).poll_next(cx) {
+                Poll::Ready(Some(message)) => message,
+                other => return other.map(|_| None),
+            };
+            match ChangeEvent::from_message(&message) {
+                Ok(change) => return Poll::Ready(Some(change)),
+                Err(e) => warn!("Skipping realtime {} message: {}", message.event, e),
+            }
+        }
+    }
 }
 
 #[cfg(all(test, feature = "realtime"))]
@@ -1743,15 +4712,71 @@ mod tests {
     use std::sync::Arc;
 
     #[tokio::test]
-    async fn test_realtime_creation() {
+    async fn test_realtime_creation() {
+        let config = Arc::new(SupabaseConfig {
+            url: "https://test.supabase.co".to_string(),
+            key: "test-key".to_string(),
+            ..Default::default()
+        });
+
+        let realtime = Realtime::new(config).unwrap();
+        assert!(!realtime.is_connected().await);
+    }
+
+    #[tokio::test]
+    async fn test_event_callbacks_dispatch() {
+        use std::sync::atomic::AtomicUsize;
+
+        let inserts = Arc::new(AtomicUsize::new(0));
+        let deletes = Arc::new(AtomicUsize::new(0));
+        let handlers: Vec<(RealtimeEvent, MessageCallback)> = vec![
+            (RealtimeEvent::Insert, {
+                let inserts = inserts.clone();
+                Arc::new(move |_| {
+                    inserts.fetch_add(1, Ordering::SeqCst);
+                })
+            }),
+            (RealtimeEvent::Delete, {
+                let deletes = deletes.clone();
+                Arc::new(move |_| {
+                    deletes.fetch_add(1, Ordering::SeqCst);
+                })
+            }),
+        ];
+        let dispatch = ChannelBuilder::dispatcher(handlers);
+
+        let message = |event: &str| RealtimeMessage {
+            event: event.to_string(),
+            payload: RealtimePayload {
+                record: None,
+                old_record: None,
+                schema: None,
+                table: None,
+                commit_timestamp: None,
+                event_type: None,
+                new: None,
+                old: None,
+            },
+            ref_id: None,
+            topic: "realtime:public:posts".to_string(),
+        };
+
+        dispatch(message("INSERT"));
+        dispatch(message("INSERT"));
+        dispatch(message("UPDATE"));
+        dispatch(message("DELETE"));
+        dispatch(message("phx_reply"));
+
+        assert_eq!(inserts.load(Ordering::SeqCst), 2);
+        assert_eq!(deletes.load(Ordering::SeqCst), 1);
+
         let config = Arc::new(SupabaseConfig {
             url: "https://test.supabase.co".to_string(),
             key: "test-key".to_string(),
             ..Default::default()
         });
-
         let realtime = Realtime::new(config).unwrap();
-        assert!(!realtime.is_connected().await);
+        assert!(realtime.channel("posts").subscribe_all().await.is_err());
     }
 
     #[tokio::test]
@@ -1910,6 +4935,93 @@ mod tests {
         assert!(!called.load(Ordering::SeqCst));
     }
 
+    #[tokio::test]
+    async fn test_channel_groups() {
+        let config = Arc::new(SupabaseConfig {
+            url: "https://test.supabase.co".to_string(),
+            key: "test-key".to_string(),
+            ..Default::default()
+        });
+
+        let realtime = Realtime::new(config).unwrap();
+        let group = realtime.group("dashboard");
+
+        group.add("sub-1").await;
+        group.add("sub-2").await;
+        group.add("sub-1").await;
+        assert_eq!(group.subscription_ids().await, vec!["sub-1", "sub-2"]);
+
+        group.remove("sub-2").await;
+        assert_eq!(group.subscription_ids().await, vec!["sub-1"]);
+
+        // Closing works without a connection and drops the group
+        realtime.close_group("dashboard").await.unwrap();
+        assert!(group.subscription_ids().await.is_empty());
+
+        realtime.group("other").add("sub-3").await;
+        realtime.unsubscribe_all().await.unwrap();
+        assert!(realtime.group("other").subscription_ids().await.is_empty());
+    }
+
+    #[cfg(feature = "auth-core")]
+    #[tokio::test]
+    async fn test_sign_out_drops_private_subscriptions() {
+        let config = Arc::new(SupabaseConfig {
+            url: "https://test.supabase.co".to_string(),
+            key: "test-key".to_string(),
+            ..Default::default()
+        });
+
+        let realtime = Realtime::new(config).unwrap();
+        {
+            let mut subscriptions = realtime.connection_manager.subscriptions.write().await;
+            for (id, private) in [("public-sub", false), ("private-sub", true)] {
+                subscriptions.insert(
+                    id.to_string(),
+                    Subscription {
+                        id: id.to_string(),
+                        topic: format!("realtime:public:{}", id),
+                        config: SubscriptionConfig {
+                            private,
+                            ..Default::default()
+                        },
+                        callback: Arc::new(|_| {}),
+                        channel: None,
+                    },
+                );
+            }
+        }
+
+        realtime
+            .set_auth(Some("user-jwt".to_string()))
+            .await
+            .unwrap();
+        assert_eq!(
+            realtime
+                .connection_manager
+                .access_token
+                .read()
+                .await
+                .as_deref(),
+            Some("user-jwt")
+        );
+
+        realtime
+            .handle_auth_event(AuthEvent::SignedOut, None)
+            .await
+            .unwrap();
+
+        assert!(realtime
+            .connection_manager
+            .access_token
+            .read()
+            .await
+            .is_none());
+        let subscriptions = realtime.connection_manager.subscriptions.read().await;
+        assert!(subscriptions.contains_key("public-sub"));
+        assert!(!subscriptions.contains_key("private-sub"));
+    }
+
     #[tokio::test]
     async fn test_protocol_message_serialization() {
         let message = RealtimeProtocolMessage {
@@ -1925,6 +5037,463 @@ mod tests {
         assert!(serialized.contains("posts"));
     }
 
+    #[tokio::test]
+    async fn test_v2_frame_encoding_and_decoding() {
+        let frame =
+            r#"["1","2","realtime:public:posts","INSERT",{"record":{"id":1},"table":"posts"}]"#;
+        let message = RealtimeMessage::decode(frame).unwrap();
+        assert_eq!(message.event, "INSERT");
+        assert_eq!(message.topic, "realtime:public:posts");
+        assert_eq!(message.ref_id.as_deref(), Some("2"));
+        assert_eq!(message.payload.table.as_deref(), Some("posts"));
+
+        let frame = r#"[null,null,"phoenix","phx_reply",{"status":"ok"}]"#;
+        assert!(RealtimeMessage::decode(frame).unwrap().ref_id.is_none());
+
+        let config = Arc::new(SupabaseConfig {
+            url: "https://test.supabase.co".to_string(),
+            key: "test-key".to_string(),
+            ..Default::default()
+        });
+        let realtime = Realtime::new(config).unwrap();
+        assert_eq!(realtime.protocol_version(), ProtocolVersion::V1);
+        realtime.set_protocol_version(ProtocolVersion::V2);
+
+        let message = |event: &str, ref_id: &str| RealtimeProtocolMessage {
+            topic: "realtime:public:posts".to_string(),
+            event: event.to_string(),
+            payload: serde_json::json!({}),
+            ref_id: ref_id.to_string(),
+        };
+        let manager = &realtime.connection_manager;
+        assert_eq!(
+            manager.encode(&message("phx_join", "1")).unwrap(),
+            r#"["1","1","realtime:public:posts","phx_join",{}]"#
+        );
+        assert_eq!(
+            manager.encode(&message("presence", "2")).unwrap(),
+            r#"["1","2","realtime:public:posts","presence",{}]"#
+        );
+        assert_eq!(
+            manager.encode(&message("phx_leave", "3")).unwrap(),
+            r#"["1","3","realtime:public:posts","phx_leave",{}]"#
+        );
+        assert_eq!(
+            manager.encode(&message("presence", "4")).unwrap(),
+            r#"[null,"4","realtime:public:posts","presence",{}]"#
+        );
+    }
+
+    #[tokio::test]
+    async fn test_presence_keep_alive() {
+        let server = testing::MockRealtimeServer::start().await.unwrap();
+        let realtime = Realtime::new(Arc::new(SupabaseConfig {
+            url: server.url(),
+            key: "test-key".to_string(),
+            ..Default::default()
+        }))
+        .unwrap();
+        realtime.connect().await.unwrap();
+
+        let presence_frames = || {
+            server
+                .frames()
+                .into_iter()
+                .filter(|frame| frame.event == "presence" && frame.payload["event"] == "track")
+                .count()
+        };
+
+        let keep_alive = realtime
+            .track_presence_auto(
+                "lobby",
+                PresenceState {
+                    user_id: "user-1".to_string(),
+                    online_at: "2024-01-01T00:00:00Z".to_string(),
+                    metadata: None,
+                },
+                Duration::from_millis(50),
+            )
+            .await
+            .unwrap();
+        assert_eq!(keep_alive.channel(), "lobby");
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while presence_frames() < 3 {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap();
+
+        // Dropping the handle stops the refresh but keeps the presence tracked
+        drop(keep_alive);
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        let stopped = presence_frames();
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        assert_eq!(presence_frames(), stopped);
+
+        // The last state is re-sent after reconnecting
+        server.disconnect_clients();
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while realtime.is_connected().await {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap();
+        realtime.connect().await.unwrap();
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while presence_frames() == stopped {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap();
+        let retracked = server.frames().pop().unwrap();
+        assert_eq!(retracked.topic, "realtime:lobby");
+        assert_eq!(retracked.payload["payload"]["user_id"], "user-1");
+        assert_eq!(presence_frames(), stopped + 1);
+
+        // Untracked presence is not restored
+        realtime.untrack_presence("lobby", "user-1").await.unwrap();
+        assert!(realtime
+            .connection_manager
+            .presence
+            .lock()
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_broadcast_join_options() {
+        let server = testing::MockRealtimeServer::start().await.unwrap();
+        let realtime = Realtime::new(Arc::new(SupabaseConfig {
+            url: server.url(),
+            key: "test-key".to_string(),
+            ..Default::default()
+        }))
+        .unwrap();
+
+        let since = chrono::DateTime::from_timestamp_millis(1_700_000_000_000).unwrap();
+        realtime
+            .channel("room")
+            .table("messages")
+            .private()
+            .broadcast(BroadcastOptions {
+                ack: true,
+                replay_since: Some(since),
+                replay_limit: Some(25),
+                ..Default::default()
+            })
+            .subscribe(|_| {})
+            .await
+            .unwrap();
+
+        let join = server
+            .wait_for_event("phx_join", Duration::from_secs(5))
+            .await
+            .unwrap();
+        assert_eq!(
+            join.payload["config"],
+            serde_json::json!({
+                "private": true,
+                "broadcast": {
+                    "ack": true,
+                    "self": false,
+                    "replay": {"since": 1_700_000_000_000i64, "limit": 25}
+                }
+            })
+        );
+
+        let legacy = SubscriptionConfig {
+            enable_broadcast: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            legacy.broadcast_join_config(),
+            Some(serde_json::json!({ "self": true }))
+        );
+        assert_eq!(SubscriptionConfig::default().broadcast_join_config(), None);
+    }
+
+    #[cfg(feature = "realtime-msgpack")]
+    #[tokio::test]
+    async fn test_binary_broadcast() {
+        const TIMEOUT: Duration = Duration::from_secs(5);
+
+        #[derive(Debug, PartialEq, Serialize, Deserialize)]
+        struct Cursor {
+            x: f64,
+            y: f64,
+        }
+
+        let server = testing::MockRealtimeServer::start().await.unwrap();
+        let realtime = Realtime::new(Arc::new(SupabaseConfig {
+            url: server.url(),
+            key: "test-key".to_string(),
+            ..Default::default()
+        }))
+        .unwrap();
+        realtime.connect().await.unwrap();
+
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let sink = received.clone();
+        realtime
+            .subscribe_advanced(
+                "canvas",
+                SubscriptionConfig {
+                    enable_broadcast: true,
+                    broadcast_callback: Some(BroadcastMessage::typed(move |cursor: Cursor| {
+                        sink.lock().unwrap().push(cursor)
+                    })),
+                    ..Default::default()
+                },
+                |_| {},
+            )
+            .await
+            .unwrap();
+        server
+            .wait_for_join("realtime:canvas", TIMEOUT)
+            .await
+            .unwrap();
+
+        realtime
+            .broadcast_binary("canvas", "cursor", &Cursor { x: 1.5, y: 2.0 })
+            .await
+            .unwrap();
+        let sent = server.wait_for_event("broadcast", TIMEOUT).await.unwrap();
+        assert_eq!(
+            sent.payload["payload"]["content_type"],
+            MSGPACK_CONTENT_TYPE
+        );
+
+        // Echo the binary broadcast back, followed by a plain JSON one
+        server.push("realtime:canvas", "broadcast", sent.payload);
+        server.push(
+            "realtime:canvas",
+            "broadcast",
+            serde_json::json!({"event": "cursor", "payload": {"x": 3.0, "y": 4.0}}),
+        );
+
+        tokio::time::timeout(TIMEOUT, async {
+            while received.lock().unwrap().len() < 2 {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap();
+        assert_eq!(
+            *received.lock().unwrap(),
+            vec![Cursor { x: 1.5, y: 2.0 }, Cursor { x: 3.0, y: 4.0 }]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_subscription_stats() {
+        let received_at = chrono::DateTime::parse_from_rfc3339("2024-01-01T00:00:02Z")
+            .unwrap()
+            .with_timezone(&chrono::Utc);
+
+        let mut stats = SubscriptionStats::default();
+        assert!(stats.average_lag().is_none());
+
+        stats.record(received_at, Some("2024-01-01T00:00:01.500Z"));
+        stats.record(
+            received_at + chrono::Duration::seconds(2),
+            Some("2024-01-01T00:00:02.500+00:00"),
+        );
+        stats.record(received_at + chrono::Duration::seconds(4), None);
+
+        assert_eq!(stats.events_received, 3);
+        assert_eq!(stats.average_lag(), Some(Duration::from_millis(1000)));
+        assert_eq!(stats.max_lag, Some(Duration::from_millis(1500)));
+        assert_eq!(stats.events_per_second(), Some(0.75));
+
+        let config = Arc::new(SupabaseConfig {
+            url: "https://test.supabase.co".to_string(),
+            key: "test-key".to_string(),
+            ..Default::default()
+        });
+        let realtime = Realtime::new(config).unwrap();
+        assert!(realtime.subscription_stats("missing").await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_lag_resync() {
+        let config = Arc::new(SupabaseConfig {
+            url: "https://test.supabase.co".to_string(),
+            key: "test-key".to_string(),
+            ..Default::default()
+        });
+        let realtime = Realtime::new(config).unwrap();
+
+        let release = Arc::new(tokio::sync::Notify::new());
+        let policy = ResyncPolicy::new(Duration::from_secs(1), {
+            let release = release.clone();
+            move |_: &SubscriptionConfig| {
+                let release = release.clone();
+                async move {
+                    release.notified().await;
+                    Ok(vec![serde_json::json!({"id": 0})])
+                }
+            }
+        });
+        let delivered = Arc::new(Mutex::new(Vec::new()));
+        {
+            let delivered = delivered.clone();
+            let mut subscriptions = realtime.connection_manager.subscriptions.write().await;
+            subscriptions.insert(
+                "orders".to_string(),
+                Subscription {
+                    id: "orders".to_string(),
+                    topic: "realtime:public:orders".to_string(),
+                    config: SubscriptionConfig {
+                        table: Some("orders".to_string()),
+                        resync: Some(policy),
+                        ..Default::default()
+                    },
+                    callback: Arc::new(move |message: RealtimeMessage| {
+                        let record = message.payload.record.unwrap_or_default();
+                        delivered
+                            .lock()
+                            .unwrap()
+                            .push(format!("{} {}", message.event, record));
+                    }),
+                    channel: None,
+                },
+            );
+        }
+
+        let manager = &realtime.connection_manager;
+        let change = |id: u32, age: chrono::Duration| RealtimeMessage {
+            event: "INSERT".to_string(),
+            payload: RealtimePayload {
+                record: Some(serde_json::json!({ "id": id })),
+                old_record: None,
+                schema: Some("public".to_string()),
+                table: Some("orders".to_string()),
+                commit_timestamp: Some((chrono::Utc::now() - age).to_rfc3339()),
+                event_type: Some("INSERT".to_string()),
+                new: None,
+                old: None,
+            },
+            ref_id: None,
+            topic: "realtime:public:orders".to_string(),
+        };
+        let fresh = chrono::Duration::zero();
+        let stale = chrono::Duration::seconds(10);
+
+        Realtime::process_message(manager, change(1, fresh)).await;
+        // Lagging change starts the snapshot; it and older backlog are covered by it
+        Realtime::process_message(manager, change(2, stale)).await;
+        Realtime::process_message(manager, change(3, stale)).await;
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        Realtime::process_message(manager, change(4, fresh)).await;
+        assert_eq!(delivered.lock().unwrap().len(), 1);
+
+        release.notify_one();
+        for _ in 0..100 {
+            if delivered.lock().unwrap().len() >= 3 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        Realtime::process_message(manager, change(5, stale)).await;
+        Realtime::process_message(manager, change(6, fresh)).await;
+
+        assert_eq!(
+            *delivered.lock().unwrap(),
+            vec![
+                r#"INSERT {"id":1}"#,
+                r#"RESYNC [{"id":0}]"#,
+                r#"INSERT {"id":4}"#,
+                r#"INSERT {"id":6}"#,
+            ]
+        );
+    }
+
+    #[cfg(feature = "database")]
+    #[test]
+    fn test_parse_change_filter() {
+        let filter = parse_change_filter("author_id=eq.123").unwrap();
+        assert!(matches!(
+            filter,
+            crate::database::Filter::Simple { ref column, operator: crate::types::FilterOperator::Equal, ref value }
+                if column == "author_id" && value == "123"
+        ));
+        assert!(parse_change_filter("status=in.(a,b)").is_ok());
+        assert!(parse_change_filter("author_id").is_err());
+        assert!(parse_change_filter("author_id=near.1").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_subscription_validation() {
+        let problems =
+            |config: &SubscriptionConfig, on_channel| match config.validate_join(on_channel) {
+                Ok(()) => Vec::new(),
+                Err(Error::Validation(error)) => error.problems,
+                Err(other) => panic!("unexpected error: {}", other),
+            };
+
+        let valid = SubscriptionConfig {
+            table: Some("posts".to_string()),
+            event: Some(RealtimeEvent::Insert),
+            filter: Some("status=in.(draft,published)".to_string()),
+            advanced_filters: vec![AdvancedFilter {
+                column: "author_id".to_string(),
+                operator: FilterOperator::Equal,
+                value: serde_json::json!(7),
+            }],
+            postgres_changes: vec![PostgresChanges::table("comments").filter("post_id=eq.1")],
+            ..Default::default()
+        };
+        assert!(problems(&valid, false).is_empty());
+
+        // Every problem is reported at once
+        let invalid = SubscriptionConfig {
+            event: Some(RealtimeEvent::Delete),
+            filter: Some("author_id=like.1".to_string()),
+            advanced_filters: vec![AdvancedFilter {
+                column: "tags".to_string(),
+                operator: FilterOperator::In,
+                value: serde_json::json!("a"),
+            }],
+            enable_presence: true,
+            broadcast_options: Some(BroadcastOptions::default()),
+            postgres_changes: vec![PostgresChanges {
+                schema: String::new(),
+                filter: Some("id".to_string()),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let found = problems(&invalid, false);
+        assert_eq!(found.len(), 9, "{:?}", found);
+        assert!(found.contains(&"filters reference columns but no table is set".to_string()));
+        assert!(found.contains(&"delete events cannot be filtered".to_string()));
+        assert!(found.iter().any(|problem| problem.contains("uses `like`")));
+        assert!(found
+            .iter()
+            .any(|problem| problem.contains("without a list")));
+        assert!(found.contains(&"postgres_changes[0]: schema is empty".to_string()));
+        assert!(found
+            .iter()
+            .any(|problem| problem.starts_with("postgres_changes[0]: filter `id`")));
+        assert!(found
+            .iter()
+            .any(|problem| problem.starts_with("presence needs a channel")));
+        assert_eq!(problems(&invalid, true).len(), 8);
+
+        // Rejected before connecting
+        let realtime = Realtime::new(Arc::new(SupabaseConfig {
+            url: "https://test.supabase.co".to_string(),
+            key: "test-key".to_string(),
+            ..Default::default()
+        }))
+        .unwrap();
+        let error = realtime.subscribe(invalid, |_| {}).await.unwrap_err();
+        assert!(error.to_string().starts_with("Validation failed: "));
+    }
+
     #[tokio::test]
     async fn test_event_filter_matching() {
         // Test INSERT event matching
@@ -1941,4 +5510,424 @@ mod tests {
         // ALL should match ALL
         assert_eq!(all_event, Some(RealtimeEvent::All));
     }
+
+    #[tokio::test]
+    async fn test_row_watcher_fan_out() {
+        const TIMEOUT: Duration = Duration::from_secs(1);
+
+        let server = testing::MockRealtimeServer::start().await.unwrap();
+        let realtime = Realtime::new(Arc::new(SupabaseConfig {
+            url: server.url(),
+            key: "test-key".to_string(),
+            ..Default::default()
+        }))
+        .unwrap();
+        let watcher = realtime.row_watcher();
+
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let watch = |row: &'static str| {
+            let received = received.clone();
+            move |message: RealtimeMessage| {
+                received
+                    .lock()
+                    .unwrap()
+                    .push(format!("{} {}", row, message.event));
+            }
+        };
+        let first = watcher.watch_row("posts", 1, watch("first")).await.unwrap();
+        let second = watcher
+            .watch_row("posts", 2, watch("second"))
+            .await
+            .unwrap();
+        server
+            .wait_for_join("realtime:public:posts", TIMEOUT)
+            .await
+            .unwrap();
+        assert_eq!(watcher.watched_rows("posts").await, 2);
+
+        let topic = "realtime:public:posts";
+        server
+            .send_postgres_change(
+                topic,
+                RealtimeEvent::Insert,
+                serde_json::json!({"id": 2}),
+                None,
+            )
+            .unwrap();
+        server
+            .send_postgres_change(
+                topic,
+                RealtimeEvent::Insert,
+                serde_json::json!({"id": 3}),
+                None,
+            )
+            .unwrap();
+        server
+            .send_postgres_change(
+                topic,
+                RealtimeEvent::Delete,
+                serde_json::json!({}),
+                Some(serde_json::json!({"id": 1})),
+            )
+            .unwrap();
+        tokio::time::timeout(TIMEOUT, async {
+            while received.lock().unwrap().len() < 2 {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap();
+        assert_eq!(
+            *received.lock().unwrap(),
+            vec!["second INSERT", "first DELETE"]
+        );
+
+        // One channel serves every watched row
+        let joins = server
+            .frames()
+            .into_iter()
+            .filter(|frame| frame.event == "phx_join")
+            .count();
+        assert_eq!(joins, 1);
+
+        first.unwatch().await.unwrap();
+        assert_eq!(watcher.watched_rows("posts").await, 1);
+        second.unwatch().await.unwrap();
+        server.wait_for_event("phx_leave", TIMEOUT).await.unwrap();
+        assert_eq!(watcher.watched_rows("posts").await, 0);
+    }
+
+    #[tokio::test]
+    async fn test_stream_drops_oldest_when_full() {
+        use futures_util::StreamExt;
+
+        const TIMEOUT: Duration = Duration::from_secs(1);
+
+        let server = testing::MockRealtimeServer::start().await.unwrap();
+        let realtime = Realtime::new(Arc::new(SupabaseConfig {
+            url: server.url(),
+            key: "test-key".to_string(),
+            ..Default::default()
+        }))
+        .unwrap();
+        let mut stream = realtime
+            .channel("posts")
+            .table("posts")
+            .stream_capacity(2)
+            .subscribe_stream()
+            .await
+            .unwrap();
+        let topic = "realtime:public:posts";
+        server.wait_for_join(topic, TIMEOUT).await.unwrap();
+
+        for id in 1..=4 {
+            server
+                .send_postgres_change(
+                    topic,
+                    RealtimeEvent::Insert,
+                    serde_json::json!({"id": id}),
+                    None,
+                )
+                .unwrap();
+        }
+        // The join reply and the first two changes make way for the last two
+        tokio::time::timeout(TIMEOUT, async {
+            while stream.dropped() < 3 {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap();
+
+        let received: Vec<RealtimeMessage> =
+            tokio::time::timeout(TIMEOUT, stream.by_ref().take(2).collect())
+                .await
+                .unwrap();
+        let ids: Vec<_> = received
+            .iter()
+            .map(|message| message.payload.record.as_ref().unwrap()["id"].clone())
+            .collect();
+        assert_eq!(ids, vec![serde_json::json!(3), serde_json::json!(4)]);
+        assert_eq!(stream.dropped(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_change_stream() {
+        use futures_util::StreamExt;
+
+        const TIMEOUT: Duration = Duration::from_secs(1);
+
+        #[derive(Debug, PartialEq, Deserialize)]
+        struct Post {
+            id: i64,
+            title: String,
+        }
+
+        let server = testing::MockRealtimeServer::start().await.unwrap();
+        let realtime = Realtime::new(Arc::new(SupabaseConfig {
+            url: server.url(),
+            key: "test-key".to_string(),
+            ..Default::default()
+        }))
+        .unwrap();
+        let mut changes = realtime
+            .channel("posts")
+            .table("posts")
+            .subscribe_changes::<Post>()
+            .await
+            .unwrap();
+        let topic = "realtime:public:posts";
+        server.wait_for_join(topic, TIMEOUT).await.unwrap();
+
+        let post = |id: i64, title: &str| serde_json::json!({"id": id, "title": title});
+        server
+            .send_postgres_change(
+                topic,
+                RealtimeEvent::Insert,
+                serde_json::json!({"id": 1}),
+                None,
+            )
+            .unwrap();
+        server
+            .send_postgres_change(topic, RealtimeEvent::Insert, post(1, "Hello"), None)
+            .unwrap();
+        server
+            .send_postgres_change(
+                topic,
+                RealtimeEvent::Update,
+                post(1, "Hello, world"),
+                Some(serde_json::json!({"id": 1})),
+            )
+            .unwrap();
+        server
+            .send_postgres_change(
+                topic,
+                RealtimeEvent::Delete,
+                serde_json::Value::Null,
+                Some(serde_json::json!({"id": 1})),
+            )
+            .unwrap();
+
+        let received: Vec<ChangeEvent<Post>> =
+            tokio::time::timeout(TIMEOUT, changes.by_ref().take(3).collect())
+                .await
+                .unwrap();
+        // The record missing a title is skipped
+        assert_eq!(
+            received,
+            vec![
+                ChangeEvent::Insert(Post {
+                    id: 1,
+                    title: "Hello".to_string()
+                }),
+                ChangeEvent::Update {
+                    new: Post {
+                        id: 1,
+                        title: "Hello, world".to_string()
+                    },
+                    old: serde_json::json!({"id": 1}),
+                },
+                ChangeEvent::Delete {
+                    old: serde_json::json!({"id": 1})
+                },
+            ]
+        );
+
+        // Dropping the stream leaves the channel
+        drop(changes);
+        server.wait_for_event("phx_leave", TIMEOUT).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_reconnect_rejoins_channels() {
+        const TIMEOUT: Duration = Duration::from_secs(5);
+
+        let server = testing::MockRealtimeServer::start().await.unwrap();
+        let realtime = Realtime::new(Arc::new(SupabaseConfig {
+            url: server.url(),
+            key: "test-key".to_string(),
+            ..Default::default()
+        }))
+        .unwrap();
+        realtime.set_connection_config(ConnectionPoolConfig {
+            reconnect_delay: 10,
+            ..Default::default()
+        });
+        let states = Arc::new(Mutex::new(Vec::new()));
+        realtime.on_connection_state_change({
+            let states = states.clone();
+            move |state| states.lock().unwrap().push(state)
+        });
+
+        let (received_tx, mut received_rx) = tokio::sync::mpsc::unbounded_channel();
+        realtime
+            .channel("posts")
+            .table("posts")
+            .subscribe(move |message| {
+                if message.event == "INSERT" {
+                    let _ = received_tx.send(message);
+                }
+            })
+            .await
+            .unwrap();
+        let topic = "realtime:public:posts";
+        server.wait_for_join(topic, TIMEOUT).await.unwrap();
+        assert_eq!(realtime.connection_state(), ConnectionState::Open);
+
+        server.disconnect_clients();
+        server.wait_for_connections(2, TIMEOUT).await.unwrap();
+        server.wait_for_join(topic, TIMEOUT).await.unwrap();
+
+        server
+            .send_postgres_change(
+                topic,
+                RealtimeEvent::Insert,
+                serde_json::json!({"id": 1}),
+                None,
+            )
+            .unwrap();
+        let message = tokio::time::timeout(TIMEOUT, received_rx.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(message.payload.record.unwrap()["id"], 1);
+        assert_eq!(
+            *states.lock().unwrap(),
+            vec![
+                ConnectionState::Connecting,
+                ConnectionState::Open,
+                ConnectionState::Reconnecting { attempt: 1 },
+                ConnectionState::Connecting,
+                ConnectionState::Open,
+            ]
+        );
+
+        realtime.disconnect().await.unwrap();
+        assert_eq!(realtime.connection_state(), ConnectionState::Closed);
+    }
+
+    #[tokio::test]
+    async fn test_missed_heartbeat_reconnects() {
+        const TIMEOUT: Duration = Duration::from_secs(5);
+
+        let server = testing::MockRealtimeServer::start().await.unwrap();
+        let realtime = Realtime::new(Arc::new(SupabaseConfig {
+            url: server.url(),
+            key: "test-key".to_string(),
+            ..Default::default()
+        }))
+        .unwrap();
+        realtime.set_connection_config(ConnectionPoolConfig {
+            keep_alive_interval: 1,
+            reconnect_delay: 10,
+            ..Default::default()
+        });
+        realtime.connect().await.unwrap();
+
+        let heartbeat = server.wait_for_event("heartbeat", TIMEOUT).await.unwrap();
+        assert_eq!(heartbeat.topic, "phoenix");
+        // Answered heartbeats keep the connection open
+        tokio::time::sleep(Duration::from_millis(1500)).await;
+        assert_eq!(server.connection_count(), 1);
+        assert_eq!(realtime.connection_state(), ConnectionState::Open);
+
+        let lost = Arc::new(AtomicBool::new(false));
+        realtime.on_connection_state_change({
+            let lost = lost.clone();
+            move |state| {
+                if matches!(state, ConnectionState::Reconnecting { .. }) {
+                    lost.store(true, Ordering::SeqCst);
+                }
+            }
+        });
+        server.set_heartbeat_replies(false);
+        server.wait_for_connections(2, TIMEOUT).await.unwrap();
+        assert!(lost.load(Ordering::SeqCst));
+
+        realtime.disconnect().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_frame_interceptors() {
+        use futures_util::StreamExt;
+
+        const TIMEOUT: Duration = Duration::from_secs(5);
+
+        #[derive(Debug, PartialEq, Deserialize)]
+        struct Post {
+            id: i64,
+            title: String,
+        }
+
+        /// Tags outgoing payloads, translates and filters incoming frames
+        struct Rewriter;
+
+        impl FrameInterceptor for Rewriter {
+            fn outbound(&self, frame: String) -> Result<String> {
+                let mut value: serde_json::Value = serde_json::from_str(&frame)?;
+                value["payload"]["trace"] = serde_json::json!("abc");
+                Ok(value.to_string())
+            }
+
+            fn inbound(&self, frame: String) -> Option<String> {
+                (!frame.contains(r#""id":2"#)).then(|| frame.replace("Hello", "Hola"))
+            }
+        }
+
+        let server = testing::MockRealtimeServer::start().await.unwrap();
+        let realtime = Realtime::new(Arc::new(SupabaseConfig {
+            url: server.url(),
+            key: "test-key".to_string(),
+            ..Default::default()
+        }))
+        .unwrap();
+        let id = realtime.add_frame_interceptor(Arc::new(Rewriter));
+
+        let mut changes = realtime
+            .channel("posts")
+            .table("posts")
+            .subscribe_changes::<Post>()
+            .await
+            .unwrap();
+        let topic = "realtime:public:posts";
+        server.wait_for_join(topic, TIMEOUT).await.unwrap();
+        let join = server.wait_for_event("phx_join", TIMEOUT).await.unwrap();
+        assert_eq!(join.payload["trace"], "abc");
+
+        let post = |id: i64| serde_json::json!({"id": id, "title": "Hello"});
+        for id in [2, 1] {
+            server
+                .send_postgres_change(topic, RealtimeEvent::Insert, post(id), None)
+                .unwrap();
+        }
+        let first = tokio::time::timeout(TIMEOUT, changes.next())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            first,
+            ChangeEvent::Insert(Post {
+                id: 1,
+                title: "Hola".to_string()
+            })
+        );
+
+        realtime.remove_frame_interceptor(&id);
+        server
+            .send_postgres_change(topic, RealtimeEvent::Insert, post(3), None)
+            .unwrap();
+        let next = tokio::time::timeout(TIMEOUT, changes.next())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            next,
+            ChangeEvent::Insert(Post {
+                id: 3,
+                title: "Hello".to_string()
+            })
+        );
+
+        realtime.disconnect().await.unwrap();
+    }
 }
@@ -0,0 +1,261 @@
+//! Presence state kept from `presence_state` and `presence_diff` messages
+//!
+//! The server sends the full state of a topic after joining and diffs
+//! afterwards. Each tracked presence is a "meta" object told apart by its
+//! `phx_ref`; several metas can share a presence key, e.g. one user in two
+//! tabs. [`PresenceTracker`] follows the Phoenix presence algorithm to keep
+//! the metas by key and report what joined and left.
+
+use super::{PresenceEventType, PresenceState};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+
+/// Presences currently on a channel: presence key to the states tracked under it
+pub type PresenceMap = HashMap<String, Vec<PresenceState>>;
+
+/// Presences that joined or left under one key
+#[derive(Debug, Clone)]
+pub struct PresenceChange {
+    /// Presence key; the server assigns one per connection unless configured
+    pub key: String,
+    /// States tracked under the key before the change
+    pub current: Vec<PresenceState>,
+    /// States that joined or left
+    pub presences: Vec<PresenceState>,
+}
+
+/// Raw metas by presence key
+type Metas = HashMap<String, Vec<Value>>;
+
+/// Presence of one topic
+#[derive(Debug, Default)]
+pub(crate) struct PresenceTracker {
+    metas: Metas,
+}
+
+impl PresenceTracker {
+    /// Apply a `presence_state` or `presence_diff` payload
+    ///
+    /// Returns the joins followed by the leaves. A full state is compared
+    /// with the presences already known, so it only reports the difference.
+    pub(crate) fn apply(
+        &mut self,
+        event: &str,
+        payload: &Value,
+    ) -> Vec<(PresenceEventType, PresenceChange)> {
+        let (joins, leaves) = match event {
+            "presence_state" => self.state_diff(payload),
+            "presence_diff" => (
+                metas_by_key(&payload["joins"]),
+                metas_by_key(&payload["leaves"]),
+            ),
+            _ => return Vec::new(),
+        };
+
+        let mut changes = Vec::new();
+        for (key, joined) in joins {
+            let current = self.metas.get(&key).cloned().unwrap_or_default();
+            let refs = meta_refs(&joined);
+            let mut metas = joined.clone();
+            metas.extend(
+                current
+                    .iter()
+                    .filter(|meta| !refs.contains(&meta_ref(meta)))
+                    .cloned(),
+            );
+            self.metas.insert(key.clone(), metas);
+            changes.push((PresenceEventType::Join, change(key, &current, &joined)));
+        }
+        for (key, left) in leaves {
+            let Some(current) = self.metas.get(&key).cloned() else {
+                continue;
+            };
+            let refs = meta_refs(&left);
+            let remaining: Vec<_> = current
+                .iter()
+                .filter(|meta| !refs.contains(&meta_ref(meta)))
+                .cloned()
+                .collect();
+            if remaining.is_empty() {
+                self.metas.remove(&key);
+            } else {
+                self.metas.insert(key.clone(), remaining);
+            }
+            changes.push((PresenceEventType::Leave, change(key, &current, &left)));
+        }
+        changes
+    }
+
+    /// Current presences
+    pub(crate) fn state(&self) -> PresenceMap {
+        self.metas
+            .iter()
+            .map(|(key, metas)| (key.clone(), presences(key, metas)))
+            .collect()
+    }
+
+    /// Joins and leaves turning the known metas into the full state `payload`
+    fn state_diff(&self, payload: &Value) -> (Metas, Metas) {
+        let next = metas_by_key(payload);
+        let mut joins = Metas::new();
+        let mut leaves = Metas::new();
+
+        for (key, metas) in &next {
+            let Some(known) = self.metas.get(key) else {
+                joins.insert(key.clone(), metas.clone());
+                continue;
+            };
+            let next_refs = meta_refs(metas);
+            let known_refs = meta_refs(known);
+            let joined: Vec<_> = metas
+                .iter()
+                .filter(|meta| !known_refs.contains(&meta_ref(meta)))
+                .cloned()
+                .collect();
+            let left: Vec<_> = known
+                .iter()
+                .filter(|meta| !next_refs.contains(&meta_ref(meta)))
+                .cloned()
+                .collect();
+            if !joined.is_empty() {
+                joins.insert(key.clone(), joined);
+            }
+            if !left.is_empty() {
+                leaves.insert(key.clone(), left);
+            }
+        }
+        for (key, metas) in &self.metas {
+            if !next.contains_key(key) {
+                leaves.insert(key.clone(), metas.clone());
+            }
+        }
+        (joins, leaves)
+    }
+}
+
+fn change(key: String, current: &[Value], changed: &[Value]) -> PresenceChange {
+    PresenceChange {
+        current: presences(&key, current),
+        presences: presences(&key, changed),
+        key,
+    }
+}
+
+fn presences(key: &str, metas: &[Value]) -> Vec<PresenceState> {
+    metas
+        .iter()
+        .map(|meta| PresenceState::from_meta(key, meta))
+        .collect()
+}
+
+impl PresenceState {
+    /// State from a presence meta
+    ///
+    /// Metas sent by [`Realtime::track_presence`](super::Realtime::track_presence)
+    /// map back field by field. For metas tracked by other clients the user id
+    /// falls back to the presence key, and fields other than the `phx_ref`
+    /// bookkeeping end up in the metadata.
+    fn from_meta(key: &str, meta: &Value) -> Self {
+        let text = |field: &str| meta[field].as_str().map(str::to_string);
+        let metadata = match meta.get("metadata") {
+            Some(Value::Object(metadata)) => Some(metadata.clone().into_iter().collect()),
+            Some(_) => None,
+            None => {
+                let rest: HashMap<String, Value> = meta
+                    .as_object()
+                    .into_iter()
+                    .flatten()
+                    .filter(|(field, _)| {
+                        !matches!(
+                            field.as_str(),
+                            "user_id" | "online_at" | "phx_ref" | "phx_ref_prev"
+                        )
+                    })
+                    .map(|(field, value)| (field.clone(), value.clone()))
+                    .collect();
+                (!rest.is_empty()).then_some(rest)
+            }
+        };
+
+        Self {
+            user_id: text("user_id").unwrap_or_else(|| key.to_string()),
+            online_at: text("online_at").unwrap_or_default(),
+            metadata,
+        }
+    }
+}
+
+/// `{ key: { metas: [...] } }` as metas by key
+fn metas_by_key(value: &Value) -> Metas {
+    value
+        .as_object()
+        .into_iter()
+        .flatten()
+        .map(|(key, presence)| {
+            let metas = presence["metas"].as_array().cloned().unwrap_or_default();
+            (key.clone(), metas)
+        })
+        .collect()
+}
+
+fn meta_ref(meta: &Value) -> Option<&str> {
+    meta["phx_ref"].as_str()
+}
+
+fn meta_refs(metas: &[Value]) -> HashSet<Option<&str>> {
+    metas.iter().map(meta_ref).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_presence_state_and_diff() {
+        let mut tracker = PresenceTracker::default();
+        let summary = |changes: Vec<(PresenceEventType, PresenceChange)>| {
+            changes
+                .into_iter()
+                .map(|(event, change)| format!("{:?} {}", event, change.key))
+                .collect::<Vec<_>>()
+        };
+
+        let mut joined = summary(tracker.apply(
+            "presence_state",
+            &json!({
+                "a": { "metas": [{ "phx_ref": "1", "user_id": "u1", "online_at": "now" }] },
+                "b": { "metas": [{ "phx_ref": "2", "status": "away" }] }
+            }),
+        ));
+        joined.sort();
+        assert_eq!(joined, vec!["Join a", "Join b"]);
+
+        let state = tracker.state();
+        assert_eq!(state["a"][0].user_id, "u1");
+        assert_eq!(state["a"][0].online_at, "now");
+        assert_eq!(state["b"][0].user_id, "b");
+        assert_eq!(state["b"][0].metadata.as_ref().unwrap()["status"], "away");
+
+        let changes = tracker.apply(
+            "presence_diff",
+            &json!({
+                "joins": { "a": { "metas": [{ "phx_ref": "3", "user_id": "u1" }] } },
+                "leaves": { "b": { "metas": [{ "phx_ref": "2" }] } }
+            }),
+        );
+        assert_eq!(changes[0].1.current.len(), 1);
+        assert_eq!(changes[0].1.presences.len(), 1);
+        assert_eq!(summary(changes), vec!["Join a", "Leave b"]);
+        assert_eq!(tracker.state().len(), 1);
+        assert_eq!(tracker.state()["a"].len(), 2);
+
+        // A full state replacing the known one reports the difference
+        let changes = tracker.apply(
+            "presence_state",
+            &json!({ "a": { "metas": [{ "phx_ref": "3", "user_id": "u1" }] } }),
+        );
+        assert_eq!(summary(changes), vec!["Leave a"]);
+        assert_eq!(tracker.state()["a"].len(), 1);
+    }
+}
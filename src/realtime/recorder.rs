@@ -0,0 +1,487 @@
+//! Change data capture from realtime subscriptions
+//!
+//! A [`ChangeRecorder`] joins one channel per recorded table and appends
+//! every INSERT, UPDATE and DELETE to a [`ChangeSink`] as a
+//! [`ChangeRecord`]. Records carry the commit timestamp reported by the
+//! server and a sequence number that increases by one per record, across
+//! all tables, in the order the sink receives them.
+//!
+//! Sinks are provided for JSON lines files ([`FileSink`]), channels
+//! ([`ChannelSink`]) and closures. Only changes delivered while the
+//! recorder is running are captured: pair it with a snapshot of the tables
+//! when the history must be complete.
+//!
+//! ```rust,no_run
+//! use supabase_lib_rs::{realtime::recorder::FileSink, Client};
+//!
+//! # async fn example() -> supabase_lib_rs::Result<()> {
+//! let client = Client::new("your-url", "your-key")?;
+//!
+//! let recorder = client
+//!     .realtime()
+//!     .change_recorder(FileSink::open("audit.jsonl")?)
+//!     .table("orders")
+//!     .table_in("billing", "invoices");
+//! recorder.start().await?;
+//!
+//! // ... later
+//! recorder.stop().await?;
+//! println!("Recorded {} changes", recorder.next_sequence() - 1);
+//! # Ok(())
+//! # }
+//! ```
+
+use super::{Realtime, RealtimeMessage};
+use crate::{
+    async_runtime::{AsyncLock, RuntimeLock},
+    error::{Error, Result},
+};
+use chrono::{DateTime, Utc};
+use futures_channel::mpsc::{self, UnboundedReceiver, UnboundedSender};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::sync::{Arc, Mutex};
+use tracing::{debug, info, warn};
+
+/// One captured database change
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChangeRecord {
+    /// Position of the change in the recording
+    pub sequence: u64,
+    /// `INSERT`, `UPDATE` or `DELETE`
+    pub event: String,
+    pub schema: String,
+    pub table: String,
+    /// When the transaction was committed, if the server reported it
+    pub commit_timestamp: Option<DateTime<Utc>>,
+    /// New row, absent for deletes
+    pub record: Option<Value>,
+    /// Previous row; only the primary key with the default replica identity
+    pub old_record: Option<Value>,
+}
+
+impl ChangeRecord {
+    /// Build a record from a change message, or `None` for other events
+    fn from_message(
+        sequence: u64,
+        message: &RealtimeMessage,
+        schema: &str,
+        table: &str,
+    ) -> Option<Self> {
+        if !matches!(message.event.as_str(), "INSERT" | "UPDATE" | "DELETE") {
+            return None;
+        }
+
+        let payload = &message.payload;
+        let commit_timestamp = payload.commit_timestamp.as_deref().and_then(|timestamp| {
+            DateTime::parse_from_rfc3339(timestamp)
+                .map(|timestamp| timestamp.with_timezone(&Utc))
+                .map_err(|e| warn!("Invalid commit timestamp {}: {}", timestamp, e))
+                .ok()
+        });
+
+        Some(Self {
+            sequence,
+            event: message.event.clone(),
+            schema: payload.schema.clone().unwrap_or_else(|| schema.to_string()),
+            table: payload.table.clone().unwrap_or_else(|| table.to_string()),
+            commit_timestamp,
+            record: payload
+                .record
+                .clone()
+                .or_else(|| payload.new.clone())
+                .filter(|record| !record.is_null()),
+            old_record: payload
+                .old_record
+                .clone()
+                .or_else(|| payload.old.clone())
+                .filter(|record| !record.is_null()),
+        })
+    }
+}
+
+/// Destination of a [`ChangeRecorder`]
+///
+/// Writes are serialized: a sink sees records one at a time, in sequence
+/// order. A failed write is logged and recording goes on with the next
+/// change. Closures taking a `&ChangeRecord` are sinks too.
+pub trait ChangeSink: Send + Sync {
+    /// Append one record
+    fn write(&self, record: &ChangeRecord) -> Result<()>;
+}
+
+impl<F> ChangeSink for F
+where
+    F: Fn(&ChangeRecord) -> Result<()> + Send + Sync,
+{
+    fn write(&self, record: &ChangeRecord) -> Result<()> {
+        self(record)
+    }
+}
+
+/// Sink appending records to a file, one JSON object per line
+///
+/// Lines are written and flushed by a background thread, so a slow disk
+/// does not hold up the realtime connection; write errors are logged there.
+/// Dropping the sink waits until the queued lines are written.
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug)]
+pub struct FileSink {
+    lines: Option<std::sync::mpsc::Sender<Vec<u8>>>,
+    writer: Option<std::thread::JoinHandle<()>>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl FileSink {
+    /// Open `path` for appending, creating it if needed
+    pub fn open<P: AsRef<std::path::Path>>(path: P) -> Result<Self> {
+        use std::io::Write;
+
+        let path = path.as_ref();
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map_err(|e| {
+                Error::platform(format!(
+                    "Failed to open change log {}: {}",
+                    path.display(),
+                    e
+                ))
+            })?;
+
+        let (lines, queue) = std::sync::mpsc::channel::<Vec<u8>>();
+        let writer = std::thread::Builder::new()
+            .name("change-log-writer".to_string())
+            .spawn(move || {
+                for line in queue {
+                    if let Err(e) = file.write_all(&line).and_then(|_| file.flush()) {
+                        warn!("Failed to write change log: {}", e);
+                    }
+                }
+            })
+            .map_err(|e| Error::platform(format!("Failed to start change log writer: {}", e)))?;
+
+        Ok(Self {
+            lines: Some(lines),
+            writer: Some(writer),
+        })
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl ChangeSink for FileSink {
+    fn write(&self, record: &ChangeRecord) -> Result<()> {
+        let mut line = serde_json::to_vec(record)?;
+        line.push(b'\n');
+        self.lines
+            .as_ref()
+            .and_then(|lines| lines.send(line).ok())
+            .ok_or_else(|| Error::platform("Change log writer has stopped"))
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Drop for FileSink {
+    fn drop(&mut self) {
+        // Closing the queue lets the writer finish the pending lines and exit
+        self.lines.take();
+        if let Some(writer) = self.writer.take() {
+            let _ = writer.join();
+        }
+    }
+}
+
+/// Sink sending records to an unbounded channel
+#[derive(Debug, Clone)]
+pub struct ChannelSink {
+    sender: UnboundedSender<ChangeRecord>,
+}
+
+impl ChannelSink {
+    /// A sink and the receiving end of its channel, which is a `Stream`
+    pub fn new() -> (Self, UnboundedReceiver<ChangeRecord>) {
+        let (sender, receiver) = mpsc::unbounded();
+        (Self { sender }, receiver)
+    }
+}
+
+impl From<UnboundedSender<ChangeRecord>> for ChannelSink {
+    fn from(sender: UnboundedSender<ChangeRecord>) -> Self {
+        Self { sender }
+    }
+}
+
+impl ChangeSink for ChannelSink {
+    fn write(&self, record: &ChangeRecord) -> Result<()> {
+        self.sender
+            .unbounded_send(record.clone())
+            .map_err(|_| Error::realtime("Change record receiver was dropped"))
+    }
+}
+
+/// Records the changes of a set of tables into a [`ChangeSink`]
+///
+/// Clones share the sink, the sequence and the subscriptions.
+#[derive(Clone)]
+pub struct ChangeRecorder {
+    realtime: Realtime,
+    tables: Vec<(String, String)>,
+    state: Arc<Mutex<RecorderState>>,
+    subscriptions: Arc<RuntimeLock<Vec<String>>>,
+}
+
+struct RecorderState {
+    next_sequence: u64,
+    sink: Box<dyn ChangeSink>,
+}
+
+impl std::fmt::Debug for ChangeRecorder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ChangeRecorder")
+            .field("tables", &self.tables)
+            .field("next_sequence", &self.next_sequence())
+            .finish_non_exhaustive()
+    }
+}
+
+impl ChangeRecorder {
+    /// Create a recorder writing to `sink`, numbering changes from 1
+    pub fn new<S: ChangeSink + 'static>(realtime: Realtime, sink: S) -> Self {
+        Self {
+            realtime,
+            tables: Vec::new(),
+            state: Arc::new(Mutex::new(RecorderState {
+                next_sequence: 1,
+                sink: Box::new(sink),
+            })),
+            subscriptions: Arc::new(RuntimeLock::new(Vec::new())),
+        }
+    }
+
+    /// Record a table of the `public` schema
+    pub fn table(self, table: &str) -> Self {
+        self.table_in("public", table)
+    }
+
+    /// Record a table of another schema
+    pub fn table_in(mut self, schema: &str, table: &str) -> Self {
+        self.tables.push((schema.to_string(), table.to_string()));
+        self
+    }
+
+    /// Number the next change `sequence`, e.g. to continue an earlier log
+    pub fn start_sequence(self, sequence: u64) -> Self {
+        self.lock().next_sequence = sequence;
+        self
+    }
+
+    /// Sequence number the next recorded change will get
+    pub fn next_sequence(&self) -> u64 {
+        self.lock().next_sequence
+    }
+
+    /// Whether the recorder is subscribed to its tables
+    pub async fn is_recording(&self) -> bool {
+        !self.subscriptions.read().await.is_empty()
+    }
+
+    /// Subscribe to every table and start recording
+    ///
+    /// Does nothing if already recording. If a subscription fails, the
+    /// tables joined so far are left again and the error is returned.
+    pub async fn start(&self) -> Result<()> {
+        if self.tables.is_empty() {
+            return Err(Error::invalid_input(
+                "ChangeRecorder has no tables to record",
+            ));
+        }
+
+        let mut subscriptions = self.subscriptions.write().await;
+        if !subscriptions.is_empty() {
+            return Ok(());
+        }
+
+        for (schema, table) in &self.tables {
+            let state = self.state.clone();
+            let (record_schema, record_table) = (schema.clone(), table.clone());
+            let subscribed = self
+                .realtime
+                .channel(table)
+                .schema(schema)
+                .table(table)
+                .subscribe(move |message| {
+                    Self::append(&state, &message, &record_schema, &record_table)
+                })
+                .await;
+
+            match subscribed {
+                Ok(id) => subscriptions.push(id),
+                Err(e) => {
+                    self.realtime.unsubscribe_many(&subscriptions).await;
+                    subscriptions.clear();
+                    return Err(e);
+                }
+            }
+        }
+
+        info!("Recording changes of {} tables", self.tables.len());
+        Ok(())
+    }
+
+    /// Leave the table channels; the sequence continues on the next start
+    pub async fn stop(&self) -> Result<()> {
+        let ids = std::mem::take(&mut *self.subscriptions.write().await);
+        self.realtime.unsubscribe_many(&ids).await;
+        debug!("Stopped recording, left {} channels", ids.len());
+        Ok(())
+    }
+
+    /// Number and write one message, if it is a change
+    fn append(state: &Mutex<RecorderState>, message: &RealtimeMessage, schema: &str, table: &str) {
+        let mut state = state.lock().unwrap_or_else(|e| e.into_inner());
+        let Some(record) = ChangeRecord::from_message(state.next_sequence, message, schema, table)
+        else {
+            return;
+        };
+
+        state.next_sequence += 1;
+        if let Err(e) = state.sink.write(&record) {
+            warn!("Failed to record change {}: {}", record.sequence, e);
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, RecorderState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+    use crate::{
+        realtime::{testing::MockRealtimeServer, RealtimeEvent},
+        types::SupabaseConfig,
+    };
+    use futures_util::StreamExt;
+    use serde_json::json;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_change_recorder() {
+        const TIMEOUT: Duration = Duration::from_secs(1);
+
+        let server = MockRealtimeServer::start().await.unwrap();
+        let realtime = Realtime::new(Arc::new(SupabaseConfig {
+            url: server.url(),
+            key: "test-key".to_string(),
+            ..Default::default()
+        }))
+        .unwrap();
+        let (sink, mut records) = ChannelSink::new();
+        let recorder = realtime
+            .change_recorder(sink)
+            .table("posts")
+            .table_in("audit", "logins")
+            .start_sequence(41);
+        recorder.start().await.unwrap();
+        server
+            .wait_for_join("realtime:public:posts", TIMEOUT)
+            .await
+            .unwrap();
+        server
+            .wait_for_join("realtime:audit:logins", TIMEOUT)
+            .await
+            .unwrap();
+        assert!(recorder.is_recording().await);
+
+        server
+            .send_postgres_change(
+                "realtime:public:posts",
+                RealtimeEvent::Insert,
+                json!({"id": 1, "title": "Hello"}),
+                None,
+            )
+            .unwrap();
+        server.push("realtime:public:posts", "presence_diff", json!({}));
+        server
+            .send_postgres_change(
+                "realtime:audit:logins",
+                RealtimeEvent::Insert,
+                json!({"user_id": 7}),
+                None,
+            )
+            .unwrap();
+        server
+            .send_postgres_change(
+                "realtime:public:posts",
+                RealtimeEvent::Delete,
+                Value::Null,
+                Some(json!({"id": 1})),
+            )
+            .unwrap();
+
+        let received: Vec<ChangeRecord> =
+            tokio::time::timeout(TIMEOUT, records.by_ref().take(3).collect())
+                .await
+                .unwrap();
+        let summary: Vec<(u64, &str, &str, &str)> = received
+            .iter()
+            .map(|r| {
+                (
+                    r.sequence,
+                    r.event.as_str(),
+                    r.schema.as_str(),
+                    r.table.as_str(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (41, "INSERT", "public", "posts"),
+                (42, "INSERT", "audit", "logins"),
+                (43, "DELETE", "public", "posts"),
+            ]
+        );
+        assert!(received.iter().all(|r| r.commit_timestamp.is_some()));
+        assert_eq!(received[2].record, None);
+        assert_eq!(received[2].old_record, Some(json!({"id": 1})));
+        assert_eq!(recorder.next_sequence(), 44);
+
+        recorder.stop().await.unwrap();
+        assert!(!recorder.is_recording().await);
+        server.wait_for_event("phx_leave", TIMEOUT).await.unwrap();
+    }
+
+    #[test]
+    fn test_file_sink_appends_json_lines() {
+        let path = std::env::temp_dir().join(format!("changes-{}.jsonl", uuid::Uuid::new_v4()));
+        let timestamp = DateTime::parse_from_rfc3339("2026-01-02T03:04:05.123456Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let records: Vec<ChangeRecord> = (1..=2)
+            .map(|sequence| ChangeRecord {
+                sequence,
+                event: "UPDATE".to_string(),
+                schema: "public".to_string(),
+                table: "posts".to_string(),
+                commit_timestamp: Some(timestamp),
+                record: Some(json!({"id": 1, "title": "Line\nbreak"})),
+                old_record: None,
+            })
+            .collect();
+
+        // Reopening appends instead of truncating
+        FileSink::open(&path).unwrap().write(&records[0]).unwrap();
+        FileSink::open(&path).unwrap().write(&records[1]).unwrap();
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let lines: Vec<ChangeRecord> = contents
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines, records);
+    }
+}
@@ -0,0 +1,519 @@
+//! In-memory realtime server for tests
+//!
+//! [`MockRealtimeServer`] listens on a local port and speaks enough of the
+//! Phoenix protocol to drive a [`Realtime`](super::Realtime) client without a
+//! Supabase instance: it acknowledges joins, leaves and heartbeats, records
+//! every frame the client sends, pushes database changes on demand, and can
+//! drop connections to exercise reconnect handling.
+//!
+//! Requires the `realtime-testing` feature, typically enabled for
+//! dev-dependencies only.
+//!
+//! # Examples
+//! ```rust,no_run
+//! use serde_json::json;
+//! use std::{sync::Arc, time::Duration};
+//! use supabase_lib_rs::realtime::{testing::MockRealtimeServer, Realtime, RealtimeEvent};
+//! use supabase_lib_rs::types::SupabaseConfig;
+//!
+//! # async fn example() -> supabase_lib_rs::Result<()> {
+//! let server = MockRealtimeServer::start().await?;
+//! let realtime = Realtime::new(Arc::new(SupabaseConfig {
+//!     url: server.url(),
+//!     key: "test-key".to_string(),
+//!     ..Default::default()
+//! }))?;
+//!
+//! realtime
+//!     .channel("posts")
+//!     .table("posts")
+//!     .subscribe(|message| println!("{}", message.event))
+//!     .await?;
+//!
+//! server
+//!     .wait_for_join("realtime:public:posts", Duration::from_secs(1))
+//!     .await?;
+//! server.send_postgres_change("realtime:public:posts", RealtimeEvent::Insert, json!({"id": 1}), None)?;
+//! # Ok(())
+//! # }
+//! ```
+
+use super::RealtimeEvent;
+use crate::error::{Error, Result};
+use futures_util::{SinkExt, StreamExt};
+use serde_json::{json, Value};
+use std::{
+    net::SocketAddr,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+use tokio::{
+    net::{TcpListener, TcpStream},
+    sync::{mpsc, Notify},
+    task::JoinHandle,
+};
+use tokio_tungstenite::tungstenite::{
+    handshake::server::{Request, Response},
+    Message,
+};
+
+/// A frame sent by the client, in either serializer format
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClientFrame {
+    /// Reference of the join the frame belongs to, absent in V1 frames
+    pub join_ref: Option<String>,
+    /// Message reference that the server echoes in its reply
+    pub ref_id: Option<String>,
+    /// Channel topic, e.g. `realtime:public:posts`
+    pub topic: String,
+    /// Phoenix event, e.g. `phx_join`, `heartbeat` or `broadcast`
+    pub event: String,
+    /// Event payload
+    pub payload: Value,
+}
+
+impl ClientFrame {
+    /// Decode a V1 object frame or a V2 array frame
+    pub fn decode(frame: &str) -> Option<Self> {
+        let value: Value = serde_json::from_str(frame).ok()?;
+        let text = |value: &Value| value.as_str().map(str::to_string);
+
+        match value {
+            Value::Array(parts) if parts.len() == 5 => Some(Self {
+                join_ref: text(&parts[0]),
+                ref_id: text(&parts[1]),
+                topic: text(&parts[2])?,
+                event: text(&parts[3])?,
+                payload: parts[4].clone(),
+            }),
+            Value::Object(object) => Some(Self {
+                join_ref: object.get("join_ref").and_then(text),
+                ref_id: object.get("ref").or(object.get("ref_id")).and_then(text),
+                topic: object.get("topic").and_then(text)?,
+                event: object.get("event").and_then(text)?,
+                payload: object.get("payload").cloned().unwrap_or(Value::Null),
+            }),
+            _ => None,
+        }
+    }
+}
+
+/// Encode a server frame in the client's serializer format
+fn encode_frame(
+    v2: bool,
+    join_ref: Option<&str>,
+    ref_id: Option<&str>,
+    topic: &str,
+    event: &str,
+    payload: Value,
+) -> String {
+    let frame = if v2 {
+        json!([join_ref, ref_id, topic, event, payload])
+    } else {
+        json!({
+            "join_ref": join_ref,
+            "ref": ref_id,
+            "topic": topic,
+            "event": event,
+            "payload": payload,
+        })
+    };
+    frame.to_string()
+}
+
+struct ClientHandle {
+    id: usize,
+    v2: bool,
+    outbound: mpsc::UnboundedSender<Message>,
+}
+
+#[derive(Default)]
+struct ServerState {
+    frames: Mutex<Vec<ClientFrame>>,
+    joined: Mutex<Vec<String>>,
+    clients: Mutex<Vec<ClientHandle>>,
+    connections: AtomicUsize,
+    silent_heartbeats: AtomicBool,
+    changed: Notify,
+}
+
+impl ServerState {
+    /// Record a client frame and build the reply, if the frame needs one
+    fn handle_frame(&self, text: &str, v2: bool) -> Option<String> {
+        let frame = ClientFrame::decode(text)?;
+
+        let acknowledged = match frame.event.as_str() {
+            "phx_join" => {
+                if let Ok(mut joined) = self.joined.lock() {
+                    joined.push(frame.topic.clone());
+                }
+                true
+            }
+            "phx_leave" => {
+                if let Ok(mut joined) = self.joined.lock() {
+                    joined.retain(|topic| *topic != frame.topic);
+                }
+                true
+            }
+            "heartbeat" => !self.silent_heartbeats.load(Ordering::SeqCst),
+            _ => false,
+        };
+
+        let reply = acknowledged.then(|| {
+            encode_frame(
+                v2,
+                frame.join_ref.as_deref(),
+                frame.ref_id.as_deref(),
+                &frame.topic,
+                "phx_reply",
+                json!({"status": "ok", "response": {}}),
+            )
+        });
+
+        if let Ok(mut frames) = self.frames.lock() {
+            frames.push(frame);
+        }
+        self.changed.notify_waiters();
+
+        reply
+    }
+
+    fn push(&self, topic: &str, event: &str, payload: &Value) {
+        if let Ok(clients) = self.clients.lock() {
+            for client in clients.iter() {
+                let frame = encode_frame(client.v2, None, None, topic, event, payload.clone());
+                let _ = client.outbound.send(Message::Text(frame));
+            }
+        }
+    }
+}
+
+/// Local Phoenix server for testing realtime subscriptions
+///
+/// The server stops when dropped.
+pub struct MockRealtimeServer {
+    addr: SocketAddr,
+    state: Arc<ServerState>,
+    accept_task: JoinHandle<()>,
+}
+
+impl MockRealtimeServer {
+    /// Start listening on a free local port
+    pub async fn start() -> Result<Self> {
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .map_err(|e| Error::network(format!("Failed to bind mock realtime server: {}", e)))?;
+        let addr = listener
+            .local_addr()
+            .map_err(|e| Error::network(format!("Failed to read mock server address: {}", e)))?;
+
+        let state = Arc::new(ServerState::default());
+        let accept_state = Arc::clone(&state);
+        let accept_task = tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                tokio::spawn(serve_connection(stream, Arc::clone(&accept_state)));
+            }
+        });
+
+        Ok(Self {
+            addr,
+            state,
+            accept_task,
+        })
+    }
+
+    /// Project URL to use as `SupabaseConfig::url`
+    pub fn url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    /// Number of WebSocket connections accepted so far
+    pub fn connection_count(&self) -> usize {
+        self.state.connections.load(Ordering::SeqCst)
+    }
+
+    /// Every frame received from clients, in arrival order
+    pub fn frames(&self) -> Vec<ClientFrame> {
+        self.state
+            .frames
+            .lock()
+            .map(|frames| frames.clone())
+            .unwrap_or_default()
+    }
+
+    /// Topics currently joined by clients
+    pub fn joined_topics(&self) -> Vec<String> {
+        self.state
+            .joined
+            .lock()
+            .map(|joined| joined.clone())
+            .unwrap_or_default()
+    }
+
+    /// Wait until a client has joined `topic`
+    pub async fn wait_for_join(&self, topic: &str, timeout: Duration) -> Result<()> {
+        self.wait_until(timeout, || {
+            self.joined_topics().iter().any(|joined| joined == topic)
+        })
+        .await
+        .map_err(|_| Error::realtime(format!("Timed out waiting for a join on {}", topic)))
+    }
+
+    /// Wait for the first frame with `event` and return it
+    pub async fn wait_for_event(&self, event: &str, timeout: Duration) -> Result<ClientFrame> {
+        let find = || self.frames().into_iter().find(|frame| frame.event == event);
+
+        self.wait_until(timeout, || find().is_some())
+            .await
+            .map_err(|_| Error::realtime(format!("Timed out waiting for a {} frame", event)))?;
+        find().ok_or_else(|| Error::realtime(format!("No {} frame received", event)))
+    }
+
+    /// Wait until `connection_count` reaches `count`
+    pub async fn wait_for_connections(&self, count: usize, timeout: Duration) -> Result<()> {
+        self.wait_until(timeout, || self.connection_count() >= count)
+            .await
+            .map_err(|_| Error::realtime(format!("Timed out waiting for {} connections", count)))
+    }
+
+    async fn wait_until<F>(&self, timeout: Duration, condition: F) -> std::result::Result<(), ()>
+    where
+        F: Fn() -> bool,
+    {
+        tokio::time::timeout(timeout, async {
+            loop {
+                // Register before checking so a change in between is not missed
+                let changed = self.state.changed.notified();
+                if condition() {
+                    return;
+                }
+                changed.await;
+            }
+        })
+        .await
+        .map_err(|_| ())
+    }
+
+    /// Push a database change to every client, in the legacy realtime format
+    ///
+    /// `topic` has the form `realtime:<schema>:<table>`.
+    pub fn send_postgres_change(
+        &self,
+        topic: &str,
+        event: RealtimeEvent,
+        record: Value,
+        old_record: Option<Value>,
+    ) -> Result<()> {
+        let event = match event {
+            RealtimeEvent::Insert => "INSERT",
+            RealtimeEvent::Update => "UPDATE",
+            RealtimeEvent::Delete => "DELETE",
+            RealtimeEvent::All => {
+                return Err(Error::invalid_input(
+                    "A database change needs a concrete event",
+                ))
+            }
+        };
+
+        let mut parts = topic.split(':').skip(1);
+        let schema = parts.next().unwrap_or("public");
+        let table = parts.next();
+
+        let payload = json!({
+            "type": event,
+            "schema": schema,
+            "table": table,
+            "commit_timestamp": chrono::Utc::now().to_rfc3339(),
+            "record": record,
+            "old_record": old_record,
+        });
+        self.state.push(topic, event, &payload);
+        Ok(())
+    }
+
+    /// Push an arbitrary event to every client
+    pub fn push(&self, topic: &str, event: &str, payload: Value) {
+        self.state.push(topic, event, &payload);
+    }
+
+    /// Send a raw text frame to every client
+    pub fn send_raw(&self, frame: &str) {
+        if let Ok(clients) = self.state.clients.lock() {
+            for client in clients.iter() {
+                let _ = client.outbound.send(Message::Text(frame.to_string()));
+            }
+        }
+    }
+
+    /// Stop or resume answering heartbeats, e.g. to test dead connection detection
+    pub fn set_heartbeat_replies(&self, enabled: bool) {
+        self.state
+            .silent_heartbeats
+            .store(!enabled, Ordering::SeqCst);
+    }
+
+    /// Close every client connection, e.g. to test reconnects
+    pub fn disconnect_clients(&self) {
+        if let Ok(mut clients) = self.state.clients.lock() {
+            for client in clients.drain(..) {
+                let _ = client.outbound.send(Message::Close(None));
+            }
+        }
+        if let Ok(mut joined) = self.state.joined.lock() {
+            joined.clear();
+        }
+        self.state.changed.notify_waiters();
+    }
+}
+
+impl Drop for MockRealtimeServer {
+    fn drop(&mut self) {
+        self.accept_task.abort();
+        self.disconnect_clients();
+    }
+}
+
+impl std::fmt::Debug for MockRealtimeServer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MockRealtimeServer")
+            .field("addr", &self.addr)
+            .field("connections", &self.connection_count())
+            .finish()
+    }
+}
+
+async fn serve_connection(stream: TcpStream, state: Arc<ServerState>) {
+    let mut v2 = false;
+    let handshake = |request: &Request, response: Response| {
+        v2 = request
+            .uri()
+            .query()
+            .is_some_and(|query| query.contains("vsn=2.0.0"));
+        Ok(response)
+    };
+
+    let Ok(socket) = tokio_tungstenite::accept_hdr_async(stream, handshake).await else {
+        return;
+    };
+    let (mut sink, mut source) = socket.split();
+
+    let id = state.connections.fetch_add(1, Ordering::SeqCst);
+    let (outbound, mut outbound_rx) = mpsc::unbounded_channel();
+    if let Ok(mut clients) = state.clients.lock() {
+        clients.push(ClientHandle { id, v2, outbound });
+    }
+    state.changed.notify_waiters();
+
+    loop {
+        tokio::select! {
+            message = outbound_rx.recv() => {
+                let Some(message) = message else { break };
+                let closing = matches!(message, Message::Close(_));
+                if sink.send(message).await.is_err() || closing {
+                    break;
+                }
+            }
+            incoming = source.next() => match incoming {
+                Some(Ok(Message::Text(text))) => {
+                    if let Some(reply) = state.handle_frame(&text, v2) {
+                        if sink.send(Message::Text(reply)).await.is_err() {
+                            break;
+                        }
+                    }
+                }
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                Some(Ok(_)) => {}
+            }
+        }
+    }
+
+    if let Ok(mut clients) = state.clients.lock() {
+        clients.retain(|client| client.id != id);
+    }
+    state.changed.notify_waiters();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::realtime::Realtime;
+    use crate::types::SupabaseConfig;
+
+    const TIMEOUT: Duration = Duration::from_secs(5);
+
+    #[tokio::test]
+    async fn test_mock_server_drives_subscription() {
+        let server = MockRealtimeServer::start().await.unwrap();
+        let realtime = Realtime::new(Arc::new(SupabaseConfig {
+            url: server.url(),
+            key: "test-key".to_string(),
+            ..Default::default()
+        }))
+        .unwrap();
+
+        let (received_tx, mut received_rx) = mpsc::unbounded_channel();
+        realtime
+            .channel("posts")
+            .table("posts")
+            .subscribe(move |message| {
+                if message.event == "INSERT" {
+                    let _ = received_tx.send(message);
+                }
+            })
+            .await
+            .unwrap();
+
+        server
+            .wait_for_join("realtime:public:posts", TIMEOUT)
+            .await
+            .unwrap();
+        let join = server.wait_for_event("phx_join", TIMEOUT).await.unwrap();
+        assert_eq!(join.payload["table"], "posts");
+
+        server
+            .send_postgres_change(
+                "realtime:public:posts",
+                RealtimeEvent::Insert,
+                json!({"id": 1, "title": "Hello"}),
+                None,
+            )
+            .unwrap();
+        let message = tokio::time::timeout(TIMEOUT, received_rx.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(message.payload.record.unwrap()["title"], "Hello");
+        assert_eq!(message.payload.table.as_deref(), Some("posts"));
+
+        server.disconnect_clients();
+        tokio::time::timeout(TIMEOUT, async {
+            while realtime.is_connected().await {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap();
+        assert_eq!(server.connection_count(), 1);
+    }
+
+    #[test]
+    fn test_client_frame_decoding() {
+        let v2 = ClientFrame::decode(r#"["1","2","realtime:public:posts","phx_join",{}]"#).unwrap();
+        assert_eq!(v2.join_ref.as_deref(), Some("1"));
+        assert_eq!(v2.ref_id.as_deref(), Some("2"));
+        assert_eq!(v2.event, "phx_join");
+
+        let v1 = ClientFrame::decode(
+            r#"{"topic":"phoenix","event":"heartbeat","payload":{},"ref_id":"7"}"#,
+        )
+        .unwrap();
+        assert_eq!(v1.ref_id.as_deref(), Some("7"));
+        assert_eq!(v1.topic, "phoenix");
+        assert!(v1.join_ref.is_none());
+
+        assert!(ClientFrame::decode("not json").is_none());
+    }
+}
@@ -0,0 +1,438 @@
+//! Automatic retries of failed HTTP requests
+//!
+//! Enabled with [`HttpConfig::retry`](crate::types::HttpConfig::retry), requests
+//! of auth, database, storage and functions are sent again after connection
+//! errors, timeouts and responses with a status in
+//! [`retry_on_status`](RetryPolicy::retry_on_status), up to
+//! [`max_attempts`](RetryPolicy::max_attempts) attempts in total. The wait
+//! between attempts follows the [`Backoff`], unless the response carries a
+//! `Retry-After` header, which is honored as given.
+//!
+//! POST and PATCH requests may have taken effect even though they failed, so
+//! by default they are only retried when no connection could be made or the
+//! server answered `429 Too Many Requests`. Requests with streaming bodies
+//! cannot be replayed and are sent once. With a
+//! [circuit breaker](crate::circuit), every attempt counts toward it and
+//! retrying stops as soon as the circuit opens. Waiting needs a timer, so
+//! builds with neither the `native` nor the `wasm` feature send every request
+//! once.
+//!
+//! ```rust,no_run
+//! use std::time::Duration;
+//! use supabase_lib_rs::{
+//!     retry::{Backoff, RetryPolicy},
+//!     types::{HttpConfig, SupabaseConfig},
+//!     Client,
+//! };
+//!
+//! # fn example() -> supabase_lib_rs::Result<()> {
+//! let client = Client::new_with_config(SupabaseConfig {
+//!     url: "https://example.supabase.co".to_string(),
+//!     key: "your-anon-key".to_string(),
+//!     http_config: HttpConfig {
+//!         retry: Some(RetryPolicy {
+//!             max_attempts: 4,
+//!             backoff: Backoff::Exponential {
+//!                 initial: Duration::from_millis(100),
+//!                 max: Duration::from_secs(2),
+//!             },
+//!             ..Default::default()
+//!         }),
+//!         ..Default::default()
+//!     },
+//!     ..Default::default()
+//! })?;
+//! # Ok(())
+//! # }
+//! ```
+
+// Sending lives behind the features of the services that send requests
+#[cfg(any(
+    feature = "auth-core",
+    feature = "database",
+    feature = "storage-core",
+    feature = "functions"
+))]
+use crate::{
+    async_runtime::{async_sleep, HAS_TIMER},
+    circuit::CircuitBreaker,
+    error::Result,
+};
+#[cfg(any(
+    feature = "auth-core",
+    feature = "database",
+    feature = "storage-core",
+    feature = "functions"
+))]
+use chrono::{DateTime, Utc};
+#[cfg(any(
+    feature = "auth-core",
+    feature = "database",
+    feature = "storage-core",
+    feature = "functions"
+))]
+use reqwest::{header::RETRY_AFTER, RequestBuilder, Response};
+use std::time::Duration;
+#[cfg(any(
+    feature = "auth-core",
+    feature = "database",
+    feature = "storage-core",
+    feature = "functions"
+))]
+use tracing::warn;
+
+/// When and how often failed requests are retried
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Attempts in total, including the first one
+    pub max_attempts: u32,
+    /// Wait between attempts when the response has no `Retry-After`
+    pub backoff: Backoff,
+    /// Randomize each backoff wait to between half and all of it
+    pub jitter: bool,
+    /// Response statuses that are retried
+    pub retry_on_status: Vec<u16>,
+    /// Longest `Retry-After` to wait for; a longer one ends retrying
+    pub max_retry_after: Duration,
+    /// Retry POST and PATCH requests like idempotent ones
+    pub retry_non_idempotent: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            backoff: Backoff::default(),
+            jitter: true,
+            retry_on_status: vec![408, 429, 500, 502, 503, 504],
+            max_retry_after: Duration::from_secs(60),
+            retry_non_idempotent: false,
+        }
+    }
+}
+
+/// Wait between attempts of a [`RetryPolicy`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Backoff {
+    /// The same wait before every retry
+    Fixed(Duration),
+    /// `initial`, doubled for every further retry, at most `max`
+    Exponential { initial: Duration, max: Duration },
+}
+
+impl Default for Backoff {
+    fn default() -> Self {
+        Self::Exponential {
+            initial: Duration::from_millis(200),
+            max: Duration::from_secs(10),
+        }
+    }
+}
+
+impl Backoff {
+    /// Wait before retry number `retry`, counting from 1
+    pub fn delay(&self, retry: u32) -> Duration {
+        match *self {
+            Self::Fixed(delay) => delay,
+            Self::Exponential { initial, max } => {
+                let factor = 1u32 << retry.saturating_sub(1).min(31);
+                initial.saturating_mul(factor).min(max)
+            }
+        }
+    }
+}
+
+#[cfg(any(
+    feature = "auth-core",
+    feature = "database",
+    feature = "storage-core",
+    feature = "functions"
+))]
+impl RetryPolicy {
+    /// Wait before sending again, or `None` if `result` is final
+    fn retry_delay(
+        &self,
+        retry: u32,
+        result: &std::result::Result<Response, reqwest::Error>,
+        idempotent: bool,
+    ) -> Option<Duration> {
+        let replayable = idempotent || self.retry_non_idempotent;
+        match result {
+            Ok(response) => {
+                let status = response.status().as_u16();
+                if !self.retry_on_status.contains(&status) || !(replayable || status == 429) {
+                    return None;
+                }
+                match retry_after(response) {
+                    Some(wait) if wait > self.max_retry_after => None,
+                    Some(wait) => Some(wait),
+                    None => Some(self.backoff_delay(retry)),
+                }
+            }
+            Err(e) if is_connect_error(e) || (replayable && e.is_timeout()) => {
+                Some(self.backoff_delay(retry))
+            }
+            Err(_) => None,
+        }
+    }
+
+    fn backoff_delay(&self, retry: u32) -> Duration {
+        let delay = self.backoff.delay(retry);
+        if self.jitter {
+            delay.mul_f64(0.5 + random_fraction() / 2.0)
+        } else {
+            delay
+        }
+    }
+}
+
+/// Whether the request failed before reaching the server
+#[cfg(any(
+    feature = "auth-core",
+    feature = "database",
+    feature = "storage-core",
+    feature = "functions"
+))]
+#[cfg(not(target_arch = "wasm32"))]
+fn is_connect_error(error: &reqwest::Error) -> bool {
+    error.is_connect()
+}
+
+/// Whether the request failed before reaching the server
+///
+/// `fetch` only reports network failures as a rejected request.
+#[cfg(any(
+    feature = "auth-core",
+    feature = "database",
+    feature = "storage-core",
+    feature = "functions"
+))]
+#[cfg(target_arch = "wasm32")]
+fn is_connect_error(error: &reqwest::Error) -> bool {
+    error.is_request()
+}
+
+/// Send `request`, retrying according to `policy` and gated by `circuit`
+///
+/// Without a timer to wait between attempts, the request is sent only once.
+#[cfg(any(
+    feature = "auth-core",
+    feature = "database",
+    feature = "storage-core",
+    feature = "functions"
+))]
+pub(crate) async fn send(
+    mut request: RequestBuilder,
+    policy: Option<&RetryPolicy>,
+    circuit: Option<&CircuitBreaker>,
+) -> Result<Response> {
+    let idempotent = policy.is_some()
+        && request
+            .try_clone()
+            .and_then(|request| request.build().ok())
+            .is_some_and(|request| request.method().is_idempotent());
+
+    let mut attempt = 1;
+    loop {
+        let replay = policy
+            .filter(|policy| attempt < policy.max_attempts)
+            .and_then(|_| request.try_clone());
+
+        if let Some(circuit) = circuit {
+            circuit.acquire()?;
+        }
+        let result = request.send().await;
+        if let Some(circuit) = circuit {
+            circuit.observe(&result);
+        }
+
+        // Retrying without a wait would hammer a struggling service
+        let (Some(policy), Some(replay)) = (policy.filter(|_| HAS_TIMER), replay) else {
+            return Ok(result?);
+        };
+        let Some(delay) = policy.retry_delay(attempt, &result, idempotent) else {
+            return Ok(result?);
+        };
+
+        match &result {
+            Ok(response) => warn!(
+                "Request failed with status {} (attempt {}/{}), retrying in {:?}",
+                response.status(),
+                attempt,
+                policy.max_attempts,
+                delay
+            ),
+            Err(e) => warn!(
+                "Request failed (attempt {}/{}), retrying in {:?}: {}",
+                attempt, policy.max_attempts, delay, e
+            ),
+        }
+        async_sleep(delay).await;
+        request = replay;
+        attempt += 1;
+    }
+}
+
+/// Wait requested by a `Retry-After` header, in seconds or as an HTTP date
+#[cfg(any(
+    feature = "auth-core",
+    feature = "database",
+    feature = "storage-core",
+    feature = "functions"
+))]
+fn retry_after(response: &Response) -> Option<Duration> {
+    let value = response.headers().get(RETRY_AFTER)?.to_str().ok()?.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+
+    let date = DateTime::parse_from_rfc2822(value).ok()?;
+    Some(
+        (date.with_timezone(&Utc) - Utc::now())
+            .to_std()
+            .unwrap_or_default(),
+    )
+}
+
+/// Uniformly distributed value in `[0, 1)`
+#[cfg(any(
+    feature = "auth-core",
+    feature = "database",
+    feature = "storage-core",
+    feature = "functions"
+))]
+fn random_fraction() -> f64 {
+    // The low 62 bits of a v4 UUID are random
+    let (_, low) = uuid::Uuid::new_v4().as_u64_pair();
+    (low & ((1 << 62) - 1)) as f64 / (1u64 << 62) as f64
+}
+
+#[cfg(all(
+    test,
+    any(
+        feature = "auth-core",
+        feature = "database",
+        feature = "storage-core",
+        feature = "functions"
+    )
+))]
+mod tests {
+    use super::*;
+    use crate::mock_http::{MockServer, Response};
+
+    /// Answer each request with the next response
+    async fn serve(responses: Vec<Response>) -> (String, MockServer) {
+        let server = MockServer::start(responses).await;
+        (format!("{}/rest/v1/posts", server.url()), server)
+    }
+
+    fn unavailable() -> Response {
+        Response::new(503).header("retry-after", "0")
+    }
+
+    fn rate_limited() -> Response {
+        Response::new(429)
+    }
+
+    fn ok() -> Response {
+        Response::json("[]")
+    }
+
+    fn policy() -> RetryPolicy {
+        RetryPolicy {
+            backoff: Backoff::Fixed(Duration::from_millis(1)),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_backoff_delays() {
+        let backoff = Backoff::Exponential {
+            initial: Duration::from_millis(100),
+            max: Duration::from_millis(500),
+        };
+        let delays: Vec<u128> = (1..=5).map(|n| backoff.delay(n).as_millis()).collect();
+        assert_eq!(delays, vec![100, 200, 400, 500, 500]);
+        assert_eq!(backoff.delay(u32::MAX), Duration::from_millis(500));
+        assert_eq!(
+            Backoff::Fixed(Duration::from_secs(1)).delay(7),
+            Duration::from_secs(1)
+        );
+
+        let jittered = RetryPolicy::default();
+        for retry in 1..=3 {
+            let delay = jittered.backoff_delay(retry);
+            let full = jittered.backoff.delay(retry);
+            assert!(delay >= full / 2 && delay <= full);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_retries_until_success() {
+        let (url, server) = serve(vec![unavailable(), unavailable(), ok()]).await;
+        let client = reqwest::Client::new();
+
+        let response = send(client.get(&url), Some(&policy()), None).await.unwrap();
+        assert_eq!(response.status(), 200);
+        assert_eq!(server.requests().len(), 3);
+
+        // The last failed response is returned once attempts run out
+        let (url, server) = serve(vec![unavailable(), unavailable()]).await;
+        let exhausted = RetryPolicy {
+            max_attempts: 2,
+            ..policy()
+        };
+        let response = send(client.get(&url), Some(&exhausted), None)
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 503);
+        assert_eq!(server.requests().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_non_idempotent_requests() {
+        let client = reqwest::Client::new();
+
+        // A POST is not replayed after a server error...
+        let (url, server) = serve(vec![unavailable()]).await;
+        let response = send(client.post(&url).body("{}"), Some(&policy()), None)
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 503);
+        let requests = server.requests();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].line(), "POST /rest/v1/posts");
+
+        // ...but is after a rate limit, which the server rejected unprocessed
+        let (url, server) = serve(vec![rate_limited(), ok()]).await;
+        let response = send(client.post(&url).body("{}"), Some(&policy()), None)
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 200);
+        assert_eq!(server.requests().len(), 2);
+
+        // ...and on any retryable status when allowed
+        let (url, server) = serve(vec![unavailable(), ok()]).await;
+        let replaying = RetryPolicy {
+            retry_non_idempotent: true,
+            ..policy()
+        };
+        let response = send(client.patch(&url).body("{}"), Some(&replaying), None)
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 200);
+        assert_eq!(server.requests().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_long_retry_after_is_not_awaited() {
+        let (url, server) = serve(vec![Response::new(503).header("retry-after", "3600")]).await;
+
+        let response = send(reqwest::Client::new().get(&url), Some(&policy()), None)
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 503);
+        assert_eq!(response.headers()[RETRY_AFTER], "3600");
+        assert_eq!(server.requests().len(), 1);
+    }
+}
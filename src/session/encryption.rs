@@ -84,8 +84,8 @@ impl SessionEncryptor {
         Ok((encryptor, salt))
     }
 
-    /// Encrypt a session
-    pub fn encrypt_session(&self, session_data: &SessionData) -> Result<SessionData> {
+    /// Encrypt a whole session into a container
+    pub fn encrypt(&self, session_data: &SessionData) -> Result<EncryptedSessionData> {
         // Serialize the original session
         let serialized = serde_json::to_vec(session_data).map_err(|e| {
             Error::crypto(format!("Failed to serialize session for encryption: {}", e))
@@ -103,8 +103,7 @@ impl SessionEncryptor {
             .encrypt(&nonce, serialized.as_ref())
             .map_err(|e| Error::crypto(format!("Failed to encrypt session: {}", e)))?;
 
-        // Create encrypted container
-        let encrypted_container = EncryptedSessionData {
+        Ok(EncryptedSessionData {
             encrypted_data,
             nonce: nonce.to_vec(),
             salt: None,
@@ -113,39 +112,11 @@ impl SessionEncryptor {
                 kdf: None,
                 version: 1,
             },
-        };
-
-        // Create a new SessionData with encrypted content
-        let mut encrypted_session = session_data.clone();
-        encrypted_session.platform_data.insert(
-            "encrypted_payload".to_string(),
-            serde_json::to_value(&encrypted_container).map_err(|e| {
-                Error::crypto(format!("Failed to serialize encrypted container: {}", e))
-            })?,
-        );
-
-        // Clear sensitive data from the session
-        encrypted_session.session.access_token = "***ENCRYPTED***".to_string();
-        encrypted_session.session.refresh_token = "***ENCRYPTED***".to_string();
-        encrypted_session.session.user.email = None;
-        encrypted_session.session.user.phone = None;
-
-        Ok(encrypted_session)
+        })
     }
 
-    /// Decrypt a session
-    pub fn decrypt_session(&self, encrypted_session: &SessionData) -> Result<SessionData> {
-        // Extract encrypted container from platform data
-        let encrypted_container_value = encrypted_session
-            .platform_data
-            .get("encrypted_payload")
-            .ok_or_else(|| Error::crypto("No encrypted payload found in session"))?;
-
-        let encrypted_container: EncryptedSessionData =
-            serde_json::from_value(encrypted_container_value.clone()).map_err(|e| {
-                Error::crypto(format!("Failed to deserialize encrypted container: {}", e))
-            })?;
-
+    /// Decrypt a container produced by [`encrypt`](Self::encrypt)
+    pub fn decrypt(&self, encrypted_container: &EncryptedSessionData) -> Result<SessionData> {
         // Verify encryption metadata
         if encrypted_container.metadata.algorithm != "AES-256-GCM" {
             return Err(Error::crypto(format!(
@@ -179,6 +150,44 @@ impl SessionEncryptor {
         Ok(original_session)
     }
 
+    /// Encrypt a session
+    pub fn encrypt_session(&self, session_data: &SessionData) -> Result<SessionData> {
+        let encrypted_container = self.encrypt(session_data)?;
+
+        // Create a new SessionData with encrypted content
+        let mut encrypted_session = session_data.clone();
+        encrypted_session.platform_data.insert(
+            "encrypted_payload".to_string(),
+            serde_json::to_value(&encrypted_container).map_err(|e| {
+                Error::crypto(format!("Failed to serialize encrypted container: {}", e))
+            })?,
+        );
+
+        // Clear sensitive data from the session
+        encrypted_session.session.access_token = "***ENCRYPTED***".to_string();
+        encrypted_session.session.refresh_token = "***ENCRYPTED***".to_string();
+        encrypted_session.session.user.email = None;
+        encrypted_session.session.user.phone = None;
+
+        Ok(encrypted_session)
+    }
+
+    /// Decrypt a session
+    pub fn decrypt_session(&self, encrypted_session: &SessionData) -> Result<SessionData> {
+        // Extract encrypted container from platform data
+        let encrypted_container_value = encrypted_session
+            .platform_data
+            .get("encrypted_payload")
+            .ok_or_else(|| Error::crypto("No encrypted payload found in session"))?;
+
+        let encrypted_container: EncryptedSessionData =
+            serde_json::from_value(encrypted_container_value.clone()).map_err(|e| {
+                Error::crypto(format!("Failed to deserialize encrypted container: {}", e))
+            })?;
+
+        self.decrypt(&encrypted_container)
+    }
+
     /// Generate a secure random encryption key
     pub fn generate_key() -> [u8; 32] {
         rand::random()
@@ -341,12 +350,33 @@ impl KeyManager {
     /// Retrieve encryption key from secure storage
     #[cfg(all(feature = "session-encryption", not(target_arch = "wasm32")))]
     pub fn retrieve_key_securely(service: &str, username: &str) -> Result<[u8; 32]> {
+        Self::find_key_securely(service, username)?.ok_or_else(|| {
+            Error::crypto(format!(
+                "Failed to retrieve key from keyring: {}",
+                keyring::Error::NoEntry
+            ))
+        })
+    }
+
+    /// Retrieve encryption key from secure storage, or `None` if none is stored
+    ///
+    /// Other keyring failures (locked or unavailable keyring, denied access)
+    /// are errors, so callers never replace a key they merely failed to read.
+    #[cfg(all(feature = "session-encryption", not(target_arch = "wasm32")))]
+    pub fn find_key_securely(service: &str, username: &str) -> Result<Option<[u8; 32]>> {
         let entry = keyring::Entry::new(service, username)
             .map_err(|e| Error::crypto(format!("Failed to create keyring entry: {}", e)))?;
 
-        let key_hex = entry
-            .get_password()
-            .map_err(|e| Error::crypto(format!("Failed to retrieve key from keyring: {}", e)))?;
+        let key_hex = match entry.get_password() {
+            Ok(key_hex) => key_hex,
+            Err(keyring::Error::NoEntry) => return Ok(None),
+            Err(e) => {
+                return Err(Error::crypto(format!(
+                    "Failed to retrieve key from keyring: {}",
+                    e
+                )))
+            }
+        };
 
         let key_bytes = hex::decode(&key_hex)
             .map_err(|e| Error::crypto(format!("Failed to decode key from hex: {}", e)))?;
@@ -357,6 +387,6 @@ impl KeyManager {
 
         let mut key = [0u8; 32];
         key.copy_from_slice(&key_bytes);
-        Ok(key)
+        Ok(Some(key))
     }
 }
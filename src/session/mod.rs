@@ -18,7 +18,7 @@ pub mod wasm;
 pub mod native;
 
 #[cfg(feature = "session-management")]
-use crate::auth::Session;
+use crate::auth::{Session, SessionSerializer};
 #[cfg(feature = "session-management")]
 use crate::error::{Error, Result};
 #[cfg(feature = "session-management")]
@@ -86,6 +86,32 @@ pub struct SessionData {
     pub platform_data: HashMap<String, serde_json::Value>,
 }
 
+#[cfg(feature = "session-management")]
+impl SessionData {
+    /// Whether the access token is still valid or can be refreshed
+    ///
+    /// Storage backends keep sessions with an expired access token as long as
+    /// they have a refresh token, so they can be restored by refreshing.
+    pub fn is_usable(&self) -> bool {
+        self.session.expires_at > Utc::now() || !self.session.refresh_token.is_empty()
+    }
+
+    /// Expiry handed to the storage backend; `None` while the session can be refreshed
+    fn storage_expiry(&self) -> Option<DateTime<Utc>> {
+        self.session
+            .refresh_token
+            .is_empty()
+            .then_some(self.session.expires_at)
+    }
+}
+
+/// Identifies a refresh token in cross-tab messages without revealing it
+#[cfg(feature = "session-management")]
+fn refresh_token_digest(refresh_token: &str) -> String {
+    use sha2::{Digest, Sha256};
+    format!("{:x}", Sha256::digest(refresh_token.as_bytes()))
+}
+
 /// Session metadata for tracking and analytics
 #[cfg(feature = "session-management")]
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -208,6 +234,67 @@ pub struct CrossTabMessage {
     pub source_tab: String,
 }
 
+/// Which side wins when tabs hold divergent sessions for the same user
+#[cfg(feature = "session-management")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConflictWinner {
+    /// Keep the session this tab already has
+    Local,
+    /// Adopt the session received from another tab
+    Incoming,
+}
+
+/// Callback deciding a session conflict, given the local and incoming session
+#[cfg(feature = "session-management")]
+pub type ConflictResolver =
+    Arc<dyn Fn(&SessionData, &SessionData) -> ConflictWinner + Send + Sync + 'static>;
+
+/// How to resolve tabs that refreshed the same user's session concurrently
+///
+/// Only one of the diverged refresh tokens stays valid on the server, so every
+/// tab has to converge on the same session. The winner is broadcast back to
+/// the other tabs, which adopt it without raising a second conflict.
+#[cfg(feature = "session-management")]
+#[derive(Clone, Default)]
+pub enum ConflictResolution {
+    /// Keep the session that expires last
+    ///
+    /// Ties are broken by refresh token so that every tab picks the same winner.
+    #[default]
+    NewestWins,
+    /// Ask the application, e.g. to prompt the user
+    Callback(ConflictResolver),
+}
+
+#[cfg(feature = "session-management")]
+impl ConflictResolution {
+    fn resolve(&self, local: &SessionData, incoming: &SessionData) -> ConflictWinner {
+        match self {
+            Self::NewestWins => {
+                let newest = |data: &SessionData| {
+                    (data.session.expires_at, data.session.refresh_token.clone())
+                };
+                if newest(incoming) > newest(local) {
+                    ConflictWinner::Incoming
+                } else {
+                    ConflictWinner::Local
+                }
+            }
+            Self::Callback(resolver) => resolver(local, incoming),
+        }
+    }
+}
+
+#[cfg(feature = "session-management")]
+impl std::fmt::Debug for ConflictResolution {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NewestWins => f.write_str("NewestWins"),
+            Self::Callback(_) => f.write_str("Callback(..)"),
+        }
+    }
+}
+
 /// Session manager configuration
 #[cfg(feature = "session-management")]
 #[derive(Debug, Clone)]
@@ -238,15 +325,20 @@ pub struct SessionManagerConfig {
 
     /// Background sync interval (in seconds)
     pub sync_interval_seconds: u64,
+
+    /// How to resolve sessions that diverged across tabs
+    pub conflict_resolution: ConflictResolution,
 }
 
 /// Advanced Session Manager with cross-platform support
 #[cfg(feature = "session-management")]
+#[derive(Clone)]
 pub struct SessionManager {
     config: SessionManagerConfig,
     active_sessions: Arc<RwLock<HashMap<Uuid, SessionData>>>,
     event_listeners: Arc<RwLock<HashMap<Uuid, SessionEventCallback>>>,
-    cross_tab_channel: Arc<Mutex<Option<Box<dyn CrossTabChannel>>>>,
+    cross_tab_channel: Arc<Mutex<Option<Arc<dyn CrossTabChannel>>>>,
+    serializer: Option<Arc<dyn SessionSerializer>>,
 }
 
 /// Session event callback type
@@ -277,9 +369,18 @@ impl SessionManager {
             active_sessions: Arc::new(RwLock::new(HashMap::new())),
             event_listeners: Arc::new(RwLock::new(HashMap::new())),
             cross_tab_channel: Arc::new(Mutex::new(None)),
+            serializer: None,
         }
     }
 
+    /// Transform sessions with `serializer` before they reach the storage backend
+    ///
+    /// Sessions kept in memory are not affected.
+    pub fn with_serializer(mut self, serializer: Arc<dyn SessionSerializer>) -> Self {
+        self.serializer = Some(serializer);
+        self
+    }
+
     /// Initialize the session manager
     pub async fn initialize(&self) -> Result<()> {
         // Load persisted sessions
@@ -330,10 +431,13 @@ impl SessionManager {
 
         // Persist to storage
         let key = format!("{}{}", self.config.session_key_prefix, session_id);
-        let expires_at = Some(session_data.session.expires_at);
         self.config
             .storage_backend
-            .store_session(&key, &session_data, expires_at)
+            .store_session(
+                &key,
+                &self.encode_for_storage(&session_data)?,
+                session_data.storage_expiry(),
+            )
             .await?;
 
         // Emit event
@@ -375,7 +479,9 @@ impl SessionManager {
 
         // Try storage if not in memory
         let key = format!("{}{}", self.config.session_key_prefix, session_id);
-        if let Some(mut session_data) = self.config.storage_backend.get_session(&key).await? {
+        if let Some(stored) = self.config.storage_backend.get_session(&key).await? {
+            let mut session_data = self.decode_from_storage(stored)?;
+
             // Update access time
             session_data.metadata.last_accessed_at = Utc::now();
 
@@ -399,58 +505,88 @@ impl SessionManager {
 
     /// Update a session
     pub async fn update_session(&self, session_id: Uuid, updated_session: Session) -> Result<()> {
-        let mut changes = Vec::new();
+        let previous_refresh_token = self.replace_session(session_id, updated_session).await?;
 
-        // Get current session
-        if let Some(mut session_data) = self.get_session(session_id).await? {
-            // Track changes
-            if session_data.session.access_token != updated_session.access_token {
-                changes.push("access_token".to_string());
-            }
-            if session_data.session.refresh_token != updated_session.refresh_token {
-                changes.push("refresh_token".to_string());
-            }
-            if session_data.session.expires_at != updated_session.expires_at {
-                changes.push("expires_at".to_string());
-            }
+        // Cross-tab sync
+        if self.config.enable_cross_tab_sync {
+            self.broadcast_session(session_id, "session_updated", Some(previous_refresh_token))
+                .await?;
+        }
 
-            // Update session
-            session_data.session = updated_session;
-            session_data.metadata.last_accessed_at = Utc::now();
+        Ok(())
+    }
 
-            if changes.contains(&"access_token".to_string())
-                || changes.contains(&"refresh_token".to_string())
-            {
-                session_data.metadata.last_refreshed_at = Some(Utc::now());
-            }
+    /// Apply a session change announced by another tab
+    ///
+    /// Registered on the cross-tab channel by [`initialize`](Self::initialize);
+    /// public for custom [`CrossTabChannel`] setups. Messages carry no tokens:
+    /// the announced session is read from the storage backend, which the tabs
+    /// must share (localStorage in browsers, a directory natively). A session
+    /// that replaces the local one is adopted as-is, while one that diverged
+    /// from it (both tabs refreshed) emits [`SessionEvent::Conflict`] and is
+    /// settled by [`SessionManagerConfig::conflict_resolution`]. A session
+    /// removed with [`remove_session`](Self::remove_session), e.g. on
+    /// sign-out, is dropped here as well. Sessions missing from the storage
+    /// are ignored.
+    pub async fn handle_cross_tab_message(&self, message: CrossTabMessage) -> Result<()> {
+        if message.event_type == "session_destroyed" {
+            return self.forget_session(message.session_id).await;
+        }
 
-            // Store in memory
-            {
-                let mut sessions = self.active_sessions.write();
-                sessions.insert(session_id, session_data.clone());
-            }
+        // Messages carry no tokens; the session is read from the shared storage
+        let key = format!("{}{}", self.config.session_key_prefix, message.session_id);
+        let Some(stored) = self.config.storage_backend.get_session(&key).await? else {
+            return Ok(());
+        };
+        let incoming = self.decode_from_storage(stored)?;
+        let previous_digest = message
+            .payload
+            .get("previous_refresh_token_digest")
+            .and_then(|digest| digest.as_str());
+
+        self.emit_session_event(SessionEvent::CrossTabSync {
+            session_id: message.session_id,
+            source_tab: message.source_tab.clone(),
+        });
+
+        let local = {
+            let sessions = self.active_sessions.read();
+            sessions
+                .iter()
+                .find(|(_, data)| data.session.user.id == incoming.session.user.id)
+                .map(|(id, data)| (*id, data.clone()))
+        };
 
-            // Persist to storage
-            let key = format!("{}{}", self.config.session_key_prefix, session_id);
-            let expires_at = Some(session_data.session.expires_at);
-            self.config
-                .storage_backend
-                .store_session(&key, &session_data, expires_at)
-                .await?;
+        let Some((session_id, local)) = local else {
+            let session_id = incoming.metadata.session_id;
+            self.save_session(session_id, &incoming).await?;
+            self.emit_session_event(SessionEvent::Created { session_id });
+            return Ok(());
+        };
 
-            // Emit event
-            self.emit_session_event(SessionEvent::Updated {
-                session_id,
-                changes,
-            });
+        if local.session.refresh_token == incoming.session.refresh_token {
+            return Ok(());
+        }
+        if previous_digest == Some(refresh_token_digest(&local.session.refresh_token).as_str()) {
+            // The other tab refreshed the session we hold: fast-forward
+            self.replace_session(session_id, incoming.session).await?;
+            return Ok(());
+        }
 
-            // Cross-tab sync
-            if self.config.enable_cross_tab_sync {
-                self.sync_to_other_tabs(session_id, "session_updated")
-                    .await?;
-            }
-        } else {
-            return Err(Error::auth(format!("Session {} not found", session_id)));
+        self.emit_session_event(SessionEvent::Conflict {
+            session_id,
+            conflict_type: "divergent_refresh_token".to_string(),
+        });
+
+        let losing_refresh_token = match self.config.conflict_resolution.resolve(&local, &incoming)
+        {
+            ConflictWinner::Local => incoming.session.refresh_token,
+            ConflictWinner::Incoming => self.replace_session(session_id, incoming.session).await?,
+        };
+
+        if self.config.enable_cross_tab_sync {
+            self.broadcast_session(session_id, "session_resolved", Some(losing_refresh_token))
+                .await?;
         }
 
         Ok(())
@@ -504,6 +640,79 @@ impl SessionManager {
     }
 
     /// Private helper methods
+    fn encode_for_storage(&self, session_data: &SessionData) -> Result<SessionData> {
+        let mut stored = session_data.clone();
+        if let Some(serializer) = &self.serializer {
+            stored.session = serializer.encode(&session_data.session)?;
+        }
+        Ok(stored)
+    }
+
+    fn decode_from_storage(&self, mut stored: SessionData) -> Result<SessionData> {
+        if let Some(serializer) = &self.serializer {
+            stored.session = serializer.decode(stored.session)?;
+        }
+        Ok(stored)
+    }
+
+    /// Replace the tokens of a stored session, returning the previous refresh token
+    async fn replace_session(&self, session_id: Uuid, updated_session: Session) -> Result<String> {
+        let mut changes = Vec::new();
+
+        let Some(mut session_data) = self.get_session(session_id).await? else {
+            return Err(Error::auth(format!("Session {} not found", session_id)));
+        };
+
+        // Track changes
+        if session_data.session.access_token != updated_session.access_token {
+            changes.push("access_token".to_string());
+        }
+        if session_data.session.refresh_token != updated_session.refresh_token {
+            changes.push("refresh_token".to_string());
+        }
+        if session_data.session.expires_at != updated_session.expires_at {
+            changes.push("expires_at".to_string());
+        }
+
+        // Update session
+        let previous = std::mem::replace(&mut session_data.session, updated_session);
+        session_data.metadata.last_accessed_at = Utc::now();
+
+        if changes.contains(&"access_token".to_string())
+            || changes.contains(&"refresh_token".to_string())
+        {
+            session_data.metadata.last_refreshed_at = Some(Utc::now());
+        }
+
+        self.save_session(session_id, &session_data).await?;
+
+        // Emit event
+        self.emit_session_event(SessionEvent::Updated {
+            session_id,
+            changes,
+        });
+
+        Ok(previous.refresh_token)
+    }
+
+    /// Store a session in memory and persist it to storage
+    async fn save_session(&self, session_id: Uuid, session_data: &SessionData) -> Result<()> {
+        {
+            let mut sessions = self.active_sessions.write();
+            sessions.insert(session_id, session_data.clone());
+        }
+
+        let key = format!("{}{}", self.config.session_key_prefix, session_id);
+        self.config
+            .storage_backend
+            .store_session(
+                &key,
+                &self.encode_for_storage(session_data)?,
+                session_data.storage_expiry(),
+            )
+            .await
+    }
+
     async fn load_persisted_sessions(&self) -> Result<()> {
         let keys = self.config.storage_backend.list_session_keys().await?;
         let mut valid_sessions = Vec::new();
@@ -511,8 +720,16 @@ impl SessionManager {
 
         // Collect valid sessions and expired keys without holding lock
         for key in keys {
-            if let Some(session_data) = self.config.storage_backend.get_session(&key).await? {
-                if session_data.session.expires_at > Utc::now() {
+            if let Some(stored) = self.config.storage_backend.get_session(&key).await? {
+                let session_data = match self.decode_from_storage(stored) {
+                    Ok(session_data) => session_data,
+                    Err(err) => {
+                        tracing::warn!("Failed to decode stored session {}: {}", key, err);
+                        continue;
+                    }
+                };
+
+                if session_data.is_usable() {
                     if let Ok(uuid) = key
                         .strip_prefix(&self.config.session_key_prefix)
                         .unwrap_or(&key)
@@ -547,8 +764,17 @@ impl SessionManager {
         #[cfg(all(target_arch = "wasm32", feature = "wasm"))]
         {
             let channel = crate::session::wasm::WasmCrossTabChannel::new()?;
+            let manager = self.clone();
+            channel.on_message(Box::new(move |message| {
+                let manager = manager.clone();
+                wasm_bindgen_futures::spawn_local(async move {
+                    if let Err(err) = manager.handle_cross_tab_message(message).await {
+                        tracing::warn!("Failed to apply cross-tab session: {}", err);
+                    }
+                });
+            }));
             let mut cross_tab = self.cross_tab_channel.lock();
-            *cross_tab = Some(Box::new(channel));
+            *cross_tab = Some(Arc::new(channel));
             Ok(())
         }
 
@@ -561,8 +787,17 @@ impl SessionManager {
         #[cfg(not(target_arch = "wasm32"))]
         {
             let channel = crate::session::native::NativeCrossTabChannel::new()?;
+            let manager = self.clone();
+            channel.on_message(Box::new(move |message| {
+                let manager = manager.clone();
+                tokio::spawn(async move {
+                    if let Err(err) = manager.handle_cross_tab_message(message).await {
+                        tracing::warn!("Failed to apply cross-tab session: {}", err);
+                    }
+                });
+            }));
             let mut cross_tab = self.cross_tab_channel.lock();
-            *cross_tab = Some(Box::new(channel));
+            *cross_tab = Some(Arc::new(channel));
             Ok(())
         }
     }
@@ -577,14 +812,48 @@ impl SessionManager {
         Ok(())
     }
 
-    #[allow(clippy::await_holding_lock)]
     async fn sync_to_other_tabs(&self, session_id: Uuid, event_type: &str) -> Result<()> {
-        if let Some(channel) = self.cross_tab_channel.lock().as_ref() {
+        self.broadcast_session(session_id, event_type, None).await
+    }
+
+    /// Drop a session another tab removed, without broadcasting it back
+    async fn forget_session(&self, session_id: Uuid) -> Result<()> {
+        if self.active_sessions.write().remove(&session_id).is_none() {
+            return Ok(());
+        }
+
+        let key = format!("{}{}", self.config.session_key_prefix, session_id);
+        self.config.storage_backend.remove_session(&key).await?;
+        self.emit_session_event(SessionEvent::Destroyed {
+            session_id,
+            reason: "removed in another tab".to_string(),
+        });
+        Ok(())
+    }
+
+    /// Tell other tabs that a stored session changed, noting which refresh token it replaces
+    ///
+    /// Only the session id and a digest of the replaced refresh token are
+    /// sent; receivers read the session itself from the shared storage.
+    async fn broadcast_session(
+        &self,
+        session_id: Uuid,
+        event_type: &str,
+        previous_refresh_token: Option<String>,
+    ) -> Result<()> {
+        let channel = self.cross_tab_channel.lock().clone();
+        if let Some(channel) = channel {
+            let payload = match previous_refresh_token {
+                Some(token) => serde_json::json!({
+                    "previous_refresh_token_digest": refresh_token_digest(&token),
+                }),
+                None => serde_json::json!({}),
+            };
             let message = CrossTabMessage {
                 message_id: Uuid::new_v4(),
                 session_id,
                 event_type: event_type.to_string(),
-                payload: serde_json::json!({}),
+                payload,
                 timestamp: Utc::now(),
                 source_tab: self
                     .detect_tab_id()
@@ -659,6 +928,535 @@ impl Default for SessionManagerConfig {
             enable_monitoring: true,
             max_memory_sessions: 100,
             sync_interval_seconds: 30,
+            conflict_resolution: ConflictResolution::default(),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "session-management"))]
+mod tests {
+    use super::*;
+    use crate::auth::User;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn session(user: &User, refresh_token: &str, expires_in_secs: i64) -> Session {
+        Session {
+            access_token: format!("access-{}", refresh_token),
+            refresh_token: refresh_token.to_string(),
+            expires_in: expires_in_secs,
+            expires_at: Utc::now() + chrono::Duration::seconds(expires_in_secs),
+            token_type: "bearer".to_string(),
+            user: user.clone(),
+            provider_token: None,
+            provider_refresh_token: None,
+            provider: None,
+        }
+    }
+
+    /// Store `data` as another tab would and build the message announcing it
+    async fn message(
+        manager: &SessionManager,
+        data: &SessionData,
+        previous_refresh_token: Option<&str>,
+    ) -> CrossTabMessage {
+        let key = format!(
+            "{}{}",
+            manager.config.session_key_prefix, data.metadata.session_id
+        );
+        manager
+            .config
+            .storage_backend
+            .store_session(&key, data, None)
+            .await
+            .unwrap();
+        CrossTabMessage {
+            message_id: Uuid::new_v4(),
+            session_id: data.metadata.session_id,
+            event_type: "session_updated".to_string(),
+            payload: serde_json::json!({
+                "previous_refresh_token_digest": previous_refresh_token.map(refresh_token_digest),
+            }),
+            timestamp: Utc::now(),
+            source_tab: "other-tab".to_string(),
+        }
+    }
+
+    /// Channel recording what a manager sends
+    #[derive(Default)]
+    struct RecordingChannel(std::sync::Mutex<Vec<CrossTabMessage>>);
+
+    #[cfg_attr(not(target_arch = "wasm32"), async_trait::async_trait)]
+    #[cfg_attr(target_arch = "wasm32", async_trait::async_trait(?Send))]
+    impl CrossTabChannel for RecordingChannel {
+        async fn send_message(&self, message: CrossTabMessage) -> Result<()> {
+            self.0.lock().unwrap().push(message);
+            Ok(())
+        }
+
+        fn on_message(&self, _callback: Box<dyn Fn(CrossTabMessage) + Send + Sync>) {}
+
+        async fn close(&self) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_cross_tab_messages_carry_no_tokens() {
+        let user = user();
+        let manager = SessionManager::new(SessionManagerConfig {
+            enable_cross_tab_sync: true,
+            ..Default::default()
+        });
+        let channel = Arc::new(RecordingChannel::default());
+        *manager.cross_tab_channel.lock() = Some(channel.clone());
+
+        let session_id = manager
+            .store_session(session(&user, "refresh-a", 3600))
+            .await
+            .unwrap();
+        manager
+            .update_session(session_id, session(&user, "refresh-b", 3600))
+            .await
+            .unwrap();
+
+        let sent = channel.0.lock().unwrap().clone();
+        assert_eq!(sent.len(), 2);
+        for message in &sent {
+            let json = serde_json::to_string(message).unwrap();
+            assert!(!json.contains("refresh-"));
+            assert!(!json.contains("access-"));
+        }
+        assert_eq!(
+            sent[1].payload["previous_refresh_token_digest"],
+            refresh_token_digest("refresh-a")
+        );
+
+        // Another tab sharing the storage fast-forwards to the refreshed session
+        let other = SessionManager::new(SessionManagerConfig {
+            storage_backend: Arc::clone(&manager.config.storage_backend),
+            enable_cross_tab_sync: false,
+            ..Default::default()
+        });
+        other.initialize().await.unwrap();
+        let conflicts = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&conflicts);
+        other.on_session_event(move |event| {
+            if matches!(event, SessionEvent::Conflict { .. }) {
+                counter.fetch_add(1, Ordering::SeqCst);
+            }
+        });
+        manager
+            .update_session(session_id, session(&user, "refresh-c", 3600))
+            .await
+            .unwrap();
+        let refreshed = channel.0.lock().unwrap()[2].clone();
+        other.handle_cross_tab_message(refreshed).await.unwrap();
+        assert_eq!(
+            other.active_sessions.read()[&session_id]
+                .session
+                .refresh_token,
+            "refresh-c"
+        );
+        assert_eq!(conflicts.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn test_serializer_applies_to_storage_only() {
+        struct ReverseRefreshToken;
+
+        impl SessionSerializer for ReverseRefreshToken {
+            fn encode(&self, session: &Session) -> Result<Session> {
+                let mut stored = session.clone();
+                stored.refresh_token = session.refresh_token.chars().rev().collect();
+                Ok(stored)
+            }
+
+            fn decode(&self, mut stored: Session) -> Result<Session> {
+                stored.refresh_token = stored.refresh_token.chars().rev().collect();
+                Ok(stored)
+            }
+        }
+
+        let user = user();
+        let manager = SessionManager::new(SessionManagerConfig {
+            enable_cross_tab_sync: false,
+            ..Default::default()
+        })
+        .with_serializer(Arc::new(ReverseRefreshToken));
+        let session_id = manager
+            .store_session(session(&user, "refresh", 3600))
+            .await
+            .unwrap();
+
+        // Memory keeps the session as is, storage holds the encoded form
+        assert_eq!(
+            manager.active_sessions.read()[&session_id]
+                .session
+                .refresh_token,
+            "refresh"
+        );
+        let key = format!("{}{}", manager.config.session_key_prefix, session_id);
+        let stored = manager
+            .config
+            .storage_backend
+            .get_session(&key)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored.session.refresh_token, "hserfer");
+        assert_eq!(stored.session.access_token, "access-refresh");
+
+        // A manager sharing the storage decodes what it loads
+        let other = SessionManager::new(SessionManagerConfig {
+            storage_backend: Arc::clone(&manager.config.storage_backend),
+            enable_cross_tab_sync: false,
+            ..Default::default()
+        })
+        .with_serializer(Arc::new(ReverseRefreshToken));
+        let loaded = other.get_session(session_id).await.unwrap().unwrap();
+        assert_eq!(loaded.session.refresh_token, "refresh");
+    }
+
+    fn user() -> User {
+        serde_json::from_value(serde_json::json!({
+            "id": Uuid::new_v4(),
+            "email": "user@example.com",
+            "phone": null,
+            "email_confirmed_at": null,
+            "phone_confirmed_at": null,
+            "created_at": Utc::now(),
+            "updated_at": Utc::now(),
+            "last_sign_in_at": null,
+            "app_metadata": {},
+            "user_metadata": {},
+            "aud": "authenticated",
+            "role": null,
+        }))
+        .unwrap()
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[tokio::test]
+    async fn test_expired_sessions_are_kept_while_refreshable() {
+        let dir = std::env::temp_dir().join(format!("supabase-sessions-{}", Uuid::new_v4()));
+        let config = SessionManagerConfig {
+            storage_backend: Arc::new(StorageBackend::FileSystem(
+                storage::FileSystemStorage::new(Some(dir.clone())).unwrap(),
+            )),
+            enable_cross_tab_sync: false,
+            ..Default::default()
+        };
+        let user = user();
+
+        let manager = SessionManager::new(config.clone());
+        let refreshable = manager
+            .store_session(session(&user, "refresh", -60))
+            .await
+            .unwrap();
+        manager
+            .store_session(session(&user, "", -60))
+            .await
+            .unwrap();
+
+        let restarted = SessionManager::new(config);
+        restarted.initialize().await.unwrap();
+        let sessions = restarted.list_sessions().await.unwrap();
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0].metadata.session_id, refreshable);
+        assert!(sessions[0].session.expires_at < Utc::now());
+
+        #[cfg(unix)]
+        for entry in std::fs::read_dir(&dir).unwrap() {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = entry.unwrap().metadata().unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_cross_tab_conflict_resolution() {
+        let user = user();
+        let config = SessionManagerConfig {
+            enable_cross_tab_sync: false,
+            ..Default::default()
+        };
+
+        let manager = SessionManager::new(config.clone());
+        let conflicts = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&conflicts);
+        manager.on_session_event(move |event| {
+            if let SessionEvent::Conflict { conflict_type, .. } = event {
+                assert_eq!(conflict_type, "divergent_refresh_token");
+                counter.fetch_add(1, Ordering::SeqCst);
+            }
+        });
+        let local_id = manager
+            .store_session(session(&user, "refresh-a", 3600))
+            .await
+            .unwrap();
+        let current = |manager: &SessionManager| {
+            manager.active_sessions.read()[&local_id]
+                .session
+                .refresh_token
+                .clone()
+        };
+
+        // Another tab refreshed the session we hold: adopted without a conflict
+        let mut remote = manager.active_sessions.read()[&local_id].clone();
+        remote.metadata.session_id = Uuid::new_v4();
+        remote.session = session(&user, "refresh-b", 3600);
+        manager
+            .handle_cross_tab_message(message(&manager, &remote, Some("refresh-a")).await)
+            .await
+            .unwrap();
+        assert_eq!(current(&manager), "refresh-b");
+        assert_eq!(conflicts.load(Ordering::SeqCst), 0);
+
+        // Both tabs refreshed: the older incoming session loses
+        remote.session = session(&user, "refresh-c", 60);
+        manager
+            .handle_cross_tab_message(message(&manager, &remote, Some("refresh-a")).await)
+            .await
+            .unwrap();
+        assert_eq!(current(&manager), "refresh-b");
+        assert_eq!(conflicts.load(Ordering::SeqCst), 1);
+
+        // ...and a newer one wins
+        remote.session = session(&user, "refresh-d", 7200);
+        manager
+            .handle_cross_tab_message(message(&manager, &remote, None).await)
+            .await
+            .unwrap();
+        assert_eq!(current(&manager), "refresh-d");
+        assert_eq!(conflicts.load(Ordering::SeqCst), 2);
+
+        // A callback can overrule the default
+        let manager = SessionManager::new(SessionManagerConfig {
+            conflict_resolution: ConflictResolution::Callback(Arc::new(|_, _| {
+                ConflictWinner::Local
+            })),
+            ..config
+        });
+        let local_id = manager
+            .store_session(session(&user, "refresh-a", 60))
+            .await
+            .unwrap();
+        remote.session = session(&user, "refresh-e", 7200);
+        manager
+            .handle_cross_tab_message(message(&manager, &remote, None).await)
+            .await
+            .unwrap();
+        assert_eq!(
+            manager.active_sessions.read()[&local_id]
+                .session
+                .refresh_token,
+            "refresh-a"
+        );
+    }
+
+    #[cfg(all(feature = "session-encryption", not(target_arch = "wasm32")))]
+    #[tokio::test]
+    async fn test_encrypted_file_storage() {
+        use storage::{FileStorage, FileStorageKey};
+
+        let dir = std::env::temp_dir().join(format!("supabase-sessions-{}", Uuid::new_v4()));
+        let open = |passphrase: &str| {
+            let storage = FileStorage::new(
+                Some(dir.clone()),
+                FileStorageKey::Passphrase(passphrase.into()),
+            )
+            .unwrap();
+            SessionManager::new(SessionManagerConfig {
+                storage_backend: Arc::new(StorageBackend::File(storage)),
+                enable_cross_tab_sync: false,
+                ..Default::default()
+            })
+        };
+
+        let session_id = open("correct horse")
+            .store_session(session(&user(), "refresh-secret", 3600))
+            .await
+            .unwrap();
+
+        // Nothing readable reaches the disk
+        let file = dir.join(format!("supabase_session_{}.session", session_id));
+        let contents = std::fs::read_to_string(&file).unwrap();
+        assert!(!contents.contains("refresh-secret"));
+        assert!(!contents.contains("user@example.com"));
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&file).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        // A restarted process with the same passphrase reads the session back
+        let manager = open("correct horse");
+        manager.initialize().await.unwrap();
+        let sessions = manager.list_sessions().await.unwrap();
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0].session.refresh_token, "refresh-secret");
+
+        // The wrong passphrase cannot decrypt it
+        let key = format!("supabase_session_{}", session_id);
+        let storage = FileStorage::new(
+            Some(dir.clone()),
+            FileStorageKey::Passphrase("wrong".into()),
+        )
+        .unwrap();
+        assert!(storage.get_session(&key).await.is_err());
+        assert_eq!(storage.list_session_keys().await.unwrap(), vec![key]);
+
+        storage.clear_all_sessions().await.unwrap();
+        assert!(storage.list_session_keys().await.unwrap().is_empty());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(all(feature = "session-encryption", not(target_arch = "wasm32")))]
+    #[tokio::test]
+    async fn test_encrypted_file_storage_keeps_refreshable_sessions() {
+        use storage::{FileStorage, FileStorageKey};
+
+        let dir = std::env::temp_dir().join(format!("supabase-sessions-{}", Uuid::new_v4()));
+        let config = SessionManagerConfig {
+            storage_backend: Arc::new(StorageBackend::File(
+                FileStorage::new(Some(dir.clone()), FileStorageKey::Raw([7; 32])).unwrap(),
+            )),
+            enable_cross_tab_sync: false,
+            ..Default::default()
+        };
+        let user = user();
+
+        let manager = SessionManager::new(config.clone());
+        let refreshable = manager
+            .store_session(session(&user, "refresh", -60))
+            .await
+            .unwrap();
+        manager
+            .store_session(session(&user, "", -60))
+            .await
+            .unwrap();
+
+        let restarted = SessionManager::new(config);
+        restarted.initialize().await.unwrap();
+        let sessions = restarted.list_sessions().await.unwrap();
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0].metadata.session_id, refreshable);
+        assert!(dir
+            .join(format!("supabase_session_{}.session", refreshable))
+            .exists());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(all(target_arch = "wasm32", feature = "wasm"))]
+    mod browser {
+        use super::*;
+        use storage::BrowserStorage;
+        use wasm_bindgen_test::{wasm_bindgen_test, wasm_bindgen_test_configure};
+
+        wasm_bindgen_test_configure!(run_in_browser);
+
+        fn local_item(key: &str) -> Option<String> {
+            web_sys::window()
+                .and_then(|window| window.local_storage().ok().flatten())
+                .and_then(|storage| storage.get_item(key).ok().flatten())
+        }
+
+        #[wasm_bindgen_test]
+        async fn test_browser_storage_moves_large_sessions_to_indexed_db() {
+            let prefix = format!("test_{}_", Uuid::new_v4());
+            let open = || {
+                let storage = BrowserStorage::new(Some(prefix.clone()))
+                    .unwrap()
+                    .with_inline_limit(2048);
+                SessionManager::new(SessionManagerConfig {
+                    storage_backend: Arc::new(StorageBackend::Browser(storage)),
+                    enable_cross_tab_sync: false,
+                    ..Default::default()
+                })
+            };
+            let manager = open();
+            let mut large_user = user();
+            large_user.user_metadata = serde_json::json!({ "bio": "x".repeat(4096) });
+
+            let small_id = manager
+                .store_session(session(&user(), "refresh-small", 3600))
+                .await
+                .unwrap();
+            let large_id = manager
+                .store_session(session(&large_user, "refresh-large", 3600))
+                .await
+                .unwrap();
+            let key = |id: Uuid| format!("{}supabase_session_{}", prefix, id);
+
+            // Small sessions stay in localStorage, large ones leave a marker there
+            assert!(local_item(&key(small_id))
+                .unwrap()
+                .contains("refresh-small"));
+            assert_eq!(
+                local_item(&key(large_id)).as_deref(),
+                Some(r#"{"stored_in":"indexeddb"}"#)
+            );
+
+            // A reloaded page reads both back
+            let reloaded = open();
+            reloaded.initialize().await.unwrap();
+            let mut refresh_tokens: Vec<String> = reloaded
+                .list_sessions()
+                .await
+                .unwrap()
+                .into_iter()
+                .map(|data| data.session.refresh_token)
+                .collect();
+            refresh_tokens.sort();
+            assert_eq!(refresh_tokens, vec!["refresh-large", "refresh-small"]);
+
+            // A session that shrinks moves back to localStorage
+            reloaded
+                .update_session(large_id, session(&user(), "refresh-shrunk", 3600))
+                .await
+                .unwrap();
+            assert!(local_item(&key(large_id))
+                .unwrap()
+                .contains("refresh-shrunk"));
+
+            reloaded
+                .remove_session(large_id, "signed out".to_string())
+                .await
+                .unwrap();
+            assert_eq!(local_item(&key(large_id)), None);
+            let storage = BrowserStorage::new(Some(prefix.clone())).unwrap();
+            assert!(storage
+                .get_session(&format!("supabase_session_{}", large_id))
+                .await
+                .unwrap()
+                .is_none());
+
+            storage.clear_all_sessions().await.unwrap();
+            assert_eq!(local_item(&key(small_id)), None);
+        }
+
+        #[wasm_bindgen_test]
+        async fn test_browser_storage_drops_unrefreshable_sessions() {
+            let prefix = format!("test_{}_", Uuid::new_v4());
+            let storage = BrowserStorage::new(Some(prefix.clone()))
+                .unwrap()
+                .with_inline_limit(0);
+            let manager = SessionManager::new(SessionManagerConfig {
+                storage_backend: Arc::new(StorageBackend::Browser(storage)),
+                enable_cross_tab_sync: false,
+                ..Default::default()
+            });
+            let session_id = manager
+                .store_session(session(&user(), "", -60))
+                .await
+                .unwrap();
+
+            let storage = BrowserStorage::new(Some(prefix.clone())).unwrap();
+            let key = format!("supabase_session_{}", session_id);
+            assert!(storage.get_session(&key).await.unwrap().is_none());
+            assert_eq!(local_item(&format!("{}{}", prefix, key)), None);
         }
     }
 }
@@ -12,6 +12,8 @@ type MessageCallback = Box<dyn Fn(CrossTabMessage) + Send + Sync>;
 #[cfg(all(feature = "session-management", not(target_arch = "wasm32")))]
 use crate::error::{Error, Result};
 #[cfg(all(feature = "session-management", not(target_arch = "wasm32")))]
+use crate::session::storage::{create_private_dir, write_private};
+#[cfg(all(feature = "session-management", not(target_arch = "wasm32")))]
 use crate::session::{CrossTabChannel, CrossTabMessage};
 #[cfg(all(feature = "session-management", not(target_arch = "wasm32")))]
 use std::path::PathBuf;
@@ -43,7 +45,7 @@ impl NativeCrossTabChannel {
             .join("session_channel");
 
         // Create directory if it doesn't exist
-        std::fs::create_dir_all(&channel_dir)
+        create_private_dir(&channel_dir)
             .map_err(|e| Error::platform(format!("Failed to create channel directory: {}", e)))?;
 
         let process_id = format!(
@@ -70,7 +72,7 @@ impl NativeCrossTabChannel {
 
     /// Create a channel with custom directory
     pub fn new_with_dir(channel_dir: PathBuf) -> Result<Self> {
-        std::fs::create_dir_all(&channel_dir)
+        create_private_dir(&channel_dir)
             .map_err(|e| Error::platform(format!("Failed to create channel directory: {}", e)))?;
 
         let process_id = format!(
@@ -165,11 +167,11 @@ impl CrossTabChannel for NativeCrossTabChannel {
             Error::platform(format!("Failed to serialize cross-tab message: {}", e))
         })?;
 
-        tokio::fs::write(&file_path, serialized)
+        // Written under a temporary name, so readers never see a partial message
+        tokio::task::spawn_blocking(move || write_private(&file_path, serialized.as_bytes()))
             .await
-            .map_err(|e| Error::platform(format!("Failed to write message file: {}", e)))?;
-
-        Ok(())
+            .map_err(|e| Error::platform(format!("Failed to write message file: {}", e)))?
+            .map_err(|e| Error::platform(format!("Failed to write message file: {}", e)))
     }
 
     fn on_message(&self, callback: Box<dyn Fn(CrossTabMessage) + Send + Sync>) {
@@ -3,8 +3,10 @@
 //! This module provides different storage backends for session persistence:
 //! - MemoryStorage: In-memory storage for testing and temporary sessions
 //! - LocalStorage: Browser localStorage backend for WASM
+//! - BrowserStorage: localStorage with IndexedDB for large sessions, for WASM
 //! - FileSystemStorage: Filesystem backend for native applications
 //! - EncryptedStorage: Wrapper for encrypted storage
+//! - FileStorage: Encrypted filesystem backend for CLI and desktop applications
 
 // Type alias for complex storage type
 #[cfg(feature = "session-management")]
@@ -221,9 +223,8 @@ impl SessionStorage for LocalStorage {
                 let session_data: SessionData = serde_json::from_str(&serialized)
                     .map_err(|e| Error::storage(format!("Failed to deserialize session: {}", e)))?;
 
-                // Check if session is expired
-                if session_data.session.expires_at <= Utc::now() {
-                    // Remove expired session
+                // Drop sessions that can no longer be refreshed
+                if !session_data.is_usable() {
                     let _ = self.remove_session(key).await;
                     Ok(None)
                 } else {
@@ -280,7 +281,299 @@ impl SessionStorage for LocalStorage {
     }
 }
 
+/// localStorage value standing in for a session kept in IndexedDB
+#[cfg(all(
+    feature = "session-management",
+    target_arch = "wasm32",
+    feature = "wasm"
+))]
+const INDEXED_DB_MARKER: &str = r#"{"stored_in":"indexeddb"}"#;
+
+/// IndexedDB object store holding large sessions
+#[cfg(all(
+    feature = "session-management",
+    target_arch = "wasm32",
+    feature = "wasm"
+))]
+const INDEXED_DB_STORE: &str = "sessions";
+
+/// Session values in an IndexedDB database, keyed like the localStorage entries
+#[cfg(all(
+    feature = "session-management",
+    target_arch = "wasm32",
+    feature = "wasm"
+))]
+#[derive(Debug)]
+struct IndexedDb {
+    name: String,
+}
+
+#[cfg(all(
+    feature = "session-management",
+    target_arch = "wasm32",
+    feature = "wasm"
+))]
+impl IndexedDb {
+    fn is_available() -> bool {
+        web_sys::window()
+            .and_then(|w| w.indexed_db().ok().flatten())
+            .is_some()
+    }
+
+    async fn open(&self) -> Result<web_sys::IdbDatabase> {
+        use wasm_bindgen::{closure::Closure, JsCast};
+
+        let factory = web_sys::window()
+            .ok_or_else(|| Error::storage("No window object available"))?
+            .indexed_db()
+            .map_err(|_| Error::storage("Failed to access IndexedDB"))?
+            .ok_or_else(|| Error::storage("IndexedDB is not available"))?;
+        let request = factory
+            .open_with_u32(&self.name, 1)
+            .map_err(|_| Error::storage("Failed to open IndexedDB"))?;
+
+        let upgrading = request.clone();
+        let on_upgrade = Closure::<dyn FnMut(web_sys::Event)>::new(move |_| {
+            let Some(db) = upgrading
+                .result()
+                .ok()
+                .and_then(|db| db.dyn_into::<web_sys::IdbDatabase>().ok())
+            else {
+                return;
+            };
+            if !db.object_store_names().contains(INDEXED_DB_STORE) {
+                let _ = db.create_object_store(INDEXED_DB_STORE);
+            }
+        });
+        request.set_onupgradeneeded(Some(on_upgrade.as_ref().unchecked_ref()));
+
+        let db = request_result(&request).await?;
+        db.dyn_into()
+            .map_err(|_| Error::storage("IndexedDB returned no database"))
+    }
+
+    async fn store(&self, mode: web_sys::IdbTransactionMode) -> Result<web_sys::IdbObjectStore> {
+        self.open()
+            .await?
+            .transaction_with_str_and_mode(INDEXED_DB_STORE, mode)
+            .and_then(|transaction| transaction.object_store(INDEXED_DB_STORE))
+            .map_err(|_| Error::storage("Failed to start IndexedDB transaction"))
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<String>> {
+        let store = self.store(web_sys::IdbTransactionMode::Readonly).await?;
+        let request = store
+            .get(&key.into())
+            .map_err(|_| Error::storage("Failed to read session from IndexedDB"))?;
+        Ok(request_result(&request).await?.as_string())
+    }
+
+    async fn put(&self, key: &str, value: &str) -> Result<()> {
+        let store = self.store(web_sys::IdbTransactionMode::Readwrite).await?;
+        let request = store
+            .put_with_key(&value.into(), &key.into())
+            .map_err(|_| Error::storage("Failed to store session in IndexedDB"))?;
+        request_result(&request).await.map(|_| ())
+    }
+
+    async fn delete(&self, key: &str) -> Result<()> {
+        let store = self.store(web_sys::IdbTransactionMode::Readwrite).await?;
+        let request = store
+            .delete(&key.into())
+            .map_err(|_| Error::storage("Failed to remove session from IndexedDB"))?;
+        request_result(&request).await.map(|_| ())
+    }
+
+    async fn clear(&self) -> Result<()> {
+        let store = self.store(web_sys::IdbTransactionMode::Readwrite).await?;
+        let request = store
+            .clear()
+            .map_err(|_| Error::storage("Failed to clear sessions from IndexedDB"))?;
+        request_result(&request).await.map(|_| ())
+    }
+}
+
+/// Wait for an IndexedDB request and return its result
+#[cfg(all(
+    feature = "session-management",
+    target_arch = "wasm32",
+    feature = "wasm"
+))]
+async fn request_result(request: &web_sys::IdbRequest) -> Result<wasm_bindgen::JsValue> {
+    let promise = js_sys::Promise::new(&mut |resolve, reject| {
+        request.set_onsuccess(Some(&resolve));
+        request.set_onerror(Some(&reject));
+    });
+    wasm_bindgen_futures::JsFuture::from(promise)
+        .await
+        .map_err(|_| Error::storage("IndexedDB request failed"))?;
+    request
+        .result()
+        .map_err(|_| Error::storage("IndexedDB request failed"))
+}
+
+/// Browser storage for WASM: localStorage for small sessions, IndexedDB for large ones
+///
+/// Sessions up to the inline limit are kept in localStorage. Larger ones,
+/// e.g. with big user metadata, go to IndexedDB and leave a marker in
+/// localStorage, so every change still fires the `storage` events other tabs
+/// listen to. Without IndexedDB all sessions are kept in localStorage.
+#[cfg(all(
+    feature = "session-management",
+    target_arch = "wasm32",
+    feature = "wasm"
+))]
+#[derive(Debug)]
+pub struct BrowserStorage {
+    local: LocalStorage,
+    indexed_db: Option<IndexedDb>,
+    inline_limit: usize,
+}
+
+// WASM is single-threaded, so Send and Sync are safe to implement
+#[cfg(all(
+    feature = "session-management",
+    target_arch = "wasm32",
+    feature = "wasm"
+))]
+unsafe impl Send for BrowserStorage {}
+#[cfg(all(
+    feature = "session-management",
+    target_arch = "wasm32",
+    feature = "wasm"
+))]
+unsafe impl Sync for BrowserStorage {}
+
+#[cfg(all(
+    feature = "session-management",
+    target_arch = "wasm32",
+    feature = "wasm"
+))]
+impl BrowserStorage {
+    /// Size of the serialized session, in bytes, above which it moves to IndexedDB
+    pub const DEFAULT_INLINE_LIMIT: usize = 16 * 1024;
+
+    pub fn new(key_prefix: Option<String>) -> Result<Self> {
+        let local = LocalStorage::new(key_prefix)?;
+        let indexed_db = IndexedDb::is_available().then(|| IndexedDb {
+            name: format!("{}sessions", local.key_prefix),
+        });
+
+        Ok(Self {
+            local,
+            indexed_db,
+            inline_limit: Self::DEFAULT_INLINE_LIMIT,
+        })
+    }
+
+    /// Keep sessions larger than `bytes` in IndexedDB
+    pub fn with_inline_limit(mut self, bytes: usize) -> Self {
+        self.inline_limit = bytes;
+        self
+    }
+}
+
+#[cfg(all(
+    feature = "session-management",
+    target_arch = "wasm32",
+    feature = "wasm"
+))]
+#[async_trait::async_trait(?Send)]
+impl SessionStorage for BrowserStorage {
+    async fn store_session(
+        &self,
+        key: &str,
+        session: &SessionData,
+        _expires_at: Option<DateTime<Utc>>,
+    ) -> Result<()> {
+        let storage = LocalStorage::get_storage()?;
+        let storage_key = self.local.make_key(key);
+        let serialized = serde_json::to_string(session)
+            .map_err(|e| Error::storage(format!("Failed to serialize session: {}", e)))?;
+        let was_in_indexed_db =
+            storage.get_item(&storage_key).ok().flatten().as_deref() == Some(INDEXED_DB_MARKER);
+
+        match &self.indexed_db {
+            Some(indexed_db) if serialized.len() > self.inline_limit => {
+                // Write the data first so other tabs reacting to the marker find it
+                indexed_db.put(key, &serialized).await?;
+                storage
+                    .set_item(&storage_key, INDEXED_DB_MARKER)
+                    .map_err(|_| Error::storage("Failed to store session in localStorage"))?;
+            }
+            indexed_db => {
+                storage
+                    .set_item(&storage_key, &serialized)
+                    .map_err(|_| Error::storage("Failed to store session in localStorage"))?;
+                if let (Some(indexed_db), true) = (indexed_db, was_in_indexed_db) {
+                    indexed_db.delete(key).await?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn get_session(&self, key: &str) -> Result<Option<SessionData>> {
+        let storage = LocalStorage::get_storage()?;
+        let serialized = match storage.get_item(&self.local.make_key(key)) {
+            Ok(Some(serialized)) if serialized == INDEXED_DB_MARKER => match &self.indexed_db {
+                Some(indexed_db) => indexed_db.get(key).await?,
+                None => None,
+            },
+            Ok(serialized) => serialized,
+            Err(_) => return Err(Error::storage("Failed to read from localStorage")),
+        };
+        let Some(serialized) = serialized else {
+            return Ok(None);
+        };
+
+        let session_data: SessionData = serde_json::from_str(&serialized)
+            .map_err(|e| Error::storage(format!("Failed to deserialize session: {}", e)))?;
+        if !session_data.is_usable() {
+            let _ = self.remove_session(key).await;
+            Ok(None)
+        } else {
+            Ok(Some(session_data))
+        }
+    }
+
+    async fn remove_session(&self, key: &str) -> Result<()> {
+        let storage = LocalStorage::get_storage()?;
+        let storage_key = self.local.make_key(key);
+        let was_in_indexed_db =
+            storage.get_item(&storage_key).ok().flatten().as_deref() == Some(INDEXED_DB_MARKER);
+
+        storage
+            .remove_item(&storage_key)
+            .map_err(|_| Error::storage("Failed to remove session from localStorage"))?;
+        if let (Some(indexed_db), true) = (&self.indexed_db, was_in_indexed_db) {
+            indexed_db.delete(key).await?;
+        }
+        Ok(())
+    }
+
+    async fn clear_all_sessions(&self) -> Result<()> {
+        self.local.clear_all_sessions().await?;
+        if let Some(indexed_db) = &self.indexed_db {
+            indexed_db.clear().await?;
+        }
+        Ok(())
+    }
+
+    async fn list_session_keys(&self) -> Result<Vec<String>> {
+        self.local.list_session_keys().await
+    }
+
+    fn is_available(&self) -> bool {
+        self.local.is_available()
+    }
+}
+
 /// Filesystem-based storage for native applications
+///
+/// Sessions are written as plain JSON files readable by the owner only; use
+/// [`FileStorage`] (`session-encryption` feature) to encrypt them.
 #[cfg(all(feature = "session-management", not(target_arch = "wasm32")))]
 #[derive(Debug)]
 pub struct FileSystemStorage {
@@ -301,7 +594,7 @@ impl FileSystemStorage {
         };
 
         // Create directory if it doesn't exist
-        std::fs::create_dir_all(&base_dir)
+        create_private_dir(&base_dir)
             .map_err(|e| Error::storage(format!("Failed to create session directory: {}", e)))?;
 
         Ok(Self { base_dir })
@@ -322,14 +615,13 @@ impl SessionStorage for FileSystemStorage {
         _expires_at: Option<DateTime<Utc>>,
     ) -> Result<()> {
         let path = self.get_session_path(key);
-        let serialized = serde_json::to_string_pretty(session)
+        let serialized = serde_json::to_vec_pretty(session)
             .map_err(|e| Error::storage(format!("Failed to serialize session: {}", e)))?;
 
-        tokio::fs::write(&path, serialized)
+        tokio::task::spawn_blocking(move || write_private(&path, &serialized))
             .await
-            .map_err(|e| Error::storage(format!("Failed to write session file: {}", e)))?;
-
-        Ok(())
+            .map_err(|e| Error::storage(format!("Failed to write session file: {}", e)))?
+            .map_err(|e| Error::storage(format!("Failed to write session file: {}", e)))
     }
 
     async fn get_session(&self, key: &str) -> Result<Option<SessionData>> {
@@ -340,9 +632,8 @@ impl SessionStorage for FileSystemStorage {
                 let session_data: SessionData = serde_json::from_str(&serialized)
                     .map_err(|e| Error::storage(format!("Failed to deserialize session: {}", e)))?;
 
-                // Check if session is expired
-                if session_data.session.expires_at <= Utc::now() {
-                    // Remove expired session
+                // Drop sessions that can no longer be refreshed
+                if !session_data.is_usable() {
                     let _ = self.remove_session(key).await;
                     Ok(None)
                 } else {
@@ -490,6 +781,295 @@ impl SessionStorage for EncryptedStorage {
     }
 }
 
+/// Source of the key encrypting a [`FileStorage`]
+#[cfg(all(
+    feature = "session-management",
+    feature = "session-encryption",
+    not(target_arch = "wasm32")
+))]
+pub enum FileStorageKey {
+    /// Key derived from a passphrase with Argon2; the salt is kept in the storage directory
+    Passphrase(String),
+    /// Random key kept in the OS keyring, generated on first use
+    Keyring { service: String, username: String },
+    /// Key managed by the application
+    Raw([u8; 32]),
+}
+
+/// Encrypted filesystem storage for CLI and desktop applications
+///
+/// Every session is written to `{key}.session` as AES-256-GCM encrypted JSON,
+/// so tokens and user details never reach the disk in the clear. Files are
+/// replaced atomically and, on Unix, are only accessible to the owner.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use std::sync::Arc;
+/// use supabase_lib_rs::session::{
+///     storage::{FileStorage, FileStorageKey, StorageBackend},
+///     SessionManager, SessionManagerConfig,
+/// };
+/// # use supabase_lib_rs::Client;
+/// # fn example(client: &Client) -> supabase_lib_rs::Result<()> {
+///
+/// let storage = FileStorage::new(
+///     None,
+///     FileStorageKey::Keyring {
+///         service: "my-cli".to_string(),
+///         username: "sessions".to_string(),
+///     },
+/// )?;
+/// let manager = SessionManager::new(SessionManagerConfig {
+///     storage_backend: Arc::new(StorageBackend::File(storage)),
+///     enable_cross_tab_sync: false,
+///     ..Default::default()
+/// });
+/// client.auth().set_session_manager(Arc::new(manager));
+/// # Ok(())
+/// # }
+/// ```
+#[cfg(all(
+    feature = "session-management",
+    feature = "session-encryption",
+    not(target_arch = "wasm32")
+))]
+pub struct FileStorage {
+    base_dir: std::path::PathBuf,
+    encryptor: Arc<crate::session::encryption::SessionEncryptor>,
+}
+
+#[cfg(all(
+    feature = "session-management",
+    feature = "session-encryption",
+    not(target_arch = "wasm32")
+))]
+impl std::fmt::Debug for FileStorage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FileStorage")
+            .field("base_dir", &self.base_dir)
+            .field("encryptor", &"Arc<SessionEncryptor>")
+            .finish()
+    }
+}
+
+#[cfg(all(
+    feature = "session-management",
+    feature = "session-encryption",
+    not(target_arch = "wasm32")
+))]
+impl FileStorage {
+    /// Open the storage in `base_dir`, or [`default_dir`](Self::default_dir) when `None`
+    pub fn new(base_dir: Option<std::path::PathBuf>, key: FileStorageKey) -> Result<Self> {
+        use crate::session::encryption::{KeyManager, SessionEncryptor};
+
+        let base_dir = match base_dir {
+            Some(dir) => dir,
+            None => Self::default_dir()?,
+        };
+        create_private_dir(&base_dir)
+            .map_err(|e| Error::storage(format!("Failed to create session directory: {}", e)))?;
+
+        let encryptor = match key {
+            FileStorageKey::Passphrase(passphrase) => {
+                let salt_path = base_dir.join("salt");
+                let salt = match std::fs::read(&salt_path) {
+                    Ok(salt) => Some(salt),
+                    Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+                    Err(e) => {
+                        return Err(Error::storage(format!("Failed to read salt file: {}", e)))
+                    }
+                };
+                let (encryptor, new_salt) =
+                    SessionEncryptor::from_password(&passphrase, salt.as_deref())?;
+                if salt.is_none() {
+                    write_private(&salt_path, &new_salt)
+                        .map_err(|e| Error::storage(format!("Failed to write salt file: {}", e)))?;
+                }
+                encryptor
+            }
+            FileStorageKey::Keyring { service, username } => {
+                // Only a missing entry gets a new key: replacing a key that
+                // could not be read would make every stored session unreadable
+                let key = match KeyManager::find_key_securely(&service, &username)? {
+                    Some(key) => key,
+                    None => {
+                        let key = SessionEncryptor::generate_key();
+                        KeyManager::store_key_securely(&service, &username, &key)?;
+                        key
+                    }
+                };
+                SessionEncryptor::new(key)?
+            }
+            FileStorageKey::Raw(key) => SessionEncryptor::new(key)?,
+        };
+
+        Ok(Self {
+            base_dir,
+            encryptor: Arc::new(encryptor),
+        })
+    }
+
+    /// `supabase/sessions` under the user's config directory, e.g. `$XDG_CONFIG_HOME`
+    pub fn default_dir() -> Result<std::path::PathBuf> {
+        Ok(dirs::config_dir()
+            .ok_or_else(|| Error::storage("Could not determine config directory"))?
+            .join("supabase")
+            .join("sessions"))
+    }
+
+    /// Directory holding the session files
+    pub fn base_dir(&self) -> &std::path::Path {
+        &self.base_dir
+    }
+
+    fn get_session_path(&self, key: &str) -> std::path::PathBuf {
+        self.base_dir.join(format!("{}.session", key))
+    }
+
+    async fn session_paths(&self) -> Result<Vec<std::path::PathBuf>> {
+        let mut dir_entries = tokio::fs::read_dir(&self.base_dir)
+            .await
+            .map_err(|e| Error::storage(format!("Failed to read session directory: {}", e)))?;
+
+        let mut paths = Vec::new();
+        while let Some(entry) = dir_entries
+            .next_entry()
+            .await
+            .map_err(|e| Error::storage(format!("Failed to read directory entry: {}", e)))?
+        {
+            let path = entry.path();
+            if path.extension().and_then(|s| s.to_str()) == Some("session") {
+                paths.push(path);
+            }
+        }
+        Ok(paths)
+    }
+}
+
+/// Create `dir`, accessible to the owner only
+#[cfg(all(feature = "session-management", not(target_arch = "wasm32")))]
+pub(crate) fn create_private_dir(dir: &std::path::Path) -> std::io::Result<()> {
+    std::fs::create_dir_all(dir)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(dir, std::fs::Permissions::from_mode(0o700))?;
+    }
+    Ok(())
+}
+
+/// Write `contents` readable by the owner only, replacing `path` atomically
+#[cfg(all(feature = "session-management", not(target_arch = "wasm32")))]
+pub(crate) fn write_private(path: &std::path::Path, contents: &[u8]) -> std::io::Result<()> {
+    use std::io::Write;
+
+    // A unique name, so concurrent writers never share a temporary file
+    let temp_path = path.with_extension(format!("{}.tmp", uuid::Uuid::new_v4()));
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+
+    let result = options.open(&temp_path).and_then(|mut file| {
+        file.write_all(contents)?;
+        file.sync_all()?;
+        std::fs::rename(&temp_path, path)
+    });
+    if result.is_err() {
+        let _ = std::fs::remove_file(&temp_path);
+    }
+    result
+}
+
+#[cfg(all(
+    feature = "session-management",
+    feature = "session-encryption",
+    not(target_arch = "wasm32")
+))]
+#[async_trait::async_trait]
+impl SessionStorage for FileStorage {
+    async fn store_session(
+        &self,
+        key: &str,
+        session: &SessionData,
+        _expires_at: Option<DateTime<Utc>>,
+    ) -> Result<()> {
+        let encrypted = self.encryptor.encrypt(session)?;
+        let serialized = serde_json::to_vec(&encrypted)
+            .map_err(|e| Error::storage(format!("Failed to serialize session: {}", e)))?;
+
+        let path = self.get_session_path(key);
+        tokio::task::spawn_blocking(move || write_private(&path, &serialized))
+            .await
+            .map_err(|e| Error::storage(format!("Failed to write session file: {}", e)))?
+            .map_err(|e| Error::storage(format!("Failed to write session file: {}", e)))
+    }
+
+    async fn get_session(&self, key: &str) -> Result<Option<SessionData>> {
+        let path = self.get_session_path(key);
+
+        match tokio::fs::read(&path).await {
+            Ok(serialized) => {
+                let encrypted: crate::session::encryption::EncryptedSessionData =
+                    serde_json::from_slice(&serialized).map_err(|e| {
+                        Error::storage(format!("Failed to deserialize session: {}", e))
+                    })?;
+                let session_data = self.encryptor.decrypt(&encrypted)?;
+
+                // Keep expired sessions while they can still be refreshed
+                if !session_data.is_usable() {
+                    let _ = self.remove_session(key).await;
+                    Ok(None)
+                } else {
+                    Ok(Some(session_data))
+                }
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(Error::storage(format!(
+                "Failed to read session file: {}",
+                e
+            ))),
+        }
+    }
+
+    async fn remove_session(&self, key: &str) -> Result<()> {
+        let path = self.get_session_path(key);
+        match tokio::fs::remove_file(&path).await {
+            Ok(_) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(Error::storage(format!(
+                "Failed to remove session file: {}",
+                e
+            ))),
+        }
+    }
+
+    async fn clear_all_sessions(&self) -> Result<()> {
+        // The salt stays so the passphrase keeps deriving the same key
+        for path in self.session_paths().await? {
+            if let Err(e) = tokio::fs::remove_file(&path).await {
+                tracing::warn!("Failed to remove session file {:?}: {}", path, e);
+            }
+        }
+        Ok(())
+    }
+
+    async fn list_session_keys(&self) -> Result<Vec<String>> {
+        Ok(self
+            .session_paths()
+            .await?
+            .iter()
+            .filter_map(|path| path.file_stem().and_then(|s| s.to_str()))
+            .map(str::to_string)
+            .collect())
+    }
+
+    fn is_available(&self) -> bool {
+        self.base_dir.is_dir()
+    }
+}
+
 /// Enum-based storage backend for dyn compatibility
 #[cfg(feature = "session-management")]
 #[derive(Debug)]
@@ -497,10 +1077,14 @@ pub enum StorageBackend {
     Memory(MemoryStorage),
     #[cfg(all(target_arch = "wasm32", feature = "wasm"))]
     LocalStorage(LocalStorage),
+    #[cfg(all(target_arch = "wasm32", feature = "wasm"))]
+    Browser(BrowserStorage),
     #[cfg(not(target_arch = "wasm32"))]
     FileSystem(FileSystemStorage),
     #[cfg(feature = "session-encryption")]
     Encrypted(EncryptedStorage),
+    #[cfg(all(feature = "session-encryption", not(target_arch = "wasm32")))]
+    File(FileStorage),
 }
 
 #[cfg(feature = "session-management")]
@@ -520,6 +1104,10 @@ impl StorageBackend {
             StorageBackend::LocalStorage(storage) => {
                 storage.store_session(key, session, expires_at).await
             }
+            #[cfg(all(target_arch = "wasm32", feature = "wasm"))]
+            StorageBackend::Browser(storage) => {
+                storage.store_session(key, session, expires_at).await
+            }
             #[cfg(not(target_arch = "wasm32"))]
             StorageBackend::FileSystem(storage) => {
                 storage.store_session(key, session, expires_at).await
@@ -528,6 +1116,8 @@ impl StorageBackend {
             StorageBackend::Encrypted(storage) => {
                 storage.store_session(key, session, expires_at).await
             }
+            #[cfg(all(feature = "session-encryption", not(target_arch = "wasm32")))]
+            StorageBackend::File(storage) => storage.store_session(key, session, expires_at).await,
         }
     }
 
@@ -537,10 +1127,14 @@ impl StorageBackend {
             StorageBackend::Memory(storage) => storage.get_session(key).await,
             #[cfg(all(target_arch = "wasm32", feature = "wasm"))]
             StorageBackend::LocalStorage(storage) => storage.get_session(key).await,
+            #[cfg(all(target_arch = "wasm32", feature = "wasm"))]
+            StorageBackend::Browser(storage) => storage.get_session(key).await,
             #[cfg(not(target_arch = "wasm32"))]
             StorageBackend::FileSystem(storage) => storage.get_session(key).await,
             #[cfg(feature = "session-encryption")]
             StorageBackend::Encrypted(storage) => storage.get_session(key).await,
+            #[cfg(all(feature = "session-encryption", not(target_arch = "wasm32")))]
+            StorageBackend::File(storage) => storage.get_session(key).await,
         }
     }
 
@@ -550,10 +1144,14 @@ impl StorageBackend {
             StorageBackend::Memory(storage) => storage.remove_session(key).await,
             #[cfg(all(target_arch = "wasm32", feature = "wasm"))]
             StorageBackend::LocalStorage(storage) => storage.remove_session(key).await,
+            #[cfg(all(target_arch = "wasm32", feature = "wasm"))]
+            StorageBackend::Browser(storage) => storage.remove_session(key).await,
             #[cfg(not(target_arch = "wasm32"))]
             StorageBackend::FileSystem(storage) => storage.remove_session(key).await,
             #[cfg(feature = "session-encryption")]
             StorageBackend::Encrypted(storage) => storage.remove_session(key).await,
+            #[cfg(all(feature = "session-encryption", not(target_arch = "wasm32")))]
+            StorageBackend::File(storage) => storage.remove_session(key).await,
         }
     }
 
@@ -563,10 +1161,14 @@ impl StorageBackend {
             StorageBackend::Memory(storage) => storage.clear_all_sessions().await,
             #[cfg(all(target_arch = "wasm32", feature = "wasm"))]
             StorageBackend::LocalStorage(storage) => storage.clear_all_sessions().await,
+            #[cfg(all(target_arch = "wasm32", feature = "wasm"))]
+            StorageBackend::Browser(storage) => storage.clear_all_sessions().await,
             #[cfg(not(target_arch = "wasm32"))]
             StorageBackend::FileSystem(storage) => storage.clear_all_sessions().await,
             #[cfg(feature = "session-encryption")]
             StorageBackend::Encrypted(storage) => storage.clear_all_sessions().await,
+            #[cfg(all(feature = "session-encryption", not(target_arch = "wasm32")))]
+            StorageBackend::File(storage) => storage.clear_all_sessions().await,
         }
     }
 
@@ -576,10 +1178,14 @@ impl StorageBackend {
             StorageBackend::Memory(storage) => storage.list_session_keys().await,
             #[cfg(all(target_arch = "wasm32", feature = "wasm"))]
             StorageBackend::LocalStorage(storage) => storage.list_session_keys().await,
+            #[cfg(all(target_arch = "wasm32", feature = "wasm"))]
+            StorageBackend::Browser(storage) => storage.list_session_keys().await,
             #[cfg(not(target_arch = "wasm32"))]
             StorageBackend::FileSystem(storage) => storage.list_session_keys().await,
             #[cfg(feature = "session-encryption")]
             StorageBackend::Encrypted(storage) => storage.list_session_keys().await,
+            #[cfg(all(feature = "session-encryption", not(target_arch = "wasm32")))]
+            StorageBackend::File(storage) => storage.list_session_keys().await,
         }
     }
 
@@ -589,10 +1195,14 @@ impl StorageBackend {
             StorageBackend::Memory(storage) => storage.is_available(),
             #[cfg(all(target_arch = "wasm32", feature = "wasm"))]
             StorageBackend::LocalStorage(storage) => storage.is_available(),
+            #[cfg(all(target_arch = "wasm32", feature = "wasm"))]
+            StorageBackend::Browser(storage) => storage.is_available(),
             #[cfg(not(target_arch = "wasm32"))]
             StorageBackend::FileSystem(storage) => storage.is_available(),
             #[cfg(feature = "session-encryption")]
             StorageBackend::Encrypted(storage) => storage.is_available(),
+            #[cfg(all(feature = "session-encryption", not(target_arch = "wasm32")))]
+            StorageBackend::File(storage) => storage.is_available(),
         }
     }
 }
@@ -602,8 +1212,8 @@ impl StorageBackend {
 pub fn create_default_storage() -> Result<Arc<StorageBackend>> {
     #[cfg(all(target_arch = "wasm32", feature = "wasm"))]
     {
-        if let Ok(storage) = LocalStorage::new(None) {
-            Ok(Arc::new(StorageBackend::LocalStorage(storage)))
+        if let Ok(storage) = BrowserStorage::new(None) {
+            Ok(Arc::new(StorageBackend::Browser(storage)))
         } else {
             // Fallback to memory storage
             Ok(Arc::new(StorageBackend::Memory(MemoryStorage::new())))
@@ -626,3 +1236,126 @@ pub fn create_default_storage() -> Result<Arc<StorageBackend>> {
         }
     }
 }
+
+/// Storage for the sessions of one project
+///
+/// Like [`create_default_storage`], but the sessions of each project URL and
+/// `storage_key` are kept apart, so clients of different projects never
+/// restore each other's sessions: in their own directory natively and under
+/// their own key prefix in the browser. With the `session-encryption` feature
+/// native sessions are encrypted with a key kept in the OS keyring; when no
+/// keyring is available they are only kept in memory.
+#[cfg(feature = "session-management")]
+pub fn create_project_storage(project_url: &str, storage_key: &str) -> Result<Arc<StorageBackend>> {
+    let scope = storage_scope(project_url, storage_key);
+
+    #[cfg(all(target_arch = "wasm32", feature = "wasm"))]
+    if let Ok(storage) = BrowserStorage::new(Some(format!("supabase.{}.", scope))) {
+        return Ok(Arc::new(StorageBackend::Browser(storage)));
+    }
+
+    // Without the `wasm` feature only memory storage is available
+    #[cfg(all(target_arch = "wasm32", not(feature = "wasm")))]
+    let _ = scope;
+
+    #[cfg(all(not(target_arch = "wasm32"), feature = "session-encryption"))]
+    {
+        let storage = FileStorage::default_dir().and_then(|dir| {
+            FileStorage::new(
+                Some(dir.join(&scope)),
+                FileStorageKey::Keyring {
+                    service: "supabase-lib-rs".to_string(),
+                    username: scope.clone(),
+                },
+            )
+        });
+        match storage {
+            Ok(storage) => return Ok(Arc::new(StorageBackend::File(storage))),
+            Err(err) => tracing::warn!("Encrypted session storage unavailable: {}", err),
+        }
+    }
+
+    #[cfg(all(not(target_arch = "wasm32"), not(feature = "session-encryption")))]
+    {
+        let storage = dirs::data_local_dir()
+            .ok_or_else(|| Error::storage("Could not determine data directory"))
+            .and_then(|dir| {
+                FileSystemStorage::new(Some(dir.join("supabase-sessions").join(&scope)))
+            });
+        if let Ok(storage) = storage {
+            return Ok(Arc::new(StorageBackend::FileSystem(storage)));
+        }
+    }
+
+    // Fallback to memory storage
+    Ok(Arc::new(StorageBackend::Memory(MemoryStorage::new())))
+}
+
+/// File name and key prefix safe name for a project URL and storage key
+#[cfg(feature = "session-management")]
+fn storage_scope(project_url: &str, storage_key: &str) -> String {
+    let project = project_url
+        .trim_start_matches("https://")
+        .trim_start_matches("http://")
+        .trim_end_matches('/');
+    format!("{}_{}", project, storage_key)
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '.' | '-') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+#[cfg(all(test, feature = "session-management"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_storage_scope_separates_projects() {
+        assert_eq!(
+            storage_scope("https://abc.supabase.co/", "supabase.auth.token"),
+            "abc.supabase.co_supabase.auth.token"
+        );
+        assert_eq!(
+            storage_scope("http://127.0.0.1:54321", "../other"),
+            "127.0.0.1_54321_.._other"
+        );
+        assert_ne!(
+            storage_scope("https://abc.supabase.co", "supabase.auth.token"),
+            storage_scope("https://xyz.supabase.co", "supabase.auth.token")
+        );
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn test_concurrent_private_writes() {
+        let dir = std::env::temp_dir().join(format!("supabase-private-{}", uuid::Uuid::new_v4()));
+        create_private_dir(&dir).unwrap();
+        let path = dir.join("session.json");
+
+        let writers: Vec<_> = (0..8)
+            .map(|i| {
+                let path = path.clone();
+                std::thread::spawn(move || {
+                    for _ in 0..20 {
+                        write_private(&path, format!("writer {}", i).as_bytes()).unwrap();
+                    }
+                })
+            })
+            .collect();
+        for writer in writers {
+            writer.join().unwrap();
+        }
+
+        // Every write replaced the file whole and left no temporary file behind
+        assert!(std::fs::read_to_string(&path)
+            .unwrap()
+            .starts_with("writer "));
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
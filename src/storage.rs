@@ -1,47 +1,133 @@
 //! Storage module for Supabase file operations
 
+#[cfg(all(
+    not(target_arch = "wasm32"),
+    feature = "native",
+    feature = "storage-resumable"
+))]
+use crate::async_runtime::async_sleep;
 use crate::{
-    error::{Error, Result},
-    types::{SupabaseConfig, Timestamp},
+    circuit::CircuitBreaker,
+    error::{Error, ErrorContext, HttpErrorContext, IntegrityError, Result, StorageError},
+    types::{RequestAuth, SupabaseConfig, Timestamp, TokenProvider},
 };
 use bytes::Bytes;
 
+use futures_util::future::{AbortHandle, AbortRegistration, Abortable};
 #[cfg(target_arch = "wasm32")]
 use reqwest::Client as HttpClient;
+use reqwest::Method;
 #[cfg(not(target_arch = "wasm32"))]
 use reqwest::{multipart, Client as HttpClient};
 use serde::{Deserialize, Serialize};
-use std::{collections::HashMap, sync::Arc};
+use std::{
+    collections::{BTreeMap, HashMap, VecDeque},
+    future::Future,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    task::{Context, Poll},
+};
 
-#[cfg(target_arch = "wasm32")]
-use tracing::{debug, info};
-#[cfg(not(target_arch = "wasm32"))]
 use tracing::{debug, info, warn};
 use url::Url;
 
-// Resumable uploads support
-#[cfg(target_arch = "wasm32")]
 use std::time::Duration;
-#[cfg(not(target_arch = "wasm32"))]
-use tokio::time::{sleep, Duration};
 
-// Helper for async sleep across platforms
-#[cfg(not(target_arch = "wasm32"))]
-async fn async_sleep(duration: Duration) {
-    sleep(duration).await;
+/// Most `name (n).ext` candidates tried by [`OverwritePolicy::VersionedCopy`]
+const MAX_VERSIONED_COPIES: u32 = 100;
+
+/// Append the `download` query parameter that makes Storage serve an attachment
+fn with_download_param(signed_url: &str, download: Option<&str>) -> String {
+    match download {
+        Some(name) => {
+            let separator = if signed_url.contains('?') { '&' } else { '?' };
+            format!(
+                "{}{}download={}",
+                signed_url,
+                separator,
+                urlencoding::encode(name)
+            )
+        }
+        None => signed_url.to_string(),
+    }
+}
+
+/// Build the error for a failed upload, detecting conflicts with an existing object
+///
+/// Older storage servers report conflicts as a 400 response with a `409`
+/// status code in the body.
+fn upload_error(status: reqwest::StatusCode, body: Option<String>, path: &str) -> Error {
+    let body_json = body
+        .as_deref()
+        .and_then(|body| serde_json::from_str::<serde_json::Value>(body).ok());
+    let is_conflict = status == reqwest::StatusCode::CONFLICT
+        || body_json.as_ref().is_some_and(|json| {
+            json["statusCode"] == "409" || json["statusCode"] == 409 || json["error"] == "Duplicate"
+        });
+
+    if is_conflict {
+        return Error::already_exists(format!("Object already exists: {}", path));
+    }
+
+    let message = body
+        .clone()
+        .unwrap_or_else(|| format!("Upload failed with status: {}", status));
+    response_error(status, body, message)
+}
+
+/// Build a storage error that keeps the response, see [`Error::storage_error`]
+fn response_error(status: reqwest::StatusCode, body: Option<String>, message: String) -> Error {
+    let context = ErrorContext {
+        http: Some(HttpErrorContext {
+            status_code: Some(status.as_u16()),
+            headers: None,
+            response_body: body,
+            url: None,
+            method: None,
+        }),
+        ..Default::default()
+    };
+    Error::storage_with_context(message, context)
 }
 
-#[cfg(all(target_arch = "wasm32", feature = "wasm"))]
-#[allow(dead_code)]
-async fn async_sleep(duration: Duration) {
-    use gloo_timers::future::sleep as gloo_sleep;
-    gloo_sleep(duration).await;
+/// Object name of content-addressed content: its SHA-256 digest in hex
+fn content_address(content: &[u8]) -> String {
+    Checksum::compute(ChecksumAlgorithm::Sha256, content).value
 }
 
-#[cfg(all(target_arch = "wasm32", not(feature = "wasm")))]
-#[allow(dead_code)]
-async fn async_sleep(_duration: Duration) {
-    // No-op for wasm32 without wasm feature (resumable uploads not fully supported)
+/// Path of the `copy`-th version of a file, e.g. `docs/report (2).pdf`
+fn versioned_path(path: &str, copy: u32) -> String {
+    let (dir, file_name) = match path.rfind('/') {
+        Some(index) => path.split_at(index + 1),
+        None => ("", path),
+    };
+
+    match file_name.rfind('.') {
+        Some(index) if index > 0 => {
+            let (stem, extension) = file_name.split_at(index);
+            format!("{}{} ({}){}", dir, stem, copy, extension)
+        }
+        _ => format!("{}{} ({})", dir, file_name, copy),
+    }
+}
+
+/// Page size used when walking folders recursively
+const LIST_PAGE_SIZE: usize = 1000;
+
+/// Read buffer size for [`Storage::upload_stream`]
+#[cfg(all(not(target_arch = "wasm32"), feature = "native"))]
+const UPLOAD_STREAM_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Join a folder prefix and an entry name into a bucket-relative path
+fn join_storage_path(prefix: &str, name: &str) -> String {
+    if prefix.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}/{}", prefix, name)
+    }
 }
 
 /// Storage client for file operations
@@ -49,6 +135,9 @@ async fn async_sleep(_duration: Duration) {
 pub struct Storage {
     http_client: Arc<HttpClient>,
     config: Arc<SupabaseConfig>,
+    transfers: TransferRegistry,
+    token_provider: RequestAuth,
+    circuit: CircuitBreaker,
 }
 
 /// Storage bucket information
@@ -75,6 +164,83 @@ pub struct FileObject {
     pub metadata: Option<HashMap<String, serde_json::Value>>,
 }
 
+impl FileObject {
+    /// Whether this entry is a folder placeholder rather than a stored object
+    ///
+    /// The list endpoint returns folders as entries without an id or metadata.
+    pub fn is_folder(&self) -> bool {
+        self.id.is_none() && self.metadata.is_none()
+    }
+
+    /// Object size in bytes from the metadata, if reported
+    pub fn size(&self) -> Option<u64> {
+        self.metadata.as_ref()?.get("size")?.as_u64()
+    }
+}
+
+/// Files found by a recursive listing, with aggregate statistics
+///
+/// File names are full paths relative to the bucket root.
+#[derive(Debug, Clone, Default)]
+pub struct StorageTree {
+    pub files: Vec<FileObject>,
+    pub file_count: usize,
+    pub folder_count: usize,
+    pub total_size: u64,
+}
+
+impl StorageTree {
+    fn add_file(&mut self, file: FileObject) {
+        self.file_count += 1;
+        self.total_size += file.size().unwrap_or(0);
+        self.files.push(file);
+    }
+}
+
+/// Object count and total size of a bucket, from [`Storage::bucket_stats`]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct BucketStats {
+    pub bucket_id: String,
+    pub object_count: usize,
+    /// Total size of all objects in bytes
+    pub total_size: u64,
+    /// Totals per folder prefix, if a breakdown was requested
+    ///
+    /// Objects directly in the bucket root are counted under `""`.
+    pub prefixes: BTreeMap<String, PrefixStats>,
+}
+
+/// Object count and total size under one folder prefix
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct PrefixStats {
+    pub object_count: usize,
+    pub total_size: u64,
+}
+
+impl BucketStats {
+    /// Aggregate a recursive listing, grouping by the first `depth` folders
+    fn from_tree(bucket_id: &str, tree: &StorageTree, depth: Option<usize>) -> Self {
+        let mut prefixes = BTreeMap::new();
+        if let Some(depth) = depth {
+            for file in &tree.files {
+                let folders: Vec<&str> = file.name.split('/').collect();
+                let folders = &folders[..folders.len() - 1];
+                let prefix = folders[..depth.min(folders.len())].join("/");
+                let stats: &mut PrefixStats = prefixes.entry(prefix).or_default();
+                stats.object_count += 1;
+                stats.total_size += file.size().unwrap_or(0);
+            }
+        }
+
+        Self {
+            bucket_id: bucket_id.to_string(),
+            object_count: tree.file_count,
+            total_size: tree.total_size,
+            prefixes,
+        }
+    }
+}
+
 /// Upload response
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UploadResponse {
@@ -90,6 +256,230 @@ pub struct FileOptions {
     pub cache_control: Option<String>,
     pub content_type: Option<String>,
     pub upsert: bool,
+    /// `Content-Disposition` for the object, e.g. `attachment; filename="report.pdf"`
+    pub content_disposition: Option<String>,
+    /// `Content-Language` for the object, e.g. `de-DE`
+    pub content_language: Option<String>,
+    /// `Content-Encoding` of the uploaded bytes, e.g. `gzip` for pre-compressed files
+    pub content_encoding: Option<String>,
+}
+
+impl FileOptions {
+    /// Set representation metadata: header name, multipart field name and value
+    fn representation_metadata(&self) -> Vec<(&'static str, &'static str, String)> {
+        [
+            (
+                "Content-Disposition",
+                "contentDisposition",
+                &self.content_disposition,
+            ),
+            (
+                "Content-Language",
+                "contentLanguage",
+                &self.content_language,
+            ),
+            (
+                "Content-Encoding",
+                "contentEncoding",
+                &self.content_encoding,
+            ),
+        ]
+        .into_iter()
+        .filter_map(|(header, field, value)| value.clone().map(|value| (header, field, value)))
+        .collect()
+    }
+}
+
+/// Options for [`Storage::create_signed_url_with_options`]
+#[derive(Debug, Clone, Default)]
+pub struct SignedUrlOptions {
+    /// Image transformation applied when the URL is served
+    pub transform: Option<TransformOptions>,
+    /// Serve the object as an attachment, overriding its `Content-Disposition`
+    ///
+    /// The value is the download file name; an empty string keeps the
+    /// object's own name.
+    pub download: Option<String>,
+}
+
+/// A signed URL that lets its holder upload one object without an API key
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignedUploadUrl {
+    /// Full URL to `PUT` the file to, including the token
+    pub signed_url: String,
+    /// Object path in the bucket
+    pub path: String,
+    /// Storage upload token carried by the URL
+    pub token: String,
+}
+
+/// Size and type limits for a browser-direct upload
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UploadConstraints {
+    /// Largest accepted object in bytes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_size: Option<u64>,
+    /// Accepted MIME types, `image/*` style wildcards allowed; empty accepts any type
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allowed_mime_types: Vec<String>,
+}
+
+impl UploadConstraints {
+    /// Check an uploaded object's size and content type against the limits
+    pub fn check(&self, size: u64, content_type: Option<&str>) -> Result<()> {
+        if let Some(max_size) = self.max_size.filter(|max_size| size > *max_size) {
+            return Err(Error::invalid_input(format!(
+                "Upload of {} bytes exceeds the limit of {} bytes",
+                size, max_size
+            )));
+        }
+
+        if self.allowed_mime_types.is_empty() {
+            return Ok(());
+        }
+        let mime = content_type
+            .and_then(|value| value.split(';').next())
+            .map(|value| value.trim().to_ascii_lowercase())
+            .unwrap_or_default();
+        let allowed = self.allowed_mime_types.iter().any(|pattern| {
+            let pattern = pattern.to_ascii_lowercase();
+            match pattern.strip_suffix("/*") {
+                Some(prefix) => mime
+                    .strip_prefix(prefix)
+                    .is_some_and(|rest| rest.starts_with('/')),
+                None => pattern == mime,
+            }
+        });
+        if !allowed {
+            return Err(Error::invalid_input(format!(
+                "Content type `{}` is not one of {}",
+                mime,
+                self.allowed_mime_types.join(", ")
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+/// Claims carried by an upload token, see [`Storage::create_upload_ticket`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UploadTokenClaims {
+    pub bucket_id: String,
+    pub path: String,
+    #[serde(flatten)]
+    pub constraints: UploadConstraints,
+    /// Unix timestamp in seconds after which the token is rejected
+    pub expires_at: i64,
+}
+
+impl UploadTokenClaims {
+    /// Sign the claims with a server-side secret
+    ///
+    /// The token is the base64url-encoded JSON claims and their HMAC-SHA256,
+    /// joined by a dot.
+    pub fn sign(&self, secret: &[u8]) -> Result<String> {
+        use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+        use hmac::Mac;
+
+        let payload = URL_SAFE_NO_PAD.encode(serde_json::to_vec(self)?);
+        let signature =
+            URL_SAFE_NO_PAD.encode(upload_token_mac(secret, &payload).finalize().into_bytes());
+        Ok(format!("{}.{}", payload, signature))
+    }
+
+    /// Verify a token's signature and expiry and return its claims
+    pub fn verify(token: &str, secret: &[u8]) -> Result<Self> {
+        use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+        use hmac::Mac;
+
+        let invalid = || Error::permission_denied("Invalid upload token");
+        let (payload, signature) = token.split_once('.').ok_or_else(invalid)?;
+        let signature = URL_SAFE_NO_PAD.decode(signature).map_err(|_| invalid())?;
+        upload_token_mac(secret, payload)
+            .verify_slice(&signature)
+            .map_err(|_| invalid())?;
+
+        let claims: Self = URL_SAFE_NO_PAD
+            .decode(payload)
+            .ok()
+            .and_then(|json| serde_json::from_slice(&json).ok())
+            .ok_or_else(invalid)?;
+        if claims.expires_at <= chrono::Utc::now().timestamp() {
+            return Err(Error::permission_denied("Upload token has expired"));
+        }
+
+        Ok(claims)
+    }
+}
+
+/// HMAC-SHA256 over an upload token payload
+fn upload_token_mac(secret: &[u8], payload: &str) -> hmac::Hmac<sha2::Sha256> {
+    use hmac::Mac;
+
+    let mut mac = hmac::Hmac::<sha2::Sha256>::new_from_slice(secret)
+        .expect("HMAC accepts keys of any length");
+    mac.update(payload.as_bytes());
+    mac
+}
+
+/// A signed upload URL for a browser, plus the token that confirms the upload
+#[derive(Debug, Clone)]
+pub struct UploadTicket {
+    /// Where the browser uploads the file
+    pub upload: SignedUploadUrl,
+    /// Token the browser sends back to [`Storage::confirm_upload`]
+    pub upload_token: String,
+    /// Claims signed into `upload_token`
+    pub claims: UploadTokenClaims,
+}
+
+impl StorageError {
+    /// Fill in the size limit and accepted types from the bucket settings
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use bytes::Bytes;
+    /// # use supabase_lib_rs::Client;
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let client = Client::new("http://localhost:54321", "test-key")?;
+    /// let storage = client.storage();
+    /// if let Err(err) = storage.upload("avatars", "me.png", Bytes::new(), None).await {
+    ///     if let Some(reason) = err.storage_error() {
+    ///         let bucket = storage.get_bucket("avatars").await?;
+    ///         println!("{}", reason.with_bucket(&bucket));
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_bucket(self, bucket: &Bucket) -> Self {
+        match self {
+            Self::PayloadTooLarge { limit } => Self::PayloadTooLarge {
+                limit: limit.or(bucket.file_size_limit),
+            },
+            Self::InvalidMimeType { mime_type, allowed } if allowed.is_empty() => {
+                Self::InvalidMimeType {
+                    mime_type,
+                    allowed: bucket.allowed_mime_types.clone().unwrap_or_default(),
+                }
+            }
+            other => other,
+        }
+    }
+}
+
+/// What to do when an upload targets a path that already has an object
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OverwritePolicy {
+    /// Reject the upload with an [`Error::AlreadyExists`] error
+    #[default]
+    Fail,
+    /// Replace the existing object (`x-upsert: true`)
+    Overwrite,
+    /// Upload under the first free `name (2).ext`-style path
+    VersionedCopy,
 }
 
 /// Transform options for image processing
@@ -166,7 +556,7 @@ impl Default for ResumableUploadConfig {
     }
 }
 
-/// Progress callback for resumable uploads
+/// Progress callback for resumable and streamed uploads, called with (uploaded, total)
 pub type UploadProgressCallback = Arc<dyn Fn(u64, u64) + Send + Sync>;
 
 /// Advanced metadata for files
@@ -218,165 +608,639 @@ pub struct StorageEventMessage {
 /// Callback for storage events
 pub type StorageEventCallback = Arc<dyn Fn(StorageEventMessage) + Send + Sync>;
 
-impl Storage {
-    /// Create a new Storage instance
-    pub fn new(config: Arc<SupabaseConfig>, http_client: Arc<HttpClient>) -> Result<Self> {
-        debug!("Initializing Storage module");
+/// Checksum algorithms for content integrity verification
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChecksumAlgorithm {
+    Md5,
+    Sha256,
+}
 
-        Ok(Self {
-            http_client,
-            config,
-        })
+impl ChecksumAlgorithm {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ChecksumAlgorithm::Md5 => "md5",
+            ChecksumAlgorithm::Sha256 => "sha256",
+        }
     }
+}
 
-    /// Get the appropriate authorization key for admin operations
-    fn get_admin_key(&self) -> &str {
-        self.config
-            .service_role_key
-            .as_ref()
-            .unwrap_or(&self.config.key)
-    }
+/// Content checksum as lowercase hex
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Checksum {
+    pub algorithm: ChecksumAlgorithm,
+    pub value: String,
+}
 
-    /// List all storage buckets
-    pub async fn list_buckets(&self) -> Result<Vec<Bucket>> {
-        debug!("Listing all storage buckets");
+impl Checksum {
+    /// Compute the checksum of `data`
+    pub fn compute(algorithm: ChecksumAlgorithm, data: &[u8]) -> Self {
+        use md5::Digest;
 
-        let url = format!("{}/storage/v1/bucket", self.config.url);
-        let response = self.http_client.get(&url).send().await?;
+        let value = match algorithm {
+            ChecksumAlgorithm::Md5 => format!("{:x}", md5::Md5::digest(data)),
+            ChecksumAlgorithm::Sha256 => format!("{:x}", sha2::Sha256::digest(data)),
+        };
 
-        if !response.status().is_success() {
-            let status = response.status();
-            let error_msg = match response.text().await {
-                Ok(text) => text,
-                Err(_) => format!("List buckets failed with status: {}", status),
-            };
-            return Err(Error::storage(error_msg));
+        Self { algorithm, value }
+    }
+
+    /// Create a checksum from a known hex value
+    pub fn new(algorithm: ChecksumAlgorithm, value: &str) -> Self {
+        Self {
+            algorithm,
+            value: value.to_ascii_lowercase(),
         }
+    }
 
-        let buckets: Vec<Bucket> = response.json().await?;
-        info!("Listed {} buckets successfully", buckets.len());
+    /// Check `data` against this checksum
+    pub fn verify(&self, data: &[u8], path: Option<&str>) -> Result<()> {
+        let actual = Self::compute(self.algorithm, data);
+        if actual.value == self.value {
+            return Ok(());
+        }
 
-        Ok(buckets)
+        Err(Error::integrity(IntegrityError {
+            algorithm: self.algorithm.as_str().to_string(),
+            expected: self.value.clone(),
+            actual: actual.value,
+            path: path.map(str::to_string),
+        }))
     }
+}
 
-    /// Get bucket information
-    pub async fn get_bucket(&self, bucket_id: &str) -> Result<Bucket> {
-        debug!("Getting bucket info for: {}", bucket_id);
+/// Direction of a tracked transfer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransferKind {
+    Upload,
+    Download,
+}
 
-        let url = format!("{}/storage/v1/bucket/{}", self.config.url, bucket_id);
-        let response = self.http_client.get(&url).send().await?;
+/// State of a tracked transfer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransferStatus {
+    InProgress,
+    Completed,
+    Failed,
+    Aborted,
+}
 
-        if !response.status().is_success() {
-            let status = response.status();
-            let error_msg = match response.text().await {
-                Ok(text) => text,
-                Err(_) => format!("Get bucket failed with status: {}", status),
-            };
-            return Err(Error::storage(error_msg));
-        }
+/// Boxed transfer future, `Send` on native targets
+#[cfg(not(target_arch = "wasm32"))]
+type TransferFuture<T> = Pin<Box<dyn Future<Output = Result<T>> + Send>>;
+#[cfg(target_arch = "wasm32")]
+type TransferFuture<T> = Pin<Box<dyn Future<Output = Result<T>>>>;
 
-        let bucket: Bucket = response.json().await?;
-        info!("Retrieved bucket info for: {}", bucket_id);
+/// `Send` on native targets, no bound on WASM
+#[cfg(not(target_arch = "wasm32"))]
+trait TransferSend: Send {}
+#[cfg(not(target_arch = "wasm32"))]
+impl<T: Send> TransferSend for T {}
+#[cfg(target_arch = "wasm32")]
+trait TransferSend {}
+#[cfg(target_arch = "wasm32")]
+impl<T> TransferSend for T {}
+
+struct TransferState {
+    id: String,
+    kind: TransferKind,
+    bucket_id: String,
+    path: String,
+    transferred: AtomicU64,
+    total: AtomicU64,
+    status: Mutex<TransferStatus>,
+    upload_id: Mutex<Option<String>>,
+    abort_handle: AbortHandle,
+}
 
-        Ok(bucket)
+/// Handle to an in-flight upload or download
+///
+/// Cheap to clone; hand it to UI code to show progress or cancel the transfer.
+#[derive(Clone)]
+pub struct TransferHandle {
+    state: Arc<TransferState>,
+}
+
+impl std::fmt::Debug for TransferHandle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TransferHandle")
+            .field("id", &self.state.id)
+            .field("kind", &self.state.kind)
+            .field("bucket_id", &self.state.bucket_id)
+            .field("path", &self.state.path)
+            .field("progress", &self.progress())
+            .field("status", &self.status())
+            .finish()
     }
+}
 
-    /// Create a new storage bucket
-    pub async fn create_bucket(&self, id: &str, name: &str, public: bool) -> Result<Bucket> {
-        debug!("Creating bucket: {} ({})", name, id);
+impl TransferHandle {
+    /// Unique transfer id
+    pub fn id(&self) -> &str {
+        &self.state.id
+    }
 
-        let payload = serde_json::json!({
-            "id": id,
-            "name": name,
-            "public": public
-        });
+    /// Upload or download
+    pub fn kind(&self) -> TransferKind {
+        self.state.kind
+    }
 
-        let url = format!("{}/storage/v1/bucket", self.config.url);
-        let response = self
-            .http_client
-            .post(&url)
-            .header("Authorization", format!("Bearer {}", self.get_admin_key()))
-            .json(&payload)
-            .send()
-            .await?;
+    /// Bucket of the object being transferred
+    pub fn bucket_id(&self) -> &str {
+        &self.state.bucket_id
+    }
 
-        if !response.status().is_success() {
-            let status = response.status();
-            let error_msg = match response.text().await {
-                Ok(text) => text,
-                Err(_) => format!("Create bucket failed with status: {}", status),
-            };
-            return Err(Error::storage(error_msg));
-        }
+    /// Path of the object being transferred
+    pub fn path(&self) -> &str {
+        &self.state.path
+    }
 
-        let bucket: Bucket = response.json().await?;
-        info!("Created bucket successfully: {}", id);
+    /// Bytes transferred so far and the total size, if known
+    pub fn progress(&self) -> (u64, Option<u64>) {
+        let total = self.state.total.load(Ordering::SeqCst);
+        (
+            self.state.transferred.load(Ordering::SeqCst),
+            (total > 0).then_some(total),
+        )
+    }
 
-        Ok(bucket)
+    /// Current state of the transfer
+    pub fn status(&self) -> TransferStatus {
+        *self
+            .state
+            .status
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
     }
 
-    /// Update bucket settings
-    pub async fn update_bucket(&self, id: &str, public: Option<bool>) -> Result<()> {
-        debug!("Updating bucket: {}", id);
+    /// Resumable upload session backing this transfer, if any
+    pub fn upload_id(&self) -> Option<String> {
+        self.state
+            .upload_id
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
+    }
 
-        let mut payload = serde_json::Map::new();
-        if let Some(public) = public {
-            payload.insert("public".to_string(), serde_json::Value::Bool(public));
+    /// Cancel the transfer
+    ///
+    /// The pending request is dropped and, for resumable uploads, the server
+    /// side upload session is cancelled. Has no effect on finished transfers.
+    pub fn abort(&self) {
+        if self.status() == TransferStatus::InProgress {
+            self.state.abort_handle.abort();
         }
+    }
 
-        let url = format!("{}/storage/v1/bucket/{}", self.config.url, id);
-        let response = self
-            .http_client
-            .put(&url)
-            .header("Authorization", format!("Bearer {}", self.get_admin_key()))
-            .json(&payload)
-            .send()
-            .await?;
-
-        if !response.status().is_success() {
-            let status = response.status();
-            let error_msg = match response.text().await {
-                Ok(text) => text,
-                Err(_) => format!("Update bucket failed with status: {}", status),
-            };
-            return Err(Error::storage(error_msg));
+    fn set_progress(&self, transferred: u64, total: Option<u64>) {
+        self.state.transferred.store(transferred, Ordering::SeqCst);
+        if let Some(total) = total {
+            self.state.total.store(total, Ordering::SeqCst);
         }
+    }
 
-        info!("Updated bucket successfully: {}", id);
-        Ok(())
+    #[cfg(all(
+        not(target_arch = "wasm32"),
+        feature = "native",
+        feature = "storage-resumable"
+    ))]
+    fn set_upload_id(&self, upload_id: &str) {
+        *self
+            .state
+            .upload_id
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(upload_id.to_string());
     }
 
-    /// Delete a storage bucket
-    pub async fn delete_bucket(&self, id: &str) -> Result<()> {
-        debug!("Deleting bucket: {}", id);
+    fn finish(&self, status: TransferStatus) {
+        *self
+            .state
+            .status
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = status;
+    }
+}
 
-        let url = format!("{}/storage/v1/bucket/{}", self.config.url, id);
-        let response = self
-            .http_client
-            .delete(&url)
-            .header("Authorization", format!("Bearer {}", self.get_admin_key()))
-            .send()
-            .await?;
+/// Registry of in-flight transfers started through the `*_abortable` methods
+#[derive(Debug, Clone, Default)]
+pub struct TransferRegistry {
+    active: Arc<Mutex<HashMap<String, TransferHandle>>>,
+}
 
-        if !response.status().is_success() {
-            let status = response.status();
-            let error_msg = match response.text().await {
-                Ok(text) => text,
-                Err(_) => format!("Delete bucket failed with status: {}", status),
-            };
-            return Err(Error::storage(error_msg));
+impl TransferRegistry {
+    /// All in-flight transfers
+    pub fn list(&self) -> Vec<TransferHandle> {
+        self.lock().values().cloned().collect()
+    }
+
+    /// Find an in-flight transfer by id
+    pub fn get(&self, id: &str) -> Option<TransferHandle> {
+        self.lock().get(id).cloned()
+    }
+
+    /// Abort every in-flight transfer
+    pub fn abort_all(&self) {
+        for handle in self.list() {
+            handle.abort();
         }
+    }
 
-        info!("Deleted bucket successfully: {}", id);
-        Ok(())
+    fn register(
+        &self,
+        kind: TransferKind,
+        bucket_id: &str,
+        path: &str,
+        total: Option<u64>,
+    ) -> (TransferHandle, AbortRegistration) {
+        let (abort_handle, registration) = AbortHandle::new_pair();
+        let handle = TransferHandle {
+            state: Arc::new(TransferState {
+                id: uuid::Uuid::new_v4().to_string(),
+                kind,
+                bucket_id: bucket_id.to_string(),
+                path: path.to_string(),
+                transferred: AtomicU64::new(0),
+                total: AtomicU64::new(total.unwrap_or(0)),
+                status: Mutex::new(TransferStatus::InProgress),
+                upload_id: Mutex::new(None),
+                abort_handle,
+            }),
+        };
+
+        self.lock().insert(handle.id().to_string(), handle.clone());
+        (handle, registration)
     }
 
-    /// List files in a bucket
-    pub async fn list(&self, bucket_id: &str, path: Option<&str>) -> Result<Vec<FileObject>> {
-        self.list_with_auth(bucket_id, path, None).await
+    fn remove(&self, id: &str) {
+        self.lock().remove(id);
     }
 
-    /// List files in a bucket with authentication token
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, TransferHandle>> {
+        self.active
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// Removes a transfer from its registry when dropped, so a [`Transfer`] that
+/// is dropped before it finishes does not stay listed as in progress
+struct TransferGuard {
+    registry: TransferRegistry,
+    handle: TransferHandle,
+}
+
+impl Drop for TransferGuard {
+    fn drop(&mut self) {
+        if self.handle.status() == TransferStatus::InProgress {
+            self.handle.finish(TransferStatus::Aborted);
+        }
+        self.registry.remove(self.handle.id());
+    }
+}
+
+/// An abortable transfer: await it for the result, or abort it via its handle
+///
+/// Resolves to a storage error if the transfer is aborted.
+pub struct Transfer<T> {
+    handle: TransferHandle,
+    future: TransferFuture<T>,
+}
+
+impl<T> Transfer<T> {
+    /// Handle for progress, state and cancellation
+    pub fn handle(&self) -> &TransferHandle {
+        &self.handle
+    }
+}
+
+impl<T> Future for Transfer<T> {
+    type Output = Result<T>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        self.future.as_mut().poll(cx)
+    }
+}
+
+/// Object body streamed from Storage, from [`Storage::download_stream`]
+///
+/// Yields the body in chunks as they arrive; [`bytes_received`](Self::bytes_received)
+/// and [`content_length`](Self::content_length) give the download progress.
+#[cfg(not(target_arch = "wasm32"))]
+pub struct DownloadStream {
+    inner: Pin<Box<dyn futures_util::Stream<Item = reqwest::Result<Bytes>> + Send>>,
+    content_length: Option<u64>,
+    received: u64,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl DownloadStream {
+    /// Size of the object, if the server sent it
+    pub fn content_length(&self) -> Option<u64> {
+        self.content_length
+    }
+
+    /// Bytes yielded so far
+    pub fn bytes_received(&self) -> u64 {
+        self.received
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl std::fmt::Debug for DownloadStream {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DownloadStream")
+            .field("content_length", &self.content_length)
+            .field("received", &self.received)
+            .finish()
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl futures_util::Stream for DownloadStream {
+    type Item = Result<Bytes>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        match self.inner.as_mut().poll_next(cx) {
+            Poll::Ready(Some(Ok(chunk))) => {
+                self.received += chunk.len() as u64;
+                Poll::Ready(Some(Ok(chunk)))
+            }
+            Poll::Ready(Some(Err(err))) => Poll::Ready(Some(Err(err.into()))),
+            Poll::Ready(None) => Poll::Ready(None),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+/// Policy describing which objects a cleanup job should remove
+#[derive(Debug, Clone)]
+pub struct CleanupPolicy {
+    /// Only remove objects last updated longer ago than this
+    pub older_than: Option<Duration>,
+    /// Only remove objects under this folder prefix
+    pub prefix: Option<String>,
+    /// Report matching objects without deleting them
+    pub dry_run: bool,
+    /// Number of objects listed and deleted per request (default: 100)
+    pub batch_size: usize,
+}
+
+impl Default for CleanupPolicy {
+    fn default() -> Self {
+        Self {
+            older_than: None,
+            prefix: None,
+            dry_run: false,
+            batch_size: 100,
+        }
+    }
+}
+
+/// Result of a cleanup job
+#[derive(Debug, Clone, Default)]
+pub struct CleanupReport {
+    /// Full paths of objects matched by the policy
+    pub matched: Vec<String>,
+    /// Number of objects actually deleted (always 0 in dry-run mode)
+    pub deleted: usize,
+    /// Whether the job ran in dry-run mode
+    pub dry_run: bool,
+}
+
+/// Progress callback for cleanup jobs, called with (processed, total)
+pub type CleanupProgressCallback = Arc<dyn Fn(usize, usize) + Send + Sync>;
+
+impl Storage {
+    /// Create a new Storage instance
+    pub fn new(config: Arc<SupabaseConfig>, http_client: Arc<HttpClient>) -> Result<Self> {
+        Ok(Self::with_http_client(config, http_client))
+    }
+
+    /// Create the Storage module of a [`Client`](crate::Client), which cannot fail
+    pub(crate) fn with_http_client(
+        config: Arc<SupabaseConfig>,
+        http_client: Arc<HttpClient>,
+    ) -> Self {
+        debug!("Initializing Storage module");
+
+        let circuit = CircuitBreaker::new("storage", config.http_config.circuit_breaker)
+            .with_retry(config.http_config.retry.clone())
+            .with_fingerprint(config.error_fingerprint);
+
+        Self {
+            http_client,
+            config,
+            transfers: TransferRegistry::default(),
+            token_provider: RequestAuth::default(),
+            circuit,
+        }
+    }
+
+    /// Circuit breaker guarding requests to the Storage API
+    pub fn circuit_breaker(&self) -> &CircuitBreaker {
+        &self.circuit
+    }
+
+    /// Authorize requests with the access token from `provider`
+    ///
+    /// [`Client`](crate::Client) sets this to its auth session, so bucket
+    /// policies see the signed-in user. Without a token the API key is sent.
+    /// Admin operations keep using the service role key.
+    pub fn with_token_provider(mut self, provider: Arc<dyn TokenProvider>) -> Self {
+        self.token_provider = RequestAuth::new(provider);
+        self
+    }
+
+    /// Storage endpoint of the project
+    fn storage_url(&self) -> String {
+        self.config.project_url().storage_url()
+    }
+
+    /// Start a request carrying the current access token
+    fn build_request(&self, method: Method, url: &str) -> reqwest::RequestBuilder {
+        self.build_request_as(method, url, None)
+    }
+
+    /// Start a request as `user_token`, falling back to the current access token
+    fn build_request_as(
+        &self,
+        method: Method,
+        url: &str,
+        user_token: Option<&str>,
+    ) -> reqwest::RequestBuilder {
+        let request = self.http_client.request(method, url);
+        match user_token {
+            Some(token) => request.bearer_auth(token),
+            None => self.token_provider.apply(request),
+        }
+    }
+
+    /// Get the registry of in-flight abortable transfers
+    ///
+    /// Shared by all clones of this storage client.
+    pub fn transfers(&self) -> &TransferRegistry {
+        &self.transfers
+    }
+
+    /// Get the appropriate authorization key for admin operations
+    fn get_admin_key(&self) -> &str {
+        self.config
+            .service_role_key
+            .as_ref()
+            .unwrap_or(&self.config.key)
+    }
+
+    /// List all storage buckets
+    pub async fn list_buckets(&self) -> Result<Vec<Bucket>> {
+        debug!("Listing all storage buckets");
+
+        let url = format!("{}/bucket", self.storage_url());
+        let response = self
+            .circuit
+            .send(self.build_request(Method::GET, &url))
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_msg = match response.text().await {
+                Ok(text) => text,
+                Err(_) => format!("List buckets failed with status: {}", status),
+            };
+            return Err(Error::storage(error_msg).with_fingerprint(self.config.error_fingerprint));
+        }
+
+        let buckets: Vec<Bucket> = response.json().await?;
+        info!("Listed {} buckets successfully", buckets.len());
+
+        Ok(buckets)
+    }
+
+    /// Get bucket information
+    pub async fn get_bucket(&self, bucket_id: &str) -> Result<Bucket> {
+        debug!("Getting bucket info for: {}", bucket_id);
+
+        let url = format!("{}/bucket/{}", self.storage_url(), bucket_id);
+        let response = self
+            .circuit
+            .send(self.build_request(Method::GET, &url))
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_msg = match response.text().await {
+                Ok(text) => text,
+                Err(_) => format!("Get bucket failed with status: {}", status),
+            };
+            return Err(Error::storage(error_msg).with_fingerprint(self.config.error_fingerprint));
+        }
+
+        let bucket: Bucket = response.json().await?;
+        info!("Retrieved bucket info for: {}", bucket_id);
+
+        Ok(bucket)
+    }
+
+    /// Create a new storage bucket
+    pub async fn create_bucket(&self, id: &str, name: &str, public: bool) -> Result<Bucket> {
+        debug!("Creating bucket: {} ({})", name, id);
+
+        let payload = serde_json::json!({
+            "id": id,
+            "name": name,
+            "public": public
+        });
+
+        let url = format!("{}/bucket", self.storage_url());
+        let response = self
+            .circuit
+            .send(
+                self.http_client
+                    .post(&url)
+                    .header("Authorization", format!("Bearer {}", self.get_admin_key()))
+                    .json(&payload),
+            )
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_msg = match response.text().await {
+                Ok(text) => text,
+                Err(_) => format!("Create bucket failed with status: {}", status),
+            };
+            return Err(Error::storage(error_msg).with_fingerprint(self.config.error_fingerprint));
+        }
+
+        let bucket: Bucket = response.json().await?;
+        info!("Created bucket successfully: {}", id);
+
+        Ok(bucket)
+    }
+
+    /// Update bucket settings
+    pub async fn update_bucket(&self, id: &str, public: Option<bool>) -> Result<()> {
+        debug!("Updating bucket: {}", id);
+
+        let mut payload = serde_json::Map::new();
+        if let Some(public) = public {
+            payload.insert("public".to_string(), serde_json::Value::Bool(public));
+        }
+
+        let url = format!("{}/bucket/{}", self.storage_url(), id);
+        let response = self
+            .circuit
+            .send(
+                self.http_client
+                    .put(&url)
+                    .header("Authorization", format!("Bearer {}", self.get_admin_key()))
+                    .json(&payload),
+            )
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_msg = match response.text().await {
+                Ok(text) => text,
+                Err(_) => format!("Update bucket failed with status: {}", status),
+            };
+            return Err(Error::storage(error_msg).with_fingerprint(self.config.error_fingerprint));
+        }
+
+        info!("Updated bucket successfully: {}", id);
+        Ok(())
+    }
+
+    /// Delete a storage bucket
+    pub async fn delete_bucket(&self, id: &str) -> Result<()> {
+        debug!("Deleting bucket: {}", id);
+
+        let url = format!("{}/bucket/{}", self.storage_url(), id);
+        let response = self
+            .circuit
+            .send(
+                self.http_client
+                    .delete(&url)
+                    .header("Authorization", format!("Bearer {}", self.get_admin_key())),
+            )
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_msg = match response.text().await {
+                Ok(text) => text,
+                Err(_) => format!("Delete bucket failed with status: {}", status),
+            };
+            return Err(Error::storage(error_msg).with_fingerprint(self.config.error_fingerprint));
+        }
+
+        info!("Deleted bucket successfully: {}", id);
+        Ok(())
+    }
+
+    /// List files in a bucket
+    pub async fn list(&self, bucket_id: &str, path: Option<&str>) -> Result<Vec<FileObject>> {
+        self.list_with_auth(bucket_id, path, None).await
+    }
+
+    /// List files in a bucket with authentication token
     ///
     /// This method allows passing a user authentication token for listing
     /// files in protected buckets with Row Level Security policies.
@@ -409,20 +1273,17 @@ impl Storage {
     ) -> Result<Vec<FileObject>> {
         debug!("Listing files in bucket: {}", bucket_id);
 
-        let url = format!("{}/storage/v1/object/list/{}", self.config.url, bucket_id);
+        let url = format!("{}/object/list/{}", self.storage_url(), bucket_id);
 
         let payload = serde_json::json!({
             "prefix": path.unwrap_or("")
         });
 
-        let mut request = self.http_client.post(&url).json(&payload);
-
-        // Override Authorization header with user token if provided
-        if let Some(token) = user_token {
-            request = request.header("Authorization", format!("Bearer {}", token));
-        }
+        let request = self
+            .build_request_as(Method::POST, &url, user_token)
+            .json(&payload);
 
-        let response = request.send().await?;
+        let response = self.circuit.send(request).await?;
 
         if !response.status().is_success() {
             let status = response.status();
@@ -430,7 +1291,7 @@ impl Storage {
                 Ok(text) => text,
                 Err(_) => format!("List files failed with status: {}", status),
             };
-            return Err(Error::storage(error_msg));
+            return Err(Error::storage(error_msg).with_fingerprint(self.config.error_fingerprint));
         }
 
         let files: Vec<FileObject> = response.json().await?;
@@ -439,6 +1300,288 @@ impl Storage {
         Ok(files)
     }
 
+    /// List all files under a prefix, descending into nested folders
+    ///
+    /// Returns a flattened list of files with full paths, plus the number of
+    /// files and folders found and the total size of the files.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// # use supabase_lib_rs::Client;
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let client = Client::new("http://localhost:54321", "test-key")?;
+    /// let tree = client.storage().list_recursive("documents", Some("reports")).await?;
+    ///
+    /// println!("{} files, {} bytes", tree.file_count, tree.total_size);
+    /// for file in &tree.files {
+    ///     println!("{}", file.name); // e.g. "reports/2024/q1.pdf"
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn list_recursive(
+        &self,
+        bucket_id: &str,
+        prefix: Option<&str>,
+    ) -> Result<StorageTree> {
+        debug!("Listing files recursively in bucket: {}", bucket_id);
+
+        let mut tree = StorageTree::default();
+        let mut pending = VecDeque::from([prefix.unwrap_or("").trim_matches('/').to_string()]);
+
+        while let Some(folder) = pending.pop_front() {
+            let mut offset = 0;
+            loop {
+                let entries = self
+                    .list_page(bucket_id, &folder, LIST_PAGE_SIZE, offset)
+                    .await?;
+                let page_len = entries.len();
+
+                for mut entry in entries {
+                    entry.name = join_storage_path(&folder, &entry.name);
+                    if entry.is_folder() {
+                        tree.folder_count += 1;
+                        pending.push_back(entry.name);
+                    } else {
+                        tree.add_file(entry);
+                    }
+                }
+
+                if page_len < LIST_PAGE_SIZE {
+                    break;
+                }
+                offset += LIST_PAGE_SIZE;
+            }
+        }
+
+        info!(
+            "Listed {} files in {} folders in bucket: {}",
+            tree.file_count, tree.folder_count, bucket_id
+        );
+
+        Ok(tree)
+    }
+
+    /// Count the objects in a bucket and add up their sizes
+    ///
+    /// Pages through the whole bucket, so this takes one request per 1000
+    /// objects and folder. With `prefix_depth`, totals are also broken down
+    /// by the first that many folder levels, e.g. `Some(1)` groups
+    /// `avatars/a/1.png` and `avatars/b/2.png` under `avatars`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// # use supabase_lib_rs::Client;
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let client = Client::new("http://localhost:54321", "test-key")?;
+    /// let stats = client.storage().bucket_stats("uploads", Some(1)).await?;
+    ///
+    /// println!("{} objects, {} bytes", stats.object_count, stats.total_size);
+    /// for (prefix, usage) in &stats.prefixes {
+    ///     println!("{}/: {} bytes", prefix, usage.total_size);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn bucket_stats(
+        &self,
+        bucket_id: &str,
+        prefix_depth: Option<usize>,
+    ) -> Result<BucketStats> {
+        let tree = self.list_recursive(bucket_id, None).await?;
+        Ok(BucketStats::from_tree(bucket_id, &tree, prefix_depth))
+    }
+
+    /// Upload a file, resolving conflicts with an existing object by `policy`
+    ///
+    /// The `upsert` flag of `options` is ignored in favour of the policy.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use bytes::Bytes;
+    /// use supabase_lib_rs::storage::OverwritePolicy;
+    /// # use supabase_lib_rs::Client;
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let client = Client::new("http://localhost:54321", "test-key")?;
+    ///
+    /// // Stored as "reports/q1 (2).pdf" if "reports/q1.pdf" is taken
+    /// let response = client.storage()
+    ///     .upload_with_policy(
+    ///         "documents",
+    ///         "reports/q1.pdf",
+    ///         Bytes::from_static(b"%PDF-1.7"),
+    ///         None,
+    ///         OverwritePolicy::VersionedCopy,
+    ///     )
+    ///     .await?;
+    /// println!("Uploaded to {}", response.key);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn upload_with_policy(
+        &self,
+        bucket_id: &str,
+        path: &str,
+        file_body: Bytes,
+        options: Option<FileOptions>,
+        policy: OverwritePolicy,
+    ) -> Result<UploadResponse> {
+        let mut options = options.unwrap_or_default();
+        options.upsert = policy == OverwritePolicy::Overwrite;
+
+        if policy != OverwritePolicy::VersionedCopy {
+            return self.upload(bucket_id, path, file_body, Some(options)).await;
+        }
+
+        let candidates = std::iter::once(path.to_string())
+            .chain((2..=MAX_VERSIONED_COPIES).map(|copy| versioned_path(path, copy)));
+        for candidate in candidates {
+            match self
+                .upload(
+                    bucket_id,
+                    &candidate,
+                    file_body.clone(),
+                    Some(options.clone()),
+                )
+                .await
+            {
+                Err(Error::AlreadyExists { .. }) => {
+                    debug!("Path {} is taken, trying the next copy", candidate);
+                }
+                result => return result,
+            }
+        }
+
+        Err(Error::already_exists(format!(
+            "No free path for {} after {} copies",
+            path, MAX_VERSIONED_COPIES
+        )))
+    }
+
+    /// Check whether an object exists, without downloading it
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// # use supabase_lib_rs::Client;
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let client = Client::new("http://localhost:54321", "test-key")?;
+    /// if client.storage().exists("avatars", "users/42.png").await? {
+    ///     println!("Avatar already uploaded");
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn exists(&self, bucket_id: &str, path: &str) -> Result<bool> {
+        debug!("Checking object in bucket: {} at path: {}", bucket_id, path);
+
+        let url = format!("{}/object/{}/{}", self.storage_url(), bucket_id, path);
+        let response = self
+            .circuit
+            .send(self.build_request(Method::HEAD, &url))
+            .await?;
+
+        let status = response.status();
+        if status.is_success() {
+            return Ok(true);
+        }
+        if self.is_missing_object(status, bucket_id, path).await? {
+            return Ok(false);
+        }
+        Err(
+            Error::storage(format!("Object check failed with status: {}", status))
+                .with_fingerprint(self.config.error_fingerprint),
+        )
+    }
+
+    /// Whether a failed HEAD request of an object means that it does not exist
+    ///
+    /// Storage answers some HEAD requests for missing objects with a bare 400
+    /// instead of a 404, so the object's info is fetched to read the error
+    /// body. Only a 404 or a "not found" body counts as missing.
+    async fn is_missing_object(
+        &self,
+        status: reqwest::StatusCode,
+        bucket_id: &str,
+        path: &str,
+    ) -> Result<bool> {
+        match status {
+            reqwest::StatusCode::NOT_FOUND => Ok(true),
+            reqwest::StatusCode::BAD_REQUEST => {
+                let url = format!("{}/object/info/{}/{}", self.storage_url(), bucket_id, path);
+                let response = self
+                    .circuit
+                    .send(self.build_request(Method::GET, &url))
+                    .await?;
+                let status = response.status();
+                let body = response.text().await.unwrap_or_default();
+                Ok(status == reqwest::StatusCode::NOT_FOUND
+                    || StorageError::from_response(Some(status.as_u16()), &body)
+                        == Some(StorageError::ObjectNotFound))
+            }
+            _ => Ok(false),
+        }
+    }
+
+    /// Upload content under a path derived from its SHA-256 digest
+    ///
+    /// The object is named by the lowercase hex digest of its content, so
+    /// identical content is stored once. Nothing is uploaded when an object
+    /// with that name already exists, including when a concurrent upload of
+    /// the same content wins the race. Returns the object path in the bucket.
+    ///
+    /// The `upsert` flag of `options` is ignored: existing content is never
+    /// rewritten.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use bytes::Bytes;
+    /// use supabase_lib_rs::storage::FileOptions;
+    /// # use supabase_lib_rs::Client;
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let client = Client::new("http://localhost:54321", "test-key")?;
+    ///
+    /// let image = Bytes::from_static(b"\x89PNG...");
+    /// let options = FileOptions {
+    ///     content_type: Some("image/png".to_string()),
+    ///     ..Default::default()
+    /// };
+    /// let path = client.storage()
+    ///     .upload_content_addressed("attachments", image, Some(options))
+    ///     .await?;
+    /// let url = client.storage().get_public_url("attachments", &path);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn upload_content_addressed(
+        &self,
+        bucket_id: &str,
+        file_body: Bytes,
+        options: Option<FileOptions>,
+    ) -> Result<String> {
+        let path = content_address(&file_body);
+
+        if self.exists(bucket_id, &path).await? {
+            debug!("Content already stored at {}, skipping upload", path);
+            return Ok(path);
+        }
+
+        let mut options = options.unwrap_or_default();
+        options.upsert = false;
+
+        match self
+            .upload(bucket_id, &path, file_body, Some(options))
+            .await
+        {
+            Ok(_) | Err(Error::AlreadyExists { .. }) => Ok(path),
+            Err(err) => Err(err),
+        }
+    }
+
     /// Upload a file from bytes
     #[cfg(not(target_arch = "wasm32"))]
     pub async fn upload(
@@ -496,44 +1639,39 @@ impl Storage {
 
         let options = options.unwrap_or_default();
 
-        let url = format!(
-            "{}/storage/v1/object/{}/{}",
-            self.config.url, bucket_id, path
-        );
+        let url = format!("{}/object/{}/{}", self.storage_url(), bucket_id, path);
 
         let mut form = multipart::Form::new().part(
             "file",
             multipart::Part::bytes(file_body.to_vec()).file_name(path.to_string()),
         );
 
-        if let Some(content_type) = options.content_type {
-            form = form.part("contentType", multipart::Part::text(content_type));
+        if let Some(content_type) = &options.content_type {
+            form = form.part("contentType", multipart::Part::text(content_type.clone()));
         }
 
-        if let Some(cache_control) = options.cache_control {
-            form = form.part("cacheControl", multipart::Part::text(cache_control));
+        if let Some(cache_control) = &options.cache_control {
+            form = form.part("cacheControl", multipart::Part::text(cache_control.clone()));
         }
 
-        let mut request = self.http_client.post(&url).multipart(form);
-
-        // Override Authorization header with user token if provided
-        if let Some(token) = user_token {
-            request = request.header("Authorization", format!("Bearer {}", token));
+        for (_, field, value) in options.representation_metadata() {
+            form = form.part(field, multipart::Part::text(value));
         }
 
+        let mut request = self
+            .build_request_as(Method::POST, &url, user_token)
+            .multipart(form);
+
         if options.upsert {
             request = request.header("x-upsert", "true");
         }
 
-        let response = request.send().await?;
+        let response = self.circuit.send(request).await?;
 
         if !response.status().is_success() {
             let status = response.status();
-            let error_msg = match response.text().await {
-                Ok(text) => text,
-                Err(_) => format!("Upload failed with status: {}", status),
-            };
-            return Err(Error::storage(error_msg));
+            return Err(upload_error(status, response.text().await.ok(), path)
+                .with_fingerprint(self.config.error_fingerprint));
         }
 
         let upload_response: UploadResponse = response.json().await?;
@@ -573,44 +1711,190 @@ impl Storage {
 
         let options = options.unwrap_or_default();
 
-        let url = format!(
-            "{}/storage/v1/object/{}/{}",
-            self.config.url, bucket_id, path
-        );
+        let url = format!("{}/object/{}/{}", self.storage_url(), bucket_id, path);
 
-        let mut request = self.http_client.post(&url).body(file_body);
+        let request = self
+            .build_request_as(Method::POST, &url, user_token)
+            .body(file_body);
+        let upload_response = self.send_body_upload(request, path, &options).await?;
+        info!("Uploaded file successfully: {}", path);
 
-        // Override Authorization header with user token if provided
-        if let Some(token) = user_token {
-            request = request.header("Authorization", format!("Bearer {}", token));
-        }
+        Ok(upload_response)
+    }
 
-        if let Some(content_type) = options.content_type {
+    /// Send an upload whose body is the raw file, with `options` as headers
+    async fn send_body_upload(
+        &self,
+        mut request: reqwest::RequestBuilder,
+        path: &str,
+        options: &FileOptions,
+    ) -> Result<UploadResponse> {
+        if let Some(content_type) = &options.content_type {
             request = request.header("Content-Type", content_type);
         }
 
-        if let Some(cache_control) = options.cache_control {
+        if let Some(cache_control) = &options.cache_control {
             request = request.header("Cache-Control", cache_control);
         }
 
+        for (header, _, value) in options.representation_metadata() {
+            request = request.header(header, value);
+        }
+
         if options.upsert {
             request = request.header("x-upsert", "true");
         }
 
-        let response = request.send().await?;
+        let response = self.circuit.send(request).await?;
 
         if !response.status().is_success() {
             let status = response.status();
-            let error_msg = match response.text().await {
-                Ok(text) => text,
-                Err(_) => format!("Upload failed with status: {}", status),
-            };
-            return Err(Error::storage(error_msg));
+            return Err(upload_error(status, response.text().await.ok(), path)
+                .with_fingerprint(self.config.error_fingerprint));
+        }
+
+        Ok(response.json().await?)
+    }
+
+    /// Upload `len` bytes read from `reader` without buffering them in memory
+    ///
+    /// The body is streamed to Storage in chunks as it is read, so multi-GB
+    /// files can be uploaded from a [`tokio::fs::File`] or any other
+    /// [`AsyncRead`](tokio::io::AsyncRead). `len` is sent as `Content-Length`
+    /// and must match what the reader yields.
+    ///
+    /// # Examples
+    /// ```rust,no_run
+    /// # use supabase_lib_rs::prelude::*;
+    /// # async fn example(client: &Client) -> std::result::Result<(), Box<dyn std::error::Error>> {
+    /// let file = tokio::fs::File::open("backup.tar").await?;
+    /// let len = file.metadata().await?.len();
+    ///
+    /// client
+    ///     .storage()
+    ///     .upload_stream("backups", "2024/backup.tar", file, len, None)
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(all(not(target_arch = "wasm32"), feature = "native"))]
+    pub async fn upload_stream<R>(
+        &self,
+        bucket_id: &str,
+        path: &str,
+        reader: R,
+        len: u64,
+        options: Option<FileOptions>,
+    ) -> Result<UploadResponse>
+    where
+        R: tokio::io::AsyncRead + Send + 'static,
+    {
+        let chunks = tokio_util::io::ReaderStream::with_capacity(reader, UPLOAD_STREAM_CHUNK_SIZE);
+        self.upload_byte_stream(bucket_id, path, chunks, len, options, None)
+            .await
+    }
+
+    /// Upload `len` bytes from a stream of chunks, reporting progress
+    ///
+    /// Each chunk is sent as soon as the stream yields it, and `progress` is
+    /// called with the bytes sent so far and `len`. To upload from an
+    /// [`AsyncRead`](tokio::io::AsyncRead) with progress, wrap it in
+    /// `tokio_util::io::ReaderStream`.
+    ///
+    /// # Examples
+    /// ```rust,no_run
+    /// # use supabase_lib_rs::prelude::*;
+    /// use bytes::Bytes;
+    /// use std::sync::Arc;
+    ///
+    /// # async fn example(client: &Client) -> Result<()> {
+    /// let chunks = futures_util::stream::iter(vec![
+    ///     Ok::<_, std::io::Error>(Bytes::from_static(b"first,")),
+    ///     Ok(Bytes::from_static(b"second")),
+    /// ]);
+    ///
+    /// client
+    ///     .storage()
+    ///     .upload_byte_stream(
+    ///         "exports",
+    ///         "report.csv",
+    ///         chunks,
+    ///         12,
+    ///         None,
+    ///         Some(Arc::new(|sent, total| println!("{}/{} bytes", sent, total))),
+    ///     )
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(not(target_arch = "wasm32"))]
+    pub async fn upload_byte_stream<S, E>(
+        &self,
+        bucket_id: &str,
+        path: &str,
+        chunks: S,
+        len: u64,
+        options: Option<FileOptions>,
+        progress: Option<UploadProgressCallback>,
+    ) -> Result<UploadResponse>
+    where
+        S: futures_util::Stream<Item = std::result::Result<Bytes, E>> + Send + 'static,
+        E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static,
+    {
+        use futures_util::StreamExt;
+
+        debug!(
+            "Streaming {} bytes to bucket: {} at path: {}",
+            len, bucket_id, path
+        );
+
+        let options = options.unwrap_or_default();
+
+        let url = format!("{}/object/{}/{}", self.storage_url(), bucket_id, path);
+
+        let mut sent = 0u64;
+        let chunks = chunks.map(move |chunk| {
+            if let (Ok(chunk), Some(progress)) = (&chunk, &progress) {
+                sent += chunk.len() as u64;
+                progress(sent, len);
+            }
+            chunk
+        });
+
+        let mut request = self
+            .build_request(Method::POST, &url)
+            .header("Content-Length", len)
+            .header(
+                "Content-Type",
+                options
+                    .content_type
+                    .as_deref()
+                    .unwrap_or("application/octet-stream"),
+            )
+            .body(reqwest::Body::wrap_stream(chunks));
+
+        if let Some(cache_control) = &options.cache_control {
+            request = request.header("Cache-Control", cache_control);
+        }
+
+        for (header, _, value) in options.representation_metadata() {
+            request = request.header(header, value);
+        }
+
+        if options.upsert {
+            request = request.header("x-upsert", "true");
+        }
+
+        let response = self.circuit.send(request).await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            return Err(upload_error(status, response.text().await.ok(), path)
+                .with_fingerprint(self.config.error_fingerprint));
         }
 
         let upload_response: UploadResponse = response.json().await?;
         info!("Uploaded file successfully: {}", path);
-
         Ok(upload_response)
     }
 
@@ -657,45 +1941,218 @@ impl Storage {
     /// let auth_response = client.auth().sign_in_with_email_and_password("user@example.com", "password").await?;
     /// let token = &auth_response.session.as_ref().unwrap().access_token;
     ///
-    /// let file_data = client.storage()
-    ///     .download_with_auth("private-bucket", "user-files/document.txt", Some(token))
-    ///     .await?;
+    /// let file_data = client.storage()
+    ///     .download_with_auth("private-bucket", "user-files/document.txt", Some(token))
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn download_with_auth(
+        &self,
+        bucket_id: &str,
+        path: &str,
+        user_token: Option<&str>,
+    ) -> Result<Bytes> {
+        debug!(
+            "Downloading file from bucket: {} at path: {}",
+            bucket_id, path
+        );
+
+        let url = format!("{}/object/{}/{}", self.storage_url(), bucket_id, path);
+
+        let request = self.build_request_as(Method::GET, &url, user_token);
+
+        let response = self.circuit.send(request).await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_msg = format!("Download failed with status: {}", status);
+            return Err(
+                response_error(status, response.text().await.ok(), error_msg)
+                    .with_fingerprint(self.config.error_fingerprint),
+            );
+        }
+
+        let bytes = response.bytes().await?;
+        info!("Downloaded file successfully: {}", path);
+
+        Ok(bytes)
+    }
+
+    /// Download a file as a stream of chunks, without buffering it in memory
+    ///
+    /// # Examples
+    /// ```rust,no_run
+    /// # use supabase_lib_rs::prelude::*;
+    /// use futures_util::StreamExt;
+    /// use tokio::io::AsyncWriteExt;
+    ///
+    /// # async fn example(client: &Client) -> std::result::Result<(), Box<dyn std::error::Error>> {
+    /// let mut stream = client.storage().download_stream("backups", "2024/backup.tar").await?;
+    /// let mut file = tokio::fs::File::create("backup.tar").await?;
+    ///
+    /// while let Some(chunk) = stream.next().await {
+    ///     file.write_all(&chunk?).await?;
+    ///     println!("{} of {:?} bytes", stream.bytes_received(), stream.content_length());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(not(target_arch = "wasm32"))]
+    pub async fn download_stream(&self, bucket_id: &str, path: &str) -> Result<DownloadStream> {
+        debug!(
+            "Streaming download from bucket: {} at path: {}",
+            bucket_id, path
+        );
+
+        let url = format!("{}/object/{}/{}", self.storage_url(), bucket_id, path);
+
+        let response = self
+            .circuit
+            .send(self.build_request(Method::GET, &url))
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_msg = format!("Download failed with status: {}", status);
+            return Err(
+                response_error(status, response.text().await.ok(), error_msg)
+                    .with_fingerprint(self.config.error_fingerprint),
+            );
+        }
+
+        Ok(DownloadStream {
+            content_length: response.content_length(),
+            inner: Box::pin(response.bytes_stream()),
+            received: 0,
+        })
+    }
+
+    /// Upload a file with an MD5 integrity check
+    ///
+    /// Like [`upload`](Self::upload), but the body is always sent as-is
+    /// (never as a multipart form) with a `Content-MD5` header, so the server
+    /// can reject content corrupted in transit. `options` are sent as headers.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::AlreadyExists`] when the object exists and
+    /// `options.upsert` is off, and a storage error when the server rejects the
+    /// checksum or the upload.
+    ///
+    /// # Examples
+    /// ```rust,no_run
+    /// use bytes::Bytes;
+    /// use supabase_lib_rs::storage::{Checksum, ChecksumAlgorithm};
+    ///
+    /// # async fn example(storage: &supabase_lib_rs::storage::Storage) -> supabase_lib_rs::Result<()> {
+    /// let contract = Bytes::from_static(b"signed contract");
+    /// // Keep the checksum to verify later downloads
+    /// let checksum = Checksum::compute(ChecksumAlgorithm::Sha256, &contract);
+    ///
+    /// storage.upload_verified("documents", "contract.pdf", contract, None).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn upload_verified(
+        &self,
+        bucket_id: &str,
+        path: &str,
+        file_body: Bytes,
+        options: Option<FileOptions>,
+    ) -> Result<UploadResponse> {
+        use base64::Engine;
+        use md5::Digest;
+
+        debug!(
+            "Uploading verified file to bucket: {} at path: {}",
+            bucket_id, path
+        );
+
+        let options = options.unwrap_or_default();
+        let content_md5 =
+            base64::engine::general_purpose::STANDARD.encode(md5::Md5::digest(&file_body));
+
+        let url = format!("{}/object/{}/{}", self.storage_url(), bucket_id, path);
+
+        let request = self
+            .build_request(Method::POST, &url)
+            .header("Content-MD5", content_md5)
+            .body(file_body);
+        let upload_response = self.send_body_upload(request, path, &options).await?;
+        info!("Uploaded verified file successfully: {}", path);
+
+        Ok(upload_response)
+    }
+
+    /// Download a file and verify its content
+    ///
+    /// With `expected` set, the content must match that checksum. Otherwise it
+    /// is checked against the object's `ETag` when it is a plain MD5 (objects
+    /// uploaded in multiple parts have no content-derived ETag and are not
+    /// checked). A mismatch returns [`Error::Integrity`].
+    ///
+    /// # Examples
+    /// ```rust,no_run
+    /// use supabase_lib_rs::storage::{Checksum, ChecksumAlgorithm};
+    ///
+    /// # async fn example(storage: &supabase_lib_rs::storage::Storage) -> supabase_lib_rs::Result<()> {
+    /// let expected = Checksum::new(ChecksumAlgorithm::Sha256, "9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08");
+    ///
+    /// match storage.download_verified("documents", "contract.pdf", Some(&expected)).await {
+    ///     Ok(data) => println!("Verified {} bytes", data.len()),
+    ///     Err(supabase_lib_rs::Error::Integrity(e)) => println!("Corrupted download: {}", e),
+    ///     Err(e) => return Err(e),
+    /// }
     /// # Ok(())
     /// # }
     /// ```
-    pub async fn download_with_auth(
+    pub async fn download_verified(
         &self,
         bucket_id: &str,
         path: &str,
-        user_token: Option<&str>,
+        expected: Option<&Checksum>,
     ) -> Result<Bytes> {
         debug!(
-            "Downloading file from bucket: {} at path: {}",
+            "Downloading verified file from bucket: {} at path: {}",
             bucket_id, path
         );
 
-        let url = format!(
-            "{}/storage/v1/object/{}/{}",
-            self.config.url, bucket_id, path
-        );
-
-        let mut request = self.http_client.get(&url);
-
-        // Override Authorization header with user token if provided
-        if let Some(token) = user_token {
-            request = request.header("Authorization", format!("Bearer {}", token));
-        }
+        let url = format!("{}/object/{}/{}", self.storage_url(), bucket_id, path);
 
-        let response = request.send().await?;
+        let response = self
+            .circuit
+            .send(self.build_request(Method::GET, &url))
+            .await?;
 
         if !response.status().is_success() {
-            let error_msg = format!("Download failed with status: {}", response.status());
-            return Err(Error::storage(error_msg));
+            let status = response.status();
+            let error_msg = format!("Download failed with status: {}", status);
+            return Err(
+                response_error(status, response.text().await.ok(), error_msg)
+                    .with_fingerprint(self.config.error_fingerprint),
+            );
         }
 
+        let etag = response
+            .headers()
+            .get("etag")
+            .and_then(|value| value.to_str().ok())
+            .map(|value| value.trim_start_matches("W/").trim_matches('"').to_string());
+
         let bytes = response.bytes().await?;
-        info!("Downloaded file successfully: {}", path);
 
+        match (expected, etag) {
+            (Some(checksum), _) => checksum.verify(&bytes, Some(path))?,
+            (None, Some(etag))
+                if etag.len() == 32 && etag.chars().all(|c| c.is_ascii_hexdigit()) =>
+            {
+                Checksum::new(ChecksumAlgorithm::Md5, &etag).verify(&bytes, Some(path))?
+            }
+            _ => warn!("No checksum available to verify download: {}", path),
+        }
+
+        info!("Downloaded and verified file successfully: {}", path);
         Ok(bytes)
     }
 
@@ -737,24 +2194,25 @@ impl Storage {
     ) -> Result<()> {
         debug!("Deleting files from bucket: {}", bucket_id);
 
-        let url = format!("{}/storage/v1/object/{}", self.config.url, bucket_id);
+        let url = format!("{}/object/{}", self.storage_url(), bucket_id);
 
         let payload = serde_json::json!({
             "prefixes": paths
         });
 
-        let mut request = self.http_client.delete(&url).json(&payload);
-
-        // Override Authorization header with user token if provided
-        if let Some(token) = user_token {
-            request = request.header("Authorization", format!("Bearer {}", token));
-        }
+        let request = self
+            .build_request_as(Method::DELETE, &url, user_token)
+            .json(&payload);
 
-        let response = request.send().await?;
+        let response = self.circuit.send(request).await?;
 
         if !response.status().is_success() {
-            let error_msg = format!("Delete files failed with status: {}", response.status());
-            return Err(Error::storage(error_msg));
+            let status = response.status();
+            let error_msg = format!("Delete files failed with status: {}", status);
+            return Err(
+                response_error(status, response.text().await.ok(), error_msg)
+                    .with_fingerprint(self.config.error_fingerprint),
+            );
         }
 
         info!("Deleted {} files successfully", paths.len());
@@ -765,7 +2223,7 @@ impl Storage {
     pub async fn r#move(&self, bucket_id: &str, from_path: &str, to_path: &str) -> Result<()> {
         debug!("Moving file from {} to {}", from_path, to_path);
 
-        let url = format!("{}/storage/v1/object/move", self.config.url);
+        let url = format!("{}/object/move", self.storage_url());
 
         let payload = serde_json::json!({
             "bucketId": bucket_id,
@@ -773,7 +2231,10 @@ impl Storage {
             "destinationKey": to_path
         });
 
-        let response = self.http_client.post(&url).json(&payload).send().await?;
+        let response = self
+            .circuit
+            .send(self.build_request(Method::POST, &url).json(&payload))
+            .await?;
 
         if !response.status().is_success() {
             let status = response.status();
@@ -781,7 +2242,7 @@ impl Storage {
                 Ok(text) => text,
                 Err(_) => format!("Move failed with status: {}", status),
             };
-            return Err(Error::storage(error_msg));
+            return Err(Error::storage(error_msg).with_fingerprint(self.config.error_fingerprint));
         }
 
         info!("Moved file successfully from {} to {}", from_path, to_path);
@@ -792,7 +2253,7 @@ impl Storage {
     pub async fn copy(&self, bucket_id: &str, from_path: &str, to_path: &str) -> Result<()> {
         debug!("Copying file from {} to {}", from_path, to_path);
 
-        let url = format!("{}/storage/v1/object/copy", self.config.url);
+        let url = format!("{}/object/copy", self.storage_url());
 
         let payload = serde_json::json!({
             "bucketId": bucket_id,
@@ -800,7 +2261,10 @@ impl Storage {
             "destinationKey": to_path
         });
 
-        let response = self.http_client.post(&url).json(&payload).send().await?;
+        let response = self
+            .circuit
+            .send(self.build_request(Method::POST, &url).json(&payload))
+            .await?;
 
         if !response.status().is_success() {
             let status = response.status();
@@ -808,7 +2272,7 @@ impl Storage {
                 Ok(text) => text,
                 Err(_) => format!("Copy failed with status: {}", status),
             };
-            return Err(Error::storage(error_msg));
+            return Err(Error::storage(error_msg).with_fingerprint(self.config.error_fingerprint));
         }
 
         info!("Copied file successfully from {} to {}", from_path, to_path);
@@ -818,8 +2282,10 @@ impl Storage {
     /// Get public URL for a file
     pub fn get_public_url(&self, bucket_id: &str, path: &str) -> String {
         format!(
-            "{}/storage/v1/object/public/{}/{}",
-            self.config.url, bucket_id, path
+            "{}/object/public/{}/{}",
+            self.storage_url(),
+            bucket_id,
+            path
         )
     }
 
@@ -831,15 +2297,58 @@ impl Storage {
         expires_in: u32,
         transform: Option<TransformOptions>,
     ) -> Result<String> {
+        self.create_signed_url_with_options(
+            bucket_id,
+            path,
+            expires_in,
+            SignedUrlOptions {
+                transform,
+                ..Default::default()
+            },
+        )
+        .await
+    }
+
+    /// Get a signed URL with response overrides
+    ///
+    /// # Examples
+    /// ```rust,no_run
+    /// use supabase_lib_rs::storage::SignedUrlOptions;
+    ///
+    /// # async fn example(storage: &supabase_lib_rs::storage::Storage) -> supabase_lib_rs::Result<()> {
+    /// // Force a download named "invoice.pdf" instead of inline display
+    /// let url = storage
+    ///     .create_signed_url_with_options(
+    ///         "invoices",
+    ///         "2024/0042.pdf",
+    ///         3600,
+    ///         SignedUrlOptions {
+    ///             download: Some("invoice.pdf".to_string()),
+    ///             ..Default::default()
+    ///         },
+    ///     )
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn create_signed_url_with_options(
+        &self,
+        bucket_id: &str,
+        path: &str,
+        expires_in: u32,
+        options: SignedUrlOptions,
+    ) -> Result<String> {
+        let SignedUrlOptions {
+            transform,
+            download,
+        } = options;
+
         debug!(
             "Creating signed URL for bucket: {} path: {} expires_in: {}",
             bucket_id, path, expires_in
         );
 
-        let url = format!(
-            "{}/storage/v1/object/sign/{}/{}",
-            self.config.url, bucket_id, path
-        );
+        let url = format!("{}/object/sign/{}/{}", self.storage_url(), bucket_id, path);
 
         let mut payload = serde_json::json!({
             "expiresIn": expires_in
@@ -867,14 +2376,17 @@ impl Storage {
             payload["transform"] = serde_json::Value::Object(transform_params);
         }
 
-        let response = self.http_client.post(&url).json(&payload).send().await?;
+        let response = self
+            .circuit
+            .send(self.build_request(Method::POST, &url).json(&payload))
+            .await?;
 
         if !response.status().is_success() {
             let error_msg = format!(
                 "Create signed URL failed with status: {}",
                 response.status()
             );
-            return Err(Error::storage(error_msg));
+            return Err(Error::storage(error_msg).with_fingerprint(self.config.error_fingerprint));
         }
 
         let response_data: serde_json::Value = response.json().await?;
@@ -883,7 +2395,289 @@ impl Storage {
             .ok_or_else(|| Error::storage("Invalid signed URL response"))?;
 
         info!("Created signed URL successfully");
-        Ok(signed_url.to_string())
+        Ok(with_download_param(signed_url, download.as_deref()))
+    }
+
+    /// Create a signed URL for uploading one object without an API key
+    ///
+    /// Storage accepts uploads to the URL for two hours. With `upsert` an
+    /// existing object at `path` is replaced.
+    pub async fn create_signed_upload_url(
+        &self,
+        bucket_id: &str,
+        path: &str,
+        upsert: bool,
+    ) -> Result<SignedUploadUrl> {
+        debug!(
+            "Creating signed upload URL for bucket: {} path: {}",
+            bucket_id, path
+        );
+
+        let url = format!(
+            "{}/object/upload/sign/{}/{}",
+            self.storage_url(),
+            bucket_id,
+            path
+        );
+        let mut request = self.build_request(Method::POST, &url);
+        if upsert {
+            request = request.header("x-upsert", "true");
+        }
+        let response = self.circuit.send(request).await?;
+
+        if !response.status().is_success() {
+            return Err(Error::storage(format!(
+                "Create signed upload URL failed with status: {}",
+                response.status()
+            ))
+            .with_fingerprint(self.config.error_fingerprint));
+        }
+
+        let response_data: serde_json::Value = response.json().await?;
+        let relative_url = response_data["url"]
+            .as_str()
+            .ok_or_else(|| Error::storage("Invalid signed upload URL response"))?;
+        let signed_url = format!("{}{}", self.storage_url(), relative_url);
+        let token = Url::parse(&signed_url)?
+            .query_pairs()
+            .find(|(key, _)| key == "token")
+            .map(|(_, value)| value.into_owned())
+            .ok_or_else(|| Error::storage("Signed upload URL has no token"))?;
+
+        info!("Created signed upload URL successfully");
+        Ok(SignedUploadUrl {
+            signed_url,
+            path: path.to_string(),
+            token,
+        })
+    }
+
+    /// Upload to a signed upload URL from [`create_signed_upload_url`](Self::create_signed_upload_url)
+    ///
+    /// The `token` authorizes the upload, so this needs neither the service
+    /// key nor a user session: a backend mints the URL and an untrusted client
+    /// uploads with the token it was handed.
+    ///
+    /// # Examples
+    /// ```rust,no_run
+    /// # use supabase_lib_rs::prelude::*;
+    /// # use bytes::Bytes;
+    /// # async fn example(backend: &Client, browser: &Client) -> Result<()> {
+    /// let signed = backend
+    ///     .storage()
+    ///     .create_signed_upload_url("avatars", "user-1.png", false)
+    ///     .await?;
+    ///
+    /// browser
+    ///     .storage()
+    ///     .upload_to_signed_url(
+    ///         "avatars",
+    ///         &signed.path,
+    ///         &signed.token,
+    ///         Bytes::from_static(b"..."),
+    ///         None,
+    ///     )
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn upload_to_signed_url(
+        &self,
+        bucket_id: &str,
+        path: &str,
+        token: &str,
+        file_body: Bytes,
+        options: Option<FileOptions>,
+    ) -> Result<UploadResponse> {
+        debug!(
+            "Uploading to signed URL for bucket: {} path: {}",
+            bucket_id, path
+        );
+
+        let options = options.unwrap_or_default();
+
+        let url = format!(
+            "{}/object/upload/sign/{}/{}",
+            self.storage_url(),
+            bucket_id,
+            path
+        );
+
+        let mut request = self
+            .http_client
+            .put(&url)
+            .query(&[("token", token)])
+            .header(
+                "Content-Type",
+                options
+                    .content_type
+                    .as_deref()
+                    .unwrap_or("application/octet-stream"),
+            )
+            .body(file_body);
+
+        if let Some(cache_control) = &options.cache_control {
+            request = request.header("Cache-Control", cache_control);
+        }
+
+        for (header, _, value) in options.representation_metadata() {
+            request = request.header(header, value);
+        }
+
+        if options.upsert {
+            request = request.header("x-upsert", "true");
+        }
+
+        let response = self.circuit.send(request).await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            return Err(upload_error(status, response.text().await.ok(), path)
+                .with_fingerprint(self.config.error_fingerprint));
+        }
+
+        let upload_response: UploadResponse = response.json().await?;
+        info!("Uploaded file to signed URL successfully: {}", path);
+        Ok(upload_response)
+    }
+
+    /// Mint a signed upload URL for a browser-direct upload
+    ///
+    /// The backend hands the ticket's URL and `upload_token` to the browser,
+    /// which uploads straight to Storage and then posts the token back.
+    /// [`Storage::confirm_upload`] checks the token and the stored object
+    /// against `constraints`. `secret` stays on the server and never reaches
+    /// Supabase; the token expires after `expires_in`, which should not
+    /// exceed the two hours Storage honours the upload URL.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use std::time::Duration;
+    /// use supabase_lib_rs::storage::UploadConstraints;
+    /// # use supabase_lib_rs::Client;
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let client = Client::service("http://localhost:54321", "service-role-key")?;
+    /// let secret = b"server-side upload secret";
+    ///
+    /// let ticket = client.storage()
+    ///     .create_upload_ticket(
+    ///         "avatars",
+    ///         "users/42.png",
+    ///         UploadConstraints {
+    ///             max_size: Some(2 * 1024 * 1024),
+    ///             allowed_mime_types: vec!["image/*".to_string()],
+    ///         },
+    ///         Duration::from_secs(600),
+    ///         secret,
+    ///     )
+    ///     .await?;
+    /// // Send ticket.upload.signed_url and ticket.upload_token to the browser
+    ///
+    /// // Later, in the upload callback handler
+    /// let claims = client.storage()
+    ///     .confirm_upload(&ticket.upload_token, secret)
+    ///     .await?;
+    /// println!("Stored {}", claims.path);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn create_upload_ticket(
+        &self,
+        bucket_id: &str,
+        path: &str,
+        constraints: UploadConstraints,
+        expires_in: Duration,
+        secret: &[u8],
+    ) -> Result<UploadTicket> {
+        let upload = self
+            .create_signed_upload_url(bucket_id, path, false)
+            .await?;
+        let claims = UploadTokenClaims {
+            bucket_id: bucket_id.to_string(),
+            path: path.to_string(),
+            constraints,
+            expires_at: chrono::Utc::now().timestamp() + expires_in.as_secs() as i64,
+        };
+        let upload_token = claims.sign(secret)?;
+
+        Ok(UploadTicket {
+            upload,
+            upload_token,
+            claims,
+        })
+    }
+
+    /// Confirm a browser-direct upload made with an [`UploadTicket`]
+    ///
+    /// Verifies the token, then checks the stored object's size and content
+    /// type against the ticket's constraints. An object that breaks them is
+    /// removed and the upload is rejected with [`Error::InvalidInput`]. When
+    /// the ticket limits the size but the server reports none, the upload is
+    /// rejected with a storage error and the object is kept.
+    pub async fn confirm_upload(
+        &self,
+        upload_token: &str,
+        secret: &[u8],
+    ) -> Result<UploadTokenClaims> {
+        let claims = UploadTokenClaims::verify(upload_token, secret)?;
+
+        let url = format!(
+            "{}/object/{}/{}",
+            self.storage_url(),
+            claims.bucket_id,
+            claims.path
+        );
+        let response = self
+            .circuit
+            .send(self.build_request(Method::HEAD, &url))
+            .await?;
+        let status = response.status();
+        if !status.is_success() {
+            if self
+                .is_missing_object(status, &claims.bucket_id, &claims.path)
+                .await?
+            {
+                return Err(
+                    Error::not_found(format!("Object not uploaded: {}", claims.path))
+                        .with_fingerprint(self.config.error_fingerprint),
+                );
+            }
+            return Err(
+                Error::storage(format!("Object check failed with status: {}", status))
+                    .with_fingerprint(self.config.error_fingerprint),
+            );
+        }
+
+        let headers = response.headers();
+        let size = headers
+            .get(reqwest::header::CONTENT_LENGTH)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.parse().ok());
+        // Without a size the limit cannot be checked, so the upload is not confirmed
+        let size = match (size, claims.constraints.max_size) {
+            (Some(size), _) => size,
+            (None, None) => 0,
+            (None, Some(_)) => {
+                return Err(Error::storage(format!(
+                    "Object check returned no Content-Length for {}",
+                    claims.path
+                ))
+                .with_fingerprint(self.config.error_fingerprint));
+            }
+        };
+        let content_type = headers
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok());
+
+        if let Err(err) = claims.constraints.check(size, content_type) {
+            debug!("Removing rejected upload: {}", claims.path);
+            self.remove(&claims.bucket_id, &[&claims.path]).await?;
+            return Err(err);
+        }
+
+        info!("Confirmed upload of {}", claims.path);
+        Ok(claims)
     }
 
     /// Get transformed image URL
@@ -957,7 +2751,7 @@ impl Storage {
     /// # Ok(())
     /// # }
     /// ```
-    #[cfg(not(target_arch = "wasm32"))]
+    #[cfg(all(not(target_arch = "wasm32"), feature = "storage-resumable"))]
     pub async fn start_resumable_upload(
         &self,
         bucket_id: &str,
@@ -975,8 +2769,10 @@ impl Storage {
         );
 
         let url = format!(
-            "{}/storage/v1/object/{}/{}/resumable",
-            self.config.url, bucket_id, path
+            "{}/object/{}/{}/resumable",
+            self.storage_url(),
+            bucket_id,
+            path
         );
 
         let payload = serde_json::json!({
@@ -984,17 +2780,23 @@ impl Storage {
             "chunkSize": config.chunk_size,
             "contentType": options.content_type,
             "cacheControl": options.cache_control,
+            "contentDisposition": options.content_disposition,
+            "contentLanguage": options.content_language,
+            "contentEncoding": options.content_encoding,
             "upsert": options.upsert
         });
 
-        let response = self.http_client.post(&url).json(&payload).send().await?;
+        let response = self
+            .circuit
+            .send(self.build_request(Method::POST, &url).json(&payload))
+            .await?;
 
         if !response.status().is_success() {
             let error_msg = format!(
                 "Start resumable upload failed with status: {}",
                 response.status()
             );
-            return Err(Error::storage(error_msg));
+            return Err(Error::storage(error_msg).with_fingerprint(self.config.error_fingerprint));
         }
 
         let session: UploadSession = response.json().await?;
@@ -1022,7 +2824,7 @@ impl Storage {
     /// # Ok(())
     /// # }
     /// ```
-    #[cfg(not(target_arch = "wasm32"))]
+    #[cfg(all(not(target_arch = "wasm32"), feature = "storage-resumable"))]
     pub async fn upload_chunk(
         &self,
         session: &UploadSession,
@@ -1037,24 +2839,28 @@ impl Storage {
         );
 
         let url = format!(
-            "{}/storage/v1/object/{}/{}/resumable/{}",
-            self.config.url, session.bucket_id, session.object_path, session.upload_id
+            "{}/object/{}/{}/resumable/{}",
+            self.storage_url(),
+            session.bucket_id,
+            session.object_path,
+            session.upload_id
         );
 
         let chunk_size = chunk_data.len() as u64;
 
         let response = self
-            .http_client
-            .put(&url)
-            .header("Content-Type", "application/octet-stream")
-            .header("X-Part-Number", part_number.to_string())
-            .body(chunk_data)
-            .send()
+            .circuit
+            .send(
+                self.build_request(Method::PUT, &url)
+                    .header("Content-Type", "application/octet-stream")
+                    .header("X-Part-Number", part_number.to_string())
+                    .body(chunk_data),
+            )
             .await?;
 
         if !response.status().is_success() {
             let error_msg = format!("Upload chunk failed with status: {}", response.status());
-            return Err(Error::storage(error_msg));
+            return Err(Error::storage(error_msg).with_fingerprint(self.config.error_fingerprint));
         }
 
         let etag = response
@@ -1086,7 +2892,7 @@ impl Storage {
     /// # Ok(())
     /// # }
     /// ```
-    #[cfg(not(target_arch = "wasm32"))]
+    #[cfg(all(not(target_arch = "wasm32"), feature = "storage-resumable"))]
     pub async fn complete_resumable_upload(
         &self,
         session: &UploadSession,
@@ -1097,22 +2903,28 @@ impl Storage {
         );
 
         let url = format!(
-            "{}/storage/v1/object/{}/{}/resumable/{}/complete",
-            self.config.url, session.bucket_id, session.object_path, session.upload_id
+            "{}/object/{}/{}/resumable/{}/complete",
+            self.storage_url(),
+            session.bucket_id,
+            session.object_path,
+            session.upload_id
         );
 
         let payload = serde_json::json!({
             "parts": session.uploaded_parts
         });
 
-        let response = self.http_client.post(&url).json(&payload).send().await?;
+        let response = self
+            .circuit
+            .send(self.build_request(Method::POST, &url).json(&payload))
+            .await?;
 
         if !response.status().is_success() {
             let error_msg = format!(
                 "Complete resumable upload failed with status: {}",
                 response.status()
             );
-            return Err(Error::storage(error_msg));
+            return Err(Error::storage(error_msg).with_fingerprint(self.config.error_fingerprint));
         }
 
         let upload_response: UploadResponse = response.json().await?;
@@ -1154,7 +2966,11 @@ impl Storage {
     /// # Ok(())
     /// # }
     /// ```
-    #[cfg(all(not(target_arch = "wasm32"), feature = "native"))]
+    #[cfg(all(
+        not(target_arch = "wasm32"),
+        feature = "native",
+        feature = "storage-resumable"
+    ))]
     pub async fn upload_large_file<P: AsRef<std::path::Path>>(
         &self,
         bucket_id: &str,
@@ -1163,10 +2979,39 @@ impl Storage {
         config: Option<ResumableUploadConfig>,
         options: Option<FileOptions>,
         progress_callback: Option<UploadProgressCallback>,
+    ) -> Result<UploadResponse> {
+        self.upload_large_file_inner(
+            bucket_id,
+            path,
+            file_path.as_ref(),
+            config,
+            options,
+            progress_callback,
+            None,
+        )
+        .await
+    }
+
+    /// Chunked upload, recording the upload session on `transfer` if given
+    #[cfg(all(
+        not(target_arch = "wasm32"),
+        feature = "native",
+        feature = "storage-resumable"
+    ))]
+    #[allow(clippy::too_many_arguments)]
+    async fn upload_large_file_inner(
+        &self,
+        bucket_id: &str,
+        path: &str,
+        file_path: &std::path::Path,
+        config: Option<ResumableUploadConfig>,
+        options: Option<FileOptions>,
+        progress_callback: Option<UploadProgressCallback>,
+        transfer: Option<&TransferHandle>,
     ) -> Result<UploadResponse> {
         let config = config.unwrap_or_default();
 
-        debug!("Starting large file upload from: {:?}", file_path.as_ref());
+        debug!("Starting large file upload from: {:?}", file_path);
 
         // Get file size
         let metadata = tokio::fs::metadata(&file_path)
@@ -1185,6 +3030,10 @@ impl Storage {
             .start_resumable_upload(bucket_id, path, total_size, Some(config.clone()), options)
             .await?;
 
+        if let Some(transfer) = transfer {
+            transfer.set_upload_id(&session.upload_id);
+        }
+
         // Open file for reading
         let mut file = tokio::fs::File::open(&file_path)
             .await
@@ -1264,45 +3113,263 @@ impl Storage {
     }
 
     /// Get resumable upload session status
-    #[cfg(not(target_arch = "wasm32"))]
+    #[cfg(all(not(target_arch = "wasm32"), feature = "storage-resumable"))]
     pub async fn get_upload_session(&self, upload_id: &str) -> Result<UploadSession> {
         debug!("Getting upload session status: {}", upload_id);
 
-        let url = format!("{}/storage/v1/resumable/{}", self.config.url, upload_id);
+        let url = format!("{}/resumable/{}", self.storage_url(), upload_id);
+
+        let response = self
+            .circuit
+            .send(self.build_request(Method::GET, &url))
+            .await?;
+
+        if !response.status().is_success() {
+            let error_msg = format!(
+                "Get upload session failed with status: {}",
+                response.status()
+            );
+            return Err(Error::storage(error_msg).with_fingerprint(self.config.error_fingerprint));
+        }
+
+        let session: UploadSession = response.json().await?;
+        Ok(session)
+    }
+
+    /// Cancel a resumable upload session
+    #[cfg(all(not(target_arch = "wasm32"), feature = "storage-resumable"))]
+    pub async fn cancel_upload_session(&self, upload_id: &str) -> Result<()> {
+        debug!("Cancelling upload session: {}", upload_id);
+
+        let url = format!("{}/resumable/{}", self.storage_url(), upload_id);
+
+        let response = self
+            .circuit
+            .send(self.build_request(Method::DELETE, &url))
+            .await?;
+
+        if !response.status().is_success() {
+            let error_msg = format!(
+                "Cancel upload session failed with status: {}",
+                response.status()
+            );
+            return Err(Error::storage(error_msg).with_fingerprint(self.config.error_fingerprint));
+        }
+
+        info!("Cancelled upload session: {}", upload_id);
+        Ok(())
+    }
+
+    // ========================
+    // ABORTABLE TRANSFERS
+    // ========================
+
+    /// Upload bytes as an abortable transfer listed in [`Storage::transfers`]
+    ///
+    /// # Examples
+    /// ```rust,no_run
+    /// use bytes::Bytes;
+    ///
+    /// # async fn example(storage: &supabase_lib_rs::storage::Storage) -> supabase_lib_rs::Result<()> {
+    /// let transfer = storage.upload_abortable("avatars", "me.png", Bytes::from_static(b"..."), None);
+    ///
+    /// // Give the handle to the UI; `handle.abort()` cancels the upload
+    /// let handle = transfer.handle().clone();
+    /// println!("Uploading {} ({:?})", handle.path(), handle.status());
+    ///
+    /// let response = transfer.await?;
+    /// println!("Uploaded {}", response.key);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn upload_abortable(
+        &self,
+        bucket_id: &str,
+        path: &str,
+        file_body: Bytes,
+        options: Option<FileOptions>,
+    ) -> Transfer<UploadResponse> {
+        let size = file_body.len() as u64;
+        let storage = self.clone();
+        let (bucket, object_path) = (bucket_id.to_string(), path.to_string());
+
+        self.track(
+            TransferKind::Upload,
+            bucket_id,
+            path,
+            Some(size),
+            move |handle| async move {
+                let response = storage
+                    .upload(&bucket, &object_path, file_body, options)
+                    .await?;
+                handle.set_progress(size, None);
+                Ok(response)
+            },
+        )
+    }
+
+    /// Download a file as an abortable transfer with byte-level progress
+    ///
+    /// # Examples
+    /// ```rust,no_run
+    /// # async fn example(storage: &supabase_lib_rs::storage::Storage) -> supabase_lib_rs::Result<()> {
+    /// let transfer = storage.download_abortable("videos", "intro.mp4");
+    /// let handle = transfer.handle().clone();
+    ///
+    /// for active in storage.transfers().list() {
+    ///     println!("{} {:?}", active.path(), active.progress());
+    /// }
+    ///
+    /// let data = transfer.await?;
+    /// println!("Downloaded {} bytes", data.len());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn download_abortable(&self, bucket_id: &str, path: &str) -> Transfer<Bytes> {
+        let url = format!("{}/object/{}/{}", self.storage_url(), bucket_id, path);
+        let request = self.build_request(Method::GET, &url);
+        let circuit = self.circuit.clone();
+
+        self.track(
+            TransferKind::Download,
+            bucket_id,
+            path,
+            None,
+            move |handle| async move {
+                use futures_util::StreamExt;
+
+                let response = circuit.send(request).await?;
+
+                if !response.status().is_success() {
+                    let status = response.status();
+                    let error_msg = format!("Download failed with status: {}", status);
+                    return Err(
+                        response_error(status, response.text().await.ok(), error_msg)
+                            .with_fingerprint(circuit.fingerprint()),
+                    );
+                }
+
+                let total = response.content_length();
+                let mut data = Vec::with_capacity(total.unwrap_or(0) as usize);
+                handle.set_progress(0, total);
 
-        let response = self.http_client.get(&url).send().await?;
+                let mut body = response.bytes_stream();
+                while let Some(chunk) = body.next().await {
+                    data.extend_from_slice(&chunk?);
+                    handle.set_progress(data.len() as u64, None);
+                }
 
-        if !response.status().is_success() {
-            let error_msg = format!(
-                "Get upload session failed with status: {}",
-                response.status()
-            );
-            return Err(Error::storage(error_msg));
-        }
+                Ok(Bytes::from(data))
+            },
+        )
+    }
 
-        let session: UploadSession = response.json().await?;
-        Ok(session)
+    /// Upload a large file in chunks as an abortable transfer
+    ///
+    /// Aborting also cancels the resumable upload session on the server.
+    #[cfg(all(
+        not(target_arch = "wasm32"),
+        feature = "native",
+        feature = "storage-resumable"
+    ))]
+    pub fn upload_large_file_abortable<P: AsRef<std::path::Path>>(
+        &self,
+        bucket_id: &str,
+        path: &str,
+        file_path: P,
+        config: Option<ResumableUploadConfig>,
+        options: Option<FileOptions>,
+    ) -> Transfer<UploadResponse> {
+        let storage = self.clone();
+        let (bucket, object_path) = (bucket_id.to_string(), path.to_string());
+        let file_path = file_path.as_ref().to_path_buf();
+
+        self.track(
+            TransferKind::Upload,
+            bucket_id,
+            path,
+            None,
+            move |handle| async move {
+                let progress_handle = handle.clone();
+                let progress: UploadProgressCallback = Arc::new(move |uploaded, total| {
+                    progress_handle.set_progress(uploaded, Some(total));
+                });
+
+                storage
+                    .upload_large_file_inner(
+                        &bucket,
+                        &object_path,
+                        &file_path,
+                        config,
+                        options,
+                        Some(progress),
+                        Some(&handle),
+                    )
+                    .await
+            },
+        )
     }
 
-    /// Cancel a resumable upload session
-    #[cfg(not(target_arch = "wasm32"))]
-    pub async fn cancel_upload_session(&self, upload_id: &str) -> Result<()> {
-        debug!("Cancelling upload session: {}", upload_id);
+    /// Register a transfer and wrap its future so it can be aborted
+    fn track<T, F, Fut>(
+        &self,
+        kind: TransferKind,
+        bucket_id: &str,
+        path: &str,
+        total: Option<u64>,
+        run: F,
+    ) -> Transfer<T>
+    where
+        T: TransferSend + 'static,
+        F: FnOnce(TransferHandle) -> Fut,
+        Fut: Future<Output = Result<T>> + TransferSend + 'static,
+    {
+        let (handle, registration) = self.transfers.register(kind, bucket_id, path, total);
+        let future = Abortable::new(run(handle.clone()), registration);
+
+        #[cfg(all(not(target_arch = "wasm32"), feature = "storage-resumable"))]
+        let storage = self.clone();
+        let tracked = handle.clone();
+        let guard = TransferGuard {
+            registry: self.transfers.clone(),
+            handle: handle.clone(),
+        };
+        let future = async move {
+            let result = match future.await {
+                Ok(Ok(value)) => {
+                    tracked.finish(TransferStatus::Completed);
+                    Ok(value)
+                }
+                Ok(Err(e)) => {
+                    tracked.finish(TransferStatus::Failed);
+                    Err(e)
+                }
+                Err(_aborted) => {
+                    tracked.finish(TransferStatus::Aborted);
+
+                    #[cfg(all(not(target_arch = "wasm32"), feature = "storage-resumable"))]
+                    if let Some(upload_id) = tracked.upload_id() {
+                        if let Err(e) = storage.cancel_upload_session(&upload_id).await {
+                            warn!("Failed to cancel upload session {}: {}", upload_id, e);
+                        }
+                    }
 
-        let url = format!("{}/storage/v1/resumable/{}", self.config.url, upload_id);
+                    info!("Transfer aborted: {}", tracked.path());
+                    Err(Error::storage(format!(
+                        "Transfer of {} was aborted",
+                        tracked.path()
+                    )))
+                }
+            };
 
-        let response = self.http_client.delete(&url).send().await?;
+            drop(guard);
+            result
+        };
 
-        if !response.status().is_success() {
-            let error_msg = format!(
-                "Cancel upload session failed with status: {}",
-                response.status()
-            );
-            return Err(Error::storage(error_msg));
+        Transfer {
+            handle,
+            future: Box::pin(future),
         }
-
-        info!("Cancelled upload session: {}", upload_id);
-        Ok(())
     }
 
     /// Update file metadata with tags and custom metadata
@@ -1345,18 +3412,23 @@ impl Storage {
         );
 
         let url = format!(
-            "{}/storage/v1/object/{}/{}/metadata",
-            self.config.url, bucket_id, path
+            "{}/object/{}/{}/metadata",
+            self.storage_url(),
+            bucket_id,
+            path
         );
 
-        let response = self.http_client.put(&url).json(metadata).send().await?;
+        let response = self
+            .circuit
+            .send(self.build_request(Method::PUT, &url).json(metadata))
+            .await?;
 
         if !response.status().is_success() {
             let error_msg = format!(
                 "Update file metadata failed with status: {}",
                 response.status()
             );
-            return Err(Error::storage(error_msg));
+            return Err(Error::storage(error_msg).with_fingerprint(self.config.error_fingerprint));
         }
 
         info!("Updated file metadata successfully");
@@ -1394,18 +3466,16 @@ impl Storage {
     ) -> Result<Vec<FileObject>> {
         debug!("Searching files in bucket: {}", bucket_id);
 
-        let url = format!("{}/storage/v1/object/{}/search", self.config.url, bucket_id);
+        let url = format!("{}/object/{}/search", self.storage_url(), bucket_id);
 
         let response = self
-            .http_client
-            .post(&url)
-            .json(search_options)
-            .send()
+            .circuit
+            .send(self.build_request(Method::POST, &url).json(search_options))
             .await?;
 
         if !response.status().is_success() {
             let error_msg = format!("Search files failed with status: {}", response.status());
-            return Err(Error::storage(error_msg));
+            return Err(Error::storage(error_msg).with_fingerprint(self.config.error_fingerprint));
         }
 
         let files: Vec<FileObject> = response.json().await?;
@@ -1414,6 +3484,181 @@ impl Storage {
         Ok(files)
     }
 
+    // ========================
+    // BUCKET LIFECYCLE HELPERS
+    // ========================
+
+    /// Remove objects matching a cleanup policy
+    ///
+    /// Objects are listed page by page, filtered by age, and deleted in
+    /// batches of `policy.batch_size`. Folders are not traversed.
+    ///
+    /// # Examples
+    /// ```rust,no_run
+    /// use std::time::Duration;
+    /// use supabase_lib_rs::storage::CleanupPolicy;
+    ///
+    /// # async fn example(storage: &supabase_lib_rs::storage::Storage) -> supabase_lib_rs::Result<()> {
+    /// let report = storage.cleanup("tmp-uploads", CleanupPolicy {
+    ///     older_than: Some(Duration::from_secs(24 * 60 * 60)),
+    ///     prefix: Some("incoming".to_string()),
+    ///     dry_run: true,
+    ///     ..Default::default()
+    /// }).await?;
+    ///
+    /// println!("Would delete {} files", report.matched.len());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn cleanup(&self, bucket_id: &str, policy: CleanupPolicy) -> Result<CleanupReport> {
+        self.cleanup_with_progress(bucket_id, policy, None).await
+    }
+
+    /// Remove objects matching a cleanup policy, reporting progress after each batch
+    ///
+    /// # Examples
+    /// ```rust,no_run
+    /// use std::sync::Arc;
+    /// use supabase_lib_rs::storage::CleanupPolicy;
+    ///
+    /// # async fn example(storage: &supabase_lib_rs::storage::Storage) -> supabase_lib_rs::Result<()> {
+    /// let progress = Arc::new(|processed: usize, total: usize| {
+    ///     println!("Cleaned {}/{}", processed, total);
+    /// });
+    ///
+    /// storage
+    ///     .cleanup_with_progress("tmp-uploads", CleanupPolicy::default(), Some(progress))
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn cleanup_with_progress(
+        &self,
+        bucket_id: &str,
+        policy: CleanupPolicy,
+        progress_callback: Option<CleanupProgressCallback>,
+    ) -> Result<CleanupReport> {
+        debug!("Running cleanup on bucket: {}", bucket_id);
+
+        if policy.batch_size == 0 {
+            return Err(Error::invalid_input(
+                "Cleanup batch size must be greater than 0",
+            ));
+        }
+
+        let cutoff = match policy.older_than {
+            Some(age) => {
+                let age = chrono::Duration::from_std(age)
+                    .map_err(|e| Error::invalid_input(format!("Invalid cleanup age: {}", e)))?;
+                Some(chrono::Utc::now() - age)
+            }
+            None => None,
+        };
+
+        let prefix = policy
+            .prefix
+            .as_deref()
+            .unwrap_or("")
+            .trim_matches('/')
+            .to_string();
+
+        // Collect matches first so deletions don't shift the listing offsets
+        let mut matched = Vec::new();
+        let mut offset = 0;
+        loop {
+            let page = self
+                .list_page(bucket_id, &prefix, policy.batch_size, offset)
+                .await?;
+            let page_len = page.len();
+
+            for object in page {
+                if object.is_folder() {
+                    continue;
+                }
+
+                if let Some(cutoff) = cutoff {
+                    match object.updated_at.or(object.created_at) {
+                        Some(timestamp) if timestamp < cutoff => {}
+                        _ => continue,
+                    }
+                }
+
+                if prefix.is_empty() {
+                    matched.push(object.name);
+                } else {
+                    matched.push(format!("{}/{}", prefix, object.name));
+                }
+            }
+
+            if page_len < policy.batch_size {
+                break;
+            }
+            offset += page_len;
+        }
+
+        let total = matched.len();
+        let mut deleted = 0;
+
+        if policy.dry_run {
+            info!(
+                "Cleanup dry run matched {} files in bucket: {}",
+                total, bucket_id
+            );
+        } else {
+            for batch in matched.chunks(policy.batch_size) {
+                let paths: Vec<&str> = batch.iter().map(String::as_str).collect();
+                self.remove(bucket_id, &paths).await?;
+                deleted += paths.len();
+
+                if let Some(callback) = &progress_callback {
+                    callback(deleted, total);
+                }
+            }
+            info!("Cleanup deleted {} files in bucket: {}", deleted, bucket_id);
+        }
+
+        Ok(CleanupReport {
+            matched,
+            deleted,
+            dry_run: policy.dry_run,
+        })
+    }
+
+    /// List a single page of objects under a prefix
+    async fn list_page(
+        &self,
+        bucket_id: &str,
+        prefix: &str,
+        limit: usize,
+        offset: usize,
+    ) -> Result<Vec<FileObject>> {
+        let url = format!("{}/object/list/{}", self.storage_url(), bucket_id);
+
+        let payload = serde_json::json!({
+            "prefix": prefix,
+            "limit": limit,
+            "offset": offset,
+            "sortBy": { "column": "name", "order": "asc" }
+        });
+
+        let response = self
+            .circuit
+            .send(self.build_request(Method::POST, &url).json(&payload))
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_msg = match response.text().await {
+                Ok(text) => text,
+                Err(_) => format!("List files failed with status: {}", status),
+            };
+            return Err(Error::storage(error_msg).with_fingerprint(self.config.error_fingerprint));
+        }
+
+        let files: Vec<FileObject> = response.json().await?;
+        Ok(files)
+    }
+
     // ========================
     // STORAGE POLICIES HELPERS
     // ========================
@@ -1440,7 +3685,10 @@ impl Storage {
     pub async fn create_policy(&self, policy: &StoragePolicy) -> Result<()> {
         debug!("Creating storage policy: {}", policy.name);
 
-        let url = format!("{}/rest/v1/rpc/create_storage_policy", self.config.url);
+        let url = format!(
+            "{}/rpc/create_storage_policy",
+            self.config.project_url().rest_url()
+        );
 
         let payload = serde_json::json!({
             "policy_name": policy.name,
@@ -1451,12 +3699,14 @@ impl Storage {
         });
 
         let response = self
-            .http_client
-            .post(&url)
-            .header("Authorization", format!("Bearer {}", self.get_admin_key()))
-            .header("apikey", self.get_admin_key())
-            .json(&payload)
-            .send()
+            .circuit
+            .send(
+                self.http_client
+                    .post(&url)
+                    .header("Authorization", format!("Bearer {}", self.get_admin_key()))
+                    .header("apikey", self.get_admin_key())
+                    .json(&payload),
+            )
             .await?;
 
         if !response.status().is_success() {
@@ -1464,7 +3714,7 @@ impl Storage {
                 "Create storage policy failed with status: {}",
                 response.status()
             );
-            return Err(Error::storage(error_msg));
+            return Err(Error::storage(error_msg).with_fingerprint(self.config.error_fingerprint));
         }
 
         info!("Created storage policy: {}", policy.name);
@@ -1493,7 +3743,10 @@ impl Storage {
     pub async fn update_policy(&self, policy: &StoragePolicy) -> Result<()> {
         debug!("Updating storage policy: {}", policy.name);
 
-        let url = format!("{}/rest/v1/rpc/update_storage_policy", self.config.url);
+        let url = format!(
+            "{}/rpc/update_storage_policy",
+            self.config.project_url().rest_url()
+        );
 
         let payload = serde_json::json!({
             "policy_name": policy.name,
@@ -1504,12 +3757,14 @@ impl Storage {
         });
 
         let response = self
-            .http_client
-            .put(&url)
-            .header("Authorization", format!("Bearer {}", self.get_admin_key()))
-            .header("apikey", self.get_admin_key())
-            .json(&payload)
-            .send()
+            .circuit
+            .send(
+                self.http_client
+                    .put(&url)
+                    .header("Authorization", format!("Bearer {}", self.get_admin_key()))
+                    .header("apikey", self.get_admin_key())
+                    .json(&payload),
+            )
             .await?;
 
         if !response.status().is_success() {
@@ -1517,7 +3772,7 @@ impl Storage {
                 "Update storage policy failed with status: {}",
                 response.status()
             );
-            return Err(Error::storage(error_msg));
+            return Err(Error::storage(error_msg).with_fingerprint(self.config.error_fingerprint));
         }
 
         info!("Updated storage policy: {}", policy.name);
@@ -1539,7 +3794,10 @@ impl Storage {
             policy_name, bucket_id
         );
 
-        let url = format!("{}/rest/v1/rpc/delete_storage_policy", self.config.url);
+        let url = format!(
+            "{}/rpc/delete_storage_policy",
+            self.config.project_url().rest_url()
+        );
 
         let payload = serde_json::json!({
             "policy_name": policy_name,
@@ -1547,12 +3805,14 @@ impl Storage {
         });
 
         let response = self
-            .http_client
-            .delete(&url)
-            .header("Authorization", format!("Bearer {}", self.get_admin_key()))
-            .header("apikey", self.get_admin_key())
-            .json(&payload)
-            .send()
+            .circuit
+            .send(
+                self.http_client
+                    .delete(&url)
+                    .header("Authorization", format!("Bearer {}", self.get_admin_key()))
+                    .header("apikey", self.get_admin_key())
+                    .json(&payload),
+            )
             .await?;
 
         if !response.status().is_success() {
@@ -1560,7 +3820,7 @@ impl Storage {
                 "Delete storage policy failed with status: {}",
                 response.status()
             );
-            return Err(Error::storage(error_msg));
+            return Err(Error::storage(error_msg).with_fingerprint(self.config.error_fingerprint));
         }
 
         info!("Deleted storage policy: {}", policy_name);
@@ -1580,19 +3840,24 @@ impl Storage {
     pub async fn list_policies(&self, bucket_id: &str) -> Result<Vec<StoragePolicy>> {
         debug!("Listing storage policies for bucket: {}", bucket_id);
 
-        let url = format!("{}/rest/v1/rpc/list_storage_policies", self.config.url);
+        let url = format!(
+            "{}/rpc/list_storage_policies",
+            self.config.project_url().rest_url()
+        );
 
         let payload = serde_json::json!({
             "bucket_name": bucket_id
         });
 
         let response = self
-            .http_client
-            .post(&url)
-            .header("Authorization", format!("Bearer {}", self.get_admin_key()))
-            .header("apikey", self.get_admin_key())
-            .json(&payload)
-            .send()
+            .circuit
+            .send(
+                self.http_client
+                    .post(&url)
+                    .header("Authorization", format!("Bearer {}", self.get_admin_key()))
+                    .header("apikey", self.get_admin_key())
+                    .json(&payload),
+            )
             .await?;
 
         if !response.status().is_success() {
@@ -1600,7 +3865,7 @@ impl Storage {
                 "List storage policies failed with status: {}",
                 response.status()
             );
-            return Err(Error::storage(error_msg));
+            return Err(Error::storage(error_msg).with_fingerprint(self.config.error_fingerprint));
         }
 
         let policies: Vec<StoragePolicy> = response.json().await?;
@@ -1647,7 +3912,10 @@ impl Storage {
             user_id, object_path
         );
 
-        let url = format!("{}/rest/v1/rpc/test_storage_policy_access", self.config.url);
+        let url = format!(
+            "{}/rpc/test_storage_policy_access",
+            self.config.project_url().rest_url()
+        );
 
         let payload = serde_json::json!({
             "bucket_name": bucket_id,
@@ -1657,12 +3925,14 @@ impl Storage {
         });
 
         let response = self
-            .http_client
-            .post(&url)
-            .header("Authorization", format!("Bearer {}", self.get_admin_key()))
-            .header("apikey", self.get_admin_key())
-            .json(&payload)
-            .send()
+            .circuit
+            .send(
+                self.http_client
+                    .post(&url)
+                    .header("Authorization", format!("Bearer {}", self.get_admin_key()))
+                    .header("apikey", self.get_admin_key())
+                    .json(&payload),
+            )
             .await?;
 
         if !response.status().is_success() {
@@ -1670,7 +3940,7 @@ impl Storage {
                 "Test policy access failed with status: {}",
                 response.status()
             );
-            return Err(Error::storage(error_msg));
+            return Err(Error::storage(error_msg).with_fingerprint(self.config.error_fingerprint));
         }
 
         let result: serde_json::Value = response.json().await?;
@@ -1772,3 +4042,558 @@ pub enum PolicyTemplate {
     /// Allow read-only access to users with specific role
     ReadOnlyForRole(String),
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_checksum_compute_and_verify() {
+        let md5 = Checksum::compute(ChecksumAlgorithm::Md5, b"hello");
+        assert_eq!(md5.value, "5d41402abc4b2a76b9719d911017c592");
+
+        let sha256 = Checksum::new(
+            ChecksumAlgorithm::Sha256,
+            "2CF24DBA5FB0A30E26E83B2AC5B9E29E1B161E5C1FA7425E73043362938B9824",
+        );
+        assert!(sha256.verify(b"hello", None).is_ok());
+
+        match sha256.verify(b"hellO", Some("docs/a.txt")) {
+            Err(Error::Integrity(e)) => {
+                assert_eq!(e.algorithm, "sha256");
+                assert_eq!(e.path.as_deref(), Some("docs/a.txt"));
+                assert_ne!(e.expected, e.actual);
+            }
+            other => panic!("expected integrity error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_representation_metadata_and_download_param() {
+        let options = FileOptions {
+            content_disposition: Some("attachment; filename=\"report.pdf\"".to_string()),
+            content_encoding: Some("gzip".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            options.representation_metadata(),
+            vec![
+                (
+                    "Content-Disposition",
+                    "contentDisposition",
+                    "attachment; filename=\"report.pdf\"".to_string()
+                ),
+                ("Content-Encoding", "contentEncoding", "gzip".to_string()),
+            ]
+        );
+        assert!(FileOptions::default().representation_metadata().is_empty());
+
+        let signed = "/object/sign/docs/a.pdf?token=abc";
+        assert_eq!(with_download_param(signed, None), signed);
+        assert_eq!(
+            with_download_param(signed, Some("my report.pdf")),
+            "/object/sign/docs/a.pdf?token=abc&download=my%20report.pdf"
+        );
+        assert_eq!(
+            with_download_param("/object/sign/docs/a.pdf", Some("")),
+            "/object/sign/docs/a.pdf?download="
+        );
+    }
+
+    #[test]
+    fn test_content_address() {
+        assert_eq!(
+            content_address(b"hello"),
+            "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824"
+        );
+        assert_eq!(content_address(b"hello"), content_address(b"hello"));
+        assert_ne!(content_address(b"hello"), content_address(b"hello!"));
+    }
+
+    #[test]
+    fn test_upload_token_sign_and_verify() {
+        let claims = UploadTokenClaims {
+            bucket_id: "avatars".to_string(),
+            path: "users/42.png".to_string(),
+            constraints: UploadConstraints {
+                max_size: Some(1024),
+                allowed_mime_types: vec!["image/*".to_string(), "application/pdf".to_string()],
+            },
+            expires_at: chrono::Utc::now().timestamp() + 60,
+        };
+
+        let token = claims.sign(b"secret").unwrap();
+        assert_eq!(
+            UploadTokenClaims::verify(&token, b"secret").unwrap(),
+            claims
+        );
+        assert!(UploadTokenClaims::verify(&token, b"other").is_err());
+        let (payload, signature) = token.split_once('.').unwrap();
+        let forged = format!("{}x.{}", payload, signature);
+        assert!(UploadTokenClaims::verify(&forged, b"secret").is_err());
+
+        let expired = UploadTokenClaims {
+            expires_at: chrono::Utc::now().timestamp() - 1,
+            ..claims.clone()
+        };
+        let expired_token = expired.sign(b"secret").unwrap();
+        assert!(UploadTokenClaims::verify(&expired_token, b"secret").is_err());
+
+        let constraints = &claims.constraints;
+        assert!(constraints.check(512, Some("image/png")).is_ok());
+        assert!(constraints
+            .check(512, Some("application/pdf; charset=binary"))
+            .is_ok());
+        assert!(constraints.check(2048, Some("image/png")).is_err());
+        assert!(constraints.check(512, Some("text/html")).is_err());
+        assert!(constraints.check(512, Some("imagery/png")).is_err());
+        assert!(constraints.check(512, None).is_err());
+        assert!(UploadConstraints::default().check(u64::MAX, None).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_confirm_upload_requires_size() {
+        use crate::mock_http::{MockServer, Response};
+
+        // A 204 carries no Content-Length
+        let server = MockServer::start([Response::new(204)]).await;
+        let storage = Storage::new(
+            Arc::new(SupabaseConfig {
+                url: server.url(),
+                ..Default::default()
+            }),
+            Arc::new(HttpClient::new()),
+        )
+        .unwrap();
+        let token = UploadTokenClaims {
+            bucket_id: "avatars".to_string(),
+            path: "users/42.png".to_string(),
+            constraints: UploadConstraints {
+                max_size: Some(1024),
+                allowed_mime_types: Vec::new(),
+            },
+            expires_at: chrono::Utc::now().timestamp() + 60,
+        }
+        .sign(b"secret")
+        .unwrap();
+
+        let err = storage.confirm_upload(&token, b"secret").await.unwrap_err();
+        assert!(err.to_string().contains("no Content-Length"));
+        // The object is not removed
+        let requests = server.requests();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(
+            requests[0].line(),
+            "HEAD /storage/v1/object/avatars/users/42.png"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_exists_missing_object() {
+        use crate::mock_http::{MockServer, Response};
+
+        let server = MockServer::start([
+            Response::new(200),
+            Response::new(404),
+            // A bare 400 is only a missing object when the info says so
+            Response::new(400),
+            Response::json(
+                r#"{"statusCode":"404","error":"not_found","message":"Object not found"}"#,
+            )
+            .status(400),
+            Response::new(400),
+            Response::json(
+                r#"{"statusCode":"400","error":"InvalidKey","message":"Invalid key: a//b"}"#,
+            )
+            .status(400),
+        ])
+        .await;
+        let storage = Storage::new(
+            Arc::new(SupabaseConfig {
+                url: server.url(),
+                ..Default::default()
+            }),
+            Arc::new(HttpClient::new()),
+        )
+        .unwrap();
+
+        assert!(storage.exists("avatars", "a.png").await.unwrap());
+        assert!(!storage.exists("avatars", "b.png").await.unwrap());
+        assert!(!storage.exists("avatars", "c.png").await.unwrap());
+        assert!(storage.exists("avatars", "a//b").await.is_err());
+
+        let requests = server.requests();
+        assert_eq!(requests[2].line(), "HEAD /storage/v1/object/avatars/c.png");
+        assert_eq!(
+            requests[3].line(),
+            "GET /storage/v1/object/info/avatars/c.png"
+        );
+    }
+
+    #[test]
+    fn test_overwrite_policy_helpers() {
+        assert_eq!(versioned_path("docs/report.pdf", 2), "docs/report (2).pdf");
+        assert_eq!(versioned_path("archive.tar.gz", 3), "archive.tar (3).gz");
+        assert_eq!(versioned_path("notes/.env", 2), "notes/.env (2)");
+        assert_eq!(versioned_path("README", 2), "README (2)");
+
+        let legacy = upload_error(
+            reqwest::StatusCode::BAD_REQUEST,
+            Some(r#"{"statusCode":"409","error":"Duplicate","message":"The resource already exists"}"#.to_string()),
+            "docs/report.pdf",
+        );
+        assert!(matches!(legacy, Error::AlreadyExists { .. }));
+        assert!(matches!(
+            upload_error(reqwest::StatusCode::CONFLICT, None, "a.txt"),
+            Error::AlreadyExists { .. }
+        ));
+        assert!(matches!(
+            upload_error(
+                reqwest::StatusCode::BAD_REQUEST,
+                Some("bad".to_string()),
+                "a.txt"
+            ),
+            Error::Storage { .. }
+        ));
+    }
+
+    #[test]
+    fn test_storage_error_mapping() {
+        let too_large = upload_error(
+            reqwest::StatusCode::PAYLOAD_TOO_LARGE,
+            Some(r#"{"statusCode":"413","error":"Payload too large","message":"The object exceeded the maximum allowed size"}"#.to_string()),
+            "big.bin",
+        );
+        assert_eq!(
+            too_large.storage_error(),
+            Some(StorageError::PayloadTooLarge { limit: None })
+        );
+        assert_eq!(too_large.status_code(), Some(413));
+
+        let mime = upload_error(
+            reqwest::StatusCode::BAD_REQUEST,
+            Some(r#"{"statusCode":"415","code":"InvalidMimeType","error":"invalid_mime_type","message":"mime type text/html is not supported"}"#.to_string()),
+            "page.html",
+        );
+        let bucket: Bucket = serde_json::from_value(serde_json::json!({
+            "id": "avatars",
+            "name": "avatars",
+            "owner": null,
+            "public": true,
+            "file_size_limit": 1048576,
+            "allowed_mime_types": ["image/png", "image/jpeg"],
+            "created_at": "2024-01-01T00:00:00Z",
+            "updated_at": "2024-01-01T00:00:00Z"
+        }))
+        .unwrap();
+        let reason = mime.storage_error().unwrap().with_bucket(&bucket);
+        assert_eq!(
+            reason,
+            StorageError::InvalidMimeType {
+                mime_type: Some("text/html".to_string()),
+                allowed: vec!["image/png".to_string(), "image/jpeg".to_string()],
+            }
+        );
+        assert_eq!(
+            reason.to_string(),
+            "Files of type text/html are not accepted; allowed types: image/png, image/jpeg"
+        );
+        assert_eq!(
+            StorageError::PayloadTooLarge { limit: None }.with_bucket(&bucket),
+            StorageError::PayloadTooLarge {
+                limit: Some(1048576)
+            }
+        );
+
+        let missing = response_error(
+            reqwest::StatusCode::BAD_REQUEST,
+            Some(
+                r#"{"statusCode":"404","error":"Bucket not found","message":"Bucket not found"}"#
+                    .to_string(),
+            ),
+            "Download failed with status: 400 Bad Request".to_string(),
+        );
+        assert_eq!(missing.storage_error(), Some(StorageError::BucketNotFound));
+        let object = StorageError::from_response(
+            Some(400),
+            r#"{"statusCode":"404","code":"NoSuchKey","error":"not_found","message":"Object not found"}"#,
+        );
+        assert_eq!(object, Some(StorageError::ObjectNotFound));
+        assert_eq!(
+            StorageError::from_response(
+                Some(402),
+                r#"{"error":"Payment Required","message":"Limit reached"}"#
+            ),
+            Some(StorageError::QuotaExceeded)
+        );
+        assert!(Error::storage("Upload failed").storage_error().is_none());
+        assert!(Error::database(r#"{"message":"Bucket not found"}"#)
+            .storage_error()
+            .is_none());
+    }
+
+    #[test]
+    fn test_storage_tree_aggregates_files() {
+        let entry = |name: &str, size: Option<u64>| FileObject {
+            name: name.to_string(),
+            id: size.map(|_| name.to_string()),
+            updated_at: None,
+            created_at: None,
+            last_accessed_at: None,
+            metadata: size.map(|size| HashMap::from([("size".to_string(), size.into())])),
+        };
+
+        assert!(entry("2024", None).is_folder());
+        assert!(!entry("q1.pdf", Some(10)).is_folder());
+        assert_eq!(join_storage_path("", "reports"), "reports");
+        assert_eq!(
+            join_storage_path("reports/2024", "q1.pdf"),
+            "reports/2024/q1.pdf"
+        );
+
+        let mut tree = StorageTree::default();
+        tree.add_file(entry("reports/q1.pdf", Some(1024)));
+        tree.add_file(entry("reports/2024/q2.pdf", Some(2048)));
+        assert_eq!(tree.file_count, 2);
+        assert_eq!(tree.total_size, 3072);
+    }
+
+    #[test]
+    fn test_bucket_stats_by_prefix() {
+        let entry = |name: &str, size: u64| FileObject {
+            name: name.to_string(),
+            id: Some(name.to_string()),
+            updated_at: None,
+            created_at: None,
+            last_accessed_at: None,
+            metadata: Some(HashMap::from([("size".to_string(), size.into())])),
+        };
+
+        let mut tree = StorageTree::default();
+        tree.add_file(entry("reports/q1.pdf", 1024));
+        tree.add_file(entry("reports/2024/q2.pdf", 2048));
+        tree.add_file(entry("readme.txt", 100));
+        let stats = BucketStats::from_tree("docs", &tree, None);
+        assert_eq!((stats.object_count, stats.total_size), (3, 3172));
+        assert!(stats.prefixes.is_empty());
+
+        let by_folder = |depth| {
+            BucketStats::from_tree("docs", &tree, Some(depth))
+                .prefixes
+                .into_iter()
+                .map(|(prefix, stats)| (prefix, stats.total_size))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            by_folder(1),
+            vec![("".to_string(), 100), ("reports".to_string(), 3072)]
+        );
+        assert_eq!(
+            by_folder(2),
+            vec![
+                ("".to_string(), 100),
+                ("reports".to_string(), 1024),
+                ("reports/2024".to_string(), 2048)
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_upload_stream_sends_sized_body() {
+        use crate::mock_http::{MockServer, Response};
+
+        let server = MockServer::start([Response::json(r#"{"Key":"backups/data.bin"}"#)]).await;
+
+        let storage = Storage::new(
+            Arc::new(SupabaseConfig {
+                url: server.url(),
+                ..Default::default()
+            }),
+            Arc::new(HttpClient::new()),
+        )
+        .unwrap();
+        let data: Vec<u8> = (0..200_000u32).map(|i| (i % 251) as u8).collect();
+
+        let response = storage
+            .upload_stream(
+                "backups",
+                "data.bin",
+                std::io::Cursor::new(data.clone()),
+                data.len() as u64,
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.key, "backups/data.bin");
+
+        let request = &server.requests()[0];
+        assert_eq!(request.line(), "POST /storage/v1/object/backups/data.bin");
+        assert!(request.header("transfer-encoding").is_none());
+        assert_eq!(request.body, data);
+    }
+
+    #[tokio::test]
+    async fn test_upload_byte_stream_reports_progress() {
+        use crate::mock_http::{MockServer, Response};
+
+        let server = MockServer::start([Response::json(r#"{"Key":"exports/report.csv"}"#)]).await;
+
+        let storage = Storage::new(
+            Arc::new(SupabaseConfig {
+                url: server.url(),
+                ..Default::default()
+            }),
+            Arc::new(HttpClient::new()),
+        )
+        .unwrap();
+        let chunks = futures_util::stream::iter(vec![
+            Ok::<_, std::io::Error>(Bytes::from_static(b"first,")),
+            Ok(Bytes::from_static(b"second")),
+        ]);
+        let reports = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let recorder = reports.clone();
+
+        storage
+            .upload_byte_stream(
+                "exports",
+                "report.csv",
+                chunks,
+                12,
+                None,
+                Some(Arc::new(move |sent, total| {
+                    recorder.lock().unwrap().push((sent, total))
+                })),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(*reports.lock().unwrap(), vec![(6, 12), (12, 12)]);
+        assert_eq!(server.requests()[0].body, b"first,second");
+    }
+
+    #[tokio::test]
+    async fn test_download_stream() {
+        use crate::mock_http::{MockServer, Response};
+        use futures_util::StreamExt;
+
+        let data: Vec<u8> = (0..100_000u32).map(|i| (i % 251) as u8).collect();
+        let server = MockServer::start([Response::new(200)
+            .header("content-type", "application/octet-stream")
+            .body(data.clone())])
+        .await;
+
+        let storage = Storage::new(
+            Arc::new(SupabaseConfig {
+                url: server.url(),
+                ..Default::default()
+            }),
+            Arc::new(HttpClient::new()),
+        )
+        .unwrap();
+        let mut stream = storage
+            .download_stream("backups", "data.bin")
+            .await
+            .unwrap();
+        assert_eq!(stream.content_length(), Some(data.len() as u64));
+
+        let mut received = Vec::new();
+        while let Some(chunk) = stream.next().await {
+            received.extend_from_slice(&chunk.unwrap());
+            assert_eq!(stream.bytes_received(), received.len() as u64);
+        }
+        assert_eq!(received, data);
+    }
+
+    #[tokio::test]
+    async fn test_upload_to_signed_url() {
+        use crate::mock_http::{MockServer, Response};
+
+        let server = MockServer::start([Response::json(r#"{"Key":"avatars/me.txt"}"#)]).await;
+
+        let storage = Storage::new(
+            Arc::new(SupabaseConfig {
+                url: server.url(),
+                ..Default::default()
+            }),
+            Arc::new(HttpClient::new()),
+        )
+        .unwrap();
+        let response = storage
+            .upload_to_signed_url(
+                "avatars",
+                "me.txt",
+                "upload-token",
+                Bytes::from_static(b"hello"),
+                Some(FileOptions {
+                    content_type: Some("text/plain".to_string()),
+                    ..Default::default()
+                }),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.key, "avatars/me.txt");
+
+        let request = &server.requests()[0];
+        assert_eq!(
+            request.line(),
+            "PUT /storage/v1/object/upload/sign/avatars/me.txt?token=upload-token"
+        );
+        assert_eq!(request.header("content-type"), Some("text/plain"));
+        assert_eq!(request.body, b"hello");
+    }
+
+    #[tokio::test]
+    async fn test_abort_tracked_transfer() {
+        let storage = Storage::new(
+            Arc::new(SupabaseConfig::default()),
+            Arc::new(HttpClient::new()),
+        )
+        .unwrap();
+
+        let transfer = storage.track(
+            TransferKind::Download,
+            "videos",
+            "intro.mp4",
+            Some(100),
+            |handle| async move {
+                handle.set_progress(40, None);
+                std::future::pending::<Result<Bytes>>().await
+            },
+        );
+        let handle = transfer.handle().clone();
+
+        let listed = storage.transfers().list();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].id(), handle.id());
+        assert_eq!(handle.progress(), (0, Some(100)));
+        assert_eq!(handle.status(), TransferStatus::InProgress);
+
+        storage.transfers().get(handle.id()).unwrap().abort();
+        assert!(transfer.await.is_err());
+        assert_eq!(handle.status(), TransferStatus::Aborted);
+        assert!(storage.transfers().list().is_empty());
+
+        // Aborting a finished transfer is a no-op
+        handle.abort();
+        assert_eq!(handle.status(), TransferStatus::Aborted);
+    }
+
+    #[tokio::test]
+    async fn test_dropped_transfer_is_unregistered() {
+        let storage = Storage::new(
+            Arc::new(SupabaseConfig::default()),
+            Arc::new(HttpClient::new()),
+        )
+        .unwrap();
+
+        let transfer = storage.track(TransferKind::Upload, "videos", "intro.mp4", None, |_| {
+            std::future::pending::<Result<Bytes>>()
+        });
+        let handle = transfer.handle().clone();
+        assert_eq!(storage.transfers().list().len(), 1);
+
+        drop(transfer);
+        assert!(storage.transfers().list().is_empty());
+        assert_eq!(handle.status(), TransferStatus::Aborted);
+    }
+}
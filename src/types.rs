@@ -1,12 +1,18 @@
 //! Common types and data structures for Supabase operations
 
+use crate::{
+    circuit::CircuitBreakerConfig,
+    error::{Error, Result},
+    retry::RetryPolicy,
+};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use url::Url;
 use uuid::Uuid;
 
 /// Configuration for Supabase client
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone)]
 pub struct SupabaseConfig {
     /// Project URL (e.g., https://your-project.supabase.co)
     pub url: String,
@@ -22,6 +28,54 @@ pub struct SupabaseConfig {
     pub database_config: DatabaseConfig,
     /// Storage configuration
     pub storage_config: StorageConfig,
+    /// Edge Functions configuration
+    pub functions_config: FunctionsConfig,
+    /// Add crate version, features, runtime and endpoint kind to the context
+    /// metadata of failed requests made by this client (default: true)
+    pub error_fingerprint: bool,
+}
+
+impl SupabaseConfig {
+    /// Whether requests are made with the service role key itself
+    ///
+    /// True for clients created with [`Client::service`](crate::Client::service),
+    /// which keep no user session.
+    pub fn is_service_role(&self) -> bool {
+        self.service_role_key.as_deref() == Some(self.key.as_str())
+    }
+
+    /// Service endpoints derived from [`url`](Self::url)
+    #[cfg(any(feature = "auth-core", feature = "storage-core", feature = "database"))]
+    pub(crate) fn project_url(&self) -> ProjectUrl {
+        ProjectUrl::unchecked(&self.url)
+    }
+
+    /// Edge Functions endpoint, on the dev base URL when one is configured
+    #[cfg(feature = "auth-core")]
+    pub(crate) fn functions_url(&self) -> String {
+        let base_url = self
+            .functions_config
+            .dev_base_url
+            .as_deref()
+            .unwrap_or(&self.url);
+        ProjectUrl::unchecked(base_url.trim_end_matches('/')).functions_url()
+    }
+}
+
+impl Default for SupabaseConfig {
+    fn default() -> Self {
+        Self {
+            url: String::new(),
+            key: String::new(),
+            service_role_key: None,
+            http_config: HttpConfig::default(),
+            auth_config: AuthConfig::default(),
+            database_config: DatabaseConfig::default(),
+            storage_config: StorageConfig::default(),
+            functions_config: FunctionsConfig::default(),
+            error_fingerprint: true,
+        }
+    }
 }
 
 /// HTTP client configuration
@@ -35,6 +89,25 @@ pub struct HttpConfig {
     pub max_redirects: usize,
     /// Custom headers to include in all requests
     pub default_headers: HashMap<String, String>,
+    /// Fail fast while a service keeps failing (default: disabled)
+    ///
+    /// See [`circuit`](crate::circuit) for how the breakers behave.
+    pub circuit_breaker: Option<CircuitBreakerConfig>,
+    /// Retry failed requests (default: disabled)
+    ///
+    /// See [`retry`](crate::retry) for which requests are retried.
+    pub retry: Option<RetryPolicy>,
+    /// Ask for gzip or brotli compressed responses (default: enabled)
+    ///
+    /// Native builds send `Accept-Encoding` and decompress responses
+    /// transparently; browsers always do this themselves.
+    pub response_compression: bool,
+    /// Gzip database request bodies of at least this many bytes (default: disabled)
+    ///
+    /// Applies to inserts, upserts and updates, where bulk payloads often
+    /// shrink tenfold. Only enable it when the gateway in front of PostgREST
+    /// accepts `Content-Encoding: gzip` requests.
+    pub request_compression_threshold: Option<usize>,
 }
 
 impl Default for HttpConfig {
@@ -44,6 +117,10 @@ impl Default for HttpConfig {
             connect_timeout: 10,
             max_redirects: 10,
             default_headers: HashMap::new(),
+            circuit_breaker: None,
+            retry: None,
+            response_compression: true,
+            request_compression_threshold: None,
         }
     }
 }
@@ -56,9 +133,22 @@ pub struct AuthConfig {
     /// Token refresh threshold in seconds before expiry
     pub refresh_threshold: u64,
     /// Persist session in storage
+    ///
+    /// On WASM builds with the `wasm` feature the session is saved to
+    /// `localStorage` under `storage_key`, restored on startup, and cleared
+    /// when another tab signs out.
     pub persist_session: bool,
     /// Custom storage implementation
     pub storage_key: String,
+    /// Write sessions through a `SessionManager` and restore them on startup
+    /// (default: disabled)
+    ///
+    /// Requires the `session-management` feature. Sessions go to the platform
+    /// storage of this project URL and `storage_key` (a data directory
+    /// natively, localStorage in browsers), and the most recent one is
+    /// checked with the server, or refreshed, and restored when the client is
+    /// created. See `Auth::set_session_manager` for custom storage.
+    pub persist_sessions: bool,
 }
 
 impl Default for AuthConfig {
@@ -68,6 +158,7 @@ impl Default for AuthConfig {
             refresh_threshold: 300, // 5 minutes
             persist_session: true,
             storage_key: "supabase.auth.token".to_string(),
+            persist_sessions: false,
         }
     }
 }
@@ -81,6 +172,15 @@ pub struct DatabaseConfig {
     pub max_retries: u32,
     /// Retry delay in milliseconds
     pub retry_delay: u64,
+    /// Fail when returned rows and model structs disagree
+    ///
+    /// Rejects columns the struct does not declare and struct fields missing
+    /// from the row, which would otherwise be ignored or silently defaulted,
+    /// and enum values that match no declared variant. Meant for tests and
+    /// staging, to catch schema drift early.
+    pub strict_models: bool,
+    /// How struct field names map to column names
+    pub field_casing: FieldCasing,
 }
 
 impl Default for DatabaseConfig {
@@ -89,7 +189,111 @@ impl Default for DatabaseConfig {
             schema: "public".to_string(),
             max_retries: 3,
             retry_delay: 1000,
+            strict_models: false,
+            field_casing: FieldCasing::SnakeCase,
+        }
+    }
+}
+
+/// Naming of struct fields relative to snake_case database columns
+///
+/// With [`FieldCasing::CamelCase`], models can carry a single
+/// `#[serde(rename_all = "camelCase")]` (e.g. for a camelCase JSON API)
+/// instead of renaming every field. The query builders then translate column
+/// names in select lists, filters and ordering to snake_case, convert the keys
+/// of insert and update payloads, and hand rows to serde with camelCase keys.
+/// Keys are converted at every depth, so embedded resources and nested structs
+/// stored in JSON columns round-trip.
+///
+/// Only camelCase identifiers are translated: identifiers starting with an
+/// uppercase letter, double-quoted identifiers and JSON keys after `->` or
+/// `->>` are kept as written.
+///
+/// # Examples
+/// ```rust
+/// use supabase_lib_rs::types::FieldCasing;
+///
+/// assert_eq!(FieldCasing::CamelCase.to_column("createdAt"), "created_at");
+/// assert_eq!(FieldCasing::CamelCase.to_field("created_at"), "createdAt");
+/// assert_eq!(FieldCasing::SnakeCase.to_column("createdAt"), "createdAt");
+/// assert_eq!(
+///     FieldCasing::CamelCase.to_column("authorId,metaData->>sourceUrl,\"UserID\""),
+///     "author_id,meta_data->>sourceUrl,\"UserID\""
+/// );
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FieldCasing {
+    /// Field names are column names
+    #[default]
+    SnakeCase,
+    /// Fields are camelCase versions of snake_case columns
+    CamelCase,
+}
+
+impl FieldCasing {
+    /// Column name for a field name, or every identifier in a column list
+    pub fn to_column(self, field: &str) -> String {
+        if self == Self::SnakeCase {
+            return field.to_string();
+        }
+
+        let is_word = |c: char| c.is_ascii_alphanumeric() || c == '_';
+        let mut column = String::with_capacity(field.len() + 4);
+        let mut rest = field;
+        // The next word is a JSON key, not a column
+        let mut json_key = false;
+        while let Some(c) = rest.chars().next() {
+            let end = if c == '"' {
+                rest[1..].find('"').map_or(rest.len(), |end| end + 2)
+            } else if is_word(c) {
+                rest.find(|c| !is_word(c)).unwrap_or(rest.len())
+            } else if rest.starts_with("->") {
+                if rest.starts_with("->>") {
+                    3
+                } else {
+                    2
+                }
+            } else {
+                c.len_utf8()
+            };
+            let (token, tail) = rest.split_at(end);
+
+            if !json_key && token.starts_with(|c: char| c.is_ascii_lowercase()) {
+                for (index, c) in token.char_indices() {
+                    if c.is_ascii_uppercase() && index > 0 {
+                        column.push('_');
+                    }
+                    column.push(c.to_ascii_lowercase());
+                }
+            } else {
+                column.push_str(token);
+            }
+            json_key = token.starts_with("->");
+            rest = tail;
+        }
+        column
+    }
+
+    /// Field name for a column name, the inverse of [`to_column`](Self::to_column)
+    pub fn to_field(self, column: &str) -> String {
+        if self == Self::SnakeCase {
+            return column.to_string();
+        }
+
+        // Same rule as `#[serde(rename_all = "camelCase")]`
+        let mut field = String::with_capacity(column.len());
+        let mut capitalize = false;
+        for c in column.chars() {
+            if c == '_' && !field.is_empty() {
+                capitalize = true;
+            } else if capitalize {
+                field.push(c.to_ascii_uppercase());
+                capitalize = false;
+            } else {
+                field.push(c);
+            }
         }
+        field
     }
 }
 
@@ -114,6 +318,275 @@ impl Default for StorageConfig {
     }
 }
 
+/// Edge Functions configuration
+#[derive(Debug, Clone)]
+pub struct FunctionsConfig {
+    /// Base URL of a local `supabase functions serve` instance
+    /// (e.g. `http://localhost:54321`). When set, function invocations are
+    /// sent there while the other modules keep using the project URL.
+    pub dev_base_url: Option<String>,
+    /// Most responses kept by `Functions::invoke_cached` (default: 256)
+    ///
+    /// When the cache is full, the response fetched longest ago is evicted.
+    /// Zero disables caching.
+    pub cache_capacity: usize,
+}
+
+impl Default for FunctionsConfig {
+    fn default() -> Self {
+        Self {
+            dev_base_url: None,
+            cache_capacity: 256,
+        }
+    }
+}
+
+/// Source of the signed-in user's access token
+///
+/// [`Client`](crate::Client) gives every module a provider backed by its auth
+/// session, so PostgREST, Storage, Functions and Realtime requests carry
+/// `Authorization: Bearer <access_token>` and Row Level Security policies see
+/// the user. While it returns `None`, requests fall back to the API key.
+///
+/// # Examples
+/// ```rust
+/// use std::sync::Arc;
+/// use supabase_lib_rs::{types::{SupabaseConfig, TokenProvider}, Database};
+///
+/// /// Token issued by a third-party auth provider
+/// struct ExternalToken(String);
+///
+/// impl TokenProvider for ExternalToken {
+///     fn access_token(&self) -> Option<String> {
+///         Some(self.0.clone())
+///     }
+/// }
+///
+/// let database = Database::new(
+///     Arc::new(SupabaseConfig::default()),
+///     Arc::new(reqwest::Client::new()),
+/// )?
+/// .with_token_provider(Arc::new(ExternalToken("jwt".to_string())));
+/// # Ok::<(), supabase_lib_rs::Error>(())
+/// ```
+pub trait TokenProvider: Send + Sync {
+    /// The current access token, if a user is signed in
+    fn access_token(&self) -> Option<String>;
+}
+
+/// Optional [`TokenProvider`] held by a service module
+#[cfg(any(
+    feature = "database",
+    feature = "storage-core",
+    feature = "functions",
+    feature = "realtime"
+))]
+#[derive(Clone, Default)]
+pub(crate) struct RequestAuth(Option<std::sync::Arc<dyn TokenProvider>>);
+
+#[cfg(any(
+    feature = "database",
+    feature = "storage-core",
+    feature = "functions",
+    feature = "realtime"
+))]
+impl RequestAuth {
+    pub(crate) fn new(provider: std::sync::Arc<dyn TokenProvider>) -> Self {
+        Self(Some(provider))
+    }
+
+    pub(crate) fn access_token(&self) -> Option<String> {
+        self.0.as_ref().and_then(|provider| provider.access_token())
+    }
+
+    /// Send the user's access token in place of the API key, if there is one
+    #[cfg(any(feature = "database", feature = "storage-core"))]
+    pub(crate) fn apply(&self, request: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        match self.access_token() {
+            Some(token) => request.bearer_auth(token),
+            None => request,
+        }
+    }
+
+    /// `Bearer` header value with the user's access token, or `api_key` without one
+    #[cfg(feature = "functions")]
+    pub(crate) fn bearer(&self, api_key: &str) -> String {
+        format!(
+            "Bearer {}",
+            self.access_token().as_deref().unwrap_or(api_key)
+        )
+    }
+}
+
+#[cfg(any(
+    feature = "database",
+    feature = "storage-core",
+    feature = "functions",
+    feature = "realtime"
+))]
+impl std::fmt::Debug for RequestAuth {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("RequestAuth")
+            .field(&self.0.as_ref().map(|_| "TokenProvider"))
+            .finish()
+    }
+}
+
+/// Validated Supabase project URL
+///
+/// Derives the service endpoints from a single base URL so that every module
+/// builds them the same way.
+///
+/// # Examples
+/// ```rust
+/// use supabase_lib_rs::types::ProjectUrl;
+///
+/// let url = ProjectUrl::parse("https://abcdefgh.supabase.co")?;
+/// assert_eq!(url.project_ref(), Some("abcdefgh"));
+/// assert_eq!(url.rest_url(), "https://abcdefgh.supabase.co/rest/v1");
+/// assert_eq!(url.realtime_url(), "wss://abcdefgh.supabase.co/realtime/v1/websocket");
+/// # Ok::<(), supabase_lib_rs::Error>(())
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProjectUrl {
+    base: String,
+    project_ref: Option<String>,
+}
+
+impl ProjectUrl {
+    /// Hosted project domain
+    const HOSTED_DOMAIN: &'static str = "supabase.co";
+
+    /// Parse and validate a project URL
+    ///
+    /// The URL must use `http` or `https`, have a host, and contain no path,
+    /// query, fragment or trailing slash.
+    pub fn parse(url: &str) -> Result<Self> {
+        if url.ends_with('/') {
+            return Err(Error::config(format!(
+                "Project URL must not end with '/': {}",
+                url
+            )));
+        }
+
+        let parsed = Url::parse(url).map_err(|e| Error::config(format!("Invalid URL: {}", e)))?;
+
+        if !matches!(parsed.scheme(), "http" | "https") {
+            return Err(Error::config(format!(
+                "Project URL must use http or https, got '{}'",
+                parsed.scheme()
+            )));
+        }
+
+        let host = parsed
+            .host_str()
+            .ok_or_else(|| Error::config("Project URL must include a host"))?;
+
+        if parsed.path() != "/" || parsed.query().is_some() || parsed.fragment().is_some() {
+            return Err(Error::config(format!(
+                "Project URL must not include a path, query or fragment: {}",
+                url
+            )));
+        }
+
+        Ok(Self {
+            base: url.to_string(),
+            project_ref: Self::hosted_ref(host),
+        })
+    }
+
+    /// Derive endpoints from `url` without validating it
+    ///
+    /// For services built from a [`SupabaseConfig`], whose URL
+    /// [`Client`](crate::Client) validated with [`parse`](Self::parse).
+    #[cfg(any(
+        feature = "auth-core",
+        feature = "storage-core",
+        feature = "database",
+        feature = "functions"
+    ))]
+    pub(crate) fn unchecked(url: &str) -> Self {
+        let project_ref = Url::parse(url)
+            .ok()
+            .and_then(|parsed| parsed.host_str().and_then(Self::hosted_ref));
+
+        Self {
+            base: url.to_string(),
+            project_ref,
+        }
+    }
+
+    /// Project reference in a `<ref>.supabase.co` host
+    fn hosted_ref(host: &str) -> Option<String> {
+        host.strip_suffix(Self::HOSTED_DOMAIN)
+            .and_then(|prefix| prefix.strip_suffix('.'))
+            .filter(|prefix| !prefix.is_empty() && !prefix.contains('.'))
+            .map(str::to_string)
+    }
+
+    /// Base project URL without trailing slash
+    pub fn as_str(&self) -> &str {
+        &self.base
+    }
+
+    /// Project reference for hosted projects (`<ref>.supabase.co`)
+    pub fn project_ref(&self) -> Option<&str> {
+        self.project_ref.as_deref()
+    }
+
+    /// REST (PostgREST) endpoint
+    pub fn rest_url(&self) -> String {
+        format!("{}/rest/v1", self.base)
+    }
+
+    /// Auth (GoTrue) endpoint
+    pub fn auth_url(&self) -> String {
+        format!("{}/auth/v1", self.base)
+    }
+
+    /// Storage endpoint
+    pub fn storage_url(&self) -> String {
+        format!("{}/storage/v1", self.base)
+    }
+
+    /// GraphQL (pg_graphql) endpoint
+    pub fn graphql_url(&self) -> String {
+        format!("{}/graphql/v1", self.base)
+    }
+
+    /// Edge Functions endpoint on the project domain
+    pub fn functions_url(&self) -> String {
+        format!("{}/functions/v1", self.base)
+    }
+
+    /// Edge Functions subdomain form (`https://<ref>.functions.supabase.co`)
+    ///
+    /// Only available for hosted projects.
+    pub fn functions_subdomain_url(&self) -> Option<String> {
+        self.project_ref
+            .as_ref()
+            .map(|project_ref| format!("https://{}.functions.{}", project_ref, Self::HOSTED_DOMAIN))
+    }
+
+    /// Realtime websocket endpoint
+    pub fn realtime_url(&self) -> String {
+        let ws_base = if let Some(rest) = self.base.strip_prefix("https://") {
+            format!("wss://{}", rest)
+        } else if let Some(rest) = self.base.strip_prefix("http://") {
+            format!("ws://{}", rest)
+        } else {
+            self.base.clone()
+        };
+        format!("{}/realtime/v1/websocket", ws_base)
+    }
+}
+
+impl std::fmt::Display for ProjectUrl {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.base)
+    }
+}
+
 /// Generic response wrapper
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SupabaseResponse<T> {
@@ -144,7 +617,7 @@ pub struct Pagination {
 }
 
 /// Query filter operations
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum FilterOperator {
     #[serde(rename = "eq")]
     Equal,
@@ -268,6 +741,54 @@ mod tests {
         assert_eq!(serialized, "\"asc\"");
     }
 
+    #[test]
+    fn test_project_url_endpoints() {
+        let url = ProjectUrl::parse("https://abcdefgh.supabase.co").unwrap();
+        assert_eq!(url.project_ref(), Some("abcdefgh"));
+        assert_eq!(url.auth_url(), "https://abcdefgh.supabase.co/auth/v1");
+        assert_eq!(url.storage_url(), "https://abcdefgh.supabase.co/storage/v1");
+        assert_eq!(url.graphql_url(), "https://abcdefgh.supabase.co/graphql/v1");
+        assert_eq!(
+            url.functions_subdomain_url().as_deref(),
+            Some("https://abcdefgh.functions.supabase.co")
+        );
+
+        let local = ProjectUrl::parse("http://localhost:54321").unwrap();
+        assert!(local.project_ref().is_none());
+        assert!(local.functions_subdomain_url().is_none());
+        assert_eq!(
+            local.realtime_url(),
+            "ws://localhost:54321/realtime/v1/websocket"
+        );
+    }
+
+    #[cfg(feature = "auth-core")]
+    #[test]
+    fn test_config_functions_url() {
+        let mut config = SupabaseConfig {
+            url: "https://project.supabase.co".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(
+            config.functions_url(),
+            "https://project.supabase.co/functions/v1"
+        );
+
+        config.functions_config.dev_base_url = Some("http://localhost:54321/".to_string());
+        assert_eq!(
+            config.functions_url(),
+            "http://localhost:54321/functions/v1"
+        );
+    }
+
+    #[test]
+    fn test_project_url_validation() {
+        assert!(ProjectUrl::parse("https://abcdefgh.supabase.co/").is_err());
+        assert!(ProjectUrl::parse("abcdefgh.supabase.co").is_err());
+        assert!(ProjectUrl::parse("ftp://abcdefgh.supabase.co").is_err());
+        assert!(ProjectUrl::parse("https://abcdefgh.supabase.co/rest/v1").is_err());
+    }
+
     #[test]
     fn test_http_method_as_str() {
         assert_eq!(HttpMethod::Get.as_str(), "GET");
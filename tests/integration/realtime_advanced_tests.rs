@@ -126,6 +126,7 @@ async fn test_subscription_config() {
         ],
         enable_presence: true,
         enable_broadcast: true,
+        private: false,
         presence_callback: None,
         broadcast_callback: None,
     };
@@ -324,6 +325,7 @@ async fn test_advanced_realtime_workflow() {
         advanced_filters: filters,
         enable_presence: true,
         enable_broadcast: true,
+        private: false,
         presence_callback: None,
         broadcast_callback: None,
     };
@@ -429,3 +429,92 @@ async fn e2e_storage_workflow() {
 
     println!("✅ E2E storage workflow completed");
 }
+
+/// Throwaway storage bucket, created with the service role key
+#[cfg(feature = "storage")]
+pub struct StorageFixture {
+    pub client: Client,
+    pub bucket: String,
+    uploaded: Vec<String>,
+}
+
+#[cfg(feature = "storage")]
+impl StorageFixture {
+    /// Create a public bucket with a random name; `None` without a service role key
+    pub async fn setup() -> Option<Self> {
+        let config = TestConfig::from_env();
+        let service_role_key = config.service_role_key.as_deref()?;
+        if !config.is_available() {
+            return None;
+        }
+
+        let client = Client::service(&config.url, service_role_key).ok()?;
+        let bucket = random_bucket_name();
+        client
+            .storage()
+            .create_bucket(&bucket, &bucket, true)
+            .await
+            .ok()?;
+
+        Some(Self {
+            client,
+            bucket,
+            uploaded: Vec::new(),
+        })
+    }
+
+    /// Remember an object so `teardown` removes it
+    pub fn track(&mut self, path: &str) {
+        self.uploaded.push(path.to_string());
+    }
+
+    /// Remove tracked objects and delete the bucket
+    pub async fn teardown(self) {
+        let storage = self.client.storage();
+        let paths: Vec<&str> = self.uploaded.iter().map(String::as_str).collect();
+        if !paths.is_empty() {
+            if let Err(e) = storage.remove(&self.bucket, &paths).await {
+                println!("⚠️ Fixture object cleanup failed: {}", e);
+            }
+        }
+        if let Err(e) = storage.delete_bucket(&self.bucket).await {
+            println!("⚠️ Fixture bucket cleanup failed: {}", e);
+        }
+    }
+}
+
+#[cfg(feature = "storage")]
+#[tokio::test]
+async fn e2e_storage_content_addressed_upload() {
+    let Some(mut fixture) = StorageFixture::setup().await else {
+        println!("⏭️ Skipping - Supabase service role key not configured");
+        return;
+    };
+    let storage = fixture.client.storage().clone();
+    let content = bytes::Bytes::from_static(b"deduplicated content");
+
+    let first = storage
+        .upload_content_addressed(&fixture.bucket, content.clone(), None)
+        .await;
+    let second = storage
+        .upload_content_addressed(&fixture.bucket, content, None)
+        .await;
+
+    let checks = async {
+        let first = first?;
+        fixture.track(&first);
+        let second = second?;
+        let stored = storage.exists(&fixture.bucket, &first).await?;
+        let missing = storage.exists(&fixture.bucket, "missing-object").await?;
+        Ok::<_, supabase_lib_rs::Error>((first, second, stored, missing))
+    }
+    .await;
+    fixture.teardown().await;
+
+    let (first, second, stored, missing) = checks.expect("Content-addressed upload failed");
+    assert_eq!(first, second);
+    assert_eq!(first.len(), 64);
+    assert!(stored);
+    assert!(!missing);
+    println!("✅ Content-addressed upload stored once at {}", first);
+}
@@ -0,0 +1,163 @@
+//! Development tasks for supabase-lib-rs
+//!
+//! ```text
+//! cargo xtask size-report [--target <triple>]
+//! ```
+
+use serde_json::Value;
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    process::{Command, ExitCode, Stdio},
+};
+
+/// Feature sets compared by `size-report`, all built with `--no-default-features`
+/// plus `wasm` or `native` depending on the target
+const FEATURE_SETS: &[&str] = &[
+    "auth-core,database",
+    "auth-core,database,auth-mfa",
+    "auth-core,database,storage-core",
+    "auth-core,database,storage",
+    "auth,database,storage",
+];
+
+const DEFAULT_TARGET: &str = "wasm32-unknown-unknown";
+
+fn main() -> ExitCode {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let result = match args.first().map(String::as_str) {
+        Some("size-report") => parse_target(&args[1..]).and_then(|target| size_report(&target)),
+        _ => Err(usage()),
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn usage() -> String {
+    "Usage: cargo xtask size-report [--target <triple>]\n\n\
+     size-report  Build the library with each feature set and print the size\n\
+     \x20            of the compiled code as a Markdown table"
+        .to_string()
+}
+
+fn parse_target(args: &[String]) -> Result<String, String> {
+    match args {
+        [] => Ok(DEFAULT_TARGET.to_string()),
+        [flag, target] if flag == "--target" => Ok(target.clone()),
+        _ => Err(usage()),
+    }
+}
+
+/// Compiled size of one feature set
+struct Sizes {
+    crates: usize,
+    dependencies: u64,
+    library: u64,
+}
+
+fn size_report(target: &str) -> Result<(), String> {
+    let root = crate_root();
+    let target_dir = root.join("target").join("size-report");
+
+    let platform = if target.starts_with("wasm32") {
+        "wasm"
+    } else {
+        "native"
+    };
+
+    let mut rows = Vec::new();
+    for features in FEATURE_SETS {
+        let features = format!("{},{}", features, platform);
+        eprintln!("Building {} with features: {}", target, features);
+        let sizes = build(&root, &target_dir, target, &features)?;
+        rows.push((features, sizes));
+    }
+
+    println!("Release build for `{}`, `--no-default-features`\n", target);
+    println!("| Features | Crates | Dependencies | supabase-lib-rs | Total |");
+    println!("| -------- | -----: | -----------: | --------------: | ----: |");
+    for (features, sizes) in rows {
+        println!(
+            "| `{}` | {} | {} | {} | {} |",
+            features,
+            sizes.crates,
+            mib(sizes.dependencies),
+            mib(sizes.library),
+            mib(sizes.dependencies + sizes.library)
+        );
+    }
+    Ok(())
+}
+
+/// Build the library and add up the rlibs cargo reports for it
+///
+/// Proc macros and build scripts only run on the host, so they are left out.
+fn build(root: &Path, target_dir: &Path, target: &str, features: &str) -> Result<Sizes, String> {
+    let cargo = env::var("CARGO").unwrap_or_else(|_| "cargo".to_string());
+    let output = Command::new(cargo)
+        .current_dir(root)
+        .args(["build", "--lib", "--release", "--message-format=json"])
+        .args(["--package", "supabase-lib-rs", "--target", target])
+        .args(["--no-default-features", "--features", features])
+        .arg("--target-dir")
+        .arg(target_dir)
+        .stderr(Stdio::inherit())
+        .output()
+        .map_err(|e| format!("Failed to run cargo: {}", e))?;
+
+    if !output.status.success() {
+        return Err(format!("Build with features `{}` failed", features));
+    }
+
+    let mut sizes = Sizes {
+        crates: 0,
+        dependencies: 0,
+        library: 0,
+    };
+    for line in String::from_utf8_lossy(&output.stdout).lines() {
+        let Ok(message) = serde_json::from_str::<Value>(line) else {
+            continue;
+        };
+        if message["reason"] != "compiler-artifact" {
+            continue;
+        }
+
+        let is_library = message["target"]["name"] == "supabase_lib_rs";
+        let rlibs = message["filenames"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(Value::as_str)
+            .filter(|file| file.ends_with(".rlib"));
+        for rlib in rlibs {
+            let size = fs::metadata(rlib)
+                .map_err(|e| format!("Failed to read {}: {}", rlib, e))?
+                .len();
+            if is_library {
+                sizes.library += size;
+            } else {
+                sizes.crates += 1;
+                sizes.dependencies += size;
+            }
+        }
+    }
+    Ok(sizes)
+}
+
+/// Directory of the library's manifest, the parent of `xtask/`
+fn crate_root() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .parent()
+        .expect("xtask lives in the crate root")
+        .to_path_buf()
+}
+
+fn mib(bytes: u64) -> String {
+    format!("{:.1} MiB", bytes as f64 / (1024.0 * 1024.0))
+}